@@ -38,7 +38,7 @@ use aptos_types::{
     state_store::{
         state_key::{StateKey, StateKeyInner},
         state_key_prefix::StateKeyPrefix,
-        state_value::StateValue,
+        state_value::{StateValue, StateValueWithProof},
     },
     transaction::{SignedTransaction, Transaction, TransactionWithProof, Version},
 };
@@ -48,11 +48,13 @@ use aptos_vm::{
 };
 use futures::{channel::oneshot, SinkExt};
 use itertools::Itertools;
-use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 use std::{
     collections::{BTreeMap, HashMap},
     sync::{Arc, RwLock},
+    time::Duration,
 };
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 // Context holds application scope context
 #[derive(Clone)]
@@ -63,6 +65,7 @@ pub struct Context {
     pub node_config: NodeConfig,
     gas_estimation: Arc<RwLock<GasEstimationCache>>,
     gas_schedule_cache: Arc<RwLock<GasScheduleCache>>,
+    transaction_stream_permits: Arc<Semaphore>,
 }
 
 impl std::fmt::Debug for Context {
@@ -94,6 +97,9 @@ impl Context {
                 last_updated_epoch: None,
                 gas_schedule_params: None,
             })),
+            transaction_stream_permits: Arc::new(Semaphore::new(
+                node_config.api.max_concurrent_transaction_streams,
+            )),
         }
     }
 
@@ -113,6 +119,23 @@ impl Context {
         self.node_config.api.max_account_modules_page_size
     }
 
+    pub fn transaction_stream_poll_interval(&self) -> Duration {
+        Duration::from_millis(self.node_config.api.transaction_stream_poll_interval_ms)
+    }
+
+    pub fn max_transaction_stream_catch_up(&self) -> u64 {
+        self.node_config.api.max_transaction_stream_catch_up
+    }
+
+    /// Reserves a transaction stream, returns None if the maximum number of concurrent streams
+    /// is reached. The stream is released once the permit is dropped.
+    pub fn try_acquire_transaction_stream(&self) -> Option<OwnedSemaphorePermit> {
+        self.transaction_stream_permits
+            .clone()
+            .try_acquire_owned()
+            .ok()
+    }
+
     pub fn move_resolver(&self) -> Result<StorageAdapterOwned<DbStateView>> {
         self.db
             .latest_state_checkpoint_view()
@@ -258,6 +281,69 @@ impl Context {
             .get_state_value_bytes(state_key)
     }
 
+    /// Reads `state_key` with proof at the latest persisted state snapshot at or before
+    /// `version`, proving the snapshot's transaction info against `ledger_version`. Snapshots are
+    /// only persisted periodically, so the value can be many versions older than `version`: the
+    /// version it is read at is returned along with it.
+    pub fn get_state_value_with_proof<E: StdApiError>(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+        ledger_version: Version,
+        ledger_info: &LedgerInfo,
+    ) -> Result<StateValueWithProof, E> {
+        let internal_error = |err: anyhow::Error| {
+            E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        };
+        let min_readable_version = self
+            .db
+            .get_state_merkle_min_readable_version()
+            .context("Failed to retrieve the oldest readable state snapshot")
+            .map_err(internal_error)?;
+        let checkpoint_version = match self.db.get_state_snapshot_before(version + 1) {
+            Ok(Some((checkpoint_version, _))) => checkpoint_version,
+            // The genesis state is a snapshot, so the ones before `version` were all pruned
+            Ok(None) => return Err(version_pruned(version, ledger_info)),
+            Err(_) if version < min_readable_version => {
+                return Err(version_pruned(version, ledger_info))
+            },
+            Err(err) => {
+                return Err(internal_error(err.context(format!(
+                    "Failed to find the state snapshot at or before version {}",
+                    version
+                ))))
+            },
+        };
+        let (value, proof) = match self
+            .db
+            .get_state_value_with_proof_by_version(state_key, checkpoint_version)
+        {
+            Ok(value_with_proof) => value_with_proof,
+            Err(_) if checkpoint_version < min_readable_version => {
+                return Err(version_pruned(version, ledger_info))
+            },
+            Err(err) => {
+                return Err(internal_error(err.context(format!(
+                    "Failed to query DB for proof of {:?} at {}",
+                    state_key, checkpoint_version
+                ))))
+            },
+        };
+        let transaction_info_with_proof = self
+            .db
+            .get_transaction_by_version(checkpoint_version, ledger_version, false)
+            .context("Failed to retrieve the transaction info of the state snapshot")
+            .map_err(internal_error)?
+            .proof;
+
+        Ok(StateValueWithProof::new(
+            checkpoint_version,
+            value,
+            proof,
+            transaction_info_with_proof,
+        ))
+    }
+
     pub fn get_state_value_poem<E: InternalError>(
         &self,
         state_key: &StateKey,
@@ -773,6 +859,25 @@ impl Context {
             .map(|h| (txn, h, txn_output).into())
     }
 
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: u64,
+        start_index: u64,
+        end_version: u64,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<(Vec<EventWithVersion>, Option<(u64, u64)>)> {
+        self.db.get_events_by_type(
+            type_tag,
+            start_version,
+            start_index,
+            end_version,
+            limit as u64,
+            ledger_version,
+        )
+    }
+
     pub fn get_events(
         &self,
         event_key: &EventKey,
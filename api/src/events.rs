@@ -9,22 +9,24 @@ use crate::{
     failpoint::fail_point_poem,
     page::Page,
     response::{
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        api_disabled, version_pruned, BadRequestError, BasicErrorWith404, BasicResponse,
+        BasicResponseStatus, BasicResultWith404, InternalError,
     },
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_field_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
-    MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
+    verify_field_identifier, Address, AptosErrorCode, AsConverter, HexEncodedBytes,
+    IdentifierWrapper, LedgerInfo, MoveStructTag, MoveType, VerifyInputWithRecursion,
+    VersionedEvent, U64,
 };
-use aptos_types::event::EventKey;
+use aptos_types::{contract_event::EventWithVersion, event::EventKey};
+use move_core_types::language_storage::TypeTag;
 use poem_openapi::{
     param::{Path, Query},
     OpenApi,
 };
-use std::sync::Arc;
+use std::{convert::TryInto, sync::Arc};
 
 pub struct EventsApi {
     pub context: Arc<Context>,
@@ -140,6 +142,147 @@ impl EventsApi {
         let key = account.find_event_key(event_handle.0, field_name.0.into())?;
         self.list(account.latest_ledger_info, accept_type, page, key)
     }
+
+    /// Get events by event type
+    ///
+    /// Returns the events of the given type emitted by transactions in a range of ledger
+    /// versions, regardless of the account they were emitted to, ordered by version.
+    ///
+    /// If there are more events in the range than `limit`, the response has a cursor in the
+    /// X-Aptos-Cursor header. To get the next page, pass it as `start`, along with the same
+    /// `end_version`.
+    ///
+    /// This requires the node to run the internal indexer with the event type index enabled.
+    /// Events are only indexed from the version the index was enabled at, earlier versions
+    /// are rejected.
+    #[oai(
+        path = "/events/by_type/:event_type",
+        method = "get",
+        operation_id = "get_events_by_event_type",
+        tag = "ApiTags::Events"
+    )]
+    async fn get_events_by_event_type(
+        &self,
+        accept_type: AcceptType,
+        /// Type of the events e.g. `0x1::coin::DepositEvent`
+        event_type: Path<MoveType>,
+        /// First ledger version to retrieve events from, inclusive
+        ///
+        /// If unspecified, defaults to the oldest ledger version covered by the index
+        start_version: Query<Option<U64>>,
+        /// Last ledger version to retrieve events from, exclusive
+        ///
+        /// If unspecified, defaults to the version after the latest ledger version
+        end_version: Query<Option<U64>>,
+        /// Cursor specifying where to start for pagination, takes precedence over `start_version`
+        ///
+        /// This cursor cannot be derived manually client-side. Instead, you must
+        /// call this endpoint once without this query parameter specified, and
+        /// then use the cursor returned in the X-Aptos-Cursor header in the
+        /// response.
+        start: Query<Option<HexEncodedBytes>>,
+        /// Max number of events to retrieve.
+        ///
+        /// If unspecified, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        event_type
+            .0
+            .verify(0)
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_events_by_event_type")?;
+        self.context
+            .check_api_output_enabled("Get events by event type", &accept_type)?;
+        let index_start_version = match self.context.db.event_type_index_start_version() {
+            Some(index_start_version) => index_start_version,
+            None => return Err(api_disabled("Get events by event type")),
+        };
+
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let type_tag: TypeTag = event_type
+            .0
+            .try_into()
+            .context("Failed to parse given event type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &latest_ledger_info,
+                )
+            })?;
+        let oldest_ledger_version = latest_ledger_info.oldest_ledger_version.0;
+        let (start_version, start_index) = match start.0 {
+            Some(cursor) => bcs::from_bytes::<(u64, u64)>(&cursor.0)
+                .context("'start' invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &latest_ledger_info,
+                    )
+                })?,
+            None => (
+                start_version.0.map_or(
+                    std::cmp::max(oldest_ledger_version, index_start_version),
+                    |v| v.0,
+                ),
+                0,
+            ),
+        };
+        if start_version < oldest_ledger_version {
+            return Err(version_pruned(start_version, &latest_ledger_info));
+        }
+        if start_version < index_start_version {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Events are only indexed by type from version {}",
+                    index_start_version
+                ),
+                AptosErrorCode::InvalidInput,
+                &latest_ledger_info,
+            ));
+        }
+        let end_version = end_version
+            .0
+            .map_or(latest_ledger_info.version() + 1, |v| v.0);
+        let limit = Page::new(None, limit.0, self.context.max_events_page_size())
+            .limit(&latest_ledger_info)?;
+
+        let (events, next) = self
+            .context
+            .get_events_by_type(
+                &type_tag,
+                start_version,
+                start_index,
+                end_version,
+                limit,
+                latest_ledger_info.version(),
+            )
+            .context(format!("Failed to find events by type {}", type_tag))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+        let cursor = next
+            .map(|next| bcs::to_bytes(&next).map(|bytes| HexEncodedBytes(bytes).to_string()))
+            .transpose()
+            .context("Failed to encode the cursor of the next page")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+        self.render(latest_ledger_info, accept_type, events)
+            .map(|response| response.with_encoded_cursor(cursor))
+    }
 }
 
 impl EventsApi {
@@ -168,7 +311,15 @@ impl EventsApi {
                     &latest_ledger_info,
                 )
             })?;
+        self.render(latest_ledger_info, accept_type, events)
+    }
 
+    fn render(
+        &self,
+        latest_ledger_info: LedgerInfo,
+        accept_type: AcceptType,
+        events: Vec<EventWithVersion>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
         match accept_type {
             AcceptType::Json => {
                 let resolver = self.context.move_resolver_poem(&latest_ledger_info)?;
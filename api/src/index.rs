@@ -5,11 +5,16 @@
 use crate::{
     accept_type::AcceptType,
     context::Context,
-    response::{BasicResponse, BasicResponseStatus, BasicResult},
+    failpoint::fail_point_poem,
+    response::{
+        version_not_found, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult,
+        BasicResultWith404, InternalError,
+    },
     ApiTags,
 };
-use aptos_api_types::{IndexResponse, IndexResponseBcs};
-use poem_openapi::OpenApi;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{AptosErrorCode, HexEncodedBytes, IndexResponse, IndexResponseBcs, U64};
+use poem_openapi::{param::Query, OpenApi};
 use std::sync::Arc;
 
 /// API for the index, to retrieve the ledger information
@@ -55,4 +60,74 @@ impl IndexApi {
             },
         }
     }
+
+    /// Get state proof
+    ///
+    /// Get the proof needed by a light client that trusts the ledger at `known_version` to
+    /// verify the latest signed ledger info. The proof contains the epoch change ledger infos
+    /// from the epoch of `known_version` along with the latest ledger info with signatures.
+    ///
+    /// The number of epoch changes in a single proof is limited, if the `more` field of the
+    /// epoch change proof is set, the client must request again from the last epoch change.
+    ///
+    /// JSON responses contain the hex encoded BCS of the `StateProof`.
+    #[oai(
+        path = "/state_proof",
+        method = "get",
+        operation_id = "get_state_proof",
+        tag = "ApiTags::General"
+    )]
+    async fn get_state_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Ledger version already trusted by the client
+        known_version: Query<U64>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_state_proof")?;
+        self.context
+            .check_api_output_enabled("Get state proof", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let known_version = known_version.0 .0;
+        if known_version > ledger_info.version() {
+            return Err(version_not_found(known_version, &ledger_info));
+        }
+
+        let state_proof = self
+            .context
+            .db
+            .get_state_proof(known_version)
+            .context(format!(
+                "Failed to get state proof from known version {}",
+                known_version
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let bytes = bcs::to_bytes(&state_proof)
+                    .context("Failed to encode state proof")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+                BasicResponse::try_from_json((
+                    HexEncodedBytes(bytes),
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((state_proof, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
 }
@@ -13,18 +13,20 @@ use crate::{
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper,
-    MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawTableItemRequest,
-    TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
+    verify_module_identifier, Address, AptosErrorCode, AsConverter, BcsStateValueWithLedgerProof,
+    IdentifierWrapper, LedgerInfo, MoveConverter, MoveModuleBytecode, MoveResource, MoveStructTag,
+    MoveValue, RawTableItemRequest, StateValueWithLedgerProof, TableItemRequest, VerifyInput,
+    VerifyInputWithRecursion, U64,
 };
 use aptos_state_view::TStateView;
+use aptos_storage_interface::state_view::DbStateView;
 use aptos_types::{
     access_path::AccessPath,
     state_store::{state_key::StateKey, table::TableHandle},
 };
-use aptos_vm::data_cache::AsMoveResolver;
+use aptos_vm::{data_cache::AsMoveResolver, move_vm_ext::MoveResolverExt};
 use move_core_types::{
-    language_storage::{ModuleId, StructTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     resolver::ResourceResolver,
 };
 use poem_openapi::{
@@ -32,7 +34,10 @@ use poem_openapi::{
     payload::Json,
     OpenApi,
 };
-use std::{convert::TryInto, sync::Arc};
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::Arc,
+};
 
 /// API for retrieving individual state
 pub struct StateApi {
@@ -84,6 +89,68 @@ impl StateApi {
         )
     }
 
+    /// Get account resource with proof
+    ///
+    /// Retrieves an individual resource from a given account along with the proofs needed to
+    /// verify it: a sparse Merkle proof against a state snapshot, the transaction info proof of
+    /// that snapshot, and the signed ledger info the proofs are relative to.
+    ///
+    /// State can only be proven at the state snapshots persisted by the node, which are taken
+    /// periodically. The resource is therefore read at the latest snapshot at or before the
+    /// requested ledger version, which is returned as `version` and can be well behind it. If
+    /// the resource doesn't exist, the response has no value and the proof is a proof of
+    /// non-existence. If the snapshots up to the requested ledger version have been pruned, the
+    /// server responds with a 410.
+    ///
+    /// Resources stored within a resource group are not individually provable, the proof is
+    /// for the state key of the resource itself.
+    #[oai(
+        path = "/accounts/:address/resource/:resource_type/proof",
+        method = "get",
+        operation_id = "get_account_resource_with_proof",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of struct to retrieve e.g. `0x1::account::Account`
+        resource_type: Path<MoveStructTag>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<StateValueWithLedgerProof> {
+        resource_type
+            .0
+            .verify(0)
+            .context("'resource_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_resource_with_proof")?;
+        self.context
+            .check_api_output_enabled("Get account resource with proof", &accept_type)?;
+        let resource_type: StructTag = resource_type
+            .0
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let access_path = AccessPath::resource_access_path(address.0.into(), resource_type)
+            .context("Failed to build access path for resource")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        self.state_value_with_proof(
+            &accept_type,
+            StateKey::access_path(access_path),
+            ledger_version.0.map(|inner| inner.0),
+        )
+    }
+
     /// Get account module
     ///
     /// Retrieves an individual module from a given account and at a specific ledger version. If the
@@ -168,6 +235,53 @@ impl StateApi {
         )
     }
 
+    /// Get table item with proof
+    ///
+    /// Get a table item from the table identified by {table_handle} in the path and the "key"
+    /// (TableItemRequest) provided in the request body, along with the proofs needed to verify it.
+    /// See `get_account_resource_with_proof` for the contents of the response.
+    ///
+    /// The value is returned as its BCS encoded state value, the `value_type` of the request is
+    /// only checked for validity.
+    #[oai(
+        path = "/tables/:table_handle/item/proof",
+        method = "post",
+        operation_id = "get_table_item_with_proof",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_table_item_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table request detailing the key type, key, and value type
+        table_item_request: Json<TableItemRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<StateValueWithLedgerProof> {
+        table_item_request
+            .0
+            .verify()
+            .context("'table_item_request' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_table_item_with_proof")?;
+        self.context
+            .check_api_output_enabled("Get table item with proof", &accept_type)?;
+        let ledger_version = ledger_version.0.map(|inner| inner.0);
+        let (ledger_info, _, state_view) = self.context.state_view(ledger_version)?;
+        let state_key = self.table_item_state_key(
+            &state_view,
+            table_handle.0,
+            table_item_request.0,
+            &ledger_info,
+        )?;
+        self.state_value_with_proof(&accept_type, state_key, ledger_version)
+    }
+
     /// Get raw table item
     ///
     /// Get a table item at a specific ledger version from the table identified by {table_handle}
@@ -355,22 +469,7 @@ impl StateApi {
         let converter = resolver.as_converter(self.context.db.clone());
 
         // Convert key to lookup version for DB
-        let vm_key = converter
-            .try_into_vm_value(&key_type, key.clone())
-            .map_err(|err| {
-                BasicErrorWith404::bad_request_with_code(
-                    err,
-                    AptosErrorCode::InvalidInput,
-                    &ledger_info,
-                )
-            })?;
-        let raw_key = vm_key.undecorate().simple_serialize().ok_or_else(|| {
-            BasicErrorWith404::bad_request_with_code(
-                "Failed to serialize table key",
-                AptosErrorCode::InvalidInput,
-                &ledger_info,
-            )
-        })?;
+        let raw_key = Self::raw_table_key(&converter, &key_type, key.clone(), &ledger_info)?;
 
         // Retrieve value from the state key
         let state_key = StateKey::table_item(TableHandle(table_handle.into()), raw_key);
@@ -461,4 +560,126 @@ impl StateApi {
             },
         }
     }
+
+    /// Serialize a JSON table key into the raw key the table item is stored under
+    fn raw_table_key<R: MoveResolverExt + ?Sized>(
+        converter: &MoveConverter<'_, R>,
+        key_type: &TypeTag,
+        key: serde_json::Value,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Vec<u8>, BasicErrorWith404> {
+        let vm_key = converter.try_into_vm_value(key_type, key).map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(err, AptosErrorCode::InvalidInput, ledger_info)
+        })?;
+        vm_key.undecorate().simple_serialize().ok_or_else(|| {
+            BasicErrorWith404::bad_request_with_code(
+                "Failed to serialize table key",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            )
+        })
+    }
+
+    /// Build the state key of a table item from its typed request
+    fn table_item_state_key(
+        &self,
+        state_view: &DbStateView,
+        table_handle: Address,
+        table_item_request: TableItemRequest,
+        ledger_info: &LedgerInfo,
+    ) -> Result<StateKey, BasicErrorWith404> {
+        let key_type: TypeTag = table_item_request
+            .key_type
+            .try_into()
+            .context("Failed to parse key_type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                )
+            })?;
+        let _value_type: TypeTag = table_item_request
+            .value_type
+            .try_into()
+            .context("Failed to parse value_type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                )
+            })?;
+
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+        let raw_key =
+            Self::raw_table_key(&converter, &key_type, table_item_request.key, ledger_info)?;
+        Ok(StateKey::table_item(
+            TableHandle(table_handle.into()),
+            raw_key,
+        ))
+    }
+
+    /// Read a state value with proofs at the ledger version
+    ///
+    /// JSON: Hex encode each BCS encoded part of the proof
+    /// BCS: Encode the value and proofs as a whole
+    fn state_value_with_proof(
+        &self,
+        accept_type: &AcceptType,
+        state_key: StateKey,
+        ledger_version: Option<u64>,
+    ) -> BasicResultWith404<StateValueWithLedgerProof> {
+        let (ledger_info, version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(ledger_version)?;
+        // The signed ledger info is read after `ledger_info`, so it is at least as new and
+        // `version` is guaranteed to be covered by it.
+        let ledger_info_with_signatures = self
+            .context
+            .get_latest_ledger_info_with_signatures()
+            .context("Failed to retrieve latest signed ledger info")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        // Without a requested version, read as of the signed ledger info, so that the snapshot
+        // is the latest one the proof can cover
+        let version = match ledger_version {
+            Some(_) => version,
+            None => ledger_info_with_signatures.ledger_info().version(),
+        };
+        let state_value_with_proof = self.context.get_state_value_with_proof(
+            &state_key,
+            version,
+            ledger_info_with_signatures.ledger_info().version(),
+            &ledger_info,
+        )?;
+        let proof = BcsStateValueWithLedgerProof {
+            state_value_with_proof,
+            ledger_info_with_signatures,
+        };
+
+        match accept_type {
+            AcceptType::Json => {
+                let proof = StateValueWithLedgerProof::try_from(proof)
+                    .context("Failed to encode state value proof")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+                BasicResponse::try_from_json((proof, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((proof, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
 }
@@ -3,8 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::new_test_context;
-use aptos_api_test_context::current_function_name;
+use aptos_api_test_context::{current_function_name, new_test_context as super_new_test_context};
+use aptos_api_types::X_APTOS_CURSOR;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::Value;
 
 static ACCOUNT_ADDRESS: &str = "0xa550c18";
 static CREATION_NUMBER: &str = "0";
@@ -136,6 +138,94 @@ async fn test_get_events_by_invalid_account_event_handle_field_type() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_event_type() {
+    let context = super_new_test_context(current_function_name!(), true);
+    let event_type = "0x1::reconfiguration::NewEpochEvent";
+
+    let resp = context
+        .get(format!("/events/by_type/{}", event_type).as_str())
+        .await;
+    let events = resp.as_array().unwrap();
+    assert!(!events.is_empty());
+    for event in events {
+        assert_eq!(event["type"], event_type);
+    }
+
+    let last_version: u64 = events.last().unwrap()["version"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let resp = context
+        .get(
+            format!(
+                "/events/by_type/{}?start_version={}",
+                event_type,
+                last_version + 1
+            )
+            .as_str(),
+        )
+        .await;
+    assert!(resp.as_array().unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_event_type_pagination() {
+    let mut context = super_new_test_context(current_function_name!(), true);
+    for _ in 0..3 {
+        let account = context.gen_account();
+        let txn = context.create_user_account(&account).await;
+        context.commit_block(&vec![txn]).await;
+    }
+    let event_type = "0x1::block::NewBlockEvent";
+
+    let resp = context
+        .get(format!("/events/by_type/{}", event_type).as_str())
+        .await;
+    let events = resp.as_array().unwrap();
+    assert!(events.len() >= 3);
+
+    let mut paged_events = vec![];
+    let mut cursor: Option<String> = None;
+    loop {
+        let path = match &cursor {
+            Some(cursor) => format!("/events/by_type/{}?limit=1&start={}", event_type, cursor),
+            None => format!("/events/by_type/{}?limit=1", event_type),
+        };
+        let resp = context
+            .reply(
+                warp::test::request()
+                    .method("GET")
+                    .path(&context.prepend_path(&path)),
+            )
+            .await;
+        assert_eq!(resp.status(), 200);
+        let page: Value = serde_json::from_slice(resp.body()).unwrap();
+        let page = page.as_array().unwrap();
+        assert!(page.len() <= 1);
+        paged_events.extend(page.iter().cloned());
+        cursor = resp
+            .headers()
+            .get(X_APTOS_CURSOR)
+            .map(|cursor| cursor.to_str().unwrap().to_string());
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(&paged_events, events);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_event_type_without_index() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(403)
+        .get("/events/by_type/0x1::reconfiguration::NewEpochEvent")
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
 // until we have generics in the genesis
 #[ignore]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -3,13 +3,23 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::{mime_types::BCS, BcsStateValueWithLedgerProof, StateValueWithLedgerProof};
 use aptos_sdk::{transaction_builder::aptos_stdlib::aptos_token_stdlib, types::LocalAccount};
 use aptos_storage_interface::DbReader;
-use move_core_types::account_address::AccountAddress;
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::AccountResource,
+    state_store::{state_key::StateKey, table::TableHandle},
+};
+use move_core_types::{account_address::AccountAddress, move_resource::MoveStructType};
 use move_package::BuildConfig;
 use serde::Serialize;
 use serde_json::{json, Value};
-use std::{convert::TryInto, path::PathBuf};
+use std::{
+    convert::{TryFrom, TryInto},
+    path::PathBuf,
+};
+use warp::http::header::ACCEPT;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resource() {
@@ -95,6 +105,150 @@ async fn test_get_account_resource_with_version_too_large() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof() {
+    let context = new_test_context(current_function_name!());
+    let address = AccountAddress::from_hex_literal("0xA550C18").unwrap();
+    let resp = context
+        .get(&get_account_resource_with_proof(
+            "0xA550C18",
+            "0x1::account::Account",
+        ))
+        .await;
+
+    let proof: StateValueWithLedgerProof = serde_json::from_value(resp).unwrap();
+    let proof = BcsStateValueWithLedgerProof::try_from(proof).unwrap();
+    assert!(proof.state_value_with_proof.value.is_some());
+    let state_key = StateKey::access_path(
+        AccessPath::resource_access_path(address, AccountResource::struct_tag()).unwrap(),
+    );
+    proof
+        .state_value_with_proof
+        .verify(proof.ledger_info_with_signatures.ledger_info(), &state_key)
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_not_found() {
+    let context = new_test_context(current_function_name!());
+    let address = AccountAddress::from_hex_literal("0xA550C19").unwrap();
+    let resp = context
+        .get(&get_account_resource_with_proof(
+            "0xA550C19",
+            "0x1::account::Account",
+        ))
+        .await;
+
+    let proof: StateValueWithLedgerProof = serde_json::from_value(resp).unwrap();
+    let proof = BcsStateValueWithLedgerProof::try_from(proof).unwrap();
+    assert!(proof.state_value_with_proof.value.is_none());
+    let state_key = StateKey::access_path(
+        AccessPath::resource_access_path(address, AccountResource::struct_tag()).unwrap(),
+    );
+    proof
+        .state_value_with_proof
+        .verify(proof.ledger_info_with_signatures.ledger_info(), &state_key)
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_bcs() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+    let ledger_version = context.db.get_latest_version().unwrap();
+
+    let req = warp::test::request()
+        .method("GET")
+        .header(ACCEPT, BCS)
+        .path(&context.prepend_path(&format!(
+            "{}?ledger_version={}",
+            get_account_resource_with_proof(
+                &account.address().to_hex_literal(),
+                "0x1::account::Account",
+            ),
+            ledger_version
+        )));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let proof: BcsStateValueWithLedgerProof = bcs::from_bytes(resp.body()).unwrap();
+
+    // The value is read at the latest state snapshot at or before the requested version, and
+    // proven against a ledger info covering it
+    let state_value_with_proof = &proof.state_value_with_proof;
+    assert!(state_value_with_proof.version <= ledger_version);
+    assert!(proof.ledger_info_with_signatures.ledger_info().version() >= ledger_version);
+    let state_key = StateKey::access_path(
+        AccessPath::resource_access_path(account.address(), AccountResource::struct_tag()).unwrap(),
+    );
+    state_value_with_proof
+        .verify(proof.ledger_info_with_signatures.ledger_info(), &state_key)
+        .unwrap();
+    assert_eq!(
+        state_value_with_proof.value,
+        context
+            .db
+            .get_state_value_by_version(&state_key, state_value_with_proof.version)
+            .unwrap()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_with_proof() {
+    let context = new_test_context(current_function_name!());
+    let factory = context
+        .api_get_account_resource(
+            AccountAddress::ONE,
+            "0x1",
+            "aggregator_factory",
+            "AggregatorFactory",
+        )
+        .await;
+    let handle: AccountAddress = factory["data"]["phantom_table"]["handle"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    // Aggregators don't use the table, so this proves the absence of the item
+    let resp = context
+        .post(
+            &format!("{}/proof", get_table_item(handle)),
+            json!({
+                "key_type": "address",
+                "value_type": "u128",
+                "key": "0x1",
+            }),
+        )
+        .await;
+    let proof: StateValueWithLedgerProof = serde_json::from_value(resp).unwrap();
+    let proof = BcsStateValueWithLedgerProof::try_from(proof).unwrap();
+    assert!(proof.state_value_with_proof.value.is_none());
+    let state_key = StateKey::table_item(
+        TableHandle(handle),
+        bcs::to_bytes(&AccountAddress::ONE).unwrap(),
+    );
+    proof
+        .state_value_with_proof
+        .verify(proof.ledger_info_with_signatures.ledger_info(), &state_key)
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_version_too_large() {
+    let context = new_test_context(current_function_name!());
+    let ledger_version = context.db.get_latest_version().unwrap();
+    context
+        .expect_status_code(404)
+        .get(&format!(
+            "{}?ledger_version={}",
+            get_account_resource_with_proof("0xA550C18", "0x1::account::Account"),
+            ledger_version + 100
+        ))
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_module() {
     let mut context = new_test_context(current_function_name!());
@@ -309,6 +463,10 @@ fn get_account_resource_with_version(address: &str, struct_tag: &str, version: u
     )
 }
 
+fn get_account_resource_with_proof(address: &str, struct_tag: &str) -> String {
+    format!("/accounts/{}/resource/{}/proof", address, struct_tag)
+}
+
 fn get_account_module(address: &str, name: &str) -> String {
     format!("/accounts/{}/module/{}", address, name)
 }
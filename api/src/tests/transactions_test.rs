@@ -3,21 +3,27 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::new_test_context;
-use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
+use aptos_api_test_context::{
+    assert_json, current_function_name, pretty, ApiSpecificConfig, TestContext,
+};
+use aptos_api_types::{BcsTransactionWithLedgerProof, TransactionWithLedgerProof};
 use aptos_crypto::{
     ed25519::Ed25519PrivateKey,
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
     PrivateKey, SigningKey, Uniform,
 };
+use aptos_rest_client::{Client, TransactionStreamFilter};
 use aptos_sdk::types::LocalAccount;
 use aptos_types::{
     account_address::AccountAddress,
+    account_config::aptos_test_root_address,
     transaction::{
         authenticator::{AuthenticationKey, TransactionAuthenticator},
-        EntryFunction, Script, SignedTransaction,
+        EntryFunction, Script, SignedTransaction, Transaction,
     },
     utility_coin::APTOS_COIN_TYPE,
 };
+use futures::StreamExt;
 use move_core_types::{
     identifier::Identifier,
     language_storage::{ModuleId, TypeTag},
@@ -25,7 +31,9 @@ use move_core_types::{
 use poem_openapi::types::ParseFromJSON;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde_json::json;
-use std::path::PathBuf;
+use std::{convert::TryFrom, path::PathBuf, time::Duration};
+use tokio::time::timeout;
+use url::Url;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_deserialize_genesis_transaction() {
@@ -430,6 +438,32 @@ async fn test_get_transaction_by_version() {
     assert_json(resp, txns[0].clone())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_by_version_with_proof() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let resp = context.get("/transactions/by_version/2/proof").await;
+    let proof: TransactionWithLedgerProof = serde_json::from_value(resp).unwrap();
+    let proof = BcsTransactionWithLedgerProof::try_from(proof).unwrap();
+    let txn_with_proof = &proof.transaction_with_proof;
+    assert_eq!(txn_with_proof.version, 2);
+    assert_eq!(
+        txn_with_proof.transaction,
+        Transaction::UserTransaction(txn.clone())
+    );
+    txn_with_proof
+        .verify_user_txn(
+            proof.ledger_info_with_signatures.ledger_info(),
+            2,
+            txn.sender(),
+            txn.sequence_number(),
+        )
+        .unwrap();
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_pending_transaction_by_hash() {
     let mut context = new_test_context(current_function_name!());
@@ -1091,6 +1125,98 @@ async fn test_gas_estimation() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_transactions_with_invalid_entry_function() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .get("/transactions/stream?entry_function=0x1::coin")
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_transactions_with_invalid_start_version() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .get("/transactions/stream?start_version=hello")
+        .await;
+    assert!(resp["message"].as_str().is_some());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let account1 = context.gen_account();
+    let txn1 = context.create_user_account(&account1).await;
+    context.commit_block(&vec![txn1.clone()]).await;
+
+    let ApiSpecificConfig::V1(address) = context.api_specific_config;
+    let client = Client::new(Url::parse(&format!("http://{}", address)).unwrap());
+    let filter = TransactionStreamFilter {
+        sender: Some(aptos_test_root_address()),
+        ..Default::default()
+    };
+    let mut stream = client.stream_transactions(Some(0), &filter).boxed();
+
+    // Catches up on committed transactions, skipping the ones of other senders
+    let txn = timeout(Duration::from_secs(10), stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        txn.transaction_info().unwrap().hash.0,
+        txn1.committed_hash()
+    );
+
+    // Then follows the ledger
+    let account2 = context.gen_account();
+    let txn2 = context.create_user_account(&account2).await;
+    context.commit_block(&vec![txn2.clone()]).await;
+    let txn = timeout(Duration::from_secs(10), stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        txn.transaction_info().unwrap().hash.0,
+        txn2.committed_hash()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_gas_profile() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+    let payload = json!({
+        "type": "entry_function_payload",
+        "function": "0x1::aptos_account::transfer",
+        "type_arguments": [],
+        "arguments": [account.address().to_hex_literal(), "10"]
+    });
+
+    let resp = context
+        .simulate_transaction_at("/transactions/simulate/gas_profile", &account, payload, 200)
+        .await;
+    assert!(resp["transaction"]["success"].as_bool().unwrap());
+    let gas_used: u64 = resp["transaction"]["gas_used"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(
+        resp["summary"]["total_cost_in_gas_units"].as_u64().unwrap(),
+        gas_used
+    );
+    // The gas is attributed to the entry function and the functions it calls
+    let folded_stacks = resp["folded_stacks"].as_str().unwrap();
+    assert!(folded_stacks
+        .lines()
+        .any(|line| line.starts_with("transaction;0x1::aptos_account::transfer;0x1::")));
+}
+
 fn gen_string(len: u64) -> String {
     let mut rng = thread_rng();
     std::iter::repeat(())
@@ -0,0 +1,304 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    accept_type::AcceptType,
+    context::Context,
+    failpoint::fail_point_poem,
+    response::{
+        api_disabled, version_pruned, BadRequestError, BasicErrorWith404, InternalError,
+        ServiceUnavailableError,
+    },
+    ApiTags,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AptosErrorCode, AsConverter, EntryFunctionId, LedgerInfo, MoveType, Transaction,
+    TransactionOnChainData, VerifyInput, U64,
+};
+use aptos_logger::warn;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{Transaction as RawTransaction, TransactionPayload},
+};
+use futures::{stream, stream::BoxStream, StreamExt};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+};
+use poem_openapi::{param::Query, payload::EventStream, OpenApi};
+use std::{collections::VecDeque, convert::TryInto, sync::Arc, time::Duration};
+use tokio::sync::OwnedSemaphorePermit;
+
+/// Interval at which keep alive comments are sent on idle streams
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// API for streaming committed transactions
+pub struct TransactionStreamApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl TransactionStreamApi {
+    /// Stream transactions
+    ///
+    /// Stream committed transactions as server-sent events, starting from the given ledger
+    /// version and following the ledger as new transactions are committed. Each event is a
+    /// JSON encoded transaction, in the same format as returned by /transactions.
+    ///
+    /// Transactions can be filtered by sender, by the entry function they call, or by the
+    /// type of an event they emit. If several filters are given, a transaction must match
+    /// all of them. Transactions that don't match are skipped.
+    ///
+    /// The stream ends if the node fails to read the next transactions, e.g. because they
+    /// have been pruned. Clients should resume from the version after the last transaction
+    /// they received.
+    ///
+    /// Streams are disabled by default. Nodes serve a limited number of streams at the same
+    /// time, and refuse to start a stream too far behind the latest ledger version.
+    #[oai(
+        path = "/transactions/stream",
+        method = "get",
+        operation_id = "stream_transactions",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn stream_transactions(
+        &self,
+        /// Ledger version to start streaming from
+        ///
+        /// If not provided, defaults to the version after the latest ledger version
+        start_version: Query<Option<U64>>,
+        /// Only stream transactions sent by this account
+        sender: Query<Option<Address>>,
+        /// Only stream transactions calling this entry function e.g. `0x1::coin::transfer`
+        entry_function: Query<Option<EntryFunctionId>>,
+        /// Only stream transactions emitting an event of this type
+        event_type: Query<Option<MoveType>>,
+    ) -> poem::Result<EventStream<BoxStream<'static, Transaction>>, BasicErrorWith404> {
+        fail_point_poem("endpoint_stream_transactions")?;
+        self.context
+            .check_api_output_enabled("Stream transactions", &AcceptType::Json)?;
+        if !self.context.node_config.api.transaction_stream_enabled {
+            return Err(api_disabled("Stream transactions"));
+        }
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if let Some(entry_function) = &entry_function.0 {
+            entry_function
+                .verify()
+                .context("'entry_function' invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    )
+                })?;
+        }
+        let event_type: Option<TypeTag> = event_type
+            .0
+            .map(|event_type| event_type.try_into())
+            .transpose()
+            .context("Failed to parse given event type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let start_version = start_version
+            .0
+            .map(|version| version.0)
+            .unwrap_or_else(|| ledger_info.version() + 1);
+        if start_version < ledger_info.oldest_ledger_version.0 {
+            return Err(version_pruned(start_version, &ledger_info));
+        }
+        let max_catch_up = self.context.max_transaction_stream_catch_up();
+        if ledger_info.version().saturating_sub(start_version) > max_catch_up {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "'start_version' {} is more than {} versions behind the latest ledger version",
+                    start_version, max_catch_up
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        let permit = self
+            .context
+            .try_acquire_transaction_stream()
+            .ok_or_else(|| {
+                BasicErrorWith404::service_unavailable_with_code(
+                    "Too many concurrent transaction streams",
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        let filter = TransactionFilter {
+            sender: sender.0.map(Into::into),
+            entry_function: entry_function
+                .0
+                .map(|entry_function| (entry_function.module.into(), entry_function.name.into())),
+            event_type,
+        };
+        let stream = TransactionStream {
+            context: self.context.clone(),
+            filter,
+            next_version: start_version,
+            pending: VecDeque::new(),
+            _permit: permit,
+        };
+
+        Ok(EventStream::new(stream.into_stream().boxed()).keep_alive(KEEP_ALIVE_INTERVAL))
+    }
+}
+
+/// Filter of a transaction stream, every given field must match
+struct TransactionFilter {
+    sender: Option<AccountAddress>,
+    entry_function: Option<(ModuleId, Identifier)>,
+    event_type: Option<TypeTag>,
+}
+
+impl TransactionFilter {
+    fn matches(&self, txn: &TransactionOnChainData) -> bool {
+        if let Some(sender) = &self.sender {
+            match &txn.transaction {
+                RawTransaction::UserTransaction(user_txn) if user_txn.sender() == *sender => {},
+                _ => return false,
+            }
+        }
+        if let Some((module, function)) = &self.entry_function {
+            match &txn.transaction {
+                RawTransaction::UserTransaction(user_txn) => match user_txn.payload() {
+                    TransactionPayload::EntryFunction(entry_function)
+                        if entry_function.module() == module
+                            && entry_function.function() == function.as_ident_str() => {},
+                    _ => return false,
+                },
+                _ => return false,
+            }
+        }
+        if let Some(event_type) = &self.event_type {
+            if !txn
+                .events
+                .iter()
+                .any(|event| event.type_tag() == event_type)
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// State of a transaction stream, reading committed transactions page by page
+struct TransactionStream {
+    context: Arc<Context>,
+    filter: TransactionFilter,
+    next_version: u64,
+    pending: VecDeque<Transaction>,
+    /// Held for as long as the stream is served
+    _permit: OwnedSemaphorePermit,
+}
+
+impl TransactionStream {
+    fn into_stream(self) -> impl futures::Stream<Item = Transaction> {
+        stream::unfold(self, |mut state| async move {
+            loop {
+                if let Some(txn) = state.pending.pop_front() {
+                    return Some((txn, state));
+                }
+                // Pages are read from storage on the blocking pool, so that scanning many pages of
+                // filtered out transactions doesn't hold up the async workers
+                let (result, returned_state) = match tokio::task::spawn_blocking(move || {
+                    let result = state.read_next_page();
+                    (result, state)
+                })
+                .await
+                {
+                    Ok(read) => read,
+                    Err(err) => {
+                        warn!(error = ?err, "Ending transaction stream");
+                        return None;
+                    },
+                };
+                state = returned_state;
+                let caught_up = match result {
+                    Ok(has_new_transactions) => !has_new_transactions,
+                    Err(err) => {
+                        warn!(
+                            next_version = state.next_version,
+                            error = ?err,
+                            "Ending transaction stream"
+                        );
+                        return None;
+                    },
+                };
+                if caught_up {
+                    tokio::time::sleep(state.context.transaction_stream_poll_interval()).await;
+                }
+            }
+        })
+    }
+
+    /// Reads the next page of transactions into `pending`. Returns false if there are no new
+    /// committed transactions.
+    fn read_next_page(&mut self) -> Result<bool, BasicErrorWith404> {
+        let ledger_info: LedgerInfo = self.context.get_latest_ledger_info()?;
+        let ledger_version = ledger_info.version();
+        if self.next_version > ledger_version {
+            return Ok(false);
+        }
+        if self.next_version < ledger_info.oldest_ledger_version.0 {
+            return Err(version_pruned(self.next_version, &ledger_info));
+        }
+
+        let limit = std::cmp::min(
+            self.context.max_transactions_page_size() as u64,
+            ledger_version - self.next_version + 1,
+        ) as u16;
+        let data = self
+            .context
+            .get_transactions(self.next_version, limit, ledger_version)
+            .context("Failed to read raw transactions from storage")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let mut timestamp = self
+            .context
+            .get_block_timestamp(&ledger_info, self.next_version)?;
+        self.next_version += data.len() as u64;
+
+        let resolver = self.context.move_resolver_poem(&ledger_info)?;
+        let converter = resolver.as_converter(self.context.db.clone());
+        for txn in data {
+            // Track the block timestamp, as skipped transactions may include block metadata
+            if let RawTransaction::BlockMetadata(block_metadata) = &txn.transaction {
+                timestamp = block_metadata.timestamp_usecs();
+            }
+            if !self.filter.matches(&txn) {
+                continue;
+            }
+            let txn = converter
+                .try_into_onchain_transaction(timestamp, txn)
+                .context("Failed to convert transaction data from storage")
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?;
+            self.pending.push_back(txn);
+        }
+
+        Ok(true)
+    }
+}
@@ -20,9 +20,10 @@ use crate::{
 use anyhow::{anyhow, Context as AnyhowContext};
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
-    AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
+    AsConverter, BcsTransactionWithLedgerProof, EncodeSubmissionRequest, GasEstimation,
+    GasEstimationBcs, HashValue, HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction,
+    SubmitTransactionRequest, Transaction, TransactionData, TransactionGasProfile,
+    TransactionOnChainData, TransactionWithLedgerProof, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
@@ -33,7 +34,8 @@ use aptos_types::{
     mempool_status::MempoolStatusCode,
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
-        RawTransactionWithData, SignedTransaction, TransactionPayload, TransactionStatus,
+        RawTransactionWithData, SignedTransaction, TransactionOutput, TransactionPayload,
+        TransactionStatus,
     },
     vm_status::StatusCode,
 };
@@ -43,7 +45,7 @@ use poem_openapi::{
     payload::Json,
     ApiRequest, OpenApi,
 };
-use std::sync::Arc;
+use std::{convert::TryFrom, sync::Arc};
 
 generate_success_response!(SubmitTransactionResponse, (202, Accepted));
 
@@ -227,6 +229,30 @@ impl TransactionsApi {
             .await
     }
 
+    /// Get transaction by version with proof
+    ///
+    /// Retrieves a transaction by a given version along with the proofs needed to verify it:
+    /// the transaction info with its accumulator proof, and the signed ledger info the proof
+    /// is relative to. The transaction and events are returned BCS encoded, so that they can be
+    /// hashed and checked against the transaction info.
+    #[oai(
+        path = "/transactions/by_version/:txn_version/proof",
+        method = "get",
+        operation_id = "get_transaction_by_version_with_proof",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_transaction_by_version_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Version of transaction to retrieve
+        txn_version: Path<U64>,
+    ) -> BasicResultWith404<TransactionWithLedgerProof> {
+        fail_point_poem("endpoint_transaction_by_version_with_proof")?;
+        self.context
+            .check_api_output_enabled("Get transactions by version with proof", &accept_type)?;
+        self.get_transaction_by_version_with_proof_inner(&accept_type, txn_version.0)
+    }
+
     /// Get account transactions
     ///
     /// Retrieves on-chain committed transactions from an account. If the start
@@ -524,6 +550,53 @@ impl TransactionsApi {
             .await
     }
 
+    /// Simulate transaction with gas profiling
+    ///
+    /// Simulates a transaction like the simulate endpoint does, and profiles its gas usage. The
+    /// gas charged is attributed to the frames of the Move call stack, by instruction class,
+    /// native function and state access, and to the intrinsic, IO and storage costs of the
+    /// transaction. It's returned in the folded stacks format, from which flame graphs can be
+    /// rendered, e.g., with `inferno-flamegraph`, along with a JSON summary.
+    ///
+    /// The gas parameters of the transaction are used as is. Only JSON output is supported.
+    #[oai(
+        path = "/transactions/simulate/gas_profile",
+        method = "post",
+        operation_id = "simulate_transaction_with_gas_profile",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_with_gas_profile(
+        &self,
+        accept_type: AcceptType,
+        data: SubmitTransactionPost,
+    ) -> SimulateTransactionResult<TransactionGasProfile> {
+        data.verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_with_gas_profile")?;
+        self.context
+            .check_api_output_enabled("Simulate transaction with gas profile", &accept_type)?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction with gas profile"));
+        }
+        if accept_type == AcceptType::Bcs {
+            return Err(SubmitTransactionError::bad_request_with_code_no_info(
+                "BCS is not supported for gas profiles",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
+
+        self.simulate_with_gas_profile(ledger_info, signed_transaction)
+            .await
+    }
+
     /// Encode submission
     ///
     /// This endpoint accepts an EncodeSubmissionRequest, which internally is a
@@ -702,6 +775,68 @@ impl TransactionsApi {
             .await
     }
 
+    fn get_transaction_by_version_with_proof_inner(
+        &self,
+        accept_type: &AcceptType,
+        version: U64,
+    ) -> BasicResultWith404<TransactionWithLedgerProof> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if version.0 > ledger_info.version() {
+            return Err(transaction_not_found_by_version(version.0, &ledger_info));
+        }
+        // The signed ledger info is read after `ledger_info`, so it is at least as new and
+        // `version` is guaranteed to be covered by it.
+        let ledger_info_with_signatures = self
+            .context
+            .get_latest_ledger_info_with_signatures()
+            .context("Failed to retrieve latest signed ledger info")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let transaction_with_proof = self
+            .context
+            .db
+            .get_transaction_by_version(
+                version.0,
+                ledger_info_with_signatures.ledger_info().version(),
+                true,
+            )
+            .context(format!("Failed to get transaction by version {}", version))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let proof = BcsTransactionWithLedgerProof {
+            transaction_with_proof,
+            ledger_info_with_signatures,
+        };
+
+        match accept_type {
+            AcceptType::Json => {
+                let proof = TransactionWithLedgerProof::try_from(proof)
+                    .context("Failed to encode transaction proof")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+                BasicResponse::try_from_json((proof, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((proof, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// Converts a transaction into the outgoing type
     async fn get_transaction_inner(
         &self,
@@ -1170,65 +1305,20 @@ impl TransactionsApi {
         // Simulate transaction
         let move_resolver = self.context.move_resolver_poem(&ledger_info)?;
         let (_, output_ext) = AptosVM::simulate_signed_transaction(&txn, &move_resolver);
-        let version = ledger_info.version();
 
         // Apply transaction outputs to build up a transaction
         // TODO: while `into_transaction_output_with_status()` should never fail
         // to apply deltas, we should propagate errors properly. Fix this when
         // VM error handling is fixed.
         let output = output_ext.into_transaction_output(&move_resolver);
-
-        // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
-        let exe_status = match output.status().clone() {
-            TransactionStatus::Keep(exec_status) => exec_status,
-            TransactionStatus::Discard(status) => ExecutionStatus::MiscellaneousError(Some(status)),
-            _ => ExecutionStatus::MiscellaneousError(None),
-        };
-
-        // Build up a transaction from the outputs
-        // All state hashes are invalid, and will be filled with 0s
-        let txn = aptos_types::transaction::Transaction::UserTransaction(txn);
-        let zero_hash = aptos_crypto::HashValue::zero();
-        let info = aptos_types::transaction::TransactionInfo::new(
-            txn.hash(),
-            zero_hash,
-            zero_hash,
-            None,
-            output.gas_used(),
-            exe_status,
-        );
-        let simulated_txn = TransactionOnChainData {
-            version,
-            transaction: txn,
-            info,
-            events: output.events().to_vec(),
-            accumulator_root_hash: zero_hash,
-            changes: output.write_set().clone(),
-        };
+        let simulated_txn = simulated_transaction(&ledger_info, txn, output);
 
         match accept_type {
             AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
-
-                // Users can only make requests to simulate UserTransactions, so unpack
-                // the Vec<Transaction> into Vec<UserTransaction>.
-                let mut user_transactions = Vec::new();
-                for transaction in transactions.into_iter() {
-                    match transaction {
-                        Transaction::UserTransaction(user_txn) => user_transactions.push(*user_txn),
-                        _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
-                                "Simulation transaction resulted in a non-UserTransaction",
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            ))
-                        },
-                    }
-                }
+                let user_transaction =
+                    self.render_simulated_transaction(&ledger_info, simulated_txn)?;
                 BasicResponse::try_from_json((
-                    user_transactions,
+                    vec![user_transaction],
                     &ledger_info,
                     BasicResponseStatus::Ok,
                 ))
@@ -1239,6 +1329,74 @@ impl TransactionsApi {
         }
     }
 
+    /// Simulate a transaction in the VM, profiling its gas usage
+    async fn simulate_with_gas_profile(
+        &self,
+        ledger_info: LedgerInfo,
+        txn: SignedTransaction,
+    ) -> SimulateTransactionResult<TransactionGasProfile> {
+        // Transactions shouldn't have a valid signature or this could be used to attack
+        if txn.signature_is_valid() {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Simulated transactions must have a non-valid signature",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let move_resolver = self.context.move_resolver_poem(&ledger_info)?;
+        let (vm_status, output_ext, gas_log) =
+            AptosVM::simulate_signed_transaction_with_gas_profiling(&txn, &move_resolver);
+        // The gas log is only missing if the transaction is discarded before any gas is charged
+        let gas_log = gas_log.ok_or_else(|| {
+            SubmitTransactionError::bad_request_with_vm_status(
+                format!("Transaction discarded before execution: {:?}", vm_status),
+                AptosErrorCode::VmError,
+                vm_status.status_code(),
+                &ledger_info,
+            )
+        })?;
+        let summary = serde_json::to_value(gas_log.to_summary()).map_err(|err| {
+            SubmitTransactionError::internal_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+
+        let output = output_ext.into_transaction_output(&move_resolver);
+        let simulated_txn = simulated_transaction(&ledger_info, txn, output);
+        let transaction = self.render_simulated_transaction(&ledger_info, simulated_txn)?;
+        BasicResponse::try_from_json((
+            TransactionGasProfile {
+                transaction,
+                folded_stacks: gas_log.to_folded_stacks(),
+                summary,
+            },
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+    }
+
+    /// Renders a simulated transaction, which can only be a user transaction
+    fn render_simulated_transaction(
+        &self,
+        ledger_info: &LedgerInfo,
+        simulated_txn: TransactionOnChainData,
+    ) -> Result<UserTransaction, SubmitTransactionError> {
+        let transactions = self
+            .context
+            .render_transactions_non_sequential(ledger_info, vec![simulated_txn])?;
+        match transactions.into_iter().next() {
+            Some(Transaction::UserTransaction(user_txn)) => Ok(*user_txn),
+            _ => Err(SubmitTransactionError::internal_with_code(
+                "Simulation transaction resulted in a non-UserTransaction",
+                AptosErrorCode::InternalError,
+                ledger_info,
+            )),
+        }
+    }
+
     /// Encode message as BCS
     pub fn get_signing_message(
         &self,
@@ -1263,8 +1421,23 @@ impl TransactionsApi {
                 BasicError::bad_request_with_code(err, AptosErrorCode::InvalidInput, &ledger_info)
             })?;
 
-        let raw_message = match request.secondary_signers {
-            Some(secondary_signer_addresses) => signing_message(
+        let raw_message = match (request.secondary_signers, request.fee_payer_address) {
+            (secondary_signers, Some(fee_payer_address)) => signing_message(
+                &RawTransactionWithData::new_multi_agent_with_fee_payer(
+                    raw_txn,
+                    secondary_signers
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|v| v.into())
+                        .collect(),
+                    fee_payer_address.into(),
+                ),
+            )
+            .context("Invalid transaction to generate signing message")
+            .map_err(|err| {
+                BasicError::bad_request_with_code(err, AptosErrorCode::InvalidInput, &ledger_info)
+            })?,
+            (Some(secondary_signer_addresses), None) => signing_message(
                 &RawTransactionWithData::new_multi_agent(
                     raw_txn,
                     secondary_signer_addresses
@@ -1277,7 +1450,7 @@ impl TransactionsApi {
             .map_err(|err| {
                 BasicError::bad_request_with_code(err, AptosErrorCode::InvalidInput, &ledger_info)
             })?,
-            None => raw_txn
+            (None, None) => raw_txn
                 .signing_message()
                 .context("Invalid transaction to generate signing message")
                 .map_err(|err| {
@@ -1317,3 +1490,37 @@ fn override_gas_parameters(
     // TODO: Check that signature is null, this would just be helpful for downstream use
     SignedTransaction::new_with_authenticator(raw_txn, signed_txn.authenticator())
 }
+
+/// Builds up a transaction from the outputs of its simulation. All state hashes are invalid, and
+/// are filled with 0s.
+fn simulated_transaction(
+    ledger_info: &LedgerInfo,
+    txn: SignedTransaction,
+    output: TransactionOutput,
+) -> TransactionOnChainData {
+    // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
+    let exe_status = match output.status().clone() {
+        TransactionStatus::Keep(exec_status) => exec_status,
+        TransactionStatus::Discard(status) => ExecutionStatus::MiscellaneousError(Some(status)),
+        _ => ExecutionStatus::MiscellaneousError(None),
+    };
+
+    let txn = aptos_types::transaction::Transaction::UserTransaction(txn);
+    let zero_hash = aptos_crypto::HashValue::zero();
+    let info = aptos_types::transaction::TransactionInfo::new(
+        txn.hash(),
+        zero_hash,
+        zero_hash,
+        None,
+        output.gas_used(),
+        exe_status,
+    );
+    TransactionOnChainData {
+        version: ledger_info.version(),
+        transaction: txn,
+        info,
+        events: output.events().to_vec(),
+        accumulator_root_hash: zero_hash,
+        changes: output.write_set().clone(),
+    }
+}
@@ -117,6 +117,7 @@ pub fn new_test_context(test_name: String, use_db_with_indexer: bool) -> TestCon
                 NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
                 RocksdbConfigs::default(),
                 false, /* indexer */
+                false, /* event type index */
                 BUFFERED_STATE_TARGET_ITEMS,
                 DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
             )
@@ -129,7 +130,8 @@ pub fn new_test_context(test_name: String, use_db_with_indexer: bool) -> TestCon
 
     let mempool = MockSharedMempool::new_in_runtime(&db_rw, VMValidator::new(db.clone()));
 
-    let node_config = NodeConfig::default();
+    let mut node_config = NodeConfig::default();
+    node_config.api.transaction_stream_enabled = true;
 
     let context = Context::new(
         ChainId::test(),
@@ -827,6 +829,18 @@ impl TestContext {
         sender: &LocalAccount,
         payload: Value,
         status_code: u16,
+    ) -> Value {
+        self.simulate_transaction_at("/transactions/simulate", sender, payload, status_code)
+            .await
+    }
+
+    /// Simulates a transaction with a simulation endpoint at `path`, e.g., the gas profiling one.
+    pub async fn simulate_transaction_at(
+        &mut self,
+        path: &str,
+        sender: &LocalAccount,
+        payload: Value,
+        status_code: u16,
     ) -> Value {
         let mut request = json!({
             "sender": sender.address(),
@@ -860,7 +874,7 @@ impl TestContext {
         });
 
         self.expect_status_code(status_code)
-            .post("/transactions/simulate", request)
+            .post(path, request)
             .await
     }
 
@@ -15,6 +15,7 @@ mod index;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
+mod proof;
 mod table;
 pub mod transaction;
 mod view;
@@ -37,20 +38,26 @@ pub use move_types::{
     MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType, MoveValue,
     MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
 };
+pub use proof::{
+    BcsStateValueWithLedgerProof, BcsTransactionWithLedgerProof, StateValueWithLedgerProof,
+    TransactionWithLedgerProof,
+};
 use serde::{Deserialize, Deserializer};
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
 pub use transaction::{
     AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
     DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event,
-    GasEstimation, GasEstimationBcs, GenesisPayload, GenesisTransaction, ModuleBundlePayload,
-    MultiAgentSignature, MultiEd25519Signature, MultisigPayload, MultisigTransactionPayload,
-    PendingTransaction, ScriptPayload, ScriptWriteSet, SubmitTransactionRequest, Transaction,
-    TransactionData, TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
-    TransactionSignature, TransactionSigningMessage, TransactionsBatchSingleSubmissionFailure,
-    TransactionsBatchSubmissionResult, UserCreateSigningMessageRequest, UserTransaction,
-    UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
-    WriteSetPayload, WriteTableItem,
+    FeePayerSignature, GasEstimation, GasEstimationBcs, GenesisPayload, GenesisTransaction,
+    ModuleBundlePayload, MultiAgentSignature, MultiEd25519Signature, MultisigPayload,
+    MultisigTransactionPayload, PendingTransaction, ScriptPayload, ScriptWriteSet,
+    Secp256r1EcdsaSignature, SubmitTransactionRequest, Transaction, TransactionData,
+    TransactionGasProfile, TransactionId, TransactionInfo, TransactionOnChainData,
+    TransactionPayload, TransactionSignature, TransactionSigningMessage,
+    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult,
+    UserCreateSigningMessageRequest, UserTransaction, UserTransactionRequest, VersionedEvent,
+    WebAuthnSignature, WriteModule, WriteResource, WriteSet, WriteSetChange, WriteSetPayload,
+    WriteTableItem,
 };
 pub use view::ViewRequest;
 pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
@@ -0,0 +1,157 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{HexEncodedBytes, U64};
+use anyhow::Context;
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleProof, TransactionInfoWithProof},
+    state_store::state_value::{StateValue, StateValueWithProof},
+    transaction::{Transaction, TransactionWithProof},
+};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// A state value along with the proofs needed to verify it against a signed ledger info
+///
+/// Every field other than `version` is hex encoded BCS of the corresponding `aptos-types`
+/// structure, so that clients can verify the response without trusting the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StateValueWithLedgerProof {
+    /// Version of the state snapshot the value was read at, the latest one at or before the
+    /// requested ledger version
+    pub version: U64,
+    /// BCS encoded `StateValue`, absent if the state key doesn't exist at `version`
+    pub value: Option<HexEncodedBytes>,
+    /// BCS encoded `SparseMerkleProof` of the value against the state checkpoint hash
+    pub proof: HexEncodedBytes,
+    /// BCS encoded `TransactionInfoWithProof` of the transaction info at `version`
+    pub transaction_info_with_proof: HexEncodedBytes,
+    /// BCS encoded `LedgerInfoWithSignatures` the transaction info proof is relative to
+    pub ledger_info_with_signatures: HexEncodedBytes,
+}
+
+/// A state value along with the proofs needed to verify it, for encoding in BCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BcsStateValueWithLedgerProof {
+    pub state_value_with_proof: StateValueWithProof,
+    pub ledger_info_with_signatures: LedgerInfoWithSignatures,
+}
+
+impl TryFrom<BcsStateValueWithLedgerProof> for StateValueWithLedgerProof {
+    type Error = anyhow::Error;
+
+    fn try_from(value: BcsStateValueWithLedgerProof) -> anyhow::Result<Self> {
+        let BcsStateValueWithLedgerProof {
+            state_value_with_proof,
+            ledger_info_with_signatures,
+        } = value;
+        Ok(Self {
+            version: state_value_with_proof.version.into(),
+            value: state_value_with_proof
+                .value
+                .as_ref()
+                .map(encode)
+                .transpose()?,
+            proof: encode(&state_value_with_proof.proof)?,
+            transaction_info_with_proof: encode(
+                &state_value_with_proof.transaction_info_with_proof,
+            )?,
+            ledger_info_with_signatures: encode(&ledger_info_with_signatures)?,
+        })
+    }
+}
+
+impl TryFrom<StateValueWithLedgerProof> for BcsStateValueWithLedgerProof {
+    type Error = anyhow::Error;
+
+    fn try_from(value: StateValueWithLedgerProof) -> anyhow::Result<Self> {
+        let state_value: Option<StateValue> = value.value.as_ref().map(decode).transpose()?;
+        let proof: SparseMerkleProof = decode(&value.proof)?;
+        let transaction_info_with_proof: TransactionInfoWithProof =
+            decode(&value.transaction_info_with_proof)?;
+        Ok(Self {
+            state_value_with_proof: StateValueWithProof::new(
+                value.version.0,
+                state_value,
+                proof,
+                transaction_info_with_proof,
+            ),
+            ledger_info_with_signatures: decode(&value.ledger_info_with_signatures)?,
+        })
+    }
+}
+
+/// A committed transaction along with the proofs needed to verify it against a signed ledger info
+///
+/// Every field other than `version` is hex encoded BCS of the corresponding `aptos-types`
+/// structure, so that clients can verify the response without trusting the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TransactionWithLedgerProof {
+    /// The ledger version of the transaction
+    pub version: U64,
+    /// BCS encoded `Transaction`
+    pub transaction: HexEncodedBytes,
+    /// BCS encoded `Vec<ContractEvent>` emitted by the transaction
+    pub events: HexEncodedBytes,
+    /// BCS encoded `TransactionInfoWithProof` of the transaction info at `version`
+    pub transaction_info_with_proof: HexEncodedBytes,
+    /// BCS encoded `LedgerInfoWithSignatures` the transaction info proof is relative to
+    pub ledger_info_with_signatures: HexEncodedBytes,
+}
+
+/// A committed transaction along with the proofs needed to verify it, for encoding in BCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BcsTransactionWithLedgerProof {
+    pub transaction_with_proof: TransactionWithProof,
+    pub ledger_info_with_signatures: LedgerInfoWithSignatures,
+}
+
+impl TryFrom<BcsTransactionWithLedgerProof> for TransactionWithLedgerProof {
+    type Error = anyhow::Error;
+
+    fn try_from(value: BcsTransactionWithLedgerProof) -> anyhow::Result<Self> {
+        let BcsTransactionWithLedgerProof {
+            transaction_with_proof,
+            ledger_info_with_signatures,
+        } = value;
+        Ok(Self {
+            version: transaction_with_proof.version.into(),
+            transaction: encode(&transaction_with_proof.transaction)?,
+            events: encode(&transaction_with_proof.events.unwrap_or_default())?,
+            transaction_info_with_proof: encode(&transaction_with_proof.proof)?,
+            ledger_info_with_signatures: encode(&ledger_info_with_signatures)?,
+        })
+    }
+}
+
+impl TryFrom<TransactionWithLedgerProof> for BcsTransactionWithLedgerProof {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TransactionWithLedgerProof) -> anyhow::Result<Self> {
+        let transaction: Transaction = decode(&value.transaction)?;
+        let events: Vec<ContractEvent> = decode(&value.events)?;
+        let proof: TransactionInfoWithProof = decode(&value.transaction_info_with_proof)?;
+        Ok(Self {
+            transaction_with_proof: TransactionWithProof::new(
+                value.version.0,
+                transaction,
+                Some(events),
+                proof,
+            ),
+            ledger_info_with_signatures: decode(&value.ledger_info_with_signatures)?,
+        })
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> anyhow::Result<HexEncodedBytes> {
+    Ok(HexEncodedBytes(
+        bcs::to_bytes(value).context("Failed to BCS encode proof field")?,
+    ))
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(bytes: &HexEncodedBytes) -> anyhow::Result<T> {
+    bcs::from_bytes(&bytes.0).context("Failed to BCS decode proof field")
+}
@@ -11,13 +11,21 @@ use anyhow::{bail, Context as AnyhowContext};
 use aptos_crypto::{
     ed25519::{self, Ed25519PublicKey, ED25519_PUBLIC_KEY_LENGTH, ED25519_SIGNATURE_LENGTH},
     multi_ed25519::{self, MultiEd25519PublicKey, BITMAP_NUM_OF_BYTES, MAX_NUM_OF_KEYS},
+    secp256r1_ecdsa::{
+        self, Secp256r1EcdsaPublicKey, SECP256R1_ECDSA_PUBLIC_KEY_LENGTH,
+        SECP256R1_ECDSA_SIGNATURE_LENGTH,
+    },
 };
 use aptos_types::{
     account_address::AccountAddress,
     block_metadata::BlockMetadata,
     contract_event::{ContractEvent, EventWithVersion},
     transaction::{
-        authenticator::{AccountAuthenticator, TransactionAuthenticator, MAX_NUM_OF_SIGS},
+        authenticator::{
+            AccountAuthenticator, Secp256r1EcdsaSignatureEnvelope, TransactionAuthenticator,
+            MAX_NUM_OF_SIGS,
+        },
+        webauthn::PartialAuthenticatorAssertionResponse,
         Script, SignedTransaction, TransactionOutput, TransactionWithProof,
     },
 };
@@ -366,6 +374,19 @@ pub struct UserTransaction {
     pub timestamp: U64,
 }
 
+/// A simulated user transaction along with the breakdown of the gas it used
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TransactionGasProfile {
+    /// The simulated transaction
+    pub transaction: UserTransaction,
+    /// Gas charged by each stack of Move calls, in internal gas units, in the folded stacks
+    /// format, one `frame;frame;...;frame cost` line per stack, from which flame graphs can be
+    /// rendered
+    pub folded_stacks: String,
+    /// Summary of the gas charged by function, instruction class, state access and storage fee
+    pub summary: serde_json::Value,
+}
+
 /// A state checkpoint transaction
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct StateCheckpointTransaction {
@@ -461,6 +482,8 @@ pub struct UserCreateSigningMessageRequest {
 }
 
 /// Request to encode a submission
+///
+/// The `fee_payer_address` is set to encode the submission of a fee payer transaction
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct EncodeSubmissionRequest {
     #[serde(flatten)]
@@ -469,6 +492,8 @@ pub struct EncodeSubmissionRequest {
     /// Secondary signer accounts of the request for Multi-agent
     #[serde(skip_serializing_if = "Option::is_none")]
     pub secondary_signers: Option<Vec<Address>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_payer_address: Option<Address>,
 }
 
 impl VerifyInput for EncodeSubmissionRequest {
@@ -848,6 +873,9 @@ pub enum TransactionSignature {
     Ed25519Signature(Ed25519Signature),
     MultiEd25519Signature(MultiEd25519Signature),
     MultiAgentSignature(MultiAgentSignature),
+    Secp256r1EcdsaSignature(Secp256r1EcdsaSignature),
+    WebAuthnSignature(WebAuthnSignature),
+    FeePayerSignature(FeePayerSignature),
 }
 
 impl VerifyInput for TransactionSignature {
@@ -856,6 +884,9 @@ impl VerifyInput for TransactionSignature {
             TransactionSignature::Ed25519Signature(inner) => inner.verify(),
             TransactionSignature::MultiEd25519Signature(inner) => inner.verify(),
             TransactionSignature::MultiAgentSignature(inner) => inner.verify(),
+            TransactionSignature::Secp256r1EcdsaSignature(inner) => inner.verify(),
+            TransactionSignature::WebAuthnSignature(inner) => inner.verify(),
+            TransactionSignature::FeePayerSignature(inner) => inner.verify(),
         }
     }
 }
@@ -868,6 +899,9 @@ impl TryFrom<TransactionSignature> for TransactionAuthenticator {
             TransactionSignature::Ed25519Signature(sig) => sig.try_into()?,
             TransactionSignature::MultiEd25519Signature(sig) => sig.try_into()?,
             TransactionSignature::MultiAgentSignature(sig) => sig.try_into()?,
+            TransactionSignature::Secp256r1EcdsaSignature(sig) => sig.try_into()?,
+            TransactionSignature::WebAuthnSignature(sig) => sig.try_into()?,
+            TransactionSignature::FeePayerSignature(sig) => sig.try_into()?,
         })
     }
 }
@@ -1064,18 +1098,169 @@ impl TryFrom<MultiEd25519Signature> for AccountAuthenticator {
     }
 }
 
+/// A single secp256r1 ECDSA signature
+///
+/// The public key is SEC1 encoded and uncompressed. The signature is the concatenation of its r
+/// and s components, s being in the lower half of the curve order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct Secp256r1EcdsaSignature {
+    pub public_key: HexEncodedBytes,
+    pub signature: HexEncodedBytes,
+}
+
+impl VerifyInput for Secp256r1EcdsaSignature {
+    fn verify(&self) -> anyhow::Result<()> {
+        verify_secp256r1_ecdsa_lengths("Secp256r1Ecdsa", &self.public_key, &self.signature)
+    }
+}
+
+impl Secp256r1EcdsaSignature {
+    fn try_into_parts(
+        self,
+    ) -> anyhow::Result<(Secp256r1EcdsaPublicKey, Secp256r1EcdsaSignatureEnvelope)> {
+        let Secp256r1EcdsaSignature {
+            public_key,
+            signature,
+        } = self;
+        Ok((
+            public_key
+                .inner()
+                .try_into()
+                .context("Failed to parse given public_key bytes as a Secp256r1EcdsaPublicKey")?,
+            Secp256r1EcdsaSignatureEnvelope::Raw(
+                signature
+                    .inner()
+                    .try_into()
+                    .context("Failed to parse given signature as a Secp256r1EcdsaSignature")?,
+            ),
+        ))
+    }
+}
+
+impl TryFrom<Secp256r1EcdsaSignature> for TransactionAuthenticator {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Secp256r1EcdsaSignature) -> Result<Self, Self::Error> {
+        let (public_key, signature) = value.try_into_parts()?;
+        Ok(TransactionAuthenticator::secp256r1_ecdsa(
+            public_key, signature,
+        ))
+    }
+}
+
+impl TryFrom<Secp256r1EcdsaSignature> for AccountAuthenticator {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Secp256r1EcdsaSignature) -> Result<Self, Self::Error> {
+        let (public_key, signature) = value.try_into_parts()?;
+        Ok(AccountAuthenticator::secp256r1_ecdsa(public_key, signature))
+    }
+}
+
+/// A secp256r1 ECDSA signature made by a WebAuthn authenticator, e.g., a passkey
+///
+/// The public key is SEC1 encoded and uncompressed. The signature is the concatenation of its r
+/// and s components. The client data JSON is UTF-8 encoded, and its challenge must be the
+/// SHA3-256 hash of the signing message of the transaction, base64url encoded without padding.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct WebAuthnSignature {
+    pub public_key: HexEncodedBytes,
+    pub signature: HexEncodedBytes,
+    pub authenticator_data: HexEncodedBytes,
+    pub client_data_json: HexEncodedBytes,
+}
+
+impl VerifyInput for WebAuthnSignature {
+    fn verify(&self) -> anyhow::Result<()> {
+        verify_secp256r1_ecdsa_lengths("WebAuthn", &self.public_key, &self.signature)
+    }
+}
+
+impl WebAuthnSignature {
+    fn try_into_parts(
+        self,
+    ) -> anyhow::Result<(Secp256r1EcdsaPublicKey, Secp256r1EcdsaSignatureEnvelope)> {
+        let WebAuthnSignature {
+            public_key,
+            signature,
+            authenticator_data,
+            client_data_json,
+        } = self;
+        Ok((
+            public_key
+                .inner()
+                .try_into()
+                .context("Failed to parse given public_key bytes as a Secp256r1EcdsaPublicKey")?,
+            // Authenticators do not necessarily produce low-s signatures.
+            Secp256r1EcdsaSignatureEnvelope::WebAuthn(PartialAuthenticatorAssertionResponse::new(
+                secp256r1_ecdsa::Secp256r1EcdsaSignature::normalize(signature.inner())
+                    .context("Failed to parse given signature as a Secp256r1EcdsaSignature")?,
+                authenticator_data.into(),
+                client_data_json.into(),
+            )),
+        ))
+    }
+}
+
+impl TryFrom<WebAuthnSignature> for TransactionAuthenticator {
+    type Error = anyhow::Error;
+
+    fn try_from(value: WebAuthnSignature) -> Result<Self, Self::Error> {
+        let (public_key, signature) = value.try_into_parts()?;
+        Ok(TransactionAuthenticator::secp256r1_ecdsa(
+            public_key, signature,
+        ))
+    }
+}
+
+impl TryFrom<WebAuthnSignature> for AccountAuthenticator {
+    type Error = anyhow::Error;
+
+    fn try_from(value: WebAuthnSignature) -> Result<Self, Self::Error> {
+        let (public_key, signature) = value.try_into_parts()?;
+        Ok(AccountAuthenticator::secp256r1_ecdsa(public_key, signature))
+    }
+}
+
+fn verify_secp256r1_ecdsa_lengths(
+    name: &str,
+    public_key: &HexEncodedBytes,
+    signature: &HexEncodedBytes,
+) -> anyhow::Result<()> {
+    let public_key_len = public_key.inner().len();
+    let signature_len = signature.inner().len();
+    if public_key_len != SECP256R1_ECDSA_PUBLIC_KEY_LENGTH {
+        bail!(
+            "{} signature's public key is an invalid number of bytes, should be {} bytes but found {}",
+            name, SECP256R1_ECDSA_PUBLIC_KEY_LENGTH, public_key_len
+        )
+    } else if signature_len != SECP256R1_ECDSA_SIGNATURE_LENGTH {
+        bail!(
+            "{} signature length is an invalid number of bytes, should be {} bytes but found {}",
+            name,
+            SECP256R1_ECDSA_SIGNATURE_LENGTH,
+            signature_len
+        )
+    } else {
+        Ok(())
+    }
+}
+
 /// Account signature scheme
 ///
-/// The account signature scheme allows you to have two types of accounts:
+/// The account signature scheme allows you to have three types of accounts:
 ///
 ///   1. A single Ed25519 key account, one private key
 ///   2. A k-of-n multi-Ed25519 key account, multiple private keys, such that k-of-n must sign a transaction.
+///   3. A single secp256r1 ECDSA key account, e.g., a passkey, which signs either directly or through WebAuthn.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Union)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
 pub enum AccountSignature {
     Ed25519Signature(Ed25519Signature),
     MultiEd25519Signature(MultiEd25519Signature),
+    Secp256r1EcdsaSignature(Secp256r1EcdsaSignature),
+    WebAuthnSignature(WebAuthnSignature),
 }
 
 impl VerifyInput for AccountSignature {
@@ -1083,6 +1268,8 @@ impl VerifyInput for AccountSignature {
         match self {
             AccountSignature::Ed25519Signature(inner) => inner.verify(),
             AccountSignature::MultiEd25519Signature(inner) => inner.verify(),
+            AccountSignature::Secp256r1EcdsaSignature(inner) => inner.verify(),
+            AccountSignature::WebAuthnSignature(inner) => inner.verify(),
         }
     }
 }
@@ -1094,6 +1281,8 @@ impl TryFrom<AccountSignature> for AccountAuthenticator {
         Ok(match sig {
             AccountSignature::Ed25519Signature(s) => s.try_into()?,
             AccountSignature::MultiEd25519Signature(s) => s.try_into()?,
+            AccountSignature::Secp256r1EcdsaSignature(s) => s.try_into()?,
+            AccountSignature::WebAuthnSignature(s) => s.try_into()?,
         })
     }
 }
@@ -1152,6 +1341,64 @@ impl TryFrom<MultiAgentSignature> for TransactionAuthenticator {
     }
 }
 
+/// Fee payer signature for fee payer transactions
+///
+/// This allows you to have transactions whose gas is paid by the account at `fee_payer_address`,
+/// which signs with `fee_payer_signer`, rather than by the sender, optionally across multiple
+/// accounts
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct FeePayerSignature {
+    pub sender: AccountSignature,
+    /// The other involved parties' addresses
+    pub secondary_signer_addresses: Vec<Address>,
+    /// The associated signatures, in the same order as the secondary addresses
+    pub secondary_signers: Vec<AccountSignature>,
+    pub fee_payer_address: Address,
+    pub fee_payer_signer: AccountSignature,
+}
+
+impl VerifyInput for FeePayerSignature {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.sender.verify()?;
+
+        if self.secondary_signers.len() != self.secondary_signer_addresses.len() {
+            bail!("FeePayer signatures don't match addresses length")
+        }
+
+        for signer in self.secondary_signers.iter() {
+            signer.verify()?;
+        }
+        self.fee_payer_signer.verify()
+    }
+}
+
+impl TryFrom<FeePayerSignature> for TransactionAuthenticator {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FeePayerSignature) -> Result<Self, Self::Error> {
+        let FeePayerSignature {
+            sender,
+            secondary_signer_addresses,
+            secondary_signers,
+            fee_payer_address,
+            fee_payer_signer,
+        } = value;
+        Ok(TransactionAuthenticator::fee_payer(
+            sender.try_into()?,
+            secondary_signer_addresses
+                .into_iter()
+                .map(|a| a.into())
+                .collect(),
+            secondary_signers
+                .into_iter()
+                .map(|s| s.try_into())
+                .collect::<anyhow::Result<_>>()?,
+            fee_payer_address.into(),
+            fee_payer_signer.try_into()?,
+        ))
+    }
+}
+
 impl From<(&Ed25519PublicKey, &ed25519::Ed25519Signature)> for Ed25519Signature {
     fn from((pk, sig): (&Ed25519PublicKey, &ed25519::Ed25519Signature)) -> Self {
         Self {
@@ -1190,6 +1437,46 @@ impl
     }
 }
 
+impl
+    From<(
+        &Secp256r1EcdsaPublicKey,
+        &secp256r1_ecdsa::Secp256r1EcdsaSignature,
+    )> for Secp256r1EcdsaSignature
+{
+    fn from(
+        (pk, sig): (
+            &Secp256r1EcdsaPublicKey,
+            &secp256r1_ecdsa::Secp256r1EcdsaSignature,
+        ),
+    ) -> Self {
+        Self {
+            public_key: pk.to_bytes().to_vec().into(),
+            signature: sig.to_bytes().to_vec().into(),
+        }
+    }
+}
+
+impl
+    From<(
+        &Secp256r1EcdsaPublicKey,
+        &PartialAuthenticatorAssertionResponse,
+    )> for WebAuthnSignature
+{
+    fn from(
+        (pk, response): (
+            &Secp256r1EcdsaPublicKey,
+            &PartialAuthenticatorAssertionResponse,
+        ),
+    ) -> Self {
+        Self {
+            public_key: pk.to_bytes().to_vec().into(),
+            signature: response.signature().to_bytes().to_vec().into(),
+            authenticator_data: response.authenticator_data().to_vec().into(),
+            client_data_json: response.client_data_json().to_vec().into(),
+        }
+    }
+}
+
 impl From<&AccountAuthenticator> for AccountSignature {
     fn from(auth: &AccountAuthenticator) -> Self {
         use AccountAuthenticator::*;
@@ -1202,6 +1489,14 @@ impl From<&AccountAuthenticator> for AccountSignature {
                 public_key,
                 signature,
             } => Self::MultiEd25519Signature((public_key, signature).into()),
+            Secp256r1Ecdsa {
+                public_key,
+                signature: Secp256r1EcdsaSignatureEnvelope::Raw(signature),
+            } => Self::Secp256r1EcdsaSignature((public_key, signature).into()),
+            Secp256r1Ecdsa {
+                public_key,
+                signature: Secp256r1EcdsaSignatureEnvelope::WebAuthn(response),
+            } => Self::WebAuthnSignature((public_key, response).into()),
         }
     }
 }
@@ -1228,6 +1523,34 @@ impl
     }
 }
 
+impl
+    From<(
+        &AccountAuthenticator,
+        &Vec<AccountAddress>,
+        &Vec<AccountAuthenticator>,
+        &AccountAddress,
+        &AccountAuthenticator,
+    )> for FeePayerSignature
+{
+    fn from(
+        (sender, addresses, signers, fee_payer_address, fee_payer_signer): (
+            &AccountAuthenticator,
+            &Vec<AccountAddress>,
+            &Vec<AccountAuthenticator>,
+            &AccountAddress,
+            &AccountAuthenticator,
+        ),
+    ) -> Self {
+        Self {
+            sender: sender.into(),
+            secondary_signer_addresses: addresses.iter().map(|address| (*address).into()).collect(),
+            secondary_signers: signers.iter().map(|s| s.into()).collect(),
+            fee_payer_address: (*fee_payer_address).into(),
+            fee_payer_signer: fee_payer_signer.into(),
+        }
+    }
+}
+
 impl From<TransactionAuthenticator> for TransactionSignature {
     fn from(auth: TransactionAuthenticator) -> Self {
         use TransactionAuthenticator::*;
@@ -1247,6 +1570,30 @@ impl From<TransactionAuthenticator> for TransactionSignature {
             } => Self::MultiAgentSignature(
                 (sender, secondary_signer_addresses, secondary_signers).into(),
             ),
+            Secp256r1Ecdsa {
+                public_key,
+                signature: Secp256r1EcdsaSignatureEnvelope::Raw(signature),
+            } => Self::Secp256r1EcdsaSignature((public_key, signature).into()),
+            Secp256r1Ecdsa {
+                public_key,
+                signature: Secp256r1EcdsaSignatureEnvelope::WebAuthn(response),
+            } => Self::WebAuthnSignature((public_key, response).into()),
+            FeePayer {
+                sender,
+                secondary_signer_addresses,
+                secondary_signers,
+                fee_payer_address,
+                fee_payer_signer,
+            } => Self::FeePayerSignature(
+                (
+                    sender,
+                    secondary_signer_addresses,
+                    secondary_signers,
+                    fee_payer_address,
+                    fee_payer_signer,
+                )
+                    .into(),
+            ),
         }
     }
 }
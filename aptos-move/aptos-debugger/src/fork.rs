@@ -0,0 +1,271 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::run_session;
+use anyhow::{format_err, Result};
+use aptos_crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use aptos_state_view::TStateView;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::CoinStoreResource,
+    state_store::state_key::StateKey,
+    transaction::{ChangeSet, RawTransaction, SignedTransaction, TransactionOutput},
+};
+use aptos_validator_interface::{
+    AptosValidatorInterface, DebuggerStateView, ForkState, ForkedStateView,
+};
+use aptos_vm::{data_cache::StorageAdapter, move_vm_ext::SessionExt, AptosVM};
+use move_binary_format::{errors::VMResult, CompiledModule};
+use move_core_types::{language_storage::StructTag, move_resource::MoveStructType};
+use std::{path::Path, sync::Arc};
+use tokio::runtime::{Builder, Handle, Runtime};
+
+/// A local fork of the chain state, on top of which modules and resources can be overridden
+/// and new transactions executed without being signed.
+///
+/// All changes are kept in a [`ForkState`], which can be saved and later resumed.
+pub struct ForkSession {
+    base: DebuggerStateView,
+    state: ForkState,
+    /// Serves the reads of `base` if the session was created outside of a tokio runtime,
+    /// dropped last so that `base` stops using it first
+    _runtime: Option<Runtime>,
+}
+
+impl ForkSession {
+    /// Reads of the chain state are served by the current tokio runtime, or by a runtime owned
+    /// by the session if there is none.
+    pub fn new(
+        debugger: Arc<dyn AptosValidatorInterface + Send>,
+        state: ForkState,
+    ) -> Result<Self> {
+        let (handle, runtime) = match Handle::try_current() {
+            Ok(handle) => (handle, None),
+            Err(_) => {
+                let runtime = Builder::new_multi_thread()
+                    .thread_name("fork-state")
+                    .enable_all()
+                    .build()?;
+                (runtime.handle().clone(), Some(runtime))
+            },
+        };
+        Ok(Self {
+            base: DebuggerStateView::new_with_handle(debugger, state.version(), &handle),
+            state,
+            _runtime: runtime,
+        })
+    }
+
+    pub fn state(&self) -> &ForkState {
+        &self.state
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.state.save(path)
+    }
+
+    pub fn state_view(&self) -> ForkedStateView {
+        ForkedStateView::new(&self.base, &self.state)
+    }
+
+    /// Publishes or replaces a module, bypassing the compatibility checks.
+    pub fn set_module(&mut self, module_bytes: Vec<u8>) -> Result<()> {
+        let module = CompiledModule::deserialize(&module_bytes)
+            .map_err(|err| format_err!("Failed to deserialize module: {:?}", err))?;
+        let state_key = StateKey::access_path(AccessPath::code_access_path(module.self_id()));
+        self.state
+            .set_state_value(state_key, Some(module_bytes.into()));
+        Ok(())
+    }
+
+    /// Sets the BCS encoded value of a resource, creating it if needed.
+    pub fn set_resource(
+        &mut self,
+        address: AccountAddress,
+        struct_tag: StructTag,
+        resource_bytes: Vec<u8>,
+    ) -> Result<()> {
+        let state_key =
+            StateKey::access_path(AccessPath::resource_access_path(address, struct_tag)?);
+        self.state
+            .set_state_value(state_key, Some(resource_bytes.into()));
+        Ok(())
+    }
+
+    /// Adds `amount` APT to the coin store of an account, which must already exist.
+    ///
+    /// The total supply of APT is not updated.
+    pub fn fund_account(&mut self, address: AccountAddress, amount: u64) -> Result<()> {
+        let struct_tag = CoinStoreResource::struct_tag();
+        let state_key = StateKey::access_path(AccessPath::resource_access_path(
+            address,
+            struct_tag.clone(),
+        )?);
+        let coin_store: CoinStoreResource = match self.state_view().get_state_value(&state_key)? {
+            Some(value) => bcs::from_bytes(value.bytes())?,
+            None => return Err(format_err!("Account {} has no coin store", address)),
+        };
+        let coin = coin_store
+            .coin()
+            .checked_add(amount)
+            .ok_or_else(|| format_err!("Balance of account {} overflows", address))?;
+        let coin_store = CoinStoreResource::new(
+            coin,
+            coin_store.frozen(),
+            coin_store.deposit_events().clone(),
+            coin_store.withdraw_events().clone(),
+        );
+        self.set_resource(address, struct_tag, bcs::to_bytes(&coin_store)?)
+    }
+
+    /// Runs a Move session against the fork and keeps its changes, e.g. to call any function
+    /// with any signer.
+    pub fn run_session<F>(&mut self, f: F) -> Result<ChangeSet>
+    where
+        F: FnOnce(&mut SessionExt<StorageAdapter<ForkedStateView>>) -> VMResult<()>,
+    {
+        let change_set = run_session(&self.state_view(), f)?;
+        self.state.apply_write_set(change_set.write_set());
+        Ok(change_set)
+    }
+
+    /// Executes a transaction without checking its signature, keeping its changes unless it
+    /// is discarded. `public_key` must match the authentication key of the sender.
+    pub fn execute_unsigned_transaction(
+        &mut self,
+        raw_txn: RawTransaction,
+        public_key: Ed25519PublicKey,
+    ) -> Result<TransactionOutput> {
+        let txn = SignedTransaction::new(raw_txn, public_key, Ed25519Signature::dummy_signature());
+        let state_view = self.state_view();
+        let (_vm_status, output_ext) = AptosVM::simulate_signed_transaction(&txn, &state_view);
+        let output = output_ext.into_transaction_output(&state_view);
+        if !output.status().is_discarded() {
+            self.state.apply_write_set(output.write_set());
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::{
+        account_state::AccountState,
+        event::{EventHandle, EventKey},
+        state_store::state_value::StateValue,
+        transaction::{Transaction, TransactionInfo, Version},
+    };
+    use aptos_validator_interface::CommittedTransactions;
+
+    /// A chain without any state.
+    struct EmptyValidatorInterface;
+
+    #[async_trait::async_trait]
+    impl AptosValidatorInterface for EmptyValidatorInterface {
+        async fn get_account_state_by_version(
+            &self,
+            _account: AccountAddress,
+            _version: Version,
+        ) -> Result<Option<AccountState>> {
+            Ok(None)
+        }
+
+        async fn get_state_value_by_version(
+            &self,
+            _state_key: &StateKey,
+            _version: Version,
+        ) -> Result<Option<StateValue>> {
+            Ok(None)
+        }
+
+        async fn get_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+        ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+            unimplemented!()
+        }
+
+        async fn get_committed_transactions_with_outputs(
+            &self,
+            _start: Version,
+            _limit: u64,
+        ) -> Result<CommittedTransactions> {
+            unimplemented!()
+        }
+
+        async fn get_latest_version(&self) -> Result<Version> {
+            unimplemented!()
+        }
+
+        async fn get_version_by_account_sequence(
+            &self,
+            _account: AccountAddress,
+            _seq: u64,
+        ) -> Result<Option<Version>> {
+            unimplemented!()
+        }
+    }
+
+    fn set_balance(session: &mut ForkSession, address: AccountAddress, coin: u64) {
+        let coin_store = CoinStoreResource::new(
+            coin,
+            false,
+            EventHandle::new(EventKey::new(2, address), 0),
+            EventHandle::new(EventKey::new(3, address), 0),
+        );
+        session
+            .set_resource(
+                address,
+                CoinStoreResource::struct_tag(),
+                bcs::to_bytes(&coin_store).unwrap(),
+            )
+            .unwrap();
+    }
+
+    fn balance(session: &ForkSession, address: AccountAddress) -> u64 {
+        let state_key = StateKey::access_path(
+            AccessPath::resource_access_path(address, CoinStoreResource::struct_tag()).unwrap(),
+        );
+        let value = session
+            .state_view()
+            .get_state_value(&state_key)
+            .unwrap()
+            .unwrap();
+        bcs::from_bytes::<CoinStoreResource>(value.bytes())
+            .unwrap()
+            .coin()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_fund_account() {
+        let mut session =
+            ForkSession::new(Arc::new(EmptyValidatorInterface), ForkState::new(10)).unwrap();
+        let address = AccountAddress::random();
+
+        // Accounts without a coin store can't be funded
+        assert!(session.fund_account(address, 1).is_err());
+
+        set_balance(&mut session, address, 5);
+        session.fund_account(address, 10).unwrap();
+        assert_eq!(balance(&session, address), 15);
+
+        // Overflows are refused, leaving the balance untouched
+        assert!(session.fund_account(address, u64::MAX - 10).is_err());
+        assert_eq!(balance(&session, address), 15);
+    }
+
+    #[test]
+    fn test_fork_outside_runtime() {
+        let mut session =
+            ForkSession::new(Arc::new(EmptyValidatorInterface), ForkState::new(10)).unwrap();
+        let address = AccountAddress::random();
+
+        set_balance(&mut session, address, 5);
+        assert_eq!(balance(&session, address), 5);
+        // Reads of the chain state are served by the runtime of the session
+        assert!(session.fund_account(AccountAddress::random(), 1).is_err());
+    }
+}
@@ -1,13 +1,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{format_err, Result};
+use crate::{fork::ForkSession, replay_report::ReplayReport};
+use anyhow::{ensure, format_err, Result};
 use aptos_gas::{
     AbstractValueSizeGasParameters, ChangeSetConfigs, NativeGasParameters,
     LATEST_GAS_FEATURE_VERSION,
 };
 use aptos_resource_viewer::{AnnotatedAccountStateBlob, AptosValueAnnotator};
 use aptos_rest_client::Client;
+use aptos_state_view::StateView;
 use aptos_types::{
     account_address::AccountAddress,
     chain_id::ChainId,
@@ -15,16 +17,21 @@ use aptos_types::{
     transaction::{ChangeSet, Transaction, TransactionInfo, TransactionOutput, Version},
 };
 use aptos_validator_interface::{
-    AptosValidatorInterface, DBDebuggerInterface, DebuggerStateView, RestDebuggerInterface,
+    AptosValidatorInterface, CommittedTransactions, DBDebuggerInterface, DebuggerStateView,
+    ForkState, RestDebuggerInterface, StateCache,
 };
 use aptos_vm::{
     data_cache::StorageAdapter,
+    execution_trace::ExecutionTrace,
     move_vm_ext::{MoveVmExt, SessionExt, SessionId},
     AptosVM, VMExecutor,
 };
 use move_binary_format::errors::VMResult;
 use std::{path::Path, sync::Arc};
 
+pub mod fork;
+pub mod replay_report;
+
 pub struct AptosDebugger {
     debugger: Arc<dyn AptosValidatorInterface + Send>,
 }
@@ -38,6 +45,18 @@ impl AptosDebugger {
         Ok(Self::new(Arc::new(RestDebuggerInterface::new(rest_client))))
     }
 
+    /// Like [`AptosDebugger::rest_client`], but caches the state read from the node on disk
+    /// at `cache_path`, where it can be reused by later runs against the same chain.
+    pub fn rest_client_with_cache<P: AsRef<Path>>(
+        rest_client: Client,
+        cache_path: P,
+    ) -> Result<Self> {
+        Ok(Self::new(Arc::new(RestDebuggerInterface::with_cache(
+            rest_client,
+            StateCache::open(cache_path)?,
+        ))))
+    }
+
     pub fn db<P: AsRef<Path> + Clone>(db_root_path: P) -> Result<Self> {
         Ok(Self::new(Arc::new(DBDebuggerInterface::open(
             db_root_path,
@@ -56,28 +75,104 @@ impl AptosDebugger {
 
     pub async fn execute_past_transactions(
         &self,
-        mut begin: Version,
-        mut limit: u64,
+        begin: Version,
+        limit: u64,
     ) -> Result<Vec<TransactionOutput>> {
-        let (mut txns, mut txn_infos) = self
+        let (txns, txn_infos) = self
+            .debugger
+            .get_committed_transactions(begin, limit)
+            .await?;
+
+        let outputs = self.execute_transactions_in_epochs(begin, txns).await?;
+        Self::print_mismatches(&outputs, &txn_infos, begin);
+        Ok(outputs)
+    }
+
+    /// Re-executes past transactions one by one, tracing the execution of the user transactions.
+    pub async fn execute_past_transactions_with_trace(
+        &self,
+        begin: Version,
+        limit: u64,
+    ) -> Result<Vec<(TransactionOutput, Option<ExecutionTrace>)>> {
+        let (txns, txn_infos) = self
             .debugger
             .get_committed_transactions(begin, limit)
             .await?;
 
         let mut ret = vec![];
-        while limit != 0 {
+        for (idx, txn) in txns.into_iter().enumerate() {
+            let version = begin + idx as Version;
+            ret.push(match txn {
+                Transaction::UserTransaction(txn) => {
+                    let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+                    let (_vm_status, output, trace) =
+                        AptosVM::execute_user_transaction_with_trace(txn, &state_view);
+                    (output, trace)
+                },
+                txn => {
+                    let mut outputs = self.execute_transactions_at_version(version, vec![txn])?;
+                    (outputs.pop().expect("One output per transaction"), None)
+                },
+            });
+        }
+        let outputs: Vec<_> = ret.iter().map(|(output, _trace)| output.clone()).collect();
+        Self::print_mismatches(&outputs, &txn_infos, begin);
+        Ok(ret)
+    }
+
+    /// Re-executes past transactions and compares each output with the committed status, gas
+    /// used, write set and events, reporting every difference found.
+    pub async fn diff_past_transactions(&self, begin: Version, limit: u64) -> Result<ReplayReport> {
+        let CommittedTransactions {
+            transactions,
+            infos,
+            write_sets,
+            events,
+        } = self
+            .debugger
+            .get_committed_transactions_with_outputs(begin, limit)
+            .await?;
+        ensure!(
+            infos.len() == write_sets.len() && infos.len() == events.len(),
+            "Got {} transaction infos, {} write sets and {} event lists",
+            infos.len(),
+            write_sets.len(),
+            events.len(),
+        );
+
+        let outputs = self
+            .execute_transactions_in_epochs(begin, transactions)
+            .await?;
+        let mut report = ReplayReport::new(begin);
+        for (idx, output) in outputs.iter().enumerate() {
+            report.add_transaction(
+                begin + idx as Version,
+                output,
+                &infos[idx],
+                &write_sets[idx],
+                &events[idx],
+            );
+        }
+        Ok(report)
+    }
+
+    async fn execute_transactions_in_epochs(
+        &self,
+        mut begin: Version,
+        mut txns: Vec<Transaction>,
+    ) -> Result<Vec<TransactionOutput>> {
+        let mut ret = vec![];
+        while !txns.is_empty() {
             println!(
                 "Starting epoch execution at {:?}, {:?} transactions remaining",
-                begin, limit
+                begin,
+                txns.len()
             );
             let mut epoch_result = self
                 .execute_transactions_by_epoch(begin, txns.clone())
                 .await?;
             begin += epoch_result.len() as u64;
-            limit -= epoch_result.len() as u64;
             txns = txns.split_off(epoch_result.len());
-            let epoch_txn_infos = txn_infos.drain(0..epoch_result.len()).collect::<Vec<_>>();
-            Self::print_mismatches(&epoch_result, &epoch_txn_infos, begin);
 
             ret.append(&mut epoch_result);
         }
@@ -175,28 +270,46 @@ impl AptosDebugger {
         F: FnOnce(&mut SessionExt<StorageAdapter<DebuggerStateView>>) -> VMResult<()>,
     {
         let state_view = DebuggerStateView::new(self.debugger.clone(), version);
-        let state_view_storage = StorageAdapter::new(&state_view);
-        let features = Features::fetch_config(&state_view_storage).unwrap_or_default();
-        let move_vm = MoveVmExt::new(
-            NativeGasParameters::zeros(),
-            AbstractValueSizeGasParameters::zeros(),
-            LATEST_GAS_FEATURE_VERSION,
-            ChainId::test().id(),
-            features,
-            TimedFeatures::enable_all(),
-        )
-        .unwrap();
-        let mut session = move_vm.new_session(&state_view_storage, SessionId::Void);
-        f(&mut session).map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
-        let change_set_ext = session
-            .finish(
-                &mut (),
-                &ChangeSetConfigs::unlimited_at_gas_feature_version(LATEST_GAS_FEATURE_VERSION),
-            )
-            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
-        let (_delta_change_set, change_set) = change_set_ext.into_inner();
-        Ok(change_set)
+        run_session(&state_view, f)
     }
+
+    /// Forks the state right before `version`, to override it and execute new transactions.
+    pub fn fork_at_version(&self, version: Version) -> Result<ForkSession> {
+        ForkSession::new(self.debugger.clone(), ForkState::new(version))
+    }
+
+    /// Resumes a fork saved with [`ForkSession::save`].
+    pub fn resume_fork<P: AsRef<Path>>(&self, path: P) -> Result<ForkSession> {
+        ForkSession::new(self.debugger.clone(), ForkState::load(path)?)
+    }
+}
+
+fn run_session<S, F>(state_view: &S, f: F) -> Result<ChangeSet>
+where
+    S: StateView,
+    F: FnOnce(&mut SessionExt<StorageAdapter<S>>) -> VMResult<()>,
+{
+    let state_view_storage = StorageAdapter::new(state_view);
+    let features = Features::fetch_config(&state_view_storage).unwrap_or_default();
+    let move_vm = MoveVmExt::new(
+        NativeGasParameters::zeros(),
+        AbstractValueSizeGasParameters::zeros(),
+        LATEST_GAS_FEATURE_VERSION,
+        ChainId::test().id(),
+        features,
+        TimedFeatures::enable_all(),
+    )
+    .unwrap();
+    let mut session = move_vm.new_session(&state_view_storage, SessionId::Void);
+    f(&mut session).map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+    let change_set_ext = session
+        .finish(
+            &mut (),
+            &ChangeSetConfigs::unlimited_at_gas_feature_version(LATEST_GAS_FEATURE_VERSION),
+        )
+        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+    let (_delta_change_set, change_set) = change_set_ext.into_inner();
+    Ok(change_set)
 }
 
 fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
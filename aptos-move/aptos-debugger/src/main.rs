@@ -1,9 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
+use anyhow::{ensure, format_err, Result};
 use aptos_debugger::AptosDebugger;
 use aptos_rest_client::Client;
+use aptos_types::account_address::AccountAddress;
 use aptos_vm::AptosVM;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
@@ -12,7 +13,12 @@ use url::Url;
 #[derive(Subcommand)]
 pub enum Target {
     /// Use full node's rest api as query endpoint.
-    Rest { endpoint: String },
+    Rest {
+        endpoint: String,
+        /// Cache the state read from the node in this directory, to reuse it across runs.
+        #[clap(long)]
+        cache_dir: Option<PathBuf>,
+    },
     /// Use a local db instance to serve as query endpoint.
     DB { path: PathBuf },
 }
@@ -24,11 +30,41 @@ pub struct Argument {
     #[clap(long)]
     begin_version: u64,
 
-    #[clap(long)]
-    limit: u64,
+    #[clap(long, required_unless_present = "fork")]
+    limit: Option<u64>,
 
     #[clap(long, default_value = "1")]
     concurrency_level: usize,
+
+    /// Compare the re-executed outputs with the committed ones and write a JSON report of
+    /// the mismatches to this file, instead of printing the outputs.
+    #[clap(long)]
+    diff_report: Option<PathBuf>,
+
+    /// Trace the execution of the user transactions and write the traces as JSON files named
+    /// after the versions of the transactions to this directory, instead of printing the outputs.
+    #[clap(long)]
+    trace_dir: Option<PathBuf>,
+
+    /// Fork the state right before `begin_version` into this file, or resume the fork saved in
+    /// it, apply the given overrides and save it, instead of replaying transactions.
+    #[clap(long)]
+    fork: Option<PathBuf>,
+
+    /// Add APT to an account of the fork, given as `<address>=<amount>`.
+    #[clap(long, requires = "fork", parse(try_from_str = parse_fund_account))]
+    fund_account: Vec<(AccountAddress, u64)>,
+
+    /// Publish or replace a compiled module in the fork, bypassing the compatibility checks.
+    #[clap(long, requires = "fork")]
+    publish_module: Vec<PathBuf>,
+}
+
+fn parse_fund_account(value: &str) -> Result<(AccountAddress, u64)> {
+    let (address, amount) = value
+        .split_once('=')
+        .ok_or_else(|| format_err!("Expected <address>=<amount>, got {}", value))?;
+    Ok((AccountAddress::from_hex_literal(address)?, amount.parse()?))
 }
 
 #[tokio::main]
@@ -38,18 +74,82 @@ async fn main() -> Result<()> {
     AptosVM::set_concurrency_level_once(args.concurrency_level);
 
     let debugger = match args.target {
-        Target::Rest { endpoint } => {
-            AptosDebugger::rest_client(Client::new(Url::parse(&endpoint)?))?
+        Target::Rest {
+            endpoint,
+            cache_dir,
+        } => {
+            let client = Client::new(Url::parse(&endpoint)?);
+            match cache_dir {
+                Some(cache_dir) => {
+                    // Keep the state of different chains apart.
+                    let chain_id = client.get_ledger_information().await?.into_inner().chain_id;
+                    AptosDebugger::rest_client_with_cache(
+                        client,
+                        cache_dir.join(chain_id.to_string()),
+                    )?
+                },
+                None => AptosDebugger::rest_client(client)?,
+            }
         },
         Target::DB { path } => AptosDebugger::db(path)?,
     };
 
-    println!(
-        "{:#?}",
-        debugger
-            .execute_past_transactions(args.begin_version, args.limit)
-            .await?
-    );
+    if let Some(path) = args.fork {
+        let mut session = if path.exists() {
+            let session = debugger.resume_fork(&path)?;
+            ensure!(
+                session.state().version() == args.begin_version,
+                "The fork saved in {} is at version {}",
+                path.display(),
+                session.state().version()
+            );
+            session
+        } else {
+            debugger.fork_at_version(args.begin_version)?
+        };
+        for module in args.publish_module {
+            session.set_module(std::fs::read(module)?)?;
+        }
+        for (address, amount) in args.fund_account {
+            session.fund_account(address, amount)?;
+        }
+        session.save(&path)?;
+        return Ok(());
+    }
+    let limit = args.limit.expect("Required unless forking");
+
+    if let Some(path) = args.diff_report {
+        let report = debugger
+            .diff_past_transactions(args.begin_version, limit)
+            .await?;
+        println!(
+            "Found {} mismatching transactions out of {}.",
+            report.mismatches.len(),
+            report.num_transactions
+        );
+        std::fs::write(path, serde_json::to_vec_pretty(&report)?)?;
+    } else if let Some(trace_dir) = args.trace_dir {
+        std::fs::create_dir_all(&trace_dir)?;
+        let outputs = debugger
+            .execute_past_transactions_with_trace(args.begin_version, limit)
+            .await?;
+        for (idx, (_output, trace)) in outputs.into_iter().enumerate() {
+            if let Some(trace) = trace {
+                let version = args.begin_version + idx as u64;
+                std::fs::write(
+                    trace_dir.join(format!("{}.json", version)),
+                    serde_json::to_vec_pretty(&trace)?,
+                )?;
+            }
+        }
+    } else {
+        println!(
+            "{:#?}",
+            debugger
+                .execute_past_transactions(args.begin_version, limit)
+                .await?
+        );
+    }
 
     Ok(())
 }
@@ -0,0 +1,262 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::{
+    contract_event::ContractEvent,
+    state_store::state_key::StateKey,
+    transaction::{TransactionInfo, TransactionOutput, TransactionStatus, Version},
+    write_set::{WriteOp, WriteSet},
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Mismatches between re-executed transaction outputs and the committed ones.
+#[derive(Debug, Serialize)]
+pub struct ReplayReport {
+    pub begin_version: Version,
+    pub num_transactions: u64,
+    pub mismatches: Vec<TransactionMismatch>,
+}
+
+/// All the differences found for a single transaction. Fields are `None` or empty when the
+/// re-executed output matches the committed one.
+#[derive(Debug, Serialize)]
+pub struct TransactionMismatch {
+    pub version: Version,
+    pub status: Option<ValueMismatch<String>>,
+    pub gas_used: Option<ValueMismatch<u64>>,
+    pub write_set: Vec<WriteOpMismatch>,
+    pub events: Vec<EventMismatch>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValueMismatch<T> {
+    pub expected: T,
+    pub actual: T,
+}
+
+/// A state key written differently, `None` meaning the key wasn't written at all.
+#[derive(Debug, Serialize)]
+pub struct WriteOpMismatch {
+    pub state_key: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+/// An event emitted differently at `index`, `None` meaning no event was emitted there.
+#[derive(Debug, Serialize)]
+pub struct EventMismatch {
+    pub index: usize,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl ReplayReport {
+    pub fn new(begin_version: Version) -> Self {
+        Self {
+            begin_version,
+            num_transactions: 0,
+            mismatches: vec![],
+        }
+    }
+
+    /// Compares the output of the transaction at `version` with what was committed, recording
+    /// a mismatch if they differ.
+    pub fn add_transaction(
+        &mut self,
+        version: Version,
+        output: &TransactionOutput,
+        expected_txn_info: &TransactionInfo,
+        expected_write_set: &WriteSet,
+        expected_events: &[ContractEvent],
+    ) {
+        self.num_transactions += 1;
+
+        let expected_status: TransactionStatus = expected_txn_info.status().clone().into();
+        let mismatch = TransactionMismatch {
+            version,
+            status: diff_value(
+                format!("{:?}", expected_status),
+                format!("{:?}", output.status()),
+            ),
+            gas_used: diff_value(expected_txn_info.gas_used(), output.gas_used()),
+            write_set: diff_write_sets(expected_write_set, output.write_set()),
+            events: diff_events(expected_events, output.events()),
+        };
+        if !mismatch.is_empty() {
+            self.mismatches.push(mismatch);
+        }
+    }
+}
+
+impl TransactionMismatch {
+    fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.gas_used.is_none()
+            && self.write_set.is_empty()
+            && self.events.is_empty()
+    }
+}
+
+fn diff_value<T: PartialEq>(expected: T, actual: T) -> Option<ValueMismatch<T>> {
+    if expected == actual {
+        None
+    } else {
+        Some(ValueMismatch { expected, actual })
+    }
+}
+
+fn diff_write_sets(expected: &WriteSet, actual: &WriteSet) -> Vec<WriteOpMismatch> {
+    let mut write_ops: BTreeMap<&StateKey, (Option<&WriteOp>, Option<&WriteOp>)> = BTreeMap::new();
+    for (state_key, write_op) in expected.iter() {
+        write_ops.entry(state_key).or_default().0 = Some(write_op);
+    }
+    for (state_key, write_op) in actual.iter() {
+        write_ops.entry(state_key).or_default().1 = Some(write_op);
+    }
+
+    write_ops
+        .into_iter()
+        .filter(|(_, (expected, actual))| expected != actual)
+        .map(|(state_key, (expected, actual))| WriteOpMismatch {
+            state_key: format!("{:?}", state_key),
+            expected: expected.map(|write_op| format!("{:?}", write_op)),
+            actual: actual.map(|write_op| format!("{:?}", write_op)),
+        })
+        .collect()
+}
+
+fn diff_events(expected: &[ContractEvent], actual: &[ContractEvent]) -> Vec<EventMismatch> {
+    (0..std::cmp::max(expected.len(), actual.len()))
+        .filter_map(|index| {
+            let (expected, actual) = (expected.get(index), actual.get(index));
+            (expected != actual).then(|| EventMismatch {
+                index,
+                expected: expected.map(|event| format!("{:?}", event)),
+                actual: actual.map(|event| format!("{:?}", event)),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::{
+        account_address::AccountAddress, event::EventKey, transaction::ExecutionStatus,
+        write_set::WriteSetMut,
+    };
+    use move_core_types::language_storage::TypeTag;
+
+    fn write_set(write_ops: Vec<(&str, WriteOp)>) -> WriteSet {
+        WriteSetMut::new(
+            write_ops
+                .into_iter()
+                .map(|(key, write_op)| (raw_key(key), write_op))
+                .collect(),
+        )
+        .freeze()
+        .unwrap()
+    }
+
+    fn raw_key(key: &str) -> StateKey {
+        StateKey::raw(key.as_bytes().to_vec())
+    }
+
+    fn event(sequence_number: u64, data: u8) -> ContractEvent {
+        ContractEvent::new(
+            EventKey::new(0, AccountAddress::ONE),
+            sequence_number,
+            TypeTag::U8,
+            vec![data],
+        )
+    }
+
+    #[test]
+    fn test_diff_write_sets() {
+        let expected = write_set(vec![
+            ("changed", WriteOp::Modification(vec![1])),
+            ("missing", WriteOp::Deletion),
+            ("same", WriteOp::Modification(vec![2])),
+        ]);
+        let actual = write_set(vec![
+            ("changed", WriteOp::Modification(vec![3])),
+            ("extra", WriteOp::Creation(vec![4])),
+            ("same", WriteOp::Modification(vec![2])),
+        ]);
+
+        let mismatches = diff_write_sets(&expected, &actual);
+        let keys: Vec<_> = mismatches
+            .iter()
+            .map(|mismatch| {
+                (
+                    mismatch.expected.is_some(),
+                    mismatch.actual.is_some(),
+                    mismatch.state_key.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(keys, vec![
+            (true, true, format!("{:?}", raw_key("changed"))),
+            (false, true, format!("{:?}", raw_key("extra"))),
+            (true, false, format!("{:?}", raw_key("missing"))),
+        ]);
+        assert!(diff_write_sets(&expected, &expected).is_empty());
+    }
+
+    #[test]
+    fn test_diff_events() {
+        let expected = vec![event(0, 1), event(1, 2), event(2, 3)];
+
+        // Changed value
+        let mismatches = diff_events(&expected, &[event(0, 1), event(1, 5), event(2, 3)]);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].index, 1);
+        assert!(mismatches[0].expected.is_some() && mismatches[0].actual.is_some());
+
+        // Missing events
+        let mismatches = diff_events(&expected, &[event(0, 1)]);
+        let indices: Vec<_> = mismatches.iter().map(|mismatch| mismatch.index).collect();
+        assert_eq!(indices, vec![1, 2]);
+        assert!(mismatches.iter().all(|mismatch| mismatch.actual.is_none()));
+
+        // Extra event
+        let mut actual = expected.clone();
+        actual.push(event(3, 4));
+        let mismatches = diff_events(&expected, &actual);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].index, 3);
+        assert!(mismatches[0].expected.is_none());
+
+        assert!(diff_events(&expected, &expected).is_empty());
+    }
+
+    #[test]
+    fn test_add_transaction() {
+        let write_set = write_set(vec![("key", WriteOp::Modification(vec![1]))]);
+        let events = vec![event(0, 1)];
+        let txn_info = TransactionInfo::new_placeholder(10, None, ExecutionStatus::Success);
+        let output = |gas_used| {
+            TransactionOutput::new(
+                write_set.clone(),
+                events.clone(),
+                gas_used,
+                TransactionStatus::Keep(ExecutionStatus::Success),
+            )
+        };
+
+        let mut report = ReplayReport::new(5);
+        report.add_transaction(5, &output(10), &txn_info, &write_set, &events);
+        assert!(report.mismatches.is_empty());
+
+        report.add_transaction(6, &output(11), &txn_info, &write_set, &events);
+        assert_eq!(report.num_transactions, 2);
+        assert_eq!(report.mismatches.len(), 1);
+        let mismatch = &report.mismatches[0];
+        assert_eq!(mismatch.version, 6);
+        assert!(mismatch.status.is_none());
+        assert!(mismatch.write_set.is_empty() && mismatch.events.is_empty());
+        let gas_used = mismatch.gas_used.as_ref().unwrap();
+        assert_eq!((gas_used.expected, gas_used.actual), (10, 11));
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This crate profiles the gas usage of transactions.
+//!
+//! `GasProfiler` wraps the gas meter used by the Aptos VM and attributes the gas charged to the
+//! frames of the Move call stack, by instruction class, native function and storage access, as
+//! well as the transaction level costs, i.e., the intrinsic gas, the IO gas of the write set and
+//! the storage fees. The resulting `TransactionGasLog` can be rendered as folded stacks, from
+//! which flame graphs can be generated, and summarized in JSON.
+
+mod log;
+mod profiler;
+mod report;
+
+pub use log::{render_state_key, CallFrame, StorageFees, TransactionGasLog};
+pub use profiler::GasProfiler;
+pub use report::{FunctionCost, GasSummary, NamedCost, StorageFeeSummary};
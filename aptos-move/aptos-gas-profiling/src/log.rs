@@ -0,0 +1,113 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_gas::{Fee, InternalGas};
+use aptos_types::{
+    access_path::Path,
+    state_store::state_key::{StateKey, StateKeyInner},
+};
+use std::collections::BTreeMap;
+
+/// A frame of the Move call stack, along with the gas charged while it was executing.
+#[derive(Clone, Debug)]
+pub struct CallFrame {
+    /// Name of the function, e.g., "0x1::coin::transfer", or of the payload for the outermost
+    /// frame, e.g., "script".
+    pub name: String,
+    /// Execution gas charged by the frame itself, by instruction class, e.g., "call", "native".
+    pub execution: BTreeMap<String, InternalGas>,
+    /// IO gas charged by the frame itself, by kind of access, e.g., "load_resource".
+    pub io: BTreeMap<String, InternalGas>,
+    /// Frames of the functions called, in the order of the calls.
+    pub callees: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            execution: BTreeMap::new(),
+            io: BTreeMap::new(),
+            callees: vec![],
+        }
+    }
+
+    /// Gas charged by the frame itself, excluding its callees.
+    pub fn self_cost(&self) -> InternalGas {
+        self.execution
+            .values()
+            .chain(self.io.values())
+            .fold(InternalGas::zero(), |total, cost| total + *cost)
+    }
+
+    /// Gas charged by the frame and all its callees.
+    pub fn total_cost(&self) -> InternalGas {
+        self.callees.iter().fold(self.self_cost(), |total, callee| {
+            total + callee.total_cost()
+        })
+    }
+}
+
+/// Storage fees of a transaction, in Octas as they are defined, before being converted into gas.
+#[derive(Clone, Debug, Default)]
+pub struct StorageFees {
+    /// Fee for each state item written.
+    pub writes: Vec<(StateKey, Fee)>,
+    /// Fee for the events emitted.
+    pub events: Fee,
+    /// Fee for the size of the transaction.
+    pub txn: Fee,
+}
+
+/// Breakdown of the gas charged for a transaction.
+#[derive(Clone, Debug)]
+pub struct TransactionGasLog {
+    /// Number of internal gas units per gas unit.
+    pub gas_unit_scaling_factor: u64,
+    /// Intrinsic gas charged for the size of the transaction.
+    pub intrinsic_cost: InternalGas,
+    /// Execution and IO gas charged while running the payload, by call frame.
+    pub call_graph: CallFrame,
+    /// IO gas charged for each state item written.
+    pub write_set_io: Vec<(StateKey, InternalGas)>,
+    /// Storage fees, as charged in gas.
+    pub storage_fee_cost: InternalGas,
+    /// Storage fees, as defined in Octas.
+    pub storage_fees: StorageFees,
+}
+
+impl TransactionGasLog {
+    /// Total gas charged for the transaction.
+    pub fn total_cost(&self) -> InternalGas {
+        self.write_set_io.iter().fold(
+            self.intrinsic_cost + self.call_graph.total_cost() + self.storage_fee_cost,
+            |total, (_key, cost)| total + *cost,
+        )
+    }
+}
+
+/// Renders a state key in a human readable form, e.g.,
+/// "resource 0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin> at 0x1".
+pub fn render_state_key(key: &StateKey) -> String {
+    match key.inner() {
+        StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+            Path::Code(module_id) => format!("module {}", module_id.short_str_lossless()),
+            Path::Resource(struct_tag) => format!(
+                "resource {} at {}",
+                struct_tag,
+                access_path.address.short_str_lossless()
+            ),
+            Path::ResourceGroup(struct_tag) => format!(
+                "resource group {} at {}",
+                struct_tag,
+                access_path.address.short_str_lossless()
+            ),
+        },
+        StateKeyInner::TableItem { handle, key } => format!(
+            "table item 0x{} of table {}",
+            hex::encode(key),
+            handle.0.short_str_lossless()
+        ),
+        StateKeyInner::Raw(bytes) => format!("raw 0x{}", hex::encode(bytes)),
+    }
+}
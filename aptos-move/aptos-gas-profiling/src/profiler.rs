@@ -0,0 +1,513 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{CallFrame, StorageFees, TransactionGasLog};
+use aptos_gas::{
+    AptosGasMeter, AptosGasParameters, FeePerGasUnit, InternalGas, NumArgs, NumBytes,
+    StorageGasParameters,
+};
+use aptos_types::{
+    contract_event::ContractEvent, state_store::state_key::StateKey, write_set::WriteOp,
+};
+use move_binary_format::errors::{PartialVMResult, VMResult};
+use move_core_types::{
+    identifier::IdentStr,
+    language_storage::{ModuleId, TypeTag},
+};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+use std::{collections::BTreeMap, iter};
+
+/// A gas meter that wraps another one and records where the gas goes: the gas charged while
+/// executing Move code is attributed to the frames of the call stack, while the transaction level
+/// costs are broken down by state item where applicable.
+///
+/// The underlying gas meter does all the charging, so the profiler doesn't change the outcome of
+/// the transaction.
+pub struct GasProfiler<G> {
+    base: G,
+
+    intrinsic_cost: InternalGas,
+    /// The call stack, the bottom of which is the outermost frame which is never popped.
+    frames: Vec<CallFrame>,
+    write_set_io: Vec<(StateKey, InternalGas)>,
+    storage_fee_cost: InternalGas,
+    storage_fees: StorageFees,
+}
+
+impl<G> GasProfiler<G> {
+    /// Creates a profiler whose outermost frame is named after the payload, e.g., "script".
+    pub fn new(base: G, name: String) -> Self {
+        Self {
+            base,
+            intrinsic_cost: InternalGas::zero(),
+            frames: vec![CallFrame::new(name)],
+            write_set_io: vec![],
+            storage_fee_cost: InternalGas::zero(),
+            storage_fees: StorageFees::default(),
+        }
+    }
+
+    pub fn new_script(base: G) -> Self {
+        Self::new(base, "script".to_string())
+    }
+
+    pub fn new_function(
+        base: G,
+        module_id: &ModuleId,
+        func_name: &IdentStr,
+        ty_args: &[TypeTag],
+    ) -> Self {
+        let ty_args = if ty_args.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<{}>",
+                ty_args
+                    .iter()
+                    .map(|ty_arg| ty_arg.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        Self::new(
+            base,
+            function_name(module_id, func_name.as_str()) + &ty_args,
+        )
+    }
+
+    fn current_frame(&mut self) -> &mut CallFrame {
+        self.frames
+            .last_mut()
+            .expect("The outermost frame is never popped.")
+    }
+
+    fn pop_frame(&mut self) {
+        if self.frames.len() > 1 {
+            let frame = self.frames.pop().expect("Checked above.");
+            self.current_frame().callees.push(frame);
+        }
+    }
+}
+
+fn function_name(module_id: &ModuleId, func_name: &str) -> String {
+    format!("{}::{}", module_id.short_str_lossless(), func_name)
+}
+
+fn record(costs: &mut BTreeMap<String, InternalGas>, name: &str, cost: InternalGas) {
+    if cost.is_zero() {
+        return;
+    }
+    *costs
+        .entry(name.to_string())
+        .or_insert_with(InternalGas::zero) += cost;
+}
+
+impl<G: GasMeter> GasProfiler<G> {
+    /// Runs a charge against the underlying gas meter, returning the result and the gas charged.
+    fn measure<R>(&mut self, charge: impl FnOnce(&mut G) -> R) -> (R, InternalGas) {
+        let balance_before = self.base.balance_internal();
+        let res = charge(&mut self.base);
+        let cost = balance_before
+            .checked_sub(self.base.balance_internal())
+            .unwrap_or_else(InternalGas::zero);
+        (res, cost)
+    }
+
+    fn charge_execution(
+        &mut self,
+        instr: &str,
+        charge: impl FnOnce(&mut G) -> PartialVMResult<()>,
+    ) -> PartialVMResult<()> {
+        let (res, cost) = self.measure(charge);
+        record(&mut self.current_frame().execution, instr, cost);
+        res
+    }
+
+    fn charge_call_and_push_frame(
+        &mut self,
+        instr: &str,
+        name: String,
+        charge: impl FnOnce(&mut G) -> PartialVMResult<()>,
+    ) -> PartialVMResult<()> {
+        // The call instruction itself is charged to the caller.
+        self.charge_execution(instr, charge)?;
+        self.frames.push(CallFrame::new(name));
+        Ok(())
+    }
+}
+
+impl<G: GasMeter> GasMeter for GasProfiler<G> {
+    fn balance_internal(&self) -> InternalGas {
+        self.base.balance_internal()
+    }
+
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        self.charge_execution(&format!("{:?}", instr), |base| {
+            base.charge_simple_instr(instr)
+        })
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge_execution("native", |base| {
+            base.charge_native_function_before_execution(ty_args, args)
+        })
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        let res = self.charge_execution("native", |base| {
+            base.charge_native_function(amount, ret_vals)
+        });
+        // Native functions are called like other functions, but their frames are not dropped
+        // as they don't have any, so this is where the frame ends.
+        self.pop_frame();
+        res
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        let (res, cost) = self.measure(|base| base.charge_load_resource(loaded));
+        record(&mut self.current_frame().io, "load_resource", cost);
+        res
+    }
+
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.charge_execution("Pop", |base| base.charge_pop(popped_val))
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.charge_call_and_push_frame("Call", function_name(module_id, func_name), |base| {
+            base.charge_call(module_id, func_name, args, num_locals)
+        })
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.charge_call_and_push_frame(
+            "CallGeneric",
+            function_name(module_id, func_name),
+            |base| base.charge_call_generic(module_id, func_name, ty_args, args, num_locals),
+        )
+    }
+
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.charge_execution("LdConst", |base| base.charge_ld_const(size))
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge_execution("LdConst", |base| {
+            base.charge_ld_const_after_deserialization(val)
+        })
+    }
+
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge_execution("CopyLoc", |base| base.charge_copy_loc(val))
+    }
+
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge_execution("MoveLoc", |base| base.charge_move_loc(val))
+    }
+
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge_execution("StLoc", |base| base.charge_store_loc(val))
+    }
+
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let instr = if is_generic { "PackGeneric" } else { "Pack" };
+        self.charge_execution(instr, |base| base.charge_pack(is_generic, args))
+    }
+
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let instr = if is_generic {
+            "UnpackGeneric"
+        } else {
+            "Unpack"
+        };
+        self.charge_execution(instr, |base| base.charge_unpack(is_generic, args))
+    }
+
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge_execution("ReadRef", |base| base.charge_read_ref(val))
+    }
+
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge_execution("WriteRef", |base| base.charge_write_ref(new_val, old_val))
+    }
+
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.charge_execution("Eq", |base| base.charge_eq(lhs, rhs))
+    }
+
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.charge_execution("Neq", |base| base.charge_neq(lhs, rhs))
+    }
+
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let instr = match (is_mut, is_generic) {
+            (false, false) => "ImmBorrowGlobal",
+            (false, true) => "ImmBorrowGlobalGeneric",
+            (true, false) => "MutBorrowGlobal",
+            (true, true) => "MutBorrowGlobalGeneric",
+        };
+        self.charge_execution(instr, |base| {
+            base.charge_borrow_global(is_mut, is_generic, ty, is_success)
+        })
+    }
+
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        let instr = if is_generic {
+            "ExistsGeneric"
+        } else {
+            "Exists"
+        };
+        self.charge_execution(instr, |base| base.charge_exists(is_generic, ty, exists))
+    }
+
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let instr = if is_generic {
+            "MoveFromGeneric"
+        } else {
+            "MoveFrom"
+        };
+        self.charge_execution(instr, |base| base.charge_move_from(is_generic, ty, val))
+    }
+
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let instr = if is_generic {
+            "MoveToGeneric"
+        } else {
+            "MoveTo"
+        };
+        self.charge_execution(instr, |base| {
+            base.charge_move_to(is_generic, ty, val, is_success)
+        })
+    }
+
+    fn charge_vec_pack<'a>(
+        &mut self,
+        ty: impl TypeView + 'a,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge_execution("VecPack", |base| base.charge_vec_pack(ty, args))
+    }
+
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge_execution("VecUnpack", |base| {
+            base.charge_vec_unpack(ty, expect_num_elements, elems)
+        })
+    }
+
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.charge_execution("VecLen", |base| base.charge_vec_len(ty))
+    }
+
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let instr = if is_mut {
+            "VecMutBorrow"
+        } else {
+            "VecImmBorrow"
+        };
+        self.charge_execution(instr, |base| base.charge_vec_borrow(is_mut, ty, is_success))
+    }
+
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge_execution("VecPushBack", |base| base.charge_vec_push_back(ty, val))
+    }
+
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge_execution("VecPopBack", |base| base.charge_vec_pop_back(ty, val))
+    }
+
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.charge_execution("VecSwap", |base| base.charge_vec_swap(ty))
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let res = self.base.charge_drop_frame(locals);
+        // The outermost frame of the interpreter, e.g., the entry function, is dropped as well,
+        // but it stays on the stack as the outermost frame of the profiler.
+        self.pop_frame();
+        res
+    }
+}
+
+impl<G: AptosGasMeter> GasProfiler<G> {
+    /// Returns the gas log, unwinding the frames left on the call stack if the execution aborted.
+    pub fn finish(mut self) -> TransactionGasLog {
+        while self.frames.len() > 1 {
+            self.pop_frame();
+        }
+
+        TransactionGasLog {
+            gas_unit_scaling_factor: self.base.gas_params().txn.gas_unit_scaling_factor.into(),
+            intrinsic_cost: self.intrinsic_cost,
+            call_graph: self
+                .frames
+                .pop()
+                .expect("The outermost frame is never popped."),
+            write_set_io: self.write_set_io,
+            storage_fee_cost: self.storage_fee_cost,
+            storage_fees: self.storage_fees,
+        }
+    }
+}
+
+impl<G: AptosGasMeter> AptosGasMeter for GasProfiler<G> {
+    fn feature_version(&self) -> u64 {
+        self.base.feature_version()
+    }
+
+    fn gas_params(&self) -> &AptosGasParameters {
+        self.base.gas_params()
+    }
+
+    fn storage_gas_params(&self) -> &StorageGasParameters {
+        self.base.storage_gas_params()
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
+        let (res, cost) = self.measure(|base| base.charge_intrinsic_gas_for_transaction(txn_size));
+        self.intrinsic_cost += cost;
+        res
+    }
+
+    fn charge_write_set_gas_for_io<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+    ) -> VMResult<()> {
+        let ops: Vec<_> = ops.into_iter().collect();
+        let pricing = &self.base.storage_gas_params().pricing;
+        let costs: Vec<_> = ops
+            .iter()
+            .map(|(key, op)| {
+                (
+                    (*key).clone(),
+                    pricing.calculate_write_set_gas(iter::once((*key, *op))),
+                )
+            })
+            .collect();
+
+        let (res, _cost) =
+            self.measure(|base| base.charge_write_set_gas_for_io(ops.iter().copied()));
+        self.write_set_io.extend(costs);
+        res
+    }
+
+    fn charge_storage_fee<'a>(
+        &mut self,
+        write_ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+        events: impl IntoIterator<Item = &'a ContractEvent>,
+        txn_size: NumBytes,
+        gas_unit_price: FeePerGasUnit,
+    ) -> VMResult<()> {
+        let write_ops: Vec<_> = write_ops.into_iter().collect();
+        let events: Vec<_> = events.into_iter().collect();
+
+        let (res, cost) = self.measure(|base| {
+            base.charge_storage_fee(
+                write_ops.iter().copied(),
+                events.iter().copied(),
+                txn_size,
+                gas_unit_price,
+            )
+        });
+        // Storage fees don't apply to all transactions, e.g., not before gas feature version 7.
+        if !cost.is_zero() {
+            let txn_params = &self.base.gas_params().txn;
+            self.storage_fees
+                .writes
+                .extend(write_ops.iter().map(|(key, op)| {
+                    (
+                        (*key).clone(),
+                        txn_params.calculate_write_set_storage_fee(iter::once((*key, *op))),
+                    )
+                }));
+            self.storage_fees.events += txn_params.calculate_event_storage_fee(events);
+            self.storage_fees.txn += txn_params.calculate_transaction_storage_fee(txn_size);
+            self.storage_fee_cost += cost;
+        }
+        res
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{render_state_key, CallFrame, TransactionGasLog};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Gas charged by all the calls to a function.
+#[derive(Debug, Serialize)]
+pub struct FunctionCost {
+    pub name: String,
+    pub num_calls: u64,
+    /// Gas charged by the function itself, excluding its callees.
+    pub self_cost: u64,
+    /// Gas charged by the function and its callees. Recursive calls are counted once per call.
+    pub total_cost: u64,
+}
+
+/// Cost of an instruction class, a kind of access or a state item.
+#[derive(Debug, Serialize)]
+pub struct NamedCost {
+    pub name: String,
+    pub cost: u64,
+}
+
+/// Storage fees in Octas.
+#[derive(Debug, Serialize)]
+pub struct StorageFeeSummary {
+    pub writes: Vec<NamedCost>,
+    pub events: u64,
+    pub txn: u64,
+}
+
+/// Summary of a `TransactionGasLog`, with all the costs in internal gas units unless specified
+/// otherwise.
+#[derive(Debug, Serialize)]
+pub struct GasSummary {
+    pub gas_unit_scaling_factor: u64,
+    pub total_cost: u64,
+    /// Total cost in gas units, rounded up.
+    pub total_cost_in_gas_units: u64,
+    pub intrinsic_cost: u64,
+    pub execution_cost: u64,
+    pub io_cost: u64,
+    pub storage_fee_cost: u64,
+    /// Functions, most expensive (by self cost) first.
+    pub functions: Vec<FunctionCost>,
+    /// Execution gas by instruction class, most expensive first.
+    pub instructions: Vec<NamedCost>,
+    /// IO gas of reading state items by kind of access and of writing them by state item.
+    pub io: Vec<NamedCost>,
+    pub storage_fees: StorageFeeSummary,
+}
+
+/// Name of the outermost frame of the folded stacks.
+const TRANSACTION_FRAME: &str = "transaction";
+
+impl TransactionGasLog {
+    /// Renders the log in the folded stacks format, i.e., one "frame;frame;...;frame cost" line
+    /// per distinct stack, which flame graph tools like `inferno-flamegraph` take as input.
+    pub fn to_folded_stacks(&self) -> String {
+        let mut stacks = BTreeMap::new();
+        let mut add = |stack: String, cost: u64| {
+            if cost > 0 {
+                *stacks.entry(stack).or_insert(0) += cost;
+            }
+        };
+
+        add(
+            format!("{};intrinsic", TRANSACTION_FRAME),
+            self.intrinsic_cost.into(),
+        );
+        fold_frame(&self.call_graph, TRANSACTION_FRAME, &mut add);
+        for (key, cost) in &self.write_set_io {
+            add(
+                format!(
+                    "{};write_set_io;{}",
+                    TRANSACTION_FRAME,
+                    sanitize(&render_state_key(key))
+                ),
+                (*cost).into(),
+            );
+        }
+        add(
+            format!("{};storage_fee", TRANSACTION_FRAME),
+            self.storage_fee_cost.into(),
+        );
+
+        stacks
+            .into_iter()
+            .map(|(stack, cost)| format!("{} {}\n", stack, cost))
+            .collect()
+    }
+
+    pub fn to_summary(&self) -> GasSummary {
+        let mut functions = BTreeMap::new();
+        let mut instructions = BTreeMap::new();
+        let mut io = BTreeMap::new();
+        summarize_frame(&self.call_graph, &mut functions, &mut instructions, &mut io);
+        let execution_cost = instructions.values().sum();
+        let read_cost: u64 = io.values().sum();
+        for (key, cost) in &self.write_set_io {
+            *io.entry(format!("write {}", render_state_key(key)))
+                .or_insert(0) += u64::from(*cost);
+        }
+
+        let mut functions: Vec<_> = functions.into_values().collect();
+        functions.sort_by(|a: &FunctionCost, b| b.self_cost.cmp(&a.self_cost));
+        let sorted = |costs: BTreeMap<String, u64>| {
+            let mut costs: Vec<_> = costs
+                .into_iter()
+                .map(|(name, cost)| NamedCost { name, cost })
+                .collect();
+            costs.sort_by(|a, b| b.cost.cmp(&a.cost));
+            costs
+        };
+
+        let total_cost: u64 = self.total_cost().into();
+        GasSummary {
+            gas_unit_scaling_factor: self.gas_unit_scaling_factor,
+            total_cost,
+            total_cost_in_gas_units: div_ceil(total_cost, self.gas_unit_scaling_factor),
+            intrinsic_cost: self.intrinsic_cost.into(),
+            execution_cost,
+            io_cost: read_cost
+                + self
+                    .write_set_io
+                    .iter()
+                    .map(|(_key, cost)| u64::from(*cost))
+                    .sum::<u64>(),
+            storage_fee_cost: self.storage_fee_cost.into(),
+            functions,
+            instructions: sorted(instructions),
+            io: sorted(io),
+            storage_fees: StorageFeeSummary {
+                writes: self
+                    .storage_fees
+                    .writes
+                    .iter()
+                    .map(|(key, fee)| NamedCost {
+                        name: render_state_key(key),
+                        cost: (*fee).into(),
+                    })
+                    .collect(),
+                events: self.storage_fees.events.into(),
+                txn: self.storage_fees.txn.into(),
+            },
+        }
+    }
+}
+
+fn div_ceil(n: u64, d: u64) -> u64 {
+    if d == 0 {
+        return n;
+    }
+    n / d + u64::from(n % d != 0)
+}
+
+/// Frame names end up in lines separated by ';', so they can't contain any.
+fn sanitize(name: &str) -> String {
+    name.replace(';', ",")
+}
+
+fn fold_frame(frame: &CallFrame, parent_stack: &str, add: &mut impl FnMut(String, u64)) {
+    let stack = format!("{};{}", parent_stack, sanitize(&frame.name));
+    for (instr, cost) in frame.execution.iter().chain(frame.io.iter()) {
+        add(format!("{};{}", stack, instr), (*cost).into());
+    }
+    for callee in &frame.callees {
+        fold_frame(callee, &stack, add);
+    }
+}
+
+fn summarize_frame(
+    frame: &CallFrame,
+    functions: &mut BTreeMap<String, FunctionCost>,
+    instructions: &mut BTreeMap<String, u64>,
+    io: &mut BTreeMap<String, u64>,
+) {
+    let function = functions
+        .entry(frame.name.clone())
+        .or_insert_with(|| FunctionCost {
+            name: frame.name.clone(),
+            num_calls: 0,
+            self_cost: 0,
+            total_cost: 0,
+        });
+    function.num_calls += 1;
+    function.self_cost += u64::from(frame.self_cost());
+    function.total_cost += u64::from(frame.total_cost());
+
+    for (instr, cost) in &frame.execution {
+        *instructions.entry(instr.clone()).or_insert(0) += u64::from(*cost);
+    }
+    for (kind, cost) in &frame.io {
+        *io.entry(kind.clone()).or_insert(0) += u64::from(*cost);
+    }
+    for callee in &frame.callees {
+        summarize_frame(callee, functions, instructions, io);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::StorageFees;
+    use aptos_gas::InternalGas;
+
+    fn frame(
+        name: &str,
+        execution: &[(&str, u64)],
+        io: &[(&str, u64)],
+        callees: Vec<CallFrame>,
+    ) -> CallFrame {
+        let costs = |costs: &[(&str, u64)]| {
+            costs
+                .iter()
+                .map(|(name, cost)| (name.to_string(), InternalGas::new(*cost)))
+                .collect()
+        };
+        CallFrame {
+            name: name.to_string(),
+            execution: costs(execution),
+            io: costs(io),
+            callees,
+        }
+    }
+
+    #[test]
+    fn test_folded_stacks_and_summary() {
+        let log = TransactionGasLog {
+            gas_unit_scaling_factor: 100,
+            intrinsic_cost: InternalGas::new(1000),
+            call_graph: frame(
+                "0x1::m::f",
+                &[("Call", 10), ("LdU64", 5)],
+                &[],
+                vec![
+                    frame("0x1::m::g", &[("Add", 3)], &[("load_resource", 50)], vec![]),
+                    frame(
+                        "0x1::m::g",
+                        &[("Add", 3)],
+                        &[],
+                        vec![frame("0x1::vector::length", &[("native", 7)], &[], vec![])],
+                    ),
+                ],
+            ),
+            write_set_io: vec![],
+            storage_fee_cost: InternalGas::new(2000),
+            storage_fees: StorageFees::default(),
+        };
+
+        assert_eq!(
+            log.to_folded_stacks(),
+            "transaction;0x1::m::f;0x1::m::g;0x1::vector::length;native 7\n\
+             transaction;0x1::m::f;0x1::m::g;Add 6\n\
+             transaction;0x1::m::f;0x1::m::g;load_resource 50\n\
+             transaction;0x1::m::f;Call 10\n\
+             transaction;0x1::m::f;LdU64 5\n\
+             transaction;intrinsic 1000\n\
+             transaction;storage_fee 2000\n"
+        );
+
+        let summary = log.to_summary();
+        assert_eq!(summary.total_cost, 3078);
+        assert_eq!(summary.total_cost_in_gas_units, 31);
+        assert_eq!(summary.execution_cost, 28);
+        assert_eq!(summary.io_cost, 50);
+        assert_eq!(summary.functions[0].name, "0x1::m::g");
+        assert_eq!(summary.functions[0].num_calls, 2);
+        assert_eq!(summary.functions[0].self_cost, 56);
+        assert_eq!(summary.functions[0].total_cost, 63);
+        assert_eq!(summary.instructions[0].name, "Call");
+    }
+}
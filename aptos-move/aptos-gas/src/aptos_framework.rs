@@ -26,6 +26,20 @@ crate::natives::define_gas_parameters_for_natives!(GasParameters, "aptos_framewo
     [.bls12381.per_msg_hashing, "bls12381.per_msg_hashing", 1_540_000 * MUL],
     [.bls12381.per_byte_hashing, "bls12381.per_byte_hashing", 50 * MUL],
 
+    [.bn254.base, { 8.. => "bn254.base" }, 150 * MUL],
+    [.bn254.per_g1_deserialize, { 8.. => "bn254.per_g1_deserialize" }, 80_000 * MUL],
+    [.bn254.per_g1_serialize, { 8.. => "bn254.per_g1_serialize" }, 15_000 * MUL],
+    [.bn254.per_g1_add, { 8.. => "bn254.per_g1_add" }, 2_000 * MUL],
+    [.bn254.per_g1_mul, { 8.. => "bn254.per_g1_mul" }, 520_000 * MUL],
+    [.bn254.per_g2_deserialize, { 8.. => "bn254.per_g2_deserialize" }, 170_000 * MUL],
+    [.bn254.per_g2_serialize, { 8.. => "bn254.per_g2_serialize" }, 30_000 * MUL],
+    [.bn254.per_g2_subgroup_check, { 8.. => "bn254.per_g2_subgroup_check" }, 1_500_000 * MUL],
+    [.bn254.per_g2_add, { 8.. => "bn254.per_g2_add" }, 6_500 * MUL],
+    [.bn254.per_g2_mul, { 8.. => "bn254.per_g2_mul" }, 1_500_000 * MUL],
+    [.bn254.per_scalar_deserialize, { 8.. => "bn254.per_scalar_deserialize" }, 500 * MUL],
+    [.bn254.per_miller_loop, { 8.. => "bn254.per_miller_loop" }, 1_700_000 * MUL],
+    [.bn254.per_final_exponentiation, { 8.. => "bn254.per_final_exponentiation" }, 2_300_000 * MUL],
+
     [.ed25519.base, "signature.base", 150 * MUL],
     [.ed25519.per_pubkey_deserialize, "signature.per_pubkey_deserialize", 38_000 * MUL],
     [.ed25519.per_pubkey_small_order_check, "signature.per_pubkey_small_order_check", 6_350 * MUL],
@@ -98,7 +112,19 @@ crate::natives::define_gas_parameters_for_natives!(GasParameters, "aptos_framewo
     [.util.from_bytes.base, "util.from_bytes.base", 300 * MUL],
     [.util.from_bytes.per_byte, "util.from_bytes.per_byte", 5 * MUL],
 
+    [.string_utils.format.base, { 8.. => "string_utils.format.base" }, 1_000 * MUL],
+    [.string_utils.format.per_byte, { 8.. => "string_utils.format.per_byte" }, 3 * MUL],
+
     [.transaction_context.get_script_hash.base, "transaction_context.get_script_hash.base", 200 * MUL],
+    [.transaction_context.generate_unique_address.base, { 8.. => "transaction_context.generate_unique_address.base" }, 2_000 * MUL],
+    [.transaction_context.sender.base, { 8.. => "transaction_context.sender.base" }, 200 * MUL],
+    [.transaction_context.secondary_signers.base, { 8.. => "transaction_context.secondary_signers.base" }, 200 * MUL],
+    [.transaction_context.secondary_signers.per_signer, { 8.. => "transaction_context.secondary_signers.per_signer" }, 20 * MUL],
+    [.transaction_context.gas_payer.base, { 8.. => "transaction_context.gas_payer.base" }, 200 * MUL],
+    [.transaction_context.max_gas_amount.base, { 8.. => "transaction_context.max_gas_amount.base" }, 200 * MUL],
+    [.transaction_context.gas_unit_price.base, { 8.. => "transaction_context.gas_unit_price.base" }, 200 * MUL],
+    [.transaction_context.entry_function_payload.base, { 8.. => "transaction_context.entry_function_payload.base" }, 200 * MUL],
+    [.transaction_context.entry_function_payload.per_byte, { 8.. => "transaction_context.entry_function_payload.per_byte" }, 5 * MUL],
 
     [.code.request_publish.base, "code.request_publish.base", 500 * MUL],
     [.code.request_publish.per_byte, "code.request_publish.per_byte", 2 * MUL],
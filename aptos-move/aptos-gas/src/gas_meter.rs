@@ -29,6 +29,11 @@ use move_vm_types::{
 use std::collections::BTreeMap;
 
 // Change log:
+// - V8
+//   - Added BN254 curve arithmetic, pairing and Groth16 verification natives.
+//   - Added the string_utils::format native.
+//   - Added transaction context natives for the sender, secondary signers, gas payer, gas
+//     parameters and entry function payload, and for generating unique addresses.
 // - V7
 //   - Native support for exists<T>
 //   - New formulae for storage fees based on fixed APT costs
@@ -53,7 +58,7 @@ use std::collections::BTreeMap;
 //       global operations.
 // - V1
 //   - TBA
-pub const LATEST_GAS_FEATURE_VERSION: u64 = 7;
+pub const LATEST_GAS_FEATURE_VERSION: u64 = 8;
 
 pub(crate) const EXECUTION_GAS_MULTIPLIER: u64 = 20;
 
@@ -209,10 +214,49 @@ impl InitialGasSchedule for AptosGasParameters {
     }
 }
 
+/// Gas meter used inside the Aptos VM. On top of the Move VM's `GasMeter`, it charges for the
+/// transaction level costs, i.e., the intrinsic gas, the IO gas of the write set and the storage
+/// fees, and exposes the gas parameters in use.
+///
+/// This allows the VM to run with gas meters other than the standard one, e.g., one that records
+/// where the gas goes for profiling purposes.
+pub trait AptosGasMeter: GasMeter {
+    fn feature_version(&self) -> u64;
+
+    fn gas_params(&self) -> &AptosGasParameters;
+
+    fn storage_gas_params(&self) -> &StorageGasParameters;
+
+    /// Returns the remaining balance, rounded down to the external gas unit.
+    fn balance(&self) -> Gas {
+        self.balance_internal()
+            .to_unit_round_down_with_params(&self.gas_params().txn)
+    }
+
+    fn change_set_configs(&self) -> &ChangeSetConfigs {
+        &self.storage_gas_params().change_set_configs
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
+
+    fn charge_write_set_gas_for_io<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+    ) -> VMResult<()>;
+
+    fn charge_storage_fee<'a>(
+        &mut self,
+        write_ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+        events: impl IntoIterator<Item = &'a ContractEvent>,
+        txn_size: NumBytes,
+        gas_unit_price: FeePerGasUnit,
+    ) -> VMResult<()>;
+}
+
 /// The official gas meter used inside the Aptos VM.
 /// It maintains an internal gas counter, measured in internal gas units, and carries an environment
 /// consisting all the gas parameters, which it can lookup when performing gas calculations.
-pub struct AptosGasMeter {
+pub struct StandardGasMeter {
     feature_version: u64,
     gas_params: AptosGasParameters,
     storage_gas_params: StorageGasParameters,
@@ -226,7 +270,7 @@ pub struct AptosGasMeter {
     should_leak_memory_for_native: bool,
 }
 
-impl AptosGasMeter {
+impl StandardGasMeter {
     pub fn new(
         gas_feature_version: u64,
         gas_params: AptosGasParameters,
@@ -249,11 +293,6 @@ impl AptosGasMeter {
         }
     }
 
-    pub fn balance(&self) -> Gas {
-        self.balance
-            .to_unit_round_down_with_params(&self.gas_params.txn)
-    }
-
     #[inline]
     fn charge(&mut self, amount: InternalGas) -> PartialVMResult<()> {
         match self.balance.checked_sub(amount) {
@@ -318,17 +357,9 @@ impl AptosGasMeter {
             self.memory_quota += amount;
         }
     }
-
-    pub fn feature_version(&self) -> u64 {
-        self.feature_version
-    }
-
-    pub fn change_set_configs(&self) -> &ChangeSetConfigs {
-        &self.storage_gas_params.change_set_configs
-    }
 }
 
-impl GasMeter for AptosGasMeter {
+impl GasMeter for StandardGasMeter {
     fn balance_internal(&self) -> InternalGas {
         self.balance
     }
@@ -808,14 +839,26 @@ impl GasMeter for AptosGasMeter {
     }
 }
 
-impl AptosGasMeter {
-    pub fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
+impl AptosGasMeter for StandardGasMeter {
+    fn feature_version(&self) -> u64 {
+        self.feature_version
+    }
+
+    fn gas_params(&self) -> &AptosGasParameters {
+        &self.gas_params
+    }
+
+    fn storage_gas_params(&self) -> &StorageGasParameters {
+        &self.storage_gas_params
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
         let cost = self.gas_params.txn.calculate_intrinsic_gas(txn_size);
         self.charge_execution(cost)
             .map_err(|e| e.finish(Location::Undefined))
     }
 
-    pub fn charge_write_set_gas_for_io<'a>(
+    fn charge_write_set_gas_for_io<'a>(
         &mut self,
         ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
     ) -> VMResult<()> {
@@ -824,7 +867,7 @@ impl AptosGasMeter {
             .map_err(|e| e.finish(Location::Undefined))
     }
 
-    pub fn charge_storage_fee<'a>(
+    fn charge_storage_fee<'a>(
         &mut self,
         write_ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
         events: impl IntoIterator<Item = &'a ContractEvent>,
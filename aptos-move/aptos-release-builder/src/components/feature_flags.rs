@@ -29,6 +29,9 @@ pub enum FeatureFlag {
     MultisigAccounts,
     DelegationPools,
     Bls12381BasicOperations,
+    Secp256r1EcdsaAuthenticator,
+    FeePayerEnabled,
+    BlockPartitioning,
 }
 
 fn generate_features_blob(writer: &CodeWriter, data: &[u64]) {
@@ -130,6 +133,11 @@ impl From<FeatureFlag> for AptosFeatureFlag {
             FeatureFlag::MultisigAccounts => AptosFeatureFlag::MULTISIG_ACCOUNTS,
             FeatureFlag::DelegationPools => AptosFeatureFlag::DELEGATION_POOLS,
             FeatureFlag::Bls12381BasicOperations => AptosFeatureFlag::BLS12381_BASIC_OPERATIONS,
+            FeatureFlag::Secp256r1EcdsaAuthenticator => {
+                AptosFeatureFlag::SECP256R1_ECDSA_AUTHENTICATOR
+            },
+            FeatureFlag::FeePayerEnabled => AptosFeatureFlag::FEE_PAYER_ENABLED,
+            FeatureFlag::BlockPartitioning => AptosFeatureFlag::BLOCK_PARTITIONING,
         }
     }
 }
@@ -156,6 +164,11 @@ impl From<AptosFeatureFlag> for FeatureFlag {
             AptosFeatureFlag::MULTISIG_ACCOUNTS => FeatureFlag::MultisigAccounts,
             AptosFeatureFlag::DELEGATION_POOLS => FeatureFlag::DelegationPools,
             AptosFeatureFlag::BLS12381_BASIC_OPERATIONS => FeatureFlag::Bls12381BasicOperations,
+            AptosFeatureFlag::SECP256R1_ECDSA_AUTHENTICATOR => {
+                FeatureFlag::Secp256r1EcdsaAuthenticator
+            },
+            AptosFeatureFlag::FEE_PAYER_ENABLED => FeatureFlag::FeePayerEnabled,
+            AptosFeatureFlag::BLOCK_PARTITIONING => FeatureFlag::BlockPartitioning,
         }
     }
 }
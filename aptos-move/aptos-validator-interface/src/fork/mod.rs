@@ -0,0 +1,97 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DebuggerStateView;
+use anyhow::Result;
+use aptos_state_view::TStateView;
+use aptos_types::{
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    transaction::Version,
+    write_set::{TransactionWrite, WriteSet},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// Local changes layered on top of the state of the chain right before `version`, so that
+/// new transactions can be run against real state without touching it.
+///
+/// A `None` value means the state key was deleted locally.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ForkState {
+    version: Version,
+    overrides: BTreeMap<StateKey, Option<StateValue>>,
+}
+
+impl ForkState {
+    pub fn new(version: Version) -> Self {
+        Self {
+            version,
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// Loads a fork saved with [`ForkState::save`], to resume a session.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(bcs::from_bytes(&std::fs::read(path)?)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, bcs::to_bytes(self)?)?;
+        Ok(())
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the local value of a state key, or `None` if it wasn't changed locally.
+    pub fn get(&self, state_key: &StateKey) -> Option<&Option<StateValue>> {
+        self.overrides.get(state_key)
+    }
+
+    pub fn set_state_value(&mut self, state_key: StateKey, value: Option<StateValue>) {
+        self.overrides.insert(state_key, value);
+    }
+
+    pub fn apply_write_set(&mut self, write_set: &WriteSet) {
+        for (state_key, write_op) in write_set.iter() {
+            self.set_state_value(state_key.clone(), write_op.as_state_value());
+        }
+    }
+}
+
+/// A [`DebuggerStateView`] with the local changes of a [`ForkState`] applied on top.
+pub struct ForkedStateView<'a> {
+    base: &'a DebuggerStateView,
+    fork: &'a ForkState,
+}
+
+impl<'a> ForkedStateView<'a> {
+    pub fn new(base: &'a DebuggerStateView, fork: &'a ForkState) -> Self {
+        Self { base, fork }
+    }
+}
+
+impl<'a> TStateView for ForkedStateView<'a> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        match self.fork.get(state_key) {
+            Some(value) => Ok(value.clone()),
+            None => self.base.get_state_value(state_key),
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.base.get_usage()
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,138 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{AptosValidatorInterface, CommittedTransactions};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
+    transaction::{Transaction, TransactionInfo},
+    write_set::{WriteOp, WriteSetMut},
+};
+use std::sync::Arc;
+
+/// Serves the state values it holds at any version.
+struct MockValidatorInterface(BTreeMap<StateKey, StateValue>);
+
+#[async_trait::async_trait]
+impl AptosValidatorInterface for MockValidatorInterface {
+    async fn get_account_state_by_version(
+        &self,
+        _account: AccountAddress,
+        _version: Version,
+    ) -> Result<Option<AccountState>> {
+        unimplemented!()
+    }
+
+    async fn get_state_value_by_version(
+        &self,
+        state_key: &StateKey,
+        _version: Version,
+    ) -> Result<Option<StateValue>> {
+        Ok(self.0.get(state_key).cloned())
+    }
+
+    async fn get_committed_transactions(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+        unimplemented!()
+    }
+
+    async fn get_committed_transactions_with_outputs(
+        &self,
+        _start: Version,
+        _limit: u64,
+    ) -> Result<CommittedTransactions> {
+        unimplemented!()
+    }
+
+    async fn get_latest_version(&self) -> Result<Version> {
+        unimplemented!()
+    }
+
+    async fn get_version_by_account_sequence(
+        &self,
+        _account: AccountAddress,
+        _seq: u64,
+    ) -> Result<Option<Version>> {
+        unimplemented!()
+    }
+}
+
+fn key(key: &str) -> StateKey {
+    StateKey::raw(key.as_bytes().to_vec())
+}
+
+fn value(value: &str) -> StateValue {
+    StateValue::new_legacy(value.as_bytes().to_vec())
+}
+
+#[test]
+fn test_save_and_load() {
+    let mut fork = ForkState::new(10);
+    fork.set_state_value(key("set"), Some(value("value")));
+    fork.set_state_value(key("deleted"), None);
+
+    let tmp_dir = TempPath::new();
+    tmp_dir.create_as_dir().unwrap();
+    let path = tmp_dir.path().join("fork.bcs");
+    fork.save(&path).unwrap();
+    let loaded = ForkState::load(&path).unwrap();
+    assert_eq!(loaded, fork);
+    assert_eq!(loaded.version(), 10);
+    assert_eq!(loaded.get(&key("deleted")), Some(&None));
+}
+
+#[test]
+fn test_apply_write_set() {
+    let mut fork = ForkState::new(10);
+    let write_set = WriteSetMut::new(vec![
+        (key("created"), WriteOp::Creation(b"value".to_vec())),
+        (key("deleted"), WriteOp::Deletion),
+    ])
+    .freeze()
+    .unwrap();
+    fork.apply_write_set(&write_set);
+    assert_eq!(fork.get(&key("created")), Some(&Some(value("value"))));
+    assert_eq!(fork.get(&key("deleted")), Some(&None));
+    assert_eq!(fork.get(&key("untouched")), None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_forked_state_view() {
+    let base = DebuggerStateView::new(
+        Arc::new(MockValidatorInterface(
+            vec![
+                (key("overridden"), value("base")),
+                (key("deleted"), value("base")),
+                (key("untouched"), value("base")),
+            ]
+            .into_iter()
+            .collect(),
+        )),
+        10,
+    );
+    let mut fork = ForkState::new(10);
+    fork.set_state_value(key("overridden"), Some(value("fork")));
+    fork.set_state_value(key("deleted"), None);
+    fork.set_state_value(key("created"), Some(value("fork")));
+
+    let view = ForkedStateView::new(&base, &fork);
+    assert_eq!(
+        view.get_state_value(&key("overridden")).unwrap(),
+        Some(value("fork"))
+    );
+    assert_eq!(view.get_state_value(&key("deleted")).unwrap(), None);
+    assert_eq!(
+        view.get_state_value(&key("created")).unwrap(),
+        Some(value("fork"))
+    );
+    assert_eq!(
+        view.get_state_value(&key("untouched")).unwrap(),
+        Some(value("base"))
+    );
+    assert_eq!(view.get_state_value(&key("missing")).unwrap(), None);
+}
@@ -2,10 +2,17 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod fork;
 mod rest_interface;
+mod state_cache;
 mod storage_interface;
 
-pub use crate::{rest_interface::RestDebuggerInterface, storage_interface::DBDebuggerInterface};
+pub use crate::{
+    fork::{ForkState, ForkedStateView},
+    rest_interface::RestDebuggerInterface,
+    state_cache::StateCache,
+    storage_interface::DBDebuggerInterface,
+};
 use anyhow::{anyhow, Result};
 use aptos_state_view::TStateView;
 use aptos_types::{
@@ -13,16 +20,29 @@ use aptos_types::{
     account_config::CORE_CODE_ADDRESS,
     account_state::AccountState,
     account_view::AccountView,
+    contract_event::ContractEvent,
     on_chain_config::ValidatorSet,
     state_store::{
         state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
     },
     transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
 };
 use lru::LruCache;
 use move_binary_format::file_format::CompiledModule;
 use std::sync::{Arc, Mutex};
-use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::{
+    runtime::Handle,
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+};
+
+/// Committed transactions of a range of versions along with their outputs, all in version order.
+pub struct CommittedTransactions {
+    pub transactions: Vec<Transaction>,
+    pub infos: Vec<TransactionInfo>,
+    pub write_sets: Vec<WriteSet>,
+    pub events: Vec<Vec<ContractEvent>>,
+}
 
 // TODO(skedia) Clean up this interfact to remove account specific logic and move to state store
 // key-value interface with fine grained storage project
@@ -46,6 +66,14 @@ pub trait AptosValidatorInterface: Sync {
         limit: u64,
     ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)>;
 
+    /// Returns the committed transactions in `[start, start + limit)` along with their write
+    /// sets and events.
+    async fn get_committed_transactions_with_outputs(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<CommittedTransactions>;
+
     async fn get_latest_version(&self) -> Result<Version>;
 
     async fn get_version_by_account_sequence(
@@ -160,10 +188,20 @@ async fn handler_thread<'a>(
 }
 
 impl DebuggerStateView {
+    /// Must be called from within a tokio runtime, which serves the reads of the state view.
     pub fn new(db: Arc<dyn AptosValidatorInterface + Send>, version: Version) -> Self {
+        Self::new_with_handle(db, version, &Handle::current())
+    }
+
+    /// Like [`DebuggerStateView::new`], but serves the reads on the runtime of `handle`.
+    pub fn new_with_handle(
+        db: Arc<dyn AptosValidatorInterface + Send>,
+        version: Version,
+        handle: &Handle,
+    ) -> Self {
         let (query_sender, thread_receiver) = unbounded_channel();
 
-        tokio::spawn(async move { handler_thread(db, thread_receiver).await });
+        handle.spawn(async move { handler_thread(db, thread_receiver).await });
         Self {
             query_sender: Mutex::new(query_sender),
             version,
@@ -1,10 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::AptosValidatorInterface;
+use crate::{AptosValidatorInterface, CommittedTransactions, StateCache};
 use anyhow::{anyhow, bail, Result};
-use aptos_api_types::MoveStructTag;
-use aptos_rest_client::Client;
+use aptos_api_types::{MoveStructTag, TransactionOnChainData};
+use aptos_rest_client::{error::RestError, Client};
 use aptos_types::{
     access_path::Path,
     account_address::AccountAddress,
@@ -14,38 +14,59 @@ use aptos_types::{
         state_value::StateValue,
     },
     transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
 };
 use std::collections::BTreeMap;
 
-pub struct RestDebuggerInterface(Client);
+pub struct RestDebuggerInterface {
+    client: Client,
+    cache: Option<StateCache>,
+}
 
 impl RestDebuggerInterface {
     pub fn new(client: Client) -> Self {
-        Self(client)
+        Self {
+            client,
+            cache: None,
+        }
     }
-}
 
-#[async_trait::async_trait]
-impl AptosValidatorInterface for RestDebuggerInterface {
-    async fn get_account_state_by_version(
+    /// Serves state reads from `cache` when possible, and caches the state values and
+    /// committed write sets fetched from the node.
+    pub fn with_cache(client: Client, cache: StateCache) -> Self {
+        Self {
+            client,
+            cache: Some(cache),
+        }
+    }
+
+    async fn get_transactions_on_chain_data(
         &self,
-        account: AccountAddress,
-        version: Version,
-    ) -> Result<Option<AccountState>> {
-        let resource = self
-            .0
-            .get_account_resources_at_version_bcs(account, version)
-            .await
-            .map_err(|err| anyhow!("Failed to get account states: {:?}", err))?
-            .into_inner()
-            .into_iter()
-            .map(|(key, value)| (key.access_vector(), value))
-            .collect::<BTreeMap<_, _>>();
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<TransactionOnChainData>> {
+        let mut txns = Vec::with_capacity(limit as usize);
 
-        Ok(Some(AccountState::new(account, resource)))
+        while txns.len() < limit as usize {
+            let batch_start = start + txns.len() as u64;
+            let batch = self
+                .client
+                .get_transactions_bcs(Some(batch_start), Some(limit as u16 - txns.len() as u16))
+                .await?
+                .into_inner();
+            if let Some(cache) = &self.cache {
+                let write_sets: Vec<WriteSet> =
+                    batch.iter().map(|txn| txn.changes.clone()).collect();
+                cache.put_write_sets(batch_start, &write_sets)?;
+            }
+            txns.extend(batch);
+            println!("Got {}/{} txns from RestApi.", txns.len(), limit);
+        }
+
+        Ok(txns)
     }
 
-    async fn get_state_value_by_version(
+    async fn fetch_state_value(
         &self,
         state_key: &StateKey,
         version: Version,
@@ -53,7 +74,7 @@ impl AptosValidatorInterface for RestDebuggerInterface {
         match state_key.inner() {
             StateKeyInner::AccessPath(path) => match path.get_path() {
                 Path::Code(module_id) => Ok(Some(StateValue::new_legacy(
-                    self.0
+                    self.client
                         .get_account_module_bcs_at_version(
                             *module_id.address(),
                             module_id.name().as_str(),
@@ -64,19 +85,23 @@ impl AptosValidatorInterface for RestDebuggerInterface {
                         .into_inner()
                         .to_vec(),
                 ))),
-                Path::Resource(tag) | Path::ResourceGroup(tag) => Ok(self
-                    .0
+                Path::Resource(tag) | Path::ResourceGroup(tag) => match self
+                    .client
                     .get_account_resource_at_version_bytes(
                         path.address,
                         MoveStructTag::from(tag).to_string().as_str(),
                         version,
                     )
                     .await
-                    .ok()
-                    .map(|inner| StateValue::new_legacy(inner.into_inner()))),
+                {
+                    Ok(inner) => Ok(Some(StateValue::new_legacy(inner.into_inner()))),
+                    // Only a missing resource is cached as such, not a failed request.
+                    Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => Ok(None),
+                    Err(err) => Err(anyhow!("Failed to get account states: {:?}", err)),
+                },
             },
             StateKeyInner::TableItem { handle, key } => Ok(Some(StateValue::new_legacy(
-                self.0
+                self.client
                     .get_raw_table_item(handle.0, key, version)
                     .await
                     .map_err(|err| anyhow!("Failed to get account states: {:?}", err))?
@@ -85,36 +110,86 @@ impl AptosValidatorInterface for RestDebuggerInterface {
             StateKeyInner::Raw(_) => bail!("Unexpected key type"),
         }
     }
+}
+
+#[async_trait::async_trait]
+impl AptosValidatorInterface for RestDebuggerInterface {
+    async fn get_account_state_by_version(
+        &self,
+        account: AccountAddress,
+        version: Version,
+    ) -> Result<Option<AccountState>> {
+        let resource = self
+            .client
+            .get_account_resources_at_version_bcs(account, version)
+            .await
+            .map_err(|err| anyhow!("Failed to get account states: {:?}", err))?
+            .into_inner()
+            .into_iter()
+            .map(|(key, value)| (key.access_vector(), value))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(Some(AccountState::new(account, resource)))
+    }
+
+    async fn get_state_value_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return self.fetch_state_value(state_key, version).await,
+        };
+        if let Some(value) = cache.get(state_key, version)? {
+            return Ok(value);
+        }
+        let value = self.fetch_state_value(state_key, version).await?;
+        cache.put(state_key, version, &value)?;
+        Ok(value)
+    }
 
     async fn get_committed_transactions(
         &self,
         start: Version,
         limit: u64,
     ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
-        let mut txns = Vec::with_capacity(limit as usize);
-        let mut txn_infos = Vec::with_capacity(limit as usize);
+        Ok(self
+            .get_transactions_on_chain_data(start, limit)
+            .await?
+            .into_iter()
+            .map(|txn| (txn.transaction, txn.info))
+            .unzip())
+    }
 
-        while txns.len() < limit as usize {
-            self.0
-                .get_transactions_bcs(
-                    Some(start + txns.len() as u64),
-                    Some(limit as u16 - txns.len() as u16),
-                )
-                .await?
-                .into_inner()
-                .into_iter()
-                .for_each(|txn| {
-                    txns.push(txn.transaction);
-                    txn_infos.push(txn.info);
-                });
-            println!("Got {}/{} txns from RestApi.", txns.len(), limit);
+    async fn get_committed_transactions_with_outputs(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<CommittedTransactions> {
+        let txns = self.get_transactions_on_chain_data(start, limit).await?;
+        let mut committed = CommittedTransactions {
+            transactions: Vec::with_capacity(txns.len()),
+            infos: Vec::with_capacity(txns.len()),
+            write_sets: Vec::with_capacity(txns.len()),
+            events: Vec::with_capacity(txns.len()),
+        };
+        for txn in txns {
+            committed.transactions.push(txn.transaction);
+            committed.infos.push(txn.info);
+            committed.write_sets.push(txn.changes);
+            committed.events.push(txn.events);
         }
-
-        Ok((txns, txn_infos))
+        Ok(committed)
     }
 
     async fn get_latest_version(&self) -> Result<Version> {
-        Ok(self.0.get_ledger_information().await?.into_inner().version)
+        Ok(self
+            .client
+            .get_ledger_information()
+            .await?
+            .into_inner()
+            .version)
     }
 
     async fn get_version_by_account_sequence(
@@ -123,7 +198,7 @@ impl AptosValidatorInterface for RestDebuggerInterface {
         seq: u64,
     ) -> Result<Option<Version>> {
         Ok(Some(
-            self.0
+            self.client
                 .get_account_transactions_bcs(account, Some(seq), None)
                 .await?
                 .into_inner()[0]
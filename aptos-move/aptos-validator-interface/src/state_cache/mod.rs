@@ -0,0 +1,128 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod schema;
+
+use self::schema::{column_families, StateValueByVersionSchema, WriteSetRangeSchema};
+use anyhow::Result;
+use aptos_config::config::RocksdbConfig;
+use aptos_rocksdb_options::gen_rocksdb_options;
+use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
+use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+    write_set::{TransactionWrite, WriteSet},
+};
+use std::path::Path;
+
+/// A persistent cache of state values read from a remote node, keyed by state key and version.
+///
+/// Committed write sets can be added to the cache, so that a value cached at some version is
+/// also served at later versions if it's known not to have changed in between. Replaying a
+/// range of transactions then only needs each state key to be fetched once.
+///
+/// The cache can be reused by later runs, but must only ever hold the state of a single chain.
+pub struct StateCache {
+    db: DB,
+}
+
+impl StateCache {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db = DB::open(
+            path,
+            "state_cache",
+            column_families(),
+            &gen_rocksdb_options(&RocksdbConfig::default(), false),
+        )?;
+        Ok(Self { db })
+    }
+
+    /// Returns the value of `state_key` right after `version` was committed, or `None` if it
+    /// can't be served from the cache.
+    pub fn get(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<Option<StateValue>>> {
+        let mut iter = self
+            .db
+            .iter::<StateValueByVersionSchema>(ReadOptions::default())?;
+        iter.seek(&(state_key.clone(), version))?;
+        let (cached_version, value) = match iter.next().transpose()? {
+            Some(((key, cached_version), value)) if &key == state_key => (cached_version, value),
+            _ => return Ok(None),
+        };
+        if cached_version == version || self.has_write_sets(cached_version + 1, version)? {
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Caches the value of `state_key` right after `version` was committed.
+    pub fn put(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+        value: &Option<StateValue>,
+    ) -> Result<()> {
+        self.db
+            .put::<StateValueByVersionSchema>(&(state_key.clone(), version), value)
+    }
+
+    /// Caches the committed write sets of the transactions starting at `first_version`.
+    pub fn put_write_sets(&self, first_version: Version, write_sets: &[WriteSet]) -> Result<()> {
+        if write_sets.is_empty() {
+            return Ok(());
+        }
+
+        let batch = SchemaBatch::new();
+        for (version, write_set) in (first_version..).zip(write_sets) {
+            for (state_key, write_op) in write_set.iter() {
+                batch.put::<StateValueByVersionSchema>(
+                    &(state_key.clone(), version),
+                    &write_op.as_state_value(),
+                )?;
+            }
+        }
+
+        // Merge the new range with the overlapping or adjacent ones already cached.
+        let (mut begin, mut end) = (first_version, first_version + write_sets.len() as Version);
+        let mut iter = self
+            .db
+            .iter::<WriteSetRangeSchema>(ReadOptions::default())?;
+        iter.seek_for_prev(&begin)?;
+        if let Some((prev_begin, prev_end)) = iter.next().transpose()? {
+            if prev_end >= begin {
+                begin = prev_begin;
+            }
+        }
+        iter.seek(&begin)?;
+        for range in iter {
+            let (range_begin, range_end) = range?;
+            if range_begin > end {
+                break;
+            }
+            end = std::cmp::max(end, range_end);
+            batch.delete::<WriteSetRangeSchema>(&range_begin)?;
+        }
+        batch.put::<WriteSetRangeSchema>(&begin, &end)?;
+
+        self.db.write_schemas(batch)
+    }
+
+    /// Returns whether the write sets of all the transactions in `[begin, end]` are cached.
+    fn has_write_sets(&self, begin: Version, end: Version) -> Result<bool> {
+        let mut iter = self
+            .db
+            .iter::<WriteSetRangeSchema>(ReadOptions::default())?;
+        iter.seek_for_prev(&begin)?;
+        Ok(iter
+            .next()
+            .transpose()?
+            .map_or(false, |(_, range_end)| range_end > end))
+    }
+}
+
+#[cfg(test)]
+mod test;
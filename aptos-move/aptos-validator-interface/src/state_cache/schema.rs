@@ -0,0 +1,120 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Physical schemas of the state cache.
+//!
+//! ```text
+//! StateValueByVersionSchema:
+//! |<-------- key -------->|<--- value --->|
+//! |  state key  | !version|  state value  |
+//!
+//! WriteSetRangeSchema:
+//! |<-- key -->|<----- value ----->|
+//! |   begin   |  end (exclusive)  |
+//! ```
+//!
+//! Versions of state values are inverted, so that seeking to `(state_key, version)` finds the
+//! latest cached value of `state_key` at or before `version`.
+
+use anyhow::{ensure, Result};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName,
+};
+use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{io::Write, mem::size_of};
+
+pub const DEFAULT_COLUMN_FAMILY_NAME: ColumnFamilyName = "default";
+pub const STATE_VALUE_BY_VERSION_CF_NAME: ColumnFamilyName = "state_value_by_version";
+pub const WRITE_SET_RANGE_CF_NAME: ColumnFamilyName = "write_set_range";
+
+pub fn column_families() -> Vec<ColumnFamilyName> {
+    vec![
+        /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
+        STATE_VALUE_BY_VERSION_CF_NAME,
+        WRITE_SET_RANGE_CF_NAME,
+    ]
+}
+
+define_schema!(
+    StateValueByVersionSchema,
+    (StateKey, Version),
+    Option<StateValue>,
+    STATE_VALUE_BY_VERSION_CF_NAME
+);
+
+impl KeyCodec<StateValueByVersionSchema> for (StateKey, Version) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = vec![];
+        encoded.write_all(&self.0.encode()?)?;
+        encoded.write_u64::<BigEndian>(!self.1)?;
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        const VERSION_SIZE: usize = size_of::<Version>();
+
+        ensure!(
+            data.len() > VERSION_SIZE,
+            "Unexpected data len {}, expected to be greater than {}.",
+            data.len(),
+            VERSION_SIZE,
+        );
+        let state_key_len = data.len() - VERSION_SIZE;
+        let state_key = StateKey::decode(&data[..state_key_len])?;
+        let version = !(&data[state_key_len..]).read_u64::<BigEndian>()?;
+        Ok((state_key, version))
+    }
+}
+
+impl ValueCodec<StateValueByVersionSchema> for Option<StateValue> {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+define_schema!(
+    WriteSetRangeSchema,
+    Version,
+    Version,
+    WRITE_SET_RANGE_CF_NAME
+);
+
+impl KeyCodec<WriteSetRangeSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        decode_version(data)
+    }
+}
+
+impl ValueCodec<WriteSetRangeSchema> for Version {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        decode_version(data)
+    }
+}
+
+fn decode_version(mut data: &[u8]) -> Result<Version> {
+    ensure!(
+        data.len() == size_of::<Version>(),
+        "Unexpected data len {}, expected {}.",
+        data.len(),
+        size_of::<Version>(),
+    );
+    Ok(data.read_u64::<BigEndian>()?)
+}
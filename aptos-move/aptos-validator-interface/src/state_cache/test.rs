@@ -0,0 +1,81 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_temppath::TempPath;
+use aptos_types::write_set::{WriteOp, WriteSetMut};
+
+fn write_set(write_ops: Vec<(StateKey, WriteOp)>) -> WriteSet {
+    WriteSetMut::new(write_ops).freeze().unwrap()
+}
+
+#[test]
+fn test_get_cached_value() {
+    let tmp_dir = TempPath::new();
+    let cache = StateCache::open(&tmp_dir).unwrap();
+    let key = StateKey::raw(b"key".to_vec());
+    let value = Some(StateValue::new_legacy(b"value".to_vec()));
+
+    assert_eq!(cache.get(&key, 10).unwrap(), None);
+    cache.put(&key, 10, &value).unwrap();
+    assert_eq!(cache.get(&key, 10).unwrap(), Some(value));
+    // Without the write sets in between, the value may have changed.
+    assert_eq!(cache.get(&key, 9).unwrap(), None);
+    assert_eq!(cache.get(&key, 11).unwrap(), None);
+    assert_eq!(
+        cache.get(&StateKey::raw(b"other".to_vec()), 10).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_get_value_through_write_sets() {
+    let tmp_dir = TempPath::new();
+    let cache = StateCache::open(&tmp_dir).unwrap();
+    let key = StateKey::raw(b"key".to_vec());
+    let other_key = StateKey::raw(b"other".to_vec());
+    let value = Some(StateValue::new_legacy(b"value".to_vec()));
+
+    cache.put(&key, 9, &value).unwrap();
+    cache.put(&other_key, 9, &value).unwrap();
+    cache
+        .put_write_sets(
+            10,
+            &[
+                write_set(vec![]),
+                write_set(vec![(other_key.clone(), WriteOp::Deletion)]),
+            ],
+        )
+        .unwrap();
+    // Versions 13 and 14 are missing.
+    cache
+        .put_write_sets(
+            15,
+            &[write_set(vec![(
+                key.clone(),
+                WriteOp::Modification(b"new".to_vec()),
+            )])],
+        )
+        .unwrap();
+
+    assert_eq!(cache.get(&key, 11).unwrap(), Some(value.clone()));
+    assert_eq!(cache.get(&other_key, 10).unwrap(), Some(value.clone()));
+    assert_eq!(cache.get(&other_key, 11).unwrap(), Some(None));
+    assert_eq!(cache.get(&key, 13).unwrap(), None);
+    assert_eq!(
+        cache.get(&key, 15).unwrap(),
+        Some(Some(StateValue::new_legacy(b"new".to_vec())))
+    );
+    assert_eq!(cache.get(&key, 16).unwrap(), None);
+
+    // Filling the gap merges the ranges of cached write sets.
+    cache
+        .put_write_sets(
+            12,
+            &[write_set(vec![]), write_set(vec![]), write_set(vec![])],
+        )
+        .unwrap();
+    assert_eq!(cache.get(&key, 14).unwrap(), Some(value));
+    assert_eq!(cache.get(&other_key, 15).unwrap(), Some(None));
+    assert_eq!(cache.get(&other_key, 16).unwrap(), None);
+}
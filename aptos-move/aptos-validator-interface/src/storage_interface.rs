@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::AptosValidatorInterface;
+use crate::{AptosValidatorInterface, CommittedTransactions};
 use anyhow::{anyhow, bail, ensure, Result};
 use aptos_config::config::{
     RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
@@ -28,6 +28,7 @@ impl DBDebuggerInterface {
             NO_OP_STORAGE_PRUNER_CONFIG,
             RocksdbConfigs::default(),
             false,
+            false, /* event type index */
             BUFFERED_STATE_TARGET_ITEMS,
             DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         )?)))
@@ -79,6 +80,25 @@ impl AptosValidatorInterface for DBDebuggerInterface {
         Ok((txns, txn_infos))
     }
 
+    async fn get_committed_transactions_with_outputs(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<CommittedTransactions> {
+        let (transactions, infos) = self.get_committed_transactions(start, limit).await?;
+        let write_set_iter = self.0.get_write_set_iterator(start, limit)?;
+        let events_iter = self.0.get_events_iterator(start, limit)?;
+        let write_sets = write_set_iter.collect::<Result<Vec<_>>>()?;
+        let events = events_iter.collect::<Result<Vec<_>>>()?;
+        ensure!(infos.len() == write_sets.len() && infos.len() == events.len());
+        Ok(CommittedTransactions {
+            transactions,
+            infos,
+            write_sets,
+            events,
+        })
+    }
+
     async fn get_latest_version(&self) -> Result<Version> {
         let (version, _) = self
             .0
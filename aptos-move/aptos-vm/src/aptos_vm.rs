@@ -12,6 +12,7 @@ use crate::{
     data_cache::{AsMoveResolver, IntoMoveResolver},
     delta_state_view::DeltaStateView,
     errors::expect_only_successful_execution,
+    execution_trace::{ExecutionTrace, ExecutionTracer, TracingStateView},
     move_vm_ext::{MoveResolverExt, SessionExt, SessionId},
     system_module_names::*,
     transaction_metadata::TransactionMetadata,
@@ -24,7 +25,8 @@ use aptos_aggregator::{
 };
 use aptos_crypto::HashValue;
 use aptos_framework::natives::code::PublishRequest;
-use aptos_gas::{AptosGasMeter, ChangeSetConfigs};
+use aptos_gas::{AptosGasMeter, ChangeSetConfigs, StandardGasMeter};
+use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
 use aptos_logger::prelude::*;
 use aptos_state_view::StateView;
 use aptos_types::{
@@ -60,6 +62,7 @@ use move_core_types::{
 use move_vm_types::gas::UnmeteredGasMeter;
 use num_cpus;
 use once_cell::sync::OnceCell;
+use read_write_set_dynamic::NormalizedReadWriteSetAnalysis;
 use std::{
     cmp::min,
     collections::{BTreeMap, BTreeSet},
@@ -76,6 +79,7 @@ static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
+static READ_WRITE_SET_ANALYSIS: OnceCell<NormalizedReadWriteSetAnalysis> = OnceCell::new();
 
 /// Remove this once the bundle is removed from the code.
 static MODULE_BUNDLE_DISALLOWED: AtomicBool = AtomicBool::new(true);
@@ -171,6 +175,20 @@ impl AptosVM {
         }
     }
 
+    /// Sets the read/write set analysis of the framework when invoked the first time, which
+    /// enables reordering blocks by predicted conflicts before executing them in parallel, as
+    /// long as the `BLOCK_PARTITIONING` feature is enabled on chain.
+    pub fn set_read_write_set_analysis_once(analysis: NormalizedReadWriteSetAnalysis) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        READ_WRITE_SET_ANALYSIS.set(analysis).ok();
+    }
+
+    /// Get the read/write set analysis if already set, otherwise return None, i.e., blocks
+    /// are executed in their original order whatever the on-chain features.
+    pub fn get_read_write_set_analysis() -> Option<&'static NormalizedReadWriteSetAnalysis> {
+        READ_WRITE_SET_ANALYSIS.get()
+    }
+
     pub fn internals(&self) -> AptosVMInternals {
         AptosVMInternals::new(&self.0)
     }
@@ -189,7 +207,7 @@ impl AptosVM {
     pub fn failed_transaction_cleanup<S: MoveResolverExt>(
         &self,
         error_code: VMStatus,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         txn_data: &TransactionMetadata,
         storage: &S,
         log_context: &AdapterLogSchema,
@@ -207,13 +225,13 @@ impl AptosVM {
     fn failed_transaction_cleanup_and_keep_vm_status<S: MoveResolverExt>(
         &self,
         error_code: VMStatus,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         txn_data: &TransactionMetadata,
         storage: &S,
         log_context: &AdapterLogSchema,
     ) -> (VMStatus, TransactionOutputExt) {
         let resolver = self.0.new_move_resolver(storage);
-        let mut session = self.0.new_session(&resolver, SessionId::txn_meta(txn_data));
+        let mut session = self.0.new_user_session(&resolver, txn_data);
 
         match TransactionStatus::from(error_code.clone()) {
             TransactionStatus::Keep(status) => {
@@ -269,7 +287,7 @@ impl AptosVM {
         &self,
         storage: &S,
         user_txn_change_set_ext: ChangeSetExt,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         txn_data: &TransactionMetadata,
         log_context: &AdapterLogSchema,
     ) -> Result<(VMStatus, TransactionOutputExt), VMStatus> {
@@ -295,7 +313,7 @@ impl AptosVM {
             DeltaStateView::new(&storage_with_changes, &delta_write_set).into_move_resolver();
 
         let resolver = self.0.new_move_resolver(&storage_with_changes);
-        let mut session = self.0.new_session(&resolver, SessionId::txn_meta(txn_data));
+        let mut session = self.0.new_user_session(&resolver, txn_data);
 
         self.0
             .run_success_epilogue(&mut session, gas_meter.balance(), txn_data, log_context)?;
@@ -332,7 +350,7 @@ impl AptosVM {
         &self,
         storage: &S,
         mut session: SessionExt<SS>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         txn_data: &TransactionMetadata,
         payload: &TransactionPayload,
         log_context: &AdapterLogSchema,
@@ -443,7 +461,7 @@ impl AptosVM {
         &self,
         storage: &S,
         mut session: SessionExt<SS>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         txn_data: &TransactionMetadata,
         txn_payload: &Multisig,
         log_context: &AdapterLogSchema,
@@ -568,7 +586,7 @@ impl AptosVM {
     fn execute_multisig_entry_function<SS: MoveResolverExt>(
         &self,
         session: &mut SessionExt<SS>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         multisig_address: AccountAddress,
         payload: &EntryFunction,
         new_published_modules_loaded: &mut bool,
@@ -604,7 +622,7 @@ impl AptosVM {
         &self,
         storage: &S,
         session: SessionExt<SS>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         txn_data: &TransactionMetadata,
         cleanup_args: Vec<Vec<u8>>,
     ) -> Result<ChangeSetExt, VMStatus> {
@@ -635,7 +653,7 @@ impl AptosVM {
         let storage_with_changes =
             DeltaStateView::new(&storage_with_changes, &delta_write_set).into_move_resolver();
         let resolver = self.0.new_move_resolver(&storage_with_changes);
-        let mut cleanup_session = self.0.new_session(&resolver, SessionId::txn_meta(txn_data));
+        let mut cleanup_session = self.0.new_user_session(&resolver, txn_data);
         cleanup_session.execute_function_bypass_visibility(
             &MULTISIG_ACCOUNT_MODULE,
             SUCCESSFUL_TRANSACTION_EXECUTION_CLEANUP,
@@ -655,14 +673,14 @@ impl AptosVM {
     fn failure_multisig_payload_cleanup<S: MoveResolverExt + StateView>(
         &self,
         storage: &S,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         execution_error: VMStatus,
         txn_data: &TransactionMetadata,
         mut cleanup_args: Vec<Vec<u8>>,
     ) -> Result<ChangeSetExt, VMStatus> {
         // Start a fresh session for running cleanup that does not contain any changes from
         // the inner function call earlier (since it failed).
-        let mut cleanup_session = self.0.new_session(storage, SessionId::txn_meta(txn_data));
+        let mut cleanup_session = self.0.new_user_session(storage, txn_data);
         let execution_error = ExecutionError::try_from(execution_error)
             .map_err(|_| VMStatus::Error(StatusCode::UNREACHABLE))?;
         // Serialization is not expected to fail so we're using invariant_violation error here.
@@ -713,7 +731,7 @@ impl AptosVM {
     fn execute_module_initialization<S: MoveResolverExt>(
         &self,
         session: &mut SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         modules: &[CompiledModule],
         exists: BTreeSet<ModuleId>,
         senders: &[AccountAddress],
@@ -787,7 +805,7 @@ impl AptosVM {
         &self,
         storage: &S,
         mut session: SessionExt<SS>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         txn_data: &TransactionMetadata,
         modules: &ModuleBundle,
         log_context: &AdapterLogSchema,
@@ -852,7 +870,7 @@ impl AptosVM {
     fn resolve_pending_code_publish<S: MoveResolverExt>(
         &self,
         session: &mut SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut impl AptosGasMeter,
         new_published_modules_loaded: &mut bool,
     ) -> VMResult<()> {
         if let Some(PublishRequest {
@@ -969,6 +987,27 @@ impl AptosVM {
         txn: &SignatureCheckedTransaction,
         log_context: &AdapterLogSchema,
     ) -> (VMStatus, TransactionOutputExt) {
+        let (vm_status, output, _gas_meter) =
+            self.execute_user_transaction_with_gas_meter(storage, txn, log_context, |gas_meter| {
+                gas_meter
+            });
+        (vm_status, output)
+    }
+
+    /// Same as `execute_user_transaction`, with a gas meter built on top of the standard one by
+    /// `make_gas_meter`, e.g., to trace the execution. The gas meter is returned unless the
+    /// transaction is discarded before it is created.
+    fn execute_user_transaction_with_gas_meter<S: MoveResolverExt, G: AptosGasMeter>(
+        &self,
+        storage: &S,
+        txn: &SignatureCheckedTransaction,
+        log_context: &AdapterLogSchema,
+        make_gas_meter: impl FnOnce(StandardGasMeter) -> G,
+    ) -> (VMStatus, TransactionOutputExt, Option<G>) {
+        let discard_error_vm_status = |err| {
+            let (vm_status, output) = discard_error_vm_status(err);
+            (vm_status, output, None)
+        };
         macro_rules! unwrap_or_discard {
             ($res:expr) => {
                 match $res {
@@ -979,8 +1018,9 @@ impl AptosVM {
         }
 
         // Revalidate the transaction.
+        let txn_data = TransactionMetadata::new(txn);
         let resolver = self.0.new_move_resolver(storage);
-        let mut session = self.0.new_session(&resolver, SessionId::txn(txn));
+        let mut session = self.0.new_user_session(&resolver, &txn_data);
         if let Err(err) = self.validate_signature_checked_transaction(
             &mut session,
             storage,
@@ -997,18 +1037,17 @@ impl AptosVM {
             // have been previously cached in the prologue.
             //
             // TODO(Gas): Do this in a better way in the future, perhaps without forcing the data cache to be flushed.
-            session = self.0.new_session(&resolver, SessionId::txn(txn));
+            session = self.0.new_user_session(&resolver, &txn_data);
         }
 
         let gas_params = unwrap_or_discard!(self.0.get_gas_parameters(log_context));
         let storage_gas_params = unwrap_or_discard!(self.0.get_storage_gas_parameters(log_context));
-        let txn_data = TransactionMetadata::new(txn);
-        let mut gas_meter = AptosGasMeter::new(
+        let mut gas_meter = make_gas_meter(StandardGasMeter::new(
             self.0.get_gas_feature_version(),
             gas_params.clone(),
             storage_gas_params.clone(),
             txn_data.max_gas_amount(),
-        );
+        ));
 
         // We keep track of whether any newly published modules are loaded into the Vm's loader
         // cache as part of executing transactions. This would allow us to decide whether the cache
@@ -1054,7 +1093,7 @@ impl AptosVM {
             .expect("Balance should always be less than or equal to max gas amount set");
         TXN_GAS_USAGE.observe(u64::from(gas_usage) as f64);
 
-        match result {
+        let (vm_status, output) = match result {
             Ok(output) => output,
             Err(err) => {
                 // Invalidate the loader cache in case there was a new module loaded from a module
@@ -1068,7 +1107,8 @@ impl AptosVM {
 
                 let txn_status = TransactionStatus::from(err.clone());
                 if txn_status.is_discarded() {
-                    discard_error_vm_status(err)
+                    let (vm_status, output, _) = discard_error_vm_status(err);
+                    (vm_status, output)
                 } else {
                     self.failed_transaction_cleanup_and_keep_vm_status(
                         err,
@@ -1079,7 +1119,8 @@ impl AptosVM {
                     )
                 }
             },
-        }
+        };
+        (vm_status, output, Some(gas_meter))
     }
 
     fn execute_writeset<S: MoveResolverExt>(
@@ -1263,6 +1304,106 @@ impl AptosVM {
         simulation_vm.simulate_signed_transaction(&state_view.as_move_resolver(), txn, &log_context)
     }
 
+    /// Simulates a signed transaction like `simulate_signed_transaction`, profiling its gas usage.
+    /// The gas log is returned unless the transaction is discarded before execution.
+    pub fn simulate_signed_transaction_with_gas_profiling(
+        txn: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutputExt, Option<TransactionGasLog>) {
+        let vm = AptosVM::new(state_view);
+        let simulation_vm = AptosSimulationVM(vm);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let (vm_status, output, gas_profiler) = simulation_vm
+            .simulate_signed_transaction_with_gas_meter(
+                &state_view.as_move_resolver(),
+                txn,
+                &log_context,
+                |gas_meter| match txn.payload() {
+                    TransactionPayload::EntryFunction(entry_function)
+                    | TransactionPayload::Multisig(Multisig {
+                        transaction_payload:
+                            Some(MultisigTransactionPayload::EntryFunction(entry_function)),
+                        ..
+                    }) => GasProfiler::new_function(
+                        gas_meter,
+                        entry_function.module(),
+                        entry_function.function(),
+                        entry_function.ty_args(),
+                    ),
+                    TransactionPayload::Script(_) => GasProfiler::new_script(gas_meter),
+                    TransactionPayload::Multisig(_) => {
+                        GasProfiler::new(gas_meter, "multisig".to_string())
+                    },
+                    TransactionPayload::ModuleBundle(_) => {
+                        GasProfiler::new(gas_meter, "module bundle".to_string())
+                    },
+                },
+            );
+        (
+            vm_status,
+            output,
+            gas_profiler.map(|gas_profiler| gas_profiler.finish()),
+        )
+    }
+
+    /// Executes a user transaction as part of a block would, tracing its execution. The trace is
+    /// returned unless the transaction is discarded before execution.
+    pub fn execute_user_transaction_with_trace(
+        txn: SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput, Option<ExecutionTrace>) {
+        let vm = AptosVM::new(state_view);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let txn = match Self::check_signature(txn) {
+            Ok(txn) => txn,
+            Err(_) => {
+                let (vm_status, output) =
+                    discard_error_vm_status(VMStatus::Error(StatusCode::INVALID_SIGNATURE));
+                return (vm_status, output.into_transaction_output(state_view), None);
+            },
+        };
+        // Only the reads of the transaction are traced, not the ones of the VM's setup.
+        let tracing_state_view = TracingStateView::new(state_view);
+        let (vm_status, output, tracer) = vm.execute_user_transaction_with_gas_meter(
+            &tracing_state_view.as_move_resolver(),
+            &txn,
+            &log_context,
+            |gas_meter| ExecutionTracer::new(gas_meter, txn.payload()),
+        );
+        Self::finish_trace(vm_status, output, tracer, tracing_state_view)
+    }
+
+    /// Simulates a signed transaction like `simulate_signed_transaction`, tracing its execution.
+    /// The trace is returned unless the transaction is discarded before execution.
+    pub fn simulate_signed_transaction_with_trace(
+        txn: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput, Option<ExecutionTrace>) {
+        let vm = AptosVM::new(state_view);
+        let simulation_vm = AptosSimulationVM(vm);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let tracing_state_view = TracingStateView::new(state_view);
+        let (vm_status, output, tracer) = simulation_vm.simulate_signed_transaction_with_gas_meter(
+            &tracing_state_view.as_move_resolver(),
+            txn,
+            &log_context,
+            |gas_meter| ExecutionTracer::new(gas_meter, txn.payload()),
+        );
+        Self::finish_trace(vm_status, output, tracer, tracing_state_view)
+    }
+
+    fn finish_trace<S: StateView>(
+        vm_status: VMStatus,
+        output: TransactionOutputExt,
+        tracer: Option<ExecutionTracer<StandardGasMeter>>,
+        tracing_state_view: TracingStateView<S>,
+    ) -> (VMStatus, TransactionOutput, Option<ExecutionTrace>) {
+        // Materializing the aggregator deltas reads their values, which are part of the trace.
+        let output = output.into_transaction_output(&tracing_state_view);
+        let trace = tracer.map(|tracer| tracer.finish(tracing_state_view.into_reads(), &output));
+        (vm_status, output, trace)
+    }
+
     pub fn execute_view_function(
         state_view: &impl StateView,
         module_id: ModuleId,
@@ -1273,7 +1414,7 @@ impl AptosVM {
     ) -> Result<Vec<Vec<u8>>> {
         let vm = AptosVM::new(state_view);
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
-        let mut gas_meter = AptosGasMeter::new(
+        let mut gas_meter = StandardGasMeter::new(
             vm.0.get_gas_feature_version(),
             vm.0.get_gas_parameters(&log_context)?.clone(),
             vm.0.get_storage_gas_parameters(&log_context)?.clone(),
@@ -1318,6 +1459,15 @@ impl AptosVM {
         // Whether the prologue is run as part of tx simulation.
         is_simulation: bool,
     ) -> Result<(), VMStatus> {
+        if txn_data.has_secp256r1_ecdsa_signer()
+            && !self
+                .0
+                .get_features()
+                .is_enabled(FeatureFlag::SECP256R1_ECDSA_AUTHENTICATOR)
+        {
+            return Err(VMStatus::Error(StatusCode::FEATURE_UNDER_GATING));
+        }
+
         match payload {
             TransactionPayload::Script(_) => {
                 self.0.check_gas(storage, txn_data, log_context)?;
@@ -1347,7 +1497,10 @@ impl AptosVM {
 
             // Deprecated. Will be removed in the future.
             TransactionPayload::ModuleBundle(_module) => {
-                if MODULE_BUNDLE_DISALLOWED.load(Ordering::Relaxed) {
+                // The module prologue does not authenticate fee payers.
+                if MODULE_BUNDLE_DISALLOWED.load(Ordering::Relaxed)
+                    || txn_data.fee_payer().is_some()
+                {
                     return Err(VMStatus::Error(StatusCode::FEATURE_UNDER_GATING));
                 }
                 self.0.check_gas(storage, txn_data, log_context)?;
@@ -1610,6 +1763,30 @@ impl AptosSimulationVM {
         txn: &SignedTransaction,
         log_context: &AdapterLogSchema,
     ) -> (VMStatus, TransactionOutputExt) {
+        let (vm_status, output, _gas_meter) = self.simulate_signed_transaction_with_gas_meter(
+            storage,
+            txn,
+            log_context,
+            |gas_meter| gas_meter,
+        );
+        (vm_status, output)
+    }
+
+    /// Same as `simulate_signed_transaction`, with a gas meter built on top of the standard one by
+    /// `make_gas_meter`, e.g., to profile the gas usage. The gas meter is returned unless the
+    /// transaction is discarded before it is created.
+    fn simulate_signed_transaction_with_gas_meter<S: MoveResolverExt, G: AptosGasMeter>(
+        &self,
+        storage: &S,
+        txn: &SignedTransaction,
+        log_context: &AdapterLogSchema,
+        make_gas_meter: impl FnOnce(StandardGasMeter) -> G,
+    ) -> (VMStatus, TransactionOutputExt, Option<G>) {
+        let discard_error_vm_status = |err| {
+            let (vm_status, output) = discard_error_vm_status(err);
+            (vm_status, output, None)
+        };
+
         // simulation transactions should not carry valid signatures, otherwise malicious fullnodes
         // may execute them without user's explicit permission.
         if txn.signature_is_valid() {
@@ -1619,9 +1796,7 @@ impl AptosSimulationVM {
         // Revalidate the transaction.
         let txn_data = TransactionMetadata::new(txn);
         let resolver = self.0 .0.new_move_resolver(storage);
-        let mut session = self
-            .0
-            .new_session(&resolver, SessionId::txn_meta(&txn_data));
+        let mut session = self.0 .0.new_user_session(&resolver, &txn_data);
         if let Err(err) =
             self.validate_simulated_transaction(&mut session, storage, txn, &txn_data, log_context)
         {
@@ -1637,12 +1812,12 @@ impl AptosSimulationVM {
             Ok(s) => s,
         };
 
-        let mut gas_meter = AptosGasMeter::new(
+        let mut gas_meter = make_gas_meter(StandardGasMeter::new(
             self.0 .0.get_gas_feature_version(),
             gas_params.clone(),
             storage_gas_params.clone(),
             txn_data.max_gas_amount(),
-        );
+        ));
 
         let mut new_published_modules_loaded = false;
         let result = match txn.payload() {
@@ -1714,7 +1889,7 @@ impl AptosSimulationVM {
             ),
         };
 
-        match result {
+        let (vm_status, output) = match result {
             Ok(output) => output,
             Err(err) => {
                 // Invalidate the loader cache in case there was a new module loaded from a module
@@ -1727,7 +1902,8 @@ impl AptosSimulationVM {
                 };
                 let txn_status = TransactionStatus::from(err.clone());
                 if txn_status.is_discarded() {
-                    discard_error_vm_status(err)
+                    let (vm_status, output, _) = discard_error_vm_status(err);
+                    (vm_status, output)
                 } else {
                     let (vm_status, output) = self.0.failed_transaction_cleanup_and_keep_vm_status(
                         err,
@@ -1739,6 +1915,7 @@ impl AptosSimulationVM {
                     (vm_status, output)
                 }
             },
-        }
+        };
+        (vm_status, output, Some(gas_meter))
     }
 }
@@ -7,7 +7,10 @@ use crate::{
     data_cache::{MoveResolverWithVMMetadata, StorageAdapter},
     errors::{convert_epilogue_error, convert_prologue_error, expect_only_successful_execution},
     move_vm_ext::{MoveResolverExt, MoveVmExt, SessionExt, SessionId},
-    system_module_names::{MULTISIG_ACCOUNT_MODULE, VALIDATE_MULTISIG_TRANSACTION},
+    system_module_names::{
+        FEE_PAYER_SCRIPT_PROLOGUE_NAME, MULTISIG_ACCOUNT_MODULE, USER_EPILOGUE_GAS_PAYER_NAME,
+        VALIDATE_MULTISIG_TRANSACTION,
+    },
     transaction_metadata::TransactionMetadata,
 };
 use aptos_aggregator::transaction::TransactionOutputExt;
@@ -353,8 +356,9 @@ impl AptosVMImpl {
         Ok(())
     }
 
-    /// Run the prologue of a transaction by calling into either `SCRIPT_PROLOGUE_NAME` function
-    /// or `MULTI_AGENT_SCRIPT_PROLOGUE_NAME` function stored in the `ACCOUNT_MODULE` on chain.
+    /// Run the prologue of a transaction by calling into either `SCRIPT_PROLOGUE_NAME` function,
+    /// `MULTI_AGENT_SCRIPT_PROLOGUE_NAME` function or `FEE_PAYER_SCRIPT_PROLOGUE_NAME` function
+    /// stored in the `ACCOUNT_MODULE` on chain.
     pub(crate) fn run_script_prologue<S: MoveResolverExt>(
         &self,
         session: &mut SessionExt<S>,
@@ -374,7 +378,25 @@ impl AptosVMImpl {
             .iter()
             .map(|auth_key| MoveValue::vector_u8(auth_key.to_vec()))
             .collect();
-        let args = if txn_data.is_multi_agent() {
+        let args = if let Some(fee_payer) = txn_data.fee_payer() {
+            let fee_payer_auth_key = txn_data
+                .fee_payer_authentication_key
+                .clone()
+                .unwrap_or_default();
+            vec![
+                MoveValue::Signer(txn_data.sender),
+                MoveValue::U64(txn_sequence_number),
+                MoveValue::vector_u8(txn_authentication_key),
+                MoveValue::vector_address(txn_data.secondary_signers()),
+                MoveValue::Vector(secondary_auth_keys),
+                MoveValue::Address(fee_payer),
+                MoveValue::vector_u8(fee_payer_auth_key),
+                MoveValue::U64(txn_gas_price.into()),
+                MoveValue::U64(txn_max_gas_units.into()),
+                MoveValue::U64(txn_expiration_timestamp_secs),
+                MoveValue::U8(chain_id.id()),
+            ]
+        } else if txn_data.is_multi_agent() {
             vec![
                 MoveValue::Signer(txn_data.sender),
                 MoveValue::U64(txn_sequence_number),
@@ -398,10 +420,16 @@ impl AptosVMImpl {
                 MoveValue::vector_u8(txn_data.script_hash.clone()),
             ]
         };
-        let prologue_function_name = if txn_data.is_multi_agent() {
-            &transaction_validation.multi_agent_prologue_name
+        // The fee payer functions are not part of the on-chain `TransactionValidation` resource,
+        // whose layout is fixed, so they are called by their names in the framework.
+        let prologue_function_name = if txn_data.fee_payer().is_some() {
+            FEE_PAYER_SCRIPT_PROLOGUE_NAME
+        } else if txn_data.is_multi_agent() {
+            transaction_validation
+                .multi_agent_prologue_name
+                .as_ident_str()
         } else {
-            &transaction_validation.script_prologue_name
+            transaction_validation.script_prologue_name.as_ident_str()
         };
         session
             .execute_function_bypass_visibility(
@@ -494,8 +522,9 @@ impl AptosVMImpl {
             .or_else(|err| convert_prologue_error(transaction_validation, err, log_context))
     }
 
-    /// Run the epilogue of a transaction by calling into `EPILOGUE_NAME` function stored
-    /// in the `ACCOUNT_MODULE` on chain.
+    /// Run the epilogue of a transaction by calling into `USER_EPILOGUE_NAME` function, or
+    /// `USER_EPILOGUE_GAS_PAYER_NAME` function if the transaction has a fee payer, stored in the
+    /// `ACCOUNT_MODULE` on chain.
     pub(crate) fn run_success_epilogue<S: MoveResolverExt>(
         &self,
         session: &mut SessionExt<S>,
@@ -513,19 +542,37 @@ impl AptosVMImpl {
         let txn_sequence_number = txn_data.sequence_number();
         let txn_gas_price = txn_data.gas_unit_price();
         let txn_max_gas_units = txn_data.max_gas_amount();
-        session
-            .execute_function_bypass_visibility(
-                &transaction_validation.module_id(),
-                &transaction_validation.user_epilogue_name,
-                // TODO: Deprecate this once we remove gas currency on the Move side.
-                vec![],
-                serialize_values(&vec![
+        let (epilogue_function_name, args) = if let Some(fee_payer) = txn_data.fee_payer() {
+            (
+                USER_EPILOGUE_GAS_PAYER_NAME,
+                vec![
                     MoveValue::Signer(txn_data.sender),
+                    MoveValue::Address(fee_payer),
                     MoveValue::U64(txn_sequence_number),
                     MoveValue::U64(txn_gas_price.into()),
                     MoveValue::U64(txn_max_gas_units.into()),
                     MoveValue::U64(gas_remaining.into()),
-                ]),
+                ],
+            )
+        } else {
+            (
+                transaction_validation.user_epilogue_name.as_ident_str(),
+                vec![
+                    MoveValue::Signer(txn_data.sender),
+                    MoveValue::U64(txn_sequence_number),
+                    MoveValue::U64(txn_gas_price.into()),
+                    MoveValue::U64(txn_max_gas_units.into()),
+                    MoveValue::U64(gas_remaining.into()),
+                ],
+            )
+        };
+        session
+            .execute_function_bypass_visibility(
+                &transaction_validation.module_id(),
+                epilogue_function_name,
+                // TODO: Deprecate this once we remove gas currency on the Move side.
+                vec![],
+                serialize_values(&args),
                 &mut UnmeteredGasMeter,
             )
             .map(|_return_vals| ())
@@ -546,29 +593,43 @@ impl AptosVMImpl {
         let txn_sequence_number = txn_data.sequence_number();
         let txn_gas_price = txn_data.gas_unit_price();
         let txn_max_gas_units = txn_data.max_gas_amount();
-        session
-            .execute_function_bypass_visibility(
-                &transaction_validation.module_id(),
-                &transaction_validation.user_epilogue_name,
-                // TODO: Deprecate this once we remove gas currency on the Move side.
-                vec![],
-                serialize_values(&vec![
+        let (epilogue_function_name, args) = if let Some(fee_payer) = txn_data.fee_payer() {
+            (
+                USER_EPILOGUE_GAS_PAYER_NAME,
+                vec![
                     MoveValue::Signer(txn_data.sender),
+                    MoveValue::Address(fee_payer),
                     MoveValue::U64(txn_sequence_number),
                     MoveValue::U64(txn_gas_price.into()),
                     MoveValue::U64(txn_max_gas_units.into()),
                     MoveValue::U64(gas_remaining.into()),
-                ]),
+                ],
+            )
+        } else {
+            (
+                transaction_validation.user_epilogue_name.as_ident_str(),
+                vec![
+                    MoveValue::Signer(txn_data.sender),
+                    MoveValue::U64(txn_sequence_number),
+                    MoveValue::U64(txn_gas_price.into()),
+                    MoveValue::U64(txn_max_gas_units.into()),
+                    MoveValue::U64(gas_remaining.into()),
+                ],
+            )
+        };
+        session
+            .execute_function_bypass_visibility(
+                &transaction_validation.module_id(),
+                epilogue_function_name,
+                // TODO: Deprecate this once we remove gas currency on the Move side.
+                vec![],
+                serialize_values(&args),
                 &mut UnmeteredGasMeter,
             )
             .map(|_return_vals| ())
             .map_err(expect_no_verification_errors)
             .or_else(|e| {
-                expect_only_successful_execution(
-                    e,
-                    transaction_validation.user_epilogue_name.as_str(),
-                    log_context,
-                )
+                expect_only_successful_execution(e, epilogue_function_name.as_str(), log_context)
             })
     }
 
@@ -610,6 +671,20 @@ impl AptosVMImpl {
         self.move_vm.new_session(r, session_id)
     }
 
+    /// Create a session for the user transaction described by `txn_data`, whose natives can read
+    /// it through the transaction context.
+    pub fn new_user_session<'r, R: MoveResolverExt>(
+        &self,
+        r: &'r R,
+        txn_data: &TransactionMetadata,
+    ) -> SessionExt<'r, '_, R> {
+        self.move_vm.new_user_session(
+            r,
+            SessionId::txn_meta(txn_data),
+            txn_data.as_user_transaction_context(),
+        )
+    }
+
     pub fn load_module<'r, R: MoveResolverExt>(
         &self,
         module_id: &ModuleId,
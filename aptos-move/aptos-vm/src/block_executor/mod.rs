@@ -2,15 +2,18 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod partitioner;
 pub(crate) mod vm_wrapper;
 
 use crate::{
     adapter_common::{preprocess_transaction, PreprocessedTransaction},
-    block_executor::vm_wrapper::AptosExecutorTask,
+    block_executor::{partitioner::BlockPartition, vm_wrapper::AptosExecutorTask},
     counters::{
         BLOCK_EXECUTOR_CONCURRENCY, BLOCK_EXECUTOR_EXECUTE_BLOCK_SECONDS,
-        BLOCK_EXECUTOR_SIGNATURE_VERIFICATION_SECONDS,
+        BLOCK_EXECUTOR_SIGNATURE_VERIFICATION_SECONDS, BLOCK_PARTITIONING_FALLBACK_COUNT,
+        BLOCK_PARTITIONING_SECONDS,
     },
+    data_cache::AsMoveResolver,
     AptosVM,
 };
 use aptos_aggregator::{delta_change_set::DeltaOp, transaction::TransactionOutputExt};
@@ -24,6 +27,7 @@ use aptos_block_executor::{
 };
 use aptos_state_view::StateView;
 use aptos_types::{
+    on_chain_config::{FeatureFlag, Features, OnChainConfig},
     state_store::state_key::StateKey,
     transaction::{Transaction, TransactionOutput, TransactionStatus},
     write_set::{WriteOp, WriteSet, WriteSetMut},
@@ -105,28 +109,64 @@ impl BlockAptosVM {
             });
         drop(signature_verification_timer);
 
-        init_speculative_logs(signature_verified_block.len());
-
         BLOCK_EXECUTOR_CONCURRENCY.set(concurrency_level as i64);
         let executor = BlockExecutor::<PreprocessedTransaction, AptosExecutorTask<S>, S>::new(
             concurrency_level,
         );
 
-        let ret = executor
-            .execute_block(state_view, signature_verified_block, state_view)
-            .map(|results| {
-                // Process the outputs in parallel, combining delta writes with other writes.
-                RAYON_EXEC_POOL.install(|| {
-                    results
-                        .into_par_iter()
-                        .map(|(output, delta_writes)| {
-                            output      // AptosTransactionOutput
-                            .into()     // TransactionOutputExt
-                            .output_with_delta_writes(WriteSetMut::new(delta_writes))
-                        })
-                        .collect()
-                })
-            });
+        // If the read/write set analysis is available and block partitioning is enabled on
+        // chain, try executing the block reordered by predicted conflicts first, and fall back
+        // to the original order if the predictions turn out to be wrong.
+        let partition = match AptosVM::get_read_write_set_analysis() {
+            Some(analysis)
+                if concurrency_level > 1
+                    && Features::fetch_config(&state_view.as_move_resolver())
+                        .unwrap_or_default()
+                        .is_enabled(FeatureFlag::BLOCK_PARTITIONING) =>
+            {
+                let _timer = BLOCK_PARTITIONING_SECONDS.start_timer();
+                BlockPartition::new(analysis, state_view, &signature_verified_block)
+            },
+            _ => None,
+        };
+        let reordered_results = partition.and_then(|partition| {
+            init_speculative_logs(signature_verified_block.len());
+            let results = executor
+                .execute_block_with_read_sets(
+                    state_view,
+                    partition.reorder(&signature_verified_block),
+                    state_view,
+                )
+                .ok()
+                .and_then(|(results, read_sets)| {
+                    partition.restore_order(state_view, results, read_sets)
+                });
+            if results.is_none() {
+                BLOCK_PARTITIONING_FALLBACK_COUNT.inc();
+            }
+            results
+        });
+
+        let ret = match reordered_results {
+            Some(results) => Ok(results),
+            None => {
+                init_speculative_logs(signature_verified_block.len());
+                executor.execute_block(state_view, signature_verified_block, state_view)
+            },
+        }
+        .map(|results| {
+            // Process the outputs in parallel, combining delta writes with other writes.
+            RAYON_EXEC_POOL.install(|| {
+                results
+                    .into_par_iter()
+                    .map(|(output, delta_writes)| {
+                        output      // AptosTransactionOutput
+                        .into()     // TransactionOutputExt
+                        .output_with_delta_writes(WriteSetMut::new(delta_writes))
+                    })
+                    .collect()
+            })
+        });
 
         flush_speculative_logs();
 
@@ -0,0 +1,524 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional pass which reorders a block before parallel execution, using the read/write sets
+//! predicted by the read/write set analysis. Transactions predicted to conflict are grouped
+//! together, and the groups are interleaved, so that conflicting transactions end up far apart
+//! in the block and Block-STM aborts less.
+//!
+//! Transactions of different groups are predicted not to conflict, so executing the reordered
+//! block yields the same outputs as executing the original one. Predictions can be wrong though,
+//! e.g., for accesses to table items, which the analysis does not cover, so the keys the moved
+//! transactions actually read and wrote are checked against the predictions, and the block must
+//! be executed again in its original order if they do not match.
+
+use crate::{
+    adapter_common::{PreprocessedTransaction, VMAdapter},
+    block_executor::AptosTransactionOutput,
+    data_cache::AsMoveResolver,
+    move_vm_ext::MoveResolverExt,
+    read_write_set_analysis::ReadWriteSetAnalysis,
+    AptosVM,
+};
+use aptos_aggregator::delta_change_set::{deserialize, serialize};
+use aptos_block_executor::task::TransactionOutput as BlockExecutorTransactionOutput;
+use aptos_state_view::StateView;
+use aptos_types::{
+    access_path::AccessPath,
+    account_config::CORE_CODE_ADDRESS,
+    state_store::state_key::StateKey,
+    transaction::{SignedTransaction, TransactionStatus},
+    write_set::{TransactionWrite, WriteOp},
+};
+use move_core_types::{ident_str, identifier::IdentStr, language_storage::ResourceKey};
+use read_write_set_dynamic::NormalizedReadWriteSetAnalysis;
+use std::collections::{HashMap, HashSet};
+
+/// Framework resources every transaction updates in its epilogue through aggregators only, e.g.,
+/// the supply of the gas coin when fees are burnt. Aggregator updates commute, so these do not
+/// make transactions conflict, unless the payload of a transaction reads them. Aggregators can
+/// only be created by the framework, which keeps them in these resources.
+const AGGREGATABLE_RESOURCES: &[(&IdentStr, &IdentStr)] = &[
+    (ident_str!("coin"), ident_str!("CoinInfo")),
+    (
+        ident_str!("transaction_fee"),
+        ident_str!("CollectedFeesPerBlock"),
+    ),
+];
+
+fn is_aggregatable(key: &ResourceKey) -> bool {
+    let type_ = key.type_();
+    type_.address == CORE_CODE_ADDRESS
+        && AGGREGATABLE_RESOURCES.iter().any(|(module, name)| {
+            type_.module.as_ident_str() == *module && type_.name.as_ident_str() == *name
+        })
+}
+
+fn resource_state_key(key: &ResourceKey) -> Option<StateKey> {
+    AccessPath::resource_access_path(key.address(), key.type_().clone())
+        .ok()
+        .map(StateKey::access_path)
+}
+
+/// Keys a user transaction is predicted to read and write, aggregatable resources aside.
+struct PredictedAccesses {
+    reads: HashSet<StateKey>,
+    writes: HashSet<StateKey>,
+}
+
+impl PredictedAccesses {
+    /// Returns `None` if the accesses of the transaction cannot be predicted, or if its payload
+    /// reads aggregatable resources.
+    fn new<R: MoveResolverExt>(
+        analysis: &ReadWriteSetAnalysis<R>,
+        txn: &SignedTransaction,
+    ) -> Option<Self> {
+        let (payload_reads, _) = analysis
+            .get_keys_user_transaction_payload(txn, false)
+            .ok()?;
+        if payload_reads.iter().any(is_aggregatable) {
+            return None;
+        }
+
+        let (reads, writes) = analysis.get_partial_keys_user_transaction(txn).ok()?;
+        let to_state_keys = |keys: Vec<ResourceKey>| {
+            keys.into_iter()
+                .filter(|key| !is_aggregatable(key))
+                .map(|key| resource_state_key(&key))
+                .collect::<Option<HashSet<_>>>()
+        };
+        Some(Self {
+            reads: to_state_keys(reads)?,
+            writes: to_state_keys(writes)?,
+        })
+    }
+}
+
+/// Order in which to execute a block, along with what its user transactions are predicted to
+/// access, to check the read sets and outputs against.
+pub(crate) struct BlockPartition {
+    /// Indices into the original block, in execution order.
+    order: Vec<usize>,
+    /// Predicted accesses by index into the original block, `None` for the transactions which
+    /// keep their position.
+    predictions: Vec<Option<PredictedAccesses>>,
+}
+
+impl BlockPartition {
+    /// Partitions the block into groups of transactions predicted to conflict. Transactions
+    /// whose accesses cannot be predicted, and the non-user ones, keep their position and no
+    /// transaction is moved across them. Returns `None` if the order would not change.
+    pub(crate) fn new<S: StateView>(
+        analysis: &NormalizedReadWriteSetAnalysis,
+        state_view: &S,
+        block: &[PreprocessedTransaction],
+    ) -> Option<Self> {
+        let resolver = state_view.as_move_resolver();
+        let analysis = ReadWriteSetAnalysis::new(analysis, &resolver);
+        let predictions: Vec<_> = block
+            .iter()
+            .map(|txn| match txn {
+                PreprocessedTransaction::UserTransaction(txn) => {
+                    PredictedAccesses::new(&analysis, txn)
+                },
+                _ => None,
+            })
+            .collect();
+
+        let mut order = Vec::with_capacity(block.len());
+        let mut segment_start = 0;
+        for (idx, prediction) in predictions.iter().enumerate() {
+            if prediction.is_none() {
+                order.extend(interleave_groups(&predictions, segment_start..idx));
+                order.push(idx);
+                segment_start = idx + 1;
+            }
+        }
+        order.extend(interleave_groups(&predictions, segment_start..block.len()));
+
+        if order.iter().enumerate().all(|(pos, idx)| pos == *idx) {
+            return None;
+        }
+        Some(Self { order, predictions })
+    }
+
+    /// Returns the block in execution order.
+    pub(crate) fn reorder<T: Clone>(&self, block: &[T]) -> Vec<T> {
+        self.order.iter().map(|idx| block[*idx].clone()).collect()
+    }
+
+    /// Takes the outputs of the reordered block back to the original order, given the keys
+    /// each transaction read in execution order. Delta writes are materialized again, in the
+    /// original order, on top of storage and of the writes of the transactions which kept their
+    /// position. Returns `None` if the outputs may differ from the ones of the original block,
+    /// i.e., if a transaction wrote keys it was not predicted to write, read keys modified by
+    /// another moved transaction it was not predicted to access, or if the rest of the block was
+    /// skipped. A moved transaction which ends the epoch makes the block fall back too, as the
+    /// transactions following it in the original order, but executed before it, would have to be
+    /// skipped.
+    pub(crate) fn restore_order<S: StateView>(
+        &self,
+        state_view: &S,
+        results: Vec<(AptosTransactionOutput, Vec<(StateKey, WriteOp)>)>,
+        read_sets: Vec<Vec<StateKey>>,
+    ) -> Option<Vec<(AptosTransactionOutput, Vec<(StateKey, WriteOp)>)>> {
+        let mut outputs: Vec<Option<AptosTransactionOutput>> =
+            (0..self.order.len()).map(|_| None).collect();
+        let mut reads: Vec<Vec<StateKey>> = vec![vec![]; self.order.len()];
+        for ((idx, (output, _delta_writes)), read_set) in
+            self.order.iter().zip(results).zip(read_sets)
+        {
+            outputs[*idx] = Some(output);
+            reads[*idx] = read_set;
+        }
+        if !self.reads_match_predictions(&outputs, &reads) {
+            return None;
+        }
+
+        // Values of the aggregators updated so far, and writes of the transactions which kept
+        // their position. User transactions which moved can only write the resources they were
+        // predicted to, never aggregators.
+        let mut aggregator_values: HashMap<StateKey, u128> = HashMap::new();
+        let mut fixed_writes: HashMap<StateKey, WriteOp> = HashMap::new();
+        let mut ret = Vec::with_capacity(outputs.len());
+        for (output, prediction) in outputs.into_iter().zip(&self.predictions) {
+            let output = output?;
+            let txn_output = output.0.txn_output();
+            if let TransactionStatus::Retry = txn_output.status() {
+                return None;
+            }
+            match prediction {
+                Some(prediction) => {
+                    if AptosVM::should_restart_execution(txn_output)
+                        || txn_output
+                            .write_set()
+                            .iter()
+                            .any(|(key, _)| !prediction.writes.contains(key))
+                    {
+                        return None;
+                    }
+                },
+                None => {
+                    for (key, op) in txn_output.write_set() {
+                        aggregator_values.remove(key);
+                        fixed_writes.insert(key.clone(), op.clone());
+                    }
+                },
+            }
+
+            let mut delta_writes = vec![];
+            for (key, delta) in output.get_deltas() {
+                let value = match (aggregator_values.get(&key), fixed_writes.get(&key)) {
+                    (Some(value), _) => *value,
+                    (None, Some(op)) => deserialize(&op.extract_raw_bytes()?),
+                    (None, None) => deserialize(&state_view.get_state_value_bytes(&key).ok()??),
+                };
+                let value = delta.apply_to(value).ok()?;
+                aggregator_values.insert(key.clone(), value);
+                delta_writes.push((key, WriteOp::Modification(serialize(&value))));
+            }
+            ret.push((output, delta_writes));
+        }
+        Some(ret)
+    }
+
+    /// Checks that the moved transactions only read keys modified by other moved transactions
+    /// if they were predicted to access them. Transactions predicted to access the same key
+    /// keep their relative order, and the ones which kept their position are not moved across,
+    /// so such reads observe the same values as in the original block. Aggregators updated by
+    /// several moved transactions are never predicted, so reading them fails the check.
+    fn reads_match_predictions(
+        &self,
+        outputs: &[Option<AptosTransactionOutput>],
+        reads: &[Vec<StateKey>],
+    ) -> bool {
+        let modified_keys = |output: &AptosTransactionOutput| -> HashSet<StateKey> {
+            output
+                .0
+                .txn_output()
+                .write_set()
+                .iter()
+                .map(|(key, _)| key.clone())
+                .chain(output.get_deltas().into_iter().map(|(key, _)| key))
+                .collect()
+        };
+        let mut modified_by_moved: Vec<HashSet<StateKey>> = vec![HashSet::new(); outputs.len()];
+        let mut num_modifiers: HashMap<StateKey, usize> = HashMap::new();
+        for ((output, prediction), modified) in outputs
+            .iter()
+            .zip(&self.predictions)
+            .zip(&mut modified_by_moved)
+        {
+            if let (Some(output), Some(_)) = (output, prediction) {
+                *modified = modified_keys(output);
+                for key in modified.iter() {
+                    *num_modifiers.entry(key.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        self.predictions
+            .iter()
+            .zip(reads)
+            .zip(&modified_by_moved)
+            .all(|((prediction, reads), modified)| match prediction {
+                Some(prediction) => reads.iter().all(|key| {
+                    prediction.reads.contains(key)
+                        || prediction.writes.contains(key)
+                        || num_modifiers.get(key).copied().unwrap_or(0)
+                            <= usize::from(modified.contains(key))
+                }),
+                None => true,
+            })
+    }
+}
+
+/// Groups the transactions of the segment, all of which have predicted accesses, and returns
+/// them in round robin order over the groups, the order within each group being preserved.
+fn interleave_groups(
+    predictions: &[Option<PredictedAccesses>],
+    segment: std::ops::Range<usize>,
+) -> Vec<usize> {
+    let mut groups = UnionFind::new(segment.len());
+    let mut writers: HashMap<&StateKey, usize> = HashMap::new();
+    let mut accessors: HashMap<&StateKey, Vec<usize>> = HashMap::new();
+    for (pos, idx) in segment.clone().enumerate() {
+        let accesses = predictions[idx]
+            .as_ref()
+            .expect("Segments only contain predicted transactions");
+        for key in &accesses.writes {
+            writers.entry(key).or_insert(pos);
+        }
+        for key in accesses.reads.iter().chain(&accesses.writes) {
+            accessors.entry(key).or_default().push(pos);
+        }
+    }
+    // All the transactions accessing a key someone writes conflict with each other.
+    for (key, writer) in writers {
+        for pos in &accessors[key] {
+            groups.union(writer, *pos);
+        }
+    }
+
+    let mut group_members: Vec<Vec<usize>> = vec![];
+    let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+    for (pos, idx) in segment.enumerate() {
+        let root = groups.find(pos);
+        let group = *group_of_root.entry(root).or_insert_with(|| {
+            group_members.push(vec![]);
+            group_members.len() - 1
+        });
+        group_members[group].push(idx);
+    }
+
+    let max_group_len = group_members.iter().map(Vec::len).max().unwrap_or(0);
+    (0..max_group_len)
+        .flat_map(|i| {
+            group_members
+                .iter()
+                .filter_map(move |members| members.get(i).copied())
+        })
+        .collect()
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parents[x] != x {
+            self.parents[x] = self.parents[self.parents[x]];
+            x = self.parents[x];
+        }
+        x
+    }
+
+    fn union(&mut self, x: usize, y: usize) {
+        let (x, y) = (self.find(x), self.find(y));
+        if x != y {
+            self.parents[x.max(y)] = x.min(y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_state_view::TStateView;
+    use aptos_types::{
+        account_address::AccountAddress,
+        contract_event::ContractEvent,
+        on_chain_config::new_epoch_event_key,
+        state_store::{state_storage_usage::StateStorageUsage, state_value::StateValue},
+        transaction::{ExecutionStatus, TransactionOutput},
+        write_set::WriteSetMut,
+    };
+    use move_core_types::language_storage::{StructTag, TypeTag};
+
+    fn key(i: u8) -> StateKey {
+        StateKey::raw(vec![i])
+    }
+
+    fn accesses(reads: &[u8], writes: &[u8]) -> Option<PredictedAccesses> {
+        Some(PredictedAccesses {
+            reads: reads.iter().copied().map(key).collect(),
+            writes: writes.iter().copied().map(key).collect(),
+        })
+    }
+
+    #[test]
+    fn test_interleave_groups() {
+        let predictions = vec![
+            accesses(&[0], &[1]),
+            accesses(&[0], &[1]),
+            accesses(&[0], &[2]),
+            accesses(&[2], &[]),
+            accesses(&[0], &[3]),
+        ];
+        // {0, 1} write 1, {2, 3} access 2, and 4 only shares reads with the others.
+        assert_eq!(interleave_groups(&predictions, 0..5), vec![0, 2, 4, 1, 3]);
+        assert_eq!(interleave_groups(&predictions, 1..4), vec![1, 2, 3]);
+        assert_eq!(interleave_groups(&predictions, 2..2), Vec::<usize>::new());
+    }
+
+    fn output(writes: &[u8]) -> Option<AptosTransactionOutput> {
+        output_with_events(writes, vec![])
+    }
+
+    fn output_with_events(
+        writes: &[u8],
+        events: Vec<ContractEvent>,
+    ) -> Option<AptosTransactionOutput> {
+        let write_set = WriteSetMut::new(
+            writes
+                .iter()
+                .map(|i| (key(*i), WriteOp::Modification(vec![*i])))
+                .collect(),
+        )
+        .freeze()
+        .unwrap();
+        Some(AptosTransactionOutput::new(
+            TransactionOutput::new(
+                write_set,
+                events,
+                0,
+                TransactionStatus::Keep(ExecutionStatus::Success),
+            )
+            .into(),
+        ))
+    }
+
+    #[test]
+    fn test_reads_match_predictions() {
+        let partition = BlockPartition {
+            order: vec![0, 2, 1, 3],
+            predictions: vec![
+                accesses(&[], &[1]),
+                accesses(&[2], &[]),
+                accesses(&[], &[3]),
+                None,
+            ],
+        };
+        let outputs = vec![output(&[1]), output(&[]), output(&[3]), output(&[4])];
+        let keys = |ids: &[u8]| -> Vec<StateKey> { ids.iter().copied().map(key).collect() };
+
+        // Predicted reads, own writes, and keys no moved transaction modifies.
+        let reads = vec![keys(&[1]), keys(&[2, 5]), keys(&[3, 4]), keys(&[1, 3])];
+        assert!(partition.reads_match_predictions(&outputs, &reads));
+        // Transaction 1 reads a key moved transaction 2 writes.
+        let reads = vec![keys(&[]), keys(&[3]), keys(&[]), keys(&[])];
+        assert!(!partition.reads_match_predictions(&outputs, &reads));
+        // Transaction 2 reads a key moved transaction 0 writes.
+        let reads = vec![keys(&[]), keys(&[]), keys(&[1]), keys(&[])];
+        assert!(!partition.reads_match_predictions(&outputs, &reads));
+    }
+
+    struct EmptyView;
+
+    impl TStateView for EmptyView {
+        type Key = StateKey;
+
+        fn get_state_value(&self, _state_key: &StateKey) -> anyhow::Result<Option<StateValue>> {
+            Ok(None)
+        }
+
+        fn is_genesis(&self) -> bool {
+            false
+        }
+
+        fn get_usage(&self) -> anyhow::Result<StateStorageUsage> {
+            Ok(StateStorageUsage::new_untracked())
+        }
+    }
+
+    #[test]
+    fn test_restore_order_rejects_moved_reconfiguration() {
+        let partition = BlockPartition {
+            order: vec![1, 0],
+            predictions: vec![accesses(&[], &[1]), accesses(&[], &[2])],
+        };
+        let results = |outputs: Vec<Option<AptosTransactionOutput>>| {
+            outputs
+                .into_iter()
+                .map(|output| (output.unwrap(), vec![]))
+                .collect()
+        };
+
+        let restored = partition
+            .restore_order(
+                &EmptyView,
+                results(vec![output(&[2]), output(&[1])]),
+                vec![vec![]; 2],
+            )
+            .unwrap();
+        let restored_writes: Vec<Vec<StateKey>> = restored
+            .iter()
+            .map(|(output, _)| {
+                output
+                    .get_writes()
+                    .into_iter()
+                    .map(|(state_key, _)| state_key)
+                    .collect()
+            })
+            .collect();
+        assert_eq!(restored_writes, vec![vec![key(1)], vec![key(2)]]);
+
+        // Transaction 1 ends the epoch, so transaction 0 should not have been executed before it.
+        let new_epoch_event = ContractEvent::new(new_epoch_event_key(), 0, TypeTag::Bool, vec![]);
+        assert!(partition
+            .restore_order(
+                &EmptyView,
+                results(vec![
+                    output_with_events(&[2], vec![new_epoch_event]),
+                    output(&[1]),
+                ]),
+                vec![vec![]; 2],
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_is_aggregatable() {
+        let coin_info: StructTag = "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>"
+            .parse()
+            .unwrap();
+        let coin_store: StructTag = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+            .parse()
+            .unwrap();
+        assert!(is_aggregatable(&ResourceKey::new(
+            CORE_CODE_ADDRESS,
+            coin_info
+        )));
+        assert!(!is_aggregatable(&ResourceKey::new(
+            AccountAddress::ONE,
+            coin_store
+        )));
+    }
+}
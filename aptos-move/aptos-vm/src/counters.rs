@@ -38,6 +38,27 @@ pub static BLOCK_EXECUTOR_SIGNATURE_VERIFICATION_SECONDS: Lazy<Histogram> = Lazy
     .unwrap()
 });
 
+pub static BLOCK_PARTITIONING_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "block_partitioning_seconds",
+        // metric description
+        "The time spent in seconds for partitioning a block by predicted read/write sets",
+        exponential_buckets(/*start=*/ 1e-3, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+/// Count the number of reordered blocks which had to be executed again in their original order,
+/// because the predicted read/write sets were wrong.
+pub static BLOCK_PARTITIONING_FALLBACK_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_vm_block_partitioning_fallback_count",
+        "Number of reordered blocks executed again in their original order",
+    )
+    .unwrap()
+});
+
 /// Count the number of transactions that brake invariants of VM.
 pub static TRANSACTIONS_INVARIANT_VIOLATION: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
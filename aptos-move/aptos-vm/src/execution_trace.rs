@@ -0,0 +1,889 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opt-in tracing of the execution of user transactions, to understand how a transaction got to
+//! its outcome, e.g., which of the calls it made aborted and with which arguments.
+//!
+//! The calls and the global storage operations are recorded by [`ExecutionTracer`], a gas meter
+//! wrapping the one charging the transaction, as the gas meter is the VM's only hook into the
+//! execution of Move code. The state items read are recorded by [`TracingStateView`].
+//!
+//! The gas meter is not given the operand stack of the interpreter, so the tracer mirrors it from
+//! the values it is charged for, to know what functions return and which addresses global storage
+//! is accessed at. Values it isn't charged for, e.g., results of arithmetic, are rendered as
+//! [`UNKNOWN_VALUE`].
+
+use anyhow::Result;
+use aptos_gas::{
+    AptosGasMeter, AptosGasParameters, FeePerGasUnit, InternalGas, NumArgs, NumBytes,
+    StorageGasParameters,
+};
+use aptos_state_view::{StateViewId, TStateView};
+use aptos_types::{
+    contract_event::ContractEvent,
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    transaction::{
+        Multisig, MultisigTransactionPayload, TransactionOutput, TransactionPayload,
+        TransactionStatus,
+    },
+    write_set::WriteOp,
+};
+use move_binary_format::errors::{PartialVMResult, VMResult};
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId, u256::U256};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView, ValueVisitor},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Mutex};
+
+/// Trace of the execution of a user transaction.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ExecutionTrace {
+    /// The payload, along with all the calls it made. Prologue and epilogue are not traced.
+    pub call_trace: CallTrace,
+    /// State items read by the transaction, including the prologue and the epilogue, along with
+    /// their values before the transaction, ordered by key.
+    pub reads: Vec<(StateKey, Option<StateValue>)>,
+    /// State items written by the transaction, including the aggregator deltas.
+    pub writes: Vec<(StateKey, WriteOp)>,
+    pub events: Vec<ContractEvent>,
+    pub status: TransactionStatus,
+}
+
+/// A function call, or the payload for the outermost one.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CallTrace {
+    /// Name of the function, e.g., "0x1::coin::transfer", or of the payload, e.g., "script".
+    pub function: String,
+    pub ty_args: Vec<String>,
+    /// Arguments, rendered with [`render_value`], or as hex encoded BCS bytes for the payload.
+    pub args: Vec<String>,
+    pub is_native: bool,
+    /// Values returned, if the call returned, rendered like the arguments.
+    pub return_values: Option<Vec<String>>,
+    /// Whether the call returned, as opposed to aborting or running out of gas.
+    pub returned: bool,
+    /// What the function did, in order.
+    pub steps: Vec<CallStep>,
+}
+
+impl CallTrace {
+    fn new(function: String, ty_args: Vec<String>, args: Vec<String>) -> Self {
+        Self {
+            function,
+            ty_args,
+            args,
+            is_native: false,
+            return_values: None,
+            returned: false,
+            steps: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum CallStep {
+    Call(CallTrace),
+    GlobalStorage(GlobalStorageAccess),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum GlobalStorageOp {
+    BorrowGlobal,
+    BorrowGlobalMut,
+    Exists,
+    MoveFrom,
+    MoveTo,
+}
+
+/// An access to a resource in global storage.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GlobalStorageAccess {
+    pub op: GlobalStorageOp,
+    /// Address the resource is stored at, unless it was computed out of sight of the tracer.
+    pub address: Option<AccountAddress>,
+    pub resource_type: String,
+    /// Value of the resource, if it was loaded from storage by this access.
+    pub loaded: Option<String>,
+    /// Value moved to or from global storage.
+    pub value: Option<String>,
+    /// Whether the operation succeeded, or whether the resource exists for `exists`.
+    pub success: bool,
+}
+
+/// A gas meter that wraps another one and traces the calls made and the global storage accessed.
+/// The underlying gas meter does all the charging, so tracing doesn't change the outcome of the
+/// transaction.
+pub struct ExecutionTracer<G> {
+    base: G,
+
+    /// The call stack, the bottom of which is the payload and is never popped.
+    frames: Vec<CallTrace>,
+    /// The operand stack of each frame in `frames`, as far as the tracer can tell.
+    operands: Vec<Vec<String>>,
+    /// Resource loaded from storage and yet to be attributed to the access that loaded it.
+    pending_load: Option<Option<String>>,
+}
+
+impl<G> ExecutionTracer<G> {
+    pub fn new(base: G, payload: &TransactionPayload) -> Self {
+        let root = match payload {
+            TransactionPayload::EntryFunction(entry_function)
+            | TransactionPayload::Multisig(Multisig {
+                transaction_payload: Some(MultisigTransactionPayload::EntryFunction(entry_function)),
+                ..
+            }) => CallTrace::new(
+                function_name(entry_function.module(), entry_function.function().as_str()),
+                entry_function
+                    .ty_args()
+                    .iter()
+                    .map(|ty_arg| ty_arg.to_string())
+                    .collect(),
+                entry_function
+                    .args()
+                    .iter()
+                    .map(|arg| format!("0x{}", hex::encode(arg)))
+                    .collect(),
+            ),
+            TransactionPayload::Script(script) => CallTrace::new(
+                "script".to_string(),
+                script
+                    .ty_args()
+                    .iter()
+                    .map(|ty_arg| ty_arg.to_string())
+                    .collect(),
+                script
+                    .args()
+                    .iter()
+                    .map(|arg| format!("{:?}", arg))
+                    .collect(),
+            ),
+            TransactionPayload::Multisig(_) => {
+                CallTrace::new("multisig".to_string(), vec![], vec![])
+            },
+            TransactionPayload::ModuleBundle(_) => {
+                CallTrace::new("module bundle".to_string(), vec![], vec![])
+            },
+        };
+        Self {
+            base,
+            frames: vec![root],
+            operands: vec![vec![]],
+            pending_load: None,
+        }
+    }
+
+    fn current_frame(&mut self) -> &mut CallTrace {
+        self.frames
+            .last_mut()
+            .expect("The outermost frame is never popped.")
+    }
+
+    fn current_operands(&mut self) -> &mut Vec<String> {
+        self.operands
+            .last_mut()
+            .expect("The outermost frame is never popped.")
+    }
+
+    fn push_operands(&mut self, vals: impl IntoIterator<Item = String>) {
+        self.current_operands().extend(vals)
+    }
+
+    fn push_unknown_operand(&mut self) {
+        self.push_operands([UNKNOWN_VALUE.to_string()])
+    }
+
+    /// Pops the top `n` operands, in the order they were pushed.
+    fn pop_operands(&mut self, n: usize) -> Vec<String> {
+        let operands = self.current_operands();
+        let popped = operands.split_off(operands.len().saturating_sub(n));
+        // The mirrored stack can only be short if the tracer got an instruction wrong, in which
+        // case the missing operands are unknown rather than taken from another frame.
+        let mut missing = vec![UNKNOWN_VALUE.to_string(); n - popped.len()];
+        missing.extend(popped);
+        missing
+    }
+
+    fn pop_operand(&mut self) -> String {
+        self.pop_operands(1).remove(0)
+    }
+
+    fn push_frame(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: Vec<String>,
+        args: Vec<String>,
+    ) {
+        self.pop_operands(args.len());
+        self.frames.push(CallTrace::new(
+            function_name(module_id, func_name),
+            ty_args,
+            args,
+        ));
+        self.operands.push(vec![]);
+    }
+
+    fn pop_frame(&mut self) {
+        if self.frames.len() > 1 {
+            let frame = self.frames.pop().expect("Checked above.");
+            self.operands.pop();
+            self.current_frame().steps.push(CallStep::Call(frame));
+        }
+    }
+
+    /// Pops the current frame, which returned the given values to its caller.
+    fn return_from_frame(&mut self, return_values: Vec<String>) {
+        if self.frames.len() > 1 {
+            let frame = self.current_frame();
+            frame.returned = true;
+            frame.return_values = Some(return_values.clone());
+            self.pop_frame();
+            self.push_operands(return_values);
+        }
+    }
+
+    fn record_global_storage_access(
+        &mut self,
+        op: GlobalStorageOp,
+        address: &str,
+        ty: &impl TypeView,
+        value: Option<String>,
+        success: bool,
+    ) {
+        let access = GlobalStorageAccess {
+            op,
+            address: rendered_address(address),
+            resource_type: ty.to_type_tag().to_string(),
+            loaded: self.pending_load.take().flatten(),
+            value,
+            success,
+        };
+        self.current_frame()
+            .steps
+            .push(CallStep::GlobalStorage(access));
+    }
+
+    /// Returns the trace, given the output of the transaction and the state items it read.
+    pub fn finish(
+        mut self,
+        reads: Vec<(StateKey, Option<StateValue>)>,
+        output: &TransactionOutput,
+    ) -> ExecutionTrace {
+        // Frames left on the stack are the ones which didn't return.
+        while self.frames.len() > 1 {
+            self.pop_frame();
+        }
+        let mut call_trace = self
+            .frames
+            .pop()
+            .expect("The outermost frame is never popped.");
+        call_trace.returned =
+            matches!(output.status(), TransactionStatus::Keep(status) if status.is_success());
+
+        ExecutionTrace {
+            call_trace,
+            reads,
+            writes: output
+                .write_set()
+                .iter()
+                .map(|(key, op)| (key.clone(), op.clone()))
+                .collect(),
+            events: output.events().to_vec(),
+            status: output.status().clone(),
+        }
+    }
+}
+
+fn function_name(module_id: &ModuleId, func_name: &str) -> String {
+    format!("{}::{}", module_id.short_str_lossless(), func_name)
+}
+
+/// Parses the address out of a rendered address, or of a rendered reference to a signer.
+fn rendered_address(rendered: &str) -> Option<AccountAddress> {
+    let rendered = rendered
+        .trim_start_matches(|c| c == '&' || c == '{')
+        .trim_end_matches('}');
+    AccountAddress::from_hex_literal(rendered).ok()
+}
+
+impl<G: GasMeter> GasMeter for ExecutionTracer<G> {
+    fn balance_internal(&self) -> InternalGas {
+        self.base.balance_internal()
+    }
+
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        use SimpleInstruction::*;
+
+        match instr {
+            // The operands of the frame are returned when it is dropped.
+            Nop | Branch | Ret => (),
+            BrTrue | BrFalse | Abort => {
+                self.pop_operand();
+            },
+            LdTrue => self.push_operands([true.to_string()]),
+            LdFalse => self.push_operands([false.to_string()]),
+            LdU8 | LdU16 | LdU32 | LdU64 | LdU128 | LdU256 | ImmBorrowLoc | MutBorrowLoc => {
+                self.push_unknown_operand()
+            },
+            // A frozen reference renders like the mutable one, and a successful cast doesn't
+            // change the number.
+            FreezeRef | CastU8 | CastU16 | CastU32 | CastU64 | CastU128 | CastU256 => (),
+            ImmBorrowField
+            | MutBorrowField
+            | ImmBorrowFieldGeneric
+            | MutBorrowFieldGeneric
+            | Not => {
+                self.pop_operand();
+                self.push_unknown_operand();
+            },
+            Add | Sub | Mul | Mod | Div | BitOr | BitAnd | Xor | Shl | Shr | Or | And | Lt | Gt
+            | Le | Ge => {
+                self.pop_operands(2);
+                self.push_unknown_operand();
+            },
+        }
+        self.base.charge_simple_instr(instr)
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.current_frame().is_native = true;
+        self.base
+            .charge_native_function_before_execution(ty_args, args)
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        let ret_vals = ret_vals.map(|ret_vals| ret_vals.collect::<Vec<_>>());
+        self.current_frame().is_native = true;
+        // Native functions are called like other functions, but their frames are not dropped
+        // as they don't have any, so this is where the frame ends.
+        match &ret_vals {
+            Some(ret_vals) => self.return_from_frame(ret_vals.iter().map(render_value).collect()),
+            None => self.pop_frame(),
+        }
+        self.base
+            .charge_native_function(amount, ret_vals.map(|ret_vals| ret_vals.into_iter()))
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        self.pending_load = Some(loaded.as_ref().map(|(_size, val)| render_value(val)));
+        self.base.charge_load_resource(loaded)
+    }
+
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.pop_operand();
+        self.base.charge_pop(popped_val)
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let args: Vec<_> = args.collect();
+        let rendered_args = args.iter().map(render_value).collect();
+        self.base
+            .charge_call(module_id, func_name, args.into_iter(), num_locals)?;
+        self.push_frame(module_id, func_name, vec![], rendered_args);
+        Ok(())
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let ty_args: Vec<_> = ty_args.collect();
+        let rendered_ty_args = ty_args
+            .iter()
+            .map(|ty_arg| ty_arg.to_type_tag().to_string())
+            .collect();
+        let args: Vec<_> = args.collect();
+        let rendered_args = args.iter().map(render_value).collect();
+        self.base.charge_call_generic(
+            module_id,
+            func_name,
+            ty_args.into_iter(),
+            args.into_iter(),
+            num_locals,
+        )?;
+        self.push_frame(module_id, func_name, rendered_ty_args, rendered_args);
+        Ok(())
+    }
+
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.base.charge_ld_const(size)
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.push_operands([render_value(&val)]);
+        self.base.charge_ld_const_after_deserialization(val)
+    }
+
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.push_operands([render_value(&val)]);
+        self.base.charge_copy_loc(val)
+    }
+
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.push_operands([render_value(&val)]);
+        self.base.charge_move_loc(val)
+    }
+
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.pop_operand();
+        self.base.charge_store_loc(val)
+    }
+
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let args: Vec<_> = args.collect();
+        self.pop_operands(args.len());
+        self.push_operands([render_fields(&args, "{", "}")]);
+        self.base.charge_pack(is_generic, args.into_iter())
+    }
+
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let args: Vec<_> = args.collect();
+        self.pop_operand();
+        self.push_operands(args.iter().map(render_value));
+        self.base.charge_unpack(is_generic, args.into_iter())
+    }
+
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.pop_operand();
+        self.push_operands([render_value(&val)]);
+        self.base.charge_read_ref(val)
+    }
+
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.pop_operands(2);
+        self.base.charge_write_ref(new_val, old_val)
+    }
+
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.pop_operands(2);
+        self.push_unknown_operand();
+        self.base.charge_eq(lhs, rhs)
+    }
+
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.pop_operands(2);
+        self.push_unknown_operand();
+        self.base.charge_neq(lhs, rhs)
+    }
+
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let op = if is_mut {
+            GlobalStorageOp::BorrowGlobalMut
+        } else {
+            GlobalStorageOp::BorrowGlobal
+        };
+        let address = self.pop_operand();
+        self.record_global_storage_access(op, &address, &ty, None, is_success);
+        self.push_unknown_operand();
+        self.base
+            .charge_borrow_global(is_mut, is_generic, ty, is_success)
+    }
+
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        let address = self.pop_operand();
+        self.record_global_storage_access(GlobalStorageOp::Exists, &address, &ty, None, exists);
+        self.push_operands([exists.to_string()]);
+        self.base.charge_exists(is_generic, ty, exists)
+    }
+
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let value = val.as_ref().map(render_value);
+        let success = value.is_some();
+        let address = self.pop_operand();
+        self.record_global_storage_access(
+            GlobalStorageOp::MoveFrom,
+            &address,
+            &ty,
+            value.clone(),
+            success,
+        );
+        self.push_operands(value);
+        self.base.charge_move_from(is_generic, ty, val)
+    }
+
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        // The resource is moved to the address of the signer below it on the stack.
+        let signer = self.pop_operands(2).remove(0);
+        self.record_global_storage_access(
+            GlobalStorageOp::MoveTo,
+            &signer,
+            &ty,
+            Some(render_value(&val)),
+            is_success,
+        );
+        self.base.charge_move_to(is_generic, ty, val, is_success)
+    }
+
+    fn charge_vec_pack<'a>(
+        &mut self,
+        ty: impl TypeView + 'a,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let args: Vec<_> = args.collect();
+        self.pop_operands(args.len());
+        self.push_operands([render_fields(&args, "[", "]")]);
+        self.base.charge_vec_pack(ty, args.into_iter())
+    }
+
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let elems: Vec<_> = elems.collect();
+        self.pop_operand();
+        self.push_operands(elems.iter().map(render_value));
+        self.base
+            .charge_vec_unpack(ty, expect_num_elements, elems.into_iter())
+    }
+
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.pop_operand();
+        self.push_unknown_operand();
+        self.base.charge_vec_len(ty)
+    }
+
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.pop_operands(2);
+        self.push_unknown_operand();
+        self.base.charge_vec_borrow(is_mut, ty, is_success)
+    }
+
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.pop_operands(2);
+        self.base.charge_vec_push_back(ty, val)
+    }
+
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.pop_operand();
+        self.push_operands(val.as_ref().map(render_value));
+        self.base.charge_vec_pop_back(ty, val)
+    }
+
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.pop_operands(3);
+        self.base.charge_vec_swap(ty)
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        // The outermost frame of the interpreter, e.g., the entry function, is dropped as well,
+        // but it stays on the stack as the payload. What is left on the operand stack of a frame
+        // when it is dropped is what it returns.
+        let return_values = std::mem::take(self.current_operands());
+        self.return_from_frame(return_values);
+        self.base.charge_drop_frame(locals)
+    }
+}
+
+impl<G: AptosGasMeter> AptosGasMeter for ExecutionTracer<G> {
+    fn feature_version(&self) -> u64 {
+        self.base.feature_version()
+    }
+
+    fn gas_params(&self) -> &AptosGasParameters {
+        self.base.gas_params()
+    }
+
+    fn storage_gas_params(&self) -> &StorageGasParameters {
+        self.base.storage_gas_params()
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
+        self.base.charge_intrinsic_gas_for_transaction(txn_size)
+    }
+
+    fn charge_write_set_gas_for_io<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+    ) -> VMResult<()> {
+        self.base.charge_write_set_gas_for_io(ops)
+    }
+
+    fn charge_storage_fee<'a>(
+        &mut self,
+        write_ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+        events: impl IntoIterator<Item = &'a ContractEvent>,
+        txn_size: NumBytes,
+        gas_unit_price: FeePerGasUnit,
+    ) -> VMResult<()> {
+        self.base
+            .charge_storage_fee(write_ops, events, txn_size, gas_unit_price)
+    }
+}
+
+/// A state view that records the state items read through it.
+pub struct TracingStateView<'a, S> {
+    base: &'a S,
+    reads: Mutex<BTreeMap<StateKey, Option<StateValue>>>,
+}
+
+impl<'a, S> TracingStateView<'a, S> {
+    pub fn new(base: &'a S) -> Self {
+        Self {
+            base,
+            reads: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn into_reads(self) -> Vec<(StateKey, Option<StateValue>)> {
+        self.reads.into_inner().unwrap().into_iter().collect()
+    }
+}
+
+impl<'a, S> TStateView for TracingStateView<'a, S>
+where
+    S: TStateView<Key = StateKey>,
+{
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        let state_value = self.base.get_state_value(state_key)?;
+        self.reads
+            .lock()
+            .unwrap()
+            .entry(state_key.clone())
+            .or_insert_with(|| state_value.clone());
+        Ok(state_value)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.base.is_genesis()
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.base.get_usage()
+    }
+}
+
+/// Rendering of values the tracer can't tell, e.g., the result of an addition.
+pub const UNKNOWN_VALUE: &str = "_";
+
+/// Renders a Move value, e.g., `{0x1, [1, 2], &true}` for a struct holding an address, a vector
+/// and a reference. Byte vectors are rendered in hex, e.g., `0xcafe`.
+pub fn render_value(val: &impl ValueView) -> String {
+    let mut renderer = ValueRenderer::default();
+    val.visit(&mut renderer);
+    renderer.out
+}
+
+/// Renders the values packed into a struct or a vector, e.g., `{0x1, 2}`.
+fn render_fields(fields: &[impl ValueView], open: &str, close: &str) -> String {
+    let fields = fields.iter().map(render_value).collect::<Vec<_>>();
+    format!("{}{}{}", open, fields.join(", "), close)
+}
+
+#[derive(Default)]
+struct ValueRenderer {
+    out: String,
+    /// For each container being rendered, its number of elements, the number of those yet to be
+    /// rendered and its closing delimiter.
+    open: Vec<(usize, usize, &'static str)>,
+}
+
+impl ValueRenderer {
+    fn start_value(&mut self) {
+        if let Some((len, left, _)) = self.open.last() {
+            if left < len {
+                self.out.push_str(", ");
+            }
+        }
+    }
+
+    fn end_value(&mut self) {
+        while let Some((_, left, close)) = self.open.last_mut() {
+            *left -= 1;
+            if *left > 0 {
+                break;
+            }
+            self.out.push_str(close);
+            self.open.pop();
+        }
+    }
+
+    fn leaf(&mut self, val: impl ToString) {
+        self.start_value();
+        self.out.push_str(&val.to_string());
+        self.end_value();
+    }
+
+    fn container(&mut self, open: &str, len: usize, close: &'static str) -> bool {
+        self.start_value();
+        self.out.push_str(open);
+        if len == 0 {
+            self.out.push_str(close);
+            self.end_value();
+        } else {
+            self.open.push((len, len, close));
+        }
+        true
+    }
+}
+
+impl ValueVisitor for ValueRenderer {
+    fn visit_u8(&mut self, _depth: usize, val: u8) {
+        self.leaf(val)
+    }
+
+    fn visit_u16(&mut self, _depth: usize, val: u16) {
+        self.leaf(val)
+    }
+
+    fn visit_u32(&mut self, _depth: usize, val: u32) {
+        self.leaf(val)
+    }
+
+    fn visit_u64(&mut self, _depth: usize, val: u64) {
+        self.leaf(val)
+    }
+
+    fn visit_u128(&mut self, _depth: usize, val: u128) {
+        self.leaf(val)
+    }
+
+    fn visit_u256(&mut self, _depth: usize, val: U256) {
+        self.leaf(val)
+    }
+
+    fn visit_bool(&mut self, _depth: usize, val: bool) {
+        self.leaf(val)
+    }
+
+    fn visit_address(&mut self, _depth: usize, val: AccountAddress) {
+        self.leaf(val.to_hex_literal())
+    }
+
+    fn visit_struct(&mut self, _depth: usize, len: usize) -> bool {
+        self.container("{", len, "}")
+    }
+
+    fn visit_vec(&mut self, _depth: usize, len: usize) -> bool {
+        self.container("[", len, "]")
+    }
+
+    fn visit_vec_u8(&mut self, _depth: usize, vals: &[u8]) {
+        self.leaf(format!("0x{}", hex::encode(vals)))
+    }
+
+    fn visit_ref(&mut self, _depth: usize, _is_global: bool) -> bool {
+        self.container("&", 1, "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_vm_types::values::{Struct, Value};
+
+    #[test]
+    fn test_render_value() {
+        assert_eq!(render_value(&Value::u64(42)), "42");
+        assert_eq!(render_value(&Value::vector_u8(vec![0xca, 0xfe])), "0xcafe");
+        assert_eq!(render_value(&Value::vector_u64(vec![])), "[]");
+        assert_eq!(
+            render_value(&Value::struct_(Struct::pack(vec![
+                Value::address(AccountAddress::ONE),
+                Value::vector_u64(vec![1, 2]),
+                Value::struct_(Struct::pack(vec![])),
+                Value::bool(true),
+            ]))),
+            "{0x1, [1, 2], {}, true}"
+        );
+    }
+
+    #[test]
+    fn test_rendered_address() {
+        let signer = Value::struct_(Struct::pack(vec![Value::address(AccountAddress::ONE)]));
+        assert_eq!(
+            rendered_address(&render_value(&Value::address(AccountAddress::ONE))),
+            Some(AccountAddress::ONE)
+        );
+        assert_eq!(
+            rendered_address(&format!("&{}", render_value(&signer))),
+            Some(AccountAddress::ONE)
+        );
+        assert_eq!(rendered_address(UNKNOWN_VALUE), None);
+    }
+}
@@ -6,9 +6,11 @@ use crate::{
     natives::aptos_natives,
 };
 use aptos_framework::natives::{
-    aggregator_natives::NativeAggregatorContext, code::NativeCodeContext,
+    aggregator_natives::NativeAggregatorContext,
+    code::NativeCodeContext,
     cryptography::ristretto255_point::NativeRistrettoPointContext,
-    state_storage::NativeStateStorageContext, transaction_context::NativeTransactionContext,
+    state_storage::NativeStateStorageContext,
+    transaction_context::{NativeTransactionContext, UserTransactionContext},
 };
 use aptos_gas::{AbstractValueSizeGasParameters, NativeGasParameters};
 use aptos_types::on_chain_config::{FeatureFlag, Features, TimedFeatureFlag, TimedFeatures};
@@ -69,6 +71,26 @@ impl MoveVmExt {
         &self,
         remote: &'r S,
         session_id: SessionId,
+    ) -> SessionExt<'r, '_, S> {
+        self.new_session_impl(remote, session_id, None)
+    }
+
+    /// Same as `new_session`, for a session of a user transaction whose details can be read by the
+    /// transaction context natives.
+    pub fn new_user_session<'r, S: MoveResolverExt>(
+        &self,
+        remote: &'r S,
+        session_id: SessionId,
+        user_transaction_context: UserTransactionContext,
+    ) -> SessionExt<'r, '_, S> {
+        self.new_session_impl(remote, session_id, Some(user_transaction_context))
+    }
+
+    fn new_session_impl<'r, S: MoveResolverExt>(
+        &self,
+        remote: &'r S,
+        session_id: SessionId,
+        user_transaction_context: Option<UserTransactionContext>,
     ) -> SessionExt<'r, '_, S> {
         let mut extensions = NativeContextExtensions::default();
         let txn_hash: [u8; 32] = session_id
@@ -90,7 +112,12 @@ impl MoveVmExt {
             _ => vec![],
         };
 
-        extensions.add(NativeTransactionContext::new(script_hash, self.chain_id));
+        extensions.add(NativeTransactionContext::new(
+            txn_hash.to_vec(),
+            script_hash,
+            self.chain_id,
+            user_transaction_context,
+        ));
         extensions.add(NativeCodeContext::default());
         extensions.add(NativeStateStorageContext::new(remote));
 
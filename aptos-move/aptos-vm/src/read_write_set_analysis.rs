@@ -107,6 +107,31 @@ impl<'a, R: MoveResolverExt> ReadWriteSetAnalysis<'a, R> {
         self.get_keys_user_transaction_impl(tx, false)
     }
 
+    /// Returns an overapproximation of the `ResourceKey`'s in global storage that will be read
+    /// and written by the payload of `tx` alone, i.e., excluding its prologue and epilogue.
+    pub fn get_keys_user_transaction_payload(
+        &self,
+        tx: &SignedTransaction,
+        concretize: bool,
+    ) -> Result<(Vec<ResourceKey>, Vec<ResourceKey>)> {
+        match tx.payload() {
+            TransactionPayload::EntryFunction(s) => {
+                let script_accesses = self
+                    .get_partially_concretized_summary(
+                        s.module(),
+                        s.function(),
+                        &[tx.sender()],
+                        s.args(),
+                        s.ty_args(),
+                        &self.module_cache,
+                    )
+                    .unwrap_or_else(|_| ConcretizedFormals::empty());
+                self.concretize_secondary_indexes(script_accesses, concretize)
+            },
+            payload => bail!("Unsupported transaction payload type {:?}", payload),
+        }
+    }
+
     fn get_keys_user_transaction_impl(
         &self,
         tx: &SignedTransaction,
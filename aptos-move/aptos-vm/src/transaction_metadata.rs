@@ -3,11 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey};
+use aptos_framework::natives::transaction_context::{EntryFunctionPayload, UserTransactionContext};
 use aptos_gas::{FeePerGasUnit, Gas, NumBytes};
 use aptos_types::{
     account_address::AccountAddress,
     chain_id::ChainId,
-    transaction::{authenticator::AuthenticationKey, SignedTransaction, TransactionPayload},
+    transaction::{
+        authenticator::{AuthenticationKey, Scheme},
+        SignedTransaction, TransactionPayload,
+    },
 };
 use std::convert::TryFrom;
 
@@ -16,6 +20,9 @@ pub struct TransactionMetadata {
     pub authentication_key: Vec<u8>,
     pub secondary_signers: Vec<AccountAddress>,
     pub secondary_authentication_keys: Vec<Vec<u8>>,
+    pub fee_payer: Option<AccountAddress>,
+    pub fee_payer_authentication_key: Option<Vec<u8>>,
+    pub has_secp256r1_ecdsa_signer: bool,
     pub sequence_number: u64,
     pub max_gas_amount: Gas,
     pub gas_unit_price: FeePerGasUnit,
@@ -24,6 +31,7 @@ pub struct TransactionMetadata {
     pub chain_id: ChainId,
     pub script_hash: Vec<u8>,
     pub script_size: NumBytes,
+    pub entry_function_payload: Option<EntryFunctionPayload>,
 }
 
 impl TransactionMetadata {
@@ -38,6 +46,15 @@ impl TransactionMetadata {
                 .iter()
                 .map(|account_auth| account_auth.authentication_key().to_vec())
                 .collect(),
+            fee_payer: txn.authenticator().fee_payer_address(),
+            fee_payer_authentication_key: txn
+                .authenticator()
+                .fee_payer_signer()
+                .map(|account_auth| account_auth.authentication_key().to_vec()),
+            has_secp256r1_ecdsa_signer: std::iter::once(txn.authenticator().sender())
+                .chain(txn.authenticator().secondary_signers())
+                .chain(txn.authenticator().fee_payer_signer())
+                .any(|account_auth| matches!(account_auth.scheme(), Scheme::Secp256r1Ecdsa)),
             sequence_number: txn.sequence_number(),
             max_gas_amount: txn.max_gas_amount().into(),
             gas_unit_price: txn.gas_unit_price().into(),
@@ -56,6 +73,21 @@ impl TransactionMetadata {
                 TransactionPayload::Script(s) => (s.code().len() as u64).into(),
                 _ => NumBytes::zero(),
             },
+            entry_function_payload: match txn.payload() {
+                TransactionPayload::EntryFunction(entry_function) => {
+                    Some(EntryFunctionPayload::new(
+                        *entry_function.module().address(),
+                        entry_function.module().name().to_string(),
+                        entry_function.function().to_string(),
+                        entry_function
+                            .ty_args()
+                            .iter()
+                            .map(|ty_arg| ty_arg.to_string())
+                            .collect(),
+                    ))
+                },
+                _ => None,
+            },
         }
     }
 
@@ -79,6 +111,10 @@ impl TransactionMetadata {
         self.secondary_signers.to_owned()
     }
 
+    pub fn fee_payer(&self) -> Option<AccountAddress> {
+        self.fee_payer.to_owned()
+    }
+
     pub fn authentication_key(&self) -> &[u8] {
         &self.authentication_key
     }
@@ -102,6 +138,27 @@ impl TransactionMetadata {
     pub fn is_multi_agent(&self) -> bool {
         !self.secondary_signers.is_empty()
     }
+
+    pub fn has_secp256r1_ecdsa_signer(&self) -> bool {
+        self.has_secp256r1_ecdsa_signer
+    }
+
+    /// The account paying the gas of the transaction: its fee payer if it has one, its sender
+    /// otherwise.
+    pub fn gas_payer(&self) -> AccountAddress {
+        self.fee_payer.unwrap_or(self.sender)
+    }
+
+    pub fn as_user_transaction_context(&self) -> UserTransactionContext {
+        UserTransactionContext::new(
+            self.sender,
+            self.secondary_signers.clone(),
+            self.gas_payer(),
+            self.max_gas_amount.into(),
+            self.gas_unit_price.into(),
+            self.entry_function_payload.clone(),
+        )
+    }
 }
 
 impl Default for TransactionMetadata {
@@ -114,6 +171,9 @@ impl Default for TransactionMetadata {
             authentication_key: AuthenticationKey::ed25519(&public_key).to_vec(),
             secondary_signers: vec![],
             secondary_authentication_keys: vec![],
+            fee_payer: None,
+            fee_payer_authentication_key: None,
+            has_secp256r1_ecdsa_signer: false,
             sequence_number: 0,
             max_gas_amount: 100_000_000.into(),
             gas_unit_price: 0.into(),
@@ -122,6 +182,7 @@ impl Default for TransactionMetadata {
             chain_id: ChainId::test(),
             script_hash: vec![],
             script_size: NumBytes::zero(),
+            entry_function_payload: None,
         }
     }
 }
@@ -0,0 +1,305 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opt-in analytics of the conflicts between the transactions of a block during parallel
+//! execution, i.e., which transactions waited on or were re-executed because of which ones, and
+//! over which keys. Reports are only collected when enabled with [`set_conflict_reporting`], and
+//! are kept until taken with [`take_conflict_reports`].
+
+use crate::{counters::EFFECTIVE_PARALLELISM, scheduler::TxnIndex};
+use aptos_infallible::Mutex;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Debug,
+    hash::Hash,
+    sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Number of keys listed in `BlockConflictReport::hot_keys`.
+const NUM_HOT_KEYS: usize = 20;
+
+static CONFLICT_REPORTING: AtomicBool = AtomicBool::new(false);
+static CONFLICT_REPORTS: Lazy<Mutex<Vec<BlockConflictReport>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Enables or disables the collection of a conflict report for each block executed in parallel.
+pub fn set_conflict_reporting(enabled: bool) {
+    CONFLICT_REPORTING.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_conflict_reporting_enabled() -> bool {
+    CONFLICT_REPORTING.load(Ordering::Relaxed)
+}
+
+/// Returns the reports collected since the last call, in the order the blocks were executed.
+pub fn take_conflict_reports() -> Vec<BlockConflictReport> {
+    std::mem::take(&mut *CONFLICT_REPORTS.lock())
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+pub enum ConflictKind {
+    /// The transaction read a key estimated to be written by a lower transaction, and waited for
+    /// it to be re-executed.
+    DependencyWait,
+    /// The validation of the transaction failed because of a key it read, and the transaction
+    /// was aborted to be re-executed.
+    ValidationFailure,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HotKey {
+    pub key: String,
+    pub num_dependency_waits: usize,
+    pub num_validation_failures: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TxnAborts {
+    pub txn_idx: TxnIndex,
+    pub num_aborts: u32,
+}
+
+/// Conflicts between the transactions of a block executed in parallel.
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockConflictReport {
+    pub num_txns: usize,
+    /// Total number of executions, including the re-executions.
+    pub num_executions: u64,
+    pub num_dependency_waits: usize,
+    pub num_validation_failures: usize,
+    /// Keys with the most conflicts, most conflicting first.
+    pub hot_keys: Vec<HotKey>,
+    /// Transactions which were aborted, most aborted first.
+    pub aborted_txns: Vec<TxnAborts>,
+    /// Longest chain of transactions each of which conflicted with the previous one, lowest
+    /// index first. Its transactions could only be executed one after another.
+    pub critical_path: Vec<TxnIndex>,
+    /// Time the threads spent executing and validating transactions divided by the time the
+    /// block took, i.e., the average number of threads doing useful work, re-executions aside.
+    pub effective_parallelism: f64,
+}
+
+struct Conflict<K> {
+    txn_idx: TxnIndex,
+    /// The transaction it conflicted with, if known, e.g., not for aggregator deltas.
+    depends_on: Option<TxnIndex>,
+    key: K,
+    kind: ConflictKind,
+}
+
+/// Records the conflicts during the parallel execution of a block. Conflicts are rare compared to
+/// reads, so they go through a lock.
+pub(crate) struct ConflictRecorder<K> {
+    conflicts: Mutex<Vec<Conflict<K>>>,
+    executions: Vec<AtomicU32>,
+    busy_nanos: AtomicU64,
+}
+
+impl<K: Clone + Debug + Eq + Hash> ConflictRecorder<K> {
+    /// Returns a recorder if conflict reporting is enabled.
+    pub(crate) fn new_if_enabled(num_txns: usize) -> Option<Self> {
+        is_conflict_reporting_enabled().then(|| Self {
+            conflicts: Mutex::new(Vec::new()),
+            executions: (0..num_txns).map(|_| AtomicU32::new(0)).collect(),
+            busy_nanos: AtomicU64::new(0),
+        })
+    }
+
+    pub(crate) fn record_conflict(
+        &self,
+        txn_idx: TxnIndex,
+        depends_on: Option<TxnIndex>,
+        key: &K,
+        kind: ConflictKind,
+    ) {
+        self.conflicts.lock().push(Conflict {
+            txn_idx,
+            depends_on,
+            key: key.clone(),
+            kind,
+        });
+    }
+
+    pub(crate) fn record_execution(&self, txn_idx: TxnIndex) {
+        self.executions[txn_idx].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records time spent executing or validating a transaction.
+    pub(crate) fn record_busy_time(&self, duration: Duration) {
+        self.busy_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Builds the report of the block, which took `elapsed` to execute, and keeps it until
+    /// taken with [`take_conflict_reports`].
+    pub(crate) fn finish(self, elapsed: Duration) {
+        let report = self.into_report(elapsed);
+        EFFECTIVE_PARALLELISM.observe(report.effective_parallelism);
+        CONFLICT_REPORTS.lock().push(report);
+    }
+
+    fn into_report(self, elapsed: Duration) -> BlockConflictReport {
+        let conflicts = self.conflicts.into_inner();
+        let num_txns = self.executions.len();
+
+        let mut hot_keys: HashMap<&K, (usize, usize)> = HashMap::new();
+        for conflict in &conflicts {
+            let (waits, failures) = hot_keys.entry(&conflict.key).or_default();
+            match conflict.kind {
+                ConflictKind::DependencyWait => *waits += 1,
+                ConflictKind::ValidationFailure => *failures += 1,
+            }
+        }
+        let mut hot_keys: Vec<_> = hot_keys
+            .into_iter()
+            .map(|(key, (waits, failures))| HotKey {
+                key: format!("{:?}", key),
+                num_dependency_waits: waits,
+                num_validation_failures: failures,
+            })
+            .collect();
+        hot_keys.sort_by(|a, b| {
+            (b.num_dependency_waits + b.num_validation_failures)
+                .cmp(&(a.num_dependency_waits + a.num_validation_failures))
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        hot_keys.truncate(NUM_HOT_KEYS);
+
+        let executions: Vec<u32> = self
+            .executions
+            .into_iter()
+            .map(AtomicU32::into_inner)
+            .collect();
+        let mut aborted_txns: Vec<_> = executions
+            .iter()
+            .enumerate()
+            .filter(|(_, executions)| **executions > 1)
+            .map(|(txn_idx, executions)| TxnAborts {
+                txn_idx,
+                num_aborts: executions - 1,
+            })
+            .collect();
+        aborted_txns.sort_by(|a, b| {
+            b.num_aborts
+                .cmp(&a.num_aborts)
+                .then(a.txn_idx.cmp(&b.txn_idx))
+        });
+
+        let edges: BTreeSet<(TxnIndex, TxnIndex)> = conflicts
+            .iter()
+            .filter_map(|conflict| {
+                conflict
+                    .depends_on
+                    .map(|depends_on| (conflict.txn_idx, depends_on))
+            })
+            .collect();
+
+        let elapsed_nanos = elapsed.as_nanos() as f64;
+        BlockConflictReport {
+            num_txns,
+            num_executions: executions.iter().map(|executions| *executions as u64).sum(),
+            num_dependency_waits: conflicts
+                .iter()
+                .filter(|conflict| conflict.kind == ConflictKind::DependencyWait)
+                .count(),
+            num_validation_failures: conflicts
+                .iter()
+                .filter(|conflict| conflict.kind == ConflictKind::ValidationFailure)
+                .count(),
+            hot_keys,
+            aborted_txns,
+            critical_path: critical_path(num_txns, &edges),
+            effective_parallelism: if elapsed_nanos > 0.0 {
+                self.busy_nanos.into_inner() as f64 / elapsed_nanos
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Returns the longest chain of transactions, given the (transaction, lower transaction it
+/// depends on) edges.
+fn critical_path(num_txns: usize, edges: &BTreeSet<(TxnIndex, TxnIndex)>) -> Vec<TxnIndex> {
+    // Length of the longest chain ending at each transaction, along with the previous one.
+    let mut longest: Vec<(usize, Option<TxnIndex>)> = vec![(1, None); num_txns];
+    // Edges are sorted by transaction, and point to lower transactions, whose longest chains are
+    // known by then.
+    for (txn_idx, depends_on) in edges {
+        if *depends_on >= *txn_idx || *txn_idx >= num_txns {
+            continue;
+        }
+        let len = longest[*depends_on].0 + 1;
+        if len > longest[*txn_idx].0 {
+            longest[*txn_idx] = (len, Some(*depends_on));
+        }
+    }
+
+    let mut path = vec![];
+    let mut next = longest
+        .iter()
+        .enumerate()
+        .max_by_key(|(txn_idx, (len, _))| (*len, std::cmp::Reverse(*txn_idx)))
+        .map(|(txn_idx, _)| txn_idx);
+    while let Some(txn_idx) = next {
+        path.push(txn_idx);
+        next = longest[txn_idx].1;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_critical_path() {
+        let edges = [(2, 0), (3, 2), (4, 1), (5, 3), (5, 4)]
+            .into_iter()
+            .collect();
+        assert_eq!(critical_path(6, &edges), vec![0, 2, 3, 5]);
+        assert_eq!(critical_path(3, &BTreeSet::new()), vec![0]);
+        assert_eq!(critical_path(0, &BTreeSet::new()), Vec::<TxnIndex>::new());
+    }
+
+    #[test]
+    fn test_report() {
+        let recorder = ConflictRecorder {
+            conflicts: Mutex::new(Vec::new()),
+            executions: (0..4).map(|_| AtomicU32::new(0)).collect(),
+            busy_nanos: AtomicU64::new(0),
+        };
+        for txn_idx in [0, 1, 1, 2, 3, 3, 3] {
+            recorder.record_execution(txn_idx);
+        }
+        recorder.record_conflict(1, Some(0), &"a", ConflictKind::ValidationFailure);
+        recorder.record_conflict(3, Some(1), &"a", ConflictKind::DependencyWait);
+        recorder.record_conflict(3, Some(1), &"a", ConflictKind::ValidationFailure);
+        recorder.record_conflict(3, None, &"b", ConflictKind::ValidationFailure);
+        recorder.record_busy_time(Duration::from_millis(30));
+
+        let report = recorder.into_report(Duration::from_millis(10));
+        assert_eq!(report.num_txns, 4);
+        assert_eq!(report.num_executions, 7);
+        assert_eq!(report.num_dependency_waits, 1);
+        assert_eq!(report.num_validation_failures, 3);
+        assert_eq!(report.hot_keys[0].key, "\"a\"");
+        assert_eq!(report.hot_keys[0].num_dependency_waits, 1);
+        assert_eq!(report.hot_keys[0].num_validation_failures, 2);
+        assert_eq!(report.hot_keys[1].key, "\"b\"");
+        assert_eq!(
+            report
+                .aborted_txns
+                .iter()
+                .map(|aborts| (aborts.txn_idx, aborts.num_aborts))
+                .collect::<Vec<_>>(),
+            vec![(3, 2), (1, 1)]
+        );
+        assert_eq!(report.critical_path, vec![0, 1, 3]);
+        assert!((report.effective_parallelism - 3.0).abs() < 1e-9);
+    }
+}
@@ -109,3 +109,21 @@ pub static DEPENDENCY_WAIT_SECONDS: Lazy<Histogram> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Count of times a transaction waited for a dependency to be re-executed in Block STM.
+pub static DEPENDENCY_WAIT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_execution_dependency_wait_count",
+        "Number of times a transaction waited on a dependency in parallel execution"
+    )
+    .unwrap()
+});
+
+pub static EFFECTIVE_PARALLELISM: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_execution_effective_parallelism",
+        "Average number of threads executing or validating transactions in Block STM, for blocks with conflict reporting enabled",
+        exponential_buckets(/*start=*/ 0.25, /*factor=*/ 2.0, /*count=*/ 10).unwrap(),
+    )
+    .unwrap()
+});
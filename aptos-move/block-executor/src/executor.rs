@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_report::{ConflictKind, ConflictRecorder},
     counters,
     counters::{
         PARALLEL_EXECUTION_SECONDS, RAYON_EXECUTION_SECONDS, TASK_EXECUTE_SECONDS,
@@ -26,6 +27,7 @@ use std::{
     collections::btree_map::BTreeMap,
     marker::PhantomData,
     sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
 };
 
 pub static RAYON_EXEC_POOL: Lazy<rayon::ThreadPool> = Lazy::new(|| {
@@ -70,14 +72,17 @@ where
         last_input_output: &TxnLastInputOutput<T::Key, E::Output, E::Error>,
         versioned_data_cache: &MVHashMap<T::Key, T::Value>,
         scheduler: &Scheduler,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
         executor: &E,
         base_view: &S,
     ) -> SchedulerTask {
         let _timer = TASK_EXECUTE_SECONDS.start_timer();
+        let start_time = Instant::now();
         let (idx_to_execute, incarnation) = version;
         let txn = &signature_verified_block[idx_to_execute];
 
-        let speculative_view = MVHashMapView::new(versioned_data_cache, scheduler);
+        let speculative_view =
+            MVHashMapView::new(versioned_data_cache, scheduler, conflict_recorder);
 
         // VM execution.
         let execute_result = executor.execute_transaction(
@@ -136,6 +141,10 @@ where
         }
 
         last_input_output.record(idx_to_execute, speculative_view.take_reads(), result);
+        if let Some(conflict_recorder) = conflict_recorder {
+            conflict_recorder.record_execution(idx_to_execute);
+            conflict_recorder.record_busy_time(start_time.elapsed());
+        }
         scheduler.finish_execution(idx_to_execute, incarnation, updates_outside)
     }
 
@@ -146,34 +155,51 @@ where
         last_input_output: &TxnLastInputOutput<T::Key, E::Output, E::Error>,
         versioned_data_cache: &MVHashMap<T::Key, T::Value>,
         scheduler: &Scheduler,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
     ) -> SchedulerTask {
         use MVHashMapError::*;
         use MVHashMapOutput::*;
 
         let _timer = TASK_VALIDATE_SECONDS.start_timer();
+        let start_time = Instant::now();
         let (idx_to_validate, incarnation) = version_to_validate;
         let read_set = last_input_output
             .read_set(idx_to_validate)
             .expect("Prior read-set must be recorded");
 
-        let valid = read_set.iter().all(|r| {
-            match versioned_data_cache.read(r.path(), idx_to_validate) {
-                Ok(Version(version, _)) => r.validate_version(version),
-                Ok(Resolved(value)) => r.validate_resolved(value),
-                Err(Dependency(_)) => false, // Dependency implies a validation failure.
-                Err(Unresolved(delta)) => r.validate_unresolved(delta),
-                Err(NotFound) => r.validate_storage(),
+        // The first read which fails validation, along with the transaction which wrote the value
+        // now read, if known.
+        let invalid_read = read_set.iter().find_map(|r| {
+            let (valid, writer) = match versioned_data_cache.read(r.path(), idx_to_validate) {
+                Ok(Version(version, _)) => (r.validate_version(version), Some(version.0)),
+                Ok(Resolved(value)) => (r.validate_resolved(value), None),
+                // Dependency implies a validation failure.
+                Err(Dependency(dep_idx)) => (false, Some(dep_idx)),
+                Err(Unresolved(delta)) => (r.validate_unresolved(delta), None),
+                Err(NotFound) => (r.validate_storage(), None),
                 // We successfully validate when read (again) results in a delta application
                 // failure. If the failure is speculative, a later validation will fail due to
                 // a read without this error. However, if the failure is real, passing
                 // validation here allows to avoid infinitely looping and instead panic when
                 // materializing deltas as writes in the final output preparation state. Panic
                 // is also preferrable as it allows testing for this scenario.
-                Err(DeltaApplicationFailure) => r.validate_delta_application_failure(),
-            }
+                Err(DeltaApplicationFailure) => (r.validate_delta_application_failure(), None),
+            };
+            (!valid).then(|| (r.path(), writer))
         });
 
-        let aborted = !valid && scheduler.try_abort(idx_to_validate, incarnation);
+        let aborted = invalid_read.is_some() && scheduler.try_abort(idx_to_validate, incarnation);
+        if let Some(conflict_recorder) = conflict_recorder {
+            if let (true, Some((key, writer))) = (aborted, invalid_read) {
+                conflict_recorder.record_conflict(
+                    idx_to_validate,
+                    writer,
+                    key,
+                    ConflictKind::ValidationFailure,
+                );
+            }
+            conflict_recorder.record_busy_time(start_time.elapsed());
+        }
 
         if aborted {
             counters::SPECULATIVE_ABORT_COUNT.inc();
@@ -200,6 +226,7 @@ where
         last_input_output: &TxnLastInputOutput<T::Key, E::Output, E::Error>,
         versioned_data_cache: &MVHashMap<T::Key, T::Value>,
         scheduler: &Scheduler,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
         base_view: &S,
         committing: bool,
     ) {
@@ -227,6 +254,7 @@ where
                     last_input_output,
                     versioned_data_cache,
                     scheduler,
+                    conflict_recorder,
                 ),
                 SchedulerTask::ExecutionTask(version_to_execute, None) => self.execute(
                     version_to_execute,
@@ -234,6 +262,7 @@ where
                     last_input_output,
                     versioned_data_cache,
                     scheduler,
+                    conflict_recorder,
                     &executor,
                     base_view,
                 ),
@@ -260,21 +289,41 @@ where
         signature_verified_block: &Vec<T>,
         base_view: &S,
     ) -> Result<Vec<(E::Output, Vec<(T::Key, WriteOp)>)>, E::Error> {
+        self.execute_transactions_parallel_impl(
+            executor_initial_arguments,
+            signature_verified_block,
+            base_view,
+            false,
+        )
+        .map(|(results, _)| results)
+    }
+
+    /// Executes the block in parallel and returns the keys read by the last incarnation of each
+    /// transaction if `capture_read_sets` is set, an empty list per transaction otherwise.
+    fn execute_transactions_parallel_impl(
+        &self,
+        executor_initial_arguments: E::Argument,
+        signature_verified_block: &Vec<T>,
+        base_view: &S,
+        capture_read_sets: bool,
+    ) -> Result<(Vec<(E::Output, Vec<(T::Key, WriteOp)>)>, Vec<Vec<T::Key>>), E::Error> {
         let _timer = PARALLEL_EXECUTION_SECONDS.start_timer();
         assert!(self.concurrency_level > 1, "Must use sequential execution");
 
         let versioned_data_cache = MVHashMap::new();
 
         if signature_verified_block.is_empty() {
-            return Ok(vec![]);
+            return Ok((vec![], vec![]));
         }
 
         let num_txns = signature_verified_block.len();
         let last_input_output = TxnLastInputOutput::new(num_txns);
         let committing = AtomicBool::new(true);
         let scheduler = Scheduler::new(num_txns);
+        let conflict_recorder = ConflictRecorder::new_if_enabled(num_txns);
 
         let timer = RAYON_EXECUTION_SECONDS.start_timer();
+        let start_time = Instant::now();
         RAYON_EXEC_POOL.scope(|s| {
             for _ in 0..self.concurrency_level {
                 s.spawn(|_| {
@@ -284,6 +333,7 @@ where
                         &last_input_output,
                         &versioned_data_cache,
                         &scheduler,
+                        conflict_recorder.as_ref(),
                         base_view,
                         committing.swap(false, Ordering::SeqCst),
                     );
@@ -291,6 +341,18 @@ where
             }
         });
         drop(timer);
+        if let Some(conflict_recorder) = conflict_recorder {
+            conflict_recorder.finish(start_time.elapsed());
+        }
+
+        let read_sets: Vec<Vec<T::Key>> = (0..num_txns)
+            .map(|idx| match last_input_output.read_set(idx) {
+                Some(reads) if capture_read_sets => {
+                    reads.iter().map(|read| read.path().clone()).collect()
+                },
+                _ => vec![],
+            })
+            .collect();
 
         // TODO: for large block sizes and many cores, extract outputs in parallel.
         let mut final_results = Vec::with_capacity(num_txns);
@@ -329,10 +391,11 @@ where
                 let delta_resolver: OutputDeltaResolver<T> =
                     OutputDeltaResolver::new(versioned_data_cache);
                 // TODO: parallelize when necessary.
-                Ok(final_results
+                let results = final_results
                     .into_iter()
                     .zip(delta_resolver.resolve(base_view, num_txns).into_iter())
-                    .collect())
+                    .collect();
+                Ok((results, read_sets))
             },
         }
     }
@@ -427,4 +490,30 @@ where
 
         ret
     }
+
+    /// Executes the block in parallel, like `execute_block`, and also returns the keys each
+    /// transaction read during its last incarnation, for the caller to check them. There is no
+    /// sequential fallback: if modules published in the block may race with their reads,
+    /// `Error::ModulePathReadWrite` is returned and the block must be executed with
+    /// `execute_block` instead.
+    pub fn execute_block_with_read_sets(
+        &self,
+        executor_arguments: E::Argument,
+        signature_verified_block: Vec<T>,
+        base_view: &S,
+    ) -> Result<(Vec<(E::Output, Vec<(T::Key, WriteOp)>)>, Vec<Vec<T::Key>>), E::Error> {
+        let ret = self.execute_transactions_parallel_impl(
+            executor_arguments,
+            &signature_verified_block,
+            base_view,
+            true,
+        );
+
+        RAYON_EXEC_POOL.spawn(move || {
+            // Explicit async drops.
+            drop(signature_verified_block);
+        });
+
+        ret
+    }
 }
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_report::{ConflictKind, ConflictRecorder},
     counters,
     scheduler::{Scheduler, TxnIndex},
     task::{ModulePath, Transaction},
@@ -19,7 +20,7 @@ use aptos_types::{
 };
 use aptos_vm_logging::{log_schema::AdapterLogSchema, prelude::*};
 use move_binary_format::errors::Location;
-use std::{cell::RefCell, collections::BTreeMap, hash::Hash, sync::Arc};
+use std::{cell::RefCell, collections::BTreeMap, fmt::Debug, hash::Hash, sync::Arc};
 
 /// Resolved and serialized data for WriteOps, None means deletion.
 pub type ResolvedData = Option<Vec<u8>>;
@@ -34,6 +35,7 @@ pub type ResolvedData = Option<Vec<u8>>;
 pub(crate) struct MVHashMapView<'a, K, V> {
     versioned_map: &'a MVHashMap<K, V>,
     scheduler: &'a Scheduler,
+    conflict_recorder: Option<&'a ConflictRecorder<K>>,
     captured_reads: RefCell<Vec<ReadDescriptor<K>>>,
 }
 
@@ -53,14 +55,19 @@ pub enum ReadResult<V> {
 
 impl<
         'a,
-        K: ModulePath + PartialOrd + Ord + Send + Clone + Hash + Eq,
+        K: ModulePath + PartialOrd + Ord + Send + Clone + Hash + Eq + Debug,
         V: TransactionWrite + Send + Sync,
     > MVHashMapView<'a, K, V>
 {
-    pub(crate) fn new(versioned_map: &'a MVHashMap<K, V>, scheduler: &'a Scheduler) -> Self {
+    pub(crate) fn new(
+        versioned_map: &'a MVHashMap<K, V>,
+        scheduler: &'a Scheduler,
+        conflict_recorder: Option<&'a ConflictRecorder<K>>,
+    ) -> Self {
         Self {
             versioned_map,
             scheduler,
+            conflict_recorder,
             captured_reads: RefCell::new(Vec::new()),
         }
     }
@@ -106,6 +113,15 @@ impl<
                     // `self.txn_idx` estimated to depend on a write from `dep_idx`.
                     match self.scheduler.wait_for_dependency(txn_idx, dep_idx) {
                         Some(dep_condition) => {
+                            counters::DEPENDENCY_WAIT_COUNT.inc();
+                            if let Some(conflict_recorder) = self.conflict_recorder {
+                                conflict_recorder.record_conflict(
+                                    txn_idx,
+                                    Some(dep_idx),
+                                    key,
+                                    ConflictKind::DependencyWait,
+                                );
+                            }
                             let _timer = counters::DEPENDENCY_WAIT_SECONDS.start_timer();
                             // Wait on a condition variable corresponding to the encountered
                             // read dependency. Once the dep_idx finishes re-execution, scheduler
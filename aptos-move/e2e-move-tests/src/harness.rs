@@ -4,7 +4,7 @@
 use crate::{assert_success, AptosPackageHooks};
 use aptos::move_tool::MemberId;
 use aptos_cached_packages::aptos_stdlib;
-use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey, Uniform};
 use aptos_framework::{natives::code::PackageMetadata, BuildOptions, BuiltPackage};
 use aptos_gas::{
     AptosGasParameters, FromOnChainGasSchedule, InitialGasSchedule, ToOnChainGasSchedule,
@@ -215,6 +215,20 @@ impl MoveHarness {
             .sign()
     }
 
+    /// Creates a transaction to simulate, based on provided payload. Simulation only accepts
+    /// transactions without a valid signature, so it is signed with another key.
+    pub fn create_simulated_transaction_payload(
+        &mut self,
+        account: &Account,
+        payload: TransactionPayload,
+    ) -> SignedTransaction {
+        let raw_txn = self
+            .create_transaction_payload(account, payload)
+            .into_raw_transaction();
+        let signature = Account::new().privkey.sign(&raw_txn).unwrap();
+        SignedTransaction::new(raw_txn, account.pubkey.clone(), signature)
+    }
+
     /// Runs a transaction, based on provided payload. If the transaction succeeds, any generated
     /// writeset will be applied to storage.
     pub fn run_transaction_payload(
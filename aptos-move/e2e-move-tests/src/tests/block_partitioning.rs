@@ -0,0 +1,55 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{
+    account_address::AccountAddress, on_chain_config::FeatureFlag, transaction::Transaction,
+};
+use aptos_vm::{block_executor::BlockAptosVM, read_write_set_analysis, AptosVM};
+
+#[test]
+fn test_block_partitioning_preserves_outputs() {
+    let modules = aptos_cached_packages::head_release_bundle().compiled_modules();
+    AptosVM::set_read_write_set_analysis_once(
+        read_write_set::analyze(modules.iter())
+            .unwrap()
+            .normalize_all_scripts(read_write_set_analysis::add_on_functions_list()),
+    );
+    let mut h = MoveHarness::new_with_features(vec![FeatureFlag::BLOCK_PARTITIONING], vec![]);
+
+    let senders: Vec<_> = (0..8)
+        .map(|i| h.new_account_at(AccountAddress::from_hex_literal(&format!("0x20{}", i)).unwrap()))
+        .collect();
+    let receiver_1 = h.new_account_at(AccountAddress::from_hex_literal("0x301").unwrap());
+    let receiver_2 = h.new_account_at(AccountAddress::from_hex_literal("0x302").unwrap());
+    let receivers = [*receiver_1.address(), *receiver_2.address()];
+
+    for round in 0..2 {
+        // The first half of the senders transfer to one receiver and the second half to the
+        // other, so the block is reordered to interleave the two groups.
+        let block: Vec<_> = senders
+            .iter()
+            .enumerate()
+            .map(|(i, sender)| {
+                let receiver = receivers[(i / 4 + round) % 2];
+                Transaction::UserTransaction(h.create_transaction_payload(
+                    sender,
+                    aptos_stdlib::aptos_coin_transfer(receiver, 100 + i as u64),
+                ))
+            })
+            .collect();
+
+        // Blocks are only partitioned when executed in parallel.
+        let original =
+            BlockAptosVM::execute_block(block.clone(), h.executor.get_state_view(), 1).unwrap();
+        let partitioned =
+            BlockAptosVM::execute_block(block, h.executor.get_state_view(), 4).unwrap();
+        assert_eq!(partitioned, original);
+
+        for output in original {
+            assert_success!(output.status().to_owned());
+            h.executor.apply_write_set(output.write_set());
+        }
+    }
+}
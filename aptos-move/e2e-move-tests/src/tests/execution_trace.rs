@@ -0,0 +1,116 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MoveHarness;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use aptos_vm::{
+    execution_trace::{CallStep, CallTrace, GlobalStorageAccess, GlobalStorageOp},
+    AptosVM,
+};
+
+fn calls(frame: &CallTrace) -> Vec<&CallTrace> {
+    frame
+        .steps
+        .iter()
+        .filter_map(|step| match step {
+            CallStep::Call(call) => Some(call),
+            CallStep::GlobalStorage(_) => None,
+        })
+        .collect()
+}
+
+fn global_storage_accesses(frame: &CallTrace) -> Vec<&GlobalStorageAccess> {
+    frame
+        .steps
+        .iter()
+        .filter_map(|step| match step {
+            CallStep::Call(_) => None,
+            CallStep::GlobalStorage(access) => Some(access),
+        })
+        .collect()
+}
+
+#[test]
+fn test_trace_aborted_transfer() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_account_at(AccountAddress::from_hex_literal("0x121").unwrap());
+    let receiver = h.new_account_at(AccountAddress::from_hex_literal("0x122").unwrap());
+
+    // One more than the sender was minted.
+    let amount = 1_000_000_000_000_001;
+    let txn = h.create_simulated_transaction_payload(
+        &sender,
+        aptos_stdlib::aptos_account_transfer(*receiver.address(), amount),
+    );
+    let (_, output, trace) =
+        AptosVM::simulate_signed_transaction_with_trace(&txn, h.executor.get_state_view());
+    let trace = trace.expect("transaction should have been executed");
+
+    // `coin::extract` aborts with `error::invalid_argument(EINSUFFICIENT_BALANCE)`.
+    assert!(matches!(
+        output.status(),
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort { code: 0x10006, .. })
+    ));
+    assert_eq!(&trace.status, output.status());
+
+    let root = &trace.call_trace;
+    assert_eq!(root.function, "0x1::aptos_account::transfer");
+    assert!(!root.returned);
+    let root_calls = calls(root);
+    let names = root_calls
+        .iter()
+        .map(|call| call.function.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![
+        "0x1::account::exists_at",
+        "0x1::coin::is_account_registered",
+        "0x1::coin::transfer",
+    ]);
+
+    // Calls which returned have their return values, and global storage accesses their address.
+    let exists_at = root_calls[0];
+    assert_eq!(exists_at.args, vec![receiver.address().to_hex_literal()]);
+    assert!(exists_at.returned);
+    assert_eq!(exists_at.return_values, Some(vec!["true".to_string()]));
+    let accesses = global_storage_accesses(exists_at);
+    assert_eq!(accesses.len(), 1);
+    assert_eq!(accesses[0].op, GlobalStorageOp::Exists);
+    assert_eq!(accesses[0].address, Some(*receiver.address()));
+    assert_eq!(accesses[0].resource_type, "0x1::account::Account");
+    assert!(accesses[0].success);
+
+    // Following the calls which didn't return leads to the one which aborted.
+    let mut aborted = vec![root];
+    while let Some(call) = calls(aborted.last().unwrap())
+        .into_iter()
+        .find(|call| !call.returned)
+    {
+        aborted.push(call);
+    }
+    let names = aborted
+        .iter()
+        .map(|call| call.function.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![
+        "0x1::aptos_account::transfer",
+        "0x1::coin::transfer",
+        "0x1::coin::withdraw",
+        "0x1::coin::extract",
+    ]);
+    assert!(aborted.iter().all(|call| call.return_values.is_none()));
+    assert_eq!(aborted[1].ty_args, vec!["0x1::aptos_coin::AptosCoin"]);
+
+    let withdraw = aborted[2];
+    assert!(global_storage_accesses(withdraw).iter().any(|access| {
+        access.op == GlobalStorageOp::BorrowGlobalMut
+            && access.address == Some(*sender.address())
+            && access.resource_type == "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+            && access.success
+    }));
+    let extract = aborted[3];
+    assert_eq!(extract.args[1], amount.to_string());
+}
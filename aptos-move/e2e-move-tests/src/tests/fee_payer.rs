@@ -0,0 +1,146 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::SigningKey;
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::CoinStoreResource,
+    on_chain_config::FeatureFlag,
+    transaction::{
+        authenticator::AccountAuthenticator, RawTransaction, RawTransactionWithData,
+        SignedTransaction, TransactionStatus,
+    },
+};
+use move_core_types::vm_status::StatusCode;
+
+const GAS_UNIT_PRICE: u64 = 100;
+const AMOUNT: u64 = 1_000;
+
+fn read_coin(h: &MoveHarness, account: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(account, CoinStoreResource::struct_tag())
+        .unwrap()
+        .coin()
+}
+
+/// Builds a transfer of `AMOUNT` from `sender` to `recipient`.
+fn transfer_raw_txn(h: &MoveHarness, sender: &Account, recipient: &Account) -> RawTransaction {
+    sender
+        .transaction()
+        .sequence_number(h.sequence_number(sender.address()))
+        .max_gas_amount(2_000_000)
+        .gas_unit_price(GAS_UNIT_PRICE)
+        .payload(aptos_stdlib::aptos_coin_transfer(
+            *recipient.address(),
+            AMOUNT,
+        ))
+        .raw()
+}
+
+/// Signs the transfer as a fee payer transaction, whose gas is paid by `fee_payer`.
+fn transfer_with_fee_payer(
+    h: &MoveHarness,
+    sender: &Account,
+    recipient: &Account,
+    fee_payer: &Account,
+) -> SignedTransaction {
+    transfer_raw_txn(h, sender, recipient)
+        .sign_fee_payer(
+            &sender.privkey,
+            vec![],
+            vec![],
+            *fee_payer.address(),
+            &fee_payer.privkey,
+        )
+        .unwrap()
+        .into_inner()
+}
+
+#[test]
+fn test_fee_payer_pays_gas() {
+    let mut h = MoveHarness::new_with_features(vec![FeatureFlag::FEE_PAYER_ENABLED], vec![]);
+    let sender = h.new_account_with_key_pair();
+    let recipient = h.new_account_with_key_pair();
+    let fee_payer = h.new_account_with_key_pair();
+    let sender_start = read_coin(&h, sender.address());
+    let recipient_start = read_coin(&h, recipient.address());
+    let fee_payer_start = read_coin(&h, fee_payer.address());
+
+    let txn = transfer_with_fee_payer(&h, &sender, &recipient, &fee_payer);
+    let output = h.run_raw(txn);
+    assert_success!(output.status().to_owned());
+    assert!(output.gas_used() > 0);
+
+    // The sender only sends the transferred amount, and its sequence number is bumped, while the
+    // fee payer is charged for the gas.
+    assert_eq!(read_coin(&h, sender.address()), sender_start - AMOUNT);
+    assert_eq!(read_coin(&h, recipient.address()), recipient_start + AMOUNT);
+    assert_eq!(
+        read_coin(&h, fee_payer.address()),
+        fee_payer_start - output.gas_used() * GAS_UNIT_PRICE
+    );
+    assert_eq!(h.sequence_number(sender.address()), 1);
+    assert_eq!(h.sequence_number(fee_payer.address()), 0);
+}
+
+#[test]
+fn test_fee_payer_wrong_signature() {
+    let mut h = MoveHarness::new_with_features(vec![FeatureFlag::FEE_PAYER_ENABLED], vec![]);
+    let sender = h.new_account_with_key_pair();
+    let recipient = h.new_account_with_key_pair();
+    let fee_payer = h.new_account_with_key_pair();
+    let fee_payer_start = read_coin(&h, fee_payer.address());
+
+    let raw_txn = transfer_raw_txn(&h, &sender, &recipient);
+    let message = RawTransactionWithData::new_multi_agent_with_fee_payer(
+        raw_txn.clone(),
+        vec![],
+        *fee_payer.address(),
+    );
+    let sender_authenticator = AccountAuthenticator::ed25519(
+        sender.pubkey.clone(),
+        sender.privkey.sign(&message).unwrap(),
+    );
+    // The fee payer signs the raw transaction rather than the fee payer message, i.e., does not
+    // commit to paying for it.
+    let fee_payer_authenticator = AccountAuthenticator::ed25519(
+        fee_payer.pubkey.clone(),
+        fee_payer.privkey.sign(&raw_txn).unwrap(),
+    );
+    let txn = SignedTransaction::new_fee_payer(
+        raw_txn,
+        sender_authenticator,
+        vec![],
+        vec![],
+        *fee_payer.address(),
+        fee_payer_authenticator,
+    );
+
+    assert_eq!(
+        h.run(txn),
+        TransactionStatus::Discard(StatusCode::INVALID_SIGNATURE)
+    );
+    assert_eq!(read_coin(&h, fee_payer.address()), fee_payer_start);
+    assert_eq!(h.sequence_number(sender.address()), 0);
+}
+
+#[test]
+fn test_fee_payer_rejected_while_feature_disabled() {
+    let mut h = MoveHarness::new_with_features(vec![], vec![FeatureFlag::FEE_PAYER_ENABLED]);
+    let sender = h.new_account_with_key_pair();
+    let recipient = h.new_account_with_key_pair();
+    let fee_payer = h.new_account_with_key_pair();
+    let sender_start = read_coin(&h, sender.address());
+    let fee_payer_start = read_coin(&h, fee_payer.address());
+
+    let txn = transfer_with_fee_payer(&h, &sender, &recipient, &fee_payer);
+    assert_eq!(
+        h.run(txn),
+        TransactionStatus::Discard(StatusCode::FEATURE_UNDER_GATING)
+    );
+    assert_eq!(read_coin(&h, sender.address()), sender_start);
+    assert_eq!(read_coin(&h, fee_payer.address()), fee_payer_start);
+    assert_eq!(h.sequence_number(sender.address()), 0);
+}
@@ -0,0 +1,63 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MoveHarness;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use aptos_vm::AptosVM;
+
+#[test]
+fn test_gas_profiling_matches_standard_gas_meter() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_account_at(AccountAddress::from_hex_literal("0x121").unwrap());
+    let receiver = h.new_account_at(AccountAddress::from_hex_literal("0x122").unwrap());
+
+    let txn = h.create_simulated_transaction_payload(
+        &sender,
+        aptos_stdlib::aptos_account_transfer(*receiver.address(), 10),
+    );
+
+    let state_view = h.executor.get_state_view();
+    let (_, output) = AptosVM::simulate_signed_transaction(&txn, state_view);
+    let (_, profiled_output, gas_log) =
+        AptosVM::simulate_signed_transaction_with_gas_profiling(&txn, state_view);
+    let gas_log = gas_log.expect("transaction should have been executed");
+
+    let output = output.txn_output();
+    let profiled_output = profiled_output.txn_output();
+    assert_eq!(
+        profiled_output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+    assert_eq!(profiled_output.gas_used(), output.gas_used());
+    assert_eq!(
+        gas_log.to_summary().total_cost_in_gas_units,
+        output.gas_used()
+    );
+
+    // The entry function is the outermost frame and the functions it calls are its callees, with
+    // the cost of calling them charged to the caller.
+    let call_graph = &gas_log.call_graph;
+    assert_eq!(call_graph.name, "0x1::aptos_account::transfer");
+    assert!(call_graph.execution.contains_key("Call"));
+    assert!(call_graph.execution.contains_key("CallGeneric"));
+    let callees = call_graph
+        .callees
+        .iter()
+        .map(|callee| callee.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(callees, vec![
+        "0x1::account::exists_at",
+        "0x1::coin::is_account_registered",
+        "0x1::coin::transfer",
+    ]);
+
+    // Moving the coins happens in `coin::transfer` and the functions it calls.
+    let coin_transfer = &call_graph.callees[2];
+    assert!(!coin_transfer.callees.is_empty());
+    assert!(coin_transfer.total_cost() > coin_transfer.self_cost());
+    assert!(call_graph.total_cost() > coin_transfer.total_cost());
+}
@@ -4,12 +4,16 @@
 mod access_path_test;
 mod aggregator;
 mod attributes;
+mod block_partitioning;
 mod chain_id;
 mod code_publishing;
 mod common;
 mod error_map;
+mod execution_trace;
+mod fee_payer;
 mod framework_compatibility;
 mod gas;
+mod gas_profiling;
 mod generate_upgrade_script;
 mod governance_updates;
 mod infinite_loop;
@@ -27,12 +31,14 @@ mod per_category_gas_limits;
 mod resource_groups;
 mod rotate_auth_key;
 mod scripts;
+mod secp256r1_ecdsa_authenticator;
 mod simple_defi;
 mod smart_data_structures;
 mod stake;
 mod string_args;
 mod token_event_store;
 mod token_objects;
+mod transaction_context;
 mod transaction_fee;
 mod type_too_large;
 mod vector_numeric_address;
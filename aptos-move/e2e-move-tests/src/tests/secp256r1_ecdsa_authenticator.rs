@@ -0,0 +1,107 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{
+    secp256r1_ecdsa::{Secp256r1EcdsaPrivateKey, Secp256r1EcdsaPublicKey},
+    PrivateKey, SigningKey, Uniform,
+};
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::{
+    account_address::AccountAddress,
+    on_chain_config::FeatureFlag,
+    transaction::{
+        authenticator::{
+            AuthenticationKey, Secp256r1EcdsaSignatureEnvelope, TransactionAuthenticator,
+        },
+        SignedTransaction, TransactionStatus,
+    },
+};
+use move_core_types::{value::MoveValue, vm_status::StatusCode};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn generate_key_pair() -> (Secp256r1EcdsaPrivateKey, Secp256r1EcdsaPublicKey) {
+    let private_key = Secp256r1EcdsaPrivateKey::generate(&mut StdRng::from_seed([0u8; 32]));
+    let public_key = private_key.public_key();
+    (private_key, public_key)
+}
+
+/// Rotates the authentication key of `account` to the one of the secp256r1 ECDSA `public_key`.
+fn rotate_to_secp256r1_ecdsa(
+    harness: &mut MoveHarness,
+    account: &Account,
+    public_key: &Secp256r1EcdsaPublicKey,
+) {
+    let auth_key = AuthenticationKey::secp256r1_ecdsa(public_key);
+    harness.executor.exec(
+        "account",
+        "rotate_authentication_key_internal",
+        vec![],
+        vec![
+            MoveValue::Signer(*account.address())
+                .simple_serialize()
+                .unwrap(),
+            bcs::to_bytes(&auth_key.to_vec()).unwrap(),
+        ],
+    );
+}
+
+/// Signs a transfer of 1 octa from `account` with a raw secp256r1 ECDSA signature.
+fn transfer_signed_with_secp256r1_ecdsa(
+    harness: &mut MoveHarness,
+    account: &Account,
+    private_key: &Secp256r1EcdsaPrivateKey,
+) -> SignedTransaction {
+    let raw_txn = account
+        .transaction()
+        .sequence_number(harness.sequence_number(account.address()))
+        .max_gas_amount(2_000_000)
+        .gas_unit_price(100)
+        .payload(aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 1))
+        .raw();
+    let signature = private_key.sign(&raw_txn).unwrap();
+    SignedTransaction::new_with_authenticator(
+        raw_txn,
+        TransactionAuthenticator::secp256r1_ecdsa(
+            private_key.public_key(),
+            Secp256r1EcdsaSignatureEnvelope::Raw(signature),
+        ),
+    )
+}
+
+#[test]
+fn test_secp256r1_ecdsa_rejected_while_feature_disabled() {
+    let mut harness =
+        MoveHarness::new_with_features(vec![], vec![FeatureFlag::SECP256R1_ECDSA_AUTHENTICATOR]);
+    let account = harness.new_account_with_key_pair();
+    let (private_key, public_key) = generate_key_pair();
+    rotate_to_secp256r1_ecdsa(&mut harness, &account, &public_key);
+
+    let txn = transfer_signed_with_secp256r1_ecdsa(&mut harness, &account, &private_key);
+    assert_eq!(
+        harness.run(txn),
+        TransactionStatus::Discard(StatusCode::FEATURE_UNDER_GATING)
+    );
+    assert_eq!(harness.sequence_number(account.address()), 0);
+}
+
+#[test]
+fn test_secp256r1_ecdsa_accepted_with_scheme_auth_key() {
+    let mut harness =
+        MoveHarness::new_with_features(vec![FeatureFlag::SECP256R1_ECDSA_AUTHENTICATOR], vec![]);
+    let account = harness.new_account_with_key_pair();
+    let (private_key, public_key) = generate_key_pair();
+
+    // The account still has its Ed25519 authentication key, which the prologue must reject.
+    let txn = transfer_signed_with_secp256r1_ecdsa(&mut harness, &account, &private_key);
+    assert_eq!(
+        harness.run(txn),
+        TransactionStatus::Discard(StatusCode::INVALID_AUTH_KEY)
+    );
+
+    rotate_to_secp256r1_ecdsa(&mut harness, &account, &public_key);
+    let txn = transfer_signed_with_secp256r1_ecdsa(&mut harness, &account, &private_key);
+    assert_success!(harness.run(txn));
+    assert_eq!(harness.sequence_number(account.address()), 1);
+}
@@ -0,0 +1,61 @@
+module 0x1::transaction_context_test {
+    use std::option;
+    use std::string::{Self, String};
+    use aptos_framework::transaction_context;
+
+    /// Since tests in e2e-move-tests/ can only call entry functions which don't have return values, we store what the
+    /// transaction context returns in this resource, which we can read back in our e2e-move-tests/ test.
+    struct TransactionContextStore has key {
+        sender: address,
+        secondary_signers: vector<address>,
+        gas_payer: address,
+        max_gas_amount: u64,
+        gas_unit_price: u64,
+        account_address: address,
+        module_name: String,
+        function_name: String,
+        type_arg_names: vector<String>,
+    }
+
+    fun init_module(sender: &signer) {
+        move_to(sender,
+            TransactionContextStore {
+                sender: @0x0,
+                secondary_signers: vector[],
+                gas_payer: @0x0,
+                max_gas_amount: 0,
+                gas_unit_price: 0,
+                account_address: @0x0,
+                module_name: string::utf8(b""),
+                function_name: string::utf8(b""),
+                type_arg_names: vector[],
+            }
+        );
+    }
+
+    fun store_transaction_context() acquires TransactionContextStore {
+        let store = borrow_global_mut<TransactionContextStore>(@0x1);
+        store.sender = transaction_context::sender();
+        store.secondary_signers = transaction_context::secondary_signers();
+        store.gas_payer = transaction_context::gas_payer();
+        store.max_gas_amount = transaction_context::max_gas_amount();
+        store.gas_unit_price = transaction_context::gas_unit_price();
+
+        let payload = option::destroy_some(transaction_context::entry_function_payload());
+        store.account_address = transaction_context::account_address(&payload);
+        store.module_name = transaction_context::module_name(&payload);
+        store.function_name = transaction_context::function_name(&payload);
+        store.type_arg_names = transaction_context::type_arg_names(&payload);
+    }
+
+    /// Stores the transaction context of a transaction signed by its sender only.
+    public entry fun store_from_entry_function<T>(_s: &signer) acquires TransactionContextStore {
+        store_transaction_context();
+    }
+
+    /// Stores the transaction context of a multi-agent transaction.
+    public entry fun store_from_multi_agent_entry_function<T>(_s: &signer, _secondary: &signer)
+    acquires TransactionContextStore {
+        store_transaction_context();
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, tests::common, MoveHarness};
+use aptos_types::{
+    account_address::AccountAddress,
+    on_chain_config::FeatureFlag,
+    transaction::{EntryFunction, TransactionPayload},
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+    parser::{parse_struct_tag, parse_type_tag},
+};
+use serde::{Deserialize, Serialize};
+
+const MAX_GAS_AMOUNT: u64 = 2_000_000;
+const GAS_UNIT_PRICE: u64 = 100;
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct TransactionContextStore {
+    sender: AccountAddress,
+    secondary_signers: Vec<AccountAddress>,
+    gas_payer: AccountAddress,
+    max_gas_amount: u64,
+    gas_unit_price: u64,
+    account_address: AccountAddress,
+    module_name: String,
+    function_name: String,
+    type_arg_names: Vec<String>,
+}
+
+fn setup(features: Vec<FeatureFlag>) -> MoveHarness {
+    let mut harness = MoveHarness::new_with_features(features, vec![]);
+    let account = harness.new_account_at(AccountAddress::ONE);
+    assert_success!(harness.publish_package(
+        &account,
+        &common::test_dir_path("transaction_context.data/pack")
+    ));
+    harness
+}
+
+fn entry_function_payload(function: &str) -> TransactionPayload {
+    TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(
+            AccountAddress::ONE,
+            Identifier::new("transaction_context_test").unwrap(),
+        ),
+        Identifier::new(function).unwrap(),
+        vec![coin_type()],
+        vec![],
+    ))
+}
+
+fn coin_type() -> TypeTag {
+    parse_type_tag("0x1::aptos_coin::AptosCoin").unwrap()
+}
+
+fn read_store(harness: &MoveHarness) -> TransactionContextStore {
+    harness
+        .read_resource::<TransactionContextStore>(
+            &AccountAddress::ONE,
+            parse_struct_tag("0x1::transaction_context_test::TransactionContextStore").unwrap(),
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_transaction_context_of_entry_function() {
+    let mut harness = setup(vec![]);
+    let sender = harness.new_account_with_key_pair();
+
+    let txn = sender
+        .transaction()
+        .sequence_number(harness.sequence_number(sender.address()))
+        .max_gas_amount(MAX_GAS_AMOUNT)
+        .gas_unit_price(GAS_UNIT_PRICE)
+        .payload(entry_function_payload("store_from_entry_function"))
+        .sign();
+    assert_success!(harness.run(txn));
+
+    // Without a fee payer, the sender pays for the gas.
+    assert_eq!(read_store(&harness), TransactionContextStore {
+        sender: *sender.address(),
+        secondary_signers: vec![],
+        gas_payer: *sender.address(),
+        max_gas_amount: MAX_GAS_AMOUNT,
+        gas_unit_price: GAS_UNIT_PRICE,
+        account_address: AccountAddress::ONE,
+        module_name: "transaction_context_test".to_string(),
+        function_name: "store_from_entry_function".to_string(),
+        type_arg_names: vec![coin_type().to_string()],
+    });
+}
+
+#[test]
+fn test_transaction_context_of_multi_agent_entry_function_with_fee_payer() {
+    let mut harness = setup(vec![FeatureFlag::FEE_PAYER_ENABLED]);
+    let sender = harness.new_account_with_key_pair();
+    let secondary_signer = harness.new_account_with_key_pair();
+    let fee_payer = harness.new_account_with_key_pair();
+
+    let txn = sender
+        .transaction()
+        .sequence_number(harness.sequence_number(sender.address()))
+        .max_gas_amount(MAX_GAS_AMOUNT)
+        .gas_unit_price(GAS_UNIT_PRICE)
+        .payload(entry_function_payload(
+            "store_from_multi_agent_entry_function",
+        ))
+        .raw()
+        .sign_fee_payer(
+            &sender.privkey,
+            vec![*secondary_signer.address()],
+            vec![&secondary_signer.privkey],
+            *fee_payer.address(),
+            &fee_payer.privkey,
+        )
+        .unwrap()
+        .into_inner();
+    assert_success!(harness.run(txn));
+
+    assert_eq!(read_store(&harness), TransactionContextStore {
+        sender: *sender.address(),
+        secondary_signers: vec![*secondary_signer.address()],
+        gas_payer: *fee_payer.address(),
+        max_gas_amount: MAX_GAS_AMOUNT,
+        gas_unit_price: GAS_UNIT_PRICE,
+        account_address: AccountAddress::ONE,
+        module_name: "transaction_context_test".to_string(),
+        function_name: "store_from_multi_agent_entry_function".to_string(),
+        type_arg_names: vec![coin_type().to_string()],
+    });
+}
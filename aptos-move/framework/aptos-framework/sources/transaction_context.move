@@ -1,4 +1,83 @@
 module aptos_framework::transaction_context {
+    use std::option::Option;
+    use std::string::String;
+
+    /// The transaction context is only available in user transactions.
+    const ETRANSACTION_CONTEXT_NOT_AVAILABLE: u64 = 1;
+
+    /// The identity of the entry function called by a user transaction.
+    struct EntryFunctionPayload has copy, drop {
+        account_address: address,
+        module_name: String,
+        function_name: String,
+        type_arg_names: vector<String>,
+    }
+
     /// Return the script hash of the current entry function.
     public native fun get_script_hash(): vector<u8>;
+
+    /// Return a new address, unique across all the transactions and across all the calls to this
+    /// function in the current transaction. It is derived from the hash of the session id, which
+    /// identifies a user transaction by its sender and sequence number, and from a counter.
+    /// Nobody has a signer for it.
+    public native fun generate_unique_address(): address;
+
+    /// Return the sender of the current transaction.
+    /// Aborts with `ETRANSACTION_CONTEXT_NOT_AVAILABLE` outside of a user transaction.
+    public native fun sender(): address;
+
+    /// Return the secondary signers of the current multi-agent transaction, empty otherwise.
+    /// Aborts with `ETRANSACTION_CONTEXT_NOT_AVAILABLE` outside of a user transaction.
+    public native fun secondary_signers(): vector<address>;
+
+    /// Return the account paying the gas of the current transaction, i.e., its fee payer if it has
+    /// one and its sender otherwise.
+    /// Aborts with `ETRANSACTION_CONTEXT_NOT_AVAILABLE` outside of a user transaction.
+    public native fun gas_payer(): address;
+
+    /// Return the maximum number of gas units the current transaction may use.
+    /// Aborts with `ETRANSACTION_CONTEXT_NOT_AVAILABLE` outside of a user transaction.
+    public native fun max_gas_amount(): u64;
+
+    /// Return the price of a gas unit in the current transaction.
+    /// Aborts with `ETRANSACTION_CONTEXT_NOT_AVAILABLE` outside of a user transaction.
+    public native fun gas_unit_price(): u64;
+
+    /// Return the entry function called by the current transaction, or none if it is not an entry
+    /// function transaction, e.g., a script or a multisig transaction.
+    /// Aborts with `ETRANSACTION_CONTEXT_NOT_AVAILABLE` outside of a user transaction.
+    public native fun entry_function_payload(): Option<EntryFunctionPayload>;
+
+    /// Return the address of the account where the module of the entry function is published.
+    public fun account_address(payload: &EntryFunctionPayload): address {
+        payload.account_address
+    }
+
+    /// Return the name of the module of the entry function.
+    public fun module_name(payload: &EntryFunctionPayload): String {
+        payload.module_name
+    }
+
+    /// Return the name of the entry function.
+    public fun function_name(payload: &EntryFunctionPayload): String {
+        payload.function_name
+    }
+
+    /// Return the type arguments of the entry function, e.g., `0x1::aptos_coin::AptosCoin`.
+    public fun type_arg_names(payload: &EntryFunctionPayload): vector<String> {
+        payload.type_arg_names
+    }
+
+    #[test]
+    fun test_generate_unique_address() {
+        let first = generate_unique_address();
+        let second = generate_unique_address();
+        assert!(first != second, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0x30001, location = Self)]
+    fun test_sender_outside_of_user_transaction() {
+        sender();
+    }
 }
@@ -6,4 +6,33 @@ spec aptos_framework::transaction_context {
     }
 
     spec fun spec_get_script_hash(): vector<u8>;
+
+    spec generate_unique_address(): address {
+        pragma opaque;
+        aborts_if false;
+    }
+
+    spec sender(): address {
+        pragma opaque;
+    }
+
+    spec secondary_signers(): vector<address> {
+        pragma opaque;
+    }
+
+    spec gas_payer(): address {
+        pragma opaque;
+    }
+
+    spec max_gas_amount(): u64 {
+        pragma opaque;
+    }
+
+    spec gas_unit_price(): u64 {
+        pragma opaque;
+    }
+
+    spec entry_function_payload(): Option<EntryFunctionPayload> {
+        pragma opaque;
+    }
 }
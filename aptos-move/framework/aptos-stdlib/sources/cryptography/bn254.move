@@ -0,0 +1,285 @@
+/// This module implements arithmetic on the BN254 (a.k.a. alt_bn128) pairing-friendly elliptic curve, and the
+/// verification of Groth16 zkSNARK proofs over it.
+///
+/// Points are represented in the compressed arkworks format: 32 bytes for points of the G1 group and 64 bytes for
+/// points of the G2 group. Scalars are elements of the scalar field of both groups, represented as 32 little-endian
+/// bytes.
+module aptos_std::bn254 {
+    use std::option::Option;
+
+    /// An error occurred while deserializing, for example due to wrong input size.
+    const E_DESERIALIZE: u64 = 1;   // This code must be the same, if ever returned from the native Rust implementation.
+
+    /// The number of G1 points and of G2 points given to a pairing check differ.
+    const E_MISMATCHED_LENGTHS: u64 = 2;
+
+    /// The size of a serialized G1 point, in bytes.
+    const G1_POINT_NUM_BYTES: u64 = 32;
+
+    /// The size of a serialized G2 point, in bytes.
+    const G2_POINT_NUM_BYTES: u64 = 64;
+
+    /// The size of a serialized scalar, in bytes.
+    const SCALAR_NUM_BYTES: u64 = 32;
+
+    /// A point of the prime-order subgroup G1.
+    struct G1Point has copy, drop, store {
+        bytes: vector<u8>
+    }
+
+    /// A point of the prime-order subgroup G2.
+    struct G2Point has copy, drop, store {
+        bytes: vector<u8>
+    }
+
+    /// An element of the scalar field of G1 and G2.
+    struct Scalar has copy, drop, store {
+        bytes: vector<u8>
+    }
+
+    /// A Groth16 verifying key, with one element of `gamma_abc_g1` more than the number of public inputs.
+    struct Groth16VerifyingKey has copy, drop, store {
+        alpha_g1: G1Point,
+        beta_g2: G2Point,
+        gamma_g2: G2Point,
+        delta_g2: G2Point,
+        gamma_abc_g1: vector<G1Point>,
+    }
+
+    /// A Groth16 proof.
+    struct Groth16Proof has copy, drop, store {
+        a: G1Point,
+        b: G2Point,
+        c: G1Point,
+    }
+
+    /// Deserializes a G1 point, returning `None` if `bytes` is not the compressed representation of a point of G1.
+    public fun g1_point_from_bytes(bytes: vector<u8>): Option<G1Point> {
+        if (std::vector::length(&bytes) == G1_POINT_NUM_BYTES && g1_point_is_valid_internal(bytes)) {
+            std::option::some(G1Point { bytes })
+        } else {
+            std::option::none<G1Point>()
+        }
+    }
+
+    /// Deserializes a G2 point, returning `None` if `bytes` is not the compressed representation of a point of the
+    /// prime-order subgroup G2.
+    public fun g2_point_from_bytes(bytes: vector<u8>): Option<G2Point> {
+        if (std::vector::length(&bytes) == G2_POINT_NUM_BYTES && g2_point_is_valid_internal(bytes)) {
+            std::option::some(G2Point { bytes })
+        } else {
+            std::option::none<G2Point>()
+        }
+    }
+
+    /// Deserializes a scalar, returning `None` if `bytes` is not the canonical representation of a scalar, i.e., is
+    /// not less than the order of the groups.
+    public fun scalar_from_bytes(bytes: vector<u8>): Option<Scalar> {
+        if (std::vector::length(&bytes) == SCALAR_NUM_BYTES && scalar_is_canonical_internal(bytes)) {
+            std::option::some(Scalar { bytes })
+        } else {
+            std::option::none<Scalar>()
+        }
+    }
+
+    /// Serializes a G1 point to 32 bytes.
+    public fun g1_point_to_bytes(point: &G1Point): vector<u8> {
+        point.bytes
+    }
+
+    /// Serializes a G2 point to 64 bytes.
+    public fun g2_point_to_bytes(point: &G2Point): vector<u8> {
+        point.bytes
+    }
+
+    /// Serializes a scalar to 32 bytes.
+    public fun scalar_to_bytes(scalar: &Scalar): vector<u8> {
+        scalar.bytes
+    }
+
+    /// Returns `a + b`.
+    public fun g1_add(a: &G1Point, b: &G1Point): G1Point {
+        G1Point { bytes: g1_add_internal(a.bytes, b.bytes) }
+    }
+
+    /// Returns `scalar * point`.
+    public fun g1_mul(point: &G1Point, scalar: &Scalar): G1Point {
+        G1Point { bytes: g1_mul_internal(point.bytes, scalar.bytes) }
+    }
+
+    /// Returns `a + b`.
+    public fun g2_add(a: &G2Point, b: &G2Point): G2Point {
+        G2Point { bytes: g2_add_internal(a.bytes, b.bytes) }
+    }
+
+    /// Returns `scalar * point`.
+    public fun g2_mul(point: &G2Point, scalar: &Scalar): G2Point {
+        G2Point { bytes: g2_mul_internal(point.bytes, scalar.bytes) }
+    }
+
+    /// Returns true if the product of the pairings `e(g1_points[i], g2_points[i])` is the identity of the target
+    /// group, e.g., to check that `e(a, b) == e(c, d)` as `e(a, b) * e(-c, d) == 1`. Aborts if the vectors have
+    /// different lengths.
+    public fun pairing_check(g1_points: &vector<G1Point>, g2_points: &vector<G2Point>): bool {
+        assert!(
+            std::vector::length(g1_points) == std::vector::length(g2_points),
+            std::error::invalid_argument(E_MISMATCHED_LENGTHS)
+        );
+        pairing_check_internal(*g1_points, *g2_points)
+    }
+
+    /// Constructs a Groth16 verifying key from its elements.
+    public fun new_groth16_verifying_key(
+        alpha_g1: G1Point,
+        beta_g2: G2Point,
+        gamma_g2: G2Point,
+        delta_g2: G2Point,
+        gamma_abc_g1: vector<G1Point>,
+    ): Groth16VerifyingKey {
+        Groth16VerifyingKey { alpha_g1, beta_g2, gamma_g2, delta_g2, gamma_abc_g1 }
+    }
+
+    /// Constructs a Groth16 proof from its elements.
+    public fun new_groth16_proof(a: G1Point, b: G2Point, c: G1Point): Groth16Proof {
+        Groth16Proof { a, b, c }
+    }
+
+    /// Returns true if `proof` is a valid proof for `public_inputs` under `vk`. A proof is never valid for a number of
+    /// public inputs other than the one of the key.
+    public fun groth16_verify(
+        vk: &Groth16VerifyingKey,
+        public_inputs: &vector<Scalar>,
+        proof: &Groth16Proof,
+    ): bool {
+        groth16_verify_internal(
+            vk.alpha_g1.bytes,
+            vk.beta_g2.bytes,
+            vk.gamma_g2.bytes,
+            vk.delta_g2.bytes,
+            vk.gamma_abc_g1,
+            *public_inputs,
+            proof.a.bytes,
+            proof.b.bytes,
+            proof.c.bytes,
+        )
+    }
+
+    //
+    // Native functions
+    //
+
+    /// Returns true if `bytes` is the compressed representation of a point of G1.
+    native fun g1_point_is_valid_internal(bytes: vector<u8>): bool;
+
+    /// Returns true if `bytes` is the compressed representation of a point of the prime-order subgroup G2.
+    native fun g2_point_is_valid_internal(bytes: vector<u8>): bool;
+
+    /// Returns true if `bytes` is the canonical representation of a scalar.
+    native fun scalar_is_canonical_internal(bytes: vector<u8>): bool;
+
+    native fun g1_add_internal(a: vector<u8>, b: vector<u8>): vector<u8>;
+
+    native fun g1_mul_internal(point: vector<u8>, scalar: vector<u8>): vector<u8>;
+
+    native fun g2_add_internal(a: vector<u8>, b: vector<u8>): vector<u8>;
+
+    native fun g2_mul_internal(point: vector<u8>, scalar: vector<u8>): vector<u8>;
+
+    native fun pairing_check_internal(g1_points: vector<G1Point>, g2_points: vector<G2Point>): bool;
+
+    native fun groth16_verify_internal(
+        alpha_g1: vector<u8>,
+        beta_g2: vector<u8>,
+        gamma_g2: vector<u8>,
+        delta_g2: vector<u8>,
+        gamma_abc_g1: vector<G1Point>,
+        public_inputs: vector<Scalar>,
+        proof_a: vector<u8>,
+        proof_b: vector<u8>,
+        proof_c: vector<u8>,
+    ): bool;
+
+    //
+    // Tests
+    //
+
+    // The generator of G1, i.e., the point (1, 2).
+    const G1_GENERATOR: vector<u8> = x"0100000000000000000000000000000000000000000000000000000000000000";
+
+    // The negation of the generator of G1, i.e., the point (1, -2).
+    const G1_GENERATOR_NEG: vector<u8> = x"0100000000000000000000000000000000000000000000000000000000000080";
+
+    // The generator of G2.
+    const G2_GENERATOR: vector<u8> = x"edf692d95cbdde46ddda5ef7d422436779445c5e66006a42761e1f12efde0018c212f3aeb785e49712e7a9353349aaf1255dfb31b7bf60723a480d9293938e19";
+
+    #[test_only]
+    fun scalar_from_u8(value: u8): Scalar {
+        let bytes = vector[value];
+        while (std::vector::length(&bytes) < SCALAR_NUM_BYTES) {
+            std::vector::push_back(&mut bytes, 0);
+        };
+        std::option::extract(&mut scalar_from_bytes(bytes))
+    }
+
+    #[test]
+    fun test_deserialization() {
+        assert!(std::option::is_some(&g1_point_from_bytes(G1_GENERATOR)), 1);
+        assert!(std::option::is_some(&g2_point_from_bytes(G2_GENERATOR)), 1);
+
+        // Wrong sizes
+        assert!(std::option::is_none(&g1_point_from_bytes(G2_GENERATOR)), 1);
+        assert!(std::option::is_none(&g2_point_from_bytes(G1_GENERATOR)), 1);
+
+        // x = 0 is not on the curve: y^2 = 3 has no solution
+        assert!(std::option::is_none(&g1_point_from_bytes(x"0000000000000000000000000000000000000000000000000000000000000000")), 1);
+
+        // Not less than the order of the groups
+        assert!(std::option::is_none(&scalar_from_bytes(x"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff3f")), 1);
+        assert!(std::option::is_some(&scalar_from_bytes(x"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0f")), 1);
+    }
+
+    #[test]
+    fun test_group_operations() {
+        let g1 = std::option::extract(&mut g1_point_from_bytes(G1_GENERATOR));
+        let g2 = std::option::extract(&mut g2_point_from_bytes(G2_GENERATOR));
+        let three = scalar_from_u8(3);
+
+        assert!(g1_add(&g1_add(&g1, &g1), &g1) == g1_mul(&g1, &three), 1);
+        assert!(g2_add(&g2_add(&g2, &g2), &g2) == g2_mul(&g2, &three), 1);
+        assert!(g1_add(&g1, &g1) != g1_mul(&g1, &three), 1);
+    }
+
+    #[test]
+    fun test_pairing_check() {
+        let g1 = std::option::extract(&mut g1_point_from_bytes(G1_GENERATOR));
+        let g1_neg = std::option::extract(&mut g1_point_from_bytes(G1_GENERATOR_NEG));
+        let g2 = std::option::extract(&mut g2_point_from_bytes(G2_GENERATOR));
+        let two = scalar_from_u8(2);
+
+        // e(g1, g2) * e(-g1, g2) == 1
+        assert!(pairing_check(&vector[g1, g1_neg], &vector[g2, g2]), 1);
+        // e(2 * g1, g2) * e(-g1, 2 * g2) == 1
+        assert!(pairing_check(&vector[g1_mul(&g1, &two), g1_neg], &vector[g2, g2_mul(&g2, &two)]), 1);
+        // e(g1, g2) != 1
+        assert!(!pairing_check(&vector[g1], &vector[g2]), 1);
+        assert!(pairing_check(&vector[], &vector[]), 1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0x10002, location = Self)]
+    fun test_pairing_check_mismatched_lengths() {
+        let g1 = std::option::extract(&mut g1_point_from_bytes(G1_GENERATOR));
+        pairing_check(&vector[g1], &vector[]);
+    }
+
+    #[test]
+    fun test_groth16_verify_wrong_number_of_inputs() {
+        let g1 = std::option::extract(&mut g1_point_from_bytes(G1_GENERATOR));
+        let g2 = std::option::extract(&mut g2_point_from_bytes(G2_GENERATOR));
+        let vk = new_groth16_verifying_key(g1, g2, g2, g2, vector[g1, g1]);
+        let proof = new_groth16_proof(g1, g2, g1);
+
+        assert!(!groth16_verify(&vk, &vector[], &proof), 1);
+        assert!(!groth16_verify(&vk, &vector[scalar_from_u8(1), scalar_from_u8(2)], &proof), 1);
+    }
+}
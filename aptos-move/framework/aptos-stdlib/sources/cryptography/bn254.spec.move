@@ -0,0 +1,46 @@
+spec aptos_std::bn254 {
+    spec g1_point_is_valid_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+
+    spec g2_point_is_valid_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+
+    spec scalar_is_canonical_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+
+    spec g1_add_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+
+    spec g1_mul_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+
+    spec g2_add_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+
+    spec g2_mul_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+
+    spec pairing_check_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+
+    spec groth16_verify_internal {
+        // TODO: temporary mockup.
+        pragma opaque;
+    }
+}
@@ -0,0 +1,87 @@
+/// Formatting of Move values as human-readable strings, e.g., to build token URIs or error
+/// messages. The formatting is deterministic:
+/// - integers are formatted in decimal, e.g., `42`, or `42u64` when including their types,
+/// - addresses are prefixed with `@`, e.g., `@0x1`, or `@0x00..01` when canonicalized,
+/// - byte vectors are formatted in hex, e.g., `0x0102`, and other vectors as lists, e.g., `[1, 2]`,
+/// - strings are quoted, with `"`, `\` and control characters escaped as `\"`, `\\` and `\xNN`,
+///   e.g., `"aptos"`,
+/// - options are formatted as `none` or `some(42)`,
+/// - other structs are formatted with their type, e.g., `0x1::m::Point { x: 1, y: 2 }`.
+module aptos_std::string_utils {
+    use std::string::String;
+
+    /// Format a value on a single line.
+    public fun to_string<T>(s: &T): String {
+        native_format(s, false, true, false)
+    }
+
+    /// Format a value on a single line, with the addresses written in full, i.e., with 64 hex
+    /// digits.
+    public fun to_string_with_canonical_addresses<T>(s: &T): String {
+        native_format(s, true, true, false)
+    }
+
+    /// Format a value on a single line, with the integers suffixed with their types.
+    public fun to_string_with_integer_types<T>(s: &T): String {
+        native_format(s, false, true, true)
+    }
+
+    /// Format a value on multiple lines, with one line per vector element and struct field.
+    public fun debug_string<T>(s: &T): String {
+        native_format(s, false, false, false)
+    }
+
+    native fun native_format<T>(s: &T, canonicalize: bool, single_line: bool, include_int_types: bool): String;
+
+    #[test_only]
+    use std::option;
+    #[test_only]
+    use std::string;
+
+    #[test_only]
+    struct Point has drop {
+        x: u64,
+        y: u64,
+    }
+
+    #[test]
+    fun test_format_primitives() {
+        assert!(to_string(&42u64) == string::utf8(b"42"), 1);
+        assert!(to_string_with_integer_types(&42u8) == string::utf8(b"42u8"), 2);
+        assert!(to_string(&true) == string::utf8(b"true"), 3);
+        assert!(to_string(&@0x1) == string::utf8(b"@0x1"), 4);
+        assert!(
+            to_string_with_canonical_addresses(&@0x1)
+                == string::utf8(b"@0x0000000000000000000000000000000000000000000000000000000000000001"),
+            5
+        );
+    }
+
+    #[test]
+    fun test_format_vectors() {
+        assert!(to_string(&b"ab") == string::utf8(b"0x6162"), 1);
+        assert!(to_string(&vector[1, 2, 3]) == string::utf8(b"[1, 2, 3]"), 2);
+        assert!(to_string(&vector<u64>[]) == string::utf8(b"[]"), 3);
+        assert!(to_string_with_integer_types(&vector[vector[1u16]]) == string::utf8(b"[[1u16]]"), 4);
+    }
+
+    #[test]
+    fun test_format_structs() {
+        assert!(to_string(&string::utf8(b"aptos")) == string::utf8(b"\"aptos\""), 1);
+        assert!(to_string(&option::some(42)) == string::utf8(b"some(42)"), 2);
+        assert!(to_string(&option::none<u64>()) == string::utf8(b"none"), 3);
+        assert!(
+            to_string(&Point { x: 1, y: 2 }) == string::utf8(b"0x1::string_utils::Point { x: 1, y: 2 }"),
+            4
+        );
+    }
+
+    #[test]
+    fun test_debug_string() {
+        assert!(
+            debug_string(&vector[Point { x: 1, y: 2 }])
+                == string::utf8(b"[\n    0x1::string_utils::Point {\n        x: 1,\n        y: 2,\n    },\n]"),
+            1
+        );
+    }
+}
@@ -0,0 +1,6 @@
+spec aptos_std::string_utils {
+    spec native_format<T>(s: &T, canonicalize: bool, single_line: bool, include_int_types: bool): String {
+        pragma opaque;
+        aborts_if false;
+    }
+}
@@ -152,6 +152,38 @@ module std::features {
         is_enabled(BLS12381_BASIC_OPERATIONS)
     }
 
+    /// Whether transactions can be authenticated with secp256r1 ECDSA signatures, made either
+    /// directly or through WebAuthn assertions, e.g., by passkeys.
+    /// Lifetime: transient
+    const SECP256R1_ECDSA_AUTHENTICATOR: u64 = 13;
+
+    public fun get_secp256r1_ecdsa_authenticator_feature(): u64 { SECP256R1_ECDSA_AUTHENTICATOR }
+
+    public fun secp256r1_ecdsa_authenticator_enabled(): bool acquires Features {
+        is_enabled(SECP256R1_ECDSA_AUTHENTICATOR)
+    }
+
+    /// Whether transactions can have their gas paid by a fee payer account other than the sender.
+    /// Lifetime: transient
+    const FEE_PAYER_ENABLED: u64 = 14;
+
+    public fun get_fee_payer_feature(): u64 { FEE_PAYER_ENABLED }
+
+    public fun fee_payer_enabled(): bool acquires Features {
+        is_enabled(FEE_PAYER_ENABLED)
+    }
+
+    /// Whether blocks are reordered by the conflicts predicted by the read/write set analysis
+    /// before being executed in parallel, on the nodes where the analysis is available.
+    /// Lifetime: transient
+    const BLOCK_PARTITIONING: u64 = 15;
+
+    public fun get_block_partitioning_feature(): u64 { BLOCK_PARTITIONING }
+
+    public fun block_partitioning_enabled(): bool acquires Features {
+        is_enabled(BLOCK_PARTITIONING)
+    }
+
     // ============================================================================================
     // Feature Flag Implementation
 
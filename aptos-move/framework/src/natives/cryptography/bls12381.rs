@@ -39,7 +39,9 @@ use std::{collections::VecDeque, convert::TryFrom, sync::Arc};
 
 /// Pops a `Vec<T>` off the argument stack and converts it to a `Vec<Vec<u8>>` by reading the first
 /// field of `T`, which is a `Vec<u8>` field named `bytes`.
-fn pop_as_vec_of_vec_u8(arguments: &mut VecDeque<Value>) -> SafeNativeResult<Vec<Vec<u8>>> {
+pub(crate) fn pop_as_vec_of_vec_u8(
+    arguments: &mut VecDeque<Value>,
+) -> SafeNativeResult<Vec<Vec<u8>>> {
     let structs = safely_pop_vec_arg!(arguments, Struct);
     let mut v = Vec::with_capacity(structs.len());
 
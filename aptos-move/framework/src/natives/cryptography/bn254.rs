@@ -0,0 +1,859 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Natives for arithmetic on the BN254 (a.k.a. alt_bn128) pairing-friendly elliptic curve and for
+//! the verification of Groth16 zkSNARK proofs over it.
+//!
+//! Points are given in the compressed arkworks format, i.e., 32 bytes for G1 points and 64 bytes
+//! for G2 points, and scalars as 32 little-endian bytes. The Move module only constructs point
+//! structs from bytes that pass the `*_is_valid_internal` checks, so the other natives skip the
+//! (expensive) subgroup checks when deserializing their arguments.
+
+use crate::{
+    natives::{
+        cryptography::bls12381::pop_as_vec_of_vec_u8,
+        helpers::{make_safe_native, SafeNativeContext, SafeNativeError, SafeNativeResult},
+    },
+    safely_pop_arg,
+};
+use aptos_types::on_chain_config::{Features, TimedFeatures};
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::Zero;
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use move_core_types::gas_algebra::{InternalGas, InternalGasPerArg, NumArgs};
+use move_vm_runtime::native_functions::NativeFunction;
+use move_vm_types::{loaded_data::runtime_types::Type, values::Value};
+use smallvec::{smallvec, SmallVec};
+use std::{collections::VecDeque, sync::Arc};
+
+/// Abort code when deserialization fails (0x01 == INVALID_ARGUMENT)
+/// NOTE: This must match the code in the Move implementation
+pub mod abort_codes {
+    pub const NFE_DESERIALIZE: u64 = 0x01_0001;
+}
+
+#[derive(Debug, Clone)]
+pub struct GasParameters {
+    pub base: InternalGas,
+
+    pub per_g1_deserialize: InternalGasPerArg,
+    pub per_g1_serialize: InternalGasPerArg,
+    pub per_g1_add: InternalGasPerArg,
+    pub per_g1_mul: InternalGasPerArg,
+
+    pub per_g2_deserialize: InternalGasPerArg,
+    pub per_g2_serialize: InternalGasPerArg,
+    pub per_g2_subgroup_check: InternalGasPerArg,
+    pub per_g2_add: InternalGasPerArg,
+    pub per_g2_mul: InternalGasPerArg,
+
+    pub per_scalar_deserialize: InternalGasPerArg,
+
+    pub per_miller_loop: InternalGasPerArg,
+    pub per_final_exponentiation: InternalGasPerArg,
+}
+
+fn deserialize_g1(bytes: &[u8]) -> Option<G1Affine> {
+    G1Affine::deserialize_compressed_unchecked(bytes).ok()
+}
+
+fn deserialize_g2(bytes: &[u8]) -> Option<G2Affine> {
+    G2Affine::deserialize_compressed_unchecked(bytes).ok()
+}
+
+/// Deserializes a G1 point, checking that it is on the curve. G1 has a cofactor of 1, so this
+/// also makes sure it is in the prime-order subgroup.
+fn deserialize_g1_checked(bytes: &[u8]) -> Option<G1Affine> {
+    G1Affine::deserialize_compressed(bytes).ok()
+}
+
+/// Deserializes a G2 point, checking that it is on the curve and in the prime-order subgroup.
+fn deserialize_g2_checked(bytes: &[u8]) -> Option<G2Affine> {
+    G2Affine::deserialize_compressed(bytes).ok()
+}
+
+/// Deserializes a scalar, which must be canonical, i.e., less than the order of the groups.
+fn deserialize_scalar(bytes: &[u8]) -> Option<Fr> {
+    Fr::deserialize_compressed(bytes).ok()
+}
+
+fn serialize<T: CanonicalSerialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.compressed_size());
+    value
+        .serialize_compressed(&mut bytes)
+        .expect("Serializing to a vector cannot fail");
+    bytes
+}
+
+/// Returns the serialized sum of two G1 points, as computed by `g1_add_internal`.
+fn g1_add(p: G1Affine, q: G1Affine) -> Vec<u8> {
+    serialize(&(p + q).into_affine())
+}
+
+/// Returns the serialized product of a G1 point by a scalar, as computed by `g1_mul_internal`.
+fn g1_mul(point: G1Affine, scalar: Fr) -> Vec<u8> {
+    serialize(&(point * scalar).into_affine())
+}
+
+/// Returns the serialized sum of two G2 points, as computed by `g2_add_internal`.
+fn g2_add(p: G2Affine, q: G2Affine) -> Vec<u8> {
+    serialize(&(p + q).into_affine())
+}
+
+/// Returns the serialized product of a G2 point by a scalar, as computed by `g2_mul_internal`.
+fn g2_mul(point: G2Affine, scalar: Fr) -> Vec<u8> {
+    serialize(&(point * scalar).into_affine())
+}
+
+/// Returns whether the product of the pairings `e(g1_points[i], g2_points[i])` is the identity.
+fn pairing_check(g1_points: &[G1Affine], g2_points: &[G2Affine]) -> bool {
+    Bn254::multi_pairing(g1_points.iter().copied(), g2_points.iter().copied()).is_zero()
+}
+
+/// Returns whether `proof` is a valid Groth16 proof for `public_inputs` under `vk`. A verifying key
+/// which does not match the number of public inputs rejects all proofs.
+fn groth16_verify(vk: &VerifyingKey<Bn254>, public_inputs: &[Fr], proof: &Proof<Bn254>) -> bool {
+    if vk.gamma_abc_g1.len() != public_inputs.len() + 1 {
+        return false;
+    }
+    let pvk = ark_groth16::prepare_verifying_key(vk);
+    Groth16::<Bn254>::verify_proof(&pvk, proof, public_inputs).unwrap_or(false)
+}
+
+fn deserialize_error() -> SafeNativeError {
+    SafeNativeError::Abort {
+        abort_code: abort_codes::NFE_DESERIALIZE,
+    }
+}
+
+impl GasParameters {
+    fn deserialize_g1(
+        &self,
+        bytes: &[u8],
+        context: &mut SafeNativeContext,
+    ) -> SafeNativeResult<G1Affine> {
+        context.charge(self.per_g1_deserialize * NumArgs::one())?;
+        deserialize_g1(bytes).ok_or_else(deserialize_error)
+    }
+
+    fn deserialize_g2(
+        &self,
+        bytes: &[u8],
+        context: &mut SafeNativeContext,
+    ) -> SafeNativeResult<G2Affine> {
+        context.charge(self.per_g2_deserialize * NumArgs::one())?;
+        deserialize_g2(bytes).ok_or_else(deserialize_error)
+    }
+
+    fn deserialize_scalar(
+        &self,
+        bytes: &[u8],
+        context: &mut SafeNativeContext,
+    ) -> SafeNativeResult<Fr> {
+        context.charge(self.per_scalar_deserialize * NumArgs::one())?;
+        deserialize_scalar(bytes).ok_or_else(deserialize_error)
+    }
+}
+
+/***************************************************************************************************
+ * native fun g1_point_is_valid_internal
+ *
+ *   gas cost: base_cost + per_g1_deserialize_cost
+ *
+ **************************************************************************************************/
+fn native_g1_point_is_valid(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 1);
+
+    let bytes = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(gas_params.base + gas_params.per_g1_deserialize * NumArgs::one())?;
+
+    let valid = deserialize_g1_checked(&bytes).is_some();
+
+    Ok(smallvec![Value::bool(valid)])
+}
+
+/***************************************************************************************************
+ * native fun g2_point_is_valid_internal
+ *
+ *   gas cost: base_cost + per_g2_deserialize_cost + per_g2_subgroup_check_cost
+ *
+ **************************************************************************************************/
+fn native_g2_point_is_valid(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 1);
+
+    let bytes = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(
+        gas_params.base
+            + gas_params.per_g2_deserialize * NumArgs::one()
+            + gas_params.per_g2_subgroup_check * NumArgs::one(),
+    )?;
+
+    let valid = deserialize_g2_checked(&bytes).is_some();
+
+    Ok(smallvec![Value::bool(valid)])
+}
+
+/***************************************************************************************************
+ * native fun scalar_is_canonical_internal
+ *
+ *   gas cost: base_cost + per_scalar_deserialize_cost
+ *
+ **************************************************************************************************/
+fn native_scalar_is_canonical(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 1);
+
+    let bytes = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(gas_params.base + gas_params.per_scalar_deserialize * NumArgs::one())?;
+
+    Ok(smallvec![Value::bool(deserialize_scalar(&bytes).is_some())])
+}
+
+/***************************************************************************************************
+ * native fun g1_add_internal
+ *
+ *   gas cost: base_cost + 2 * per_g1_deserialize_cost + per_g1_add_cost + per_g1_serialize_cost
+ *
+ **************************************************************************************************/
+fn native_g1_add(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 2);
+
+    let q_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let p_bytes = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(gas_params.base)?;
+
+    let p = gas_params.deserialize_g1(&p_bytes, context)?;
+    let q = gas_params.deserialize_g1(&q_bytes, context)?;
+
+    context.charge(
+        gas_params.per_g1_add * NumArgs::one() + gas_params.per_g1_serialize * NumArgs::one(),
+    )?;
+
+    Ok(smallvec![Value::vector_u8(g1_add(p, q))])
+}
+
+/***************************************************************************************************
+ * native fun g1_mul_internal
+ *
+ *   gas cost: base_cost + per_g1_deserialize_cost + per_scalar_deserialize_cost
+ *                       + per_g1_mul_cost + per_g1_serialize_cost
+ *
+ **************************************************************************************************/
+fn native_g1_mul(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 2);
+
+    let scalar_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let point_bytes = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(gas_params.base)?;
+
+    let point = gas_params.deserialize_g1(&point_bytes, context)?;
+    let scalar = gas_params.deserialize_scalar(&scalar_bytes, context)?;
+
+    context.charge(
+        gas_params.per_g1_mul * NumArgs::one() + gas_params.per_g1_serialize * NumArgs::one(),
+    )?;
+
+    Ok(smallvec![Value::vector_u8(g1_mul(point, scalar))])
+}
+
+/***************************************************************************************************
+ * native fun g2_add_internal
+ *
+ *   gas cost: base_cost + 2 * per_g2_deserialize_cost + per_g2_add_cost + per_g2_serialize_cost
+ *
+ **************************************************************************************************/
+fn native_g2_add(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 2);
+
+    let q_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let p_bytes = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(gas_params.base)?;
+
+    let p = gas_params.deserialize_g2(&p_bytes, context)?;
+    let q = gas_params.deserialize_g2(&q_bytes, context)?;
+
+    context.charge(
+        gas_params.per_g2_add * NumArgs::one() + gas_params.per_g2_serialize * NumArgs::one(),
+    )?;
+
+    Ok(smallvec![Value::vector_u8(g2_add(p, q))])
+}
+
+/***************************************************************************************************
+ * native fun g2_mul_internal
+ *
+ *   gas cost: base_cost + per_g2_deserialize_cost + per_scalar_deserialize_cost
+ *                       + per_g2_mul_cost + per_g2_serialize_cost
+ *
+ **************************************************************************************************/
+fn native_g2_mul(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 2);
+
+    let scalar_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let point_bytes = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(gas_params.base)?;
+
+    let point = gas_params.deserialize_g2(&point_bytes, context)?;
+    let scalar = gas_params.deserialize_scalar(&scalar_bytes, context)?;
+
+    context.charge(
+        gas_params.per_g2_mul * NumArgs::one() + gas_params.per_g2_serialize * NumArgs::one(),
+    )?;
+
+    Ok(smallvec![Value::vector_u8(g2_mul(point, scalar))])
+}
+
+/***************************************************************************************************
+ * native fun pairing_check_internal
+ *
+ *   gas cost: base_cost + n * (per_g1_deserialize_cost + per_g2_deserialize_cost)
+ *                       + n * per_miller_loop_cost + per_final_exponentiation_cost
+ *
+ * where n is the number of pairs of points. The Move module makes sure both vectors have the same
+ * length.
+ **************************************************************************************************/
+fn native_pairing_check(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 2);
+
+    let g2_bytes = pop_as_vec_of_vec_u8(&mut arguments)?;
+    let g1_bytes = pop_as_vec_of_vec_u8(&mut arguments)?;
+    if g1_bytes.len() != g2_bytes.len() {
+        return Err(deserialize_error());
+    }
+
+    context.charge(gas_params.base)?;
+
+    let g1_points = g1_bytes
+        .iter()
+        .map(|bytes| gas_params.deserialize_g1(bytes, context))
+        .collect::<SafeNativeResult<Vec<_>>>()?;
+    let g2_points = g2_bytes
+        .iter()
+        .map(|bytes| gas_params.deserialize_g2(bytes, context))
+        .collect::<SafeNativeResult<Vec<_>>>()?;
+
+    context.charge(
+        gas_params.per_miller_loop * NumArgs::new(g1_points.len() as u64)
+            + gas_params.per_final_exponentiation * NumArgs::one(),
+    )?;
+
+    Ok(smallvec![Value::bool(pairing_check(
+        &g1_points, &g2_points
+    ))])
+}
+
+/***************************************************************************************************
+ * native fun groth16_verify_internal
+ *
+ *   gas cost: base_cost + (n + 4) * per_g1_deserialize_cost + 4 * per_g2_deserialize_cost
+ *                       + n * (per_scalar_deserialize_cost + per_g1_mul_cost + per_g1_add_cost)
+ *                       + 4 * per_miller_loop_cost + 2 * per_final_exponentiation_cost
+ *
+ * where n is the number of public inputs. The verification computes e(alpha, beta) and a
+ * 3-pairing product, each of which needs a final exponentiation.
+ **************************************************************************************************/
+fn native_groth16_verify(
+    gas_params: &GasParameters,
+    context: &mut SafeNativeContext,
+    _ty_args: Vec<Type>,
+    mut arguments: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert!(_ty_args.is_empty());
+    debug_assert!(arguments.len() == 9);
+
+    let proof_c_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let proof_b_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let proof_a_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let public_inputs_bytes = pop_as_vec_of_vec_u8(&mut arguments)?;
+    let gamma_abc_g1_bytes = pop_as_vec_of_vec_u8(&mut arguments)?;
+    let delta_g2_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let gamma_g2_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let beta_g2_bytes = safely_pop_arg!(arguments, Vec<u8>);
+    let alpha_g1_bytes = safely_pop_arg!(arguments, Vec<u8>);
+
+    context.charge(gas_params.base)?;
+
+    // A key for a different number of public inputs rejects the proof: do not charge for work
+    // which is not going to be done.
+    if gamma_abc_g1_bytes.len() != public_inputs_bytes.len() + 1 {
+        return Ok(smallvec![Value::bool(false)]);
+    }
+
+    let vk = VerifyingKey::<Bn254> {
+        alpha_g1: gas_params.deserialize_g1(&alpha_g1_bytes, context)?,
+        beta_g2: gas_params.deserialize_g2(&beta_g2_bytes, context)?,
+        gamma_g2: gas_params.deserialize_g2(&gamma_g2_bytes, context)?,
+        delta_g2: gas_params.deserialize_g2(&delta_g2_bytes, context)?,
+        gamma_abc_g1: gamma_abc_g1_bytes
+            .iter()
+            .map(|bytes| gas_params.deserialize_g1(bytes, context))
+            .collect::<SafeNativeResult<Vec<_>>>()?,
+    };
+    let public_inputs = public_inputs_bytes
+        .iter()
+        .map(|bytes| gas_params.deserialize_scalar(bytes, context))
+        .collect::<SafeNativeResult<Vec<_>>>()?;
+    let proof = Proof::<Bn254> {
+        a: gas_params.deserialize_g1(&proof_a_bytes, context)?,
+        b: gas_params.deserialize_g2(&proof_b_bytes, context)?,
+        c: gas_params.deserialize_g1(&proof_c_bytes, context)?,
+    };
+
+    let num_inputs = NumArgs::new(public_inputs.len() as u64);
+    context.charge(
+        (gas_params.per_g1_mul + gas_params.per_g1_add) * num_inputs
+            + gas_params.per_miller_loop * NumArgs::new(4)
+            + gas_params.per_final_exponentiation * NumArgs::new(2),
+    )?;
+
+    let valid = groth16_verify(&vk, &public_inputs, &proof);
+
+    Ok(smallvec![Value::bool(valid)])
+}
+
+/***************************************************************************************************
+ * module
+ *
+ **************************************************************************************************/
+pub fn make_all(
+    gas_params: GasParameters,
+    timed_features: TimedFeatures,
+    features: Arc<Features>,
+) -> impl Iterator<Item = (String, NativeFunction)> {
+    let natives = [
+        (
+            "g1_point_is_valid_internal",
+            make_safe_native(
+                gas_params.clone(),
+                timed_features.clone(),
+                features.clone(),
+                native_g1_point_is_valid,
+            ),
+        ),
+        (
+            "g2_point_is_valid_internal",
+            make_safe_native(
+                gas_params.clone(),
+                timed_features.clone(),
+                features.clone(),
+                native_g2_point_is_valid,
+            ),
+        ),
+        (
+            "scalar_is_canonical_internal",
+            make_safe_native(
+                gas_params.clone(),
+                timed_features.clone(),
+                features.clone(),
+                native_scalar_is_canonical,
+            ),
+        ),
+        (
+            "g1_add_internal",
+            make_safe_native(
+                gas_params.clone(),
+                timed_features.clone(),
+                features.clone(),
+                native_g1_add,
+            ),
+        ),
+        (
+            "g1_mul_internal",
+            make_safe_native(
+                gas_params.clone(),
+                timed_features.clone(),
+                features.clone(),
+                native_g1_mul,
+            ),
+        ),
+        (
+            "g2_add_internal",
+            make_safe_native(
+                gas_params.clone(),
+                timed_features.clone(),
+                features.clone(),
+                native_g2_add,
+            ),
+        ),
+        (
+            "g2_mul_internal",
+            make_safe_native(
+                gas_params.clone(),
+                timed_features.clone(),
+                features.clone(),
+                native_g2_mul,
+            ),
+        ),
+        (
+            "pairing_check_internal",
+            make_safe_native(
+                gas_params.clone(),
+                timed_features.clone(),
+                features.clone(),
+                native_pairing_check,
+            ),
+        ),
+        (
+            "groth16_verify_internal",
+            make_safe_native(gas_params, timed_features, features, native_groth16_verify),
+        ),
+    ];
+
+    crate::natives::helpers::make_module_natives(natives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Fq, Fq2, G1Projective, G2Projective};
+    use ark_ec::AffineRepr;
+    use ark_ff::{Field, PrimeField, UniformRand};
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    };
+    use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use proptest::prelude::*;
+
+    /// A circuit proving knowledge of `a` and `b` such that `a * b = c`, for a public `c`.
+    struct MulCircuit {
+        a: Fr,
+        b: Fr,
+    }
+
+    impl ConstraintSynthesizer<Fr> for MulCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| Ok(self.a))?;
+            let b = cs.new_witness_variable(|| Ok(self.b))?;
+            let c = cs.new_input_variable(|| Ok(self.a * self.b))?;
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)
+        }
+    }
+
+    fn rng(seed: u64) -> StdRng {
+        StdRng::seed_from_u64(seed)
+    }
+
+    // Points encoded as in the EIP-196 and EIP-197 precompiles, i.e., with big-endian coordinates,
+    // and elements `a * i + b` of F_p^2 as `a` followed by `b`. The generators are the ones of the
+    // precompiles, the other points come from their test vectors, or were computed with an
+    // independent implementation of the curve arithmetic.
+    const G1_GENERATOR: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+                                0000000000000000000000000000000000000000000000000000000000000002";
+    const G2_GENERATOR: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+                                1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+                                090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+                                12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+
+    fn fq(hex: &str) -> Fq {
+        Fq::from_be_bytes_mod_order(&hex::decode(hex).unwrap())
+    }
+
+    /// Converts an EIP-196 G1 point to the compressed format of the natives.
+    fn g1(hex: &str) -> Vec<u8> {
+        serialize(&G1Affine::new(fq(&hex[..64]), fq(&hex[64..])))
+    }
+
+    /// Converts an EIP-197 G2 point to the compressed format of the natives.
+    fn g2(hex: &str) -> Vec<u8> {
+        let x = Fq2::new(fq(&hex[64..128]), fq(&hex[..64]));
+        let y = Fq2::new(fq(&hex[192..]), fq(&hex[128..192]));
+        serialize(&G2Affine::new(x, y))
+    }
+
+    /// Converts a big-endian EIP-196 scalar to the little-endian format of the natives.
+    fn scalar(hex: &str) -> Fr {
+        let mut bytes = hex::decode(format!("{:0>64}", hex)).unwrap();
+        bytes.reverse();
+        deserialize_scalar(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_g1_add_vectors() {
+        let add = |p: &str, q: &str| {
+            g1_add(
+                deserialize_g1_checked(&g1(p)).unwrap(),
+                deserialize_g1_checked(&g1(q)).unwrap(),
+            )
+        };
+        assert_eq!(
+            add(G1_GENERATOR, G1_GENERATOR),
+            g1(
+                "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+                 15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4"
+            )
+        );
+        assert_eq!(
+            add(
+                "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9\
+                 063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266",
+                "07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed\
+                 06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7"
+            ),
+            g1(
+                "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703\
+                 301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915"
+            )
+        );
+        // The generator plus its negation is the point at infinity.
+        assert_eq!(
+            add(
+                G1_GENERATOR,
+                "0000000000000000000000000000000000000000000000000000000000000001\
+                 30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"
+            ),
+            serialize(&G1Affine::zero())
+        );
+    }
+
+    #[test]
+    fn test_g1_mul_vectors() {
+        let point = deserialize_g1_checked(&g1(
+            "2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7\
+             21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204",
+        ))
+        .unwrap();
+        assert_eq!(
+            g1_mul(point, scalar("11138ce750fa15c2")),
+            g1(
+                "070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c\
+                 031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc"
+            )
+        );
+        assert_eq!(g1_mul(point, scalar("0")), serialize(&G1Affine::zero()));
+    }
+
+    #[test]
+    fn test_g2_vectors() {
+        let generator = deserialize_g2_checked(&g2(G2_GENERATOR)).unwrap();
+        let double = g2(
+            "203e205db4f19b37b60121b83a7333706db86431c6d835849957ed8c3928ad79\
+             27dc7234fd11d3e8c36c59277c3e6f149d5cd3cfa9a62aee49f8130962b4b3b9\
+             195e8aa5b7827463722b8c153931579d3505566b4edf48d498e185f0509de152\
+             04bb53b8977e5f92a0bc372742c4830944a59b4fe6b1c0466e2a6dad122b5d2e",
+        );
+        assert_eq!(g2_add(generator, generator), double);
+        assert_eq!(g2_mul(generator, scalar("2")), double);
+        assert_eq!(
+            g2_add(generator, deserialize_g2_checked(&double).unwrap()),
+            g2(
+                "1014772f57bb9742735191cd5dcfe4ebbc04156b6878a0a7c9824f32ffb66e85\
+                 06064e784db10e9051e52826e192715e8d7e478cb09a5e0012defa0694fbc7f5\
+                 021e2335f3354bb7922ffcc2f38d3323dd9453ac49b55441452aeaca147711b2\
+                 058e1d5681b5b9e0074b0f9c8d2c68a069b920d74521e79765036d57666c5597"
+            )
+        );
+        assert_eq!(
+            g2_mul(generator, scalar("11138ce750fa15c2")),
+            g2(
+                "0c1358075a4af3ce4567a4389925db643e4a8ec08812bc11af03331ef09189e9\
+                 24e3607971fee5a16e42e2f6e6c53cebcca187b4f2058ee560b58591c648b024\
+                 150a3fc92d718bc13a1ad6e10204531b853189589e0aeb44355340b9adb0b477\
+                 09832da3430ab33f2f76b52506fe0133e23549a55a3918f23e559db06d455a16"
+            )
+        );
+    }
+
+    #[test]
+    fn test_pairing_check_vectors() {
+        let check = |g1_points: &[&str], g2_points: &[&str]| {
+            let g1_points: Vec<_> = g1_points
+                .iter()
+                .map(|hex| deserialize_g1_checked(&g1(hex)).unwrap())
+                .collect();
+            let g2_points: Vec<_> = g2_points
+                .iter()
+                .map(|hex| deserialize_g2_checked(&g2(hex)).unwrap())
+                .collect();
+            pairing_check(&g1_points, &g2_points)
+        };
+        let neg_g1_generator = "0000000000000000000000000000000000000000000000000000000000000001\
+                                30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+
+        // The empty product is the identity.
+        assert!(check(&[], &[]));
+        assert!(!check(&[G1_GENERATOR], &[G2_GENERATOR]));
+        assert!(check(
+            &[G1_GENERATOR, neg_g1_generator],
+            &[G2_GENERATOR, G2_GENERATOR]
+        ));
+        assert!(!check(
+            &[G1_GENERATOR, G1_GENERATOR],
+            &[G2_GENERATOR, G2_GENERATOR]
+        ));
+        // e(a * G1, b * G2) * e(-(a * b) * G1, G2) == 1, for a = 0x2bd3e6d0f3b14292 and
+        // b = 0x4f5ca7b49ce5b9d5.
+        assert!(check(
+            &[
+                "15a06bde1947ab93e119e135989ba26aae78e8ee01942bb27a6e1d04d25c0b76\
+                 238c912faacd8d2c519b841cae62bef436ead6cf4dad888fd2b124361b34e66c",
+                "0fabbdc109a84f6520264ccec89801c6ed6ebf6ac7d423332c4d6c79f820984b\
+                 1b63f245bb59c742eec66c22f14566d7b70594ab9c48a61e374d2aadc3a6bf92",
+            ],
+            &[
+                "1d1953bf570635416345b5c17445baf61cd71d3fa48b2886a192da85e20df0f0\
+                 2ffa4c2c4e1cbef7e83164b2a49b2b62a47a56500854f67064a996235db09440\
+                 14a8f2b9a4f107a353c71f79395a7d94abd43420b0e9a7637f35687d14a42c38\
+                 096f88ec1ebf65b9810c19e88c3e52d3eb541190bc16b04f0395797fac0553c5",
+                G2_GENERATOR,
+            ]
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn test_g1_serialization_roundtrip(seed in any::<u64>()) {
+            let point = G1Projective::rand(&mut rng(seed)).into_affine();
+            let bytes = serialize(&point);
+            prop_assert_eq!(bytes.len(), 32);
+            prop_assert_eq!(deserialize_g1(&bytes), Some(point));
+            prop_assert_eq!(deserialize_g1_checked(&bytes), Some(point));
+        }
+
+        #[test]
+        fn test_g2_serialization_roundtrip(seed in any::<u64>()) {
+            let point = G2Projective::rand(&mut rng(seed)).into_affine();
+            let bytes = serialize(&point);
+            prop_assert_eq!(bytes.len(), 64);
+            prop_assert_eq!(deserialize_g2(&bytes), Some(point));
+            prop_assert_eq!(deserialize_g2_checked(&bytes), Some(point));
+        }
+
+        #[test]
+        fn test_scalar_must_be_canonical(bytes in any::<[u8; 32]>()) {
+            // The modulus of Fr is less than 2^254.
+            let mut bytes = bytes.to_vec();
+            bytes[31] |= 0xc0;
+            prop_assert!(deserialize_scalar(&bytes).is_none());
+            bytes[31] &= 0x1f;
+            prop_assert!(deserialize_scalar(&bytes).is_some());
+        }
+
+        #[test]
+        fn test_pairing_check(seed in any::<u64>()) {
+            let mut rng = rng(seed);
+            let g1 = G1Projective::rand(&mut rng);
+            let g2 = G2Projective::rand(&mut rng);
+            let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+
+            // e(a * g1, b * g2) * e(-(a * b) * g1, g2) == 1, by bilinearity.
+            let g1_points = [(g1 * a).into_affine(), (g1 * -(a * b)).into_affine()];
+            let g2_points = [(g2 * b).into_affine(), g2.into_affine()];
+            prop_assert!(pairing_check(&g1_points, &g2_points));
+
+            // Matches the product of the reference pairings.
+            for n in 0..=g1_points.len() {
+                let expected = (0..n)
+                    .map(|i| Bn254::pairing(g1_points[i], g2_points[i]).0)
+                    .fold(<Bn254 as Pairing>::TargetField::ONE, |acc, x| acc * x);
+                prop_assert_eq!(
+                    pairing_check(&g1_points[..n], &g2_points[..n]),
+                    expected == <Bn254 as Pairing>::TargetField::ONE
+                );
+            }
+        }
+    }
+
+    proptest! {
+        // Setting up a circuit and proving are slow.
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn test_groth16_verify(seed in any::<u64>()) {
+            let mut rng = rng(seed);
+            let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+            let (pk, vk) =
+                Groth16::<Bn254>::circuit_specific_setup(MulCircuit { a, b }, &mut rng).unwrap();
+            let proof = Groth16::<Bn254>::prove(&pk, MulCircuit { a, b }, &mut rng).unwrap();
+
+            // Round-trip everything through the format used by the natives.
+            let vk = VerifyingKey::<Bn254> {
+                alpha_g1: deserialize_g1(&serialize(&vk.alpha_g1)).unwrap(),
+                beta_g2: deserialize_g2(&serialize(&vk.beta_g2)).unwrap(),
+                gamma_g2: deserialize_g2(&serialize(&vk.gamma_g2)).unwrap(),
+                delta_g2: deserialize_g2(&serialize(&vk.delta_g2)).unwrap(),
+                gamma_abc_g1: vk
+                    .gamma_abc_g1
+                    .iter()
+                    .map(|point| deserialize_g1(&serialize(point)).unwrap())
+                    .collect(),
+            };
+            let proof = Proof::<Bn254> {
+                a: deserialize_g1(&serialize(&proof.a)).unwrap(),
+                b: deserialize_g2(&serialize(&proof.b)).unwrap(),
+                c: deserialize_g1(&serialize(&proof.c)).unwrap(),
+            };
+            let c = deserialize_scalar(&serialize(&(a * b))).unwrap();
+
+            for inputs in [vec![c], vec![c + Fr::ONE], vec![], vec![c, c]] {
+                let expected = inputs.len() == 1
+                    && Groth16::<Bn254>::verify(&vk, &inputs, &proof).unwrap();
+                prop_assert_eq!(groth16_verify(&vk, &inputs, &proof), expected);
+            }
+            prop_assert!(groth16_verify(&vk, &[c], &proof));
+
+            let tampered = Proof::<Bn254> {
+                a: (proof.a + G1Affine::generator()).into_affine(),
+                ..proof.clone()
+            };
+            prop_assert!(!groth16_verify(&vk, &[c], &tampered));
+        }
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod bls12381;
+pub mod bn254;
 pub mod ed25519;
 pub mod multi_ed25519;
 pub mod ristretto255;
@@ -13,6 +13,7 @@ pub mod hash;
 mod helpers;
 pub mod object;
 pub mod state_storage;
+pub mod string_utils;
 pub mod transaction_context;
 pub mod type_info;
 pub mod util;
@@ -40,11 +41,13 @@ pub struct GasParameters {
     pub account: account::GasParameters,
     pub ed25519: ed25519::GasParameters,
     pub bls12381: cryptography::bls12381::GasParameters,
+    pub bn254: cryptography::bn254::GasParameters,
     pub secp256k1: cryptography::secp256k1::GasParameters,
     pub ristretto255: cryptography::ristretto255::GasParameters,
     pub hash: hash::GasParameters,
     pub type_info: type_info::GasParameters,
     pub util: util::GasParameters,
+    pub string_utils: string_utils::GasParameters,
     pub transaction_context: transaction_context::GasParameters,
     pub code: code::GasParameters,
     pub event: event::GasParameters,
@@ -75,6 +78,21 @@ impl GasParameters {
                 per_msg_hashing: 0.into(),
                 per_byte_hashing: 0.into(),
             },
+            bn254: cryptography::bn254::GasParameters {
+                base: 0.into(),
+                per_g1_deserialize: 0.into(),
+                per_g1_serialize: 0.into(),
+                per_g1_add: 0.into(),
+                per_g1_mul: 0.into(),
+                per_g2_deserialize: 0.into(),
+                per_g2_serialize: 0.into(),
+                per_g2_subgroup_check: 0.into(),
+                per_g2_add: 0.into(),
+                per_g2_mul: 0.into(),
+                per_scalar_deserialize: 0.into(),
+                per_miller_loop: 0.into(),
+                per_final_exponentiation: 0.into(),
+            },
             ed25519: cryptography::ed25519::GasParameters {
                 base: 0.into(),
                 per_pubkey_deserialize: 0.into(),
@@ -158,8 +176,29 @@ impl GasParameters {
                     per_byte: 0.into(),
                 },
             },
+            string_utils: string_utils::GasParameters {
+                format: string_utils::FormatGasParameters {
+                    base: 0.into(),
+                    per_byte: 0.into(),
+                },
+            },
             transaction_context: transaction_context::GasParameters {
                 get_script_hash: transaction_context::GetScriptHashGasParameters { base: 0.into() },
+                generate_unique_address: transaction_context::GenerateUniqueAddressGasParameters {
+                    base: 0.into(),
+                },
+                sender: transaction_context::SenderGasParameters { base: 0.into() },
+                secondary_signers: transaction_context::SecondarySignersGasParameters {
+                    base: 0.into(),
+                    per_signer: 0.into(),
+                },
+                gas_payer: transaction_context::GasPayerGasParameters { base: 0.into() },
+                max_gas_amount: transaction_context::MaxGasAmountGasParameters { base: 0.into() },
+                gas_unit_price: transaction_context::GasUnitPriceGasParameters { base: 0.into() },
+                entry_function_payload: transaction_context::EntryFunctionPayloadGasParameters {
+                    base: 0.into(),
+                    per_byte: 0.into(),
+                },
             },
             code: code::GasParameters {
                 request_publish: code::RequestPublishGasParameters {
@@ -259,6 +298,10 @@ pub fn all_natives(
             features.clone()
         )
     );
+    add_natives_from_module!(
+        "bn254",
+        cryptography::bn254::make_all(gas_params.bn254, timed_features.clone(), features.clone())
+    );
     add_natives_from_module!(
         "secp256k1",
         cryptography::secp256k1::make_all(
@@ -299,6 +342,14 @@ pub fn all_natives(
         "from_bcs",
         util::make_all(gas_params.util, timed_features.clone(), features.clone())
     );
+    add_natives_from_module!(
+        "string_utils",
+        string_utils::make_all(
+            gas_params.string_utils,
+            timed_features.clone(),
+            features.clone()
+        )
+    );
     add_natives_from_module!(
         "transaction_context",
         transaction_context::make_all(
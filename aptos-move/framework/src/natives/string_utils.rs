@@ -0,0 +1,417 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    natives::helpers::{make_safe_native, SafeNativeContext, SafeNativeResult},
+    safely_pop_arg,
+};
+use aptos_types::on_chain_config::{Features, TimedFeatures};
+use move_binary_format::errors::PartialVMError;
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::{InternalGas, InternalGasPerByte, NumBytes},
+    language_storage::StructTag,
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::native_functions::NativeFunction;
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    values::{Reference, Struct, Value},
+};
+use smallvec::{smallvec, SmallVec};
+use std::{collections::VecDeque, fmt::Display, sync::Arc};
+
+/// The indentation of nested values when formatting on multiple lines.
+const INDENT: &str = "    ";
+
+/// How to format Move values, see `string_utils.move`.
+struct FormatOptions {
+    canonicalize: bool,
+    single_line: bool,
+    include_int_types: bool,
+}
+
+impl FormatOptions {
+    /// Format `value`, calling `charge` with the length of every piece of the output as it is
+    /// produced, so that gas is charged before formatting goes any further.
+    fn format_value(
+        &self,
+        value: &MoveValue,
+        layout: &MoveTypeLayout,
+        depth: usize,
+        charge: &mut impl FnMut(usize) -> SafeNativeResult<()>,
+    ) -> SafeNativeResult<String> {
+        // The length of the formatted nested values, which they were already charged for.
+        let mut nested_len = 0;
+        let output = match (value, layout) {
+            (MoveValue::U8(v), _) => self.format_int(v, "u8"),
+            (MoveValue::U16(v), _) => self.format_int(v, "u16"),
+            (MoveValue::U32(v), _) => self.format_int(v, "u32"),
+            (MoveValue::U64(v), _) => self.format_int(v, "u64"),
+            (MoveValue::U128(v), _) => self.format_int(v, "u128"),
+            (MoveValue::U256(v), _) => self.format_int(v, "u256"),
+            (MoveValue::Bool(v), _) => v.to_string(),
+            (MoveValue::Address(address), _) => self.format_address(address),
+            (MoveValue::Signer(address), _) => format!("signer({})", self.format_address(address)),
+            (MoveValue::Vector(elems), MoveTypeLayout::Vector(elem_layout)) => {
+                if let MoveTypeLayout::U8 = elem_layout.as_ref() {
+                    charge(2 + 2 * elems.len())?;
+                    return Ok(format!("0x{}", hex::encode(to_bytes(elems))));
+                }
+                let elems = elems
+                    .iter()
+                    .map(|elem| self.format_value(elem, elem_layout, depth + 1, charge))
+                    .collect::<SafeNativeResult<Vec<_>>>()?;
+                nested_len = elems.iter().map(String::len).sum();
+                self.format_list("[", elems, "]", depth)
+            },
+            (
+                MoveValue::Struct(s),
+                MoveTypeLayout::Struct(MoveStructLayout::WithTypes { type_, fields }),
+            ) => {
+                let values: Vec<&MoveValue> = match s {
+                    MoveStruct::Runtime(values) => values.iter().collect(),
+                    MoveStruct::WithFields(fields) | MoveStruct::WithTypes { fields, .. } => {
+                        fields.iter().map(|(_, value)| value).collect()
+                    },
+                };
+                match (values[0], &fields[0].layout) {
+                    (MoveValue::Vector(bytes), _) if is_std_struct(type_, "string", "String") => {
+                        let output = quote(&String::from_utf8_lossy(&to_bytes(bytes)));
+                        charge(output.len())?;
+                        return Ok(output);
+                    },
+                    (MoveValue::Vector(elems), MoveTypeLayout::Vector(elem_layout))
+                        if is_std_struct(type_, "option", "Option") =>
+                    {
+                        match elems.first() {
+                            Some(elem) => {
+                                let elem = self.format_value(elem, elem_layout, depth, charge)?;
+                                nested_len = elem.len();
+                                format!("some({})", elem)
+                            },
+                            None => "none".to_string(),
+                        }
+                    },
+                    _ => {
+                        let fields = values
+                            .into_iter()
+                            .zip(fields)
+                            .map(|(value, field)| {
+                                let value =
+                                    self.format_value(value, &field.layout, depth + 1, charge)?;
+                                nested_len += value.len();
+                                Ok(format!("{}: {}", field.name, value))
+                            })
+                            .collect::<SafeNativeResult<Vec<_>>>()?;
+                        self.format_list(&format!("{} {{", type_), fields, "}", depth)
+                    },
+                }
+            },
+            // The value was deserialized with this layout, so they always match.
+            _ => unreachable!("value does not match its layout"),
+        };
+        charge(output.len() - nested_len)?;
+        Ok(output)
+    }
+
+    fn format_int(&self, value: impl Display, ty: &str) -> String {
+        if self.include_int_types {
+            format!("{}{}", value, ty)
+        } else {
+            value.to_string()
+        }
+    }
+
+    fn format_address(&self, address: &AccountAddress) -> String {
+        if self.canonicalize {
+            format!("@0x{}", address.to_hex())
+        } else {
+            format!("@{}", address.to_hex_literal())
+        }
+    }
+
+    /// Format the already formatted `elems` between `open` and `close`, either on a single line or
+    /// on one line per element indented one level deeper than `depth`.
+    fn format_list(&self, open: &str, elems: Vec<String>, close: &str, depth: usize) -> String {
+        if elems.is_empty() {
+            return format!("{}{}", open, close);
+        }
+        if self.single_line {
+            let sep = if open.ends_with('{') { " " } else { "" };
+            return format!("{}{}{}{}{}", open, sep, elems.join(", "), sep, close);
+        }
+        let indent = INDENT.repeat(depth + 1);
+        let mut output = open.to_string();
+        output.push('\n');
+        for elem in elems {
+            output.push_str(&indent);
+            output.push_str(&elem);
+            output.push_str(",\n");
+        }
+        output.push_str(&INDENT.repeat(depth));
+        output.push_str(close);
+        output
+    }
+}
+
+/// The bytes of a `vector<u8>`.
+fn to_bytes(elems: &[MoveValue]) -> Vec<u8> {
+    elems
+        .iter()
+        .map(|elem| match elem {
+            MoveValue::U8(byte) => *byte,
+            _ => unreachable!("vector<u8> elements are u8"),
+        })
+        .collect()
+}
+
+/// Quote `s`, escaping quotes and backslashes as `\"` and `\\`, and control characters as `\xNN`.
+/// Other characters, including non-ASCII ones, are kept as they are.
+fn quote(s: &str) -> String {
+    let mut output = String::with_capacity(s.len() + 2);
+    output.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            c if c.is_ascii_control() => output.push_str(&format!("\\x{:02x}", c as u8)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Whether `type_` is the struct `module::name` of the standard library.
+fn is_std_struct(type_: &StructTag, module: &str, name: &str) -> bool {
+    type_.address == AccountAddress::ONE
+        && type_.module.as_str() == module
+        && type_.name.as_str() == name
+}
+
+/***************************************************************************************************
+ * native fun native_format
+ *
+ *   gas cost: base_cost + unit_cost * output_length
+ *
+ **************************************************************************************************/
+#[derive(Debug, Clone)]
+pub struct FormatGasParameters {
+    pub base: InternalGas,
+    pub per_byte: InternalGasPerByte,
+}
+
+fn native_format(
+    gas_params: &FormatGasParameters,
+    context: &mut SafeNativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    debug_assert_eq!(ty_args.len(), 1);
+    debug_assert_eq!(args.len(), 4);
+
+    context.charge(gas_params.base)?;
+
+    let include_int_types = safely_pop_arg!(args, bool);
+    let single_line = safely_pop_arg!(args, bool);
+    let canonicalize = safely_pop_arg!(args, bool);
+    let value = safely_pop_arg!(args, Reference).read_ref()?;
+    let options = FormatOptions {
+        canonicalize,
+        single_line,
+        include_int_types,
+    };
+
+    let invariant_violation = |message: &str| {
+        PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).with_message(format!(
+            "{} of type {:?} -- this should not happen",
+            message, ty_args[0]
+        ))
+    };
+    let layout = context
+        .type_to_type_layout(&ty_args[0])?
+        .ok_or_else(|| invariant_violation("Failed to get layout"))?;
+    let annotated_layout = context
+        .type_to_fully_annotated_layout(&ty_args[0])?
+        .ok_or_else(|| invariant_violation("Failed to get annotated layout"))?;
+    // Round trip the value through its serialization to view it with its field and struct names.
+    let move_value = value
+        .simple_serialize(&layout)
+        .and_then(|bytes| MoveValue::simple_deserialize(&bytes, &annotated_layout).ok())
+        .ok_or_else(|| invariant_violation("Failed to annotate a value"))?;
+
+    let output = options.format_value(&move_value, &annotated_layout, 0, &mut |len: usize| {
+        context.charge(gas_params.per_byte * NumBytes::new(len as u64))
+    })?;
+
+    Ok(smallvec![Value::struct_(Struct::pack(vec![
+        Value::vector_u8(output.into_bytes())
+    ]))])
+}
+
+/***************************************************************************************************
+ * module
+ *
+ **************************************************************************************************/
+#[derive(Debug, Clone)]
+pub struct GasParameters {
+    pub format: FormatGasParameters,
+}
+
+pub fn make_all(
+    gas_params: GasParameters,
+    timed_features: TimedFeatures,
+    features: Arc<Features>,
+) -> impl Iterator<Item = (String, NativeFunction)> {
+    let natives = [(
+        "native_format",
+        make_safe_native(gas_params.format, timed_features, features, native_format),
+    )];
+
+    crate::natives::helpers::make_module_natives(natives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::natives::helpers::SafeNativeError;
+    use move_core_types::{
+        identifier::Identifier, language_storage::TypeTag, value::MoveFieldLayout,
+    };
+
+    fn struct_layout(
+        address: AccountAddress,
+        module: &str,
+        name: &str,
+        type_params: Vec<TypeTag>,
+        fields: Vec<(&str, MoveTypeLayout)>,
+    ) -> MoveTypeLayout {
+        MoveTypeLayout::Struct(MoveStructLayout::WithTypes {
+            type_: StructTag {
+                address,
+                module: Identifier::new(module).unwrap(),
+                name: Identifier::new(name).unwrap(),
+                type_params,
+            },
+            fields: fields
+                .into_iter()
+                .map(|(name, layout)| MoveFieldLayout::new(Identifier::new(name).unwrap(), layout))
+                .collect(),
+        })
+    }
+
+    /// The value and layout of `0xcafe::m::Data { name: <name>, count: some(7u8), flags: [true, false] }`.
+    fn data(name: &[u8]) -> (MoveValue, MoveTypeLayout) {
+        let string_layout = struct_layout(
+            AccountAddress::ONE,
+            "string",
+            "String",
+            vec![],
+            vec![(
+                "bytes",
+                MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+            )],
+        );
+        let option_layout = struct_layout(
+            AccountAddress::ONE,
+            "option",
+            "Option",
+            vec![TypeTag::U8],
+            vec![("vec", MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)))],
+        );
+        let layout = struct_layout(
+            AccountAddress::from_hex_literal("0xcafe").unwrap(),
+            "m",
+            "Data",
+            vec![],
+            vec![
+                ("name", string_layout),
+                ("count", option_layout),
+                (
+                    "flags",
+                    MoveTypeLayout::Vector(Box::new(MoveTypeLayout::Bool)),
+                ),
+            ],
+        );
+        let value = MoveValue::Struct(MoveStruct::Runtime(vec![
+            MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::vector_u8(
+                name.to_vec(),
+            )])),
+            MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(vec![
+                MoveValue::U8(7),
+            ])])),
+            MoveValue::Vector(vec![MoveValue::Bool(true), MoveValue::Bool(false)]),
+        ]));
+        (value, layout)
+    }
+
+    /// Format `value`, checking that exactly the length of the output is charged.
+    fn format(options: &FormatOptions, value: &MoveValue, layout: &MoveTypeLayout) -> String {
+        let mut charged = 0;
+        let output = options
+            .format_value(value, layout, 0, &mut |len: usize| {
+                charged += len;
+                Ok(())
+            })
+            .unwrap_or_else(|_| panic!("failed to format {:?}", value));
+        assert_eq!(charged, output.len());
+        output
+    }
+
+    #[test]
+    fn test_format_value() {
+        let (value, layout) = data(b"aptos");
+        let options = FormatOptions {
+            canonicalize: false,
+            single_line: true,
+            include_int_types: true,
+        };
+        assert_eq!(
+            format(&options, &value, &layout),
+            "0xcafe::m::Data { name: \"aptos\", count: some(7u8), flags: [true, false] }"
+        );
+
+        let options = FormatOptions {
+            canonicalize: false,
+            single_line: false,
+            include_int_types: false,
+        };
+        assert_eq!(
+            format(&options, &value, &layout),
+            "0xcafe::m::Data {\n    name: \"aptos\",\n    count: some(7),\n    flags: [\n        \
+             true,\n        false,\n    ],\n}"
+        );
+    }
+
+    #[test]
+    fn test_format_value_escapes_strings() {
+        let (value, layout) = data("a\"b\\c\nd\u{7f}é".as_bytes());
+        let options = FormatOptions {
+            canonicalize: false,
+            single_line: true,
+            include_int_types: false,
+        };
+        assert!(format(&options, &value, &layout)
+            .starts_with(r#"0xcafe::m::Data { name: "a\"b\\c\x0ad\x7fé", "#));
+    }
+
+    #[test]
+    fn test_format_value_stops_when_out_of_gas() {
+        let (value, layout) = data(b"aptos");
+        let options = FormatOptions {
+            canonicalize: false,
+            single_line: true,
+            include_int_types: false,
+        };
+        // Formatting stops at the first piece of output which is not paid for.
+        let mut calls = 0;
+        let result = options.format_value(&value, &layout, 0, &mut |_: usize| {
+            calls += 1;
+            Err(SafeNativeError::OutOfGas)
+        });
+        assert!(matches!(result, Err(SafeNativeError::OutOfGas)));
+        assert_eq!(calls, 1);
+    }
+}
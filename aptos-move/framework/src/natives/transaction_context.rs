@@ -1,37 +1,135 @@
-// Copyright © Aptos Foundation
-// SPDX-License-Identifier: Apache-2.0
-
-use crate::natives::helpers::{make_safe_native, SafeNativeContext, SafeNativeResult};
-use aptos_types::on_chain_config::{Features, TimedFeatures};
+use crate::natives::helpers::{
+    make_safe_native, SafeNativeContext, SafeNativeError, SafeNativeResult,
+};
+use aptos_types::{
+    account_address::create_unique_address,
+    on_chain_config::{Features, TimedFeatures},
+};
 use better_any::{Tid, TidAble};
-use move_core_types::gas_algebra::InternalGas;
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::{InternalGas, InternalGasPerArg, InternalGasPerByte, NumArgs, NumBytes},
+};
 use move_vm_runtime::native_functions::NativeFunction;
-use move_vm_types::{loaded_data::runtime_types::Type, values::Value};
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    values::{Struct, Value},
+};
 use smallvec::{smallvec, SmallVec};
 use std::{collections::VecDeque, fmt::Debug, sync::Arc};
 
+/// Abort code when the transaction context is read outside of a user transaction, e.g., in a
+/// block prologue or in genesis: `error::invalid_state(ETRANSACTION_CONTEXT_NOT_AVAILABLE)`.
+pub const ETRANSACTION_CONTEXT_NOT_AVAILABLE: u64 = 0x03_0001;
+
+/// The identity of the entry function called by a user transaction.
+#[derive(Clone, Debug)]
+pub struct EntryFunctionPayload {
+    account_address: AccountAddress,
+    module_name: String,
+    function_name: String,
+    type_arg_names: Vec<String>,
+}
+
+impl EntryFunctionPayload {
+    pub fn new(
+        account_address: AccountAddress,
+        module_name: String,
+        function_name: String,
+        type_arg_names: Vec<String>,
+    ) -> Self {
+        Self {
+            account_address,
+            module_name,
+            function_name,
+            type_arg_names,
+        }
+    }
+
+    /// The number of bytes of the names in this payload, used to charge gas.
+    fn names_size(&self) -> usize {
+        self.module_name.len()
+            + self.function_name.len()
+            + self.type_arg_names.iter().map(String::len).sum::<usize>()
+    }
+}
+
+/// The parts of a user transaction which are readable from Move through the transaction context.
+#[derive(Clone, Debug)]
+pub struct UserTransactionContext {
+    sender: AccountAddress,
+    secondary_signers: Vec<AccountAddress>,
+    gas_payer: AccountAddress,
+    max_gas_amount: u64,
+    gas_unit_price: u64,
+    entry_function_payload: Option<EntryFunctionPayload>,
+}
+
+impl UserTransactionContext {
+    pub fn new(
+        sender: AccountAddress,
+        secondary_signers: Vec<AccountAddress>,
+        gas_payer: AccountAddress,
+        max_gas_amount: u64,
+        gas_unit_price: u64,
+        entry_function_payload: Option<EntryFunctionPayload>,
+    ) -> Self {
+        Self {
+            sender,
+            secondary_signers,
+            gas_payer,
+            max_gas_amount,
+            gas_unit_price,
+            entry_function_payload,
+        }
+    }
+}
+
 /// The native transaction context extension. This needs to be attached to the
 /// NativeContextExtensions value which is passed into session functions, so its accessible from
 /// natives of this extension.
 #[derive(Tid)]
 pub struct NativeTransactionContext {
+    /// The hash of the session id, rather than of the transaction itself. Unique addresses are
+    /// derived from it.
+    txn_hash: Vec<u8>,
+    /// The number of unique addresses generated so far in this session.
+    auid_counter: u64,
     script_hash: Vec<u8>,
     chain_id: u8,
+    /// Only present in the sessions of user transactions.
+    user_transaction_context: Option<UserTransactionContext>,
 }
 
 impl NativeTransactionContext {
     /// Create a new instance of a native transaction context. This must be passed in via an
     /// extension into VM session functions.
-    pub fn new(script_hash: Vec<u8>, chain_id: u8) -> Self {
+    pub fn new(
+        txn_hash: Vec<u8>,
+        script_hash: Vec<u8>,
+        chain_id: u8,
+        user_transaction_context: Option<UserTransactionContext>,
+    ) -> Self {
         Self {
+            txn_hash,
+            auid_counter: 0,
             script_hash,
             chain_id,
+            user_transaction_context,
         }
     }
 
     pub fn chain_id(&self) -> u8 {
         self.chain_id
     }
+
+    fn user_transaction_context(&self) -> SafeNativeResult<&UserTransactionContext> {
+        self.user_transaction_context
+            .as_ref()
+            .ok_or(SafeNativeError::Abort {
+                abort_code: ETRANSACTION_CONTEXT_NOT_AVAILABLE,
+            })
+    }
 }
 
 /***************************************************************************************************
@@ -60,6 +158,227 @@ fn native_get_script_hash(
     )])
 }
 
+/***************************************************************************************************
+ * native fun generate_unique_address
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Clone, Debug)]
+pub struct GenerateUniqueAddressGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_generate_unique_address(
+    gas_params: &GenerateUniqueAddressGasParameters,
+    context: &mut SafeNativeContext,
+    mut _ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    context.charge(gas_params.base)?;
+
+    let transaction_context = context
+        .extensions_mut()
+        .get_mut::<NativeTransactionContext>();
+    transaction_context.auid_counter += 1;
+
+    Ok(smallvec![Value::address(create_unique_address(
+        &transaction_context.txn_hash,
+        transaction_context.auid_counter,
+    ))])
+}
+
+/***************************************************************************************************
+ * native fun sender
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Clone, Debug)]
+pub struct SenderGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_sender(
+    gas_params: &SenderGasParameters,
+    context: &mut SafeNativeContext,
+    mut _ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    context.charge(gas_params.base)?;
+
+    let transaction_context = context.extensions().get::<NativeTransactionContext>();
+    let sender = transaction_context.user_transaction_context()?.sender;
+
+    Ok(smallvec![Value::address(sender)])
+}
+
+/***************************************************************************************************
+ * native fun secondary_signers
+ *
+ *   gas cost: base_cost + per_signer_cost * num_secondary_signers
+ *
+ **************************************************************************************************/
+#[derive(Clone, Debug)]
+pub struct SecondarySignersGasParameters {
+    pub base: InternalGas,
+    pub per_signer: InternalGasPerArg,
+}
+
+fn native_secondary_signers(
+    gas_params: &SecondarySignersGasParameters,
+    context: &mut SafeNativeContext,
+    mut _ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    context.charge(gas_params.base)?;
+
+    let transaction_context = context.extensions().get::<NativeTransactionContext>();
+    let secondary_signers = transaction_context
+        .user_transaction_context()?
+        .secondary_signers
+        .clone();
+
+    context.charge(gas_params.per_signer * NumArgs::new(secondary_signers.len() as u64))?;
+
+    Ok(smallvec![Value::vector_address(secondary_signers)])
+}
+
+/***************************************************************************************************
+ * native fun gas_payer
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Clone, Debug)]
+pub struct GasPayerGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_gas_payer(
+    gas_params: &GasPayerGasParameters,
+    context: &mut SafeNativeContext,
+    mut _ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    context.charge(gas_params.base)?;
+
+    let transaction_context = context.extensions().get::<NativeTransactionContext>();
+    let gas_payer = transaction_context.user_transaction_context()?.gas_payer;
+
+    Ok(smallvec![Value::address(gas_payer)])
+}
+
+/***************************************************************************************************
+ * native fun max_gas_amount
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Clone, Debug)]
+pub struct MaxGasAmountGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_max_gas_amount(
+    gas_params: &MaxGasAmountGasParameters,
+    context: &mut SafeNativeContext,
+    mut _ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    context.charge(gas_params.base)?;
+
+    let transaction_context = context.extensions().get::<NativeTransactionContext>();
+    let max_gas_amount = transaction_context
+        .user_transaction_context()?
+        .max_gas_amount;
+
+    Ok(smallvec![Value::u64(max_gas_amount)])
+}
+
+/***************************************************************************************************
+ * native fun gas_unit_price
+ *
+ *   gas cost: base_cost
+ *
+ **************************************************************************************************/
+#[derive(Clone, Debug)]
+pub struct GasUnitPriceGasParameters {
+    pub base: InternalGas,
+}
+
+fn native_gas_unit_price(
+    gas_params: &GasUnitPriceGasParameters,
+    context: &mut SafeNativeContext,
+    mut _ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    context.charge(gas_params.base)?;
+
+    let transaction_context = context.extensions().get::<NativeTransactionContext>();
+    let gas_unit_price = transaction_context
+        .user_transaction_context()?
+        .gas_unit_price;
+
+    Ok(smallvec![Value::u64(gas_unit_price)])
+}
+
+/***************************************************************************************************
+ * native fun entry_function_payload
+ *
+ *   gas cost: base_cost + per_byte_cost * names_size
+ *
+ **************************************************************************************************/
+#[derive(Clone, Debug)]
+pub struct EntryFunctionPayloadGasParameters {
+    pub base: InternalGas,
+    pub per_byte: InternalGasPerByte,
+}
+
+fn string_value(s: &str) -> Value {
+    Value::struct_(Struct::pack(vec![Value::vector_u8(s.as_bytes().to_vec())]))
+}
+
+fn native_entry_function_payload(
+    gas_params: &EntryFunctionPayloadGasParameters,
+    context: &mut SafeNativeContext,
+    mut _ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> SafeNativeResult<SmallVec<[Value; 1]>> {
+    context.charge(gas_params.base)?;
+
+    let transaction_context = context.extensions().get::<NativeTransactionContext>();
+    let payload = transaction_context
+        .user_transaction_context()?
+        .entry_function_payload
+        .clone();
+
+    // An `Option<EntryFunctionPayload>` is represented by a vector of zero or one payloads.
+    let payload = match payload {
+        Some(payload) => {
+            context.charge(gas_params.per_byte * NumBytes::new(payload.names_size() as u64))?;
+            let type_arg_names = payload
+                .type_arg_names
+                .iter()
+                .map(|name| string_value(name))
+                .collect();
+            // The vectors built with `vector_for_testing_only` only hold values of a single type,
+            // which is safe.
+            vec![Value::struct_(Struct::pack(vec![
+                Value::address(payload.account_address),
+                string_value(&payload.module_name),
+                string_value(&payload.function_name),
+                Value::vector_for_testing_only(type_arg_names),
+            ]))]
+        },
+        None => vec![],
+    };
+
+    Ok(smallvec![Value::struct_(Struct::pack(vec![
+        Value::vector_for_testing_only(payload)
+    ]))])
+}
+
 /***************************************************************************************************
  * module
  *
@@ -67,6 +386,13 @@ fn native_get_script_hash(
 #[derive(Debug, Clone)]
 pub struct GasParameters {
     pub get_script_hash: GetScriptHashGasParameters,
+    pub generate_unique_address: GenerateUniqueAddressGasParameters,
+    pub sender: SenderGasParameters,
+    pub secondary_signers: SecondarySignersGasParameters,
+    pub gas_payer: GasPayerGasParameters,
+    pub max_gas_amount: MaxGasAmountGasParameters,
+    pub gas_unit_price: GasUnitPriceGasParameters,
+    pub entry_function_payload: EntryFunctionPayloadGasParameters,
 }
 
 pub fn make_all(
@@ -74,15 +400,80 @@ pub fn make_all(
     timed_features: TimedFeatures,
     features: Arc<Features>,
 ) -> impl Iterator<Item = (String, NativeFunction)> {
-    let natives = [(
-        "get_script_hash",
-        make_safe_native(
-            gas_params.get_script_hash,
-            timed_features,
-            features,
-            native_get_script_hash,
+    let natives = [
+        (
+            "get_script_hash",
+            make_safe_native(
+                gas_params.get_script_hash,
+                timed_features.clone(),
+                features.clone(),
+                native_get_script_hash,
+            ),
+        ),
+        (
+            "generate_unique_address",
+            make_safe_native(
+                gas_params.generate_unique_address,
+                timed_features.clone(),
+                features.clone(),
+                native_generate_unique_address,
+            ),
+        ),
+        (
+            "sender",
+            make_safe_native(
+                gas_params.sender,
+                timed_features.clone(),
+                features.clone(),
+                native_sender,
+            ),
+        ),
+        (
+            "secondary_signers",
+            make_safe_native(
+                gas_params.secondary_signers,
+                timed_features.clone(),
+                features.clone(),
+                native_secondary_signers,
+            ),
+        ),
+        (
+            "gas_payer",
+            make_safe_native(
+                gas_params.gas_payer,
+                timed_features.clone(),
+                features.clone(),
+                native_gas_payer,
+            ),
+        ),
+        (
+            "max_gas_amount",
+            make_safe_native(
+                gas_params.max_gas_amount,
+                timed_features.clone(),
+                features.clone(),
+                native_max_gas_amount,
+            ),
+        ),
+        (
+            "gas_unit_price",
+            make_safe_native(
+                gas_params.gas_unit_price,
+                timed_features.clone(),
+                features.clone(),
+                native_gas_unit_price,
+            ),
+        ),
+        (
+            "entry_function_payload",
+            make_safe_native(
+                gas_params.entry_function_payload,
+                timed_features,
+                features,
+                native_entry_function_payload,
+            ),
         ),
-    )];
+    ];
 
     crate::natives::helpers::make_module_natives(natives)
 }
@@ -94,6 +94,7 @@ pub fn initialize_database_and_checkpoints(
         node_config.storage.storage_pruner_config,
         node_config.storage.rocksdb_configs,
         node_config.storage.enable_indexer,
+        node_config.storage.enable_event_type_index,
         node_config.storage.buffered_state_target_items,
         node_config.storage.max_num_nodes_per_lru_cache_shard,
     )
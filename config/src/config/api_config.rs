@@ -42,6 +42,16 @@ pub struct ApiConfig {
 
     /// Max gas unit for view function.
     pub max_gas_view_function: u64,
+
+    #[serde(default = "default_disabled")]
+    pub transaction_stream_enabled: bool,
+    /// How often a transaction stream checks storage for new transactions once caught up
+    pub transaction_stream_poll_interval_ms: u64,
+    /// Maximum number of transaction streams served at the same time
+    pub max_concurrent_transaction_streams: usize,
+    /// Maximum number of transactions a stream may scan to catch up with the ledger, i.e., how
+    /// far behind the latest ledger version a stream may start
+    pub max_transaction_stream_catch_up: u64,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -51,6 +61,9 @@ pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 pub const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 pub const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
+pub const DEFAULT_TRANSACTION_STREAM_POLL_INTERVAL_MS: u64 = 100;
+pub const DEFAULT_MAX_CONCURRENT_TRANSACTION_STREAMS: usize = 16;
+pub const DEFAULT_MAX_TRANSACTION_STREAM_CATCH_UP: u64 = 1_000_000;
 pub const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
 
 fn default_enabled() -> bool {
@@ -83,6 +96,10 @@ impl Default for ApiConfig {
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            transaction_stream_enabled: default_disabled(),
+            transaction_stream_poll_interval_ms: DEFAULT_TRANSACTION_STREAM_POLL_INTERVAL_MS,
+            max_concurrent_transaction_streams: DEFAULT_MAX_CONCURRENT_TRANSACTION_STREAMS,
+            max_transaction_stream_catch_up: DEFAULT_MAX_TRANSACTION_STREAM_CATCH_UP,
         }
     }
 }
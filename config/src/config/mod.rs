@@ -296,7 +296,8 @@ impl NodeConfig {
         let mut config = config
             .validate_indexer_configs()?
             .validate_indexer_grpc_configs()?
-            .validate_network_configs()?;
+            .validate_network_configs()?
+            .validate_safety_rules_configs()?;
         config.set_data_dir(config.data_dir().to_path_buf());
         Ok(config)
     }
@@ -435,6 +436,25 @@ impl NodeConfig {
         Ok(self)
     }
 
+    /// Checks that `SafetyRulesConfig` uses a backend able to hold the consensus key, and that a
+    /// remote safety rules service is authenticated by exactly one public key
+    fn validate_safety_rules_configs(self) -> Result<NodeConfig, Error> {
+        let safety_rules = &self.consensus.safety_rules;
+        invariant(
+            !matches!(safety_rules.backend, SecureBackend::Pkcs11Storage(_)),
+            "PKCS#11 storage cannot hold the consensus key and is not a safety rules backend"
+                .into(),
+        )?;
+        if let SafetyRulesService::Process(RemoteService {
+            authentication: Some(authentication),
+            ..
+        }) = &safety_rules.service
+        {
+            authentication.safety_rules_public_key()?;
+        }
+        Ok(self)
+    }
+
     pub fn save<P: AsRef<Path>>(&mut self, output_path: P) -> Result<(), Error> {
         let output_dir = RootPath::new(&output_path);
         self.execution.save(&output_dir)?;
@@ -602,6 +622,9 @@ impl RootPath {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::keys::ConfigKey;
+    use aptos_crypto::Uniform;
+    use aptos_types::network_address::NetworkAddress;
 
     #[test]
     fn verify_role_type_conversion() {
@@ -644,4 +667,37 @@ mod test {
             Err(Error::InvariantViolation(_))
         ));
     }
+
+    #[test]
+    fn validate_pkcs11_safety_rules_backend() {
+        let mut config = NodeConfig::default_for_validator();
+        config.consensus.safety_rules.backend = SecureBackend::Pkcs11Storage(Pkcs11StorageConfig {
+            module_path: PathBuf::from("/usr/lib/softhsm/libsofthsm2.so"),
+            token_label: "validator".to_string(),
+            pin: Token::FromConfig("1234".to_string()),
+            namespace: None,
+        });
+        assert!(matches!(
+            config.validate_safety_rules_configs(),
+            Err(Error::InvariantViolation(_))
+        ));
+    }
+    #[test]
+    fn validate_remote_safety_rules_trusted_peers() {
+        let mut config = NodeConfig::default_for_validator();
+        config.consensus.safety_rules.service = SafetyRulesService::Process(RemoteService {
+            server_address: NetworkAddress::from_str("/ip4/127.0.0.1/tcp/5555").unwrap(),
+            authentication: Some(RemoteServiceAuthentication {
+                identity: RemoteServiceIdentity::FromConfig(ConfigKey::new(
+                    x25519::PrivateKey::generate(&mut StdRng::from_seed([0u8; 32])),
+                )),
+                trusted_peers: vec![],
+            }),
+            request_timeout_ms: None,
+        });
+        assert!(matches!(
+            config.validate_safety_rules_configs(),
+            Err(Error::InvariantViolation(_))
+        ));
+    }
 }
@@ -3,14 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    config::{IdentityBlob, LoggerConfig, SecureBackend, WaypointConfig},
+    config::{Error, IdentityBlob, LoggerConfig, SecureBackend, WaypointConfig},
     keys::ConfigKey,
 };
-use aptos_crypto::{bls12381, Uniform};
+use aptos_crypto::{bls12381, x25519, Uniform};
 use aptos_types::{network_address::NetworkAddress, waypoint::Waypoint, PeerId};
 use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashSet,
     net::{SocketAddr, ToSocketAddrs},
     path::PathBuf,
 };
@@ -47,6 +48,8 @@ impl SafetyRulesConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
         if let SecureBackend::OnDiskStorage(backend) = &mut self.backend {
             backend.set_data_dir(data_dir);
+        } else if let SecureBackend::EncryptedOnDiskStorage(backend) = &mut self.backend {
+            backend.set_data_dir(data_dir);
         } else if let SecureBackend::RocksDbStorage(backend) = &mut self.backend {
             backend.set_data_dir(data_dir);
         }
@@ -90,7 +93,7 @@ impl InitialSafetyRulesConfig {
 }
 
 /// Defines how safety rules should be executed
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum SafetyRulesService {
     /// This runs safety rules in the same thread as event processor
@@ -104,10 +107,18 @@ pub enum SafetyRulesService {
     Thread,
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RemoteService {
     pub server_address: NetworkAddress,
+    /// If set, the connection between the validator and safety rules is mutually authenticated
+    /// and encrypted with Noise IK, otherwise it is plain TCP.
+    #[serde(default)]
+    pub authentication: Option<RemoteServiceAuthentication>,
+    /// How long the validator retries a request, reconnecting as needed, before failing it, in
+    /// milliseconds. Requests are retried indefinitely if unset.
+    #[serde(default)]
+    pub request_timeout_ms: Option<u64>,
 }
 
 impl RemoteService {
@@ -120,6 +131,61 @@ impl RemoteService {
     }
 }
 
+/// The keys authenticating the validator and safety rules to each other. The same config is used on
+/// both ends, each with its own identity.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteServiceAuthentication {
+    /// The key authenticating this end of the connection
+    pub identity: RemoteServiceIdentity,
+    /// On the validator, the public key of safety rules. On safety rules, the public keys of the
+    /// validators allowed to connect.
+    pub trusted_peers: Vec<x25519::PublicKey>,
+}
+
+impl RemoteServiceAuthentication {
+    pub fn identity_key(&self) -> x25519::PrivateKey {
+        match &self.identity {
+            RemoteServiceIdentity::FromConfig(key) => key.private_key(),
+            RemoteServiceIdentity::FromFile(path) => {
+                IdentityBlob::from_file(path)
+                    .expect("Unable to read identity blob")
+                    .network_private_key
+            },
+        }
+    }
+
+    /// On the validator, the public key of safety rules, which must be its only trusted peer.
+    pub fn safety_rules_public_key(&self) -> Result<x25519::PublicKey, Error> {
+        match self.trusted_peers.as_slice() {
+            [public_key] => Ok(*public_key),
+            trusted_peers => Err(Error::InvariantViolation(format!(
+                "The validator must trust exactly one safety rules public key, found {}",
+                trusted_peers.len()
+            ))),
+        }
+    }
+
+    /// On safety rules, the public keys of the validators allowed to connect, at least one.
+    pub fn validator_public_keys(&self) -> Result<HashSet<x25519::PublicKey>, Error> {
+        if self.trusted_peers.is_empty() {
+            return Err(Error::InvariantViolation(
+                "Safety rules must trust at least one validator public key".into(),
+            ));
+        }
+        Ok(self.trusted_peers.iter().copied().collect())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteServiceIdentity {
+    /// The key is stored within the config
+    FromConfig(ConfigKey<x25519::PrivateKey>),
+    /// The network key of the identity blob at this path, e.g., the validator identity
+    FromFile(PathBuf),
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SafetyRulesTestConfig {
     pub author: PeerId,
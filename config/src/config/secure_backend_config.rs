@@ -4,8 +4,9 @@
 
 use crate::config::Error;
 use aptos_secure_storage::{
-    GitHubStorage, InMemoryStorage, Namespaced, OnDiskStorage, RocksDbStorage, Storage,
-    VaultStorage, SECURE_STORAGE_DB_NAME,
+    EncryptedOnDiskStorage, GitHubStorage, InMemoryStorage, Namespaced, OnDiskStorage,
+    Pkcs11Storage, RocksDbStorage, Storage, VaultStorage, WrappingKeySource,
+    SECURE_STORAGE_DB_NAME,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -21,7 +22,9 @@ pub enum SecureBackend {
     InMemoryStorage,
     Vault(VaultConfig),
     OnDiskStorage(OnDiskStorageConfig),
+    EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig),
     RocksDbStorage(RocksDbStorageConfig),
+    Pkcs11Storage(Pkcs11StorageConfig),
 }
 
 impl SecureBackend {
@@ -30,7 +33,12 @@ impl SecureBackend {
             SecureBackend::GitHub(GitHubConfig { namespace, .. })
             | SecureBackend::Vault(VaultConfig { namespace, .. })
             | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
-            | SecureBackend::RocksDbStorage(RocksDbStorageConfig { namespace, .. }) => {
+            | SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+                namespace,
+                ..
+            })
+            | SecureBackend::RocksDbStorage(RocksDbStorageConfig { namespace, .. })
+            | SecureBackend::Pkcs11Storage(Pkcs11StorageConfig { namespace, .. }) => {
                 namespace.as_deref()
             },
             SecureBackend::InMemoryStorage => None,
@@ -42,7 +50,12 @@ impl SecureBackend {
             SecureBackend::GitHub(GitHubConfig { namespace, .. })
             | SecureBackend::Vault(VaultConfig { namespace, .. })
             | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
-            | SecureBackend::RocksDbStorage(RocksDbStorageConfig { namespace, .. }) => {
+            | SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+                namespace,
+                ..
+            })
+            | SecureBackend::RocksDbStorage(RocksDbStorageConfig { namespace, .. })
+            | SecureBackend::Pkcs11Storage(Pkcs11StorageConfig { namespace, .. }) => {
                 *namespace = None;
             },
             SecureBackend::InMemoryStorage => {},
@@ -102,6 +115,24 @@ impl VaultConfig {
     }
 }
 
+/// A PKCS#11 token signing with Ed25519 keys that never leave it. PKCS#11 defines no BLS12-381
+/// mechanism, so the token cannot hold the consensus key and is not a safety rules backend.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pkcs11StorageConfig {
+    /// The PKCS#11 module (shared library) of the token, this is expected to be a full path,
+    /// e.g., /usr/lib/softhsm/libsofthsm2.so.
+    pub module_path: PathBuf,
+    /// The label of the token holding the keys and data.
+    pub token_label: String,
+    /// The user pin for logging into the token
+    pub pin: Token,
+    /// A namespace is an optional portion of the path to a key stored within the token. For
+    /// example, a key, S, without a namespace would be available in S, with a namespace, N, it
+    /// would be in N/S.
+    pub namespace: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct OnDiskStorageConfig {
@@ -169,6 +200,65 @@ impl OnDiskStorageConfig {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptedOnDiskStorageConfig {
+    // Required path for encrypted on disk storage
+    pub path: PathBuf,
+    /// The secret the key encrypting the storage is derived from
+    pub wrapping_key: WrappingKeyConfig,
+    /// A namespace is an optional portion of the path to a key stored within
+    /// EncryptedOnDiskStorage. For example, a key, S, without a namespace would be available in S,
+    /// with a namespace, N, it would be in N/S.
+    pub namespace: Option<String>,
+    #[serde(skip)]
+    data_dir: PathBuf,
+}
+
+impl EncryptedOnDiskStorageConfig {
+    pub fn new(path: PathBuf, wrapping_key: WrappingKeyConfig) -> Self {
+        Self {
+            path,
+            wrapping_key,
+            namespace: None,
+            data_dir: PathBuf::from("/opt/aptos/data"),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        if self.path.is_relative() {
+            self.data_dir.join(&self.path)
+        } else {
+            self.path.clone()
+        }
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+}
+
+/// The secret the key encrypting an encrypted on disk storage is derived from.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrappingKeyConfig {
+    /// A passphrase, either directly within this config or stored somewhere on disk
+    Passphrase(Token),
+    /// This is an absolute path to a file holding key material, e.g., random bytes, used as is
+    KeyFile(PathBuf),
+}
+
+impl WrappingKeyConfig {
+    pub fn wrapping_key_source(&self) -> Result<WrappingKeySource, Error> {
+        match self {
+            WrappingKeyConfig::Passphrase(token) => {
+                Ok(WrappingKeySource::Passphrase(token.read_token()?))
+            },
+            WrappingKeyConfig::KeyFile(path) => Ok(WrappingKeySource::KeyFile(path.clone())),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RocksDbStorageConfig {
@@ -244,6 +334,20 @@ impl From<&SecureBackend> for Storage {
                     storage
                 }
             },
+            SecureBackend::EncryptedOnDiskStorage(config) => {
+                let storage = Storage::from(EncryptedOnDiskStorage::new(
+                    config.path(),
+                    &config
+                        .wrapping_key
+                        .wrapping_key_source()
+                        .expect("Unable to read wrapping key"),
+                ));
+                if let Some(namespace) = &config.namespace {
+                    Storage::from(Namespaced::new(namespace, Box::new(storage)))
+                } else {
+                    storage
+                }
+            },
             SecureBackend::RocksDbStorage(config) => {
                 let storage = Storage::from(RocksDbStorage::new(config.path()));
                 if let Some(namespace) = &config.namespace {
@@ -252,6 +356,18 @@ impl From<&SecureBackend> for Storage {
                     storage
                 }
             },
+            SecureBackend::Pkcs11Storage(config) => {
+                let storage = Storage::from(Pkcs11Storage::new(
+                    &config.module_path,
+                    &config.token_label,
+                    config.pin.read_token().expect("Unable to read pin"),
+                ));
+                if let Some(namespace) = &config.namespace {
+                    Storage::from(Namespaced::new(namespace, Box::new(storage)))
+                } else {
+                    storage
+                }
+            },
             SecureBackend::Vault(config) => {
                 let storage = Storage::from(VaultStorage::new(
                     config.server.clone(),
@@ -370,6 +486,51 @@ vault:
         serde_yaml::to_string(&from_disk).unwrap();
     }
 
+    #[test]
+    fn test_encrypted_on_disk_storage_parsing() {
+        let config = SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig::new(
+            PathBuf::from("secure_storage.json.enc"),
+            WrappingKeyConfig::Passphrase(Token::FromDisk(PathBuf::from("/passphrase"))),
+        ));
+
+        let text = r#"
+type: "encrypted_on_disk_storage"
+path: "secure_storage.json.enc"
+wrapping_key:
+    passphrase:
+        from_disk: "/passphrase"
+        "#;
+
+        let de_config: SecureBackend = serde_yaml::from_str(text).unwrap();
+        assert_eq!(de_config, config);
+        // Just assert that it can be serialized, no need to do string comparison
+        serde_yaml::to_string(&config).unwrap();
+    }
+
+    #[test]
+    fn test_pkcs11_storage_parsing() {
+        let config = SecureBackend::Pkcs11Storage(Pkcs11StorageConfig {
+            module_path: PathBuf::from("/usr/lib/softhsm/libsofthsm2.so"),
+            token_label: "validator".to_string(),
+            pin: Token::FromDisk(PathBuf::from("/pin")),
+            namespace: None,
+        });
+
+        let text = r#"
+type: "pkcs11_storage"
+module_path: "/usr/lib/softhsm/libsofthsm2.so"
+token_label: "validator"
+pin:
+    from_disk: "/pin"
+namespace: ~
+        "#;
+
+        let de_config: SecureBackend = serde_yaml::from_str(text).unwrap();
+        assert_eq!(de_config, config);
+        // Just assert that it can be serialized, no need to do string comparison
+        serde_yaml::to_string(&config).unwrap();
+    }
+
     #[test]
     fn test_token_reading() {
         let temppath = aptos_temppath::TempPath::new();
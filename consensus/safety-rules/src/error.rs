@@ -56,6 +56,8 @@ pub enum Error {
     WaypointOutOfDate(u64, u64, u64, u64),
     #[error("Invalid Timeout: {0}")]
     InvalidTimeout(String),
+    #[error("Invalid SafetyData import: {0}")]
+    InvalidSafetyDataImport(String),
 }
 
 impl From<serde_json::Error> for Error {
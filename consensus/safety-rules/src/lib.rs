@@ -12,6 +12,7 @@ mod logging;
 mod persistent_safety_storage;
 mod process;
 mod remote_service;
+mod safety_data_export;
 mod safety_rules;
 mod safety_rules_2chain;
 mod safety_rules_manager;
@@ -20,9 +21,13 @@ mod t_safety_rules;
 mod thread;
 
 pub use crate::{
-    consensus_state::ConsensusState, error::Error,
-    persistent_safety_storage::PersistentSafetyStorage, process::Process,
-    safety_rules::SafetyRules, safety_rules_manager::SafetyRulesManager,
+    consensus_state::ConsensusState,
+    error::Error,
+    persistent_safety_storage::PersistentSafetyStorage,
+    process::Process,
+    safety_data_export::{SafetyDataExport, SAFETY_DATA_EXPORT_VERSION},
+    safety_rules::SafetyRules,
+    safety_rules_manager::SafetyRulesManager,
     t_safety_rules::TSafetyRules,
 };
 
@@ -7,7 +7,11 @@ use crate::{
     remote_service::{self, RemoteService},
     safety_rules_manager,
 };
-use aptos_config::config::{SafetyRulesConfig, SafetyRulesService};
+use aptos_config::config::{
+    Error, RemoteService as RemoteServiceConfig, RemoteServiceAuthentication, SafetyRulesConfig,
+    SafetyRulesService,
+};
+use aptos_crypto::x25519;
 use std::net::SocketAddr;
 
 pub struct Process {
@@ -15,27 +19,42 @@ pub struct Process {
 }
 
 impl Process {
-    pub fn new(config: SafetyRulesConfig) -> Self {
-        let storage = safety_rules_manager::storage(&config);
-
+    /// Fails if the config is not for a SafetyRules process, or if an authenticated process trusts
+    /// no validator.
+    pub fn new(config: SafetyRulesConfig) -> Result<Self, Error> {
         let service = match &config.service {
             SafetyRulesService::Process(service) => service,
-            _ => panic!("Unexpected SafetyRules service: {:?}", config.service),
+            _ => {
+                return Err(Error::InvariantViolation(format!(
+                    "Unexpected SafetyRules service: {:?}",
+                    config.service
+                )))
+            },
         };
+        if let Some(authentication) = &service.authentication {
+            authentication.validator_public_keys()?;
+        }
         let server_addr = service.server_address();
+        let storage = safety_rules_manager::storage(&config);
 
-        Self {
+        Ok(Self {
             data: Some(ProcessData {
                 server_addr,
                 storage,
                 network_timeout: config.network_timeout_ms,
+                authentication: service.authentication.clone(),
             }),
-        }
+        })
     }
 
     pub fn start(&mut self) {
         let data = self.data.take().expect("Unable to retrieve ProcessData");
-        remote_service::execute(data.storage, data.server_addr, data.network_timeout);
+        remote_service::execute(
+            data.storage,
+            data.server_addr,
+            data.network_timeout,
+            data.authentication.as_ref(),
+        );
     }
 }
 
@@ -44,19 +63,33 @@ struct ProcessData {
     storage: PersistentSafetyStorage,
     // Timeout in Seconds for network operations
     network_timeout: u64,
+    authentication: Option<RemoteServiceAuthentication>,
 }
 
 pub struct ProcessService {
     server_addr: SocketAddr,
     network_timeout_ms: u64,
+    /// The keys of the validator and the public key of SafetyRules, its only trusted peer.
+    authentication: Option<(RemoteServiceAuthentication, x25519::PublicKey)>,
+    request_timeout_ms: Option<u64>,
 }
 
 impl ProcessService {
-    pub fn new(server_addr: SocketAddr, network_timeout: u64) -> Self {
-        Self {
-            server_addr,
+    /// Fails if the connection is authenticated but does not trust exactly one SafetyRules key.
+    pub fn new(config: &RemoteServiceConfig, network_timeout: u64) -> Result<Self, Error> {
+        let authentication = match &config.authentication {
+            Some(authentication) => Some((
+                authentication.clone(),
+                authentication.safety_rules_public_key()?,
+            )),
+            None => None,
+        };
+        Ok(Self {
+            server_addr: config.server_address(),
             network_timeout_ms: network_timeout,
-        }
+            authentication,
+            request_timeout_ms: config.request_timeout_ms,
+        })
     }
 }
 
@@ -68,4 +101,16 @@ impl RemoteService for ProcessService {
     fn network_timeout_ms(&self) -> u64 {
         self.network_timeout_ms
     }
+
+    fn noise_keys(&self) -> Option<(x25519::PrivateKey, x25519::PublicKey)> {
+        self.authentication
+            .as_ref()
+            .map(|(authentication, server_public_key)| {
+                (authentication.identity_key(), *server_public_key)
+            })
+    }
+
+    fn request_timeout_ms(&self) -> Option<u64> {
+        self.request_timeout_ms
+    }
 }
@@ -7,18 +7,27 @@ use crate::{
     serializer::{SafetyRulesInput, SerializerClient, SerializerService, TSerializerClient},
     Error, SafetyRules, TSafetyRules,
 };
+use aptos_config::config::RemoteServiceAuthentication;
+use aptos_crypto::x25519;
 use aptos_logger::warn;
 use aptos_secure_net::{NetworkClient, NetworkServer};
-use std::net::SocketAddr;
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 
 pub trait RemoteService {
     fn client(&self) -> SerializerClient {
-        let network_client = NetworkClient::new(
+        let mut network_client = NetworkClient::new(
             "safety-rules",
             self.server_address(),
             self.network_timeout_ms(),
         );
-        let service = Box::new(RemoteClient::new(network_client));
+        if let Some((identity_key, server_public_key)) = self.noise_keys() {
+            network_client = network_client.with_noise(identity_key, server_public_key);
+        }
+        let request_timeout = self.request_timeout_ms().map(Duration::from_millis);
+        let service = Box::new(RemoteClient::new(network_client, request_timeout));
         SerializerClient::new_client(service)
     }
 
@@ -26,9 +35,25 @@ pub trait RemoteService {
 
     /// Network Timeout in milliseconds.
     fn network_timeout_ms(&self) -> u64;
+
+    /// The identity key of this end and the public key of SafetyRules, if the connection is
+    /// authenticated.
+    fn noise_keys(&self) -> Option<(x25519::PrivateKey, x25519::PublicKey)> {
+        None
+    }
+
+    /// Request Timeout in milliseconds, requests are retried indefinitely if None.
+    fn request_timeout_ms(&self) -> Option<u64> {
+        None
+    }
 }
 
-pub fn execute(storage: PersistentSafetyStorage, listen_addr: SocketAddr, network_timeout_ms: u64) {
+pub fn execute(
+    storage: PersistentSafetyStorage,
+    listen_addr: SocketAddr,
+    network_timeout_ms: u64,
+    authentication: Option<&RemoteServiceAuthentication>,
+) {
     let mut safety_rules = SafetyRules::new(storage);
     if let Err(e) = safety_rules.consensus_state() {
         warn!("Unable to print consensus state: {}", e);
@@ -36,6 +61,12 @@ pub fn execute(storage: PersistentSafetyStorage, listen_addr: SocketAddr, networ
 
     let mut serializer_service = SerializerService::new(safety_rules);
     let mut network_server = NetworkServer::new("safety-rules", listen_addr, network_timeout_ms);
+    if let Some(authentication) = authentication {
+        network_server = network_server.with_noise(
+            authentication.identity_key(),
+            authentication.trusted_peers.iter().copied().collect(),
+        );
+    }
 
     loop {
         if let Err(e) = process_one_message(&mut network_server, &mut serializer_service) {
@@ -56,11 +87,15 @@ fn process_one_message(
 
 struct RemoteClient {
     network_client: NetworkClient,
+    request_timeout: Option<Duration>,
 }
 
 impl RemoteClient {
-    pub fn new(network_client: NetworkClient) -> Self {
-        Self { network_client }
+    pub fn new(network_client: NetworkClient, request_timeout: Option<Duration>) -> Self {
+        Self {
+            network_client,
+            request_timeout,
+        }
     }
 
     fn process_one_message(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
@@ -72,9 +107,20 @@ impl RemoteClient {
 impl TSerializerClient for RemoteClient {
     fn request(&mut self, input: SafetyRulesInput) -> Result<Vec<u8>, Error> {
         let input_message = serde_json::to_vec(&input)?;
+        let start = Instant::now();
         loop {
             match self.process_one_message(&input_message) {
-                Err(err) => warn!("Failed to communicate with SafetyRules service: {}", err),
+                Err(err) => {
+                    warn!("Failed to communicate with SafetyRules service: {}", err);
+                    if let Some(request_timeout) = self.request_timeout {
+                        if start.elapsed() >= request_timeout {
+                            return Err(Error::InternalError(format!(
+                                "Request to SafetyRules service timed out after {:?}: {}",
+                                request_timeout, err
+                            )));
+                        }
+                    }
+                },
                 Ok(value) => return Ok(value),
             }
         }
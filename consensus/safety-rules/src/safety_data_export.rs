@@ -0,0 +1,144 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{persistent_safety_storage::PersistentSafetyStorage, Error};
+use aptos_consensus_types::{common::Author, safety_data::SafetyData};
+use aptos_crypto::{bls12381, PrivateKey};
+use aptos_global_constants::{CONSENSUS_KEY, OWNER_ACCOUNT};
+use aptos_secure_storage::KVStorage;
+use aptos_types::waypoint::Waypoint;
+use serde::{Deserialize, Serialize};
+
+/// The version of the format of SafetyDataExport, bumped on incompatible changes.
+pub const SAFETY_DATA_EXPORT_VERSION: u64 = 1;
+
+/// A portable copy of the SafetyData and waypoint of a validator, i.e., the watermarks of what it
+/// has voted and signed over. This allows moving a validator between hosts or secure storage
+/// backends without losing the watermarks protecting it from equivocation.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SafetyDataExport {
+    pub version: u64,
+    pub author: Author,
+    pub safety_data: SafetyData,
+    pub waypoint: Waypoint,
+}
+
+impl SafetyDataExport {
+    /// Exports the SafetyData and waypoint of an initialized storage.
+    pub fn export(storage: &mut PersistentSafetyStorage) -> Result<Self, Error> {
+        Ok(Self {
+            version: SAFETY_DATA_EXPORT_VERSION,
+            author: storage.author()?,
+            safety_data: storage.safety_data()?,
+            waypoint: storage.waypoint()?,
+        })
+    }
+
+    /// Imports the SafetyData and waypoint into the storage of the same validator, whose consensus
+    /// key is `consensus_private_key`. An empty storage is initialized with the author of the
+    /// export and this key, so the validator never runs against reset watermarks. Otherwise, the
+    /// import refuses to lower any of the watermarks already in the storage: its epoch and, within
+    /// the same epoch, its rounds, as well as its waypoint version.
+    pub fn import(
+        &self,
+        storage: &mut PersistentSafetyStorage,
+        consensus_private_key: bls12381::PrivateKey,
+    ) -> Result<(), Error> {
+        if self.version != SAFETY_DATA_EXPORT_VERSION {
+            return Err(Error::InvalidSafetyDataImport(format!(
+                "unsupported version {}, expected {}",
+                self.version, SAFETY_DATA_EXPORT_VERSION
+            )));
+        }
+
+        match storage.author() {
+            Ok(author) => self.check_storage(storage, author, &consensus_private_key)?,
+            Err(Error::SecureStorageMissingDataError(_)) => {
+                let internal_store = storage.internal_store();
+                internal_store.set(CONSENSUS_KEY, consensus_private_key)?;
+                internal_store.set(OWNER_ACCOUNT, self.author)?;
+            },
+            Err(error) => return Err(error),
+        }
+
+        storage.set_safety_data(self.safety_data.clone())?;
+        storage.set_waypoint(&self.waypoint)
+    }
+
+    /// Ensures that the initialized `storage`, owned by `author`, belongs to the validator of the
+    /// export and that importing lowers none of its watermarks.
+    fn check_storage(
+        &self,
+        storage: &mut PersistentSafetyStorage,
+        author: Author,
+        consensus_private_key: &bls12381::PrivateKey,
+    ) -> Result<(), Error> {
+        if self.author != author {
+            return Err(Error::InvalidSafetyDataImport(format!(
+                "exported from {}, but the storage belongs to {}",
+                self.author, author
+            )));
+        }
+        if storage
+            .consensus_key_for_version(consensus_private_key.public_key())
+            .is_err()
+        {
+            return Err(Error::InvalidSafetyDataImport(
+                "the consensus key does not match the one in the storage".into(),
+            ));
+        }
+
+        let current = storage.safety_data()?;
+        check_safety_data(&current, &self.safety_data)?;
+        let current_waypoint = storage.waypoint()?;
+        if self.waypoint.version() < current_waypoint.version() {
+            return Err(Error::InvalidSafetyDataImport(format!(
+                "waypoint version {} is lower than the current one {}",
+                self.waypoint.version(),
+                current_waypoint.version()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Ensures that replacing `current` by `imported` lowers none of the watermarks. Rounds only
+/// matter within an epoch, as they start over with every epoch.
+fn check_safety_data(current: &SafetyData, imported: &SafetyData) -> Result<(), Error> {
+    if imported.epoch < current.epoch {
+        return Err(Error::InvalidSafetyDataImport(format!(
+            "epoch {} is lower than the current one {}",
+            imported.epoch, current.epoch
+        )));
+    }
+    if imported.epoch > current.epoch {
+        return Ok(());
+    }
+
+    let rounds = [
+        (
+            "last_voted_round",
+            imported.last_voted_round,
+            current.last_voted_round,
+        ),
+        (
+            "preferred_round",
+            imported.preferred_round,
+            current.preferred_round,
+        ),
+        (
+            "one_chain_round",
+            imported.one_chain_round,
+            current.one_chain_round,
+        ),
+    ];
+    for (name, imported_round, current_round) in rounds {
+        if imported_round < current_round {
+            return Err(Error::InvalidSafetyDataImport(format!(
+                "{} {} is lower than the current one {} in epoch {}",
+                name, imported_round, current_round, current.epoch
+            )));
+        }
+    }
+    Ok(())
+}
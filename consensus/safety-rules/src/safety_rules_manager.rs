@@ -11,13 +11,20 @@ use crate::{
     thread::ThreadService,
     SafetyRules, TSafetyRules,
 };
-use aptos_config::config::{InitialSafetyRulesConfig, SafetyRulesConfig, SafetyRulesService};
+use aptos_config::config::{
+    InitialSafetyRulesConfig, RemoteService as RemoteServiceConfig, SafetyRulesConfig,
+    SafetyRulesService, SecureBackend,
+};
 use aptos_infallible::RwLock;
 use aptos_secure_storage::{KVStorage, Storage};
-use std::{convert::TryInto, net::SocketAddr, sync::Arc};
+use std::{convert::TryInto, sync::Arc};
 
 pub fn storage(config: &SafetyRulesConfig) -> PersistentSafetyStorage {
     let backend = &config.backend;
+    // PKCS#11 tokens cannot sign with BLS12-381 keys, so the consensus key would leave the token.
+    if let SecureBackend::Pkcs11Storage(_) = backend {
+        panic!("PKCS#11 storage cannot hold the consensus key and is not a safety rules backend");
+    }
     let internal_storage: Storage = backend.try_into().expect("Unable to initialize storage");
     if let Err(error) = internal_storage.available() {
         panic!("Storage is not available: {:?}", error);
@@ -88,7 +95,7 @@ pub struct SafetyRulesManager {
 impl SafetyRulesManager {
     pub fn new(config: &SafetyRulesConfig) -> Self {
         if let SafetyRulesService::Process(conf) = &config.service {
-            return Self::new_process(conf.server_address(), config.network_timeout_ms);
+            return Self::new_process(conf, config.network_timeout_ms);
         }
 
         let storage = storage(config);
@@ -107,8 +114,10 @@ impl SafetyRulesManager {
         }
     }
 
-    pub fn new_process(server_addr: SocketAddr, timeout_ms: u64) -> Self {
-        let process_service = ProcessService::new(server_addr, timeout_ms);
+    pub fn new_process(config: &RemoteServiceConfig, timeout_ms: u64) -> Self {
+        // The config of the validator is checked when it is loaded, see `NodeConfig::load`.
+        let process_service = ProcessService::new(config, timeout_ms)
+            .unwrap_or_else(|error| panic!("Invalid SafetyRules service config: {}", error));
         Self {
             internal_safety_rules: SafetyRulesWrapper::Process(process_service),
         }
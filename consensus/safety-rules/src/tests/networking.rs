@@ -2,8 +2,25 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{test_utils, SafetyRulesManager};
-use aptos_types::validator_signer::ValidatorSigner;
+use crate::{
+    process::{Process, ProcessService},
+    remote_service, test_utils, SafetyRulesManager,
+};
+use aptos_config::{
+    config::{
+        RemoteService, RemoteServiceAuthentication, RemoteServiceIdentity, SafetyRulesConfig,
+        SafetyRulesService,
+    },
+    keys::ConfigKey,
+    utils,
+};
+use aptos_crypto::{x25519, Uniform};
+use aptos_types::{network_address::NetworkAddress, validator_signer::ValidatorSigner};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    thread,
+};
 
 #[test]
 fn test_reconnect() {
@@ -18,3 +35,91 @@ fn test_reconnect() {
     let state1 = safety_rules_manager.client().consensus_state().unwrap();
     assert_eq!(state0, state1);
 }
+
+#[test]
+fn test_authentication() {
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let server_key = x25519::PrivateKey::generate(&mut rng);
+    let client_key = x25519::PrivateKey::generate(&mut rng);
+    let untrusted_key = x25519::PrivateKey::generate(&mut rng);
+    let server_public_key = server_key.public_key();
+    let client_public_key = client_key.public_key();
+
+    let listen_port = utils::get_available_port();
+    let listen_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listen_port);
+    // test value for network timeout, in milliseconds.
+    let network_timeout = 5_000;
+
+    let signer = ValidatorSigner::from_int(0);
+    let storage = test_utils::test_storage(&signer);
+    let server_authentication = authentication(server_key, client_public_key);
+    thread::spawn(move || {
+        remote_service::execute(
+            storage,
+            listen_addr,
+            network_timeout,
+            Some(&server_authentication),
+        )
+    });
+
+    // A trusted validator is served
+    let config = remote_service_config(listen_addr, authentication(client_key, server_public_key));
+    let safety_rules_manager = SafetyRulesManager::new_process(&config, network_timeout);
+    safety_rules_manager.client().consensus_state().unwrap();
+
+    // An untrusted validator is not, and gives up once its request times out
+    let config = remote_service_config(
+        listen_addr,
+        authentication(untrusted_key, server_public_key),
+    );
+    let safety_rules_manager = SafetyRulesManager::new_process(&config, network_timeout);
+    safety_rules_manager.client().consensus_state().unwrap_err();
+}
+
+#[test]
+fn test_trusted_peers() {
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let public_keys: Vec<_> = (0..2)
+        .map(|_| x25519::PrivateKey::generate(&mut rng).public_key())
+        .collect();
+    let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+    let identity_key = x25519::PrivateKey::generate(&mut rng);
+    let mut config =
+        remote_service_config(server_addr, authentication(identity_key, public_keys[0]));
+    let network_timeout = 5_000;
+
+    // The validator trusts exactly one SafetyRules public key
+    ProcessService::new(&config, network_timeout).unwrap();
+    config.authentication.as_mut().unwrap().trusted_peers = public_keys;
+    assert!(ProcessService::new(&config, network_timeout).is_err());
+
+    // SafetyRules trust at least one validator public key
+    config.authentication.as_mut().unwrap().trusted_peers = vec![];
+    assert!(ProcessService::new(&config, network_timeout).is_err());
+    let safety_rules_config = SafetyRulesConfig {
+        service: SafetyRulesService::Process(config),
+        ..Default::default()
+    };
+    assert!(Process::new(safety_rules_config).is_err());
+}
+
+fn authentication(
+    identity_key: x25519::PrivateKey,
+    trusted_peer: x25519::PublicKey,
+) -> RemoteServiceAuthentication {
+    RemoteServiceAuthentication {
+        identity: RemoteServiceIdentity::FromConfig(ConfigKey::new(identity_key)),
+        trusted_peers: vec![trusted_peer],
+    }
+}
+
+fn remote_service_config(
+    server_addr: SocketAddr,
+    authentication: RemoteServiceAuthentication,
+) -> RemoteService {
+    RemoteService {
+        server_address: NetworkAddress::from(server_addr),
+        authentication: Some(authentication),
+        request_timeout_ms: Some(1_000),
+    }
+}
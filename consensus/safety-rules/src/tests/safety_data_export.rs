@@ -0,0 +1,175 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{test_utils, Error, PersistentSafetyStorage, SafetyDataExport};
+use aptos_consensus_types::safety_data::SafetyData;
+use aptos_crypto::HashValue;
+use aptos_secure_storage::{InMemoryStorage, Storage};
+use aptos_types::{
+    block_info::BlockInfo, epoch_state::EpochState, ledger_info::LedgerInfo,
+    validator_signer::ValidatorSigner, waypoint::Waypoint,
+};
+
+fn waypoint(version: u64) -> Waypoint {
+    let ledger_info = LedgerInfo::new(
+        BlockInfo::new(
+            1,
+            10,
+            HashValue::random(),
+            HashValue::random(),
+            version,
+            1000,
+            Some(EpochState::empty()),
+        ),
+        HashValue::zero(),
+    );
+    Waypoint::new_epoch_boundary(&ledger_info).unwrap()
+}
+
+/// Returns the storage of `signer` with the given safety data and waypoint version.
+fn storage(
+    signer: &ValidatorSigner,
+    safety_data: SafetyData,
+    waypoint_version: u64,
+) -> PersistentSafetyStorage {
+    let mut storage = test_utils::test_storage(signer);
+    storage.set_safety_data(safety_data).unwrap();
+    storage.set_waypoint(&waypoint(waypoint_version)).unwrap();
+    storage
+}
+
+fn assert_invalid_import(result: Result<(), Error>) {
+    assert!(matches!(result, Err(Error::InvalidSafetyDataImport(_))));
+}
+
+#[test]
+fn test_export_import() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut source = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 5);
+    let export = SafetyDataExport::export(&mut source).unwrap();
+    assert_eq!(export.author, signer.author());
+
+    // The export survives a round trip through its file format
+    let export: SafetyDataExport =
+        serde_json::from_slice(&serde_json::to_vec(&export).unwrap()).unwrap();
+
+    let mut target = test_utils::test_storage(&signer);
+    export
+        .import(&mut target, signer.private_key().clone())
+        .unwrap();
+    assert_eq!(target.safety_data().unwrap(), export.safety_data);
+    assert_eq!(target.waypoint().unwrap(), export.waypoint);
+
+    // Importing the same data again is a no-op
+    export
+        .import(&mut target, signer.private_key().clone())
+        .unwrap();
+    assert_eq!(SafetyDataExport::export(&mut target).unwrap(), export);
+}
+
+#[test]
+fn test_import_into_empty_storage() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut source = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 5);
+    let export = SafetyDataExport::export(&mut source).unwrap();
+
+    // The storage is initialized by the import itself, never holding reset watermarks
+    let mut target = PersistentSafetyStorage::new(Storage::from(InMemoryStorage::new()), true);
+    export
+        .import(&mut target, signer.private_key().clone())
+        .unwrap();
+    assert_eq!(target.author().unwrap(), signer.author());
+    assert!(target
+        .consensus_key_for_version(signer.public_key())
+        .is_ok());
+    assert_eq!(SafetyDataExport::export(&mut target).unwrap(), export);
+}
+
+#[test]
+fn test_import_refuses_other_consensus_key() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut source = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 5);
+    let export = SafetyDataExport::export(&mut source).unwrap();
+
+    let mut target = test_utils::test_storage(&signer);
+    let other_signer = ValidatorSigner::from_int(1);
+    assert_invalid_import(export.import(&mut target, other_signer.private_key().clone()));
+}
+
+#[test]
+fn test_import_refuses_lower_rounds() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut source = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 5);
+    let export = SafetyDataExport::export(&mut source).unwrap();
+
+    for current in [
+        SafetyData::new(2, 11, 8, 9, None),
+        SafetyData::new(2, 10, 9, 9, None),
+        SafetyData::new(2, 10, 8, 10, None),
+    ] {
+        let mut target = storage(&signer, current.clone(), 5);
+        assert_invalid_import(export.import(&mut target, signer.private_key().clone()));
+        // The target is left untouched
+        assert_eq!(target.safety_data().unwrap(), current);
+    }
+}
+
+#[test]
+fn test_import_refuses_lower_epoch() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut source = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 5);
+    let export = SafetyDataExport::export(&mut source).unwrap();
+
+    let mut target = storage(&signer, SafetyData::new(3, 1, 0, 0, None), 5);
+    assert_invalid_import(export.import(&mut target, signer.private_key().clone()));
+}
+
+#[test]
+fn test_import_higher_epoch_with_lower_rounds() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut source = storage(&signer, SafetyData::new(3, 1, 0, 0, None), 5);
+    let export = SafetyDataExport::export(&mut source).unwrap();
+
+    // Rounds start over with every epoch
+    let mut target = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 5);
+    export
+        .import(&mut target, signer.private_key().clone())
+        .unwrap();
+    assert_eq!(target.safety_data().unwrap(), export.safety_data);
+}
+
+#[test]
+fn test_import_refuses_lower_waypoint() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut source = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 5);
+    let export = SafetyDataExport::export(&mut source).unwrap();
+
+    let mut target = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 6);
+    assert_invalid_import(export.import(&mut target, signer.private_key().clone()));
+    assert_eq!(target.waypoint().unwrap().version(), 6);
+}
+
+#[test]
+fn test_import_refuses_other_validator() {
+    let mut source = storage(
+        &ValidatorSigner::from_int(0),
+        SafetyData::new(2, 10, 8, 9, None),
+        5,
+    );
+    let export = SafetyDataExport::export(&mut source).unwrap();
+
+    let signer = ValidatorSigner::from_int(1);
+    let mut target = test_utils::test_storage(&signer);
+    assert_invalid_import(export.import(&mut target, signer.private_key().clone()));
+}
+
+#[test]
+fn test_import_refuses_unsupported_version() {
+    let signer = ValidatorSigner::from_int(0);
+    let mut source = storage(&signer, SafetyData::new(2, 10, 8, 9, None), 5);
+    let mut export = SafetyDataExport::export(&mut source).unwrap();
+    export.version += 1;
+
+    let mut target = test_utils::test_storage(&signer);
+    assert_invalid_import(export.import(&mut target, signer.private_key().clone()));
+}
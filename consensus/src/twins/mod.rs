@@ -3,4 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod basic_twins_test;
+mod scenario;
+mod simulation_test;
+mod simulator;
 mod twins_node;
@@ -0,0 +1,175 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_consensus_types::common::Round;
+use std::{collections::BTreeMap, ops::Range};
+
+/// Identifies a node of a simulation. Nodes `0..num_nodes` are the validators, and twins are
+/// appended after them in the order of `Scenario::twins`, as with `SMRNode`.
+pub type NodeId = usize;
+
+/// A fault injected into a simulation during `steps`, the steps of its scheduler. Simulated time
+/// goes on while nodes are stuck, so every fault ends.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Fault {
+    /// Nodes within different partitions don't exchange messages during these steps. Nodes that
+    /// are part of no partition reach all nodes.
+    Partition {
+        steps: Range<u64>,
+        partitions: Vec<Vec<NodeId>>,
+    },
+    /// Drops the messages from `from` to `to` during these steps.
+    Drop {
+        steps: Range<u64>,
+        from: NodeId,
+        to: NodeId,
+    },
+    /// Holds back the messages sent from `from` to `to` during these steps for `delay` steps.
+    Delay {
+        steps: Range<u64>,
+        from: NodeId,
+        to: NodeId,
+        delay: u64,
+    },
+    /// Crashes the node during these steps, losing the messages in flight from and to it. The node
+    /// then restarts from its storage.
+    Crash { steps: Range<u64>, node: NodeId },
+}
+
+impl Fault {
+    pub fn steps(&self) -> &Range<u64> {
+        match self {
+            Fault::Partition { steps, .. }
+            | Fault::Drop { steps, .. }
+            | Fault::Delay { steps, .. }
+            | Fault::Crash { steps, .. } => steps,
+        }
+    }
+
+    fn steps_mut(&mut self) -> &mut Range<u64> {
+        match self {
+            Fault::Partition { steps, .. }
+            | Fault::Drop { steps, .. }
+            | Fault::Delay { steps, .. }
+            | Fault::Crash { steps, .. } => steps,
+        }
+    }
+
+    /// Returns the faults one step simpler than this one, each lasting half of its steps or, for
+    /// a partition, leaving a node out of it.
+    fn shrink_candidates(&self) -> Vec<Fault> {
+        let mut candidates = vec![];
+        let Range { start, end } = self.steps().clone();
+        if end - start > 1 {
+            let middle = start + (end - start) / 2;
+            for steps in [start..middle, middle..end] {
+                let mut candidate = self.clone();
+                *candidate.steps_mut() = steps;
+                candidates.push(candidate);
+            }
+        }
+        if let Fault::Partition { steps, partitions } = self {
+            for (index, partition) in partitions.iter().enumerate() {
+                for node in partition {
+                    let mut partitions = partitions.clone();
+                    partitions[index].retain(|other| other != node);
+                    partitions.retain(|partition| !partition.is_empty());
+                    candidates.push(Fault::Partition {
+                        steps: steps.clone(),
+                        partitions,
+                    });
+                }
+            }
+        }
+        candidates
+    }
+}
+
+/// A scripted run of a network of validators, replayed deterministically by `simulate` for a
+/// given seed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Scenario {
+    pub num_nodes: usize,
+    /// Validators running a twin, i.e., a second instance with the same identity but its own
+    /// storage, which makes them equivocate. Twins and their validators are byzantine.
+    pub twins: Vec<NodeId>,
+    /// Leaders of specific rounds, overriding the rotation over validators.
+    pub leaders: BTreeMap<Round, NodeId>,
+    pub faults: Vec<Fault>,
+    /// The simulation stops once the network is stable and every live honest node is this many
+    /// rounds past the round of the simulation at the global stabilization time.
+    pub rounds: Round,
+    /// Number of steps the network has to be quiet for before the round timers expire.
+    pub timeout_steps: u64,
+    /// Upper bound on the number of steps, after which the simulation stops regardless.
+    pub max_steps: u64,
+    /// Whether honest nodes are expected to commit again once all faults are over.
+    pub check_liveness: bool,
+}
+
+impl Scenario {
+    pub fn new(num_nodes: usize) -> Self {
+        Self {
+            num_nodes,
+            twins: vec![],
+            leaders: BTreeMap::new(),
+            faults: vec![],
+            rounds: 10,
+            timeout_steps: 20,
+            max_steps: 5_000,
+            check_liveness: true,
+        }
+    }
+
+    pub fn num_instances(&self) -> usize {
+        self.num_nodes + self.twins.len()
+    }
+
+    /// Returns the validator the node is an instance of.
+    pub fn validator(&self, node: NodeId) -> NodeId {
+        if node < self.num_nodes {
+            node
+        } else {
+            self.twins[node - self.num_nodes]
+        }
+    }
+
+    /// Returns whether the node is an instance of a validator without twins.
+    pub fn is_honest(&self, node: NodeId) -> bool {
+        !self.twins.contains(&self.validator(node))
+    }
+
+    /// Returns the step from which on the network is free of faults, i.e., the global
+    /// stabilization time.
+    pub fn gst(&self) -> u64 {
+        self.faults
+            .iter()
+            .map(|fault| fault.steps().end)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the scenarios one step simpler than this one, each dropping a single fault or
+    /// leader override, or narrowing a single fault.
+    pub fn shrink_candidates(&self) -> Vec<Scenario> {
+        let mut candidates = vec![];
+        for index in 0..self.faults.len() {
+            let mut candidate = self.clone();
+            candidate.faults.remove(index);
+            candidates.push(candidate);
+        }
+        for round in self.leaders.keys() {
+            let mut candidate = self.clone();
+            candidate.leaders.remove(round);
+            candidates.push(candidate);
+        }
+        for (index, fault) in self.faults.iter().enumerate() {
+            for narrowed_fault in fault.shrink_candidates() {
+                let mut candidate = self.clone();
+                candidate.faults[index] = narrowed_fault;
+                candidates.push(candidate);
+            }
+        }
+        candidates
+    }
+}
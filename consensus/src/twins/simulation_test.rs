@@ -0,0 +1,228 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::twins::{
+    scenario::{Fault, Scenario},
+    simulator::{simulate, simulate_and_shrink, SimulationReport, TraceEvent, Violation},
+};
+use maplit::btreemap;
+
+fn assert_upholds_invariants(scenario: &Scenario, seed: u64) -> SimulationReport {
+    simulate_and_shrink(scenario, seed).unwrap_or_else(|reproduction| {
+        panic!(
+            "Seed {} violates {:?}, reproduced by {:?}",
+            reproduction.seed, reproduction.violation, reproduction.scenario
+        )
+    })
+}
+
+#[test]
+/// This test checks that honest nodes commit without faults, whatever the order messages are
+/// delivered in.
+///
+/// Run the test:
+/// cargo xtest -p consensus simulation_no_faults_test -- --nocapture
+fn simulation_no_faults_test() {
+    let scenario = Scenario::new(4);
+    for seed in 0..3 {
+        let report = assert_upholds_invariants(&scenario, seed);
+        assert!(report
+            .committed_rounds
+            .iter()
+            .all(|round| round.unwrap() > 0));
+    }
+}
+
+#[test]
+/// This test checks that a simulation is replayed exactly for a given seed, also when a node
+/// retrieves the blocks it missed while partitioned away.
+fn simulation_determinism_test() {
+    let mut scenario = Scenario::new(4);
+    scenario.faults = vec![
+        Fault::Delay {
+            steps: 0..40,
+            from: 1,
+            to: 2,
+            delay: 5,
+        },
+        Fault::Partition {
+            steps: 0..40,
+            partitions: vec![vec![0, 1, 2], vec![3]],
+        },
+    ];
+    for seed in [0, 42] {
+        let report = assert_upholds_invariants(&scenario, seed);
+        assert_eq!(simulate(&scenario, seed).unwrap().trace, report.trace);
+    }
+}
+
+#[test]
+/// This test checks that a node isolated by a partition catches up once it heals.
+///
+/// Setup:
+///
+/// 4 honest nodes, with node 3 partitioned away for the first 40 steps
+fn simulation_partition_test() {
+    let mut scenario = Scenario::new(4);
+    scenario.faults = vec![Fault::Partition {
+        steps: 0..40,
+        partitions: vec![vec![0, 1, 2], vec![3]],
+    }];
+    let report = assert_upholds_invariants(&scenario, 0);
+    assert!(report.committed_rounds[3].unwrap() >= 4);
+}
+
+#[test]
+/// This test checks that rounds time out while their leader is crashed, and that it recovers
+/// from its storage once it restarts.
+///
+/// Setup:
+///
+/// 4 honest nodes, with node 2 crashed for the first 100 steps, and leading rounds 2 and 3
+fn simulation_leader_crash_test() {
+    let mut scenario = Scenario::new(4);
+    scenario.leaders = btreemap! {2 => 2, 3 => 2};
+    scenario.faults = vec![Fault::Crash {
+        steps: 0..100,
+        node: 2,
+    }];
+    let report = assert_upholds_invariants(&scenario, 0);
+    assert!(report.trace.contains(&TraceEvent::Crash { node: 2 }));
+    assert!(report.trace.contains(&TraceEvent::Restart { node: 2 }));
+    assert!(report
+        .trace
+        .iter()
+        .any(|event| matches!(event, TraceEvent::Timeout { .. })));
+}
+
+#[test]
+/// This test checks that honest nodes commit despite messages being delayed, reordered and
+/// dropped.
+fn simulation_delay_and_drop_test() {
+    let mut scenario = Scenario::new(4);
+    scenario.faults = vec![
+        Fault::Delay {
+            steps: 0..50,
+            from: 0,
+            to: 1,
+            delay: 15,
+        },
+        Fault::Delay {
+            steps: 10..40,
+            from: 3,
+            to: 2,
+            delay: 8,
+        },
+        Fault::Drop {
+            steps: 0..50,
+            from: 2,
+            to: 0,
+        },
+    ];
+    for seed in 0..3 {
+        assert_upholds_invariants(&scenario, seed);
+    }
+}
+
+#[test]
+/// This test checks that an equivocating leader can't make honest nodes commit conflicting
+/// blocks.
+///
+/// Setup:
+///
+/// 4 nodes, and 1 twin of node 0, which leads rounds 1 to 4. The twin is partitioned with node 3
+/// from the other nodes for the first 50 steps, so that each instance gathers votes for its own
+/// proposals.
+fn simulation_equivocating_twin_test() {
+    let mut scenario = Scenario::new(4);
+    scenario.twins = vec![0];
+    scenario.leaders = btreemap! {1 => 0, 2 => 0, 3 => 0, 4 => 0};
+    scenario.faults = vec![Fault::Partition {
+        steps: 0..50,
+        partitions: vec![vec![0, 1, 2], vec![4, 3]],
+    }];
+    for seed in 0..3 {
+        let report = assert_upholds_invariants(&scenario, seed);
+        // The twin proposes too
+        assert!(report
+            .trace
+            .iter()
+            .any(|event| matches!(event, TraceEvent::Deliver { from: 4, to: 3, .. })));
+    }
+}
+
+#[test]
+/// This test checks that the network recovers from a partition leaving no quorum on either side,
+/// as it heals while the nodes are stuck.
+///
+/// Setup:
+///
+/// 4 honest nodes, split in halves for the first 200 steps
+fn simulation_split_partition_test() {
+    let mut scenario = Scenario::new(4);
+    scenario.faults = vec![Fault::Partition {
+        steps: 0..200,
+        partitions: vec![vec![0, 1], vec![2, 3]],
+    }];
+    let report = assert_upholds_invariants(&scenario, 0);
+    assert!(report
+        .trace
+        .iter()
+        .any(|event| matches!(event, TraceEvent::Timeout { .. })));
+}
+
+#[test]
+/// This test checks that twins exceeding the fault threshold make honest nodes commit conflicting
+/// blocks, and that the scenario is shrunk to a minimal one.
+///
+/// Setup:
+///
+/// 4 nodes, and twins of nodes 0 and 1, with node 0 leading rounds 1 to 4. The twins are
+/// partitioned with node 3 from the other nodes for the first 400 steps, so that both sides have
+/// a quorum, along with faults that don't matter: they only affect messages across the partition,
+/// or start once the nodes committed conflicting blocks.
+fn simulation_shrink_test() {
+    let mut scenario = Scenario::new(4);
+    scenario.twins = vec![0, 1];
+    scenario.leaders = btreemap! {1 => 0, 2 => 0, 3 => 0, 4 => 0};
+    scenario.faults = vec![
+        Fault::Delay {
+            steps: 0..400,
+            from: 4,
+            to: 2,
+            delay: 5,
+        },
+        Fault::Drop {
+            steps: 0..400,
+            from: 3,
+            to: 2,
+        },
+        Fault::Crash {
+            steps: 400..420,
+            node: 2,
+        },
+        Fault::Partition {
+            steps: 0..400,
+            partitions: vec![vec![0, 1, 2], vec![4, 5, 3]],
+        },
+    ];
+
+    let reproduction = simulate_and_shrink(&scenario, 0).unwrap_err();
+    assert!(matches!(
+        reproduction.violation,
+        Violation::ConflictingCommits { .. } | Violation::ForkedCommit { .. }
+    ));
+    assert!(simulate(&reproduction.scenario, 0).is_err());
+
+    // The faults that don't matter are dropped, and no simpler scenario violates safety
+    assert!(reproduction
+        .scenario
+        .faults
+        .iter()
+        .all(|fault| matches!(fault, Fault::Partition { .. })));
+    for candidate in reproduction.scenario.shrink_candidates() {
+        if let Err(violation) = simulate(&candidate, 0) {
+            assert!(matches!(violation, Violation::NoProgress { .. }));
+        }
+    }
+}
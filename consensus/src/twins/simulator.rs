@@ -0,0 +1,976 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A deterministic simulation of a network of validators running the round manager, scripted by
+//! a `Scenario` of partitions, message drops and delays, crashes and equivocating twins.
+//!
+//! Instead of a network and timers, a single scheduler drives all nodes: at every step, it picks
+//! one of the messages in flight with a seeded rng, i.e., in any order, and hands it to the round
+//! manager of its recipient. Once the network is quiet, it expires the round timers of all live
+//! nodes. Block retrievals are served inline by the first reachable peer holding the requested
+//! block, whichever peer `BlockRetriever` picked, as it picks the peers to retry with at random.
+//! Time is simulated, a step being a millisecond. Given a seed, the run is thus replayed exactly.
+//! Faults are scheduled in steps too, so they end even while no node makes progress.
+//!
+//! Commits are checked for safety after every step: as committing a block requires a quorum, this
+//! holds for the commits of twins too. Once the simulation is over, live honest nodes are checked
+//! to have committed blocks past those committed by the end of the last fault. A scenario
+//! violating either invariant can then be shrunk to a minimal reproduction.
+
+use crate::{
+    block_storage::{BlockReader, BlockStore},
+    experimental::buffer_manager::OrderedBlocks,
+    liveness::{
+        proposal_generator::{ChainHealthBackoffConfig, ProposalGenerator},
+        proposer_election::ProposerElection,
+        rotating_proposer_election::RotatingProposer,
+        round_state::{ExponentialTimeInterval, RoundState},
+    },
+    metrics_safety_rules::MetricsSafetyRules,
+    network::{IncomingBlockRetrievalRequest, NetworkSender},
+    network_interface::{ConsensusMsg, ConsensusNetworkClient, DIRECT_SEND, RPC},
+    payload_manager::PayloadManager,
+    persistent_liveness_storage::RecoveryData,
+    round_manager::RoundManager,
+    test_utils::{consensus_runtime, MockPayloadManager, MockStateComputer, MockStorage},
+    twins::scenario::{Fault, NodeId, Scenario},
+    util::{mock_time_service::SimulatedTimeService, time_service::TimeService},
+};
+use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
+use aptos_config::{
+    config::ConsensusConfig,
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_network::{
+    application::{interface::NetworkClient, storage::PeersAndMetadata},
+    peer_manager::{ConnectionRequestSender, PeerManagerRequest, PeerManagerRequestSender},
+    protocols::{
+        network,
+        network::{Event, NewNetworkSender, SerializedRequest},
+        rpc::error::RpcError,
+        wire::handshake::v1::ProtocolIdSet,
+    },
+    transport::ConnectionMetadata,
+    ProtocolId,
+};
+use aptos_safety_rules::{PersistentSafetyStorage, SafetyRulesManager};
+use aptos_secure_storage::Storage;
+use aptos_types::{
+    epoch_state::EpochState,
+    ledger_info::LedgerInfo,
+    on_chain_config::{OnChainConsensusConfig, ValidatorSet},
+    transaction::SignedTransaction,
+    validator_signer::ValidatorSigner,
+    validator_verifier::random_validator_verifier,
+    waypoint::Waypoint,
+    PeerId,
+};
+use futures::{channel::mpsc, executor::block_on, pin_mut, select_biased, FutureExt, StreamExt};
+use maplit::hashmap;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem::{self, discriminant},
+    sync::Arc,
+    time::Duration,
+};
+
+/// An event of a simulation, in the order of the trace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TraceEvent {
+    Deliver {
+        from: NodeId,
+        to: NodeId,
+        round: Round,
+        message: String,
+    },
+    Timeout {
+        node: NodeId,
+        round: Round,
+    },
+    Crash {
+        node: NodeId,
+    },
+    Restart {
+        node: NodeId,
+    },
+}
+
+/// An invariant violated by a simulation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Violation {
+    /// Two nodes committed different blocks in the same round.
+    ConflictingCommits {
+        round: Round,
+        node: NodeId,
+        block: HashValue,
+        other_node: NodeId,
+        other_block: HashValue,
+    },
+    /// A committed block doesn't extend the block committed before it.
+    ForkedCommit {
+        round: Round,
+        block: HashValue,
+        parent: HashValue,
+        previous_block: HashValue,
+    },
+    /// A live honest node didn't commit any block past those committed by the global
+    /// stabilization time.
+    NoProgress {
+        node: NodeId,
+        committed_round: Round,
+        gst_committed_round: Round,
+    },
+}
+
+impl Violation {
+    fn is_same_kind(&self, other: &Violation) -> bool {
+        discriminant(self) == discriminant(other)
+    }
+}
+
+/// The outcome of a simulation upholding all invariants.
+#[derive(Debug)]
+pub struct SimulationReport {
+    pub trace: Vec<TraceEvent>,
+    /// The highest round each node committed, or None if it is crashed.
+    pub committed_rounds: Vec<Option<Round>>,
+}
+
+/// A minimal scenario violating an invariant, and the seed to replay it with.
+#[derive(Debug)]
+pub struct Reproduction {
+    pub seed: u64,
+    pub scenario: Scenario,
+    pub violation: Violation,
+}
+
+/// Runs the scenario, with the seed deciding the order messages are delivered in.
+pub fn simulate(scenario: &Scenario, seed: u64) -> Result<SimulationReport, Violation> {
+    // Nodes are driven from this thread, but the round manager may spawn tasks, e.g., to resend a
+    // proposal to itself under back pressure
+    let runtime = consensus_runtime();
+    let _entered = runtime.enter();
+    Simulation::new(scenario, seed).run()
+}
+
+/// Runs the scenario, shrinking it to a minimal reproduction if it violates an invariant.
+pub fn simulate_and_shrink(
+    scenario: &Scenario,
+    seed: u64,
+) -> Result<SimulationReport, Reproduction> {
+    simulate(scenario, seed).map_err(|violation| shrink(scenario.clone(), seed, violation))
+}
+
+/// Shrinks a scenario for as long as one of its simpler versions violates an invariant of the
+/// same kind, with the same seed.
+pub fn shrink(scenario: Scenario, seed: u64, violation: Violation) -> Reproduction {
+    let mut reproduction = Reproduction {
+        seed,
+        scenario,
+        violation,
+    };
+    'shrinking: loop {
+        for candidate in reproduction.scenario.shrink_candidates() {
+            if let Err(violation) = simulate(&candidate, seed) {
+                if violation.is_same_kind(&reproduction.violation) {
+                    reproduction = Reproduction {
+                        seed,
+                        scenario: candidate,
+                        violation,
+                    };
+                    continue 'shrinking;
+                }
+            }
+        }
+        return reproduction;
+    }
+}
+
+/// Returns the round a message belongs to, if it carries one.
+fn message_round(message: &ConsensusMsg) -> Option<Round> {
+    match message {
+        ConsensusMsg::ProposalMsg(proposal) => Some(proposal.proposal().round()),
+        ConsensusMsg::VoteMsg(vote) => Some(vote.vote().vote_data().proposed().round()),
+        _ => None,
+    }
+}
+
+/// Elects the leaders set by the scenario, and rotates over the validators otherwise.
+struct ScheduledProposer {
+    leaders: HashMap<Round, Author>,
+    rotation: RotatingProposer,
+}
+
+impl ProposerElection for ScheduledProposer {
+    fn get_valid_proposer(&self, round: Round) -> Author {
+        match self.leaders.get(&round) {
+            Some(leader) => *leader,
+            None => self.rotation.get_valid_proposer(round),
+        }
+    }
+}
+
+fn proposer_election(
+    scenario: &Scenario,
+    authors: &[Author],
+) -> Box<dyn ProposerElection + Send + Sync> {
+    Box::new(ScheduledProposer {
+        leaders: scenario
+            .leaders
+            .iter()
+            .map(|(round, node)| (*round, authors[*node]))
+            .collect(),
+        rotation: RotatingProposer::new(authors.to_vec(), 1),
+    })
+}
+
+enum Input {
+    Message(Author, ConsensusMsg),
+    Timeout,
+}
+
+/// What a node sent and ordered while processing an input.
+#[derive(Default)]
+struct Output {
+    messages: Vec<(Author, ConsensusMsg)>,
+    self_messages: Vec<ConsensusMsg>,
+    /// The round, id and parent id of the ordered blocks
+    ordered: Vec<(Round, HashValue, HashValue)>,
+}
+
+/// The running part of a node, which is lost when it crashes.
+struct SimNode {
+    round_manager: RoundManager,
+    block_store: Arc<BlockStore>,
+    network_reqs_rx: aptos_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
+    self_receiver: aptos_channels::Receiver<Event<ConsensusMsg>>,
+    ordered_blocks_events: mpsc::UnboundedReceiver<OrderedBlocks>,
+    mock_state_computer: Arc<MockStateComputer>,
+    _state_sync_receiver: mpsc::UnboundedReceiver<Vec<SignedTransaction>>,
+}
+
+impl SimNode {
+    fn start(
+        signer: &ValidatorSigner,
+        proposer_election: Box<dyn ProposerElection + Send + Sync>,
+        peers_and_metadata: Arc<PeersAndMetadata>,
+        time_service: Arc<dyn TimeService>,
+        storage: Arc<MockStorage>,
+        initial_data: RecoveryData,
+        safety_rules_manager: &SafetyRulesManager,
+    ) -> Self {
+        let epoch_state = EpochState {
+            epoch: 1,
+            verifier: storage.get_validator_set().into(),
+        };
+        let validators = epoch_state.verifier.clone();
+        // Outbound messages are queued until the scheduler collects them after each step
+        let (network_reqs_tx, network_reqs_rx) = aptos_channel::new(QueueStyle::FIFO, 1_024, None);
+        let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+        let network_sender = network::NetworkSender::new(
+            PeerManagerRequestSender::new(network_reqs_tx),
+            ConnectionRequestSender::new(connection_reqs_tx),
+        );
+        let network_client = NetworkClient::new(
+            DIRECT_SEND.into(),
+            RPC.into(),
+            hashmap! {NetworkId::Validator => network_sender},
+            peers_and_metadata,
+        );
+        let consensus_network_client = ConsensusNetworkClient::new(network_client);
+        let author = signer.author();
+        let (self_sender, self_receiver) = aptos_channels::new_test(1_024);
+        let network = NetworkSender::new(author, consensus_network_client, self_sender, validators);
+
+        let last_vote_sent = initial_data.last_vote();
+        let (ordered_blocks_tx, ordered_blocks_events) = mpsc::unbounded::<OrderedBlocks>();
+        let (state_sync_client, _state_sync_receiver) = mpsc::unbounded();
+        let mock_state_computer = Arc::new(MockStateComputer::new(
+            state_sync_client,
+            ordered_blocks_tx,
+            Arc::clone(&storage),
+        ));
+        let block_store = Arc::new(BlockStore::new(
+            storage.clone(),
+            initial_data,
+            mock_state_computer.clone(),
+            10, // max pruned blocks in mem
+            time_service.clone(),
+            10,
+            Arc::from(PayloadManager::DirectMempool),
+        ));
+        let proposal_generator = ProposalGenerator::new(
+            author,
+            block_store.clone(),
+            Arc::new(MockPayloadManager::new(None)),
+            time_service.clone(),
+            10,
+            1000,
+            10,
+            ChainHealthBackoffConfig::new_no_backoff(),
+            false,
+        );
+
+        // The timeouts the round state schedules never fire, as the simulated time service only
+        // runs tasks within its time limit, which stays at zero: the scheduler expires them.
+        let (round_timeout_sender, _) = aptos_channels::new_test(1_024);
+        let round_state = RoundState::new(
+            Box::new(ExponentialTimeInterval::fixed(Duration::from_secs(60))),
+            time_service,
+            round_timeout_sender,
+        );
+        let mut safety_rules =
+            MetricsSafetyRules::new(safety_rules_manager.client(), storage.clone());
+        safety_rules.perform_initialize().unwrap();
+
+        let (round_manager_tx, _) = aptos_channel::new(QueueStyle::LIFO, 1, None);
+        let mut round_manager = RoundManager::new(
+            epoch_state,
+            Arc::clone(&block_store),
+            round_state,
+            proposer_election,
+            proposal_generator,
+            Arc::new(Mutex::new(safety_rules)),
+            network,
+            storage,
+            OnChainConsensusConfig::default(),
+            round_manager_tx,
+            ConsensusConfig::default(),
+        );
+        block_on(round_manager.init(last_vote_sent));
+        Self {
+            round_manager,
+            block_store,
+            network_reqs_rx,
+            self_receiver,
+            ordered_blocks_events,
+            mock_state_computer,
+            _state_sync_receiver,
+        }
+    }
+
+    fn current_round(&self) -> Round {
+        self.round_manager.round_state().current_round()
+    }
+
+    /// Processes the input, serving the block retrievals it issues from the block stores of
+    /// `peers`, and returns what the node sent and ordered meanwhile.
+    fn process(&mut self, input: Input, peers: &[(Author, Arc<BlockStore>)]) -> Output {
+        let mut output = Output::default();
+        let round_manager = &mut self.round_manager;
+        let network_reqs_rx = &mut self.network_reqs_rx;
+        block_on(async {
+            let process = Self::process_input(round_manager, input).fuse();
+            pin_mut!(process);
+            loop {
+                select_biased! {
+                    () = process => break,
+                    request = network_reqs_rx.select_next_some() => {
+                        Self::handle_request(request, peers, &mut output).await
+                    },
+                }
+            }
+        });
+        self.collect_output(output)
+    }
+
+    async fn process_input(round_manager: &mut RoundManager, input: Input) {
+        // Errors are part of a simulation, e.g., for stale messages or timeouts, and only the
+        // invariants checked on the outcome matter
+        let _ = match input {
+            Input::Message(_, ConsensusMsg::ProposalMsg(proposal)) => {
+                round_manager.process_proposal_msg(*proposal).await
+            },
+            Input::Message(_, ConsensusMsg::VoteMsg(vote)) => {
+                round_manager.process_vote_msg(*vote).await
+            },
+            Input::Message(author, ConsensusMsg::SyncInfo(sync_info)) => {
+                round_manager
+                    .process_sync_info_msg(*sync_info, author)
+                    .await
+            },
+            // The execution pipeline and the quorum store aren't simulated
+            Input::Message(..) => Ok(()),
+            Input::Timeout => {
+                let round = round_manager.round_state().current_round();
+                round_manager.process_local_timeout(round).await
+            },
+        };
+    }
+
+    async fn handle_request(
+        request: PeerManagerRequest,
+        peers: &[(Author, Arc<BlockStore>)],
+        output: &mut Output,
+    ) {
+        match request {
+            PeerManagerRequest::SendDirectSend(peer, message) => {
+                output
+                    .messages
+                    .push((peer, message.to_message::<ConsensusMsg>().unwrap()));
+            },
+            PeerManagerRequest::SendRpc(_, rpc) => {
+                // The peer the request was sent to is ignored, as `BlockRetriever` picks it at
+                // random once its preferred peer fails, which would make the run depend on it
+                match rpc.to_message::<ConsensusMsg>() {
+                    Ok(ConsensusMsg::BlockRetrievalRequest(request)) => {
+                        let block_store = peers
+                            .iter()
+                            .map(|(_, block_store)| block_store)
+                            .find(|block_store| block_store.block_exists(request.block_id()));
+                        match block_store {
+                            Some(block_store) => {
+                                let _ = block_store
+                                    .process_block_retrieval(IncomingBlockRetrievalRequest {
+                                        req: *request,
+                                        protocol: rpc.protocol_id,
+                                        response_sender: rpc.res_tx,
+                                    })
+                                    .await;
+                            },
+                            // No peer in reach holds the block
+                            None => {
+                                let _ = rpc.res_tx.send(Err(RpcError::TimedOut));
+                            },
+                        }
+                    },
+                    _ => {
+                        let _ = rpc.res_tx.send(Err(RpcError::TimedOut));
+                    },
+                }
+            },
+        }
+    }
+
+    /// Collects the messages sent and blocks ordered since the last poll, acknowledging the
+    /// ordered blocks as the buffer manager would once they are executed.
+    fn collect_output(&mut self, mut output: Output) -> Output {
+        while let Some(Some(request)) = self.network_reqs_rx.next().now_or_never() {
+            block_on(Self::handle_request(request, &[], &mut output));
+        }
+        while let Some(Some(Event::Message(_, message))) = self.self_receiver.next().now_or_never()
+        {
+            output.self_messages.push(message);
+        }
+        while let Ok(Some(ordered_blocks)) = self.ordered_blocks_events.try_next() {
+            output.ordered.extend(
+                ordered_blocks
+                    .ordered_blocks
+                    .iter()
+                    .map(|block| (block.round(), block.id(), block.parent_id())),
+            );
+            block_on(self.mock_state_computer.commit_to_storage(ordered_blocks))
+                .expect("Failed to commit ordered blocks");
+        }
+        output
+    }
+}
+
+/// A node of the simulation, which survives crashes.
+struct Instance {
+    signer: ValidatorSigner,
+    storage: Arc<MockStorage>,
+    safety_rules_manager: SafetyRulesManager,
+    /// None while crashed
+    node: Option<SimNode>,
+}
+
+struct Envelope {
+    from: NodeId,
+    to: NodeId,
+    round: Round,
+    message: ConsensusMsg,
+    /// The step from which on the message can be delivered
+    release_step: u64,
+}
+
+struct Commit {
+    node: NodeId,
+    block: HashValue,
+    parent: HashValue,
+}
+
+/// The state of a simulation once the network got stable.
+struct Stabilization {
+    /// The round of the simulation
+    round: Round,
+    /// The highest round committed by any node
+    committed_round: Round,
+}
+
+struct Simulation<'a> {
+    scenario: &'a Scenario,
+    rng: StdRng,
+    authors: Vec<Author>,
+    peers_and_metadata: Arc<PeersAndMetadata>,
+    time_service: Arc<dyn TimeService>,
+    instances: Vec<Instance>,
+    /// Messages sent but not delivered yet, in the order they were sent
+    in_flight: Vec<Envelope>,
+    step: u64,
+    trace: Vec<TraceEvent>,
+    /// The blocks committed by any node, by round
+    commits: BTreeMap<Round, Commit>,
+    /// None until the global stabilization time
+    stabilization: Option<Stabilization>,
+}
+
+impl<'a> Simulation<'a> {
+    fn new(scenario: &'a Scenario, seed: u64) -> Self {
+        assert!(scenario.twins.iter().all(|node| *node < scenario.num_nodes));
+        assert!(scenario
+            .leaders
+            .values()
+            .all(|node| *node < scenario.num_nodes));
+        // The scheduler waits for at most `timeout_steps` at once, so it doesn't skip past GST
+        assert!(
+            scenario.gst() + scenario.timeout_steps <= scenario.max_steps,
+            "The faults must end before the simulation does"
+        );
+
+        let (signers, validators) = random_validator_verifier(scenario.num_nodes, None, false);
+        let validator_set: ValidatorSet = (&validators).into();
+        let waypoint =
+            Waypoint::new_epoch_boundary(&LedgerInfo::mock_genesis(Some(validator_set.clone())))
+                .unwrap();
+        let authors: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+
+        let peers_and_metadata = PeersAndMetadata::new(&[NetworkId::Validator]);
+        for author in &authors {
+            let mut conn_meta = ConnectionMetadata::mock(*author);
+            conn_meta.application_protocols = ProtocolIdSet::from_iter([
+                ProtocolId::ConsensusDirectSendJson,
+                ProtocolId::ConsensusDirectSendBcs,
+                ProtocolId::ConsensusRpcBcs,
+            ]);
+            let peer_network_id = PeerNetworkId::new(NetworkId::Validator, *author);
+            peers_and_metadata
+                .insert_connection_metadata(peer_network_id, conn_meta)
+                .unwrap();
+        }
+
+        let mut simulation = Self {
+            scenario,
+            rng: StdRng::seed_from_u64(seed),
+            authors,
+            peers_and_metadata,
+            time_service: Arc::new(SimulatedTimeService::new()),
+            instances: vec![],
+            in_flight: vec![],
+            step: 0,
+            trace: vec![],
+            commits: BTreeMap::new(),
+            stabilization: None,
+        };
+        // Proposals need a timestamp past the one of genesis
+        simulation.advance(1);
+
+        for node in 0..scenario.num_instances() {
+            let signer = signers[scenario.validator(node)].clone();
+            let (initial_data, storage) = MockStorage::start_for_testing(validator_set.clone());
+            let safety_storage = PersistentSafetyStorage::initialize(
+                Storage::from(aptos_secure_storage::InMemoryStorage::new()),
+                signer.author(),
+                signer.private_key().clone(),
+                waypoint,
+                true,
+            );
+            let safety_rules_manager = SafetyRulesManager::new_local(safety_storage);
+            let sim_node = SimNode::start(
+                &signer,
+                proposer_election(scenario, &simulation.authors),
+                simulation.peers_and_metadata.clone(),
+                simulation.time_service.clone(),
+                storage.clone(),
+                initial_data,
+                &safety_rules_manager,
+            );
+            simulation.instances.push(Instance {
+                signer,
+                storage,
+                safety_rules_manager,
+                node: Some(sim_node),
+            });
+        }
+
+        let root = simulation.live_node(0).block_store.ordered_root();
+        simulation.commits.insert(root.round(), Commit {
+            node: 0,
+            block: root.id(),
+            parent: root.parent_id(),
+        });
+        // Only route the messages sent while starting once all recipients exist
+        for node in 0..simulation.instances.len() {
+            let output = simulation.live_node(node).collect_output(Output::default());
+            simulation
+                .handle_output(node, output)
+                .expect("Nothing is committed at genesis");
+        }
+        simulation
+    }
+
+    fn run(mut self) -> Result<SimulationReport, Violation> {
+        while self.step < self.scenario.max_steps {
+            if self.stabilization.is_none() && self.step >= self.scenario.gst() {
+                self.stabilization = Some(Stabilization {
+                    round: self.round(),
+                    committed_round: self.highest_committed_round(),
+                });
+            }
+            if self.is_done() {
+                break;
+            }
+            self.apply_faults()?;
+            self.next_step()?;
+        }
+        self.check_chain()?;
+        if self.scenario.check_liveness {
+            self.check_liveness()?;
+        }
+
+        let committed_rounds = self
+            .instances
+            .iter()
+            .map(|instance| {
+                instance
+                    .node
+                    .as_ref()
+                    .map(|node| node.block_store.ordered_root().round())
+            })
+            .collect();
+        Ok(SimulationReport {
+            trace: self.trace,
+            committed_rounds,
+        })
+    }
+
+    fn live_node(&mut self, node: NodeId) -> &mut SimNode {
+        self.instances[node]
+            .node
+            .as_mut()
+            .expect("The node is crashed")
+    }
+
+    fn current_round(&self, node: NodeId) -> Option<Round> {
+        self.instances[node]
+            .node
+            .as_ref()
+            .map(|sim_node| sim_node.current_round())
+    }
+
+    /// Returns the round of the simulation, i.e., the highest round of any live honest node.
+    fn round(&self) -> Round {
+        (0..self.instances.len())
+            .filter(|node| self.scenario.is_honest(*node))
+            .filter_map(|node| self.current_round(node))
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn highest_committed_round(&self) -> Round {
+        self.commits.keys().next_back().copied().unwrap_or_default()
+    }
+
+    fn is_done(&self) -> bool {
+        let stabilization = match &self.stabilization {
+            Some(stabilization) => stabilization,
+            None => return false,
+        };
+        (0..self.instances.len())
+            .filter(|node| self.scenario.is_honest(*node))
+            .filter_map(|node| self.current_round(node))
+            .all(|round| round >= stabilization.round + self.scenario.rounds)
+    }
+
+    /// Advances the step, and the simulated time with it.
+    fn advance(&mut self, steps: u64) {
+        self.step += steps;
+        block_on(self.time_service.sleep(Duration::from_millis(steps)));
+    }
+
+    /// Crashes and restarts nodes as the crash faults of the scenario start and end, and drops the
+    /// messages in flight between nodes which got disconnected.
+    fn apply_faults(&mut self) -> Result<(), Violation> {
+        let in_flight = mem::take(&mut self.in_flight);
+        self.in_flight = in_flight
+            .into_iter()
+            .filter(|envelope| self.is_connected(envelope.from, envelope.to))
+            .collect();
+
+        for node in 0..self.instances.len() {
+            let crashed = self.scenario.faults.iter().any(|fault| {
+                matches!(fault, Fault::Crash { steps, node: crashed_node }
+                    if *crashed_node == node && steps.contains(&self.step))
+            });
+            match (crashed, self.instances[node].node.is_some()) {
+                (true, true) => {
+                    self.instances[node].node = None;
+                    self.in_flight
+                        .retain(|envelope| envelope.from != node && envelope.to != node);
+                    self.trace.push(TraceEvent::Crash { node });
+                },
+                (false, false) => {
+                    let instance = &mut self.instances[node];
+                    let recovery_data = instance
+                        .storage
+                        .try_start()
+                        .unwrap_or_else(|e| panic!("fail to restart due to: {}", e));
+                    instance.node = Some(SimNode::start(
+                        &instance.signer,
+                        proposer_election(self.scenario, &self.authors),
+                        self.peers_and_metadata.clone(),
+                        self.time_service.clone(),
+                        instance.storage.clone(),
+                        recovery_data,
+                        &instance.safety_rules_manager,
+                    ));
+                    self.trace.push(TraceEvent::Restart { node });
+                    let output = self.live_node(node).collect_output(Output::default());
+                    self.handle_output(node, output)?;
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    /// Delivers one of the messages due, picked at random. If there is none, waits for the next
+    /// delayed message, unless the round timers expire first.
+    fn next_step(&mut self) -> Result<(), Violation> {
+        let due: Vec<_> = self
+            .in_flight
+            .iter()
+            .enumerate()
+            .filter(|(_, envelope)| envelope.release_step <= self.step)
+            .map(|(index, _)| index)
+            .collect();
+        if due.is_empty() {
+            let next_release_step = self
+                .in_flight
+                .iter()
+                .map(|envelope| envelope.release_step)
+                .min();
+            match next_release_step {
+                Some(release_step) if release_step <= self.step + self.scenario.timeout_steps => {
+                    self.advance(release_step - self.step);
+                },
+                _ => {
+                    self.advance(self.scenario.timeout_steps);
+                    self.expire_round_timers()?;
+                },
+            }
+            return Ok(());
+        }
+
+        let index = due[self.rng.gen_range(0, due.len())];
+        let envelope = self.in_flight.remove(index);
+        self.advance(1);
+        self.deliver(envelope)
+    }
+
+    fn expire_round_timers(&mut self) -> Result<(), Violation> {
+        for node in 0..self.instances.len() {
+            if let Some(round) = self.current_round(node) {
+                self.trace.push(TraceEvent::Timeout { node, round });
+                self.process(node, Input::Timeout)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn deliver(&mut self, envelope: Envelope) -> Result<(), Violation> {
+        let Envelope {
+            from,
+            to,
+            round,
+            message,
+            ..
+        } = envelope;
+        self.trace.push(TraceEvent::Deliver {
+            from,
+            to,
+            round,
+            message: message.name().to_string(),
+        });
+        let author = self.instances[from].signer.author();
+        self.process(to, Input::Message(author, message))
+    }
+
+    fn process(&mut self, node: NodeId, input: Input) -> Result<(), Violation> {
+        let peers = self.reachable_block_stores(node);
+        let output = self.live_node(node).process(input, &peers);
+        self.handle_output(node, output)
+    }
+
+    /// Returns the block stores of the live nodes the node can currently reach, to serve the
+    /// block retrievals it issues.
+    fn reachable_block_stores(&self, node: NodeId) -> Vec<(Author, Arc<BlockStore>)> {
+        self.instances
+            .iter()
+            .enumerate()
+            .filter(|(peer, _)| *peer != node && self.is_connected(node, *peer))
+            .filter_map(|(_, instance)| {
+                instance
+                    .node
+                    .as_ref()
+                    .map(|sim_node| (instance.signer.author(), sim_node.block_store.clone()))
+            })
+            .collect()
+    }
+
+    fn handle_output(&mut self, node: NodeId, output: Output) -> Result<(), Violation> {
+        let Output {
+            messages,
+            self_messages,
+            ordered,
+        } = output;
+        let sender_round = self.current_round(node).unwrap_or_default();
+        for (author, message) in messages {
+            // Messages to a validator reach all its instances
+            for to in 0..self.instances.len() {
+                if self.instances[to].signer.author() == author && self.instances[to].node.is_some()
+                {
+                    self.send(node, to, sender_round, message.clone());
+                }
+            }
+        }
+        for message in self_messages {
+            self.send(node, node, sender_round, message);
+        }
+        for (round, block, parent) in ordered {
+            self.record_commit(node, round, block, parent)?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, from: NodeId, to: NodeId, sender_round: Round, message: ConsensusMsg) {
+        if !self.is_connected(from, to) {
+            return;
+        }
+        let round = message_round(&message).unwrap_or(sender_round);
+        let release_step = self.step + self.delay(from, to);
+        self.in_flight.push(Envelope {
+            from,
+            to,
+            round,
+            message,
+            release_step,
+        });
+    }
+
+    /// Returns whether messages currently make it from one node to the other.
+    fn is_connected(&self, from: NodeId, to: NodeId) -> bool {
+        from == to
+            || self.scenario.faults.iter().all(|fault| match fault {
+                Fault::Partition { steps, partitions } if steps.contains(&self.step) => {
+                    let partition_of = |node| {
+                        partitions
+                            .iter()
+                            .position(|partition| partition.contains(&node))
+                    };
+                    match (partition_of(from), partition_of(to)) {
+                        (Some(partition), Some(other_partition)) => partition == other_partition,
+                        _ => true,
+                    }
+                },
+                Fault::Drop {
+                    steps,
+                    from: dropped_from,
+                    to: dropped_to,
+                } if steps.contains(&self.step) => (*dropped_from, *dropped_to) != (from, to),
+                _ => true,
+            })
+    }
+
+    /// Returns the number of steps messages currently sent from one node to the other are held
+    /// back for.
+    fn delay(&self, from: NodeId, to: NodeId) -> u64 {
+        self.scenario
+            .faults
+            .iter()
+            .map(|fault| match fault {
+                Fault::Delay {
+                    steps,
+                    from: delayed_from,
+                    to: delayed_to,
+                    delay,
+                } if steps.contains(&self.step) && (*delayed_from, *delayed_to) == (from, to) => {
+                    *delay
+                },
+                _ => 0,
+            })
+            .sum()
+    }
+
+    fn record_commit(
+        &mut self,
+        node: NodeId,
+        round: Round,
+        block: HashValue,
+        parent: HashValue,
+    ) -> Result<(), Violation> {
+        match self.commits.get(&round) {
+            Some(commit) if commit.block != block => Err(Violation::ConflictingCommits {
+                round,
+                node,
+                block,
+                other_node: commit.node,
+                other_block: commit.block,
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.commits.insert(round, Commit {
+                    node,
+                    block,
+                    parent,
+                });
+                Ok(())
+            },
+        }
+    }
+
+    /// Checks that the committed blocks form a single chain. Nodes order every block on the path
+    /// to the blocks they commit, and those catching up by state sync only do so from commits of
+    /// other nodes, so there are no gaps.
+    fn check_chain(&self) -> Result<(), Violation> {
+        let commits = self.commits.iter().zip(self.commits.iter().skip(1));
+        for ((_, previous), (round, commit)) in commits {
+            if commit.parent != previous.block {
+                return Err(Violation::ForkedCommit {
+                    round: *round,
+                    block: commit.block,
+                    parent: commit.parent,
+                    previous_block: previous.block,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn check_liveness(&self) -> Result<(), Violation> {
+        let gst_committed_round = self
+            .stabilization
+            .as_ref()
+            .expect("The simulation runs past the global stabilization time")
+            .committed_round;
+        for (node, instance) in self.instances.iter().enumerate() {
+            let sim_node = match &instance.node {
+                Some(sim_node) if self.scenario.is_honest(node) => sim_node,
+                _ => continue,
+            };
+            let committed_round = sim_node.block_store.ordered_root().round();
+            if committed_round <= gst_committed_round {
+                return Err(Violation::NoProgress {
+                    node,
+                    committed_round,
+                    gst_committed_round,
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an API for ECDSA signatures over the secp256r1 (a.k.a. P-256 or
+//! prime256v1) curve with SHA-256, as used by WebAuthn authenticators such as device passkeys.
+//!
+//! Public keys are SEC1 encoded and uncompressed. Signatures are the 64-byte concatenation of
+//! their `r` and `s` components, and verification rejects signatures whose `s` is in the upper
+//! half of the curve order, so that a third party cannot derive another valid signature from a
+//! given one. Signing always produces such "low-s" signatures.
+//!
+//! # Examples
+//!
+//! ```
+//! use aptos_crypto_derive::{CryptoHasher, BCSCryptoHash};
+//! use aptos_crypto::{
+//!     secp256r1_ecdsa::*,
+//!     traits::{Signature, SigningKey, Uniform},
+//!     test_utils::KeyPair
+//! };
+//! use rand_core::OsRng;
+//! use serde::{Serialize, Deserialize};
+//!
+//! #[derive(Serialize, Deserialize, CryptoHasher, BCSCryptoHash)]
+//! pub struct TestCryptoDocTest(String);
+//! let message = TestCryptoDocTest("Test message".to_string());
+//!
+//! let mut rng = OsRng;
+//! let kp = KeyPair::<Secp256r1EcdsaPrivateKey, Secp256r1EcdsaPublicKey>::generate(&mut rng);
+//!
+//! let signature = kp.private_key.sign(&message).unwrap();
+//! assert!(signature.verify(&message, &kp.public_key).is_ok());
+//! ```
+
+/// The length of the Secp256r1EcdsaPrivateKey
+pub const SECP256R1_ECDSA_PRIVATE_KEY_LENGTH: usize = 32;
+/// The length of the Secp256r1EcdsaPublicKey, uncompressed
+pub const SECP256R1_ECDSA_PUBLIC_KEY_LENGTH: usize = 65;
+/// The length of the Secp256r1EcdsaSignature
+pub const SECP256R1_ECDSA_SIGNATURE_LENGTH: usize = 64;
+
+pub mod secp256r1_ecdsa_keys;
+pub mod secp256r1_ecdsa_sigs;
+
+#[cfg(any(test, feature = "fuzzing"))]
+pub use secp256r1_ecdsa_keys::keypair_strategy;
+pub use secp256r1_ecdsa_keys::{
+    Secp256r1EcdsaPrivateKey, Secp256r1EcdsaPrivateKey as PrivateKey, Secp256r1EcdsaPublicKey,
+    Secp256r1EcdsaPublicKey as PublicKey,
+};
+pub use secp256r1_ecdsa_sigs::{Secp256r1EcdsaSignature, Secp256r1EcdsaSignature as Signature};
@@ -0,0 +1,161 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file implements traits for secp256r1 ECDSA signatures.
+
+use crate::{
+    hash::CryptoHash,
+    secp256r1_ecdsa::{
+        Secp256r1EcdsaPrivateKey, Secp256r1EcdsaPublicKey, SECP256R1_ECDSA_SIGNATURE_LENGTH,
+    },
+    traits::*,
+};
+use anyhow::{anyhow, Result};
+use aptos_crypto_derive::{DeserializeKey, SerializeKey};
+use core::convert::TryFrom;
+use p256::ecdsa::signature::Verifier;
+use serde::Serialize;
+use std::fmt;
+
+/// A secp256r1 ECDSA signature
+#[derive(DeserializeKey, Clone, SerializeKey)]
+pub struct Secp256r1EcdsaSignature(pub(crate) p256::ecdsa::Signature);
+
+impl Secp256r1EcdsaSignature {
+    /// The length of the Secp256r1EcdsaSignature
+    pub const LENGTH: usize = SECP256R1_ECDSA_SIGNATURE_LENGTH;
+
+    /// Serialize a Secp256r1EcdsaSignature, as the concatenation of `r` and `s`.
+    pub fn to_bytes(&self) -> [u8; SECP256R1_ECDSA_SIGNATURE_LENGTH] {
+        let mut bytes = [0u8; SECP256R1_ECDSA_SIGNATURE_LENGTH];
+        bytes.copy_from_slice(self.0.as_ref());
+        bytes
+    }
+
+    /// Deserialize a Secp256r1EcdsaSignature without any validation checks (malleability)
+    /// apart from expected signature size and non-zero components.
+    pub(crate) fn from_bytes_unchecked(
+        bytes: &[u8],
+    ) -> std::result::Result<Secp256r1EcdsaSignature, CryptoMaterialError> {
+        if bytes.len() != SECP256R1_ECDSA_SIGNATURE_LENGTH {
+            return Err(CryptoMaterialError::WrongLengthError);
+        }
+        p256::ecdsa::Signature::try_from(bytes)
+            .map(Secp256r1EcdsaSignature)
+            .map_err(|_| CryptoMaterialError::DeserializationError)
+    }
+
+    /// Check for correct size and signature malleability issues.
+    ///
+    /// For any valid ECDSA signature (r, s), (r, n - s), where n is the order of the curve, is a
+    /// valid signature of the same message under the same key. Only the signature whose s is in
+    /// the lower half of the order is accepted, so that a third party cannot produce a distinct
+    /// yet valid signature from a given one. Signers whose signatures are not normalized, e.g.,
+    /// WebAuthn authenticators, must be followed by a normalization step.
+    pub fn check_s_malleability(bytes: &[u8]) -> std::result::Result<(), CryptoMaterialError> {
+        let signature = Secp256r1EcdsaSignature::from_bytes_unchecked(bytes)?;
+        if signature.0.normalize_s().is_some() {
+            return Err(CryptoMaterialError::CanonicalRepresentationError);
+        }
+        Ok(())
+    }
+
+    /// Returns the low-s form of a signature, which is the only one accepted, see
+    /// [Secp256r1EcdsaSignature::check_s_malleability][Secp256r1EcdsaSignature::check_s_malleability].
+    pub fn normalize(bytes: &[u8]) -> std::result::Result<Self, CryptoMaterialError> {
+        let signature = Secp256r1EcdsaSignature::from_bytes_unchecked(bytes)?;
+        Ok(Secp256r1EcdsaSignature(
+            signature.0.normalize_s().unwrap_or(signature.0),
+        ))
+    }
+}
+
+//////////////////////
+// Signature Traits //
+//////////////////////
+
+impl Signature for Secp256r1EcdsaSignature {
+    type SigningKeyMaterial = Secp256r1EcdsaPrivateKey;
+    type VerifyingKeyMaterial = Secp256r1EcdsaPublicKey;
+
+    /// Verifies that the provided signature is valid for the provided message, which is hashed
+    /// with SHA-256, and that it is in low-s form.
+    fn verify<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        public_key: &Secp256r1EcdsaPublicKey,
+    ) -> Result<()> {
+        Self::verify_arbitrary_msg(self, &signing_message(message)?, public_key)
+    }
+
+    /// Checks that `self` is valid for an arbitrary &[u8] `message` using `public_key`.
+    /// Outside of this crate, this particular function should only be used for native signature
+    /// verification in Move, and for WebAuthn assertions, which are not over a signing message.
+    fn verify_arbitrary_msg(
+        &self,
+        message: &[u8],
+        public_key: &Secp256r1EcdsaPublicKey,
+    ) -> Result<()> {
+        Secp256r1EcdsaSignature::check_s_malleability(&self.to_bytes())?;
+
+        public_key
+            .0
+            .verify(message, &self.0)
+            .map_err(|e| anyhow!("{}", e))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl Length for Secp256r1EcdsaSignature {
+    fn length(&self) -> usize {
+        SECP256R1_ECDSA_SIGNATURE_LENGTH
+    }
+}
+
+impl ValidCryptoMaterial for Secp256r1EcdsaSignature {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
+impl std::hash::Hash for Secp256r1EcdsaSignature {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let encoded_signature = self.to_bytes();
+        state.write(&encoded_signature);
+    }
+}
+
+impl TryFrom<&[u8]> for Secp256r1EcdsaSignature {
+    type Error = CryptoMaterialError;
+
+    fn try_from(bytes: &[u8]) -> std::result::Result<Secp256r1EcdsaSignature, CryptoMaterialError> {
+        // Detect mauled signatures early, as for Ed25519 signatures. This check is performed
+        // again in Secp256r1EcdsaSignature::verify_arbitrary_msg.
+        Secp256r1EcdsaSignature::check_s_malleability(bytes)?;
+        Secp256r1EcdsaSignature::from_bytes_unchecked(bytes)
+    }
+}
+
+// Those are required by the implementation of hash above
+impl PartialEq for Secp256r1EcdsaSignature {
+    fn eq(&self, other: &Secp256r1EcdsaSignature) -> bool {
+        self.to_bytes()[..] == other.to_bytes()[..]
+    }
+}
+
+impl Eq for Secp256r1EcdsaSignature {}
+
+impl fmt::Display for Secp256r1EcdsaSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(&self.to_bytes()[..]))
+    }
+}
+
+impl fmt::Debug for Secp256r1EcdsaSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secp256r1EcdsaSignature({})", self)
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    secp256r1_ecdsa::{
+        keypair_strategy, Secp256r1EcdsaPrivateKey, Secp256r1EcdsaPublicKey,
+        Secp256r1EcdsaSignature, SECP256R1_ECDSA_PUBLIC_KEY_LENGTH,
+        SECP256R1_ECDSA_SIGNATURE_LENGTH,
+    },
+    test_utils::{random_serializable_struct, TestAptosCrypto},
+    traits::*,
+    CryptoMaterialError,
+};
+use core::convert::TryFrom;
+use proptest::prelude::*;
+
+/// The order of the secp256r1 curve, big endian.
+const N: [u8; 32] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xBC, 0xE6, 0xFA, 0xAD, 0xA7, 0x17, 0x9E, 0x84, 0xF3, 0xB9, 0xCA, 0xC2, 0xFC, 0x63, 0x25, 0x51,
+];
+
+/// Returns the (r, n - s) counterpart of a (r, s) signature.
+fn negate_s(signature: &[u8; SECP256R1_ECDSA_SIGNATURE_LENGTH]) -> Vec<u8> {
+    let mut negated = signature.to_vec();
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let diff = N[i] as i16 - signature[32 + i] as i16 - borrow;
+        borrow = i16::from(diff < 0);
+        negated[32 + i] = diff.rem_euclid(256) as u8;
+    }
+    negated
+}
+
+proptest! {
+    #[test]
+    fn test_sign_and_verify(
+        keypair in keypair_strategy(),
+        message in random_serializable_struct(),
+    ) {
+        let signature = keypair.private_key.sign(&message).unwrap();
+        prop_assert!(signature.verify(&message, &keypair.public_key).is_ok());
+        prop_assert!(signature
+            .verify(&TestAptosCrypto("other message".to_string()), &keypair.public_key)
+            .is_err());
+    }
+
+    #[test]
+    fn test_serialization_round_trip(
+        keypair in keypair_strategy(),
+        message in random_serializable_struct(),
+    ) {
+        let public_key_bytes = keypair.public_key.to_bytes();
+        prop_assert_eq!(public_key_bytes.len(), SECP256R1_ECDSA_PUBLIC_KEY_LENGTH);
+        prop_assert_eq!(public_key_bytes[0], 0x04);
+        prop_assert_eq!(
+            Secp256r1EcdsaPublicKey::try_from(&public_key_bytes[..]).unwrap(),
+            keypair.public_key.clone()
+        );
+        prop_assert_eq!(
+            Secp256r1EcdsaPrivateKey::try_from(&keypair.private_key.to_bytes()[..]).unwrap(),
+            keypair.private_key.clone()
+        );
+
+        let signature = keypair.private_key.sign(&message).unwrap();
+        let signature_bytes = signature.to_bytes();
+        prop_assert_eq!(
+            Secp256r1EcdsaSignature::try_from(&signature_bytes[..]).unwrap(),
+            signature
+        );
+        let serialized = bcs::to_bytes(&keypair.public_key).unwrap();
+        prop_assert_eq!(
+            bcs::from_bytes::<Secp256r1EcdsaPublicKey>(&serialized).unwrap(),
+            keypair.public_key
+        );
+    }
+
+    #[test]
+    fn test_high_s_signatures_are_rejected(
+        keypair in keypair_strategy(),
+        message in random_serializable_struct(),
+    ) {
+        let signature = keypair.private_key.sign(&message).unwrap();
+        let high_s = negate_s(&signature.to_bytes());
+        prop_assert_eq!(
+            Secp256r1EcdsaSignature::try_from(&high_s[..]),
+            Err(CryptoMaterialError::CanonicalRepresentationError)
+        );
+
+        // Normalization recovers the low-s form, which verifies.
+        let normalized = Secp256r1EcdsaSignature::normalize(&high_s).unwrap();
+        prop_assert_eq!(&normalized, &signature);
+        prop_assert!(normalized.verify(&message, &keypair.public_key).is_ok());
+    }
+}
+
+#[test]
+fn test_invalid_key_material() {
+    assert_eq!(
+        Secp256r1EcdsaPublicKey::try_from(&[0u8; SECP256R1_ECDSA_PUBLIC_KEY_LENGTH][..]),
+        Err(CryptoMaterialError::DeserializationError)
+    );
+    assert_eq!(
+        Secp256r1EcdsaPublicKey::try_from(&[4u8; 33][..]),
+        Err(CryptoMaterialError::WrongLengthError)
+    );
+    // Zero and the curve order are out of the scalar range.
+    assert!(Secp256r1EcdsaPrivateKey::try_from(&[0u8; 32][..]).is_err());
+    assert!(Secp256r1EcdsaPrivateKey::try_from(&N[..]).is_err());
+    assert_eq!(
+        Secp256r1EcdsaSignature::try_from(&[0u8; SECP256R1_ECDSA_SIGNATURE_LENGTH][..]),
+        Err(CryptoMaterialError::DeserializationError)
+    );
+}
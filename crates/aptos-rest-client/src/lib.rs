@@ -11,11 +11,16 @@ pub use faucet::FaucetClient;
 pub mod response;
 pub use response::Response;
 pub mod state;
+pub mod transaction_stream;
+pub use transaction_stream::TransactionStreamFilter;
 pub mod types;
+pub mod verifying_client;
+pub use verifying_client::VerifyingClient;
 
 use crate::{
     aptos::{AptosVersion, Balance},
     error::RestError,
+    transaction_stream::parse_transaction_events,
 };
 use anyhow::{anyhow, Result};
 pub use aptos_api_types::{
@@ -24,9 +29,10 @@ pub use aptos_api_types::{
 use aptos_api_types::{
     deserialize_from_string,
     mime_types::{BCS, BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, JSON},
-    AptosError, BcsBlock, Block, GasEstimation, HexEncodedBytes, IndexResponse, MoveModuleId,
-    TransactionData, TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction,
-    VersionedEvent, ViewRequest,
+    AptosError, BcsBlock, BcsStateValueWithLedgerProof, BcsTransactionWithLedgerProof, Block,
+    GasEstimation, HexEncodedBytes, IndexResponse, MoveModuleId, TransactionData,
+    TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction, VersionedEvent,
+    ViewRequest,
 };
 use aptos_crypto::HashValue;
 use aptos_logger::{debug, info, sample, sample::SampleRate};
@@ -34,8 +40,10 @@ use aptos_types::{
     account_address::AccountAddress,
     account_config::{AccountResource, CoinStoreResource, NewBlockEvent, CORE_CODE_ADDRESS},
     contract_event::EventWithVersion,
+    state_proof::StateProof,
     transaction::SignedTransaction,
 };
+use futures::{stream, Stream, StreamExt, TryStreamExt};
 use move_core_types::language_storage::StructTag;
 use reqwest::{
     header::{ACCEPT, CONTENT_TYPE},
@@ -58,6 +66,8 @@ static DEFAULT_INTERVAL_DURATION: Duration = Duration::from_millis(DEFAULT_INTER
 const DEFAULT_MAX_SERVER_LAG_WAIT_DURATION: Duration = Duration::from_secs(60);
 const RESOURCES_PER_CALL_PAGINATION: u64 = 9999;
 const MODULES_PER_CALL_PAGINATION: u64 = 1000;
+/// Maximum duration of a transaction stream, after which it has to be resumed
+pub const TRANSACTION_STREAM_TIMEOUT: Duration = Duration::from_secs(60 * 60);
 
 type AptosResult<T> = Result<T, RestError>;
 
@@ -835,6 +845,43 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Subscribes to committed transactions matching `filter`, starting at `start_version`
+    /// or at the next committed transaction if not provided.
+    ///
+    /// Failures to open the stream, e.g. a rejected request, and transport errors are yielded
+    /// as items, after which the stream ends. The stream also ends if the connection is closed,
+    /// e.g. because the node failed to read the next transactions, or after
+    /// [`TRANSACTION_STREAM_TIMEOUT`]. Resume from the version after the last received
+    /// transaction.
+    pub fn stream_transactions(
+        &self,
+        start_version: Option<u64>,
+        filter: &TransactionStreamFilter,
+    ) -> impl Stream<Item = AptosResult<Transaction>> {
+        let request = self.build_path("transactions/stream").map(|url| {
+            // The stream is long lived, so it can't share the request timeout of the client
+            let request = self
+                .inner
+                .get(url)
+                .timeout(TRANSACTION_STREAM_TIMEOUT)
+                .header(ACCEPT, "text/event-stream")
+                .query(&filter.query());
+            match start_version {
+                Some(start_version) => request.query(&[("start_version", start_version)]),
+                None => request,
+            }
+        });
+
+        stream::once(async move {
+            let response = request?.send().await?;
+            if !response.status().is_success() {
+                return Err(parse_error(response).await);
+            }
+            Ok(parse_transaction_events(response.bytes_stream().boxed()))
+        })
+        .try_flatten()
+    }
+
     pub async fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -886,6 +933,15 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    pub async fn get_transaction_by_version_with_proof_bcs(
+        &self,
+        version: u64,
+    ) -> AptosResult<Response<BcsTransactionWithLedgerProof>> {
+        let url = self.build_path(&format!("transactions/by_version/{}/proof", version))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     async fn get_transaction_by_version_inner(
         &self,
         version: u64,
@@ -1065,6 +1121,26 @@ impl Client {
         Ok(response.map(|inner| inner.to_vec()))
     }
 
+    pub async fn get_account_resource_with_proof_bcs(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: Option<u64>,
+    ) -> AptosResult<Response<BcsStateValueWithLedgerProof>> {
+        let url = self.build_path(&format!(
+            "accounts/{}/resource/{}/proof",
+            address, resource_type
+        ))?;
+        let mut request = self.inner.get(url).header(ACCEPT, BCS);
+        if let Some(version) = version {
+            request = request.query(&[("ledger_version", version)])
+        }
+
+        let response = request.send().await?;
+        let response = self.check_and_parse_bcs_response(response).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_account_resource_at_version(
         &self,
         address: AccountAddress,
@@ -1267,6 +1343,24 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    pub async fn get_table_item_with_proof_bcs<K: Serialize>(
+        &self,
+        table_handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: K,
+    ) -> AptosResult<Response<BcsStateValueWithLedgerProof>> {
+        let url = self.build_path(&format!("tables/{}/item/proof", table_handle))?;
+        let data = json!({
+            "key_type": key_type,
+            "value_type": value_type,
+            "key": json!(key),
+        });
+
+        let response = self.post_bcs(url, data).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_raw_table_item(
         &self,
         table_handle: AccountAddress,
@@ -1285,6 +1379,15 @@ impl Client {
         Ok(response.map(|inner| inner.to_vec()))
     }
 
+    pub async fn get_state_proof_bcs(
+        &self,
+        known_version: u64,
+    ) -> AptosResult<Response<StateProof>> {
+        let url = self.build_path(&format!("state_proof?known_version={}", known_version))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_account(&self, address: AccountAddress) -> AptosResult<Response<Account>> {
         let url = self.build_path(&format!("accounts/{}", address))?;
         let response = self.inner.get(url).send().await?;
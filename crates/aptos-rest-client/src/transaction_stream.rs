@@ -0,0 +1,88 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::RestError, AptosResult};
+use aptos_api_types::Transaction;
+use aptos_types::account_address::AccountAddress;
+use bytes::Bytes;
+use futures::{stream, Stream, StreamExt};
+
+/// Filter of a transaction stream, a transaction must match every given field
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransactionStreamFilter {
+    /// Only stream transactions sent by this account
+    pub sender: Option<AccountAddress>,
+    /// Only stream transactions calling this entry function e.g. `0x1::coin::transfer`
+    pub entry_function: Option<String>,
+    /// Only stream transactions emitting an event of this type
+    pub event_type: Option<String>,
+}
+
+impl TransactionStreamFilter {
+    pub(crate) fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![];
+        if let Some(sender) = &self.sender {
+            query.push(("sender", sender.to_hex_literal()));
+        }
+        if let Some(entry_function) = &self.entry_function {
+            query.push(("entry_function", entry_function.clone()));
+        }
+        if let Some(event_type) = &self.event_type {
+            query.push(("event_type", event_type.clone()));
+        }
+        query
+    }
+}
+
+/// Parses a body of server-sent events, each carrying a JSON encoded transaction.
+///
+/// Events without data, such as keep alive comments, are skipped. The stream ends after
+/// the first transport error.
+pub(crate) fn parse_transaction_events<S>(body: S) -> impl Stream<Item = AptosResult<Transaction>>
+where
+    S: Stream<Item = reqwest::Result<Bytes>> + Unpin,
+{
+    stream::unfold(
+        (body, Vec::new(), false),
+        |(mut body, mut buffer, done)| async move {
+            loop {
+                if let Some(end) = find_event_end(&buffer) {
+                    let event: Vec<u8> = buffer.drain(..end + 2).collect();
+                    let data = match event_data(&event) {
+                        Some(data) => data,
+                        None => continue,
+                    };
+                    let txn = serde_json::from_str(&data).map_err(RestError::from);
+                    return Some((txn, (body, buffer, done)));
+                }
+                if done {
+                    return None;
+                }
+                match body.next().await {
+                    Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                    Some(Err(err)) => return Some((Err(err.into()), (body, buffer, true))),
+                    None => return None,
+                }
+            }
+        },
+    )
+}
+
+fn find_event_end(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(2).position(|window| window == b"\n\n")
+}
+
+/// Joins the `data` lines of an event, returns `None` if there are none
+fn event_data(event: &[u8]) -> Option<String> {
+    let event = String::from_utf8_lossy(event);
+    let lines: Vec<&str> = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
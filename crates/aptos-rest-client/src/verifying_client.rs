@@ -0,0 +1,269 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::RestError, AptosResult, Client, Response};
+use anyhow::anyhow;
+use aptos_infallible::RwLock;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    contract_event::EventWithVersion,
+    epoch_change::Verifier,
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::state_key::StateKey,
+    transaction::{TransactionWithProof, Version},
+    trusted_state::TrustedState,
+    waypoint::Waypoint,
+};
+use move_core_types::parser::parse_struct_tag;
+use serde::de::DeserializeOwned;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    sync::Arc,
+};
+
+/// A resource verified by a [`VerifyingClient`], along with the version it was proven at.
+#[derive(Clone, Debug)]
+pub struct ProvenResource<T> {
+    /// Version of the state snapshot the resource was read at
+    pub version: Version,
+    /// `None` if the resource doesn't exist at `version`
+    pub resource: Option<T>,
+}
+
+/// A [`Client`] that doesn't trust the node it talks to.
+///
+/// The client keeps a [`TrustedState`], starting from a waypoint, and ratchets it forward
+/// using the epoch change proofs served by the node. Every response is verified against a
+/// ledger info signed by the validator set of the trusted epoch before being returned.
+#[derive(Clone, Debug)]
+pub struct VerifyingClient {
+    inner: Client,
+    trusted_state: Arc<RwLock<TrustedState>>,
+}
+
+impl VerifyingClient {
+    pub fn new(inner: Client, waypoint: Waypoint) -> Self {
+        Self::new_with_state(inner, TrustedState::from_epoch_waypoint(waypoint))
+    }
+
+    pub fn new_with_state(inner: Client, trusted_state: TrustedState) -> Self {
+        Self {
+            inner,
+            trusted_state: Arc::new(RwLock::new(trusted_state)),
+        }
+    }
+
+    /// The underlying client, whose responses are not verified
+    pub fn inner(&self) -> &Client {
+        &self.inner
+    }
+
+    pub fn trusted_state(&self) -> TrustedState {
+        self.trusted_state.read().clone()
+    }
+
+    /// Ratchets the trusted state forward to the latest ledger info of the node, following
+    /// epoch changes as needed.
+    pub async fn sync(&self) -> AptosResult<TrustedState> {
+        loop {
+            let trusted_state = self.trusted_state();
+            let state_proof = self
+                .inner
+                .get_state_proof_bcs(trusted_state.version())
+                .await?
+                .into_inner();
+            let more = state_proof.epoch_changes().more;
+            let change = trusted_state
+                .verify_and_ratchet(&state_proof)
+                .map_err(RestError::InvalidProof)?;
+            if let Some(new_state) = change.new_state() {
+                self.update_trusted_state(new_state);
+            }
+            if !more {
+                return Ok(self.trusted_state());
+            }
+        }
+    }
+
+    /// Reads a resource and verifies it against the trusted state. The resource is `None` if
+    /// the node proves that it doesn't exist.
+    ///
+    /// Proofs are only available at the state snapshots persisted by the node, which are taken
+    /// periodically. The resource is read at the latest snapshot at or before `version`, or at
+    /// or before the latest ledger info of the node if `version` is not given, and
+    /// [`ProvenResource::version`] tells which one. That snapshot can be well behind the
+    /// requested version, its proof chains it to the signed ledger info served along with it.
+    /// Without a `version`, that ledger info must be at least as new as the trusted state when
+    /// the request is sent.
+    ///
+    /// Resources stored in a resource group can't be read through this method.
+    pub async fn get_account_resource_bcs<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: Option<Version>,
+    ) -> AptosResult<Response<ProvenResource<T>>> {
+        let struct_tag = parse_struct_tag(resource_type)?;
+        let state_key = StateKey::access_path(AccessPath::resource_access_path(
+            address,
+            struct_tag.clone(),
+        )?);
+        let trusted_version = self.trusted_state().version();
+        let (proof, state) = self
+            .inner
+            .get_account_resource_with_proof_bcs(address, &struct_tag.to_string(), version)
+            .await?
+            .into_parts();
+
+        self.verify_ledger_info(&proof.ledger_info_with_signatures)
+            .await?;
+        let ledger_info = proof.ledger_info_with_signatures.ledger_info();
+        let state_value_with_proof = proof.state_value_with_proof;
+        let max_version = match version {
+            Some(version) => version,
+            None => {
+                ensure_valid(
+                    ledger_info.version() >= trusted_version,
+                    format!(
+                        "Ledger info at version {} is older than the trusted state at version {}",
+                        ledger_info.version(),
+                        trusted_version
+                    ),
+                )?;
+                ledger_info.version()
+            },
+        };
+        ensure_valid(
+            state_value_with_proof.version <= max_version,
+            format!(
+                "Value read at version {} for requested version {}",
+                state_value_with_proof.version, max_version
+            ),
+        )?;
+        // The proof of the transaction info at the snapshot version is relative to the ledger
+        // info, so verifying it also checks that the snapshot is committed in the ledger.
+        state_value_with_proof
+            .verify(ledger_info, &state_key)
+            .map_err(RestError::InvalidProof)?;
+
+        let resource = state_value_with_proof
+            .value
+            .map(|value| bcs::from_bytes(value.bytes()))
+            .transpose()?;
+        Ok(Response::new(
+            ProvenResource {
+                version: state_value_with_proof.version,
+                resource,
+            },
+            state,
+        ))
+    }
+
+    /// Reads a committed transaction along with its events and verifies both against the
+    /// trusted state.
+    pub async fn get_transaction_by_version_bcs(
+        &self,
+        version: Version,
+    ) -> AptosResult<Response<TransactionWithProof>> {
+        let (proof, state) = self
+            .inner
+            .get_transaction_by_version_with_proof_bcs(version)
+            .await?
+            .into_parts();
+
+        self.verify_ledger_info(&proof.ledger_info_with_signatures)
+            .await?;
+        let transaction_with_proof = proof.transaction_with_proof;
+        ensure_valid(
+            transaction_with_proof.version == version,
+            format!(
+                "Transaction at version {} returned for requested version {}",
+                transaction_with_proof.version, version
+            ),
+        )?;
+        ensure_valid(
+            transaction_with_proof.events.is_some(),
+            "Transaction returned without events",
+        )?;
+        transaction_with_proof
+            .verify(proof.ledger_info_with_signatures.ledger_info())
+            .map_err(RestError::InvalidProof)?;
+
+        Ok(Response::new(transaction_with_proof, state))
+    }
+
+    /// Reads events from an event handle and verifies that each of them was emitted by the
+    /// transaction at its version.
+    ///
+    /// This proves that every returned event is authentic, but not that the node didn't omit
+    /// any event of the requested range.
+    pub async fn get_account_events_bcs(
+        &self,
+        address: AccountAddress,
+        struct_tag: &str,
+        field_name: &str,
+        start: Option<u64>,
+        limit: Option<u16>,
+    ) -> AptosResult<Response<Vec<EventWithVersion>>> {
+        let (events, state) = self
+            .inner
+            .get_account_events_bcs(address, struct_tag, field_name, start, limit)
+            .await?
+            .into_parts();
+
+        let mut transactions = BTreeMap::new();
+        for event in &events {
+            let version = event.transaction_version;
+            if let Entry::Vacant(entry) = transactions.entry(version) {
+                let transaction = self.get_transaction_by_version_bcs(version).await?;
+                entry.insert(transaction.into_inner());
+            }
+            let emitted = transactions[&version]
+                .events
+                .as_ref()
+                .map_or(false, |emitted| emitted.contains(&event.event));
+            ensure_valid(
+                emitted,
+                format!(
+                    "Event {:?} was not emitted by transaction {}",
+                    event.event, version
+                ),
+            )?;
+        }
+
+        Ok(Response::new(events, state))
+    }
+
+    /// Verifies the signatures of a ledger info using the validator set of the trusted epoch,
+    /// first syncing to the epoch of the ledger info if it is newer.
+    async fn verify_ledger_info(&self, ledger_info: &LedgerInfoWithSignatures) -> AptosResult<()> {
+        let epoch = ledger_info.ledger_info().epoch();
+        if self
+            .trusted_state()
+            .epoch_change_verification_required(epoch)
+        {
+            self.sync().await?;
+        }
+
+        self.trusted_state()
+            .verify(ledger_info)
+            .map_err(RestError::InvalidProof)
+    }
+
+    fn update_trusted_state(&self, new_state: TrustedState) {
+        let mut trusted_state = self.trusted_state.write();
+        // Concurrent syncs may finish out of order, never move backwards.
+        if new_state.version() > trusted_state.version() {
+            *trusted_state = new_state;
+        }
+    }
+}
+
+fn ensure_valid<M: Into<String>>(condition: bool, message: M) -> AptosResult<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(RestError::InvalidProof(anyhow!(message.into())))
+    }
+}
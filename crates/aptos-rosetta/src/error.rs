@@ -302,6 +302,7 @@ impl From<RestError> for ApiError {
             ))),
             RestError::UrlParse(err) => ApiError::InternalError(Some(err.to_string())),
             RestError::Timeout(err) => ApiError::InternalError(Some(err.to_string())),
+            RestError::InvalidProof(err) => ApiError::InternalError(Some(err.to_string())),
             RestError::Unknown(err) => ApiError::InternalError(Some(err.to_string())),
         }
     }
@@ -18,7 +18,7 @@ use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     x25519, PrivateKey, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
 };
-use aptos_global_constants::adjust_gas_headroom;
+use aptos_global_constants::{adjust_gas_headroom, MAX_GAS_AMOUNT};
 use aptos_keygen::KeyGen;
 use aptos_rest_client::{
     aptos_api_types::{HashValue, ViewRequest},
@@ -28,8 +28,13 @@ use aptos_rest_client::{
 use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
 use aptos_types::{
     chain_id::ChainId,
-    transaction::{authenticator::AuthenticationKey, SignedTransaction, TransactionPayload},
+    transaction::{
+        authenticator::AuthenticationKey, ExecutionStatus, SignedTransaction, TransactionPayload,
+        TransactionStatus,
+    },
 };
+use aptos_validator_interface::{DebuggerStateView, RestDebuggerInterface};
+use aptos_vm::AptosVM;
 use async_trait::async_trait;
 use clap::{ArgEnum, Parser};
 use hex::FromHexError;
@@ -44,6 +49,7 @@ use std::{
     fs::OpenOptions,
     path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use thiserror::Error;
@@ -1440,6 +1446,94 @@ impl TransactionOptions {
         Ok(response.into_inner())
     }
 
+    /// Simulates a transaction locally against the latest state of the network, and writes its
+    /// gas profile to `gas-profiling/` instead of submitting it
+    pub async fn profile_gas(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        let client = self.rest_client()?;
+        let (sender_key, sender_address) = self.get_key_and_address()?;
+
+        let gas_unit_price = if let Some(gas_unit_price) = self.gas_options.gas_unit_price {
+            gas_unit_price
+        } else {
+            client.estimate_gas_price().await?.into_inner().gas_estimate
+        };
+        let (account, state) = get_account_with_state(&client, sender_address).await?;
+
+        let transaction_factory = TransactionFactory::new(ChainId::new(state.chain_id))
+            .with_gas_unit_price(gas_unit_price)
+            .with_max_gas_amount(self.gas_options.max_gas.unwrap_or(MAX_GAS_AMOUNT))
+            .with_transaction_expiration_time(self.gas_options.expiration_secs);
+        let unsigned_transaction = transaction_factory
+            .payload(payload)
+            .sender(sender_address)
+            .sequence_number(account.sequence_number)
+            .build();
+        let signed_transaction = SignedTransaction::new(
+            unsigned_transaction,
+            sender_key.public_key(),
+            Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+        );
+        let hash = signed_transaction.clone().committed_hash();
+
+        // The state view reads the state as of the version before the one it is given
+        let state_view = DebuggerStateView::new(
+            Arc::new(RestDebuggerInterface::new(client)),
+            state.version + 1,
+        );
+        let (vm_status, output, gas_log) = tokio::task::spawn_blocking(move || {
+            AptosVM::simulate_signed_transaction_with_gas_profiling(
+                &signed_transaction,
+                &state_view,
+            )
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let gas_log =
+            gas_log.ok_or_else(|| CliError::SimulationError(format!("{:?}", vm_status)))?;
+
+        let profile_dir = PathBuf::from("gas-profiling");
+        create_dir_if_not_exist(&profile_dir)?;
+        let name = format!("txn-{}", hash.to_hex());
+        write_to_file(
+            &profile_dir.join(format!("{}.folded", name)),
+            "gas profile",
+            gas_log.to_folded_stacks().as_bytes(),
+        )?;
+        let summary = serde_json::to_string_pretty(&gas_log.to_summary())
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        write_to_file(
+            &profile_dir.join(format!("{}.json", name)),
+            "gas summary",
+            summary.as_bytes(),
+        )?;
+        eprintln!(
+            "Gas profile written to {}",
+            profile_dir
+                .join(format!("{}.{{folded,json}}", name))
+                .display()
+        );
+
+        let output = output.txn_output();
+        Ok(TransactionSummary {
+            transaction_hash: hash.into(),
+            gas_used: Some(output.gas_used()),
+            gas_unit_price: Some(gas_unit_price),
+            pending: None,
+            sender: Some(sender_address),
+            sequence_number: Some(account.sequence_number),
+            success: Some(matches!(
+                output.status(),
+                TransactionStatus::Keep(ExecutionStatus::Success)
+            )),
+            timestamp_us: None,
+            version: None,
+            vm_status: Some(format!("{:?}", output.status())),
+        })
+    }
+
     pub async fn estimate_gas_price(&self) -> CliTypedResult<u64> {
         let client = self.rest_client()?;
         client
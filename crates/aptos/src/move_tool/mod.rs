@@ -1205,6 +1205,13 @@ pub struct RunFunction {
     #[clap(long, multiple_values = true)]
     pub(crate) type_args: Vec<MoveType>,
 
+    /// Simulate the transaction locally instead of submitting it, and write its gas profile
+    ///
+    /// The profile is written to `gas-profiling/` as folded stacks, which can be rendered as a
+    /// flame graph with e.g. `inferno-flamegraph`, along with a JSON summary.
+    #[clap(long)]
+    pub(crate) profile_gas: bool,
+
     #[clap(flatten)]
     pub(crate) txn_options: TransactionOptions,
 }
@@ -1230,15 +1237,20 @@ impl CliCommand<TransactionSummary> for RunFunction {
             type_args.push(type_tag)
         }
 
-        self.txn_options
-            .submit_transaction(TransactionPayload::EntryFunction(EntryFunction::new(
-                self.function_id.module_id,
-                self.function_id.member_id,
-                type_args,
-                args,
-            )))
-            .await
-            .map(TransactionSummary::from)
+        let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+            self.function_id.module_id,
+            self.function_id.member_id,
+            type_args,
+            args,
+        ));
+        if self.profile_gas {
+            self.txn_options.profile_gas(payload).await
+        } else {
+            self.txn_options
+                .submit_transaction(payload)
+                .await
+                .map(TransactionSummary::from)
+        }
     }
 }
 
@@ -10,7 +10,7 @@ use crate::{
             OptionalPoolAddressArgs, PoolAddressArgs, ProfileOptions, PromptOptions, RestOptions,
             TransactionOptions, TransactionSummary,
         },
-        utils::{prompt_yes_with_override, read_from_file},
+        utils::{check_if_file_exists, prompt_yes_with_override, read_from_file, write_to_file},
     },
     config::GlobalConfig,
     genesis::git::from_yaml,
@@ -26,7 +26,9 @@ use aptos_backup_cli::{
     utils::{ConcurrentDownloadsOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt, RocksdbOpt},
 };
 use aptos_cached_packages::aptos_stdlib;
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{
+    IdentityBlob, InitialSafetyRulesConfig, NodeConfig, Token, WrappingKeyConfig,
+};
 use aptos_crypto::{bls12381, bls12381::PublicKey, x25519, ValidCryptoMaterialStringExt};
 use aptos_faucet::FaucetArgs;
 use aptos_genesis::config::{HostAndPort, OperatorConfiguration};
@@ -34,6 +36,8 @@ use aptos_network_checker::args::{
     validate_address, CheckEndpointArgs, HandshakeArgs, NodeAddressArgs,
 };
 use aptos_rest_client::{aptos_api_types::VersionedEvent, Client, State};
+use aptos_safety_rules::{PersistentSafetyStorage, SafetyDataExport};
+use aptos_secure_storage::{EncryptedOnDiskStorage, KVStorage, Storage, WrappingKeySource};
 use aptos_types::{
     account_address::AccountAddress,
     account_config::{BlockResource, CORE_CODE_ADDRESS},
@@ -57,7 +61,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
-    path::PathBuf,
+    path::{Path, PathBuf},
     pin::Pin,
     sync::Arc,
     thread,
@@ -88,6 +92,10 @@ pub enum NodeTool {
     UpdateValidatorNetworkAddresses(UpdateValidatorNetworkAddresses),
     AnalyzeValidatorPerformance(AnalyzeValidatorPerformance),
     BootstrapDbFromBackup(BootstrapDbFromBackup),
+    EncryptSecureStorage(EncryptSecureStorage),
+    RotateSecureStorageKey(RotateSecureStorageKey),
+    ExportSafetyData(ExportSafetyData),
+    ImportSafetyData(ImportSafetyData),
 }
 
 impl NodeTool {
@@ -109,6 +117,10 @@ impl NodeTool {
             UpdateValidatorNetworkAddresses(tool) => tool.execute_serialized().await,
             AnalyzeValidatorPerformance(tool) => tool.execute_serialized().await,
             BootstrapDbFromBackup(tool) => tool.execute_serialized().await,
+            EncryptSecureStorage(tool) => tool.execute_serialized().await,
+            RotateSecureStorageKey(tool) => tool.execute_serialized().await,
+            ExportSafetyData(tool) => tool.execute_serialized().await,
+            ImportSafetyData(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -1541,6 +1553,248 @@ impl CliCommand<()> for BootstrapDbFromBackup {
     }
 }
 
+/// The secret the key encrypting a secure storage is derived from
+#[derive(Parser)]
+pub struct WrappingKeyArgs {
+    /// File holding the passphrase the wrapping key is derived from
+    #[clap(long, group = "wrapping_key", parse(from_os_str))]
+    pub passphrase_file: Option<PathBuf>,
+
+    /// File holding key material the wrapping key is derived from, e.g., random bytes
+    #[clap(long, group = "wrapping_key", parse(from_os_str))]
+    pub key_file: Option<PathBuf>,
+}
+
+impl WrappingKeyArgs {
+    fn wrapping_key_source(&self) -> CliTypedResult<WrappingKeySource> {
+        // Go through the node config types, so passphrases are read the same way a node does
+        let config = match (&self.passphrase_file, &self.key_file) {
+            (Some(passphrase_file), None) => {
+                WrappingKeyConfig::Passphrase(Token::FromDisk(passphrase_file.clone()))
+            },
+            (None, Some(key_file)) => WrappingKeyConfig::KeyFile(key_file.clone()),
+            _ => {
+                return Err(CliError::CommandArgumentError(
+                    "Exactly one of --passphrase-file or --key-file must be provided".to_string(),
+                ))
+            },
+        };
+        config
+            .wrapping_key_source()
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))
+    }
+}
+
+/// Encrypt an on disk secure storage
+///
+/// Creates an encrypted on disk secure storage from the content of an existing, plaintext,
+/// on disk secure storage. The plaintext storage is left in place, and should be removed once the
+/// node config points to the encrypted storage.
+#[derive(Parser)]
+pub struct EncryptSecureStorage {
+    /// Path to the existing on disk secure storage, e.g. /opt/aptos/data/secure-data.json
+    #[clap(long, parse(from_os_str))]
+    pub source_path: PathBuf,
+
+    /// Path to the encrypted secure storage to create
+    #[clap(long, parse(from_os_str))]
+    pub target_path: PathBuf,
+
+    /// The secret to encrypt the new storage with
+    #[clap(flatten)]
+    pub wrapping_key_args: WrappingKeyArgs,
+}
+
+#[async_trait]
+impl CliCommand<()> for EncryptSecureStorage {
+    fn command_name(&self) -> &'static str {
+        "EncryptSecureStorage"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let wrapping_key_source = self.wrapping_key_args.wrapping_key_source()?;
+        EncryptedOnDiskStorage::migrate_from_on_disk(
+            &self.source_path,
+            self.target_path,
+            &wrapping_key_source,
+        )
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Rotate the key of an encrypted on disk secure storage
+///
+/// Re-encrypts the storage under a key derived from the new secret. The node must be stopped, and
+/// its config updated to the new secret before it is restarted.
+#[derive(Parser)]
+pub struct RotateSecureStorageKey {
+    /// Path to the encrypted secure storage
+    #[clap(long, parse(from_os_str))]
+    pub path: PathBuf,
+
+    /// File holding the current passphrase the wrapping key is derived from
+    #[clap(long, group = "current_wrapping_key", parse(from_os_str))]
+    pub current_passphrase_file: Option<PathBuf>,
+
+    /// File holding the current key material the wrapping key is derived from
+    #[clap(long, group = "current_wrapping_key", parse(from_os_str))]
+    pub current_key_file: Option<PathBuf>,
+
+    /// The new secret to encrypt the storage with
+    #[clap(flatten)]
+    pub wrapping_key_args: WrappingKeyArgs,
+}
+
+#[async_trait]
+impl CliCommand<()> for RotateSecureStorageKey {
+    fn command_name(&self) -> &'static str {
+        "RotateSecureStorageKey"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let current_wrapping_key_source = WrappingKeyArgs {
+            passphrase_file: self.current_passphrase_file,
+            key_file: self.current_key_file,
+        }
+        .wrapping_key_source()?;
+        let new_wrapping_key_source = self.wrapping_key_args.wrapping_key_source()?;
+
+        let mut storage =
+            EncryptedOnDiskStorage::open_existing(self.path, &current_wrapping_key_source)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        storage
+            .rotate_wrapping_key(&new_wrapping_key_source)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))
+    }
+}
+
+/// Loads the node config at `node_config_path`
+fn load_node_config(node_config_path: &Path) -> CliTypedResult<NodeConfig> {
+    NodeConfig::load(node_config_path).map_err(|err| {
+        CliError::UnableToReadFile(node_config_path.display().to_string(), err.to_string())
+    })
+}
+
+/// Opens the safety rules storage of a node, as configured in its node config
+fn safety_rules_storage(config: &NodeConfig) -> CliTypedResult<PersistentSafetyStorage> {
+    let storage: Storage = (&config.consensus.safety_rules.backend).into();
+    storage
+        .available()
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+    Ok(PersistentSafetyStorage::new(storage, false))
+}
+
+/// Export the safety data of a validator
+///
+/// Exports the safety data, i.e., the epoch and rounds the validator last voted in, and the
+/// waypoint from the safety rules storage of a validator, so they can be imported into a new
+/// storage with `aptos node import-safety-data`. The validator must be stopped first.
+#[derive(Parser)]
+pub struct ExportSafetyData {
+    /// Path to the node config of the validator, e.g. /opt/aptos/etc/validator.yaml
+    #[clap(long, parse(from_os_str))]
+    pub node_config_path: PathBuf,
+
+    /// File to write the safety data to
+    #[clap(long, parse(from_os_str))]
+    pub output_file: PathBuf,
+
+    #[clap(flatten)]
+    pub prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<()> for ExportSafetyData {
+    fn command_name(&self) -> &'static str {
+        "ExportSafetyData"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        check_if_file_exists(&self.output_file, self.prompt_options)?;
+        let config = load_node_config(&self.node_config_path)?;
+        let mut storage = safety_rules_storage(&config)?;
+        let export = SafetyDataExport::export(&mut storage)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let bytes = serde_json::to_vec_pretty(&export)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        write_to_file(&self.output_file, "Safety data", &bytes)
+    }
+}
+
+/// Import the safety data of a validator
+///
+/// Imports safety data exported with `aptos node export-safety-data` into the safety rules
+/// storage of the same validator. An empty storage is initialized with the author of the export
+/// and the consensus key of the validator, so there is no need to start the validator against it
+/// first. The import is refused if it would lower the epoch, rounds or waypoint already in the
+/// storage, as that could lead the validator to equivocate.
+#[derive(Parser)]
+pub struct ImportSafetyData {
+    /// Path to the node config of the validator, e.g. /opt/aptos/etc/validator.yaml
+    #[clap(long, parse(from_os_str))]
+    pub node_config_path: PathBuf,
+
+    /// File to read the safety data from
+    #[clap(long, parse(from_os_str))]
+    pub input_file: PathBuf,
+
+    /// Identity file holding the consensus key of the validator, e.g.
+    /// /opt/aptos/genesis/validator-identity.yaml
+    ///
+    /// Defaults to the identity blob of the initial safety rules config of the node config.
+    #[clap(long, parse(from_os_str))]
+    pub validator_identity_file: Option<PathBuf>,
+}
+
+#[async_trait]
+impl CliCommand<()> for ImportSafetyData {
+    fn command_name(&self) -> &'static str {
+        "ImportSafetyData"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let bytes = read_from_file(&self.input_file)?;
+        let export: SafetyDataExport = serde_json::from_slice(&bytes)
+            .map_err(|err| CliError::UnableToParse("Safety data", err.to_string()))?;
+        let config = load_node_config(&self.node_config_path)?;
+        let identity_file = match (
+            self.validator_identity_file,
+            &config.consensus.safety_rules.initial_safety_rules_config,
+        ) {
+            (Some(identity_file), _) => identity_file,
+            (
+                None,
+                InitialSafetyRulesConfig::FromFile {
+                    identity_blob_path, ..
+                },
+            ) => identity_blob_path.clone(),
+            (None, InitialSafetyRulesConfig::None) => {
+                return Err(CliError::CommandArgumentError(
+                    "No identity blob in the node config, provide --validator-identity-file"
+                        .to_string(),
+                ))
+            },
+        };
+        let consensus_private_key = IdentityBlob::from_file(&identity_file)
+            .map_err(|err| {
+                CliError::UnableToReadFile(identity_file.display().to_string(), err.to_string())
+            })?
+            .consensus_private_key
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "No consensus key in {}",
+                    identity_file.display()
+                ))
+            })?;
+
+        let mut storage = safety_rules_storage(&config)?;
+        export
+            .import(&mut storage, consensus_private_key)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))
+    }
+}
+
 /// Checks the network connectivity of a node
 ///
 /// Checks network connectivity by dialing the node and attempting
@@ -318,6 +318,7 @@ impl CliTestFramework {
                 IdentifierWrapper::from_str("AptosCoin").unwrap(),
                 vec![],
             ))],
+            profile_gas: false,
             txn_options: self.transaction_options(sender_index, gas_options),
         }
         .execute()
@@ -587,6 +588,7 @@ impl CliTestFramework {
                 ArgWithType::bytes(vec![]),
             ],
             type_args: vec![],
+            profile_gas: false,
             txn_options: self.transaction_options(owner_index, None),
         }
         .execute()
@@ -897,6 +899,7 @@ impl CliTestFramework {
             function_id,
             args: parsed_args,
             type_args: parsed_type_args,
+            profile_gas: false,
         }
         .execute()
         .await
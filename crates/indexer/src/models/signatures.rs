@@ -6,6 +6,7 @@ use crate::{models::transactions::Transaction, schema::signatures, util::standar
 use anyhow::{Context, Result};
 use aptos_api_types::{
     AccountSignature as APIAccountSignature, Ed25519Signature as APIEd25519Signature,
+    FeePayerSignature as APIFeePayerSignature, HexEncodedBytes,
     MultiAgentSignature as APIMultiAgentSignature,
     MultiEd25519Signature as APIMultiEd25519Signature,
     TransactionSignature as APITransactionSignature,
@@ -74,6 +75,38 @@ impl Signature {
                 transaction_version,
                 transaction_block_height,
             ),
+            APITransactionSignature::Secp256r1EcdsaSignature(sig) => {
+                Ok(vec![Self::parse_secp256r1_ecdsa_signature(
+                    "secp256r1_ecdsa_signature",
+                    &sig.public_key,
+                    &sig.signature,
+                    sender,
+                    transaction_version,
+                    transaction_block_height,
+                    true,
+                    0,
+                    None,
+                )])
+            },
+            APITransactionSignature::WebAuthnSignature(sig) => {
+                Ok(vec![Self::parse_secp256r1_ecdsa_signature(
+                    "web_authn_signature",
+                    &sig.public_key,
+                    &sig.signature,
+                    sender,
+                    transaction_version,
+                    transaction_block_height,
+                    true,
+                    0,
+                    None,
+                )])
+            },
+            APITransactionSignature::FeePayerSignature(sig) => Self::parse_fee_payer_signature(
+                sig,
+                sender,
+                transaction_version,
+                transaction_block_height,
+            ),
         }
     }
 
@@ -86,6 +119,11 @@ impl Signature {
             APITransactionSignature::MultiAgentSignature(_) => {
                 String::from("multi_agent_signature")
             },
+            APITransactionSignature::Secp256r1EcdsaSignature(_) => {
+                String::from("secp256r1_ecdsa_signature")
+            },
+            APITransactionSignature::WebAuthnSignature(_) => String::from("web_authn_signature"),
+            APITransactionSignature::FeePayerSignature(_) => String::from("fee_payer_signature"),
         }
     }
 
@@ -114,6 +152,35 @@ impl Signature {
         }
     }
 
+    /// Parses a secp256r1 ECDSA signature, made either directly or through WebAuthn, in which
+    /// case the authenticator data and client data are not kept.
+    fn parse_secp256r1_ecdsa_signature(
+        type_: &str,
+        public_key: &HexEncodedBytes,
+        signature: &HexEncodedBytes,
+        sender: &String,
+        transaction_version: i64,
+        transaction_block_height: i64,
+        is_sender_primary: bool,
+        multi_agent_index: i64,
+        override_address: Option<&String>,
+    ) -> Self {
+        let signer = standardize_address(override_address.unwrap_or(sender));
+        Self {
+            transaction_version,
+            transaction_block_height,
+            signer,
+            is_sender_primary,
+            type_: String::from(type_),
+            public_key: public_key.to_string(),
+            threshold: 1,
+            public_key_indices: serde_json::Value::Array(vec![]),
+            signature: signature.to_string(),
+            multi_agent_index,
+            multi_sig_index: 0,
+        }
+    }
+
     fn parse_multi_signature(
         s: &APIMultiEd25519Signature,
         sender: &String,
@@ -192,6 +259,52 @@ impl Signature {
         Ok(signatures)
     }
 
+    /// Parses a fee payer signature like a multi agent one, the fee payer coming after the
+    /// secondary signers.
+    fn parse_fee_payer_signature(
+        s: &APIFeePayerSignature,
+        sender: &String,
+        transaction_version: i64,
+        transaction_block_height: i64,
+    ) -> Result<Vec<Self>> {
+        let mut signatures = Vec::default();
+        // process sender signature
+        signatures.append(&mut Self::parse_multi_agent_signature_helper(
+            &s.sender,
+            sender,
+            transaction_version,
+            transaction_block_height,
+            true,
+            0,
+            None,
+        ));
+        for (index, address) in s.secondary_signer_addresses.iter().enumerate() {
+            let secondary_sig = s.secondary_signers.get(index).context(format!(
+                "Failed to parse index {} for fee payer secondary signers",
+                index
+            ))?;
+            signatures.append(&mut Self::parse_multi_agent_signature_helper(
+                secondary_sig,
+                sender,
+                transaction_version,
+                transaction_block_height,
+                false,
+                index as i64,
+                Some(&address.to_string()),
+            ));
+        }
+        signatures.append(&mut Self::parse_multi_agent_signature_helper(
+            &s.fee_payer_signer,
+            sender,
+            transaction_version,
+            transaction_block_height,
+            false,
+            s.secondary_signer_addresses.len() as i64,
+            Some(&s.fee_payer_address.to_string()),
+        ));
+        Ok(signatures)
+    }
+
     fn parse_multi_agent_signature_helper(
         s: &APIAccountSignature,
         sender: &String,
@@ -220,6 +333,32 @@ impl Signature {
                 multi_agent_index,
                 override_address,
             ),
+            APIAccountSignature::Secp256r1EcdsaSignature(sig) => {
+                vec![Self::parse_secp256r1_ecdsa_signature(
+                    "secp256r1_ecdsa_signature",
+                    &sig.public_key,
+                    &sig.signature,
+                    sender,
+                    transaction_version,
+                    transaction_block_height,
+                    is_sender_primary,
+                    multi_agent_index,
+                    override_address,
+                )]
+            },
+            APIAccountSignature::WebAuthnSignature(sig) => {
+                vec![Self::parse_secp256r1_ecdsa_signature(
+                    "web_authn_signature",
+                    &sig.public_key,
+                    &sig.signature,
+                    sender,
+                    transaction_version,
+                    transaction_block_height,
+                    is_sender_primary,
+                    multi_agent_index,
+                    override_address,
+                )]
+            },
         }
     }
 }
@@ -3,7 +3,7 @@
 
 use aptos_indexer_grpc_utils::{
     cache_operator::CacheOperator,
-    config::IndexerGrpcConfig,
+    config::{IndexerGrpcConfig, IndexerGrpcFileStoreConfig},
     create_grpc_client,
     file_store_operator::{FileStoreMetadata, FileStoreOperator},
 };
@@ -25,8 +25,8 @@ pub struct Worker {
     redis_client: redis::Client,
     /// Fullnode grpc address.
     fullnode_grpc_address: String,
-    /// File store bucket name.
-    pub file_store_bucket_name: String,
+    /// File store config.
+    pub file_store_config: IndexerGrpcFileStoreConfig,
 }
 
 /// GRPC data status enum is to identify the data frame.
@@ -58,7 +58,7 @@ impl Worker {
             redis_client,
             // The fullnode grpc address is required.
             fullnode_grpc_address: format!("http://{}", config.fullnode_grpc_address.unwrap()),
-            file_store_bucket_name: config.file_store_bucket_name,
+            file_store_config: config.file_store_config,
         }
     }
 
@@ -82,7 +82,8 @@ impl Worker {
             let mut rpc_client = create_grpc_client(self.fullnode_grpc_address.clone()).await;
 
             // 1. Fetch metadata.
-            let file_store_operator = FileStoreOperator::new(self.file_store_bucket_name.clone());
+            let file_store_operator = FileStoreOperator::new(self.file_store_config.clone())
+                .expect("Failed to create file store operator.");
             file_store_operator.verify_storage_bucket_existence().await;
             let mut starting_version = 0;
             let file_store_metadata = file_store_operator.get_file_store_metadata().await;
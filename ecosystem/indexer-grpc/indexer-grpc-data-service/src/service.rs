@@ -87,13 +87,14 @@ impl IndexerStream for DatastreamServer {
         let req = req.into_inner();
         let mut current_version = req.starting_version;
 
-        let file_store_bucket_name = self.server_config.file_store_bucket_name.clone();
+        let file_store_config = self.server_config.file_store_config.clone();
         let redis_client = self.redis_client.clone();
 
         tokio::spawn(async move {
             let conn = redis_client.get_async_connection().await.unwrap();
             let mut cache_operator = CacheOperator::new(conn);
-            let file_store_operator = FileStoreOperator::new(file_store_bucket_name);
+            let file_store_operator = FileStoreOperator::new(file_store_config)
+                .expect("Failed to create file store operator.");
             file_store_operator.verify_storage_bucket_existence().await;
 
             let chain_id = cache_operator.get_chain_id().await.unwrap();
@@ -48,8 +48,8 @@ impl Processor {
             .await
             .expect("Get chain id failed.");
 
-        let file_store_operator =
-            FileStoreOperator::new(self.config.file_store_bucket_name.clone());
+        let file_store_operator = FileStoreOperator::new(self.config.file_store_config.clone())
+            .expect("Failed to create file store operator.");
         file_store_operator.verify_storage_bucket_existence().await;
 
         self.cache_operator = Some(cache_operator);
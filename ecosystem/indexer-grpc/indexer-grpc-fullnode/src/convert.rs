@@ -563,20 +563,27 @@ pub fn convert_account_signature(
         AccountSignature::MultiEd25519Signature(_) => {
             transaction::account_signature::Type::MultiEd25519
         },
+        // secp256r1 ECDSA signatures are not part of the protobuf schema yet.
+        AccountSignature::Secp256r1EcdsaSignature(_) | AccountSignature::WebAuthnSignature(_) => {
+            transaction::account_signature::Type::NotSet
+        },
     };
     let signature = match account_signature {
-        AccountSignature::Ed25519Signature(s) => {
-            transaction::account_signature::Signature::Ed25519(convert_ed25519_signature(s))
-        },
+        AccountSignature::Ed25519Signature(s) => Some(
+            transaction::account_signature::Signature::Ed25519(convert_ed25519_signature(s)),
+        ),
         AccountSignature::MultiEd25519Signature(s) => {
-            transaction::account_signature::Signature::MultiEd25519(
+            Some(transaction::account_signature::Signature::MultiEd25519(
                 convert_multi_ed25519_signature(s),
-            )
+            ))
+        },
+        AccountSignature::Secp256r1EcdsaSignature(_) | AccountSignature::WebAuthnSignature(_) => {
+            None
         },
     };
     transaction::AccountSignature {
         r#type: r#type as i32,
-        signature: Some(signature),
+        signature,
     }
 }
 
@@ -593,16 +600,20 @@ pub fn convert_transaction_signature(
             transaction::signature::Type::MultiEd25519
         },
         TransactionSignature::MultiAgentSignature(_) => transaction::signature::Type::MultiAgent,
+        // secp256r1 ECDSA and fee payer signatures are not part of the protobuf schema yet.
+        TransactionSignature::Secp256r1EcdsaSignature(_)
+        | TransactionSignature::WebAuthnSignature(_)
+        | TransactionSignature::FeePayerSignature(_) => transaction::signature::Type::NotSet,
     };
 
     let signature = match signature {
-        TransactionSignature::Ed25519Signature(s) => {
-            transaction::signature::Signature::Ed25519(convert_ed25519_signature(s))
-        },
-        TransactionSignature::MultiEd25519Signature(s) => {
-            transaction::signature::Signature::MultiEd25519(convert_multi_ed25519_signature(s))
-        },
-        TransactionSignature::MultiAgentSignature(s) => {
+        TransactionSignature::Ed25519Signature(s) => Some(
+            transaction::signature::Signature::Ed25519(convert_ed25519_signature(s)),
+        ),
+        TransactionSignature::MultiEd25519Signature(s) => Some(
+            transaction::signature::Signature::MultiEd25519(convert_multi_ed25519_signature(s)),
+        ),
+        TransactionSignature::MultiAgentSignature(s) => Some(
             transaction::signature::Signature::MultiAgent(transaction::MultiAgentSignature {
                 sender: Some(convert_account_signature(&s.sender)),
                 secondary_signer_addresses: s
@@ -615,13 +626,16 @@ pub fn convert_transaction_signature(
                     .iter()
                     .map(convert_account_signature)
                     .collect(),
-            })
-        },
+            }),
+        ),
+        TransactionSignature::Secp256r1EcdsaSignature(_)
+        | TransactionSignature::WebAuthnSignature(_)
+        | TransactionSignature::FeePayerSignature(_) => None,
     };
 
     Some(transaction::Signature {
         r#type: r#type as i32,
-        signature: Some(signature),
+        signature,
     })
 }
 
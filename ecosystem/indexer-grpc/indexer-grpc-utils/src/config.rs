@@ -16,12 +16,67 @@ pub struct IndexerGrpcConfig {
     pub data_service_grpc_listen_address: Option<String>,
     /// Redis address, e.g. "127.0.0.1:6379".
     pub redis_address: String,
-    /// File store bucket name, e.g., "indexer-grpc-file-store".
-    pub file_store_bucket_name: String,
+    /// File store to read and write transactions files, e.g., a GCS bucket.
+    #[serde(default)]
+    pub file_store_config: IndexerGrpcFileStoreConfig,
+    /// Deprecated, use `file_store_config` instead. GCS bucket name of the file store, e.g.,
+    /// "indexer-grpc-file-store", kept for the configs written before other stores were supported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_store_bucket_name: Option<String>,
     /// Health check port.
     pub health_check_port: u16,
 }
 
+/// File store configuration, tagged by `file_store_type`, e.g.,
+/// ```yaml
+/// file_store_config:
+///   file_store_type: GcsFileStore
+///   gcs_file_store_bucket_name: indexer-grpc-file-store
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "file_store_type")]
+pub enum IndexerGrpcFileStoreConfig {
+    GcsFileStore(GcsFileStore),
+    LocalFileStore(LocalFileStore),
+    S3FileStore(S3FileStore),
+}
+
+impl Default for IndexerGrpcFileStoreConfig {
+    fn default() -> Self {
+        Self::GcsFileStore(GcsFileStore::default())
+    }
+}
+
+/// Google Cloud Storage bucket, accessed with the service account in env var `SERVICE_ACCOUNT`.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct GcsFileStore {
+    /// Bucket name, e.g., "indexer-grpc-file-store".
+    pub gcs_file_store_bucket_name: String,
+}
+
+/// Local directory, e.g., for on-prem deployments and tests.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LocalFileStore {
+    /// Path of the directory, which has to exist, e.g., "/data/indexer-grpc-file-store".
+    pub local_file_store_path: PathBuf,
+}
+
+/// Bucket of an S3 compatible object store, e.g., AWS S3 or MinIO.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct S3FileStore {
+    /// Endpoint of the store, e.g., "https://s3.us-west-2.amazonaws.com". The bucket is addressed
+    /// path-style, i.e., "<endpoint>/<bucket>/<file name>".
+    pub s3_endpoint: String,
+    /// Region of the bucket, e.g., "us-west-2".
+    pub s3_region: String,
+    /// Bucket name, e.g., "indexer-grpc-file-store".
+    pub s3_bucket_name: String,
+    /// Defaults to env var `AWS_ACCESS_KEY_ID`.
+    pub s3_access_key_id: Option<String>,
+    /// Defaults to env var `AWS_SECRET_ACCESS_KEY`.
+    pub s3_secret_access_key: Option<String>,
+}
+
 /// Indexer GRPC Processor configuration. This is to configure the processors,
 /// e.g., `default_processor` to process data from Indexer GRPC.
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
@@ -45,7 +100,21 @@ pub struct IndexerGrpcProcessorConfig {
 
 impl IndexerGrpcConfig {
     pub fn load(path: PathBuf) -> Result<Self, anyhow::Error> {
-        load::<Self>(path)
+        let mut config = load::<Self>(path)?;
+        if let Some(bucket_name) = config.file_store_bucket_name.take() {
+            anyhow::ensure!(
+                matches!(
+                    &config.file_store_config,
+                    IndexerGrpcFileStoreConfig::GcsFileStore(gcs)
+                        if gcs.gcs_file_store_bucket_name.is_empty()
+                ),
+                "Only one of file_store_bucket_name and file_store_config can be set."
+            );
+            config.file_store_config = IndexerGrpcFileStoreConfig::GcsFileStore(GcsFileStore {
+                gcs_file_store_bucket_name: bucket_name,
+            });
+        }
+        Ok(config)
     }
 }
 
@@ -80,3 +149,42 @@ pub fn load<T: for<'de> serde::Deserialize<'de>>(path: PathBuf) -> Result<T, any
         )
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn load_from_str(contents: &str) -> anyhow::Result<IndexerGrpcConfig> {
+        let path = aptos_temppath::TempPath::new();
+        File::create(path.path())?.write_all(contents.as_bytes())?;
+        IndexerGrpcConfig::load(path.path().to_path_buf())
+    }
+
+    #[test]
+    fn load_legacy_file_store_bucket_name() {
+        let config = load_from_str(
+            "redis_address: 127.0.0.1:6379\n\
+            file_store_bucket_name: indexer-grpc-file-store\n\
+            health_check_port: 8081\n",
+        )
+        .unwrap();
+        assert!(config.file_store_bucket_name.is_none());
+        match config.file_store_config {
+            IndexerGrpcFileStoreConfig::GcsFileStore(gcs) => {
+                assert_eq!(gcs.gcs_file_store_bucket_name, "indexer-grpc-file-store")
+            },
+            _ => panic!("Expected a GCS file store."),
+        }
+
+        assert!(load_from_str(
+            "redis_address: 127.0.0.1:6379\n\
+            file_store_bucket_name: indexer-grpc-file-store\n\
+            file_store_config:\n  \
+              file_store_type: LocalFileStore\n  \
+              local_file_store_path: /data/indexer-grpc-file-store\n\
+            health_check_port: 8081\n",
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{config::GcsFileStore as GcsFileStoreConfig, file_store_operator::FileStore};
+use async_trait::async_trait;
+use cloud_storage::{Bucket, Object};
+
+/// File store in a Google Cloud Storage bucket.
+pub(super) struct GcsFileStore {
+    bucket_name: String,
+}
+
+impl GcsFileStore {
+    pub fn new(config: GcsFileStoreConfig) -> Self {
+        Self {
+            bucket_name: config.gcs_file_store_bucket_name,
+        }
+    }
+}
+
+#[async_trait]
+impl FileStore for GcsFileStore {
+    fn store_name(&self) -> String {
+        self.bucket_name.clone()
+    }
+
+    async fn verify_existence(&self) -> anyhow::Result<()> {
+        Bucket::read(&self.bucket_name).await?;
+        Ok(())
+    }
+
+    async fn get_file(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        match Object::download(&self.bucket_name, name).await {
+            Ok(file) => Ok(Some(file)),
+            Err(cloud_storage::Error::Other(err)) if err.contains("No such object: ") => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn put_file(
+        &self,
+        name: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> anyhow::Result<()> {
+        Object::create(&self.bucket_name, content, name, content_type).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{config::LocalFileStore as LocalFileStoreConfig, file_store_operator::FileStore};
+use anyhow::Context;
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// File store in a local directory, with files at the same relative paths as in a bucket.
+pub(super) struct LocalFileStore {
+    path: PathBuf,
+}
+
+impl LocalFileStore {
+    pub fn new(config: LocalFileStoreConfig) -> Self {
+        Self {
+            path: config.local_file_store_path,
+        }
+    }
+}
+
+#[async_trait]
+impl FileStore for LocalFileStore {
+    fn store_name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    async fn verify_existence(&self) -> anyhow::Result<()> {
+        let metadata = tokio::fs::metadata(&self.path)
+            .await
+            .with_context(|| format!("Failed to access {}", self.path.display()))?;
+        anyhow::ensure!(
+            metadata.is_dir(),
+            "{} is not a directory.",
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    async fn get_file(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self.path.join(name);
+        match tokio::fs::read(&path).await {
+            Ok(file) => Ok(Some(file)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    async fn put_file(
+        &self,
+        name: &str,
+        content: Vec<u8>,
+        _content_type: &str,
+    ) -> anyhow::Result<()> {
+        let path = self.path.join(name);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write to a temporary file first, so readers never see a partially written file.
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, content)
+            .await
+            .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .with_context(|| format!("Failed to rename to {}", path.display()))?;
+        Ok(())
+    }
+}
@@ -1,10 +1,17 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{constants::BLOB_STORAGE_SIZE, EncodedTransactionWithVersion};
-use cloud_storage::{Bucket, Object};
+mod gcs;
+mod local;
+mod s3;
+
+use crate::{
+    config::IndexerGrpcFileStoreConfig, constants::BLOB_STORAGE_SIZE, EncodedTransactionWithVersion,
+};
+use async_trait::async_trait;
 use itertools::{any, Itertools};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 const FILE_FOLDER_NAME: &str = "files";
 const METADATA_FILE_NAME: &str = "metadata.json";
@@ -48,19 +55,56 @@ impl FileStoreMetadata {
     }
 }
 
+/// FileStore is where the files of the file store live, e.g., a GCS bucket or a local directory.
+/// Files are addressed by names like "files/1000.json", relative to the root of the store.
+#[async_trait]
+pub trait FileStore: Send + Sync {
+    /// Name of the store for logging, e.g., the bucket name.
+    fn store_name(&self) -> String;
+
+    /// Verifies the store exists and is accessible.
+    async fn verify_existence(&self) -> anyhow::Result<()>;
+
+    /// Gets the content of a file; returns None if the file doesn't exist.
+    async fn get_file(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Creates or overwrites a file with the content.
+    async fn put_file(
+        &self,
+        name: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> anyhow::Result<()>;
+}
+
+/// Creates the file store specified in the config.
+pub fn create_file_store(config: IndexerGrpcFileStoreConfig) -> anyhow::Result<Arc<dyn FileStore>> {
+    Ok(match config {
+        IndexerGrpcFileStoreConfig::GcsFileStore(gcs) => Arc::new(gcs::GcsFileStore::new(gcs)),
+        IndexerGrpcFileStoreConfig::LocalFileStore(local) => {
+            Arc::new(local::LocalFileStore::new(local))
+        },
+        IndexerGrpcFileStoreConfig::S3FileStore(s3) => Arc::new(s3::S3FileStore::new(s3)?),
+    })
+}
+
 /// The file store operator is stateless and the state should be handled by the caller, e.g., current version.
 /// The only state it maintains is the latest metadata update timestamp.
 /// The file store operator is not thread safe and is intended to be used in a single thread.
 pub struct FileStoreOperator {
-    bucket_name: String,
+    file_store: Arc<dyn FileStore>,
     /// The timestamp of the latest metadata update; this is to avoid too frequent metadata update.
     latest_metadata_update_timestamp: std::time::Instant,
 }
 
 impl FileStoreOperator {
-    pub fn new(bucket_name: String) -> Self {
+    pub fn new(config: IndexerGrpcFileStoreConfig) -> anyhow::Result<Self> {
+        Ok(Self::new_with_file_store(create_file_store(config)?))
+    }
+
+    pub fn new_with_file_store(file_store: Arc<dyn FileStore>) -> Self {
         Self {
-            bucket_name,
+            file_store,
             latest_metadata_update_timestamp: std::time::Instant::now(),
         }
     }
@@ -68,11 +112,12 @@ impl FileStoreOperator {
     /// Bootstraps the file store operator. This is required before any other operations.
     pub async fn verify_storage_bucket_existence(&self) {
         aptos_logger::info!(
-            bucket_name = self.bucket_name,
+            file_store_name = self.file_store.store_name(),
             "Before file store operator starts, verify the bucket exists."
         );
         // Verifies the bucket exists.
-        Bucket::read(&self.bucket_name)
+        self.file_store
+            .verify_existence()
             .await
             .expect("Failed to read bucket.");
     }
@@ -81,8 +126,8 @@ impl FileStoreOperator {
     pub async fn get_transactions(&self, version: u64) -> anyhow::Result<Vec<String>> {
         let batch_start_version = version / BLOB_STORAGE_SIZE as u64 * BLOB_STORAGE_SIZE as u64;
         let current_file_name = generate_blob_name(batch_start_version);
-        match Object::download(&self.bucket_name, current_file_name.as_str()).await {
-            Ok(file) => {
+        match self.file_store.get_file(current_file_name.as_str()).await {
+            Ok(Some(file)) => {
                 let file: TransactionsFile =
                     serde_json::from_slice(&file).expect("Expected file to be valid JSON.");
                 Ok(file
@@ -91,15 +136,8 @@ impl FileStoreOperator {
                     .skip((version % BLOB_STORAGE_SIZE as u64) as usize)
                     .collect())
             },
-            Err(cloud_storage::Error::Other(err)) => {
-                if err.contains("No such object: ") {
-                    anyhow::bail!("[Indexer File] Transactions file not found. Gap might happen between cache and file store. {}", err)
-                } else {
-                    anyhow::bail!(
-                        "[Indexer File] Error happens when transaction file. {}",
-                        err
-                    );
-                }
+            Ok(None) => {
+                anyhow::bail!("[Indexer File] Transactions file not found. Gap might happen between cache and file store. {}", current_file_name)
             },
             Err(err) => {
                 anyhow::bail!(
@@ -112,27 +150,18 @@ impl FileStoreOperator {
 
     /// Gets the metadata from the file store. Operator will panic if error happens when accessing the metadata file(except not found).
     pub async fn get_file_store_metadata(&self) -> Option<FileStoreMetadata> {
-        match Object::download(&self.bucket_name, METADATA_FILE_NAME).await {
-            Ok(metadata) => {
+        match self.file_store.get_file(METADATA_FILE_NAME).await {
+            Ok(Some(metadata)) => {
                 let metadata: FileStoreMetadata =
                     serde_json::from_slice(&metadata).expect("Expected metadata to be valid JSON.");
                 Some(metadata)
             },
-            Err(cloud_storage::Error::Other(err)) => {
-                if err.contains("No such object: ") {
-                    // Metadata is not found.
-                    None
-                } else {
-                    panic!(
-                        "[Indexer File] Error happens when accessing metadata file. {}",
-                        err
-                    );
-                }
-            },
-            Err(e) => {
+            // Metadata is not found.
+            Ok(None) => None,
+            Err(err) => {
                 panic!(
                     "[Indexer File] Error happens when accessing metadata file. {}",
-                    e
+                    err
                 );
             },
         }
@@ -143,30 +172,20 @@ impl FileStoreOperator {
         &mut self,
         expected_chain_id: u64,
     ) -> anyhow::Result<FileStoreMetadata> {
-        match Object::download(&self.bucket_name, METADATA_FILE_NAME).await {
-            Ok(metadata) => {
+        match self.file_store.get_file(METADATA_FILE_NAME).await? {
+            Some(metadata) => {
                 let metadata: FileStoreMetadata =
                     serde_json::from_slice(&metadata).expect("Expected metadata to be valid JSON.");
                 anyhow::ensure!(metadata.chain_id == expected_chain_id, "Chain ID mismatch.");
                 Ok(metadata)
             },
-            Err(cloud_storage::Error::Other(err)) => {
-                let is_file_missing = err.contains("No such object: ");
-                if is_file_missing {
-                    // If the metadata is not found, it means the file store is empty.
-                    self.update_file_store_metadata(expected_chain_id, 0)
-                        .await
-                        .expect("[Indexer File] Update metadata failed.");
-                    Ok(FileStoreMetadata::new(expected_chain_id, 0))
-                } else {
-                    // If not in write mode, the metadata must exist.
-                    Err(anyhow::Error::msg(format!(
-                        "Metadata not found or file store operator is not in write mode. {}",
-                        err
-                    )))
-                }
+            None => {
+                // If the metadata is not found, it means the file store is empty.
+                self.update_file_store_metadata(expected_chain_id, 0)
+                    .await
+                    .expect("[Indexer File] Update metadata failed.");
+                Ok(FileStoreMetadata::new(expected_chain_id, 0))
             },
-            Err(err) => Err(anyhow::Error::from(err)),
         }
     }
 
@@ -182,20 +201,15 @@ impl FileStoreOperator {
 
         let metadata = FileStoreMetadata::new(chain_id, version);
         // If the metadata is not updated, the indexer will be restarted.
-        match Object::create(
-            self.bucket_name.as_str(),
-            serde_json::to_vec(&metadata).unwrap(),
-            METADATA_FILE_NAME,
-            JSON_FILE_TYPE,
-        )
-        .await
-        {
-            Ok(_) => {
-                self.latest_metadata_update_timestamp = std::time::Instant::now();
-                Ok(())
-            },
-            Err(err) => Err(anyhow::Error::from(err)),
-        }
+        self.file_store
+            .put_file(
+                METADATA_FILE_NAME,
+                serde_json::to_vec(&metadata).unwrap(),
+                JSON_FILE_TYPE,
+            )
+            .await?;
+        self.latest_metadata_update_timestamp = std::time::Instant::now();
+        Ok(())
     }
 
     /// Uploads the transactions to the file store. The transactions are grouped into batches of BLOB_STORAGE_SIZE.
@@ -219,21 +233,17 @@ impl FileStoreOperator {
 
         // Split the transactions into batches of BLOB_STORAGE_SIZE.
         for i in transactions.chunks(BLOB_STORAGE_SIZE) {
-            let bucket_name = self.bucket_name.clone();
+            let file_store = self.file_store.clone();
             let current_batch = i.iter().cloned().collect_vec();
             let transactions_file = build_transactions_file(current_batch).unwrap();
             let task = tokio::spawn(async move {
-                match Object::create(
-                    bucket_name.clone().as_str(),
-                    serde_json::to_vec(&transactions_file).unwrap(),
-                    generate_blob_name(transactions_file.starting_version).as_str(),
-                    JSON_FILE_TYPE,
-                )
-                .await
-                {
-                    Ok(_) => Ok(()),
-                    Err(err) => Err(anyhow::Error::from(err)),
-                }
+                file_store
+                    .put_file(
+                        generate_blob_name(transactions_file.starting_version).as_str(),
+                        serde_json::to_vec(&transactions_file).unwrap(),
+                        JSON_FILE_TYPE,
+                    )
+                    .await
             });
             tasks.push(task);
         }
@@ -325,4 +335,32 @@ mod tests {
 
         assert!(build_transactions_file(transactions).is_err());
     }
+
+    #[tokio::test]
+    async fn verify_local_file_store_round_trip() {
+        let dir = aptos_temppath::TempPath::new();
+        dir.create_as_dir().unwrap();
+        let mut operator = FileStoreOperator::new(IndexerGrpcFileStoreConfig::LocalFileStore(
+            crate::config::LocalFileStore {
+                local_file_store_path: dir.path().to_path_buf(),
+            },
+        ))
+        .unwrap();
+        operator.verify_storage_bucket_existence().await;
+        assert!(operator.get_file_store_metadata().await.is_none());
+        assert!(operator.get_transactions(0).await.is_err());
+
+        let transactions = (0..2 * BLOB_STORAGE_SIZE as u64)
+            .map(|version| (version.to_string(), version))
+            .collect_vec();
+        operator.upload_transactions(1, transactions).await.unwrap();
+
+        let first_batch = operator.get_transactions(0).await.unwrap();
+        assert_eq!(first_batch.len(), BLOB_STORAGE_SIZE);
+        assert_eq!(first_batch[0], "0");
+        let second_batch = operator.get_transactions(1_500).await.unwrap();
+        assert_eq!(second_batch.len(), 500);
+        assert_eq!(second_batch[0], "1500");
+        assert!(operator.get_transactions(2_000).await.is_err());
+    }
 }
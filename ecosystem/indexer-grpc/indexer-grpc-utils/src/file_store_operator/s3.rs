@@ -0,0 +1,147 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{config::S3FileStore as S3FileStoreConfig, file_store_operator::FileStore};
+use anyhow::Context;
+use aptos_sigv4 as sigv4;
+use async_trait::async_trait;
+use reqwest::{Method, StatusCode};
+
+/// File store in a bucket of an S3 compatible object store, with requests signed by AWS signature
+/// version 4.
+pub(super) struct S3FileStore {
+    client: reqwest::Client,
+    endpoint: String,
+    region: String,
+    bucket_name: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3FileStore {
+    pub fn new(config: S3FileStoreConfig) -> anyhow::Result<Self> {
+        let access_key_id = config
+            .s3_access_key_id
+            .or_else(|| std::env::var("AWS_ACCESS_KEY_ID").ok())
+            .context("S3 access key id is neither configured nor in env var AWS_ACCESS_KEY_ID.")?;
+        let secret_access_key = config
+            .s3_secret_access_key
+            .or_else(|| std::env::var("AWS_SECRET_ACCESS_KEY").ok())
+            .context(
+                "S3 secret access key is neither configured nor in env var AWS_SECRET_ACCESS_KEY.",
+            )?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint: config.s3_endpoint.trim_end_matches('/').to_string(),
+            region: config.s3_region,
+            bucket_name: config.s3_bucket_name,
+            access_key_id,
+            secret_access_key,
+        })
+    }
+
+    /// Sends a request for the object `name`, or for the bucket itself if `name` is empty.
+    async fn send(
+        &self,
+        method: Method,
+        name: &str,
+        content: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let path = if name.is_empty() {
+            format!("/{}", self.bucket_name)
+        } else {
+            format!("/{}/{}", self.bucket_name, name)
+        };
+        let encoded_path = sigv4::encode_path(&path);
+        let url = format!("{}{}", self.endpoint, encoded_path);
+        let host = reqwest::Url::parse(&url)?
+            .host_str()
+            .map(|host| host.to_string())
+            .context("S3 endpoint has no host.")?;
+        let host = match reqwest::Url::parse(&url)?.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        };
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let content_sha256 = sigv4::sha256_hex(&content);
+        let headers = [
+            ("host", host),
+            ("x-amz-content-sha256", content_sha256),
+            ("x-amz-date", amz_date),
+        ];
+        let authorization = sigv4::authorization(
+            &self.access_key_id,
+            &self.secret_access_key,
+            &self.region,
+            method.as_str(),
+            &encoded_path,
+            "",
+            &headers,
+        );
+
+        let mut request = self
+            .client
+            .request(method, &url)
+            .header("authorization", authorization);
+        for (name, value) in &headers[1..] {
+            request = request.header(*name, value);
+        }
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+        Ok(request.body(content).send().await?)
+    }
+}
+
+#[async_trait]
+impl FileStore for S3FileStore {
+    fn store_name(&self) -> String {
+        self.bucket_name.clone()
+    }
+
+    async fn verify_existence(&self) -> anyhow::Result<()> {
+        let response = self.send(Method::HEAD, "", vec![], None).await?;
+        anyhow::ensure!(
+            response.status().is_success(),
+            "Failed to access bucket {}: {}",
+            self.bucket_name,
+            response.status()
+        );
+        Ok(())
+    }
+
+    async fn get_file(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let response = self.send(Method::GET, name, vec![], None).await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.bytes().await?.to_vec())),
+            status => anyhow::bail!(
+                "Failed to get {}: {}, {}",
+                name,
+                status,
+                response.text().await.unwrap_or_default()
+            ),
+        }
+    }
+
+    async fn put_file(
+        &self,
+        name: &str,
+        content: Vec<u8>,
+        content_type: &str,
+    ) -> anyhow::Result<()> {
+        let response = self
+            .send(Method::PUT, name, content, Some(content_type))
+            .await?;
+        let status = response.status();
+        anyhow::ensure!(
+            status.is_success(),
+            "Failed to put {}: {}, {}",
+            name,
+            status,
+            response.text().await.unwrap_or_default()
+        );
+        Ok(())
+    }
+}
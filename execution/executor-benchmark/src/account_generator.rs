@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_sdk::{move_types::account_address::AccountAddress, types::LocalAccount};
-use rand::{rngs::StdRng, RngCore, SeedableRng};
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
 use std::{collections::VecDeque, sync::mpsc};
 
 type Seed = [u8; 32];
@@ -111,4 +111,21 @@ impl AccountCache {
 
         (sender, receivers)
     }
+
+    /// Like `get_random_transfer_batch`, but the receivers are drawn from the first
+    /// `num_hot_receivers` accounts only, so that the transfers contend on them.
+    pub fn get_random_hot_transfer_batch(
+        &mut self,
+        batch_size: usize,
+        num_hot_receivers: usize,
+    ) -> (&mut LocalAccount, Vec<AccountAddress>) {
+        let num_hot_receivers = num_hot_receivers.clamp(1, self.accounts.len());
+        let receivers = (0..batch_size)
+            .map(|_| self.accounts[self.rng.gen_range(0, num_hot_receivers)].address())
+            .collect();
+        let sender_idx = self.rng.gen_range(0, self.accounts.len());
+        let sender = &mut self.accounts[sender_idx];
+
+        (sender, receivers)
+    }
 }
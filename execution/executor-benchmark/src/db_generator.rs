@@ -16,6 +16,12 @@ use aptos_executor::{
     db_bootstrapper::{generate_waypoint, maybe_bootstrap},
 };
 use aptos_storage_interface::DbReaderWriter;
+use aptos_types::{
+    on_chain_config::{FeatureFlag, Features, OnChainConfig},
+    state_store::state_key::StateKey,
+    transaction::{ChangeSet, NoOpChangeSetChecker, Transaction, WriteSetPayload},
+    write_set::WriteOp,
+};
 use aptos_vm::AptosVM;
 use std::{fs, path::Path};
 
@@ -27,6 +33,7 @@ pub fn run<V>(
     storage_pruner_config: PrunerConfig,
     verify_sequence_numbers: bool,
     use_state_kv_db: bool,
+    enable_block_partitioning: bool,
 ) where
     V: TransactionBlockExecutor<BenchmarkTransaction> + 'static,
 {
@@ -38,7 +45,7 @@ pub fn run<V>(
     // create if not exists
     fs::create_dir_all(db_dir.as_ref()).unwrap();
 
-    bootstrap_with_genesis(&db_dir, use_state_kv_db);
+    bootstrap_with_genesis(&db_dir, use_state_kv_db, enable_block_partitioning);
 
     println!(
         "Finished empty DB creation, DB dir: {}. Creating accounts now...",
@@ -57,8 +64,16 @@ pub fn run<V>(
     );
 }
 
-fn bootstrap_with_genesis(db_dir: impl AsRef<Path>, use_state_kv_db: bool) {
+fn bootstrap_with_genesis(
+    db_dir: impl AsRef<Path>,
+    use_state_kv_db: bool,
+    enable_block_partitioning: bool,
+) {
     let (config, _genesis_key) = aptos_genesis::test_utils::test_config();
+    let mut genesis_txn = get_genesis_txn(&config).unwrap().clone();
+    if enable_block_partitioning {
+        genesis_txn = enable_feature(genesis_txn, FeatureFlag::BLOCK_PARTITIONING);
+    }
 
     let mut rocksdb_configs = RocksdbConfigs::default();
     rocksdb_configs.state_merkle_db_config.max_open_files = -1;
@@ -70,6 +85,7 @@ fn bootstrap_with_genesis(db_dir: impl AsRef<Path>, use_state_kv_db: bool) {
             NO_OP_STORAGE_PRUNER_CONFIG,
             rocksdb_configs,
             false, /* indexer */
+            false, /* event type index */
             BUFFERED_STATE_TARGET_ITEMS,
             DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         )
@@ -77,6 +93,39 @@ fn bootstrap_with_genesis(db_dir: impl AsRef<Path>, use_state_kv_db: bool) {
     );
 
     // Bootstrap db with genesis
-    let waypoint = generate_waypoint::<AptosVM>(&db_rw, get_genesis_txn(&config).unwrap()).unwrap();
-    maybe_bootstrap::<AptosVM>(&db_rw, get_genesis_txn(&config).unwrap(), waypoint).unwrap();
+    let waypoint = generate_waypoint::<AptosVM>(&db_rw, &genesis_txn).unwrap();
+    maybe_bootstrap::<AptosVM>(&db_rw, &genesis_txn, waypoint).unwrap();
+}
+
+/// Returns the genesis transaction with the given feature enabled, as if genesis had enabled it
+/// along with the default ones.
+fn enable_feature(genesis_txn: Transaction, flag: FeatureFlag) -> Transaction {
+    let change_set = match genesis_txn {
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(change_set)) => change_set,
+        _ => panic!("Genesis transaction should be a direct write set."),
+    };
+    let (write_set, events) = change_set.into_inner();
+    let mut write_set = write_set.into_mut();
+    let features_key = StateKey::access_path(Features::access_path().unwrap());
+    let mut features: Features = bcs::from_bytes(
+        write_set
+            .get(&features_key)
+            .and_then(WriteOp::bytes)
+            .expect("Genesis should create the features."),
+    )
+    .unwrap();
+
+    let byte_index = flag as usize / 8;
+    if features.features.len() <= byte_index {
+        features.features.resize(byte_index + 1, 0);
+    }
+    features.features[byte_index] |= 1 << (flag as u64 % 8);
+    write_set.insert((
+        features_key,
+        WriteOp::Creation(bcs::to_bytes(&features).unwrap()),
+    ));
+
+    Transaction::GenesisTransaction(WriteSetPayload::Direct(
+        ChangeSet::new(write_set.freeze().unwrap(), events, &NoOpChangeSetChecker).unwrap(),
+    ))
 }
@@ -25,6 +25,7 @@ use aptos_jellyfish_merkle::metrics::{
 };
 use aptos_logger::info;
 use aptos_storage_interface::DbReaderWriter;
+use read_write_set_dynamic::NormalizedReadWriteSetAnalysis;
 use std::{fs, path::Path, time::Instant};
 
 pub fn init_db_and_executor<V>(
@@ -40,6 +41,7 @@ where
             config.storage.storage_pruner_config,
             config.storage.rocksdb_configs,
             false,
+            false, /* event type index */
             config.storage.buffered_state_target_items,
             config.storage.max_num_nodes_per_lru_cache_shard,
         )
@@ -61,11 +63,21 @@ fn create_checkpoint(source_dir: impl AsRef<Path>, checkpoint_dir: impl AsRef<Pa
     AptosDB::create_checkpoint(source_dir, checkpoint_dir).expect("db checkpoint creation fails.");
 }
 
+/// Runs the read/write set analysis over the framework, for `AptosVM` to reorder blocks by
+/// predicted conflicts with.
+pub fn analyze_framework_read_write_sets() -> NormalizedReadWriteSetAnalysis {
+    let modules = aptos_cached_packages::head_release_bundle().compiled_modules();
+    read_write_set::analyze(modules.iter())
+        .expect("Framework read/write set analysis should succeed.")
+        .normalize_all_scripts(aptos_vm::read_write_set_analysis::add_on_functions_list())
+}
+
 /// Runs the benchmark with given parameters.
 pub fn run_benchmark<V>(
     block_size: usize,
     num_transfer_blocks: usize,
     transactions_per_sender: usize,
+    num_hot_receivers: Option<usize>,
     source_dir: impl AsRef<Path>,
     checkpoint_dir: impl AsRef<Path>,
     verify_sequence_numbers: bool,
@@ -95,7 +107,12 @@ pub fn run_benchmark<V>(
     );
 
     let start_time = Instant::now();
-    generator.run_transfer(block_size, num_transfer_blocks, transactions_per_sender);
+    generator.run_transfer(
+        block_size,
+        num_transfer_blocks,
+        transactions_per_sender,
+        num_hot_receivers,
+    );
     generator.drop_sender();
     pipeline.join();
 
@@ -228,12 +245,14 @@ mod tests {
             NO_OP_STORAGE_PRUNER_CONFIG, /* prune_window */
             true,
             false,
+            false,
         );
 
         super::run_benchmark::<AptosVM>(
             6, /* block_size */
             5, /* num_transfer_blocks */
             2, /* transactions per sender */
+            None,
             storage_dir.as_ref(),
             checkpoint_dir,
             true,
@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_block_executor::conflict_report::{set_conflict_reporting, take_conflict_reports};
 use aptos_config::config::{
     EpochSnapshotPrunerConfig, LedgerPrunerConfig, PrunerConfig, StateKvPrunerConfig,
     StateMerklePrunerConfig,
@@ -124,6 +125,17 @@ struct Opt {
 
     #[structopt(long)]
     use_fake_executor: bool,
+
+    /// Reorder blocks by the conflicts predicted by the read/write set analysis of the framework
+    /// before executing them. Only effective while the `BLOCK_PARTITIONING` feature is enabled
+    /// on chain, which it is at genesis if the DB was created with this flag too.
+    #[structopt(long)]
+    partition_blocks: bool,
+
+    /// Write a report of the conflicts between the transactions of each block executed in
+    /// parallel to the given file, as JSON.
+    #[structopt(long, parse(from_os_str))]
+    conflict_report: Option<PathBuf>,
 }
 
 impl Opt {
@@ -167,6 +179,12 @@ enum Command {
 
         #[structopt(long, parse(from_os_str))]
         checkpoint_dir: PathBuf,
+
+        #[structopt(
+            long,
+            about = "Send all the transfers to this many accounts, to benchmark contended workloads"
+        )]
+        num_hot_receivers: Option<usize>,
     },
     AddAccounts {
         #[structopt(long, parse(from_os_str))]
@@ -201,17 +219,20 @@ where
                 opt.pruner_opt.pruner_config(),
                 opt.verify_sequence_numbers,
                 opt.use_state_kv_db,
+                opt.partition_blocks,
             );
         },
         Command::RunExecutor {
             blocks,
             data_dir,
             checkpoint_dir,
+            num_hot_receivers,
         } => {
             aptos_executor_benchmark::run_benchmark::<E>(
                 opt.block_size,
                 blocks,
                 opt.transactions_per_sender,
+                num_hot_receivers,
                 data_dir,
                 checkpoint_dir,
                 opt.verify_sequence_numbers,
@@ -256,10 +277,34 @@ fn main() {
         .expect("Failed to build rayon global thread pool.");
     AptosVM::set_concurrency_level_once(opt.concurrency_level());
     FakeExecutor::set_concurrency_level_once(opt.concurrency_level());
+    if opt.partition_blocks {
+        AptosVM::set_read_write_set_analysis_once(
+            aptos_executor_benchmark::analyze_framework_read_write_sets(),
+        );
+    }
+
+    let conflict_report = opt.conflict_report.clone();
+    if conflict_report.is_some() {
+        set_conflict_reporting(true);
+    }
 
     if opt.use_fake_executor {
         run::<FakeExecutor>(opt);
     } else {
         run::<AptosVM>(opt);
     }
+
+    if let Some(path) = conflict_report {
+        let reports = take_conflict_reports();
+        std::fs::write(
+            &path,
+            serde_json::to_vec_pretty(&reports).expect("Conflict reports must serialize"),
+        )
+        .expect("Failed to write the conflict report");
+        println!(
+            "Wrote conflict reports of {} blocks to {:?}",
+            reports.len(),
+            path
+        );
+    }
 }
@@ -231,9 +231,15 @@ impl TransactionGenerator {
         block_size: usize,
         num_transfer_blocks: usize,
         transactions_per_sender: usize,
+        num_hot_receivers: Option<usize>,
     ) {
         assert!(self.block_sender.is_some());
-        self.gen_transfer_transactions(block_size, num_transfer_blocks, transactions_per_sender);
+        self.gen_transfer_transactions(
+            block_size,
+            num_transfer_blocks,
+            transactions_per_sender,
+            num_hot_receivers,
+        );
     }
 
     pub fn create_seed_accounts(
@@ -349,23 +355,29 @@ impl TransactionGenerator {
         println!("[{}] done.", now_fmt!());
     }
 
-    /// Generates transactions for random pairs of accounts.
+    /// Generates transactions for random pairs of accounts, or from random accounts to the first
+    /// `num_hot_receivers` accounts if set.
     pub fn gen_transfer_transactions(
         &mut self,
         block_size: usize,
         num_blocks: usize,
         transactions_per_sender: usize,
+        num_hot_receivers: Option<usize>,
     ) {
         for _ in 0..num_blocks {
             // TODO: handle when block_size isn't divisible by transactions_per_sender
             let transactions: Vec<_> = (0..(block_size / transactions_per_sender))
                 .into_iter()
                 .flat_map(|_| {
-                    let (sender, receivers) = self
-                        .accounts_cache
-                        .as_mut()
-                        .unwrap()
-                        .get_random_transfer_batch(transactions_per_sender);
+                    let accounts_cache = self.accounts_cache.as_mut().unwrap();
+                    let (sender, receivers) = match num_hot_receivers {
+                        Some(num_hot_receivers) => accounts_cache
+                            .get_random_hot_transfer_batch(
+                                transactions_per_sender,
+                                num_hot_receivers,
+                            ),
+                        None => accounts_cache.get_random_transfer_batch(transactions_per_sender),
+                    };
                     receivers
                         .into_iter()
                         .map(|receiver| {
@@ -23,6 +23,7 @@ use aptos_executor_types::{
 };
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
+use aptos_scratchpad::FrozenSparseMerkleTree;
 use aptos_state_view::StateViewId;
 use aptos_storage_interface::{
     cached_state_view::CachedStateView, sync_proof_fetcher::SyncProofFetcher, DbReaderWriter,
@@ -31,6 +32,7 @@ use aptos_storage_interface::{
 use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
+    state_store::state_value::StateValue,
     transaction::{
         Transaction, TransactionInfo, TransactionListWithProof, TransactionOutput,
         TransactionOutputListWithProof, TransactionStatus, Version,
@@ -55,6 +57,33 @@ impl<V: VMExecutor> ChunkExecutor<V> {
         }
     }
 
+    /// Like [`TransactionReplayer::replay`], but instead of executing the transactions and
+    /// verifying the results against the transaction infos before applying the chunk, applies it
+    /// right away and returns the execution verification to be run later. The verifications of
+    /// consecutive chunks don't depend on each other, so they can run concurrently with each other
+    /// and with replaying and committing the following chunks.
+    pub fn replay_and_defer_verification(
+        &self,
+        transactions: Vec<Transaction>,
+        transaction_infos: Vec<TransactionInfo>,
+        write_sets: Vec<WriteSet>,
+        event_vecs: Vec<Vec<ContractEvent>>,
+        verify_execution_mode: &VerifyExecutionMode,
+    ) -> Result<ChunkReplayVerification<V>> {
+        self.maybe_initialize()?;
+        self.inner
+            .read()
+            .as_ref()
+            .expect("not reset")
+            .replay_and_defer_verification(
+                transactions,
+                transaction_infos,
+                write_sets,
+                event_vecs,
+                verify_execution_mode,
+            )
+    }
+
     fn maybe_initialize(&self) -> Result<()> {
         if self.inner.read().is_none() {
             self.reset()?;
@@ -126,13 +155,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
     }
 
     fn state_view(&self, latest_view: &ExecutedTrees) -> Result<CachedStateView> {
-        latest_view.verified_state_view(
-            StateViewId::ChunkExecution {
-                first_version: latest_view.txn_accumulator().num_leaves(),
-            },
-            Arc::clone(&self.db.reader),
-            Arc::new(SyncProofFetcher::new(self.db.reader.clone())),
-        )
+        chunk_state_view(&self.db, latest_view)
     }
 
     fn apply_chunk_output_for_state_sync(
@@ -385,12 +408,65 @@ impl<V: VMExecutor> TransactionReplayer for ChunkExecutor<V> {
 
 impl<V: VMExecutor> TransactionReplayer for ChunkExecutorInner<V> {
     fn replay(
+        &self,
+        transactions: Vec<Transaction>,
+        transaction_infos: Vec<TransactionInfo>,
+        write_sets: Vec<WriteSet>,
+        event_vecs: Vec<Vec<ContractEvent>>,
+        verify_execution_mode: &VerifyExecutionMode,
+    ) -> Result<()> {
+        self.replay_impl(
+            transactions,
+            transaction_infos,
+            write_sets,
+            event_vecs,
+            verify_execution_mode,
+            None,
+        )
+    }
+
+    fn commit(&self) -> Result<Arc<ExecutedChunk>> {
+        self.commit_chunk_impl()
+    }
+}
+
+impl<V: VMExecutor> ChunkExecutorInner<V> {
+    fn replay_and_defer_verification(
+        &self,
+        transactions: Vec<Transaction>,
+        transaction_infos: Vec<TransactionInfo>,
+        write_sets: Vec<WriteSet>,
+        event_vecs: Vec<Vec<ContractEvent>>,
+        verify_execution_mode: &VerifyExecutionMode,
+    ) -> Result<ChunkReplayVerification<V>> {
+        let mut batches_to_verify = Vec::new();
+        self.replay_impl(
+            transactions,
+            transaction_infos,
+            write_sets,
+            event_vecs,
+            verify_execution_mode,
+            Some(&mut batches_to_verify),
+        )?;
+
+        Ok(ChunkReplayVerification {
+            db: self.db.clone(),
+            batches: batches_to_verify,
+            verify_execution_mode: verify_execution_mode.clone(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Replays the chunk, verifying the execution of the transactions before applying them, or
+    /// pushing what is needed to verify it later to `batches_to_verify` if present.
+    fn replay_impl(
         &self,
         mut transactions: Vec<Transaction>,
         mut transaction_infos: Vec<TransactionInfo>,
         mut write_sets: Vec<WriteSet>,
         mut event_vecs: Vec<Vec<ContractEvent>>,
         verify_execution_mode: &VerifyExecutionMode,
+        mut batches_to_verify: Option<&mut Vec<BatchToVerify>>,
     ) -> Result<()> {
         let (_parent_view, mut latest_view) = self.commit_queue.lock().persisted_and_latest_view();
         let chunk_begin = latest_view.num_transactions() as Version;
@@ -425,6 +501,7 @@ impl<V: VMExecutor> TransactionReplayer for ChunkExecutorInner<V> {
                 begin,
                 end,
                 verify_execution_mode,
+                batches_to_verify.as_deref_mut(),
             )?;
         }
 
@@ -432,12 +509,6 @@ impl<V: VMExecutor> TransactionReplayer for ChunkExecutorInner<V> {
         Ok(())
     }
 
-    fn commit(&self) -> Result<Arc<ExecutedChunk>> {
-        self.commit_chunk_impl()
-    }
-}
-
-impl<V: VMExecutor> ChunkExecutorInner<V> {
     fn remove_and_replay_epoch(
         &self,
         executed_chunk: &mut ExecutedChunk,
@@ -449,6 +520,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
         begin_version: Version,
         end_version: Version,
         verify_execution_mode: &VerifyExecutionMode,
+        mut batches_to_verify: Option<&mut Vec<BatchToVerify>>,
     ) -> Result<()> {
         // we try to apply the txns in sub-batches split by known txns to skip and the end of the batch
         let txns_to_skip = verify_execution_mode.txns_to_skip();
@@ -477,8 +549,10 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
             }
 
             // Try to run the transactions with the VM
-            let next_begin = if verify_execution_mode.should_verify() {
-                self.verify_execution(
+            let next_begin = if !verify_execution_mode.should_verify() {
+                batch_end
+            } else if let Some(batches_to_verify) = batches_to_verify.as_deref_mut() {
+                batches_to_verify.push(BatchToVerify::new(
                     latest_view,
                     transactions,
                     transaction_infos,
@@ -486,10 +560,19 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
                     event_vecs,
                     batch_begin,
                     batch_end,
+                ));
+                batch_end
+            } else {
+                verify_execution::<V>(
+                    self.state_view(latest_view)?,
+                    transactions,
+                    transaction_infos,
+                    write_sets,
+                    event_vecs,
+                    batch_begin,
+                    batch_end,
                     verify_execution_mode,
                 )?
-            } else {
-                batch_end
             };
             self.remove_and_apply(
                 executed_chunk,
@@ -507,52 +590,6 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
         Ok(())
     }
 
-    fn verify_execution(
-        &self,
-        latest_view: &mut ExecutedTrees,
-        transactions: &[Transaction],
-        transaction_infos: &[TransactionInfo],
-        write_sets: &[WriteSet],
-        event_vecs: &[Vec<ContractEvent>],
-        begin_version: Version,
-        end_version: Version,
-        verify_execution_mode: &VerifyExecutionMode,
-    ) -> Result<Version> {
-        // Execute transactions.
-        let state_view = self.state_view(latest_view)?;
-        let txns = transactions
-            .iter()
-            .take((end_version - begin_version) as usize)
-            .cloned()
-            .collect();
-
-        let chunk_output = ChunkOutput::by_transaction_execution::<V>(txns, state_view)?;
-        // not `zip_eq`, deliberately
-        for (version, txn_out, txn_info, write_set, events) in multizip((
-            begin_version..end_version,
-            chunk_output.transaction_outputs.iter(),
-            transaction_infos.iter(),
-            write_sets.iter(),
-            event_vecs.iter(),
-        )) {
-            if let Err(err) = txn_out.ensure_match_transaction_info(
-                version,
-                txn_info,
-                Some(write_set),
-                Some(events),
-            ) {
-                if verify_execution_mode.is_lazy_quit() {
-                    error!("(Not quitting right away.) {}", err);
-                    verify_execution_mode.mark_seen_error();
-                    return Ok(version + 1);
-                } else {
-                    return Err(err);
-                }
-            }
-        }
-        Ok(end_version)
-    }
-
     fn remove_and_apply(
         &self,
         executed_chunk: &mut ExecutedChunk,
@@ -565,35 +602,213 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
         end_version: Version,
     ) -> Result<()> {
         let num_txns = (end_version - begin_version) as usize;
-        let txn_infos: Vec<_> = transaction_infos.drain(..num_txns).collect();
-        let txns_and_outputs = multizip((
-            transactions.drain(..num_txns),
-            txn_infos.iter(),
-            write_sets.drain(..num_txns),
-            event_vecs.drain(..num_txns),
-        ))
-        .map(|(txn, txn_info, write_set, events)| {
-            (
-                txn,
-                TransactionOutput::new(
-                    write_set,
-                    events,
-                    txn_info.gas_used(),
-                    TransactionStatus::Keep(txn_info.status().clone()),
-                ),
-            )
-        })
-        .collect();
-
-        let state_view = self.state_view(latest_view)?;
-        let chunk_output = ChunkOutput::by_transaction_output(txns_and_outputs, state_view)?;
-        let (executed_batch, to_discard, to_retry) = chunk_output.apply_to_ledger(latest_view)?;
-        ensure_no_discard(to_discard)?;
-        ensure_no_retry(to_retry)?;
-        executed_batch.ensure_transaction_infos_match(&txn_infos)?;
+        let executed_batch = apply_recorded_outputs(
+            self.state_view(latest_view)?,
+            latest_view,
+            transactions.drain(..num_txns).collect(),
+            transaction_infos.drain(..num_txns).collect(),
+            write_sets.drain(..num_txns).collect(),
+            event_vecs.drain(..num_txns).collect(),
+        )?;
 
         executed_chunk.combine(executed_batch);
         *latest_view = executed_chunk.result_view.clone();
         Ok(())
     }
 }
+
+/// The execution verification of a chunk applied by
+/// [`ChunkExecutor::replay_and_defer_verification`].
+pub struct ChunkReplayVerification<V> {
+    db: DbReaderWriter,
+    batches: Vec<BatchToVerify>,
+    verify_execution_mode: VerifyExecutionMode,
+    _phantom: PhantomData<V>,
+}
+
+impl<V: VMExecutor> ChunkReplayVerification<V> {
+    /// Executes the transactions of the chunk and verifies the results against the transaction
+    /// infos, the same way [`TransactionReplayer::replay`] does before applying the chunk.
+    pub fn verify(self) -> Result<()> {
+        for batch in self.batches {
+            Self::verify_batch(&self.db, batch, &self.verify_execution_mode)?;
+        }
+        Ok(())
+    }
+
+    fn verify_batch(
+        db: &DbReaderWriter,
+        batch: BatchToVerify,
+        verify_execution_mode: &VerifyExecutionMode,
+    ) -> Result<()> {
+        let BatchToVerify {
+            mut parent_view,
+            _frozen_base,
+            mut begin_version,
+            mut transactions,
+            mut transaction_infos,
+            mut write_sets,
+            mut event_vecs,
+        } = batch;
+        let end_version = begin_version + transactions.len() as Version;
+
+        while begin_version < end_version {
+            let next_begin = verify_execution::<V>(
+                chunk_state_view(db, &parent_view)?,
+                &transactions,
+                &transaction_infos,
+                &write_sets,
+                &event_vecs,
+                begin_version,
+                end_version,
+                verify_execution_mode,
+            )?;
+            if next_begin == end_version {
+                break;
+            }
+
+            // Lazy quitting on a mismatch, go on verifying the rest of the batch on top of the
+            // recorded outputs of the transactions up to the mismatching one, like replay does.
+            let num_txns = (next_begin - begin_version) as usize;
+            let executed_txns = apply_recorded_outputs(
+                chunk_state_view(db, &parent_view)?,
+                &parent_view,
+                transactions.drain(..num_txns).collect(),
+                transaction_infos.drain(..num_txns).collect(),
+                write_sets.drain(..num_txns).collect(),
+                event_vecs.drain(..num_txns).collect(),
+            )?;
+            parent_view = executed_txns.result_view;
+            begin_version = next_begin;
+        }
+        Ok(())
+    }
+}
+
+/// A batch of replayed transactions whose execution is yet to be verified.
+struct BatchToVerify {
+    /// The ledger before the batch.
+    parent_view: ExecutedTrees,
+    /// Keeps the in-memory state of `parent_view` from being dropped as later chunks get
+    /// committed, so that the batch can still be executed on top of it.
+    _frozen_base: FrozenSparseMerkleTree<StateValue>,
+    begin_version: Version,
+    transactions: Vec<Transaction>,
+    transaction_infos: Vec<TransactionInfo>,
+    write_sets: Vec<WriteSet>,
+    event_vecs: Vec<Vec<ContractEvent>>,
+}
+
+impl BatchToVerify {
+    fn new(
+        parent_view: &ExecutedTrees,
+        transactions: &[Transaction],
+        transaction_infos: &[TransactionInfo],
+        write_sets: &[WriteSet],
+        event_vecs: &[Vec<ContractEvent>],
+        begin_version: Version,
+        end_version: Version,
+    ) -> Self {
+        let num_txns = (end_version - begin_version) as usize;
+        Self {
+            parent_view: parent_view.clone(),
+            _frozen_base: parent_view.state().current.clone().freeze(),
+            begin_version,
+            transactions: transactions[..num_txns].to_vec(),
+            transaction_infos: transaction_infos[..num_txns].to_vec(),
+            write_sets: write_sets[..num_txns].to_vec(),
+            event_vecs: event_vecs[..num_txns].to_vec(),
+        }
+    }
+}
+
+fn chunk_state_view(db: &DbReaderWriter, latest_view: &ExecutedTrees) -> Result<CachedStateView> {
+    latest_view.verified_state_view(
+        StateViewId::ChunkExecution {
+            first_version: latest_view.txn_accumulator().num_leaves(),
+        },
+        Arc::clone(&db.reader),
+        Arc::new(SyncProofFetcher::new(db.reader.clone())),
+    )
+}
+
+/// Executes the transactions in [begin_version, end_version) on top of `state_view` and verifies
+/// the outputs against the transaction infos, write sets and events. Returns the version to
+/// continue from, which is the one following the first mismatch when lazy quitting.
+fn verify_execution<V: VMExecutor>(
+    state_view: CachedStateView,
+    transactions: &[Transaction],
+    transaction_infos: &[TransactionInfo],
+    write_sets: &[WriteSet],
+    event_vecs: &[Vec<ContractEvent>],
+    begin_version: Version,
+    end_version: Version,
+    verify_execution_mode: &VerifyExecutionMode,
+) -> Result<Version> {
+    // Execute transactions.
+    let txns = transactions
+        .iter()
+        .take((end_version - begin_version) as usize)
+        .cloned()
+        .collect();
+
+    let chunk_output = ChunkOutput::by_transaction_execution::<V>(txns, state_view)?;
+    // not `zip_eq`, deliberately
+    for (version, txn_out, txn_info, write_set, events) in multizip((
+        begin_version..end_version,
+        chunk_output.transaction_outputs.iter(),
+        transaction_infos.iter(),
+        write_sets.iter(),
+        event_vecs.iter(),
+    )) {
+        if let Err(err) =
+            txn_out.ensure_match_transaction_info(version, txn_info, Some(write_set), Some(events))
+        {
+            if verify_execution_mode.is_lazy_quit() {
+                error!("(Not quitting right away.) {}", err);
+                verify_execution_mode.mark_seen_error();
+                return Ok(version + 1);
+            } else {
+                return Err(err);
+            }
+        }
+    }
+    Ok(end_version)
+}
+
+/// Applies the transactions with the outputs recorded in the backup, i.e. the write sets, events
+/// and the gas used and status of the transaction infos, on top of `latest_view`.
+fn apply_recorded_outputs(
+    state_view: CachedStateView,
+    latest_view: &ExecutedTrees,
+    transactions: Vec<Transaction>,
+    transaction_infos: Vec<TransactionInfo>,
+    write_sets: Vec<WriteSet>,
+    event_vecs: Vec<Vec<ContractEvent>>,
+) -> Result<ExecutedChunk> {
+    let txns_and_outputs = multizip((
+        transactions,
+        transaction_infos.iter(),
+        write_sets,
+        event_vecs,
+    ))
+    .map(|(txn, txn_info, write_set, events)| {
+        (
+            txn,
+            TransactionOutput::new(
+                write_set,
+                events,
+                txn_info.gas_used(),
+                TransactionStatus::Keep(txn_info.status().clone()),
+            ),
+        )
+    })
+    .collect();
+
+    let chunk_output = ChunkOutput::by_transaction_output(txns_and_outputs, state_view)?;
+    let (executed_batch, to_discard, to_retry) = chunk_output.apply_to_ledger(latest_view)?;
+    ensure_no_discard(to_discard)?;
+    ensure_no_retry(to_retry)?;
+    executed_batch.ensure_transaction_infos_match(&transaction_infos)?;
+    Ok(executed_batch)
+}
@@ -11,14 +11,21 @@ use crate::{
     mock_vm::{encode_mint_transaction, MockVM},
     tests,
 };
-use aptos_crypto::HashValue;
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_db::AptosDB;
-use aptos_executor_types::{BlockExecutorTrait, ChunkExecutorTrait};
+use aptos_executor_types::{
+    BlockExecutorTrait, ChunkExecutorTrait, TransactionReplayer, VerifyExecutionMode,
+};
 use aptos_storage_interface::DbReaderWriter;
 use aptos_types::{
+    contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     test_helpers::transaction_test_helpers::block,
-    transaction::{Transaction, TransactionListWithProof, TransactionOutputListWithProof},
+    transaction::{
+        Transaction, TransactionInfo, TransactionListWithProof, TransactionOutput,
+        TransactionOutputListWithProof,
+    },
+    write_set::WriteSet,
 };
 use rand::Rng;
 
@@ -334,3 +341,106 @@ fn test_executor_execute_and_commit_chunk_without_verify() {
         .execute_chunk(chunks[1].clone(), &ledger_info, None)
         .is_ok());
 }
+
+/// Commits a block minting twice to the same account, and returns what is needed to replay it,
+/// i.e., the transactions along with their infos, write sets and events. If `corrupt`, the write set
+/// of the first mint is replaced by the one of the second mint, and its info updated accordingly.
+/// Since the second mint overwrites the same keys, the recorded outputs still lead to the state of
+/// the block and can be applied, but they don't match the execution of the first mint anymore.
+fn replay_chunk(
+    corrupt: bool,
+) -> (
+    Vec<Transaction>,
+    Vec<TransactionInfo>,
+    Vec<WriteSet>,
+    Vec<Vec<ContractEvent>>,
+) {
+    let TestExecutor { _path, db, .. } = TestExecutor::new();
+    let executor = BlockExecutor::<MockVM, Transaction>::new(db.clone());
+    let parent_block_id = executor.committed_block_id();
+    let block_id = tests::gen_block_id(1);
+    let sender = tests::gen_address(1);
+    let txns = vec![
+        encode_mint_transaction(sender, 100),
+        encode_mint_transaction(sender, 50),
+    ];
+    let output = executor
+        .execute_block((block_id, block(txns)), parent_block_id)
+        .unwrap();
+    let ledger_info = tests::gen_ledger_info(3, output.root_hash(), block_id, 1);
+    executor.commit_blocks(vec![block_id], ledger_info).unwrap();
+
+    let TransactionOutputListWithProof {
+        transactions_and_outputs,
+        proof,
+        ..
+    } = db.reader.get_transaction_outputs(1, 3, 3).unwrap();
+    let (transactions, outputs): (Vec<_>, Vec<_>) = transactions_and_outputs.into_iter().unzip();
+    let (mut write_sets, event_vecs): (Vec<_>, Vec<_>) =
+        outputs.into_iter().map(TransactionOutput::into).unzip();
+    let mut transaction_infos = proof.transaction_infos;
+    if corrupt {
+        write_sets[0] = write_sets[1].clone();
+        let txn_info = &transaction_infos[0];
+        transaction_infos[0] = TransactionInfo::new(
+            txn_info.transaction_hash(),
+            CryptoHash::hash(&write_sets[0]),
+            txn_info.event_root_hash(),
+            txn_info.state_checkpoint_hash(),
+            txn_info.gas_used(),
+            txn_info.status().clone(),
+        );
+    }
+    (transactions, transaction_infos, write_sets, event_vecs)
+}
+
+/// Replays the chunk into a fresh DB, deferring the verification of its execution, and returns the
+/// result of the verification.
+fn replay_and_verify(corrupt: bool, verify_execution_mode: &VerifyExecutionMode) -> bool {
+    let (transactions, transaction_infos, write_sets, event_vecs) = replay_chunk(corrupt);
+    let TestExecutor {
+        _path, executor, ..
+    } = TestExecutor::new();
+
+    // The recorded outputs match the transaction infos, so the chunk gets applied and committed
+    // before its execution is verified.
+    let verification = executor
+        .replay_and_defer_verification(
+            transactions,
+            transaction_infos,
+            write_sets,
+            event_vecs,
+            verify_execution_mode,
+        )
+        .unwrap();
+    let committed_chunk = executor.commit().unwrap();
+    assert_eq!(committed_chunk.result_view.version(), Some(3));
+
+    verification.verify().is_ok()
+}
+
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_executor_replay_and_defer_verification() {
+    let verify_execution_mode = VerifyExecutionMode::verify_all();
+    assert!(replay_and_verify(false, &verify_execution_mode));
+    assert!(!verify_execution_mode.seen_error());
+
+    let verify_execution_mode = VerifyExecutionMode::verify_all().set_lazy_quit(true);
+    assert!(replay_and_verify(false, &verify_execution_mode));
+    assert!(!verify_execution_mode.seen_error());
+}
+
+#[test]
+#[cfg_attr(feature = "consensus-only-perf-test", ignore)]
+fn test_executor_replay_and_defer_verification_of_corrupted_write_set() {
+    // The verification fails right away on the mismatch.
+    let verify_execution_mode = VerifyExecutionMode::verify_all();
+    assert!(!replay_and_verify(true, &verify_execution_mode));
+
+    // The verification goes through the whole chunk, and records the mismatch for the replay to
+    // fail once done, like `replay-verify` does.
+    let verify_execution_mode = VerifyExecutionMode::verify_all().set_lazy_quit(true);
+    assert!(replay_and_verify(true, &verify_execution_mode));
+    assert!(verify_execution_mode.seen_error());
+}
@@ -6,7 +6,10 @@ use crate::{
     move_types::account_address::AccountAddress,
     types::{
         chain_id::ChainId,
-        transaction::{authenticator::AuthenticationKey, RawTransaction, TransactionPayload},
+        transaction::{
+            authenticator::AuthenticationKey, RawTransaction, RawTransactionWithData,
+            TransactionPayload,
+        },
     },
 };
 pub use aptos_cached_packages::aptos_stdlib;
@@ -86,6 +89,20 @@ impl TransactionBuilder {
             self.chain_id,
         )
     }
+
+    /// Builds the message signed by the sender, the secondary signers and the fee payer of a
+    /// transaction whose gas is paid by `fee_payer_address`.
+    pub fn build_with_fee_payer(
+        self,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        fee_payer_address: AccountAddress,
+    ) -> RawTransactionWithData {
+        RawTransactionWithData::new_multi_agent_with_fee_payer(
+            self.build(),
+            secondary_signer_addresses,
+            fee_payer_address,
+        )
+    }
 }
 
 #[derive(Clone, Debug)]
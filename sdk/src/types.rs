@@ -5,12 +5,15 @@
 use crate::{
     crypto::{
         ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
-        traits::Uniform,
+        traits::{SigningKey, Uniform},
     },
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
-        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+        transaction::{
+            authenticator::{AccountAuthenticator, AuthenticationKey},
+            RawTransaction, RawTransactionWithData, SignedTransaction,
+        },
     },
 };
 use anyhow::Result;
@@ -128,6 +131,53 @@ impl LocalAccount {
             .into_inner()
     }
 
+    /// Signs a transaction whose gas is paid by `fee_payer_signer` rather than by this account,
+    /// along with the secondary signers and the fee payer.
+    pub fn sign_fee_payer_with_transaction_builder(
+        &mut self,
+        secondary_signers: Vec<&Self>,
+        fee_payer_signer: &Self,
+        builder: TransactionBuilder,
+    ) -> SignedTransaction {
+        let secondary_signer_addresses = secondary_signers
+            .iter()
+            .map(|signer| signer.address())
+            .collect();
+        let secondary_signer_privkeys = secondary_signers
+            .iter()
+            .map(|signer| signer.private_key())
+            .collect();
+        let raw_txn = builder
+            .sender(self.address())
+            .sequence_number(self.sequence_number())
+            .build();
+        *self.sequence_number_mut() += 1;
+        raw_txn
+            .sign_fee_payer(
+                self.private_key(),
+                secondary_signer_addresses,
+                secondary_signer_privkeys,
+                fee_payer_signer.address(),
+                fee_payer_signer.private_key(),
+            )
+            .expect("Signing fee payer txn failed")
+            .into_inner()
+    }
+
+    /// Signs a multi-agent or fee payer transaction message, e.g., as the fee payer of a
+    /// transaction built by another account with `TransactionBuilder::build_with_fee_payer`.
+    /// The signatures of all parties are assembled with `SignedTransaction::new_fee_payer`.
+    pub fn sign_raw_transaction_with_data(
+        &self,
+        message: &RawTransactionWithData,
+    ) -> AccountAuthenticator {
+        let signature = self
+            .private_key()
+            .sign(message)
+            .expect("Signing a txn can't fail");
+        AccountAuthenticator::ed25519(self.public_key().clone(), signature)
+    }
+
     pub fn address(&self) -> AccountAddress {
         self.address
     }
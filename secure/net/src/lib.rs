@@ -15,18 +15,35 @@
 //!
 //! Internally both the client and server leverage a NetworkStream that communications in blocks
 //! where a block is a length prefixed array of bytes.
+//!
+//! Optionally, the client and server can authenticate each other with a Noise IK handshake upon
+//! connecting, see `NetworkClient::with_noise` and `NetworkServer::with_noise`. All blocks are
+//! then encrypted and authenticated within the resulting Noise session.
 
+use aptos_crypto::{
+    noise::{self, NoiseConfig, NoiseError, NoiseSession},
+    x25519,
+};
 use aptos_logger::{info, trace, warn, Schema};
 use aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
 use once_cell::sync::Lazy;
+use rand::rngs::OsRng;
 use serde::Serialize;
 use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
     io::{Read, Write},
     net::{Shutdown, SocketAddr, TcpListener, TcpStream},
     thread, time,
+    time::{SystemTime, UNIX_EPOCH},
 };
 use thiserror::Error;
 
+/// The size of the timestamp sent by the client within the Noise handshake.
+const TIMESTAMP_SIZE: usize = 8;
+/// The largest plaintext that fits within a single Noise message.
+const MAX_NOISE_PLAINTEXT_SIZE: usize = noise::MAX_SIZE_NOISE_MSG - noise::AES_GCM_TAGLEN;
+
 #[derive(Schema)]
 struct SecureNetLogSchema<'a> {
     service: &'static str,
@@ -139,14 +156,110 @@ pub enum Error {
     AlreadyShutdown,
     #[error("Found data that is too large to decode: {0}")]
     DataTooLarge(usize),
+    #[error("Invalid handshake from {0}: {1}")]
+    InvalidHandshake(x25519::PublicKey, String),
     #[error("Internal network error:")]
     NetworkError(#[from] std::io::Error),
     #[error("No active stream")]
     NoActiveStream,
+    #[error("Noise error: {0}")]
+    NoiseError(#[from] NoiseError),
     #[error("Overflow error: {0}")]
     OverflowError(String),
     #[error("Remote stream cleanly closed")]
     RemoteStreamClosed,
+    #[error("Untrusted peer: {0}")]
+    UntrustedPeer(x25519::PublicKey),
+}
+
+/// The client side of the Noise IK handshake, see `NetworkClient::with_noise`.
+struct ClientNoise {
+    config: NoiseConfig,
+    server_public_key: x25519::PublicKey,
+    /// The timestamp sent in the last handshake, the next one must be strictly greater.
+    last_timestamp: u64,
+}
+
+impl ClientNoise {
+    /// Authenticates the server and authenticates to the server over `stream`. The handshake
+    /// carries a timestamp, strictly increasing across handshakes, for the server to reject
+    /// replayed handshakes.
+    fn handshake(
+        &mut self,
+        service: &'static str,
+        stream: &mut NetworkStream,
+    ) -> Result<NoiseSession, Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time is before the UNIX epoch")
+            .as_millis() as u64;
+        self.last_timestamp = std::cmp::max(now, self.last_timestamp + 1);
+
+        let mut init_message = vec![0; noise::handshake_init_msg_len(TIMESTAMP_SIZE)];
+        let handshake_state = self.config.initiate_connection(
+            &mut OsRng,
+            service.as_bytes(),
+            self.server_public_key,
+            Some(&self.last_timestamp.to_le_bytes()),
+            &mut init_message,
+        )?;
+        stream.write(&init_message)?;
+
+        let response = stream.read()?;
+        let (_, session) = self
+            .config
+            .finalize_connection(handshake_state, &response)?;
+        Ok(session)
+    }
+}
+
+/// The server side of the Noise IK handshake, see `NetworkServer::with_noise`.
+struct ServerNoise {
+    config: NoiseConfig,
+    trusted_clients: HashSet<x25519::PublicKey>,
+    /// The timestamp of the last handshake of each client, used to reject replayed handshakes.
+    last_timestamps: HashMap<x25519::PublicKey, u64>,
+}
+
+impl ServerNoise {
+    /// Authenticates the client and authenticates to the client over `stream`, rejecting clients
+    /// that are not trusted as well as replayed handshakes.
+    fn handshake(
+        &mut self,
+        service: &'static str,
+        stream: &mut NetworkStream,
+    ) -> Result<NoiseSession, Error> {
+        let init_message = stream.read()?;
+        let (client_public_key, handshake_state, payload) = self
+            .config
+            .parse_client_init_message(service.as_bytes(), &init_message)?;
+        if !self.trusted_clients.contains(&client_public_key) {
+            return Err(Error::UntrustedPeer(client_public_key));
+        }
+
+        let timestamp: [u8; TIMESTAMP_SIZE] = payload.as_slice().try_into().map_err(|_| {
+            Error::InvalidHandshake(client_public_key, "malformed timestamp".into())
+        })?;
+        let timestamp = u64::from_le_bytes(timestamp);
+        let last_timestamp = self.last_timestamps.entry(client_public_key).or_insert(0);
+        if timestamp <= *last_timestamp {
+            return Err(Error::InvalidHandshake(
+                client_public_key,
+                format!(
+                    "replayed handshake, timestamp {} is not after {}",
+                    timestamp, last_timestamp
+                ),
+            ));
+        }
+        *last_timestamp = timestamp;
+
+        let mut response = vec![0; noise::handshake_resp_msg_len(0)];
+        let session =
+            self.config
+                .respond_to_client(&mut OsRng, handshake_state, None, &mut response)?;
+        stream.write(&response)?;
+        Ok(session)
+    }
 }
 
 pub struct NetworkClient {
@@ -155,6 +268,7 @@ pub struct NetworkClient {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<ClientNoise>,
 }
 
 impl NetworkClient {
@@ -164,9 +278,25 @@ impl NetworkClient {
             server,
             stream: None,
             timeout_ms,
+            noise: None,
         }
     }
 
+    /// Authenticates and encrypts every connection to the server with Noise IK: the client
+    /// authenticates with `identity_key` and the server must hold the key of `server_public_key`.
+    pub fn with_noise(
+        mut self,
+        identity_key: x25519::PrivateKey,
+        server_public_key: x25519::PublicKey,
+    ) -> Self {
+        self.noise = Some(ClientNoise {
+            config: NoiseConfig::new(identity_key),
+            server_public_key,
+            last_timestamp: 0,
+        });
+        self
+    }
+
     fn increment_counter(&self, method: Method, result: MethodResult) {
         increment_counter(self.service, NetworkMode::Client, method, result)
     }
@@ -258,7 +388,24 @@ impl NetworkClient {
 
             let stream = stream?;
             stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, self.server, self.timeout_ms));
+            let mut stream = NetworkStream::new(stream, self.server, self.timeout_ms);
+            if let Some(noise) = &mut self.noise {
+                match noise.handshake(self.service, &mut stream) {
+                    Ok(session) => stream.session = Some(session),
+                    Err(err) => {
+                        self.increment_counter(Method::Connect, MethodResult::Failure);
+                        warn!(SecureNetLogSchema::new(
+                            self.service,
+                            NetworkMode::Client,
+                            LogEvent::ConnectionFailed,
+                        )
+                        .error(&err)
+                        .remote_peer(&self.server));
+                        return Err(err);
+                    },
+                }
+            }
+            self.stream = Some(stream);
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -278,6 +425,7 @@ pub struct NetworkServer {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<ServerNoise>,
 }
 
 impl NetworkServer {
@@ -288,9 +436,26 @@ impl NetworkServer {
             listener: Some(listener.unwrap()),
             stream: None,
             timeout_ms,
+            noise: None,
         }
     }
 
+    /// Authenticates and encrypts every connection from a client with Noise IK: the server
+    /// authenticates with `identity_key` and only accepts clients holding the key of one of
+    /// `trusted_clients`.
+    pub fn with_noise(
+        mut self,
+        identity_key: x25519::PrivateKey,
+        trusted_clients: HashSet<x25519::PublicKey>,
+    ) -> Self {
+        self.noise = Some(ServerNoise {
+            config: NoiseConfig::new(identity_key),
+            trusted_clients,
+            last_timestamps: HashMap::new(),
+        });
+        self
+    }
+
     fn increment_counter(&self, method: Method, result: MethodResult) {
         increment_counter(self.service, NetworkMode::Server, method, result)
     }
@@ -391,6 +556,25 @@ impl NetworkServer {
                 },
             };
 
+            stream.set_nodelay(true)?;
+            let mut stream = NetworkStream::new(stream, stream_addr, self.timeout_ms);
+            if let Some(noise) = &mut self.noise {
+                match noise.handshake(self.service, &mut stream) {
+                    Ok(session) => stream.session = Some(session),
+                    Err(err) => {
+                        self.increment_counter(Method::Connect, MethodResult::Failure);
+                        warn!(SecureNetLogSchema::new(
+                            self.service,
+                            NetworkMode::Server,
+                            LogEvent::ConnectionFailed,
+                        )
+                        .error(&err)
+                        .remote_peer(&stream_addr));
+                        return Err(err);
+                    },
+                }
+            }
+
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -398,9 +582,7 @@ impl NetworkServer {
                 LogEvent::ConnectionSuccessful,
             )
             .remote_peer(&stream_addr));
-
-            stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, stream_addr, self.timeout_ms));
+            self.stream = Some(stream);
         }
 
         self.stream.as_mut().ok_or(Error::NoActiveStream)
@@ -412,6 +594,8 @@ struct NetworkStream {
     remote: SocketAddr,
     buffer: Vec<u8>,
     temp_buffer: [u8; 1024],
+    /// The Noise session encrypting messages, if the connection is authenticated.
+    session: Option<NoiseSession>,
 }
 
 impl NetworkStream {
@@ -426,11 +610,30 @@ impl NetworkStream {
             remote,
             buffer: Vec::new(),
             temp_buffer: [0; 1024],
+            session: None,
         }
     }
 
-    /// Blocking read until able to successfully read an entire message
+    /// Blocking read until able to successfully read an entire message, decrypting it within the
+    /// Noise session, if any.
     pub fn read(&mut self) -> Result<Vec<u8>, Error> {
+        let message = self.read_message()?;
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => return Ok(message),
+        };
+
+        // Messages are encrypted as a sequence of Noise messages, see `write`.
+        let mut plaintext = Vec::with_capacity(message.len());
+        for chunk in message.chunks(noise::MAX_SIZE_NOISE_MSG) {
+            let mut chunk = chunk.to_vec();
+            plaintext.extend_from_slice(session.read_message_in_place(&mut chunk)?);
+        }
+        Ok(plaintext)
+    }
+
+    /// Blocking read until able to successfully read an entire block
+    fn read_message(&mut self) -> Result<Vec<u8>, Error> {
         let result = self.read_buffer();
         if !result.is_empty() {
             return Ok(result);
@@ -458,8 +661,32 @@ impl NetworkStream {
         Ok(self.stream.shutdown(Shutdown::Both)?)
     }
 
-    /// Blocking write until able to successfully send an entire message
+    /// Blocking write until able to successfully send an entire message, encrypting it within the
+    /// Noise session, if any.
     pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => return self.write_message(data),
+        };
+
+        // Noise messages are bounded in size, so larger messages are split into a sequence of
+        // Noise messages, each but the last one of the maximum size.
+        let mut ciphertext = Vec::with_capacity(noise::encrypted_len(data.len()));
+        let mut chunks: Vec<&[u8]> = data.chunks(MAX_NOISE_PLAINTEXT_SIZE).collect();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        for chunk in chunks {
+            let mut chunk = chunk.to_vec();
+            let tag = session.write_message_in_place(&mut chunk)?;
+            ciphertext.extend_from_slice(&chunk);
+            ciphertext.extend_from_slice(&tag);
+        }
+        self.write_message(&ciphertext)
+    }
+
+    /// Blocking write until able to successfully send an entire block
+    fn write_message(&mut self, data: &[u8]) -> Result<(), Error> {
         let u32_max = u32::max_value() as usize;
         if u32_max <= data.len() {
             return Err(Error::DataTooLarge(data.len()));
@@ -519,6 +746,8 @@ impl NetworkStream {
 mod test {
     use super::*;
     use aptos_config::utils;
+    use aptos_crypto::Uniform;
+    use rand::{rngs::StdRng, SeedableRng};
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
     /// Read, Write, Connect timeout in milliseconds.
@@ -630,6 +859,124 @@ mod test {
         assert_eq!(data2, result2);
     }
 
+    fn noise_keys() -> (x25519::PrivateKey, x25519::PrivateKey) {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        (
+            x25519::PrivateKey::generate(&mut rng),
+            x25519::PrivateKey::generate(&mut rng),
+        )
+    }
+
+    #[test]
+    fn test_noise_ping() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let (server_key, client_key) = noise_keys();
+        let server_public_key = server_key.public_key();
+        let trusted_clients = [client_key.public_key()].into_iter().collect();
+        let mut server = NetworkServer::new("test", server_addr, TIMEOUT)
+            .with_noise(server_key, trusted_clients);
+        let client_thread = thread::spawn(move || {
+            let mut client = NetworkClient::new("test", server_addr, TIMEOUT)
+                .with_noise(client_key, server_public_key);
+            client.write(&[0, 1, 2, 3]).unwrap();
+            client.read().unwrap()
+        });
+
+        let data = server.read().unwrap();
+        assert_eq!(data, vec![0, 1, 2, 3]);
+        // Larger than a single Noise message
+        let data = vec![7; 3 * noise::MAX_SIZE_NOISE_MSG];
+        server.write(&data).unwrap();
+        assert_eq!(client_thread.join().unwrap(), data);
+    }
+
+    #[test]
+    fn test_noise_untrusted_client() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let (server_key, client_key) = noise_keys();
+        let server_public_key = server_key.public_key();
+        let mut server =
+            NetworkServer::new("test", server_addr, TIMEOUT).with_noise(server_key, HashSet::new());
+        let client_thread = thread::spawn(move || {
+            let mut client = NetworkClient::new("test", server_addr, TIMEOUT)
+                .with_noise(client_key, server_public_key);
+            client.write(&[0, 1, 2, 3]).unwrap_err();
+        });
+
+        assert!(matches!(server.read(), Err(Error::UntrustedPeer(_))));
+        client_thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_noise_unauthenticated_client() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let (server_key, client_key) = noise_keys();
+        let trusted_clients = [client_key.public_key()].into_iter().collect();
+        let mut server = NetworkServer::new("test", server_addr, TIMEOUT)
+            .with_noise(server_key, trusted_clients);
+        let mut client = NetworkClient::new("test", server_addr, TIMEOUT);
+
+        // A client without Noise is rejected as its message is not a valid handshake
+        client.write(&[0, 1, 2, 3]).unwrap();
+        server.read().unwrap_err();
+    }
+
+    #[test]
+    fn test_noise_replayed_handshake() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let (server_key, client_key) = noise_keys();
+        let server_public_key = server_key.public_key();
+        let trusted_clients = [client_key.public_key()].into_iter().collect();
+        let mut server = NetworkServer::new("test", server_addr, TIMEOUT)
+            .with_noise(server_key, trusted_clients);
+
+        // Record the handshake of a client
+        let mut client_noise = ClientNoise {
+            config: NoiseConfig::new(client_key),
+            server_public_key,
+            last_timestamp: 0,
+        };
+        let mut init_message = vec![0; noise::handshake_init_msg_len(TIMESTAMP_SIZE)];
+        client_noise
+            .config
+            .initiate_connection(
+                &mut OsRng,
+                b"test",
+                server_public_key,
+                Some(&1u64.to_le_bytes()),
+                &mut init_message,
+            )
+            .unwrap();
+        let mut client = NetworkClient::new("test", server_addr, TIMEOUT);
+        client.write(&init_message).unwrap();
+        client.shutdown().unwrap();
+        server.read().unwrap_err();
+
+        // Replaying it is rejected
+        let mut client = NetworkClient::new("test", server_addr, TIMEOUT);
+        client.write(&init_message).unwrap();
+        client.shutdown().unwrap();
+        assert!(matches!(server.read(), Err(Error::InvalidHandshake(..))));
+
+        // While a fresh handshake is accepted
+        let client_thread = thread::spawn(move || {
+            let mut stream = NetworkStream::new(
+                TcpStream::connect(server_addr).unwrap(),
+                server_addr,
+                TIMEOUT,
+            );
+            let session = client_noise.handshake("test", &mut stream).unwrap();
+            stream.session = Some(session);
+            stream.write(&[0, 1, 2, 3]).unwrap();
+        });
+        assert_eq!(server.read().unwrap(), vec![0, 1, 2, 3]);
+        client_thread.join().unwrap();
+    }
+
     #[test]
     fn test_client_timeout() {
         let server_port = utils::get_available_port();
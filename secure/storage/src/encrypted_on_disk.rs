@@ -0,0 +1,351 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    from_base64, on_disk::OnDiskStorage, to_base64, CryptoKVStorage, Error, GetResponse, KVStorage,
+};
+use aptos_temppath::TempPath;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use rand::{rngs::OsRng, RngCore};
+use ring::{
+    aead::{self, Aad, LessSafeKey, Nonce, UnboundKey},
+    pbkdf2,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+/// The version of the format of the encrypted file, bumped on incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+/// The number of PBKDF2-HMAC-SHA256 iterations used to derive new wrapping keys. The iterations
+/// are recorded in the encrypted file, so that this can be raised without breaking existing files.
+const PBKDF2_ITERATIONS: u32 = if cfg!(test) { 1_000 } else { 600_000 };
+const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+
+/// The secret the key wrapping, i.e., encrypting, an EncryptedOnDiskStorage is derived from.
+pub enum WrappingKeySource {
+    /// A passphrase chosen by the operator.
+    Passphrase(String),
+    /// A file holding key material, e.g., random bytes, read as is.
+    KeyFile(PathBuf),
+}
+
+impl WrappingKeySource {
+    fn secret(&self) -> Result<Vec<u8>, Error> {
+        let secret = match self {
+            WrappingKeySource::Passphrase(passphrase) => passphrase.as_bytes().to_vec(),
+            WrappingKeySource::KeyFile(path) => fs::read(path)?,
+        };
+        if secret.is_empty() {
+            return Err(Error::InternalError(
+                "The wrapping key secret is empty".to_string(),
+            ));
+        }
+        Ok(secret)
+    }
+}
+
+/// EncryptedOnDiskStorage is a key value store persisted to a single file on the local filesystem,
+/// like OnDiskStorage, except that the data is encrypted at rest with AES-256-GCM under a wrapping
+/// key derived from a passphrase or a key file with PBKDF2. The file is authenticated as a whole,
+/// so any tampering with it is detected on read, and it is replaced atomically on every write.
+/// Like OnDiskStorage, it is intended for single threads (or must be wrapped by a Arc<RwLock<>>)
+/// and offers no permission checks.
+pub struct EncryptedOnDiskStorage {
+    file_path: PathBuf,
+    temp_path: TempPath,
+    wrapping_key: WrappingKey,
+    time_service: TimeService,
+}
+
+impl EncryptedOnDiskStorage {
+    /// Opens the storage at `file_path`, creating it if it doesn't exist, and panics on failure,
+    /// e.g., if the wrapping key doesn't match the one the storage was encrypted with.
+    pub fn new(file_path: PathBuf, wrapping_key_source: &WrappingKeySource) -> Self {
+        Self::open(file_path.clone(), wrapping_key_source).unwrap_or_else(|error| {
+            panic!(
+                "Unable to open encrypted storage at path: {:?}: {}",
+                file_path, error
+            )
+        })
+    }
+
+    /// Opens the storage at `file_path`, creating it if it doesn't exist.
+    pub fn open(
+        file_path: PathBuf,
+        wrapping_key_source: &WrappingKeySource,
+    ) -> Result<Self, Error> {
+        Self::open_with_time_service(file_path, wrapping_key_source, TimeService::real())
+    }
+
+    /// Opens the existing storage at `file_path`, failing instead of creating a new one if there
+    /// is none, e.g., when the path is mistyped.
+    pub fn open_existing(
+        file_path: PathBuf,
+        wrapping_key_source: &WrappingKeySource,
+    ) -> Result<Self, Error> {
+        if !file_path.exists() {
+            return Err(Error::InternalError(format!(
+                "Encrypted storage not found at path: {:?}",
+                file_path
+            )));
+        }
+        Self::open(file_path, wrapping_key_source)
+    }
+
+    fn open_with_time_service(
+        file_path: PathBuf,
+        wrapping_key_source: &WrappingKeySource,
+        time_service: TimeService,
+    ) -> Result<Self, Error> {
+        let secret = wrapping_key_source.secret()?;
+        let exists = file_path.exists() && fs::metadata(&file_path)?.len() > 0;
+        let wrapping_key = if exists {
+            let file = read_file(&file_path)?;
+            let wrapping_key = WrappingKey::derive(&secret, file.salt.clone(), file.iterations)?;
+            // Fail early if the wrapping key is not the one the storage was encrypted with.
+            wrapping_key.open(file)?;
+            wrapping_key
+        } else {
+            WrappingKey::generate(&secret)?
+        };
+
+        let storage = Self {
+            temp_path: TempPath::new_with_temp_dir(parent_dir(&file_path)),
+            file_path,
+            wrapping_key,
+            time_service,
+        };
+        if !exists {
+            storage.write(&HashMap::new())?;
+        }
+        Ok(storage)
+    }
+
+    /// Encrypts the content of the OnDiskStorage at `on_disk_path` into a new storage at
+    /// `file_path`. The plaintext file is left untouched, it is up to the caller to remove it.
+    pub fn migrate_from_on_disk(
+        on_disk_path: &Path,
+        file_path: PathBuf,
+        wrapping_key_source: &WrappingKeySource,
+    ) -> Result<Self, Error> {
+        if !on_disk_path.exists() {
+            return Err(Error::InternalError(format!(
+                "On disk storage not found at path: {:?}",
+                on_disk_path
+            )));
+        }
+        if file_path.exists() {
+            return Err(Error::InternalError(format!(
+                "Encrypted storage already exists at path: {:?}",
+                file_path
+            )));
+        }
+
+        let data = OnDiskStorage::new(on_disk_path.to_path_buf()).read()?;
+        let storage = Self::open(file_path, wrapping_key_source)?;
+        storage.write(&data)?;
+        Ok(storage)
+    }
+
+    /// Re-encrypts the storage under a new wrapping key derived from `wrapping_key_source`, with
+    /// a fresh salt. The storage can only be opened with the new wrapping key afterwards.
+    pub fn rotate_wrapping_key(
+        &mut self,
+        wrapping_key_source: &WrappingKeySource,
+    ) -> Result<(), Error> {
+        let data = self.read()?;
+        let wrapping_key = WrappingKey::generate(&wrapping_key_source.secret()?)?;
+        self.write_file(&wrapping_key.seal(&data)?)?;
+        self.wrapping_key = wrapping_key;
+        Ok(())
+    }
+
+    fn read(&self) -> Result<HashMap<String, Value>, Error> {
+        self.wrapping_key.open(read_file(&self.file_path)?)
+    }
+
+    fn write(&self, data: &HashMap<String, Value>) -> Result<(), Error> {
+        self.write_file(&self.wrapping_key.seal(data)?)
+    }
+
+    fn write_file(&self, file: &EncryptedFile) -> Result<(), Error> {
+        let contents = serde_json::to_vec(file)?;
+        let mut temp_file = File::create(self.temp_path.path())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            temp_file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        temp_file.write_all(&contents)?;
+        temp_file.sync_all()?;
+        fs::rename(&self.temp_path, &self.file_path)?;
+        // Persist the rename itself, otherwise a crash could still revert the file to its previous
+        // content, e.g., encrypted under a wrapping key which was just rotated away.
+        #[cfg(unix)]
+        File::open(parent_dir(&self.file_path))?.sync_all()?;
+        Ok(())
+    }
+}
+
+impl KVStorage for EncryptedOnDiskStorage {
+    fn available(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        let mut data = self.read()?;
+        data.remove(key)
+            .ok_or_else(|| Error::KeyNotSet(key.to_string()))
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.into()))
+    }
+
+    fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        let now = self.time_service.now_secs();
+        let mut data = self.read()?;
+        data.insert(
+            key.to_string(),
+            serde_json::to_value(&GetResponse::new(value, now))?,
+        );
+        self.write(&data)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.write(&HashMap::new())
+    }
+}
+
+impl CryptoKVStorage for EncryptedOnDiskStorage {}
+
+/// The content of the storage file: the data, encrypted and authenticated along with the
+/// parameters needed to derive the wrapping key again.
+#[derive(Deserialize, Serialize)]
+struct EncryptedFile {
+    version: u32,
+    iterations: u32,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    salt: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    nonce: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedFile {
+    /// The parameters of the file which are authenticated along with the ciphertext.
+    fn associated_data(version: u32, iterations: u32, salt: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(bcs::to_bytes(&(version, iterations, salt))?)
+    }
+}
+
+/// The AES-256-GCM key the storage file is encrypted with, along with the parameters it was
+/// derived with.
+struct WrappingKey {
+    iterations: u32,
+    salt: Vec<u8>,
+    key: LessSafeKey,
+}
+
+impl WrappingKey {
+    /// Derives a new wrapping key from `secret` with a random salt.
+    fn generate(secret: &[u8]) -> Result<Self, Error> {
+        let mut salt = vec![0; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        Self::derive(secret, salt, PBKDF2_ITERATIONS)
+    }
+
+    fn derive(secret: &[u8], salt: Vec<u8>, iterations: u32) -> Result<Self, Error> {
+        let num_iterations = NonZeroU32::new(iterations).ok_or_else(|| {
+            Error::SerializationError("Invalid number of key derivation iterations".to_string())
+        })?;
+        let mut key_bytes = [0; KEY_LENGTH];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            num_iterations,
+            &salt,
+            secret,
+            &mut key_bytes,
+        );
+        let key = UnboundKey::new(&aead::AES_256_GCM, &key_bytes)
+            .map_err(|_| Error::InternalError("Unable to create wrapping key".to_string()))?;
+
+        Ok(Self {
+            iterations,
+            salt,
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    fn seal(&self, data: &HashMap<String, Value>) -> Result<EncryptedFile, Error> {
+        let mut nonce = [0; aead::NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let associated_data =
+            EncryptedFile::associated_data(FORMAT_VERSION, self.iterations, &self.salt)?;
+
+        let mut ciphertext = serde_json::to_vec(data)?;
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(associated_data),
+                &mut ciphertext,
+            )
+            .map_err(|_| Error::InternalError("Unable to encrypt storage".to_string()))?;
+
+        Ok(EncryptedFile {
+            version: FORMAT_VERSION,
+            iterations: self.iterations,
+            salt: self.salt.clone(),
+            nonce: nonce.to_vec(),
+            ciphertext,
+        })
+    }
+
+    fn open(&self, file: EncryptedFile) -> Result<HashMap<String, Value>, Error> {
+        if file.version != FORMAT_VERSION {
+            return Err(Error::SerializationError(format!(
+                "Unsupported encrypted storage version: {}",
+                file.version
+            )));
+        }
+        let nonce = Nonce::try_assume_unique_for_key(&file.nonce).map_err(|_| {
+            Error::SerializationError("Invalid encrypted storage nonce".to_string())
+        })?;
+        let associated_data =
+            EncryptedFile::associated_data(file.version, file.iterations, &file.salt)?;
+
+        let mut ciphertext = file.ciphertext;
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(associated_data), &mut ciphertext)
+            .map_err(|_| {
+                Error::InternalError(
+                    "Unable to decrypt storage, either the wrapping key is wrong or the file was \
+                    tampered with"
+                        .to_string(),
+                )
+            })?;
+        Ok(serde_json::from_slice(plaintext)?)
+    }
+}
+
+fn read_file(file_path: &Path) -> Result<EncryptedFile, Error> {
+    Ok(serde_json::from_slice(&fs::read(file_path)?)?)
+}
+
+/// The directory of `file_path`, used for the temporary file written before replacing it.
+fn parent_dir(file_path: &Path) -> PathBuf {
+    // Only a filename is supplied when there is no parent, i.e., the file is in the current
+    // working directory.
+    match file_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
@@ -54,6 +54,17 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<cryptoki::error::Error> for Error {
+    fn from(error: cryptoki::error::Error) -> Self {
+        match error {
+            cryptoki::error::Error::Pkcs11(cryptoki::error::RvError::PinIncorrect) => {
+                Self::PermissionDenied
+            },
+            _ => Self::InternalError(format!("{}", error)),
+        }
+    }
+}
+
 impl From<aptos_vault_client::Error> for Error {
     fn from(error: aptos_vault_client::Error) -> Self {
         match error {
@@ -0,0 +1,406 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{CryptoStorage, Error, GetResponse, KVStorage, PublicKeyResponse};
+use aptos_crypto::{
+    _once_cell::sync::Lazy,
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
+    signing_message, PrivateKey,
+};
+use aptos_infallible::Mutex;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    error::{Error as Pkcs11Error, RvError},
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    path::{Path, PathBuf},
+};
+
+/// Identifies the objects managed by Pkcs11Storage within the token: it is the CKA_APPLICATION of
+/// the data objects holding key value pairs and the CKA_ID of the Ed25519 keys.
+const APPLICATION: &[u8] = b"aptos-secure-storage";
+/// The DER encoding of the Ed25519 curve OID (1.3.101.112), used as CKA_EC_PARAMS.
+const ED25519_EC_PARAMS: [u8; 5] = [0x06, 0x03, 0x2b, 0x65, 0x70];
+
+/// The PKCS#11 modules loaded by this process, by path. A module is initialized once and shared
+/// by all the storages using it, as finalizing it would close the sessions of the others.
+static MODULES: Lazy<Mutex<HashMap<PathBuf, Pkcs11>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Pkcs11Storage is a secure storage backed by a PKCS#11 token, e.g., a hardware security module,
+/// or SoftHSM for local testing.
+///
+/// Ed25519 private keys are generated (or imported) within the token as sensitive, non-extractable
+/// objects and all signing happens within the token, so these keys never reach process memory and
+/// cannot be exported. The token holds the public key of each named key along with its previous
+/// version, see `rotate_key`.
+///
+/// Key value pairs are stored as private data objects of the token, so they are only readable
+/// after login. This storage only keeps Ed25519 keys within the token: PKCS#11 defines no
+/// BLS12-381 mechanism, so it cannot hold the consensus key and is not a safety rules backend.
+pub struct Pkcs11Storage {
+    session: Mutex<Session>,
+    time_service: TimeService,
+}
+
+impl Pkcs11Storage {
+    /// Opens a session on the token labeled `token_label` of the PKCS#11 module at `module_path`
+    /// and logs in as user with `pin`, panicking on failure.
+    pub fn new(module_path: &Path, token_label: &str, pin: String) -> Self {
+        Self::open(module_path, token_label, pin).unwrap_or_else(|error| {
+            panic!(
+                "Unable to open PKCS#11 token {} with module {:?}: {}",
+                token_label, module_path, error
+            )
+        })
+    }
+
+    /// Opens a session on the token labeled `token_label` of the PKCS#11 module at `module_path`
+    /// and logs in as user with `pin`.
+    pub fn open(module_path: &Path, token_label: &str, pin: String) -> Result<Self, Error> {
+        let pkcs11 = module(module_path)?;
+        let slot = pkcs11
+            .get_slots_with_token()?
+            .into_iter()
+            .find(|slot| {
+                pkcs11
+                    .get_token_info(*slot)
+                    .map_or(false, |info| info.label() == token_label)
+            })
+            .ok_or_else(|| {
+                Error::InternalError(format!("PKCS#11 token not found: {}", token_label))
+            })?;
+        let session = pkcs11.open_rw_session(slot)?;
+        match session.login(UserType::User, Some(&AuthPin::new(pin))) {
+            // Logins are shared by all the sessions of an application with a token.
+            Ok(()) | Err(Pkcs11Error::Pkcs11(RvError::UserAlreadyLoggedIn)) => (),
+            Err(error) => return Err(error.into()),
+        }
+
+        Ok(Self {
+            session: Mutex::new(session),
+            time_service: TimeService::real(),
+        })
+    }
+
+    /// Generates, within the token, the Ed25519 key pair `label`.
+    fn generate_key_pair(session: &Session, label: &str) -> Result<Ed25519PublicKey, Error> {
+        let mut public_template = key_template(ObjectClass::PUBLIC_KEY, label);
+        public_template.extend(public_key_attributes());
+        let mut private_template = key_template(ObjectClass::PRIVATE_KEY, label);
+        private_template.extend(private_key_attributes());
+
+        let (public_key, _) = session.generate_key_pair(
+            &Mechanism::EccEdwardsKeyPairGen,
+            &public_template,
+            &private_template,
+        )?;
+        read_public_key(session, public_key)
+    }
+
+    /// Renames the key pair `label` to `new_label`, replacing any key pair `new_label`.
+    fn rename_key_pair(session: &Session, label: &str, new_label: &str) -> Result<(), Error> {
+        for class in [ObjectClass::PUBLIC_KEY, ObjectClass::PRIVATE_KEY] {
+            for object in session.find_objects(&key_template(class, new_label))? {
+                session.destroy_object(object)?;
+            }
+            for object in session.find_objects(&key_template(class, label))? {
+                session.update_attributes(object, &[Attribute::Label(new_label.into())])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Signs `message` within the token with the private key `label`.
+    fn sign_message(&self, label: &str, message: &[u8]) -> Result<Ed25519Signature, Error> {
+        let session = self.session.lock();
+        let private_key = find_object(&session, &key_template(ObjectClass::PRIVATE_KEY, label))?
+            .ok_or_else(|| Error::KeyNotSet(label.to_string()))?;
+        let signature = session.sign(&Mechanism::Eddsa, private_key, message)?;
+        Ed25519Signature::try_from(signature.as_slice())
+            .map_err(|error| Error::InternalError(error.to_string()))
+    }
+
+    /// Returns the label of the key pair `name` whose public key is `version`, either the current
+    /// or the previous one.
+    fn version_label(&self, name: &str, version: &Ed25519PublicKey) -> Result<String, Error> {
+        if &self.get_public_key(name)?.public_key == version {
+            return Ok(name.to_string());
+        }
+        match self.get_public_key_previous_version(name) {
+            Ok(previous_public_key) if &previous_public_key == version => {
+                Ok(get_previous_version_name(name))
+            },
+            Ok(_) | Err(Error::KeyVersionNotFound(..)) => {
+                Err(Error::KeyVersionNotFound(name.into(), version.to_string()))
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl KVStorage for Pkcs11Storage {
+    fn available(&self) -> Result<(), Error> {
+        self.session.lock().get_session_info()?;
+        Ok(())
+    }
+
+    fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        let session = self.session.lock();
+        let object = find_object(&session, &data_template(key))?
+            .ok_or_else(|| Error::KeyNotSet(key.to_string()))?;
+        match session
+            .get_attributes(object, &[AttributeType::Value])?
+            .pop()
+        {
+            Some(Attribute::Value(value)) => Ok(serde_json::from_slice(&value)?),
+            _ => Err(Error::InternalError(format!(
+                "PKCS#11 data object without value: {}",
+                key
+            ))),
+        }
+    }
+
+    fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        let now = self.time_service.now_secs();
+        let value = serde_json::to_vec(&GetResponse::new(value, now))?;
+
+        let session = self.session.lock();
+        match find_object(&session, &data_template(key))? {
+            Some(object) => session.update_attributes(object, &[Attribute::Value(value)])?,
+            None => {
+                let mut template = data_template(key);
+                template.extend([
+                    Attribute::Token(true),
+                    Attribute::Private(true),
+                    Attribute::Value(value),
+                ]);
+                session.create_object(&template)?;
+            },
+        }
+        Ok(())
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        let session = self.session.lock();
+        let data_objects = session.find_objects(&[
+            Attribute::Class(ObjectClass::DATA),
+            Attribute::Application(APPLICATION.to_vec()),
+        ])?;
+        let keys = session.find_objects(&[Attribute::Id(APPLICATION.to_vec())])?;
+        for object in data_objects.into_iter().chain(keys) {
+            session.destroy_object(object)?;
+        }
+        Ok(())
+    }
+}
+
+impl CryptoStorage for Pkcs11Storage {
+    fn create_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        match self.get_public_key(name) {
+            Ok(_) => return Err(Error::KeyAlreadyExists(name.to_string())),
+            Err(Error::KeyNotSet(_)) => (/* Expected this for new keys! */),
+            Err(e) => return Err(e),
+        }
+
+        let public_key = Self::generate_key_pair(&self.session.lock(), name)?;
+        self.set(name, public_key.clone())?;
+        Ok(public_key)
+    }
+
+    fn export_private_key(&self, _name: &str) -> Result<Ed25519PrivateKey, Error> {
+        // Private keys are non-extractable from the token
+        Err(Error::PermissionDenied)
+    }
+
+    fn import_private_key(&mut self, name: &str, key: Ed25519PrivateKey) -> Result<(), Error> {
+        match self.get_public_key(name) {
+            Ok(_) => return Err(Error::KeyAlreadyExists(name.to_string())),
+            Err(Error::KeyNotSet(_)) => (/* Expected this for new keys! */),
+            Err(e) => return Err(e),
+        }
+
+        let public_key = key.public_key();
+        {
+            let session = self.session.lock();
+            let mut public_template = key_template(ObjectClass::PUBLIC_KEY, name);
+            public_template.extend(public_key_attributes());
+            public_template.extend([
+                Attribute::KeyType(KeyType::EC_EDWARDS),
+                Attribute::EcPoint(der_octet_string(&public_key.to_bytes())),
+            ]);
+            let mut private_template = key_template(ObjectClass::PRIVATE_KEY, name);
+            private_template.extend(private_key_attributes());
+            private_template.extend([
+                Attribute::KeyType(KeyType::EC_EDWARDS),
+                Attribute::EcParams(ED25519_EC_PARAMS.to_vec()),
+                Attribute::Value(key.to_bytes().to_vec()),
+            ]);
+            session.create_object(&public_template)?;
+            session.create_object(&private_template)?;
+        }
+        self.set(name, public_key)
+    }
+
+    fn export_private_key_for_version(
+        &self,
+        _name: &str,
+        _version: Ed25519PublicKey,
+    ) -> Result<Ed25519PrivateKey, Error> {
+        // Private keys are non-extractable from the token
+        Err(Error::PermissionDenied)
+    }
+
+    fn get_public_key(&self, name: &str) -> Result<PublicKeyResponse, Error> {
+        let response = self.get(name)?;
+        Ok(PublicKeyResponse {
+            last_update: response.last_update,
+            public_key: response.value,
+        })
+    }
+
+    fn get_public_key_previous_version(&self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        match self.get(&get_previous_version_name(name)) {
+            Ok(response) => Ok(response.value),
+            Err(Error::KeyNotSet(_)) => Err(Error::KeyVersionNotFound(
+                name.into(),
+                "previous version".into(),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn rotate_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let public_key = self.get_public_key(name)?.public_key;
+        let previous_name = get_previous_version_name(name);
+
+        let new_public_key = {
+            let session = self.session.lock();
+            Self::rename_key_pair(&session, name, &previous_name)?;
+            Self::generate_key_pair(&session, name)?
+        };
+        self.set(&previous_name, public_key)?;
+        self.set(name, new_public_key.clone())?;
+        Ok(new_public_key)
+    }
+
+    fn sign<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let message =
+            signing_message(message).map_err(|err| Error::SerializationError(err.to_string()))?;
+        self.sign_message(name, &message)
+    }
+
+    fn sign_using_version<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        version: Ed25519PublicKey,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let label = self.version_label(name, &version)?;
+        let message =
+            signing_message(message).map_err(|err| Error::SerializationError(err.to_string()))?;
+        self.sign_message(&label, &message)
+    }
+}
+
+/// Returns the PKCS#11 module at `module_path`, loading and initializing it on first use.
+fn module(module_path: &Path) -> Result<Pkcs11, Error> {
+    let mut modules = MODULES.lock();
+    if let Some(pkcs11) = modules.get(module_path) {
+        return Ok(pkcs11.clone());
+    }
+
+    let pkcs11 = Pkcs11::new(module_path)?;
+    match pkcs11.initialize(CInitializeArgs::OsThreads) {
+        // The module may already be initialized by another library within this process.
+        Ok(()) | Err(Pkcs11Error::Pkcs11(RvError::CryptokiAlreadyInitialized)) => (),
+        Err(error) => return Err(error.into()),
+    }
+    modules.insert(module_path.to_path_buf(), pkcs11.clone());
+    Ok(pkcs11)
+}
+
+/// The attributes identifying the data object holding the value of `key`.
+fn data_template(key: &str) -> Vec<Attribute> {
+    vec![
+        Attribute::Class(ObjectClass::DATA),
+        Attribute::Application(APPLICATION.to_vec()),
+        Attribute::Label(key.into()),
+    ]
+}
+
+/// The attributes identifying the `class` object of the key pair `label`.
+fn key_template(class: ObjectClass, label: &str) -> Vec<Attribute> {
+    vec![
+        Attribute::Class(class),
+        Attribute::Id(APPLICATION.to_vec()),
+        Attribute::Label(label.into()),
+    ]
+}
+
+fn public_key_attributes() -> Vec<Attribute> {
+    vec![
+        Attribute::Token(true),
+        Attribute::Verify(true),
+        Attribute::EcParams(ED25519_EC_PARAMS.to_vec()),
+    ]
+}
+
+fn private_key_attributes() -> Vec<Attribute> {
+    vec![
+        Attribute::Token(true),
+        Attribute::Private(true),
+        Attribute::Sensitive(true),
+        Attribute::Extractable(false),
+        Attribute::Sign(true),
+    ]
+}
+
+fn find_object(session: &Session, template: &[Attribute]) -> Result<Option<ObjectHandle>, Error> {
+    Ok(session.find_objects(template)?.into_iter().next())
+}
+
+fn read_public_key(session: &Session, public_key: ObjectHandle) -> Result<Ed25519PublicKey, Error> {
+    let ec_point = match session
+        .get_attributes(public_key, &[AttributeType::EcPoint])?
+        .pop()
+    {
+        Some(Attribute::EcPoint(ec_point)) => ec_point,
+        _ => {
+            return Err(Error::InternalError(
+                "PKCS#11 public key without EC point".to_string(),
+            ))
+        },
+    };
+    // Tokens return the point either raw or, as mandated by the standard, as a DER octet string.
+    let bytes = match ec_point.as_slice() {
+        [0x04, 0x20, point @ ..] if point.len() == 0x20 => point,
+        point => point,
+    };
+    Ed25519PublicKey::try_from(bytes).map_err(|error| Error::InternalError(error.to_string()))
+}
+
+fn der_octet_string(bytes: &[u8]) -> Vec<u8> {
+    let mut der = vec![0x04, bytes.len() as u8];
+    der.extend_from_slice(bytes);
+    der
+}
+
+/// Private helper method to get the name of the previous version of the given key pair, as held in
+/// secure cryptographic storage.
+fn get_previous_version_name(name: &str) -> String {
+    format!("{}_previous", name)
+}
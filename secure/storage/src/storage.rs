@@ -2,8 +2,9 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    rocks_db::RocksDbStorage, CryptoStorage, Error, GetResponse, GitHubStorage, InMemoryStorage,
-    KVStorage, Namespaced, OnDiskStorage, PublicKeyResponse, VaultStorage,
+    rocks_db::RocksDbStorage, CryptoStorage, EncryptedOnDiskStorage, Error, GetResponse,
+    GitHubStorage, InMemoryStorage, KVStorage, Namespaced, OnDiskStorage, Pkcs11Storage,
+    PublicKeyResponse, VaultStorage,
 };
 use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
 use enum_dispatch::enum_dispatch;
@@ -22,7 +23,9 @@ pub enum Storage {
     InMemoryStorage(InMemoryStorage),
     NamespacedStorage(Namespaced<Box<Storage>>),
     OnDiskStorage(OnDiskStorage),
+    EncryptedOnDiskStorage(EncryptedOnDiskStorage),
     RocksDbStorage(RocksDbStorage),
+    Pkcs11Storage(Pkcs11Storage),
 }
 
 impl KVStorage for Box<Storage> {
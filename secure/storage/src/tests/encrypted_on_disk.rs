@@ -0,0 +1,130 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    tests::suite, EncryptedOnDiskStorage, KVStorage, OnDiskStorage, Storage, WrappingKeySource,
+};
+use aptos_temppath::TempPath;
+use std::fs;
+
+const KEY: &str = "Key";
+const VALUE: &str = "Secret_Value";
+
+fn passphrase(passphrase: &str) -> WrappingKeySource {
+    WrappingKeySource::Passphrase(passphrase.to_string())
+}
+
+#[test]
+fn encrypted_on_disk() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage = Storage::from(EncryptedOnDiskStorage::new(
+        path_buf,
+        &passphrase("passphrase"),
+    ));
+    suite::execute_all_storage_tests(&mut storage);
+}
+
+#[test]
+fn test_reopen() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), &passphrase("passphrase"));
+    storage.set(KEY, VALUE.to_string()).unwrap();
+
+    // The data is not stored in plaintext
+    let contents = fs::read_to_string(&path_buf).unwrap();
+    assert!(!contents.contains(VALUE));
+
+    let storage =
+        EncryptedOnDiskStorage::open(path_buf.clone(), &passphrase("passphrase")).unwrap();
+    assert_eq!(storage.get::<String>(KEY).unwrap().value, VALUE);
+    assert!(EncryptedOnDiskStorage::open(path_buf, &passphrase("wrong passphrase")).is_err());
+}
+
+#[test]
+fn test_key_file() {
+    let key_file = TempPath::new();
+    fs::write(key_file.path(), [7u8; 32]).unwrap();
+    let key_file = WrappingKeySource::KeyFile(key_file.path().to_path_buf());
+
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), &key_file);
+    storage.set(KEY, VALUE.to_string()).unwrap();
+
+    let storage = EncryptedOnDiskStorage::open(path_buf.clone(), &key_file).unwrap();
+    assert_eq!(storage.get::<String>(KEY).unwrap().value, VALUE);
+    assert!(EncryptedOnDiskStorage::open(path_buf, &passphrase("passphrase")).is_err());
+}
+
+#[test]
+fn test_tampering() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), &passphrase("passphrase"));
+    storage.set(KEY, VALUE.to_string()).unwrap();
+
+    let mut file: serde_json::Value =
+        serde_json::from_slice(&fs::read(&path_buf).unwrap()).unwrap();
+    file["iterations"] = serde_json::json!(1);
+    fs::write(&path_buf, serde_json::to_vec(&file).unwrap()).unwrap();
+
+    storage.get::<String>(KEY).unwrap_err();
+}
+
+#[test]
+fn test_rotate_wrapping_key() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), &passphrase("old"));
+    storage.set(KEY, VALUE.to_string()).unwrap();
+
+    storage.rotate_wrapping_key(&passphrase("new")).unwrap();
+    assert_eq!(storage.get::<String>(KEY).unwrap().value, VALUE);
+
+    assert!(EncryptedOnDiskStorage::open(path_buf.clone(), &passphrase("old")).is_err());
+    let storage = EncryptedOnDiskStorage::open(path_buf, &passphrase("new")).unwrap();
+    assert_eq!(storage.get::<String>(KEY).unwrap().value, VALUE);
+}
+
+#[test]
+fn test_open_existing() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    assert!(
+        EncryptedOnDiskStorage::open_existing(path_buf.clone(), &passphrase("passphrase")).is_err()
+    );
+    assert!(!path_buf.exists());
+
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), &passphrase("passphrase"));
+    storage.set(KEY, VALUE.to_string()).unwrap();
+    let storage =
+        EncryptedOnDiskStorage::open_existing(path_buf, &passphrase("passphrase")).unwrap();
+    assert_eq!(storage.get::<String>(KEY).unwrap().value, VALUE);
+}
+
+#[test]
+fn test_migrate_from_on_disk() {
+    let on_disk_path = TempPath::new();
+    let mut on_disk = OnDiskStorage::new(on_disk_path.path().to_path_buf());
+    on_disk.set(KEY, VALUE.to_string()).unwrap();
+    let on_disk_value = on_disk.get::<String>(KEY).unwrap();
+
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let storage = EncryptedOnDiskStorage::migrate_from_on_disk(
+        on_disk_path.path(),
+        path_buf.clone(),
+        &passphrase("passphrase"),
+    )
+    .unwrap();
+    assert_eq!(storage.get::<String>(KEY).unwrap(), on_disk_value);
+
+    // Refuses to overwrite an existing storage
+    assert!(EncryptedOnDiskStorage::migrate_from_on_disk(
+        on_disk_path.path(),
+        path_buf,
+        &passphrase("passphrase"),
+    )
+    .is_err());
+}
@@ -2,9 +2,11 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod encrypted_on_disk;
 mod github;
 mod in_memory;
 mod on_disk;
+mod pkcs11;
 mod rocks_db;
 mod suite;
 mod vault;
@@ -0,0 +1,157 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{CryptoStorage, Error, KVStorage, Pkcs11Storage};
+use aptos_crypto::{
+    ed25519::Ed25519PrivateKey, test_utils::TestAptosCrypto, PrivateKey, Signature, Uniform,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{env, path::PathBuf};
+
+const CRYPTO_NAME: &str = "crypto_name";
+
+/// The environment variables configuring the PKCS#11 module, token and user pin to test against.
+const PKCS11_MODULE: &str = "PKCS11_MODULE";
+const PKCS11_TOKEN_LABEL: &str = "PKCS11_TOKEN_LABEL";
+const PKCS11_PIN: &str = "PKCS11_PIN";
+
+/// This holds the canonical list of PKCS#11 storage tests. As they share the same token, they are
+/// run sequentially, resetting the storage after each test.
+const PKCS11_TESTS: &[fn(&mut Pkcs11Storage)] = &[
+    test_get_set,
+    test_create_sign_verify,
+    test_no_export,
+    test_import_key,
+    test_rotate_and_sign_using_version,
+];
+
+/// Runs the tests against a PKCS#11 token. This needs a token, so it is ignored by default. To
+/// run it against SoftHSM:
+///
+///   softhsm2-util --init-token --free --label aptos-test --so-pin 1234 --pin 1234
+///   PKCS11_MODULE=/usr/lib/softhsm/libsofthsm2.so PKCS11_TOKEN_LABEL=aptos-test PKCS11_PIN=1234 \
+///       cargo test -p aptos-secure-storage execute_storage_tests_pkcs11 -- --ignored
+#[ignore]
+#[test]
+fn execute_storage_tests_pkcs11() {
+    let mut storage = create_pkcs11();
+    for test in PKCS11_TESTS {
+        storage.reset_and_clear().unwrap();
+        test(&mut storage);
+    }
+    storage.reset_and_clear().unwrap();
+}
+
+fn create_pkcs11() -> Pkcs11Storage {
+    let env_var = |name: &str| env::var(name).unwrap_or_else(|_| panic!("{} is not set", name));
+    let module_path = PathBuf::from(env_var(PKCS11_MODULE));
+    let token_label = env_var(PKCS11_TOKEN_LABEL);
+    Pkcs11Storage::new(&module_path, &token_label, env_var(PKCS11_PIN))
+}
+
+fn test_get_set(storage: &mut Pkcs11Storage) {
+    let key = "key";
+    assert_eq!(
+        storage.get::<u64>(key).unwrap_err(),
+        Error::KeyNotSet(key.to_string())
+    );
+    storage.set(key, 1u64).unwrap();
+    assert_eq!(storage.get::<u64>(key).unwrap().value, 1);
+    storage.set(key, 2u64).unwrap();
+    assert_eq!(storage.get::<u64>(key).unwrap().value, 2);
+}
+
+fn test_create_sign_verify(storage: &mut Pkcs11Storage) {
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        public_key
+    );
+    assert_eq!(
+        storage.create_key(CRYPTO_NAME).unwrap_err(),
+        Error::KeyAlreadyExists(CRYPTO_NAME.to_string())
+    );
+
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+    signature.verify(&message, &public_key).unwrap();
+}
+
+fn test_no_export(storage: &mut Pkcs11Storage) {
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    assert_eq!(
+        storage.export_private_key(CRYPTO_NAME).unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage
+            .export_private_key_for_version(CRYPTO_NAME, public_key)
+            .unwrap_err(),
+        Error::PermissionDenied
+    );
+}
+
+fn test_import_key(storage: &mut Pkcs11Storage) {
+    let mut rng = StdRng::from_seed([13u8; 32]);
+    let private_key = Ed25519PrivateKey::generate(&mut rng);
+    let public_key = private_key.public_key();
+    storage
+        .import_private_key(CRYPTO_NAME, private_key.clone())
+        .unwrap();
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        public_key
+    );
+
+    // Signing within the token matches signing with the imported key
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+    signature.verify(&message, &public_key).unwrap();
+    assert_eq!(
+        signature,
+        aptos_crypto::SigningKey::sign(&private_key, &message).unwrap()
+    );
+}
+
+fn test_rotate_and_sign_using_version(storage: &mut Pkcs11Storage) {
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    assert!(storage
+        .get_public_key_previous_version(CRYPTO_NAME)
+        .is_err());
+
+    let rotated_public_key = storage.rotate_key(CRYPTO_NAME).unwrap();
+    assert_ne!(public_key, rotated_public_key);
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        rotated_public_key
+    );
+    assert_eq!(
+        storage
+            .get_public_key_previous_version(CRYPTO_NAME)
+            .unwrap(),
+        public_key
+    );
+
+    let message = TestAptosCrypto("Hello, World".to_string());
+    for version in [public_key.clone(), rotated_public_key.clone()] {
+        let signature = storage
+            .sign_using_version(CRYPTO_NAME, version.clone(), &message)
+            .unwrap();
+        signature.verify(&message, &version).unwrap();
+    }
+    let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+    signature.verify(&message, &rotated_public_key).unwrap();
+
+    // Only the current and previous versions are retained
+    storage.rotate_key(CRYPTO_NAME).unwrap();
+    assert_eq!(
+        storage
+            .get_public_key_previous_version(CRYPTO_NAME)
+            .unwrap(),
+        rotated_public_key
+    );
+    assert!(matches!(
+        storage.sign_using_version(CRYPTO_NAME, public_key, &message),
+        Err(Error::KeyVersionNotFound(..))
+    ));
+}
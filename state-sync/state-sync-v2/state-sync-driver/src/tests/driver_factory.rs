@@ -39,6 +39,7 @@ fn test_new_initialized_configs() {
         NO_OP_STORAGE_PRUNER_CONFIG,
         RocksdbConfigs::default(),
         false,
+        false, /* event type index */
         BUFFERED_STATE_TARGET_ITEMS,
         DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
     )
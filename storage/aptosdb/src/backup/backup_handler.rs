@@ -133,6 +133,31 @@ impl BackupHandler {
         Ok(Box::new(iterator))
     }
 
+    /// Gets the number of items in the state tree at specified version.
+    pub fn get_state_item_count(&self, version: Version) -> Result<usize> {
+        self.state_store.get_value_count(version)
+    }
+
+    /// Gets an iterator which yields up to `limit` items in the state tree, starting from the
+    /// `start_idx`-th one in the order of their hashed keys.
+    pub fn get_state_item_iter(
+        &self,
+        version: Version,
+        start_idx: usize,
+        limit: usize,
+    ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, StateValue)>> + Send + Sync>> {
+        let iterator = self
+            .state_store
+            .get_value_chunk_iter(version, start_idx, limit)?
+            .enumerate()
+            .map(move |(idx, res)| {
+                BACKUP_STATE_SNAPSHOT_VERSION.set(version as i64);
+                BACKUP_STATE_SNAPSHOT_LEAF_IDX.set((start_idx + idx) as i64);
+                res
+            });
+        Ok(Box::new(iterator))
+    }
+
     /// Gets the proof that proves a range of accounts.
     pub fn get_account_state_range_proof(
         &self,
@@ -48,7 +48,7 @@ use aptos_types::{
 use arc_swap::ArcSwapOption;
 use dashmap::DashMap;
 use itertools::zip_eq;
-use move_core_types::move_resource::MoveStructType;
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
 use std::{collections::HashMap, mem::swap, sync::Arc};
 
 /// Alternate implementation of [crate::state_store::buffered_state::BufferedState] for use with consensus-only-perf-test feature.
@@ -579,6 +579,25 @@ impl DbReader for FakeAptosDB {
             .get_events(event_key, start, order, limit, ledger_version)
     }
 
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        start_index: u64,
+        end_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<(Vec<EventWithVersion>, Option<(Version, u64)>)> {
+        self.inner.get_events_by_type(
+            type_tag,
+            start_version,
+            start_index,
+            end_version,
+            limit,
+            ledger_version,
+        )
+    }
+
     fn get_block_timestamp(&self, version: Version) -> Result<u64> {
         self.inner.get_block_timestamp(version)
     }
@@ -835,6 +854,14 @@ impl DbReader for FakeAptosDB {
         self.inner.indexer_enabled()
     }
 
+    fn event_type_index_enabled(&self) -> bool {
+        self.inner.event_type_index_enabled()
+    }
+
+    fn event_type_index_start_version(&self) -> Option<Version> {
+        self.inner.event_type_index_start_version()
+    }
+
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         self.inner.get_state_storage_usage(version)
     }
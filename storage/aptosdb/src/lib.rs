@@ -116,6 +116,7 @@ use aptos_types::{
 };
 use aptos_vm::data_cache::AsMoveResolver;
 use itertools::zip_eq;
+use move_core_types::language_storage::TypeTag;
 use move_resource_viewer::MoveValueAnnotator;
 use once_cell::sync::Lazy;
 use std::{
@@ -335,6 +336,7 @@ impl AptosDB {
         pruner_config: PrunerConfig,
         rocksdb_configs: RocksdbConfigs,
         enable_indexer: bool,
+        enable_event_type_index: bool,
         buffered_state_target_items: usize,
         max_num_nodes_per_lru_cache_shard: usize,
     ) -> Result<Self> {
@@ -357,7 +359,11 @@ impl AptosDB {
         );
 
         if !readonly && enable_indexer {
-            myself.open_indexer(db_root_path, rocksdb_configs.index_db_config)?;
+            myself.open_indexer(
+                db_root_path,
+                rocksdb_configs.index_db_config,
+                enable_event_type_index,
+            )?;
         }
 
         Ok(myself)
@@ -445,8 +451,9 @@ impl AptosDB {
         &mut self,
         db_root_path: impl AsRef<Path>,
         rocksdb_config: RocksdbConfig,
+        enable_event_type_index: bool,
     ) -> Result<()> {
-        let indexer = Indexer::open(&db_root_path, rocksdb_config)?;
+        let indexer = Indexer::open(&db_root_path, rocksdb_config, enable_event_type_index)?;
         let ledger_next_version = self
             .get_latest_transaction_info_option()?
             .map_or(0, |(v, _)| v + 1);
@@ -473,7 +480,20 @@ impl AptosDB {
                     .transaction_store
                     .get_write_sets(next_version, end_version)?;
                 let write_sets_ref: Vec<_> = write_sets.iter().collect();
-                indexer.index_with_annotator(&annotator, next_version, &write_sets_ref)?;
+                let events = self
+                    .event_store
+                    .get_events_by_version_iter(
+                        next_version,
+                        (end_version - next_version) as usize,
+                    )?
+                    .collect::<Result<Vec<_>>>()?;
+                let events_ref: Vec<_> = events.iter().map(Vec::as_slice).collect();
+                indexer.index_with_annotator(
+                    &annotator,
+                    next_version,
+                    &write_sets_ref,
+                    &events_ref,
+                )?;
 
                 next_version = end_version;
             }
@@ -549,6 +569,7 @@ impl AptosDB {
             NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
             RocksdbConfigs::default(),
             enable_indexer,
+            enable_indexer, /* event type index */
             buffered_state_target_items,
             max_num_nodes_per_lru_cache_shard,
         )
@@ -1313,6 +1334,44 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        start_index: u64,
+        end_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<(Vec<EventWithVersion>, Option<(Version, u64)>)> {
+        gauged_api("get_events_by_type", || {
+            error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+            self.error_if_ledger_pruned("Event", start_version)?;
+            let indexer = match &self.indexer {
+                Some(indexer) => indexer,
+                None => bail!("Indexer not enabled."),
+            };
+
+            let end_version = std::cmp::min(end_version, ledger_version.saturating_add(1));
+            let (positions, next) = indexer.get_events_by_type(
+                type_tag,
+                start_version,
+                start_index,
+                end_version,
+                limit,
+            )?;
+            let events = positions
+                .into_iter()
+                .map(|(version, index)| {
+                    let event = self
+                        .event_store
+                        .get_event_by_version_and_index(version, index)?;
+                    Ok(EventWithVersion::new(version, event))
+                })
+                .collect::<Result<_>>()?;
+            Ok((events, next))
+        })
+    }
+
     fn get_transaction_iterator(
         &self,
         start_version: Version,
@@ -1693,6 +1752,16 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_merkle_min_readable_version(&self) -> Result<Version> {
+        gauged_api("get_state_merkle_min_readable_version", || {
+            Ok(self
+                .state_store
+                .state_db
+                .state_merkle_pruner
+                .get_min_readable_version())
+        })
+    }
+
     fn is_ledger_pruner_enabled(&self) -> Result<bool> {
         gauged_api("is_ledger_pruner_enabled", || {
             Ok(self.ledger_pruner.is_pruner_enabled())
@@ -1717,6 +1786,20 @@ impl DbReader for AptosDB {
         self.indexer.is_some()
     }
 
+    /// Returns whether the event type index of the indexer DB has been enabled or not
+    fn event_type_index_enabled(&self) -> bool {
+        self.indexer
+            .as_ref()
+            .map_or(false, |indexer| indexer.event_type_index_enabled())
+    }
+
+    /// Returns the first version covered by the event type index, None if it's disabled
+    fn event_type_index_start_version(&self) -> Option<Version> {
+        self.indexer
+            .as_ref()
+            .and_then(|indexer| indexer.event_type_index_start_version())
+    }
+
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         gauged_api("get_state_storage_usage", || {
             if let Some(v) = version {
@@ -1909,7 +1992,13 @@ impl DbWriter for AptosDB {
                     .with_label_values(&["indexer_index"])
                     .start_timer();
                 let write_sets: Vec<_> = txns_to_commit.iter().map(|txn| txn.write_set()).collect();
-                indexer.index(self.state_store.clone(), first_version, &write_sets)?;
+                let events: Vec<_> = txns_to_commit.iter().map(|txn| txn.events()).collect();
+                indexer.index(
+                    self.state_store.clone(),
+                    first_version,
+                    &write_sets,
+                    &events,
+                )?;
             }
 
             // Once everything is successfully persisted, update the latest in-memory ledger info.
@@ -778,25 +778,35 @@ impl StateStore {
         }))
     }
 
-    pub fn get_value_chunk_with_proof(
+    pub fn get_value_chunk_iter(
         self: &Arc<Self>,
         version: Version,
         first_index: usize,
         chunk_size: usize,
-    ) -> Result<StateValueChunkWithProof> {
-        let result_iter = JellyfishMerkleIterator::new_by_index(
+    ) -> Result<impl Iterator<Item = Result<(StateKey, StateValue)>> + Send + Sync> {
+        let store = Arc::clone(self);
+        Ok(JellyfishMerkleIterator::new_by_index(
             Arc::clone(&self.state_merkle_db),
             version,
             first_index,
         )?
-        .take(chunk_size);
-        let state_key_values: Vec<(StateKey, StateValue)> = result_iter
-            .into_iter()
-            .map(|res| {
-                res.and_then(|(_, (key, version))| {
-                    Ok((key.clone(), self.expect_value_by_version(&key, version)?))
-                })
-            })
+        .take(chunk_size)
+        .map(move |res| match res {
+            Ok((_hashed_key, (key, version))) => {
+                Ok((key.clone(), store.expect_value_by_version(&key, version)?))
+            },
+            Err(err) => Err(err),
+        }))
+    }
+
+    pub fn get_value_chunk_with_proof(
+        self: &Arc<Self>,
+        version: Version,
+        first_index: usize,
+        chunk_size: usize,
+    ) -> Result<StateValueChunkWithProof> {
+        let state_key_values: Vec<(StateKey, StateValue)> = self
+            .get_value_chunk_iter(version, first_index, chunk_size)?
             .collect::<Result<Vec<_>>>()?;
         ensure!(
             !state_key_values.is_empty(),
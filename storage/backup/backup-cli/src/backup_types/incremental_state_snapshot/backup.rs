@@ -0,0 +1,387 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::incremental_state_snapshot::manifest::{
+        IncrementalStateSnapshotBackup, IncrementalStateSnapshotChunk, StateSnapshotChunkProof,
+        StateSnapshotManifest,
+    },
+    metadata::Metadata,
+    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    utils::{
+        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
+        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::{TransactionWrite, WriteSet},
+};
+use clap::Parser;
+use once_cell::sync::Lazy;
+use std::{collections::BTreeMap, convert::TryInto, str::FromStr, sync::Arc};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Parser)]
+pub struct IncrementalStateSnapshotBackupOpt {
+    #[clap(
+        long = "state-snapshot-epoch",
+        help = "Epoch at the end of which a state snapshot is to be taken."
+    )]
+    pub epoch: u64,
+    #[clap(
+        long = "base-state-manifest",
+        help = "Manifest of the state snapshot, either full or incremental, to record the changes \
+        since."
+    )]
+    pub base_manifest: FileHandle,
+}
+
+/// Backs up the state items changed since a base snapshot, as found in the write sets of the
+/// transactions in between.
+///
+/// The changes are collected in memory before being written, so the base snapshot should be
+/// recent enough.
+pub struct IncrementalStateSnapshotBackupController {
+    epoch: u64,
+    version: Option<Version>, // initialize before using
+    base_manifest: FileHandle,
+    max_chunk_size: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl IncrementalStateSnapshotBackupController {
+    pub fn new(
+        opt: IncrementalStateSnapshotBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            epoch: opt.epoch,
+            version: None,
+            base_manifest: opt.base_manifest,
+            max_chunk_size: global_opt.max_chunk_size,
+            client,
+            storage,
+        }
+    }
+
+    pub async fn run(self) -> Result<FileHandle> {
+        info!(
+            "Incremental state snapshot backup started, for epoch {}, base: {}.",
+            self.epoch, self.base_manifest,
+        );
+        let ret = self
+            .run_impl()
+            .await
+            .map_err(|e| anyhow!("Incremental state snapshot backup failed: {}", e))?;
+        info!(
+            "Incremental state snapshot backup succeeded. Manifest: {}",
+            ret
+        );
+        Ok(ret)
+    }
+
+    async fn run_impl(mut self) -> Result<FileHandle> {
+        self.version = Some(self.get_version_for_epoch_ending(self.epoch).await?);
+        let base: StateSnapshotManifest = self.storage.load_json_file(&self.base_manifest).await?;
+        let base_version = base.version();
+        ensure!(
+            base_version <= self.version(),
+            "Base state snapshot at version {} is newer than version {}.",
+            base_version,
+            self.version(),
+        );
+
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&self.backup_name())
+            .await?;
+
+        let changes = self.get_changes_since(base_version).await?;
+        info!(
+            base_version = base_version,
+            num_changes = changes.len(),
+            "State changes collected."
+        );
+        let chunks = self.write_chunks(&backup_handle, &changes).await?;
+
+        let num_items = self.client.get_state_item_count(self.version()).await?;
+        ensure!(num_items > 0, "State is empty.");
+        let right_most_key = self.get_right_most_key(num_items).await?;
+        // Items that are not deleted since the base snapshot still exist, so are the proofs of
+        // their ranges.
+        let mut chunk_last_keys: Vec<HashValue> = base
+            .chunk_last_keys()
+            .into_iter()
+            .filter(|key| *key < right_most_key && !matches!(changes.get(key), Some((_, None))))
+            .collect();
+        chunk_last_keys.push(right_most_key);
+        let chunk_proofs = self
+            .write_chunk_proofs(&backup_handle, chunk_last_keys)
+            .await?;
+
+        self.write_manifest(
+            &backup_handle,
+            base_version,
+            num_items,
+            chunks,
+            chunk_proofs,
+        )
+        .await
+    }
+}
+
+impl IncrementalStateSnapshotBackupController {
+    fn version(&self) -> Version {
+        self.version.unwrap()
+    }
+
+    fn backup_name(&self) -> String {
+        format!(
+            "incremental_state_epoch_{}_ver_{}",
+            self.epoch,
+            self.version()
+        )
+    }
+
+    fn manifest_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("incremental_state.manifest").unwrap());
+        &NAME
+    }
+
+    fn proof_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state.proof").unwrap());
+        &NAME
+    }
+
+    fn chunk_name(first_idx: usize) -> ShellSafeName {
+        format!("{}-.changes", first_idx).try_into().unwrap()
+    }
+
+    fn chunk_proof_name(chunk_idx: usize) -> ShellSafeName {
+        format!("{}.proof", chunk_idx).try_into().unwrap()
+    }
+
+    async fn get_version_for_epoch_ending(&self, epoch: u64) -> Result<u64> {
+        let ledger_info: LedgerInfoWithSignatures = bcs::from_bytes(
+            self.client
+                .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+                .await?
+                .read_record_bytes()
+                .await?
+                .ok_or_else(|| {
+                    anyhow!("Failed to get epoch ending ledger info for epoch {}", epoch)
+                })?
+                .as_ref(),
+        )?;
+        Ok(ledger_info.ledger_info().version())
+    }
+
+    /// Replays the write sets of the transactions after `base_version`, returning the final value
+    /// of each touched item by its key hash.
+    async fn get_changes_since(
+        &self,
+        base_version: Version,
+    ) -> Result<BTreeMap<HashValue, (StateKey, Option<StateValue>)>> {
+        let mut changes = BTreeMap::new();
+        if base_version == self.version() {
+            return Ok(changes);
+        }
+
+        let mut transactions_file = self
+            .client
+            .get_transactions(base_version + 1, (self.version() - base_version) as usize)
+            .await?;
+        while let Some(record_bytes) = transactions_file.read_record_bytes().await? {
+            let (_txn, _txn_info, _events, write_set): (
+                Transaction,
+                TransactionInfo,
+                Vec<ContractEvent>,
+                WriteSet,
+            ) = bcs::from_bytes(&record_bytes)?;
+            for (key, write_op) in write_set {
+                changes.insert(key.hash(), (key, write_op.as_state_value()));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    async fn get_right_most_key(&self, num_items: usize) -> Result<HashValue> {
+        let record_bytes = self
+            .client
+            .get_state_snapshot_chunk(self.version(), num_items - 1, 1)
+            .await?
+            .read_record_bytes()
+            .await?
+            .ok_or_else(|| anyhow!("Failed to get the right most state item."))?;
+        let (key, _): (StateKey, StateValue) = bcs::from_bytes(&record_bytes)?;
+        Ok(key.hash())
+    }
+
+    async fn write_chunks(
+        &self,
+        backup_handle: &BackupHandleRef,
+        changes: &BTreeMap<HashValue, (StateKey, Option<StateValue>)>,
+    ) -> Result<Vec<IncrementalStateSnapshotChunk>> {
+        let mut chunks = vec![];
+        let mut chunk_bytes = vec![];
+        let mut chunk_first_idx = 0;
+        let mut chunk_keys: Option<(HashValue, HashValue)> = None;
+
+        for (idx, (key_hash, record)) in changes.iter().enumerate() {
+            let record_bytes = bcs::to_bytes(record)?;
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+                let (first_key, last_key) = chunk_keys.take().expect("Chunk not empty.");
+                chunks.push(
+                    self.write_chunk(
+                        backup_handle,
+                        &chunk_bytes,
+                        chunk_first_idx,
+                        first_key,
+                        last_key,
+                    )
+                    .await?,
+                );
+                chunk_bytes = vec![];
+                chunk_first_idx = idx;
+            }
+
+            chunk_bytes.extend((record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+            chunk_keys = Some((chunk_keys.map_or(*key_hash, |(first, _)| first), *key_hash));
+        }
+
+        if let Some((first_key, last_key)) = chunk_keys {
+            chunks.push(
+                self.write_chunk(
+                    backup_handle,
+                    &chunk_bytes,
+                    chunk_first_idx,
+                    first_key,
+                    last_key,
+                )
+                .await?,
+            );
+        }
+
+        Ok(chunks)
+    }
+
+    async fn write_chunk(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_bytes: &[u8],
+        first_idx: usize,
+        first_key: HashValue,
+        last_key: HashValue,
+    ) -> Result<IncrementalStateSnapshotChunk> {
+        let (chunk_handle, mut chunk_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
+            .await?;
+        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file.shutdown().await?;
+
+        Ok(IncrementalStateSnapshotChunk {
+            first_key,
+            last_key,
+            blobs: chunk_handle,
+        })
+    }
+
+    async fn write_chunk_proofs(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_last_keys: Vec<HashValue>,
+    ) -> Result<Vec<StateSnapshotChunkProof>> {
+        let mut chunk_proofs = vec![];
+        for (chunk_idx, last_key) in chunk_last_keys.into_iter().enumerate() {
+            let (proof_handle, mut proof_file) = self
+                .storage
+                .create_for_write(backup_handle, &Self::chunk_proof_name(chunk_idx))
+                .await?;
+            tokio::io::copy(
+                &mut self
+                    .client
+                    .get_account_range_proof(last_key, self.version())
+                    .await?,
+                &mut proof_file,
+            )
+            .await?;
+            proof_file.shutdown().await?;
+
+            chunk_proofs.push(StateSnapshotChunkProof {
+                last_key,
+                proof: proof_handle,
+            });
+        }
+
+        Ok(chunk_proofs)
+    }
+
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        base_version: Version,
+        num_items: usize,
+        chunks: Vec<IncrementalStateSnapshotChunk>,
+        chunk_proofs: Vec<StateSnapshotChunkProof>,
+    ) -> Result<FileHandle> {
+        let proof_bytes = self.client.get_state_root_proof(self.version()).await?;
+        let (txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&proof_bytes)?;
+
+        let (proof_handle, mut proof_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::proof_name())
+            .await?;
+        proof_file.write_all(&proof_bytes).await?;
+        proof_file.shutdown().await?;
+
+        let manifest = IncrementalStateSnapshotBackup {
+            epoch: self.epoch,
+            version: self.version(),
+            root_hash: txn_info.transaction_info().ensure_state_checkpoint_hash()?,
+            base: self.base_manifest.clone(),
+            base_version,
+            num_items,
+            chunks,
+            chunk_proofs,
+            proof: proof_handle,
+        };
+
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata = Metadata::new_incremental_state_snapshot_backup(
+            self.epoch,
+            self.version(),
+            base_version,
+            manifest_handle.clone(),
+        );
+        self.storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+            .await?;
+
+        Ok(manifest_handle)
+    }
+}
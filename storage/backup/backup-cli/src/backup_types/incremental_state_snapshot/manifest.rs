@@ -0,0 +1,91 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{backup_types::state_snapshot::manifest::StateSnapshotBackup, storage::FileHandle};
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// A chunk of the changes recorded in an incremental state snapshot, representing the changed
+/// state items in the key range [`first_key`, `last_key`] (right side inclusive).
+#[derive(Deserialize, Serialize)]
+pub struct IncrementalStateSnapshotChunk {
+    /// key of the first changed item in this chunk.
+    pub first_key: HashValue,
+    /// key of the last changed item in this chunk.
+    pub last_key: HashValue,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, Option<state_value>)`, a `None` meaning the item was deleted.
+    pub blobs: FileHandle,
+}
+
+/// Proof of a chunk of the complete state view at the version of an incremental state snapshot,
+/// used to cut the state into chunks when restoring it.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotChunkProof {
+    /// key of the last item in the chunk, which exists at the version of the snapshot.
+    pub last_key: HashValue,
+    /// BCS serialized `SparseMerkleRangeProof` that proves the items up to `last_key` add up to
+    /// the root hash indicated in the backup (`IncrementalStateSnapshotBackup::root_hash`).
+    pub proof: FileHandle,
+}
+
+/// Incremental state snapshot backup manifest, representing the changes to the state since a base
+/// snapshot, which is either a full `StateSnapshotBackup` or another incremental one. Applied on
+/// top of its chain of bases, it represents a complete state view at specified version.
+#[derive(Deserialize, Serialize)]
+pub struct IncrementalStateSnapshotBackup {
+    /// Version at which this state snapshot is taken.
+    pub version: Version,
+    /// Epoch in which this state snapshot is taken.
+    pub epoch: u64,
+    /// Hash of the state tree root.
+    pub root_hash: HashValue,
+    /// Manifest of the base snapshot.
+    pub base: FileHandle,
+    /// Version of the base snapshot.
+    pub base_version: Version,
+    /// Number of items in the state tree.
+    pub num_items: usize,
+    /// Items created, modified or deleted since the base snapshot, in chunks.
+    pub chunks: Vec<IncrementalStateSnapshotChunk>,
+    /// Proofs cutting the complete state into chunks, ordered by key. The last one is of the
+    /// right most item in the state tree.
+    ///
+    /// These are carried over from the base snapshot, except those of deleted items, so as not to
+    /// depend on the unchanged items.
+    pub chunk_proofs: Vec<StateSnapshotChunkProof>,
+    /// BCS serialized
+    /// `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)`, same as
+    /// `StateSnapshotBackup::proof`.
+    pub proof: FileHandle,
+}
+
+/// Either kind of state snapshot manifest, telling them apart by their fields.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum StateSnapshotManifest {
+    Incremental(IncrementalStateSnapshotBackup),
+    Full(StateSnapshotBackup),
+}
+
+impl StateSnapshotManifest {
+    pub fn version(&self) -> Version {
+        match self {
+            Self::Incremental(manifest) => manifest.version,
+            Self::Full(manifest) => manifest.version,
+        }
+    }
+
+    /// Keys of the last items of the chunks the complete state view is cut into.
+    pub fn chunk_last_keys(&self) -> Vec<HashValue> {
+        match self {
+            Self::Incremental(manifest) => manifest
+                .chunk_proofs
+                .iter()
+                .map(|chunk| chunk.last_key)
+                .collect(),
+            Self::Full(manifest) => manifest.chunks.iter().map(|chunk| chunk.last_key).collect(),
+        }
+    }
+}
@@ -0,0 +1,391 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::EpochHistory,
+        incremental_state_snapshot::manifest::{
+            IncrementalStateSnapshotBackup, StateSnapshotManifest,
+        },
+        state_snapshot::{manifest::StateSnapshotBackup, restore::StateSnapshotRestoreController},
+    },
+    metrics::{
+        restore::{
+            STATE_SNAPSHOT_LEAF_INDEX, STATE_SNAPSHOT_TARGET_LEAF_INDEX, STATE_SNAPSHOT_VERSION,
+        },
+        verify::{
+            VERIFY_STATE_SNAPSHOT_LEAF_INDEX, VERIFY_STATE_SNAPSHOT_TARGET_LEAF_INDEX,
+            VERIFY_STATE_SNAPSHOT_VERSION,
+        },
+        OTHER_TIMERS_SECONDS,
+    },
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
+        GlobalRestoreOptions, RestoreRunMode,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use clap::Parser;
+use futures::{
+    future,
+    stream::{self, BoxStream},
+    Stream, StreamExt, TryStreamExt,
+};
+use serde::de::DeserializeOwned;
+use std::{cmp::Ordering, sync::Arc};
+use tokio::time::Instant;
+
+/// A state item with its key hash, the value being `None` if it's deleted.
+type StateRecord = (HashValue, StateKey, Option<StateValue>);
+
+#[derive(Parser)]
+pub struct IncrementalStateSnapshotRestoreOpt {
+    #[clap(
+        long = "state-manifest",
+        help = "Manifest of the incremental state snapshot. Its chain of base snapshots, down to \
+        a full one, is restored along."
+    )]
+    pub manifest_handle: FileHandle,
+    #[clap(long = "state-into-version")]
+    pub version: Version,
+    #[clap(long)]
+    pub validate_modules: bool,
+}
+
+/// Restores the state at the version of an incremental state snapshot, by applying the changes
+/// of it and of its chain of base snapshots on top of the full snapshot at the bottom of the chain.
+///
+/// The resulting state items are fed in chunks to the same receiver as a full snapshot is, with
+/// the chunk proofs of the incremental snapshot, so the root hash is verified in the end.
+pub struct IncrementalStateSnapshotRestoreController {
+    storage: Arc<dyn BackupStorage>,
+    run_mode: Arc<RestoreRunMode>,
+    /// State snapshot restores to this version.
+    version: Version,
+    manifest_handle: FileHandle,
+    /// Global "target_version" for the entire restore process, if `version` is newer than this,
+    /// nothing will be done, otherwise, this has no effect.
+    target_version: Version,
+    epoch_history: Option<Arc<EpochHistory>>,
+    concurrent_downloads: usize,
+    validate_modules: bool,
+}
+
+impl IncrementalStateSnapshotRestoreController {
+    pub fn new(
+        opt: IncrementalStateSnapshotRestoreOpt,
+        global_opt: GlobalRestoreOptions,
+        storage: Arc<dyn BackupStorage>,
+        epoch_history: Option<Arc<EpochHistory>>,
+    ) -> Self {
+        Self {
+            storage,
+            run_mode: global_opt.run_mode,
+            version: opt.version,
+            manifest_handle: opt.manifest_handle,
+            target_version: global_opt.target_version,
+            epoch_history,
+            concurrent_downloads: global_opt.concurrent_downloads,
+            validate_modules: opt.validate_modules,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let name = self.name();
+        let start = Instant::now();
+        info!("{} started. Manifest: {}", name, self.manifest_handle);
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("{} failed: {}", name, e))?;
+        info!(time = start.elapsed().as_secs(), "{} succeeded.", name);
+        Ok(())
+    }
+}
+
+impl IncrementalStateSnapshotRestoreController {
+    fn name(&self) -> String {
+        format!("incremental state snapshot {}", self.run_mode.name())
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        if self.version > self.target_version {
+            warn!(
+                "Trying to restore state snapshot to version {}, which is newer than the target version {}, skipping.",
+                self.version,
+                self.target_version,
+            );
+            return Ok(());
+        }
+
+        let manifest: IncrementalStateSnapshotBackup =
+            self.storage.load_json_file(&self.manifest_handle).await?;
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(&manifest.proof).await?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        let state_root_hash = txn_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        ensure!(
+            state_root_hash == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            manifest.root_hash,
+            state_root_hash,
+        );
+        if let Some(epoch_history) = self.epoch_history.as_ref() {
+            epoch_history.verify_ledger_info(&li)?;
+        }
+
+        let (base, increments) = self.load_chain(&manifest).await?;
+        info!(
+            base_version = base.version,
+            num_increments = increments.len() + 1,
+            "Loaded chain of state snapshots."
+        );
+        let mut records = self.read_full_snapshot(base);
+        for increment in increments.iter().chain(std::iter::once(&manifest)) {
+            records = Self::merge(records, self.read_increment(increment));
+        }
+        let mut records = records
+            .try_filter_map(|(key_hash, key, value)| {
+                future::ok(value.map(|value| (key_hash, key, value)))
+            })
+            .boxed();
+
+        let receiver = Arc::new(Mutex::new(Some(
+            self.run_mode
+                .get_state_restore_receiver(self.version, manifest.root_hash)?,
+        )));
+
+        let (ver_gauge, tgt_leaf_idx, leaf_idx) = if self.run_mode.is_verify() {
+            (
+                &VERIFY_STATE_SNAPSHOT_VERSION,
+                &VERIFY_STATE_SNAPSHOT_TARGET_LEAF_INDEX,
+                &VERIFY_STATE_SNAPSHOT_LEAF_INDEX,
+            )
+        } else {
+            (
+                &STATE_SNAPSHOT_VERSION,
+                &STATE_SNAPSHOT_TARGET_LEAF_INDEX,
+                &STATE_SNAPSHOT_LEAF_INDEX,
+            )
+        };
+        ver_gauge.set(self.version as i64);
+        tgt_leaf_idx.set(manifest.num_items as i64 - 1);
+
+        let resume_point_opt = receiver.lock().as_mut().unwrap().previous_key_hash()?;
+        if let Some(resume_point) = resume_point_opt {
+            info!(
+                resume_point = %resume_point,
+                "Resumed incremental state snapshot restore."
+            );
+        }
+
+        let total_chunks = manifest.chunk_proofs.len();
+        let mut num_items = 0;
+        let mut pending_record = None;
+        let start = Instant::now();
+        for (chunk_idx, chunk_proof) in manifest.chunk_proofs.into_iter().enumerate() {
+            let mut blobs = vec![];
+            let mut last_key = None;
+            loop {
+                let record = match pending_record.take() {
+                    Some(record) => Some(record),
+                    None => records.try_next().await?,
+                };
+                match record {
+                    Some((key_hash, key, value)) if key_hash <= chunk_proof.last_key => {
+                        blobs.push((key, value));
+                        last_key = Some(key_hash);
+                    },
+                    record => {
+                        pending_record = record;
+                        break;
+                    },
+                }
+            }
+            ensure!(
+                last_key == Some(chunk_proof.last_key),
+                "State item {:x} proven by chunk proof {} is missing.",
+                chunk_proof.last_key,
+                chunk_idx,
+            );
+            num_items += blobs.len();
+
+            if resume_point_opt.map_or(false, |resume_point| chunk_proof.last_key <= resume_point) {
+                continue;
+            }
+            let _timer = OTHER_TIMERS_SECONDS
+                .with_label_values(&["add_state_chunk"])
+                .start_timer();
+            let proof = self.storage.load_bcs_file(&chunk_proof.proof).await?;
+            if self.validate_modules {
+                StateSnapshotRestoreController::validate_modules(&blobs);
+            }
+            let receiver = receiver.clone();
+            tokio::task::spawn_blocking(move || {
+                receiver.lock().as_mut().unwrap().add_chunk(blobs, proof)
+            })
+            .await??;
+            leaf_idx.set(num_items as i64 - 1);
+            info!(
+                chunk = chunk_idx,
+                total_chunks = total_chunks,
+                last_idx = num_items - 1,
+                values_per_second = (num_items as f64 / start.elapsed().as_secs_f64()) as u64,
+                "State chunk added.",
+            );
+        }
+        ensure!(
+            pending_record.is_none() && records.try_next().await?.is_none(),
+            "State items found beyond the last chunk proof.",
+        );
+        ensure!(
+            num_items == manifest.num_items,
+            "Number of state items mismatch. restored: {}, expected: {}",
+            num_items,
+            manifest.num_items,
+        );
+
+        tokio::task::spawn_blocking(move || receiver.lock().take().unwrap().finish()).await??;
+        self.run_mode.finish();
+        Ok(())
+    }
+
+    /// Follows the bases of `manifest` down to a full snapshot, returning it and the incremental
+    /// snapshots on top of it, oldest first.
+    async fn load_chain(
+        &self,
+        manifest: &IncrementalStateSnapshotBackup,
+    ) -> Result<(StateSnapshotBackup, Vec<IncrementalStateSnapshotBackup>)> {
+        let mut increments = vec![];
+        let mut base_handle = manifest.base.clone();
+        let mut base_version = manifest.base_version;
+        loop {
+            let base: StateSnapshotManifest = self.storage.load_json_file(&base_handle).await?;
+            ensure!(
+                base.version() == base_version,
+                "Base state snapshot {} is at version {}, expected: {}",
+                base_handle,
+                base.version(),
+                base_version,
+            );
+            match base {
+                StateSnapshotManifest::Full(full) => {
+                    increments.reverse();
+                    return Ok((full, increments));
+                },
+                StateSnapshotManifest::Incremental(increment) => {
+                    base_handle = increment.base.clone();
+                    base_version = increment.base_version;
+                    increments.push(increment);
+                },
+            }
+        }
+    }
+
+    fn read_full_snapshot(
+        &self,
+        manifest: StateSnapshotBackup,
+    ) -> BoxStream<'static, Result<StateRecord>> {
+        let files = manifest
+            .chunks
+            .into_iter()
+            .map(|chunk| chunk.blobs)
+            .collect();
+        self.read_records(files)
+            .map_ok(|(key, value): (StateKey, StateValue)| (key.hash(), key, Some(value)))
+            .boxed()
+    }
+
+    fn read_increment(
+        &self,
+        manifest: &IncrementalStateSnapshotBackup,
+    ) -> BoxStream<'static, Result<StateRecord>> {
+        let files = manifest
+            .chunks
+            .iter()
+            .map(|chunk| chunk.blobs.clone())
+            .collect();
+        self.read_records(files)
+            .map_ok(|(key, value): (StateKey, Option<StateValue>)| (key.hash(), key, value))
+            .boxed()
+    }
+
+    /// Reads the records in a series of chunk files, downloading a few files ahead.
+    fn read_records<R: DeserializeOwned + Send + 'static>(
+        &self,
+        files: Vec<FileHandle>,
+    ) -> impl Stream<Item = Result<R>> + Send + 'static {
+        let storage = self.storage.clone();
+        let futs_iter = files.into_iter().map(move |file_handle| {
+            let storage = storage.clone();
+            async move {
+                tokio::spawn(async move {
+                    let mut file = storage.open_for_read(&file_handle).await?;
+                    let mut records: Vec<R> = vec![];
+                    while let Some(record_bytes) = file.read_record_bytes().await? {
+                        records.push(bcs::from_bytes(&record_bytes)?);
+                    }
+                    Result::<_>::Ok(records)
+                })
+                .await?
+            }
+        });
+        let con = self.concurrent_downloads;
+        stream::iter(futs_iter)
+            .buffered_x(con * 2, con)
+            .map_ok(|records| stream::iter(records.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Merges two streams of records ordered by key hash, records in `changes` overriding those in
+    /// `base` of the same key.
+    fn merge(
+        base: BoxStream<'static, Result<StateRecord>>,
+        changes: BoxStream<'static, Result<StateRecord>>,
+    ) -> BoxStream<'static, Result<StateRecord>> {
+        stream::try_unfold(
+            (base.fuse(), changes.fuse(), None, None),
+            |(mut base, mut changes, mut base_head, mut changes_head): (
+                _,
+                _,
+                Option<StateRecord>,
+                Option<StateRecord>,
+            )| async move {
+                if base_head.is_none() {
+                    base_head = base.try_next().await?;
+                }
+                if changes_head.is_none() {
+                    changes_head = changes.try_next().await?;
+                }
+                let next = match (base_head.take(), changes_head.take()) {
+                    (None, None) => return Ok(None),
+                    (Some(record), None) | (None, Some(record)) => record,
+                    (Some(base_record), Some(change)) => match base_record.0.cmp(&change.0) {
+                        Ordering::Less => {
+                            changes_head = Some(change);
+                            base_record
+                        },
+                        Ordering::Greater => {
+                            base_head = Some(base_record);
+                            change
+                        },
+                        Ordering::Equal => change,
+                    },
+                };
+                Ok(Some((next, (base, changes, base_head, changes_head))))
+            },
+        )
+        .boxed()
+    }
+}
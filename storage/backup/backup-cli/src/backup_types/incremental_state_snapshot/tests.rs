@@ -0,0 +1,275 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        incremental_state_snapshot::{
+            backup::{IncrementalStateSnapshotBackupController, IncrementalStateSnapshotBackupOpt},
+            restore::{
+                IncrementalStateSnapshotRestoreController, IncrementalStateSnapshotRestoreOpt,
+            },
+        },
+        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        transaction::backup::{TransactionBackupController, TransactionBackupOpt},
+    },
+    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    metadata::cache::MetadataCacheOpt,
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        backup_service_client::BackupServiceClient,
+        test_utils::{start_local_backup_service, tmp_db_with_random_content},
+        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt,
+        RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use aptos_db::AptosDB;
+use aptos_storage_interface::DbReader;
+use aptos_temppath::TempPath;
+use std::{convert::TryInto, sync::Arc};
+use tokio::time::Duration;
+
+#[test]
+fn end_to_end() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let latest_epoch = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .next_block_epoch()
+        - 1;
+    // (epoch, version, state root hash) at the end of each epoch.
+    let epoch_endings: Vec<_> = src_db
+        .get_epoch_ending_ledger_infos(0, latest_epoch + 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .into_iter()
+        .map(|li| {
+            let version = li.ledger_info().version();
+            let state_root_hash = src_db
+                .get_transactions(version, 1, version, false)
+                .unwrap()
+                .proof
+                .transaction_infos
+                .pop()
+                .unwrap()
+                .state_checkpoint_hash()
+                .unwrap();
+            (li.ledger_info().epoch(), version, state_root_hash)
+        })
+        .collect();
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_backup_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+    };
+
+    // A full snapshot at the end of the first epoch, and a chain of incremental ones on top of it,
+    // starting with one at the same version which records no change.
+    let mut base_manifest = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt {
+                    epoch: epoch_endings[0].0,
+                },
+                global_backup_opt.clone(),
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    for &(epoch, version, state_root_hash) in &epoch_endings {
+        let manifest_handle = rt
+            .block_on(
+                IncrementalStateSnapshotBackupController::new(
+                    IncrementalStateSnapshotBackupOpt {
+                        epoch,
+                        base_manifest,
+                    },
+                    global_backup_opt.clone(),
+                    Arc::clone(&client),
+                    Arc::clone(&store),
+                )
+                .run(),
+            )
+            .unwrap();
+
+        let tgt_db_dir = TempPath::new();
+        tgt_db_dir.create_as_dir().unwrap();
+        rt.block_on(
+            IncrementalStateSnapshotRestoreController::new(
+                IncrementalStateSnapshotRestoreOpt {
+                    manifest_handle: manifest_handle.clone(),
+                    version,
+                    validate_modules: false,
+                },
+                GlobalRestoreOpt {
+                    dry_run: false,
+                    db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                    target_version: None, // max
+                    trusted_waypoints: TrustedWaypointOpt::default(),
+                    rocksdb_opt: RocksdbOpt::default(),
+                    concurrent_downloads: ConcurrentDownloadsOpt::default(),
+                    replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                }
+                .try_into()
+                .unwrap(),
+                Arc::clone(&store),
+                None, /* epoch_history */
+            )
+            .run(),
+        )
+        .unwrap();
+
+        let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+        assert_eq!(
+            tgt_db
+                .get_state_snapshot_before(version + 1)
+                .unwrap()
+                .unwrap(),
+            (version, state_root_hash)
+        );
+
+        base_manifest = manifest_handle;
+    }
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn restore_coordinator_picks_incremental() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let latest_version = src_db.get_latest_version().unwrap();
+    let latest_li = src_db.get_latest_ledger_info().unwrap();
+    let latest_epoch = latest_li.ledger_info().next_block_epoch() - 1;
+    let last_epoch_ending = src_db
+        .get_epoch_ending_ledger_infos(latest_epoch, latest_epoch + 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .pop()
+        .unwrap();
+    let version = last_epoch_ending.ledger_info().version();
+    assert!(latest_epoch > 0);
+    let state_root_hash = src_db
+        .get_transactions(version, 1, version, false)
+        .unwrap()
+        .proof
+        .transaction_infos
+        .pop()
+        .unwrap()
+        .state_checkpoint_hash()
+        .unwrap();
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_backup_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+    };
+
+    rt.block_on(
+        EpochEndingBackupController::new(
+            EpochEndingBackupOpt {
+                start_epoch: 0,
+                end_epoch: latest_epoch + 1,
+            },
+            global_backup_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        TransactionBackupController::new(
+            TransactionBackupOpt {
+                start_version: 0,
+                num_transactions: latest_version as usize + 1,
+            },
+            global_backup_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    // Only the incremental snapshot is at the target version, on top of a full one at epoch 0.
+    let base_manifest = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { epoch: 0 },
+                global_backup_opt.clone(),
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    rt.block_on(
+        IncrementalStateSnapshotBackupController::new(
+            IncrementalStateSnapshotBackupOpt {
+                epoch: latest_epoch,
+                base_manifest,
+            },
+            global_backup_opt,
+            client,
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let metadata_cache_dir = TempPath::new();
+    rt.block_on(
+        RestoreCoordinator::new(
+            RestoreCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::new(Some(metadata_cache_dir.path())),
+                replay_all: false,
+                ledger_history_start_version: None,
+                skip_epoch_endings: false,
+            },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                target_version: Some(version),
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurrent_downloads: ConcurrentDownloadsOpt::default(),
+                replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            }
+            .try_into()
+            .unwrap(),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(version + 1)
+            .unwrap()
+            .unwrap(),
+        (version, state_root_hash)
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
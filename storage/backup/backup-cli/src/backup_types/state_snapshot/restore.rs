@@ -222,7 +222,7 @@ impl StateSnapshotRestoreController {
         Ok(())
     }
 
-    fn validate_modules(blob: &[(StateKey, StateValue)]) {
+    pub(crate) fn validate_modules(blob: &[(StateKey, StateValue)]) {
         let config = verifier_config(
             false,
             // FIXME: feed chain id & timestamp from the state.
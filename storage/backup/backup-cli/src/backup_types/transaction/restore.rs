@@ -189,6 +189,7 @@ pub struct TransactionRestoreBatchController {
     replay_from_version: Option<Version>,
     epoch_history: Option<Arc<EpochHistory>>,
     verify_execution_mode: VerifyExecutionMode,
+    defer_execution_verification: bool,
 }
 
 impl TransactionRestoreBatchController {
@@ -207,9 +208,18 @@ impl TransactionRestoreBatchController {
             replay_from_version,
             epoch_history,
             verify_execution_mode,
+            defer_execution_verification: false,
         }
     }
 
+    /// Verify the execution of the replayed chunks concurrently with replaying the following
+    /// ones, instead of before applying each chunk. A chunk is still only committed once verified,
+    /// so that a replay resumed from the DB never skips the verification of a chunk.
+    pub fn defer_execution_verification(mut self) -> Self {
+        self.defer_execution_verification = true;
+        self
+    }
+
     pub async fn run(self) -> Result<()> {
         let name = self.name();
         info!("{} started.", name);
@@ -450,6 +460,7 @@ impl TransactionRestoreBatchController {
         let replay_start = Instant::now();
         let db = DbReaderWriter::from_arc(Arc::clone(&restore_handler.aptosdb));
         let chunk_replayer = Arc::new(ChunkExecutor::<AptosVM>::new(db));
+        let defer_execution_verification = self.defer_execution_verification;
 
         let db_commit_stream = txns_to_execute_stream
             .try_chunks(BATCH_SIZE)
@@ -465,20 +476,43 @@ impl TransactionRestoreBatchController {
                         .with_label_values(&["replay_txn_chunk"])
                         .start_timer();
                     tokio::task::spawn_blocking(move || {
-                        chunk_replayer.replay(
-                            txns,
-                            txn_infos,
-                            write_sets,
-                            events,
-                            &verify_execution_mode,
-                        )
+                        if defer_execution_verification {
+                            chunk_replayer
+                                .replay_and_defer_verification(
+                                    txns,
+                                    txn_infos,
+                                    write_sets,
+                                    events,
+                                    &verify_execution_mode,
+                                )
+                                .map(Some)
+                        } else {
+                            chunk_replayer
+                                .replay(txns, txn_infos, write_sets, events, &verify_execution_mode)
+                                .map(|()| None)
+                        }
                     })
                     .err_into::<anyhow::Error>()
                     .await
                 }
             })
             .try_buffered_x(self.global_opt.concurrent_downloads, 1)
-            .and_then(future::ready);
+            .and_then(future::ready)
+            .map_ok(|verification| async move {
+                if let Some(verification) = verification {
+                    let _timer = OTHER_TIMERS_SECONDS
+                        .with_label_values(&["verify_txn_chunk"])
+                        .start_timer();
+                    tokio::task::spawn_blocking(move || verification.verify()).await??;
+                }
+                Result::<_>::Ok(())
+            })
+            // The verifications of consecutive chunks don't depend on each other, so they can run
+            // in parallel, while chunks still get committed in order, once verified.
+            .try_buffered_x(
+                self.global_opt.concurrent_downloads,
+                self.global_opt.concurrent_downloads,
+            );
 
         let total_replayed = db_commit_stream
             .and_then(|()| {
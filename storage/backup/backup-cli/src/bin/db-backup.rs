@@ -6,6 +6,9 @@ use anyhow::Result;
 use aptos_backup_cli::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        incremental_state_snapshot::backup::{
+            IncrementalStateSnapshotBackupController, IncrementalStateSnapshotBackupOpt,
+        },
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
@@ -100,6 +103,12 @@ enum BackupType {
         #[clap(subcommand)]
         storage: StorageOpt,
     },
+    IncrementalStateSnapshot {
+        #[clap(flatten)]
+        opt: IncrementalStateSnapshotBackupOpt,
+        #[clap(subcommand)]
+        storage: StorageOpt,
+    },
     Transaction {
         #[clap(flatten)]
         opt: TransactionBackupOpt,
@@ -191,6 +200,16 @@ async fn main_impl() -> Result<()> {
                         .run()
                         .await?;
                     },
+                    BackupType::IncrementalStateSnapshot { opt, storage } => {
+                        IncrementalStateSnapshotBackupController::new(
+                            opt,
+                            global_opt,
+                            client,
+                            storage.init_storage().await?,
+                        )
+                        .run()
+                        .await?;
+                    },
                     BackupType::Transaction { opt, storage } => {
                         TransactionBackupController::new(
                             opt,
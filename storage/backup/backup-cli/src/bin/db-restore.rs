@@ -6,6 +6,9 @@ use anyhow::Result;
 use aptos_backup_cli::{
     backup_types::{
         epoch_ending::restore::{EpochEndingRestoreController, EpochEndingRestoreOpt},
+        incremental_state_snapshot::restore::{
+            IncrementalStateSnapshotRestoreController, IncrementalStateSnapshotRestoreOpt,
+        },
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::{TransactionRestoreController, TransactionRestoreOpt},
     },
@@ -42,6 +45,12 @@ enum RestoreType {
         #[clap(subcommand)]
         storage: StorageOpt,
     },
+    IncrementalStateSnapshot {
+        #[clap(flatten)]
+        opt: IncrementalStateSnapshotRestoreOpt,
+        #[clap(subcommand)]
+        storage: StorageOpt,
+    },
     Transaction {
         #[clap(flatten)]
         opt: TransactionRestoreOpt,
@@ -87,6 +96,16 @@ async fn main_impl() -> Result<()> {
             .run()
             .await?;
         },
+        RestoreType::IncrementalStateSnapshot { opt, storage } => {
+            IncrementalStateSnapshotRestoreController::new(
+                opt,
+                global_opt,
+                storage.init_storage().await?,
+                None, /* epoch_history */
+            )
+            .run()
+            .await?;
+        },
         RestoreType::Transaction { opt, storage } => {
             TransactionRestoreController::new(
                 opt,
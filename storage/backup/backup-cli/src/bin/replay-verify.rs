@@ -4,21 +4,16 @@
 
 use anyhow::Result;
 use aptos_backup_cli::{
-    coordinators::replay_verify::ReplayVerifyCoordinator,
+    coordinators::replay_verify::{open_shard_dbs, ReplayVerifyCoordinator},
     metadata::cache::MetadataCacheOpt,
     storage::StorageOpt,
     utils::{ConcurrentDownloadsOpt, ReplayConcurrencyLevelOpt, RocksdbOpt, TrustedWaypointOpt},
 };
-use aptos_config::config::{
-    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
-    NO_OP_STORAGE_PRUNER_CONFIG,
-};
-use aptos_db::{AptosDB, GetRestoreHandler};
 use aptos_executor_types::VerifyExecutionMode;
 use aptos_logger::{prelude::*, Level, Logger};
 use aptos_types::transaction::Version;
 use clap::Parser;
-use std::{path::PathBuf, sync::Arc};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 struct Opt {
@@ -57,6 +52,14 @@ struct Opt {
     txns_to_skip: Vec<Version>,
     #[clap(long, help = "Do not quit right away when a replay issue is detected.")]
     lazy_quit: bool,
+    #[clap(
+        long,
+        default_value = "1",
+        help = "Number of shards to split the version range into. Every shard but the first starts \
+        right after a state snapshot, is replayed from it into a DB of its own under \
+        `target-db-dir` and verified in parallel with the other shards."
+    )]
+    num_shards: usize,
 }
 
 #[tokio::main]
@@ -72,23 +75,14 @@ async fn main_impl() -> Result<()> {
 
     let opt = Opt::from_args();
 
-    let restore_handler = Arc::new(AptosDB::open(
-        opt.db_dir,
-        false,                       /* read_only */
-        NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
-        opt.rocksdb_opt.into(),
-        false,
-        BUFFERED_STATE_TARGET_ITEMS,
-        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
-    )?)
-    .get_restore_handler();
+    let restore_handlers = open_shard_dbs(&opt.db_dir, opt.rocksdb_opt, opt.num_shards)?;
     ReplayVerifyCoordinator::new(
         opt.storage.init_storage().await?,
         opt.metadata_cache_opt,
         opt.trusted_waypoints_opt,
         opt.concurrent_downloads.get(),
         opt.replay_concurrency_level.get(),
-        restore_handler,
+        restore_handlers,
         opt.start_version.unwrap_or(0),
         opt.end_version.unwrap_or(Version::MAX),
         opt.validate_modules,
@@ -4,21 +4,29 @@
 
 use crate::{
     backup_types::{
+        incremental_state_snapshot::restore::{
+            IncrementalStateSnapshotRestoreController, IncrementalStateSnapshotRestoreOpt,
+        },
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::TransactionRestoreBatchController,
     },
     metadata,
-    metadata::cache::MetadataCacheOpt,
+    metadata::{cache::MetadataCacheOpt, view::MetadataView, AnyStateSnapshotBackupMeta},
     storage::BackupStorage,
-    utils::{GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{GlobalRestoreOptions, RestoreRunMode, RocksdbOpt, TrustedWaypointOpt},
 };
 use anyhow::{bail, ensure, Result};
-use aptos_db::backup::restore_handler::RestoreHandler;
+use aptos_config::config::{
+    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::{backup::restore_handler::RestoreHandler, AptosDB, GetRestoreHandler};
 use aptos_executor_types::VerifyExecutionMode;
 use aptos_logger::prelude::*;
 use aptos_types::{on_chain_config::TimedFeatureOverride, transaction::Version};
 use aptos_vm::AptosVM;
-use std::sync::Arc;
+use futures::future::try_join_all;
+use std::{cmp::min, path::Path, sync::Arc};
 
 pub struct ReplayVerifyCoordinator {
     storage: Arc<dyn BackupStorage>,
@@ -26,7 +34,8 @@ pub struct ReplayVerifyCoordinator {
     trusted_waypoints_opt: TrustedWaypointOpt,
     concurrent_downloads: usize,
     replay_concurrency_level: usize,
-    restore_handler: RestoreHandler,
+    /// The DBs the shards of the version range are replayed into, one per shard.
+    restore_handlers: Vec<RestoreHandler>,
     start_version: Version,
     end_version: Version,
     validate_modules: bool,
@@ -40,19 +49,23 @@ impl ReplayVerifyCoordinator {
         trusted_waypoints_opt: TrustedWaypointOpt,
         concurrent_downloads: usize,
         replay_concurrency_level: usize,
-        restore_handler: RestoreHandler,
+        restore_handlers: Vec<RestoreHandler>,
         start_version: Version,
         end_version: Version,
         validate_modules: bool,
         verify_execution_mode: VerifyExecutionMode,
     ) -> Result<Self> {
+        ensure!(
+            !restore_handlers.is_empty(),
+            "At least one DB to replay into is needed."
+        );
         Ok(Self {
             storage,
             metadata_cache_opt,
             trusted_waypoints_opt,
             concurrent_downloads,
             replay_concurrency_level,
-            restore_handler,
+            restore_handlers,
             start_version,
             end_version,
             validate_modules,
@@ -92,9 +105,57 @@ impl ReplayVerifyCoordinator {
             "start_version should precede end_version."
         );
 
-        let run_mode = Arc::new(RestoreRunMode::Restore {
-            restore_handler: self.restore_handler,
-        });
+        let end_version = min(
+            self.end_version,
+            metadata_view
+                .max_transaction_version()?
+                .unwrap_or(self.end_version),
+        );
+        let snapshot_versions = metadata_view.state_snapshot_versions()?;
+        let shard_ranges = get_shard_ranges(
+            self.start_version,
+            end_version.max(self.start_version),
+            &snapshot_versions,
+            self.restore_handlers.len(),
+        );
+        info!(
+            num_shards = shard_ranges.len(),
+            shard_ranges = ?shard_ranges,
+            "Planned shards to replay and verify."
+        );
+
+        try_join_all(
+            shard_ranges
+                .into_iter()
+                .zip(self.restore_handlers.iter())
+                .map(|((start_version, end_version), restore_handler)| {
+                    self.replay_verify_shard(
+                        &metadata_view,
+                        restore_handler.clone(),
+                        start_version,
+                        end_version,
+                    )
+                }),
+        )
+        .await?;
+
+        if self.verify_execution_mode.seen_error() {
+            bail!("Seen replay errors, check out logs.")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Replays and verifies the transactions in [start_version, end_version] into a DB of its own,
+    /// starting from the latest state snapshot before `start_version` if the DB is empty.
+    async fn replay_verify_shard(
+        &self,
+        metadata_view: &MetadataView,
+        restore_handler: RestoreHandler,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<()> {
+        let run_mode = Arc::new(RestoreRunMode::Restore { restore_handler });
         let next_txn_version = run_mode.get_next_expected_transaction_version()?;
         let (state_snapshot, replay_transactions_from_version) = if next_txn_version != 0 {
             // DB is already in workable state
@@ -108,17 +169,22 @@ impl ReplayVerifyCoordinator {
                 version = version,
                 "Found in progress state snapshot restore",
             );
-            (Some(metadata_view.expect_state_snapshot(version)?), version)
-        } else if self.start_version == 0 {
+            (
+                Some(metadata_view.expect_any_state_snapshot(version)?),
+                version,
+            )
+        } else if start_version == 0 {
             (None, 0)
         } else {
-            let state_snapshot = metadata_view.select_state_snapshot(self.start_version - 1)?;
-            let replay_transactions_from_version =
-                state_snapshot.as_ref().map(|b| b.version + 1).unwrap_or(0);
+            let state_snapshot = metadata_view.select_any_state_snapshot(start_version - 1)?;
+            let replay_transactions_from_version = state_snapshot
+                .as_ref()
+                .map(|b| b.version() + 1)
+                .unwrap_or(0);
             (state_snapshot, replay_transactions_from_version)
         };
         ensure!(
-            next_txn_version <= self.start_version,
+            next_txn_version <= start_version,
             "DB version is already beyond start_version requested.",
         );
 
@@ -126,47 +192,160 @@ impl ReplayVerifyCoordinator {
             // transaction info at the snapshot must be restored otherwise the db will be confused
             // about the latest version after snapshot is restored.
             replay_transactions_from_version.saturating_sub(1),
-            self.end_version,
+            end_version,
         )?;
         let global_opt = GlobalRestoreOptions {
-            target_version: self.end_version,
+            target_version: end_version,
             trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
             run_mode,
             concurrent_downloads: self.concurrent_downloads,
             replay_concurrency_level: 0, // won't replay, doesn't matter
         };
 
-        if let Some(backup) = state_snapshot {
-            StateSnapshotRestoreController::new(
-                StateSnapshotRestoreOpt {
-                    manifest_handle: backup.manifest,
-                    version: backup.version,
-                    validate_modules: self.validate_modules,
-                },
-                global_opt.clone(),
-                Arc::clone(&self.storage),
-                None, /* epoch_history */
-            )
-            .run()
-            .await?;
+        match state_snapshot {
+            Some(AnyStateSnapshotBackupMeta::Full(backup)) => {
+                StateSnapshotRestoreController::new(
+                    StateSnapshotRestoreOpt {
+                        manifest_handle: backup.manifest,
+                        version: backup.version,
+                        validate_modules: self.validate_modules,
+                    },
+                    global_opt.clone(),
+                    Arc::clone(&self.storage),
+                    None, /* epoch_history */
+                )
+                .run()
+                .await?
+            },
+            Some(AnyStateSnapshotBackupMeta::Incremental(backup)) => {
+                IncrementalStateSnapshotRestoreController::new(
+                    IncrementalStateSnapshotRestoreOpt {
+                        manifest_handle: backup.manifest,
+                        version: backup.version,
+                        validate_modules: self.validate_modules,
+                    },
+                    global_opt.clone(),
+                    Arc::clone(&self.storage),
+                    None, /* epoch_history */
+                )
+                .run()
+                .await?
+            },
+            None => {},
         }
 
         let txn_manifests = transactions.into_iter().map(|b| b.manifest).collect();
         TransactionRestoreBatchController::new(
             global_opt,
-            self.storage,
+            Arc::clone(&self.storage),
             txn_manifests,
             Some(replay_transactions_from_version), /* replay_from_version */
             None,                                   /* epoch_history */
             self.verify_execution_mode.clone(),
         )
+        .defer_execution_verification()
         .run()
         .await?;
 
-        if self.verify_execution_mode.seen_error() {
-            bail!("Seen replay errors, check out logs.")
-        } else {
-            Ok(())
+        info!(
+            start_version = start_version,
+            end_version = end_version,
+            "Shard replayed and verified."
+        );
+        Ok(())
+    }
+}
+
+/// Opens the DBs to replay the shards into: `db_dir` itself if there is a single shard, otherwise
+/// `db_dir/shard-{i}` for each of the `num_shards` shards.
+pub fn open_shard_dbs(
+    db_dir: &Path,
+    rocksdb_opt: RocksdbOpt,
+    num_shards: usize,
+) -> Result<Vec<RestoreHandler>> {
+    ensure!(num_shards > 0, "num_shards should be positive.");
+
+    (0..num_shards)
+        .map(|shard| {
+            let shard_db_dir = if num_shards == 1 {
+                db_dir.to_path_buf()
+            } else {
+                db_dir.join(format!("shard-{}", shard))
+            };
+            Ok(Arc::new(AptosDB::open(
+                shard_db_dir,
+                false,                       /* read_only */
+                NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+                rocksdb_opt.clone().into(),
+                false,
+                false, /* event type index */
+                BUFFERED_STATE_TARGET_ITEMS,
+                DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+            )?)
+            .get_restore_handler())
+        })
+        .collect()
+}
+
+/// Splits [start_version, end_version] into at most `num_shards` ranges of similar lengths which
+/// can be replayed independently: every range but the first starts right after a state snapshot,
+/// so it can be replayed from that snapshot instead of from the end of the previous range.
+fn get_shard_ranges(
+    start_version: Version,
+    end_version: Version,
+    snapshot_versions: &[Version],
+    num_shards: usize,
+) -> Vec<(Version, Version)> {
+    let shard_len = (end_version - start_version) / num_shards as Version;
+
+    let mut ranges = Vec::new();
+    let mut shard_start = start_version;
+    for i in 1..num_shards as Version {
+        let target_start = start_version + shard_len * i;
+        if let Some(&snapshot_version) = snapshot_versions
+            .iter()
+            .rev()
+            .find(|&&version| version < target_start)
+        {
+            if snapshot_version >= shard_start {
+                ranges.push((shard_start, snapshot_version));
+                shard_start = snapshot_version + 1;
+            }
         }
     }
+    ranges.push((shard_start, end_version));
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coordinators::replay_verify::get_shard_ranges;
+
+    #[test]
+    fn test_get_shard_ranges() {
+        let snapshots = [0, 99, 199, 299, 399];
+
+        assert_eq!(get_shard_ranges(0, 450, &snapshots, 1), vec![(0, 450)]);
+        assert_eq!(get_shard_ranges(0, 450, &snapshots, 2), vec![
+            (0, 199),
+            (200, 450)
+        ]);
+        assert_eq!(get_shard_ranges(0, 450, &snapshots, 3), vec![
+            (0, 99),
+            (100, 299),
+            (300, 450)
+        ]);
+        assert_eq!(get_shard_ranges(150, 450, &snapshots, 3), vec![
+            (150, 199),
+            (200, 299),
+            (300, 450)
+        ]);
+        // Not enough snapshots in the range for the number of shards.
+        assert_eq!(get_shard_ranges(150, 250, &snapshots, 4), vec![
+            (150, 199),
+            (200, 250)
+        ]);
+        assert_eq!(get_shard_ranges(0, 450, &[], 4), vec![(0, 450)]);
+    }
 }
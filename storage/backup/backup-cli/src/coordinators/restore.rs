@@ -5,11 +5,14 @@
 use crate::{
     backup_types::{
         epoch_ending::restore::EpochHistoryRestoreController,
+        incremental_state_snapshot::restore::{
+            IncrementalStateSnapshotRestoreController, IncrementalStateSnapshotRestoreOpt,
+        },
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::TransactionRestoreBatchController,
     },
     metadata,
-    metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
+    metadata::{cache::MetadataCacheOpt, AnyStateSnapshotBackupMeta, TransactionBackupMeta},
     metrics::restore::{
         COORDINATOR_FAIL_TS, COORDINATOR_START_TS, COORDINATOR_SUCC_TS, COORDINATOR_TARGET_VERSION,
     },
@@ -92,7 +95,7 @@ impl RestoreCoordinator {
         // N.b.
         // The coordinator now focuses on doing one procedure, ignoring the combination of options
         // supported before:
-        //   1. a most recent state snapshot before --target-version
+        //   1. a most recent state snapshot before --target-version, full or incremental
         //   2. a only transaction and its output, at the state snapshot version
         //   3. the epoch history from 0 up until the latest closed epoch preceding the state
         //      snapshot version.
@@ -130,16 +133,16 @@ impl RestoreCoordinator {
                     version = version,
                     "Found in progress state snapshot restore",
                 );
-                metadata_view.expect_state_snapshot(version)?
+                metadata_view.expect_any_state_snapshot(version)?
             } else {
                 let max_txn_ver = metadata_view
                     .max_transaction_version()?
                     .ok_or_else(|| anyhow!("No transaction backup found."))?;
                 metadata_view
-                    .select_state_snapshot(std::cmp::min(self.target_version(), max_txn_ver))?
+                    .select_any_state_snapshot(std::cmp::min(self.target_version(), max_txn_ver))?
                     .ok_or_else(|| anyhow!("No usable state snapshot."))?
             };
-        let version = state_snapshot_backup.version;
+        let version = state_snapshot_backup.version();
         self.global_opt.target_version = version;
         let epoch_ending_backups = metadata_view.select_epoch_ending_backups(version)?;
         let transaction_backups = metadata_view
@@ -164,18 +167,36 @@ impl RestoreCoordinator {
             None
         };
 
-        StateSnapshotRestoreController::new(
-            StateSnapshotRestoreOpt {
-                manifest_handle: state_snapshot_backup.manifest,
-                version,
-                validate_modules: false,
+        match state_snapshot_backup {
+            AnyStateSnapshotBackupMeta::Full(backup) => {
+                StateSnapshotRestoreController::new(
+                    StateSnapshotRestoreOpt {
+                        manifest_handle: backup.manifest,
+                        version,
+                        validate_modules: false,
+                    },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.storage),
+                    epoch_history.clone(),
+                )
+                .run()
+                .await?
             },
-            self.global_opt.clone(),
-            Arc::clone(&self.storage),
-            epoch_history.clone(),
-        )
-        .run()
-        .await?;
+            AnyStateSnapshotBackupMeta::Incremental(backup) => {
+                IncrementalStateSnapshotRestoreController::new(
+                    IncrementalStateSnapshotRestoreOpt {
+                        manifest_handle: backup.manifest,
+                        version,
+                        validate_modules: false,
+                    },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.storage),
+                    epoch_history.clone(),
+                )
+                .run()
+                .await?
+            },
+        }
 
         let txn_manifests = transaction_backups
             .iter()
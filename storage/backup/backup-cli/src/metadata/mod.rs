@@ -17,6 +17,7 @@ use std::convert::TryInto;
 pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
     StateSnapshotBackup(StateSnapshotBackupMeta),
+    IncrementalStateSnapshotBackup(IncrementalStateSnapshotBackupMeta),
     TransactionBackup(TransactionBackupMeta),
     Identity(IdentityMeta),
 }
@@ -46,6 +47,20 @@ impl Metadata {
         })
     }
 
+    pub fn new_incremental_state_snapshot_backup(
+        epoch: u64,
+        version: Version,
+        base_version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::IncrementalStateSnapshotBackup(IncrementalStateSnapshotBackupMeta {
+            epoch,
+            version,
+            base_version,
+            manifest,
+        })
+    }
+
     pub fn new_transaction_backup(
         first_version: Version,
         last_version: Version,
@@ -70,6 +85,10 @@ impl Metadata {
                 format!("epoch_ending_{}-{}.meta", e.first_epoch, e.last_epoch)
             },
             Self::StateSnapshotBackup(s) => format!("state_snapshot_ver_{}.meta", s.version),
+            Self::IncrementalStateSnapshotBackup(s) => format!(
+                "incremental_state_snapshot_ver_{}-{}.meta",
+                s.base_version, s.version
+            ),
             Self::TransactionBackup(t) => {
                 format!("transaction_{}-{}.meta", t.first_version, t.last_version,)
             },
@@ -100,6 +119,31 @@ pub struct StateSnapshotBackupMeta {
     pub manifest: FileHandle,
 }
 
+#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct IncrementalStateSnapshotBackupMeta {
+    pub epoch: u64,
+    pub version: Version,
+    pub base_version: Version,
+    pub manifest: FileHandle,
+}
+
+/// A state snapshot to restore from, either a full one or an incremental one chained on top of
+/// other snapshots.
+#[derive(Clone)]
+pub enum AnyStateSnapshotBackupMeta {
+    Full(StateSnapshotBackupMeta),
+    Incremental(IncrementalStateSnapshotBackupMeta),
+}
+
+impl AnyStateSnapshotBackupMeta {
+    pub fn version(&self) -> Version {
+        match self {
+            Self::Full(s) => s.version,
+            Self::Incremental(s) => s.version,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct TransactionBackupMeta {
     pub first_version: Version,
@@ -3,7 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::metadata::{
-    EpochEndingBackupMeta, IdentityMeta, Metadata, StateSnapshotBackupMeta, TransactionBackupMeta,
+    AnyStateSnapshotBackupMeta, EpochEndingBackupMeta, IdentityMeta,
+    IncrementalStateSnapshotBackupMeta, Metadata, StateSnapshotBackupMeta, TransactionBackupMeta,
 };
 use anyhow::{anyhow, ensure, Result};
 use aptos_types::transaction::Version;
@@ -13,6 +14,7 @@ use std::{fmt, str::FromStr};
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    incremental_state_snapshot_backups: Vec<IncrementalStateSnapshotBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
     _identity: Option<IdentityMeta>,
 }
@@ -54,6 +56,23 @@ impl MetadataView {
             .map(Clone::clone))
     }
 
+    /// The versions of all the state snapshots in the backup, full or incremental, in ascending
+    /// order.
+    pub fn state_snapshot_versions(&self) -> Result<Vec<Version>> {
+        Ok(self
+            .state_snapshot_backups
+            .iter()
+            .map(|m| m.version)
+            .chain(
+                self.incremental_state_snapshot_backups
+                    .iter()
+                    .map(|m| m.version),
+            )
+            .sorted()
+            .dedup()
+            .collect())
+    }
+
     pub fn expect_state_snapshot(&self, version: Version) -> Result<StateSnapshotBackupMeta> {
         self.state_snapshot_backups
             .iter()
@@ -62,6 +81,54 @@ impl MetadataView {
             .ok_or_else(|| anyhow!("State snapshot not found at version {}", version))
     }
 
+    pub fn select_incremental_state_snapshot(
+        &self,
+        target_version: Version,
+    ) -> Result<Option<IncrementalStateSnapshotBackupMeta>> {
+        Ok(self
+            .incremental_state_snapshot_backups
+            .iter()
+            .sorted()
+            .rev()
+            .find(|m| m.version <= target_version)
+            .map(Clone::clone))
+    }
+
+    /// Selects the latest state snapshot, full or incremental, not newer than `target_version`.
+    /// A full snapshot wins over an incremental one at the same version, since it's restored
+    /// without going through a chain of bases.
+    pub fn select_any_state_snapshot(
+        &self,
+        target_version: Version,
+    ) -> Result<Option<AnyStateSnapshotBackupMeta>> {
+        let full = self.select_state_snapshot(target_version)?;
+        let incremental = self.select_incremental_state_snapshot(target_version)?;
+        Ok(match (full, incremental) {
+            (Some(full), Some(incremental)) if incremental.version > full.version => {
+                Some(AnyStateSnapshotBackupMeta::Incremental(incremental))
+            },
+            (Some(full), _) => Some(AnyStateSnapshotBackupMeta::Full(full)),
+            (None, incremental) => incremental.map(AnyStateSnapshotBackupMeta::Incremental),
+        })
+    }
+
+    pub fn expect_any_state_snapshot(
+        &self,
+        version: Version,
+    ) -> Result<AnyStateSnapshotBackupMeta> {
+        self.state_snapshot_backups
+            .iter()
+            .find(|m| m.version == version)
+            .map(|m| AnyStateSnapshotBackupMeta::Full(m.clone()))
+            .or_else(|| {
+                self.incremental_state_snapshot_backups
+                    .iter()
+                    .find(|m| m.version == version)
+                    .map(|m| AnyStateSnapshotBackupMeta::Incremental(m.clone()))
+            })
+            .ok_or_else(|| anyhow!("State snapshot not found at version {}", version))
+    }
+
     pub fn select_transaction_backups(
         &self,
         start_version: Version,
@@ -133,6 +200,7 @@ impl From<Vec<Metadata>> for MetadataView {
     fn from(metadata_vec: Vec<Metadata>) -> Self {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
+        let mut incremental_state_snapshot_backups = Vec::new();
         let mut transaction_backups = Vec::new();
         let mut identity = None;
 
@@ -140,6 +208,9 @@ impl From<Vec<Metadata>> for MetadataView {
             match meta {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
+                Metadata::IncrementalStateSnapshotBackup(s) => {
+                    incremental_state_snapshot_backups.push(s)
+                },
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
                 Metadata::Identity(i) => identity = Some(i),
             }
@@ -148,6 +219,7 @@ impl From<Vec<Metadata>> for MetadataView {
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
+            incremental_state_snapshot_backups,
             transaction_backups,
             _identity: identity,
         }
@@ -4,6 +4,7 @@
 
 pub mod command_adapter;
 pub mod local_fs;
+pub mod object_store;
 
 #[cfg(test)]
 mod test_util;
@@ -13,6 +14,7 @@ mod tests;
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    object_store::{ObjectStore, ObjectStoreOpt},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -181,6 +183,12 @@ pub enum StorageOpt {
     https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/command_adapter/sample_configs/"
     )]
     CommandAdapter(CommandAdapterOpt),
+    #[clap(
+        about = "Select the ObjectStore backup storage type, which talks directly to S3 compatible \
+    object stores or to GCS, without relying on command line tools. See a sample config here: \
+    https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/object_store/sample_configs/"
+    )]
+    ObjectStore(ObjectStoreOpt),
 }
 
 impl StorageOpt {
@@ -188,6 +196,7 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::ObjectStore(opt) => Arc::new(ObjectStore::new_with_opt(opt).await?),
         })
     }
 }
@@ -196,7 +205,7 @@ impl StorageOpt {
 #[clap(group(
     ArgGroup::new("storage")
     .required(true)
-    .args(&["local-fs-dir", "command-adapter-config"]),
+    .args(&["local-fs-dir", "command-adapter-config", "object-store-config"]),
 ))]
 pub struct DBToolStorageOpt {
     #[clap(
@@ -212,12 +221,21 @@ pub struct DBToolStorageOpt {
     https://github.com/aptos-labs/aptos-networks/tree/main/testnet/backups "
     )]
     command_adapter_config: Option<CommandAdapterOpt>,
+    #[clap(
+        long,
+        help = "Select the ObjectStore backup storage type, which talks directly to S3 compatible \
+    object stores or to GCS, without relying on command line tools. See a sample config here: \
+    https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/object_store/sample_configs/"
+    )]
+    object_store_config: Option<ObjectStoreOpt>,
 }
 
 impl DBToolStorageOpt {
     pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
         Ok(if self.local_fs_dir.is_some() {
             Arc::new(LocalFs::new_with_opt(self.local_fs_dir.unwrap()))
+        } else if self.object_store_config.is_some() {
+            Arc::new(ObjectStore::new_with_opt(self.object_store_config.unwrap()).await?)
         } else {
             Arc::new(CommandAdapter::new_with_opt(self.command_adapter_config.unwrap()).await?)
         })
@@ -0,0 +1,433 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::object_store::config::{ObjectStoreConfig, Provider};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_logger::warn;
+use aptos_sigv4 as sigv4;
+use bytes::Bytes;
+use reqwest::{header, Method, Response, StatusCode};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const GCS_ENDPOINT: &str = "https://storage.googleapis.com";
+const GCE_METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+/// Access tokens are refreshed this long before they expire.
+const TOKEN_EXPIRATION_MARGIN: Duration = Duration::from_secs(60);
+
+enum Credentials {
+    Aws {
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+        region: String,
+    },
+    Gcs {
+        /// Token from the environment, otherwise tokens are fetched from the metadata server.
+        static_token: Option<String>,
+        cached_token: Mutex<Option<(String, Instant)>>,
+    },
+}
+
+#[derive(Deserialize)]
+struct GceAccessToken {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// A minimal client of the object APIs shared by S3 and the GCS XML API, retrying failed
+/// requests.
+pub(super) struct ObjectClient {
+    http: reqwest::Client,
+    endpoint: String,
+    bucket: String,
+    credentials: Credentials,
+    max_retries: usize,
+    retry_backoff: Duration,
+}
+
+impl ObjectClient {
+    pub fn new(config: &ObjectStoreConfig) -> Result<Self> {
+        let (endpoint, credentials) = match &config.provider {
+            Provider::S3 {
+                endpoint,
+                region,
+                access_key_id,
+                secret_access_key,
+                session_token,
+            } => (
+                endpoint.clone(),
+                Credentials::Aws {
+                    access_key_id: config_or_env(access_key_id, "AWS_ACCESS_KEY_ID")?,
+                    secret_access_key: config_or_env(secret_access_key, "AWS_SECRET_ACCESS_KEY")?,
+                    session_token: session_token
+                        .clone()
+                        .or_else(|| std::env::var("AWS_SESSION_TOKEN").ok()),
+                    region: region.clone(),
+                },
+            ),
+            Provider::Gcs { endpoint } => (
+                endpoint.clone().unwrap_or_else(|| GCS_ENDPOINT.to_string()),
+                Credentials::Gcs {
+                    static_token: std::env::var("GCS_OAUTH_TOKEN").ok(),
+                    cached_token: Mutex::new(None),
+                },
+            ),
+        };
+
+        Ok(Self {
+            http: reqwest::Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket: config.bucket.clone(),
+            credentials,
+            max_retries: config.max_retries,
+            retry_backoff: Duration::from_millis(config.retry_backoff_ms),
+        })
+    }
+
+    pub async fn put_object(&self, key: &str, data: Bytes) -> Result<()> {
+        self.send(Method::PUT, key, &[], None, data).await?;
+        Ok(())
+    }
+
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let response = self
+            .send(
+                Method::POST,
+                key,
+                &[("uploads", String::new())],
+                None,
+                Bytes::new(),
+            )
+            .await?;
+        let body = response.text().await?;
+        xml_elements(&body, "UploadId")
+            .pop()
+            .ok_or_else(|| anyhow!("No UploadId in response: {}", body))
+    }
+
+    /// Uploads a part of a multipart upload, returning its ETag.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        data: Bytes,
+    ) -> Result<String> {
+        let query = [
+            ("partNumber", part_number.to_string()),
+            ("uploadId", upload_id.to_string()),
+        ];
+        let response = self.send(Method::PUT, key, &query, None, data).await?;
+        Ok(response
+            .headers()
+            .get(header::ETAG)
+            .ok_or_else(|| anyhow!("No ETag in response to uploading part {}.", part_number))?
+            .to_str()?
+            .to_string())
+    }
+
+    /// Completes a multipart upload given the ETags of all its parts, in order.
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etags: &[String],
+    ) -> Result<()> {
+        let parts: String = etags
+            .iter()
+            .enumerate()
+            .map(|(index, etag)| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    index + 1,
+                    xml_escape(etag)
+                )
+            })
+            .collect();
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+        );
+        let query = [("uploadId", upload_id.to_string())];
+        let response = self
+            .send(Method::POST, key, &query, None, body.into())
+            .await?;
+        // Errors can come after a 200 OK status, as the response is streamed while the parts
+        // are being assembled.
+        let body = response.text().await?;
+        ensure!(
+            !body.contains("<Error>"),
+            "Failed to complete multipart upload of {}: {}",
+            key,
+            body
+        );
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        let query = [("uploadId", upload_id.to_string())];
+        self.send(Method::DELETE, key, &query, None, Bytes::new())
+            .await?;
+        Ok(())
+    }
+
+    /// Reads up to `len` bytes of an object from `offset`, also returning the object size.
+    /// `offset` is expected to be within the object, unless it's empty.
+    pub async fn get_object_range(&self, key: &str, offset: u64, len: u64) -> Result<(Bytes, u64)> {
+        let range = format!("bytes={}-{}", offset, offset + len - 1);
+        let response = self
+            .send(Method::GET, key, &[], Some(range), Bytes::new())
+            .await?;
+        if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            return Ok((Bytes::new(), 0));
+        }
+        let size = match response.headers().get(header::CONTENT_RANGE) {
+            // e.g. "bytes 0-99/1234"
+            Some(content_range) => content_range
+                .to_str()?
+                .rsplit('/')
+                .next()
+                .and_then(|size| size.parse().ok())
+                .ok_or_else(|| anyhow!("Invalid Content-Range: {:?}", content_range))?,
+            // The whole object was returned, e.g. because it's empty.
+            None => response.content_length().unwrap_or_default(),
+        };
+        Ok((response.bytes().await?, size))
+    }
+
+    /// Lists the keys of all objects starting with `prefix`.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token", token));
+            }
+            let body = self
+                .send(Method::GET, "", &query, None, Bytes::new())
+                .await?
+                .text()
+                .await?;
+            keys.extend(xml_elements(&body, "Key"));
+            if xml_elements(&body, "IsTruncated")
+                .first()
+                .map(String::as_str)
+                != Some("true")
+            {
+                break;
+            }
+            continuation_token = Some(
+                xml_elements(&body, "NextContinuationToken")
+                    .pop()
+                    .ok_or_else(|| anyhow!("No NextContinuationToken in response: {}", body))?,
+            );
+        }
+        Ok(keys)
+    }
+
+    /// Sends a request, retrying on transport errors, throttling and server errors.
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        range: Option<String>,
+        body: Bytes,
+    ) -> Result<Response> {
+        let mut backoff = self.retry_backoff;
+        let mut retries = 0;
+        loop {
+            let err = match self
+                .try_send(method.clone(), key, query, range.clone(), body.clone())
+                .await
+            {
+                // Ranges can't be satisfied if the object is empty, see `get_object_range`.
+                Ok(response)
+                    if response.status().is_success()
+                        || response.status() == StatusCode::RANGE_NOT_SATISFIABLE =>
+                {
+                    return Ok(response)
+                },
+                Ok(response) => {
+                    let status = response.status();
+                    let err = anyhow!(
+                        "{} {} failed with status {}: {}",
+                        method,
+                        key,
+                        status,
+                        response.text().await.unwrap_or_default()
+                    );
+                    if !Self::is_retriable(status) {
+                        return Err(err);
+                    }
+                    err
+                },
+                Err(err) => err,
+            };
+            if retries >= self.max_retries {
+                return Err(err);
+            }
+            warn!(error = ?err, retries = retries, "Retrying object store request.");
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+            retries += 1;
+        }
+    }
+
+    fn is_retriable(status: StatusCode) -> bool {
+        status.is_server_error()
+            || status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::REQUEST_TIMEOUT
+    }
+
+    async fn try_send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        range: Option<String>,
+        body: Bytes,
+    ) -> Result<Response> {
+        let path = sigv4::encode_path(&format!("/{}/{}", self.bucket, key));
+        let query_string = sigv4::encode_query(query);
+        let url = if query_string.is_empty() {
+            format!("{}{}", self.endpoint, path)
+        } else {
+            format!("{}{}?{}", self.endpoint, path, query_string)
+        };
+
+        let mut request = self.http.request(method.clone(), &url);
+        if let Some(range) = &range {
+            request = request.header(header::RANGE, range);
+        }
+        match &self.credentials {
+            Credentials::Aws {
+                access_key_id,
+                secret_access_key,
+                session_token,
+                region,
+            } => {
+                let url = reqwest::Url::parse(&url)?;
+                let host = match url.port() {
+                    Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+                    None => url.host_str().unwrap_or_default().to_string(),
+                };
+                // Sorted by name, as required to sign them.
+                let mut headers = vec![("host", host)];
+                if let Some(range) = range {
+                    headers.push(("range", range));
+                }
+                headers.push(("x-amz-content-sha256", sigv4::sha256_hex(&body)));
+                headers.push((
+                    "x-amz-date",
+                    chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+                ));
+                if let Some(session_token) = session_token {
+                    headers.push(("x-amz-security-token", session_token.clone()));
+                }
+                let authorization = sigv4::authorization(
+                    access_key_id,
+                    secret_access_key,
+                    region,
+                    method.as_str(),
+                    &path,
+                    &query_string,
+                    &headers,
+                );
+                for (name, value) in headers {
+                    if name != "host" && name != "range" {
+                        request = request.header(name, value);
+                    }
+                }
+                request = request.header(header::AUTHORIZATION, authorization);
+            },
+            Credentials::Gcs { .. } => {
+                request = request.bearer_auth(self.gcs_token().await?);
+            },
+        }
+
+        Ok(request.body(body).send().await?)
+    }
+
+    async fn gcs_token(&self) -> Result<String> {
+        let (static_token, cached_token) = match &self.credentials {
+            Credentials::Gcs {
+                static_token,
+                cached_token,
+            } => (static_token, cached_token),
+            Credentials::Aws { .. } => bail!("Not using GCS credentials."),
+        };
+        if let Some(token) = static_token {
+            return Ok(token.clone());
+        }
+
+        let mut cached_token = cached_token.lock().await;
+        if let Some((token, expiration)) = cached_token.as_ref() {
+            if Instant::now() + TOKEN_EXPIRATION_MARGIN < *expiration {
+                return Ok(token.clone());
+            }
+        }
+        let token: GceAccessToken = self
+            .http
+            .get(GCE_METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        *cached_token = Some((
+            token.access_token.clone(),
+            Instant::now() + Duration::from_secs(token.expires_in),
+        ));
+        Ok(token.access_token)
+    }
+}
+
+fn config_or_env(value: &Option<String>, env_var: &str) -> Result<String> {
+    match value {
+        Some(value) => Ok(value.clone()),
+        None => std::env::var(env_var).map_err(|_| anyhow!("${} is not set.", env_var)),
+    }
+}
+
+/// Returns the text of all the elements named `tag`. The responses of the object APIs being
+/// simple, this doesn't need a full XML parser.
+fn xml_elements(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        match rest.find(&close) {
+            Some(end) => {
+                elements.push(xml_unescape(&rest[..end]));
+                rest = &rest[end + close.len()..];
+            },
+            None => break,
+        }
+    }
+    elements
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
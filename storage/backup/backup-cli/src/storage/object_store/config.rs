@@ -0,0 +1,86 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::error_notes::ErrorNotes;
+use anyhow::Result;
+use serde::Deserialize;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+#[derive(Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Provider {
+    /// Any storage implementing the S3 API, like AWS S3 or MinIO. Requests are signed with
+    /// AWS signature version 4.
+    S3 {
+        /// Endpoint of the service, e.g. "https://s3.us-west-2.amazonaws.com". Buckets are
+        /// addressed path-style, i.e. "<endpoint>/<bucket>/<key>".
+        endpoint: String,
+        region: String,
+        /// Defaults to env var $AWS_ACCESS_KEY_ID.
+        access_key_id: Option<String>,
+        /// Defaults to env var $AWS_SECRET_ACCESS_KEY.
+        secret_access_key: Option<String>,
+        /// Defaults to env var $AWS_SESSION_TOKEN, if set.
+        session_token: Option<String>,
+    },
+    /// Google Cloud Storage through its XML API, authenticated with an OAuth 2.0 access token
+    /// taken from env var $GCS_OAUTH_TOKEN, or else from the GCE metadata server.
+    Gcs {
+        /// Defaults to "https://storage.googleapis.com".
+        endpoint: Option<String>,
+    },
+}
+
+#[derive(Clone, Deserialize)]
+pub struct ObjectStoreConfig {
+    pub provider: Provider,
+    pub bucket: String,
+    /// Key prefix under which everything is stored, without trailing slash.
+    #[serde(default)]
+    pub prefix: String,
+    /// Size of the parts of multipart uploads and of the ranges of parallel reads. Except for
+    /// tests, must be at least 5MB, the minimum part size of S3 and GCS.
+    #[serde(default = "ObjectStoreConfig::default_part_size")]
+    pub part_size: usize,
+    /// Maximum number of parts uploaded or ranges read concurrently for a single file.
+    #[serde(default = "ObjectStoreConfig::default_concurrency")]
+    pub concurrency: usize,
+    /// Number of times a failed request is retried before giving up.
+    #[serde(default = "ObjectStoreConfig::default_max_retries")]
+    pub max_retries: usize,
+    /// Delay before the first retry of a request, doubled on each following retry.
+    #[serde(default = "ObjectStoreConfig::default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+}
+
+impl ObjectStoreConfig {
+    fn default_part_size() -> usize {
+        16 * 1024 * 1024
+    }
+
+    fn default_concurrency() -> usize {
+        8
+    }
+
+    fn default_max_retries() -> usize {
+        5
+    }
+
+    fn default_retry_backoff_ms() -> u64 {
+        200
+    }
+
+    pub async fn load_from_file(path: &Path) -> Result<Self> {
+        let path_str = path.to_str().unwrap_or_default();
+        let mut file = tokio::fs::File::open(path).await.err_notes(path_str)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).await.err_notes(path_str)?;
+
+        Ok(serde_yaml::from_slice(&content)?)
+    }
+
+    pub fn load_from_str(content: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+pub mod config;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        object_store::{client::ObjectClient, config::ObjectStoreConfig},
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{anyhow, ensure, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use clap::Parser;
+use futures::{
+    future::{self, BoxFuture},
+    stream::{self, FuturesUnordered},
+    FutureExt, StreamExt, TryStreamExt,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    sync::oneshot,
+    task::{JoinError, JoinHandle},
+};
+use tokio_util::io::StreamReader;
+
+#[derive(Parser, Debug, Serialize, Deserialize)]
+pub struct ObjectStoreOpt {
+    #[clap(
+        long = "config",
+        help = "Config file for the object store backup store."
+    )]
+    config: PathBuf,
+}
+
+impl FromStr for ObjectStoreOpt {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ObjectStoreOpt {
+            config: PathBuf::from(s),
+        })
+    }
+}
+
+/// A BackupStorage talking directly to S3 compatible object stores or to GCS.
+/// see `ObjectStoreConfig`.
+///
+/// Files are uploaded in parts and read by ranges, concurrently.
+pub struct ObjectStore {
+    client: Arc<ObjectClient>,
+    prefix: String,
+    part_size: usize,
+    concurrency: usize,
+}
+
+impl ObjectStore {
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new(config: ObjectStoreConfig) -> Result<Self> {
+        ensure!(
+            config.part_size > 0 && config.concurrency > 0,
+            "Part size and concurrency must be positive."
+        );
+        Ok(Self {
+            client: Arc::new(ObjectClient::new(&config)?),
+            prefix: config.prefix.trim_end_matches('/').to_string(),
+            part_size: config.part_size,
+            concurrency: config.concurrency,
+        })
+    }
+
+    pub async fn new_with_opt(opt: ObjectStoreOpt) -> Result<Self> {
+        let config = ObjectStoreConfig::load_from_file(&opt.config).await?;
+
+        Self::new(config)
+    }
+
+    fn object_key(&self, file_handle: &FileHandleRef) -> String {
+        if self.prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", self.prefix, file_handle)
+        }
+    }
+
+    fn file_handle(&self, object_key: &str) -> FileHandle {
+        if self.prefix.is_empty() {
+            object_key.to_string()
+        } else {
+            object_key[self.prefix.len() + 1..].to_string()
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for ObjectStore {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no directories to create in object stores.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let writer = ObjectWriter::new(
+            self.client.clone(),
+            self.object_key(&file_handle),
+            self.part_size,
+            self.concurrency,
+        );
+        Ok((file_handle, Box::new(writer)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let key = self.object_key(file_handle);
+        let part_size = self.part_size as u64;
+        // The first range tells the size of the object, and whether it exists at all.
+        let (first_part, size) = self.client.get_object_range(&key, 0, part_size).await?;
+
+        let client = self.client.clone();
+        let rest = stream::iter((part_size..size).step_by(part_size as usize))
+            .map(move |offset| {
+                let client = client.clone();
+                let key = key.clone();
+                async move {
+                    let len = std::cmp::min(part_size, size - offset);
+                    let (part, _size) = client.get_object_range(&key, offset, len).await?;
+                    Ok::<_, anyhow::Error>(part)
+                }
+            })
+            .buffered(self.concurrency);
+        let parts = stream::once(future::ok(first_part))
+            .chain(rest)
+            .map_err(|err: anyhow::Error| std::io::Error::new(std::io::ErrorKind::Other, err));
+
+        Ok(Box::new(StreamReader::new(parts.boxed())))
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        self.client
+            .put_object(
+                &self.object_key(&file_handle),
+                Bytes::copy_from_slice(content.as_ref().as_bytes()),
+            )
+            .await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.object_key(&format!("{}/", Self::METADATA_DIR));
+        Ok(self
+            .client
+            .list_objects(&prefix)
+            .await?
+            .iter()
+            .map(|key| self.file_handle(key))
+            .collect())
+    }
+}
+
+/// Streams written bytes into an object, uploading it in parts once it's large enough.
+///
+/// The object is only created once the writer is shut down, which fails if the upload failed.
+struct ObjectWriter {
+    pipe: DuplexStream,
+    commit_sender: Option<oneshot::Sender<()>>,
+    upload_fut: Option<BoxFuture<'static, Result<()>>>,
+}
+
+impl ObjectWriter {
+    fn new(client: Arc<ObjectClient>, key: String, part_size: usize, concurrency: usize) -> Self {
+        let (pipe, data) = tokio::io::duplex(part_size);
+        let (commit_sender, commit_receiver) = oneshot::channel();
+        let upload: JoinHandle<Result<()>> = tokio::spawn(upload_object(
+            client,
+            key,
+            data,
+            commit_receiver,
+            part_size,
+            concurrency,
+        ));
+        Self {
+            pipe,
+            commit_sender: Some(commit_sender),
+            upload_fut: Some(
+                upload
+                    .map(|res| match res {
+                        Ok(res) => res,
+                        Err(err) => Err(anyhow!("Upload task failed: {}", err)),
+                    })
+                    .boxed(),
+            ),
+        }
+    }
+}
+
+impl AsyncWrite for ObjectWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, tokio::io::Error>> {
+        Pin::new(&mut self.pipe).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        Pin::new(&mut self.pipe).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        if let Some(commit_sender) = self.commit_sender.take() {
+            // The upload task might have failed already, which is reported below.
+            let _ = commit_sender.send(());
+        }
+        futures::ready!(Pin::new(&mut self.pipe).poll_shutdown(cx))?;
+
+        match self.upload_fut.as_mut() {
+            Some(upload_fut) => {
+                let res = futures::ready!(upload_fut.poll_unpin(cx));
+                self.upload_fut = None;
+                Poll::Ready(res.map_err(|e| tokio::io::Error::new(tokio::io::ErrorKind::Other, e)))
+            },
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+/// Reads the data of an object until EOF and uploads it. Unless `commit_receiver` gets a
+/// signal, the writer is assumed to have been dropped midway and nothing is created.
+async fn upload_object(
+    client: Arc<ObjectClient>,
+    key: String,
+    mut data: DuplexStream,
+    commit_receiver: oneshot::Receiver<()>,
+    part_size: usize,
+    concurrency: usize,
+) -> Result<()> {
+    let first_part = read_part(&mut data, part_size).await?;
+    if first_part.len() < part_size {
+        commit_receiver
+            .await
+            .map_err(|_| anyhow!("Writing {} was not completed.", key))?;
+        return client.put_object(&key, first_part).await.err_notes(&key);
+    }
+
+    let upload_id = client.create_multipart_upload(&key).await?;
+    let res = async {
+        let etags = upload_parts(
+            &client,
+            &key,
+            &upload_id,
+            first_part,
+            data,
+            part_size,
+            concurrency,
+        )
+        .await?;
+        commit_receiver
+            .await
+            .map_err(|_| anyhow!("Writing {} was not completed.", key))?;
+        client
+            .complete_multipart_upload(&key, &upload_id, &etags)
+            .await
+    }
+    .await;
+    if res.is_err() {
+        // Best effort, incomplete uploads should also be cleaned up by lifecycle rules.
+        let _ = client.abort_multipart_upload(&key, &upload_id).await;
+    }
+    res.err_notes(&key)
+}
+
+/// Uploads `first_part` and the rest of `data` as parts, returning their ETags in order.
+async fn upload_parts(
+    client: &Arc<ObjectClient>,
+    key: &str,
+    upload_id: &str,
+    first_part: Bytes,
+    mut data: DuplexStream,
+    part_size: usize,
+    concurrency: usize,
+) -> Result<Vec<String>> {
+    let mut etags = Vec::new();
+    let mut uploads = FuturesUnordered::new();
+    let mut next_part = Some(first_part);
+    while let Some(part) = next_part.take() {
+        if uploads.len() >= concurrency {
+            etags.push(join_part_upload(uploads.next().await)?);
+        }
+        let client = client.clone();
+        let (key, upload_id) = (key.to_string(), upload_id.to_string());
+        let part_number = etags.len() + uploads.len() + 1;
+        uploads.push(tokio::spawn(async move {
+            let etag = client
+                .upload_part(&key, &upload_id, part_number, part)
+                .await?;
+            Ok((part_number, etag))
+        }));
+
+        let part = read_part(&mut data, part_size).await?;
+        if !part.is_empty() {
+            next_part = Some(part);
+        }
+    }
+    while !uploads.is_empty() {
+        etags.push(join_part_upload(uploads.next().await)?);
+    }
+
+    etags.sort();
+    Ok(etags.into_iter().map(|(_, etag)| etag).collect())
+}
+
+fn join_part_upload(
+    res: Option<Result<Result<(usize, String)>, JoinError>>,
+) -> Result<(usize, String)> {
+    res.expect("Not empty.")
+        .map_err(|err| anyhow!("Part upload task failed: {}", err))?
+}
+
+async fn read_part(data: &mut DuplexStream, part_size: usize) -> Result<Bytes> {
+    let mut part = Vec::with_capacity(part_size);
+    data.take(part_size as u64).read_to_end(&mut part).await?;
+    Ok(part.into())
+}
@@ -0,0 +1,269 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    test_write_and_read_impl,
+};
+use aptos_infallible::Mutex;
+use proptest::prelude::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tokio::{io::AsyncWriteExt, runtime::Runtime};
+use warp::{
+    http::{Method, StatusCode},
+    path::FullPath,
+    reply::Response,
+    Filter,
+};
+
+const BUCKET: &str = "bucket";
+/// Number of keys per page when listing objects, small to exercise pagination.
+const LIST_PAGE_SIZE: usize = 2;
+
+/// An in memory stand-in for an S3 compatible object store, which doesn't check signatures
+/// and fails every fourth request to exercise retries.
+#[derive(Default)]
+struct FakeObjectStore {
+    objects: Mutex<BTreeMap<String, Bytes>>,
+    uploads: Mutex<HashMap<String, BTreeMap<usize, Bytes>>>,
+    num_requests: AtomicUsize,
+}
+
+impl FakeObjectStore {
+    fn start() -> (Arc<Self>, SocketAddr) {
+        let store = Arc::new(Self::default());
+        let handler = store.clone();
+        let route = warp::method()
+            .and(warp::path::full())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::header::optional::<String>("range"))
+            .and(warp::body::bytes())
+            .map(move |method, path: FullPath, query, range, body| {
+                handler.handle(method, path.as_str(), query, range, body)
+            });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        (store, addr)
+    }
+
+    fn handle(
+        &self,
+        method: Method,
+        path: &str,
+        query: HashMap<String, String>,
+        range: Option<String>,
+        body: Bytes,
+    ) -> Response {
+        if self.num_requests.fetch_add(1, Ordering::SeqCst) % 4 == 3 {
+            return reply(StatusCode::SERVICE_UNAVAILABLE, "<Error>SlowDown</Error>");
+        }
+        let key = match path.strip_prefix(&format!("/{}/", BUCKET)) {
+            Some(key) => key.to_string(),
+            None => return reply(StatusCode::NOT_FOUND, "<Error>NoSuchBucket</Error>"),
+        };
+
+        match (method, query.get("uploadId")) {
+            (Method::GET, _) if query.contains_key("list-type") => self.list(&query),
+            (Method::GET, _) => self.get(&key, range),
+            (Method::PUT, Some(upload_id)) => {
+                let part_number: usize = query["partNumber"].parse().unwrap();
+                self.uploads
+                    .lock()
+                    .get_mut(upload_id)
+                    .unwrap()
+                    .insert(part_number, body);
+                let mut response = reply(StatusCode::OK, "");
+                response
+                    .headers_mut()
+                    .insert("etag", format!("\"part-{}\"", part_number).parse().unwrap());
+                response
+            },
+            (Method::PUT, None) => {
+                self.objects.lock().insert(key, body);
+                reply(StatusCode::OK, "")
+            },
+            (Method::POST, None) if query.contains_key("uploads") => {
+                let upload_id = format!("upload-{}", self.uploads.lock().len());
+                self.uploads
+                    .lock()
+                    .insert(upload_id.clone(), BTreeMap::new());
+                reply(
+                    StatusCode::OK,
+                    &format!(
+                        "<InitiateMultipartUploadResult><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                        upload_id
+                    ),
+                )
+            },
+            (Method::POST, Some(upload_id)) => {
+                let parts = self.uploads.lock().remove(upload_id).unwrap();
+                let body = String::from_utf8(body.to_vec()).unwrap();
+                let mut object = Vec::new();
+                for (part_number, part) in parts {
+                    assert!(body.contains(&format!(
+                        "<Part><PartNumber>{}</PartNumber><ETag>&quot;part-{}&quot;</ETag></Part>",
+                        part_number, part_number
+                    )));
+                    object.extend_from_slice(&part);
+                }
+                self.objects.lock().insert(key, object.into());
+                reply(StatusCode::OK, "<CompleteMultipartUploadResult/>")
+            },
+            (Method::DELETE, Some(upload_id)) => {
+                self.uploads.lock().remove(upload_id);
+                reply(StatusCode::NO_CONTENT, "")
+            },
+            _ => reply(StatusCode::BAD_REQUEST, "<Error>Unsupported</Error>"),
+        }
+    }
+
+    fn get(&self, key: &str, range: Option<String>) -> Response {
+        let object = match self.objects.lock().get(key) {
+            Some(object) => object.clone(),
+            None => return reply(StatusCode::NOT_FOUND, "<Error>NoSuchKey</Error>"),
+        };
+        let (start, end) = range
+            .as_deref()
+            .and_then(|range| range.strip_prefix("bytes="))
+            .and_then(|range| range.split_once('-'))
+            .map(|(start, end)| (start.parse().unwrap(), end.parse::<usize>().unwrap() + 1))
+            .unwrap();
+        if start >= object.len() {
+            return reply(
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "<Error>InvalidRange</Error>",
+            );
+        }
+        let end = std::cmp::min(end, object.len());
+
+        let mut response = Response::new(object.slice(start..end).into());
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        response.headers_mut().insert(
+            "content-range",
+            format!("bytes {}-{}/{}", start, end - 1, object.len())
+                .parse()
+                .unwrap(),
+        );
+        response
+    }
+
+    fn list(&self, query: &HashMap<String, String>) -> Response {
+        let prefix = query.get("prefix").cloned().unwrap_or_default();
+        let after = query.get("continuation-token").cloned().unwrap_or_default();
+        let keys: Vec<String> = self
+            .objects
+            .lock()
+            .keys()
+            .filter(|key| key.starts_with(&prefix) && **key > after)
+            .cloned()
+            .collect();
+        let page = &keys[..std::cmp::min(LIST_PAGE_SIZE, keys.len())];
+
+        let mut body = format!(
+            "<ListBucketResult><IsTruncated>{}</IsTruncated>",
+            keys.len() > page.len()
+        );
+        if let Some(last) = page.last() {
+            body += &format!("<NextContinuationToken>{}</NextContinuationToken>", last);
+        }
+        for key in page {
+            body += &format!("<Contents><Key>{}</Key></Contents>", key);
+        }
+        body += "</ListBucketResult>";
+        reply(StatusCode::OK, &body)
+    }
+}
+
+fn reply(status: StatusCode, body: &str) -> Response {
+    let mut response = Response::new(body.to_string().into());
+    *response.status_mut() = status;
+    response
+}
+
+fn get_store(addr: SocketAddr) -> ObjectStore {
+    let config = ObjectStoreConfig::load_from_str(&format!(
+        r#"
+provider:
+  type: s3
+  endpoint: "http://{}"
+  region: "us-east-1"
+  access_key_id: "test"
+  secret_access_key: "test"
+bucket: "{}"
+prefix: "backup1/e1"
+part_size: 100
+concurrency: 4
+retry_backoff_ms: 1
+"#,
+        addr, BUCKET
+    ))
+    .unwrap();
+
+    ObjectStore::new(config).unwrap()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (_store, addr) = FakeObjectStore::start();
+            test_write_and_read_impl(Box::new(get_store(addr)), backups).await
+        });
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (_store, addr) = FakeObjectStore::start();
+            test_save_and_list_metadata_files_impl(Box::new(get_store(addr)), input).await
+        });
+    }
+}
+
+#[test]
+fn test_incomplete_write_is_discarded() {
+    Runtime::new().unwrap().block_on(async {
+        let (fake_store, addr) = FakeObjectStore::start();
+        let store = get_store(addr);
+        let backup_handle = store
+            .create_backup(&"backup".parse().unwrap())
+            .await
+            .unwrap();
+
+        for size in [10, 1000] {
+            let name = format!("file{}", size).parse().unwrap();
+            let (_handle, mut file) = store.create_for_write(&backup_handle, &name).await.unwrap();
+            file.write_all(&vec![0; size]).await.unwrap();
+            // Dropped without being shut down.
+        }
+        // Give the upload tasks time to notice.
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        assert!(fake_store.objects.lock().is_empty());
+        assert!(fake_store.uploads.lock().is_empty());
+    });
+}
+
+#[test]
+fn test_sample_configs() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/storage/object_store/sample_configs");
+    Runtime::new().unwrap().block_on(async {
+        for name in ["s3.sample.yaml", "gcs.sample.yaml", "minio.sample.yaml"] {
+            ObjectStoreConfig::load_from_file(&dir.join(name))
+                .await
+                .unwrap();
+        }
+    });
+}
@@ -78,6 +78,28 @@ impl BackupServiceClient {
         self.get(&format!("state_snapshot/{}", version)).await
     }
 
+    pub async fn get_state_item_count(&self, version: Version) -> Result<usize> {
+        let mut buf = Vec::new();
+        self.get(&format!("state_item_count/{}", version))
+            .await?
+            .read_to_end(&mut buf)
+            .await?;
+        Ok(bcs::from_bytes::<u64>(&buf)? as usize)
+    }
+
+    pub async fn get_state_snapshot_chunk(
+        &self,
+        version: Version,
+        start_idx: usize,
+        limit: usize,
+    ) -> Result<impl AsyncRead> {
+        self.get(&format!(
+            "state_snapshot_chunk/{}/{}/{}",
+            version, start_idx, limit
+        ))
+        .await
+    }
+
     pub async fn get_state_root_proof(&self, version: Version) -> Result<Vec<u8>> {
         let mut buf = Vec::new();
         self.get(&format!("state_root_proof/{}", version))
@@ -272,6 +272,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
                 NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
                 opt.rocksdb_opt.into(),
                 false,
+                false, /* event type index */
                 BUFFERED_STATE_TARGET_ITEMS,
                 DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
             )?)
@@ -68,6 +68,8 @@ mod tests {
         assert_eq!(resp.status(), 400);
         let resp = get(format!("http://127.0.0.1:{}/state_snapshot", port)).unwrap();
         assert_eq!(resp.status(), 400);
+        let resp = get(format!("http://127.0.0.1:{}/state_snapshot_chunk/1", port)).unwrap();
+        assert_eq!(resp.status(), 400);
 
         // Params fail to parse (HashValue)
         let resp = get(format!("http://127.0.0.1:{}/state_range_proof/1/ff", port)).unwrap();
@@ -83,6 +85,8 @@ mod tests {
         assert_eq!(resp.status(), 500);
         let resp = get(format!("http://127.0.0.1:{}/state_root_proof/0", port)).unwrap();
         assert_eq!(resp.status(), 500);
+        let resp = get(format!("http://127.0.0.1:{}/state_item_count/0", port)).unwrap();
+        assert_eq!(resp.status(), 500);
 
         // In an endpoint handled by `reply_with_async_channel_writer', connection terminates
         // prematurely when the channel writer errors. However a 200 is either returned or not
@@ -5,6 +5,9 @@ use anyhow::Result;
 use aptos_backup_cli::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        incremental_state_snapshot::backup::{
+            IncrementalStateSnapshotBackupController, IncrementalStateSnapshotBackupOpt,
+        },
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
@@ -98,6 +101,12 @@ enum BackupType {
         #[clap[flatten]]
         storage: DBToolStorageOpt,
     },
+    IncrementalStateSnapshot {
+        #[clap(flatten)]
+        opt: IncrementalStateSnapshotBackupOpt,
+        #[clap[flatten]]
+        storage: DBToolStorageOpt,
+    },
     Transaction {
         #[clap(flatten)]
         opt: TransactionBackupOpt,
@@ -163,6 +172,16 @@ impl Command {
                         .run()
                         .await?;
                     },
+                    BackupType::IncrementalStateSnapshot { opt, storage } => {
+                        IncrementalStateSnapshotBackupController::new(
+                            opt,
+                            global_opt,
+                            client,
+                            storage.init_storage().await?,
+                        )
+                        .run()
+                        .await?;
+                    },
                     BackupType::Transaction { opt, storage } => {
                         TransactionBackupController::new(
                             opt,
@@ -3,21 +3,16 @@
 
 use anyhow::Result;
 use aptos_backup_cli::{
-    coordinators::replay_verify::ReplayVerifyCoordinator,
+    coordinators::replay_verify::{open_shard_dbs, ReplayVerifyCoordinator},
     metadata::cache::MetadataCacheOpt,
     storage::DBToolStorageOpt,
     utils::{ConcurrentDownloadsOpt, ReplayConcurrencyLevelOpt, RocksdbOpt, TrustedWaypointOpt},
 };
-use aptos_config::config::{
-    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
-    NO_OP_STORAGE_PRUNER_CONFIG,
-};
-use aptos_db::{AptosDB, GetRestoreHandler};
 use aptos_executor_types::VerifyExecutionMode;
 use aptos_logger::{Level, Logger};
 use aptos_types::transaction::Version;
 use clap::Parser;
-use std::{path::PathBuf, sync::Arc};
+use std::path::PathBuf;
 
 /// Read the backup files, replay them and verify the modules
 #[derive(Parser)]
@@ -57,29 +52,28 @@ pub struct Opt {
     txns_to_skip: Vec<Version>,
     #[clap(long, help = "Do not quit right away when a replay issue is detected.")]
     lazy_quit: bool,
+    #[clap(
+        long,
+        default_value = "1",
+        help = "Number of shards to split the version range into. Every shard but the first starts \
+        right after a state snapshot, is replayed from it into a DB of its own under \
+        `target-db-dir` and verified in parallel with the other shards."
+    )]
+    num_shards: usize,
 }
 
 impl Opt {
     pub async fn run(self) -> Result<()> {
         Logger::new().level(Level::Info).init();
 
-        let restore_handler = Arc::new(AptosDB::open(
-            self.db_dir,
-            false,                       /* read_only */
-            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
-            self.rocksdb_opt.into(),
-            false,
-            BUFFERED_STATE_TARGET_ITEMS,
-            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
-        )?)
-        .get_restore_handler();
+        let restore_handlers = open_shard_dbs(&self.db_dir, self.rocksdb_opt, self.num_shards)?;
         ReplayVerifyCoordinator::new(
             self.storage.init_storage().await?,
             self.metadata_cache_opt,
             self.trusted_waypoints_opt,
             self.concurrent_downloads.get(),
             self.replay_concurrency_level.get(),
-            restore_handler,
+            restore_handlers,
             self.start_version.unwrap_or(0),
             self.end_version.unwrap_or(Version::MAX),
             self.validate_modules,
@@ -9,18 +9,20 @@ use crate::{
     db::INDEX_DB_NAME,
     metadata::{MetadataKey, MetadataValue},
     schema::{
-        column_families, indexer_metadata::IndexerMetadataSchema, table_info::TableInfoSchema,
+        column_families, event_by_type::EventByTypeSchema, indexer_metadata::IndexerMetadataSchema,
+        table_info::TableInfoSchema,
     },
 };
 use anyhow::{bail, ensure, Result};
 use aptos_config::config::RocksdbConfig;
 use aptos_logger::warn;
 use aptos_rocksdb_options::gen_rocksdb_options;
-use aptos_schemadb::{SchemaBatch, DB};
+use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
 use aptos_storage_interface::{state_view::DbStateView, DbReader};
 use aptos_types::{
     access_path::Path,
     account_address::AccountAddress,
+    contract_event::ContractEvent,
     state_store::{
         state_key::{StateKey, StateKeyInner},
         table::{TableHandle, TableInfo},
@@ -40,16 +42,22 @@ use std::{
     sync::{atomic::Ordering, Arc},
 };
 
+/// Number of events deleted per write when cleaning up the event type index
+const DELETE_BATCH_SIZE: usize = 10_000;
+
 #[derive(Debug)]
 pub struct Indexer {
     db: DB,
     next_version: AtomicVersion,
+    /// The first version of the event type index, None if it's disabled
+    event_type_index_start_version: Option<Version>,
 }
 
 impl Indexer {
     pub fn open(
         db_root_path: impl AsRef<std::path::Path>,
         rocksdb_config: RocksdbConfig,
+        enable_event_type_index: bool,
     ) -> Result<Self> {
         let db_path = db_root_path.as_ref().join(INDEX_DB_NAME);
 
@@ -64,17 +72,62 @@ impl Indexer {
             .get::<IndexerMetadataSchema>(&MetadataKey::LatestVersion)?
             .map_or(0, |v| v.expect_version());
 
+        // Events are only indexed by type from the version the index was enabled at, which is
+        // recorded so that earlier ranges aren't served incomplete. Disabling the index forgets
+        // it and deletes the indexed events, as the events committed meanwhile are not indexed.
+        let start_version_key = MetadataKey::EventTypeIndexStartVersion;
+        let start_version = db
+            .get::<IndexerMetadataSchema>(&start_version_key)?
+            .map(|start_version| start_version.expect_version());
+        let event_type_index_start_version = match (enable_event_type_index, start_version) {
+            (true, Some(start_version)) => Some(start_version),
+            (true, None) => {
+                db.put::<IndexerMetadataSchema>(
+                    &start_version_key,
+                    &MetadataValue::Version(next_version),
+                )?;
+                Some(next_version)
+            },
+            (false, Some(_)) => {
+                // The start version is deleted last, so that an interrupted cleanup is resumed
+                // the next time the DB is opened.
+                Self::delete_events_by_type(&db)?;
+                let batch = SchemaBatch::new();
+                batch.delete::<IndexerMetadataSchema>(&start_version_key)?;
+                db.write_schemas(batch)?;
+                None
+            },
+            (false, None) => None,
+        };
+
         Ok(Self {
             db,
             next_version: AtomicVersion::new(next_version),
+            event_type_index_start_version,
         })
     }
 
+    fn delete_events_by_type(db: &DB) -> Result<()> {
+        let mut iter = db.iter::<EventByTypeSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let mut batch = SchemaBatch::new();
+        let mut num_deleted = 0;
+        while let Some((key, ())) = iter.next().transpose()? {
+            batch.delete::<EventByTypeSchema>(&key)?;
+            num_deleted += 1;
+            if num_deleted % DELETE_BATCH_SIZE == 0 {
+                db.write_schemas(std::mem::replace(&mut batch, SchemaBatch::new()))?;
+            }
+        }
+        db.write_schemas(batch)
+    }
+
     pub fn index(
         &self,
         db_reader: Arc<dyn DbReader>,
         first_version: Version,
         write_sets: &[&WriteSet],
+        events: &[&[ContractEvent]],
     ) -> Result<()> {
         let last_version = first_version + write_sets.len() as Version;
         let state_view = DbStateView {
@@ -83,7 +136,7 @@ impl Indexer {
         };
         let resolver = state_view.as_move_resolver();
         let annotator = MoveValueAnnotator::new(&resolver);
-        self.index_with_annotator(&annotator, first_version, write_sets)
+        self.index_with_annotator(&annotator, first_version, write_sets, events)
     }
 
     pub fn index_with_annotator(
@@ -91,7 +144,14 @@ impl Indexer {
         annotator: &MoveValueAnnotator<StorageAdapter<DbStateView>>,
         first_version: Version,
         write_sets: &[&WriteSet],
+        events: &[&[ContractEvent]],
     ) -> Result<()> {
+        ensure!(
+            write_sets.len() == events.len(),
+            "Indexer expects write sets and events of the same transactions. Got {} write sets and {} event lists.",
+            write_sets.len(),
+            events.len(),
+        );
         let next_version = self.next_version();
         ensure!(
             first_version <= next_version,
@@ -119,6 +179,16 @@ impl Indexer {
 
         let mut batch = SchemaBatch::new();
         table_info_parser.finish(&mut batch)?;
+        if self.event_type_index_enabled() {
+            for (version, events) in (first_version..).zip(events) {
+                for (index, event) in events.iter().enumerate() {
+                    batch.put::<EventByTypeSchema>(
+                        &(event.type_tag().clone(), version, index as u64),
+                        &(),
+                    )?;
+                }
+            }
+        }
         batch.put::<IndexerMetadataSchema>(
             &MetadataKey::LatestVersion,
             &MetadataValue::Version(end_version - 1),
@@ -136,6 +206,57 @@ impl Indexer {
     pub fn get_table_info(&self, handle: TableHandle) -> Result<Option<TableInfo>> {
         self.db.get::<TableInfoSchema>(&handle)
     }
+
+    pub fn event_type_index_enabled(&self) -> bool {
+        self.event_type_index_start_version.is_some()
+    }
+
+    /// Returns the first version covered by the event type index, None if it's disabled.
+    pub fn event_type_index_start_version(&self) -> Option<Version> {
+        self.event_type_index_start_version
+    }
+
+    /// Returns the `(version, index)` of at most `limit` events of the given type emitted by
+    /// transactions in `[start_version, end_version)`, in order, starting from the event at
+    /// `start_index` in the events of the transaction at `start_version`.
+    ///
+    /// Also returns the `(version, index)` of the next event in the range, if the page is full
+    /// and there are more.
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        start_index: u64,
+        end_version: Version,
+        limit: u64,
+    ) -> Result<(Vec<(Version, u64)>, Option<(Version, u64)>)> {
+        let index_start_version = match self.event_type_index_start_version {
+            Some(index_start_version) => index_start_version,
+            None => bail!("Event type index not enabled."),
+        };
+        ensure!(
+            start_version >= index_start_version,
+            "Events are only indexed by type from version {}, got start version {}.",
+            index_start_version,
+            start_version,
+        );
+
+        let mut iter = self.db.iter::<EventByTypeSchema>(ReadOptions::default())?;
+        iter.seek(&(type_tag.clone(), start_version, start_index))?;
+
+        let mut result: Vec<(Version, u64)> = vec![];
+        while let Some(((tag, version, index), ())) = iter.next().transpose()? {
+            if &tag != type_tag || version >= end_version {
+                break;
+            }
+            if result.len() as u64 >= limit {
+                return Ok((result, Some((version, index))));
+            }
+            result.push((version, index));
+        }
+
+        Ok((result, None))
+    }
 }
 
 struct TableInfoParser<'a> {
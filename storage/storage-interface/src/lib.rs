@@ -33,6 +33,7 @@ use aptos_types::{
     },
     write_set::WriteSet,
 };
+use move_core_types::language_storage::TypeTag;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 use thiserror::Error;
@@ -207,6 +208,24 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns at most `limit` events of the given type emitted by transactions in
+    /// `[start_version, end_version)`, from the event type index of the internal indexer,
+    /// starting from the event at `start_index` in the events of the transaction at
+    /// `start_version`.
+    ///
+    /// Also returns the `(version, index)` of the next event in the range, if there are more.
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        start_index: u64,
+        end_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<(Vec<EventWithVersion>, Option<(Version, u64)>)> {
+        unimplemented!()
+    }
+
     fn get_transaction_iterator(
         &self,
         start_version: Version,
@@ -510,6 +529,12 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns the oldest version whose state snapshot, if any, hasn't been pruned. Snapshots at
+    /// the end of epochs are kept longer, see `get_epoch_snapshot_prune_window`.
+    fn get_state_merkle_min_readable_version(&self) -> Result<Version> {
+        unimplemented!()
+    }
+
     /// Returns if the ledger pruner is enabled.
     fn is_ledger_pruner_enabled(&self) -> Result<bool> {
         unimplemented!()
@@ -530,6 +555,16 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns whether the event type index of the internal indexer DB has been enabled or not
+    fn event_type_index_enabled(&self) -> bool {
+        unimplemented!()
+    }
+
+    /// Returns the first version covered by the event type index, None if it's disabled
+    fn event_type_index_start_version(&self) -> Option<Version> {
+        unimplemented!()
+    }
+
     /// Returns state storage usage at the end of an epoch.
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         unimplemented!()
@@ -6,6 +6,7 @@ use aptos_crypto::{
     bls12381,
     ed25519::Ed25519PrivateKey,
     multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    secp256r1_ecdsa,
     traits::{SigningKey, Uniform},
     PrivateKey,
 };
@@ -41,12 +42,18 @@ fn trace_crypto_values(tracer: &mut Tracer, samples: &mut Samples) -> Result<()>
     let bls_public_key = bls_private_key.public_key();
     let bls_signature = bls_private_key.sign(&message).unwrap();
 
+    let secp256r1_ecdsa_private_key = secp256r1_ecdsa::PrivateKey::generate(&mut rng);
+    let secp256r1_ecdsa_public_key = secp256r1_ecdsa_private_key.public_key();
+    let secp256r1_ecdsa_signature = secp256r1_ecdsa_private_key.sign(&message).unwrap();
+
     tracer.trace_value(samples, &public_key)?;
     tracer.trace_value(samples, &signature)?;
     tracer.trace_value(samples, &bls_public_key)?;
     tracer.trace_value(samples, &bls_signature)?;
     tracer.trace_value::<MultiEd25519PublicKey>(samples, &public_key.into())?;
     tracer.trace_value::<MultiEd25519Signature>(samples, &signature.into())?;
+    tracer.trace_value(samples, &secp256r1_ecdsa_public_key)?;
+    tracer.trace_value(samples, &secp256r1_ecdsa_signature)?;
     Ok(())
 }
 
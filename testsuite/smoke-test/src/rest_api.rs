@@ -7,7 +7,10 @@ use aptos_cached_packages::aptos_stdlib;
 use aptos_crypto::ed25519::Ed25519Signature;
 use aptos_forge::Swarm;
 use aptos_gas::{AptosGasParameters, FromOnChainGasSchedule};
-use aptos_rest_client::aptos_api_types::{MoveModuleId, TransactionData};
+use aptos_rest_client::{
+    aptos_api_types::{MoveModuleId, TransactionData},
+    VerifyingClient,
+};
 use aptos_sdk::move_types::language_storage::StructTag;
 use aptos_types::{
     account_address::AccountAddress,
@@ -70,6 +73,110 @@ async fn test_basic_client() {
     info.client().get_transactions(None, None).await.unwrap();
 }
 
+#[tokio::test]
+async fn test_verifying_client() {
+    let mut swarm = new_local_swarm_with_aptos(1).await;
+    let genesis_waypoint = swarm
+        .validators()
+        .next()
+        .unwrap()
+        .config()
+        .base
+        .waypoint
+        .genesis_waypoint();
+    let mut info = swarm.aptos_public_info();
+    let client = VerifyingClient::new(info.client().clone(), genesis_waypoint);
+
+    let mut account1 = info
+        .create_and_fund_user_account(10_000_000_000)
+        .await
+        .unwrap();
+    let account2 = info
+        .create_and_fund_user_account(10_000_000_000)
+        .await
+        .unwrap();
+    let tx = account1.sign_with_transaction_builder(
+        info.transaction_factory()
+            .payload(aptos_stdlib::aptos_coin_transfer(account2.address(), 1)),
+    );
+    let pending_txn = info.client().submit(&tx).await.unwrap().into_inner();
+    let committed_txn = info
+        .client()
+        .wait_for_transaction_bcs(&pending_txn)
+        .await
+        .unwrap()
+        .into_inner();
+
+    let trusted_state = client.sync().await.unwrap();
+    assert!(!trusted_state.is_epoch_waypoint());
+
+    let proven = client
+        .get_account_resource_bcs::<AccountResource>(
+            account1.address(),
+            "0x1::account::Account",
+            None,
+        )
+        .await
+        .unwrap()
+        .into_inner();
+    // Values are read at the latest state snapshot, which can be older than the transfer
+    let unverified = info
+        .client()
+        .get_account_resource_at_version_bcs::<AccountResource>(
+            account1.address(),
+            "0x1::account::Account",
+            proven.version,
+        )
+        .await
+        .ok()
+        .map(|resource| resource.into_inner().sequence_number());
+    assert_eq!(
+        proven.resource.map(|resource| resource.sequence_number()),
+        unverified
+    );
+
+    // Values are proven at exactly the requested state snapshot
+    let proven_at_version = client
+        .get_account_resource_bcs::<AccountResource>(
+            account1.address(),
+            "0x1::account::Account",
+            Some(proven.version),
+        )
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(proven_at_version.version, proven.version);
+
+    let missing = client
+        .get_account_resource_bcs::<AccountResource>(
+            AccountAddress::random(),
+            "0x1::account::Account",
+            None,
+        )
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(missing.resource.is_none());
+
+    let txn_with_proof = client
+        .get_transaction_by_version_bcs(committed_txn.version)
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(txn_with_proof.transaction, Transaction::UserTransaction(tx));
+
+    client
+        .get_account_events_bcs(
+            CORE_CODE_ADDRESS,
+            "0x1::block::BlockResource",
+            "new_block_events",
+            None,
+            Some(5),
+        )
+        .await
+        .unwrap();
+}
+
 // Test needs to be fixed to estimate over a longer period of time / probably needs an adjustable window
 // to test
 #[ignore]
@@ -170,6 +170,17 @@ pub fn create_object_address(creator: AccountAddress, seed: &[u8]) -> AccountAdd
     AccountAddress::from_bytes(hash.as_ref()).unwrap()
 }
 
+/// Return the `counter`-th address generated by the session whose id hashes to `session_hash`. The
+/// session id of a user transaction consists of its sender and sequence number, so that no two
+/// committed transactions share it.
+pub fn create_unique_address(session_hash: &[u8], counter: u64) -> AccountAddress {
+    let mut input = session_hash.to_vec();
+    input.extend(counter.to_le_bytes());
+    input.push(Scheme::DeriveAuid as u8);
+    let hash = HashValue::sha3_256_of(&input);
+    AccountAddress::from_bytes(hash.as_ref()).unwrap()
+}
+
 pub fn default_owner_stake_pool_address(owner: AccountAddress) -> AccountAddress {
     default_stake_pool_address(owner, owner)
 }
@@ -286,4 +297,23 @@ mod test {
         );
         // println!("{:?}", create_object_address(address, guid);
     }
+
+    #[test]
+    fn unique_address() {
+        let session_hash = HashValue::sha3_256_of(b"session");
+        let other_session_hash = HashValue::sha3_256_of(b"other session");
+        let address = super::create_unique_address(session_hash.as_ref(), 1);
+        assert_eq!(
+            address,
+            super::create_unique_address(session_hash.as_ref(), 1)
+        );
+        assert_ne!(
+            address,
+            super::create_unique_address(session_hash.as_ref(), 2)
+        );
+        assert_ne!(
+            address,
+            super::create_unique_address(other_session_hash.as_ref(), 1)
+        );
+    }
 }
@@ -20,6 +20,9 @@ pub enum FeatureFlag {
     MULTISIG_ACCOUNTS = 10,
     DELEGATION_POOLS = 11,
     BLS12381_BASIC_OPERATIONS = 12,
+    SECP256R1_ECDSA_AUTHENTICATOR = 13,
+    FEE_PAYER_ENABLED = 14,
+    BLOCK_PARTITIONING = 15,
 }
 
 /// Representation of features on chain as a bitset.
@@ -2,8 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    proof::SparseMerkleRangeProof, state_store::state_key::StateKey, transaction::Version,
+    ledger_info::LedgerInfo,
+    proof::{SparseMerkleProof, SparseMerkleRangeProof, TransactionInfoWithProof},
+    state_store::state_key::StateKey,
+    transaction::Version,
 };
+use anyhow::Result;
 use aptos_crypto::{
     hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
     HashValue,
@@ -178,6 +182,49 @@ impl StateValueChunkWithProof {
     }
 }
 
+/// A state value (or the absence of it) at a state checkpoint version, together with the
+/// proofs connecting it to the ledger.
+///
+/// The `proof` authenticates `value` against the state checkpoint hash carried by the
+/// `TransactionInfo` at `version`, which in turn is authenticated against a ledger info by
+/// `transaction_info_with_proof`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValueWithProof {
+    pub version: Version,
+    pub value: Option<StateValue>,
+    pub proof: SparseMerkleProof,
+    pub transaction_info_with_proof: TransactionInfoWithProof,
+}
+
+impl StateValueWithProof {
+    pub fn new(
+        version: Version,
+        value: Option<StateValue>,
+        proof: SparseMerkleProof,
+        transaction_info_with_proof: TransactionInfoWithProof,
+    ) -> Self {
+        Self {
+            version,
+            value,
+            proof,
+            transaction_info_with_proof,
+        }
+    }
+
+    /// Verifies that the value carried by `self` is the value of `state_key` at `self.version`
+    /// in the ledger represented by `ledger_info`. A `None` value proves non-existence.
+    pub fn verify(&self, ledger_info: &LedgerInfo, state_key: &StateKey) -> Result<()> {
+        let state_checkpoint_hash = self
+            .transaction_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        self.proof
+            .verify(state_checkpoint_hash, state_key.hash(), self.value.as_ref())?;
+        self.transaction_info_with_proof
+            .verify(ledger_info, self.version)
+    }
+}
+
 /// Indicates a state value becomes stale since `stale_since_version`.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
@@ -4,13 +4,16 @@
 
 use crate::{
     account_address::AccountAddress,
-    transaction::{RawTransaction, RawTransactionWithData},
+    transaction::{
+        webauthn::PartialAuthenticatorAssertionResponse, RawTransaction, RawTransactionWithData,
+    },
 };
 use anyhow::{ensure, Error, Result};
 use aptos_crypto::{
     ed25519::{Ed25519PublicKey, Ed25519Signature},
     hash::CryptoHash,
     multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    secp256r1_ecdsa::{Secp256r1EcdsaPublicKey, Secp256r1EcdsaSignature},
     traits::Signature,
     CryptoMaterialError, HashValue, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
 };
@@ -58,6 +61,19 @@ pub enum TransactionAuthenticator {
         secondary_signer_addresses: Vec<AccountAddress>,
         secondary_signers: Vec<AccountAuthenticator>,
     },
+    /// Single secp256r1 ECDSA signature, possibly through a WebAuthn assertion
+    Secp256r1Ecdsa {
+        public_key: Secp256r1EcdsaPublicKey,
+        signature: Secp256r1EcdsaSignatureEnvelope,
+    },
+    /// Multi-agent transaction whose gas is paid by a fee payer rather than by the sender.
+    FeePayer {
+        sender: AccountAuthenticator,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        secondary_signers: Vec<AccountAuthenticator>,
+        fee_payer_address: AccountAddress,
+        fee_payer_signer: AccountAuthenticator,
+    },
 }
 
 impl TransactionAuthenticator {
@@ -80,6 +96,17 @@ impl TransactionAuthenticator {
         }
     }
 
+    /// Create a single-signature secp256r1 ECDSA authenticator
+    pub fn secp256r1_ecdsa(
+        public_key: Secp256r1EcdsaPublicKey,
+        signature: Secp256r1EcdsaSignatureEnvelope,
+    ) -> Self {
+        Self::Secp256r1Ecdsa {
+            public_key,
+            signature,
+        }
+    }
+
     /// Create a multi-agent authenticator
     pub fn multi_agent(
         sender: AccountAuthenticator,
@@ -93,6 +120,23 @@ impl TransactionAuthenticator {
         }
     }
 
+    /// Create a fee payer authenticator
+    pub fn fee_payer(
+        sender: AccountAuthenticator,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        secondary_signers: Vec<AccountAuthenticator>,
+        fee_payer_address: AccountAddress,
+        fee_payer_signer: AccountAuthenticator,
+    ) -> Self {
+        Self::FeePayer {
+            sender,
+            secondary_signer_addresses,
+            secondary_signers,
+            fee_payer_address,
+            fee_payer_signer,
+        }
+    }
+
     /// Return Ok if all AccountAuthenticator's public keys match their signatures, Err otherwise
     pub fn verify(&self, raw_txn: &RawTransaction) -> Result<()> {
         let num_sigs: usize = self.sender().number_of_signatures()
@@ -100,7 +144,10 @@ impl TransactionAuthenticator {
                 .secondary_signers()
                 .iter()
                 .map(|auth| auth.number_of_signatures())
-                .sum::<usize>();
+                .sum::<usize>()
+            + self
+                .fee_payer_signer()
+                .map_or(0, |auth| auth.number_of_signatures());
         if num_sigs > MAX_NUM_OF_SIGS {
             return Err(Error::new(AuthenticationError::MaxSignaturesExceeded));
         }
@@ -128,6 +175,28 @@ impl TransactionAuthenticator {
                 }
                 Ok(())
             },
+            Self::Secp256r1Ecdsa {
+                public_key,
+                signature,
+            } => signature.verify(raw_txn, public_key),
+            Self::FeePayer {
+                sender,
+                secondary_signer_addresses,
+                secondary_signers,
+                fee_payer_address,
+                fee_payer_signer,
+            } => {
+                let message = RawTransactionWithData::new_multi_agent_with_fee_payer(
+                    raw_txn.clone(),
+                    secondary_signer_addresses.clone(),
+                    *fee_payer_address,
+                );
+                sender.verify(&message)?;
+                for signer in secondary_signers {
+                    signer.verify(&message)?;
+                }
+                fee_payer_signer.verify(&message)
+            },
         }
     }
 
@@ -144,7 +213,11 @@ impl TransactionAuthenticator {
                 public_key,
                 signature,
             } => AccountAuthenticator::multi_ed25519(public_key.clone(), signature.clone()),
-            Self::MultiAgent { sender, .. } => sender.clone(),
+            Self::MultiAgent { sender, .. } | Self::FeePayer { sender, .. } => sender.clone(),
+            Self::Secp256r1Ecdsa {
+                public_key,
+                signature,
+            } => AccountAuthenticator::secp256r1_ecdsa(public_key.clone(), signature.clone()),
         }
     }
 
@@ -154,11 +227,16 @@ impl TransactionAuthenticator {
             | Self::MultiEd25519 {
                 public_key: _,
                 signature: _,
-            } => vec![],
+            }
+            | Self::Secp256r1Ecdsa { .. } => vec![],
             Self::MultiAgent {
                 sender: _,
                 secondary_signer_addresses,
                 ..
+            }
+            | Self::FeePayer {
+                secondary_signer_addresses,
+                ..
             } => secondary_signer_addresses.to_vec(),
         }
     }
@@ -169,14 +247,38 @@ impl TransactionAuthenticator {
             | Self::MultiEd25519 {
                 public_key: _,
                 signature: _,
-            } => vec![],
+            }
+            | Self::Secp256r1Ecdsa { .. } => vec![],
             Self::MultiAgent {
                 sender: _,
                 secondary_signer_addresses: _,
                 secondary_signers,
+            }
+            | Self::FeePayer {
+                secondary_signers, ..
             } => secondary_signers.to_vec(),
         }
     }
+
+    /// Return the address of the account paying for gas, if it is not the sender
+    pub fn fee_payer_address(&self) -> Option<AccountAddress> {
+        match self {
+            Self::FeePayer {
+                fee_payer_address, ..
+            } => Some(*fee_payer_address),
+            _ => None,
+        }
+    }
+
+    /// Return the authenticator of the account paying for gas, if it is not the sender
+    pub fn fee_payer_signer(&self) -> Option<AccountAuthenticator> {
+        match self {
+            Self::FeePayer {
+                fee_payer_signer, ..
+            } => Some(fee_payer_signer.clone()),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for TransactionAuthenticator {
@@ -202,6 +304,16 @@ impl fmt::Display for TransactionAuthenticator {
                     self.sender()
                 )
             },
+            Self::Secp256r1Ecdsa {
+                public_key: _,
+                signature: _,
+            } => {
+                write!(
+                    f,
+                    "TransactionAuthenticator[scheme: Secp256r1Ecdsa, sender: {}]",
+                    self.sender()
+                )
+            },
             Self::MultiAgent {
                 sender,
                 secondary_signer_addresses,
@@ -225,6 +337,33 @@ impl fmt::Display for TransactionAuthenticator {
                     sender, sec_addrs, sec_signers,
                 )
             },
+            Self::FeePayer {
+                sender,
+                secondary_signer_addresses,
+                secondary_signers,
+                fee_payer_address,
+                fee_payer_signer,
+            } => {
+                let mut sec_addrs: String = "".to_string();
+                for sec_addr in secondary_signer_addresses {
+                    sec_addrs = format!("{}\n\t\t\t{:#?},", sec_addrs, sec_addr);
+                }
+                let mut sec_signers: String = "".to_string();
+                for sec_signer in secondary_signers {
+                    sec_signers = format!("{}\n\t\t\t{:#?},", sec_signers, sec_signer);
+                }
+                write!(
+                    f,
+                    "TransactionAuthenticator[\n\
+                        \tscheme: FeePayer, \n\
+                        \tsender: {}\n\
+                        \tsecondary signer addresses: {}\n\
+                        \tsecondary signers: {}\n\
+                        \tfee payer address: {}\n\
+                        \tfee payer signer: {}]",
+                    sender, sec_addrs, sec_signers, fee_payer_address, fee_payer_signer,
+                )
+            },
         }
     }
 }
@@ -242,7 +381,11 @@ impl fmt::Display for TransactionAuthenticator {
 pub enum Scheme {
     Ed25519 = 0,
     MultiEd25519 = 1,
+    Secp256r1Ecdsa = 2,
     // ... add more schemes here
+    /// Scheme identifier used to derive the unique addresses generated by transactions, see
+    /// `account_address::create_unique_address`.
+    DeriveAuid = 251,
     /// Scheme identifier used to derive addresses (not the authentication key) of objects and
     /// resources accounts. This application serves to domain separate hashes. Without such
     /// separation, an adversary could create (and get a signer for) a these accounts
@@ -257,6 +400,8 @@ impl fmt::Display for Scheme {
         let display = match self {
             Scheme::Ed25519 => "Ed25519",
             Scheme::MultiEd25519 => "MultiEd25519",
+            Scheme::Secp256r1Ecdsa => "Secp256r1Ecdsa",
+            Scheme::DeriveAuid => "DeriveAuid",
             Scheme::DeriveObjectAddressFromGuid => "DeriveObjectAddressFromGuid",
             Scheme::DeriveObjectAddressFromSeed => "DeriveObjectAddressFromSeed",
             Scheme::DeriveResourceAccountAddress => "DeriveResourceAccountAddress",
@@ -277,6 +422,11 @@ pub enum AccountAuthenticator {
         public_key: MultiEd25519PublicKey,
         signature: MultiEd25519Signature,
     },
+    /// Single secp256r1 ECDSA signature, possibly through a WebAuthn assertion
+    Secp256r1Ecdsa {
+        public_key: Secp256r1EcdsaPublicKey,
+        signature: Secp256r1EcdsaSignatureEnvelope,
+    },
     // ... add more schemes here
 }
 
@@ -286,6 +436,7 @@ impl AccountAuthenticator {
         match self {
             Self::Ed25519 { .. } => Scheme::Ed25519,
             Self::MultiEd25519 { .. } => Scheme::MultiEd25519,
+            Self::Secp256r1Ecdsa { .. } => Scheme::Secp256r1Ecdsa,
         }
     }
 
@@ -308,6 +459,17 @@ impl AccountAuthenticator {
         }
     }
 
+    /// Create a single-signature secp256r1 ECDSA authenticator
+    pub fn secp256r1_ecdsa(
+        public_key: Secp256r1EcdsaPublicKey,
+        signature: Secp256r1EcdsaSignatureEnvelope,
+    ) -> Self {
+        Self::Secp256r1Ecdsa {
+            public_key,
+            signature,
+        }
+    }
+
     /// Return Ok if the authenticator's public key matches its signature, Err otherwise
     pub fn verify<T: Serialize + CryptoHash>(&self, message: &T) -> Result<()> {
         match self {
@@ -319,6 +481,10 @@ impl AccountAuthenticator {
                 public_key,
                 signature,
             } => signature.verify(message, public_key),
+            Self::Secp256r1Ecdsa {
+                public_key,
+                signature,
+            } => signature.verify(message, public_key),
         }
     }
 
@@ -327,6 +493,7 @@ impl AccountAuthenticator {
         match self {
             Self::Ed25519 { public_key, .. } => public_key.to_bytes().to_vec(),
             Self::MultiEd25519 { public_key, .. } => public_key.to_bytes().to_vec(),
+            Self::Secp256r1Ecdsa { public_key, .. } => public_key.to_bytes().to_vec(),
         }
     }
 
@@ -335,6 +502,7 @@ impl AccountAuthenticator {
         match self {
             Self::Ed25519 { signature, .. } => signature.to_bytes().to_vec(),
             Self::MultiEd25519 { signature, .. } => signature.to_bytes().to_vec(),
+            Self::Secp256r1Ecdsa { signature, .. } => signature.to_bytes(),
         }
     }
 
@@ -353,6 +521,39 @@ impl AccountAuthenticator {
         match self {
             Self::Ed25519 { .. } => 1,
             Self::MultiEd25519 { signature, .. } => signature.signatures().len(),
+            Self::Secp256r1Ecdsa { .. } => 1,
+        }
+    }
+}
+
+/// The signature of a secp256r1 ECDSA authenticator, either directly over the signing message of
+/// the transaction, or made by a WebAuthn authenticator through an assertion of the transaction.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Secp256r1EcdsaSignatureEnvelope {
+    Raw(Secp256r1EcdsaSignature),
+    WebAuthn(PartialAuthenticatorAssertionResponse),
+}
+
+impl Secp256r1EcdsaSignatureEnvelope {
+    /// Return Ok if `self` is a signature of `message` by `public_key`, Err otherwise
+    pub fn verify<T: Serialize + CryptoHash>(
+        &self,
+        message: &T,
+        public_key: &Secp256r1EcdsaPublicKey,
+    ) -> Result<()> {
+        match self {
+            Self::Raw(signature) => signature.verify(message, public_key),
+            Self::WebAuthn(response) => response.verify(message, public_key),
+        }
+    }
+
+    /// Return the raw bytes of the signature, or the BCS bytes of the whole WebAuthn assertion
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Raw(signature) => signature.to_bytes().to_vec(),
+            Self::WebAuthn(response) => {
+                bcs::to_bytes(response).expect("WebAuthn assertions must serialize")
+            },
         }
     }
 }
@@ -405,6 +606,11 @@ impl AuthenticationKey {
         Self::from_preimage(&AuthenticationKeyPreimage::multi_ed25519(public_key))
     }
 
+    /// Create an authentication key from a secp256r1 ECDSA public key
+    pub fn secp256r1_ecdsa(public_key: &Secp256r1EcdsaPublicKey) -> Self {
+        Self::from_preimage(&AuthenticationKeyPreimage::secp256r1_ecdsa(public_key))
+    }
+
     /// Return an address derived from the last `AccountAddress::LENGTH` bytes of this
     /// authentication key.
     pub fn derived_address(&self) -> AccountAddress {
@@ -460,6 +666,11 @@ impl AuthenticationKeyPreimage {
         Self::new(public_key.to_bytes(), Scheme::MultiEd25519)
     }
 
+    /// Construct a preimage from a secp256r1 ECDSA public key
+    pub fn secp256r1_ecdsa(public_key: &Secp256r1EcdsaPublicKey) -> AuthenticationKeyPreimage {
+        Self::new(public_key.to_bytes().to_vec(), Scheme::Secp256r1Ecdsa)
+    }
+
     /// Construct a vector from this authentication key
     pub fn into_vec(self) -> Vec<u8> {
         self.0
@@ -534,11 +745,111 @@ impl fmt::Display for AuthenticationKey {
 
 #[cfg(test)]
 mod tests {
-    use crate::transaction::authenticator::AuthenticationKey;
+    use crate::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{
+            authenticator::{
+                AuthenticationKey, AuthenticationKeyPreimage, Scheme,
+                Secp256r1EcdsaSignatureEnvelope, TransactionAuthenticator,
+            },
+            RawTransaction, Script,
+        },
+    };
+    use aptos_crypto::{
+        ed25519::Ed25519PrivateKey,
+        secp256r1_ecdsa::Secp256r1EcdsaPrivateKey,
+        traits::{PrivateKey, SigningKey, Uniform},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
     use std::str::FromStr;
 
     #[test]
     fn test_from_str_should_not_panic_by_given_empty_string() {
         assert!(AuthenticationKey::from_str("").is_err());
     }
+
+    #[test]
+    fn test_secp256r1_ecdsa_authenticator() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let private_key = Secp256r1EcdsaPrivateKey::generate(&mut rng);
+        let public_key = private_key.public_key();
+        let raw_txn = RawTransaction::new_script(
+            AccountAddress::random(),
+            0,
+            Script::new(vec![], vec![], vec![]),
+            0,
+            0,
+            0,
+            ChainId::test(),
+        );
+
+        let signature = private_key.sign(&raw_txn).unwrap();
+        let authenticator = TransactionAuthenticator::secp256r1_ecdsa(
+            public_key.clone(),
+            Secp256r1EcdsaSignatureEnvelope::Raw(signature),
+        );
+        assert!(authenticator.verify(&raw_txn).is_ok());
+
+        let preimage = AuthenticationKeyPreimage::secp256r1_ecdsa(&public_key).into_vec();
+        assert_eq!(preimage.last(), Some(&(Scheme::Secp256r1Ecdsa as u8)));
+        assert_eq!(
+            authenticator.sender().authentication_key(),
+            AuthenticationKey::secp256r1_ecdsa(&public_key)
+        );
+
+        let other_key = Secp256r1EcdsaPrivateKey::generate(&mut rng);
+        let authenticator = TransactionAuthenticator::secp256r1_ecdsa(
+            public_key,
+            Secp256r1EcdsaSignatureEnvelope::Raw(other_key.sign(&raw_txn).unwrap()),
+        );
+        assert!(authenticator.verify(&raw_txn).is_err());
+    }
+
+    #[test]
+    fn test_fee_payer_authenticator() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let sender_key = Ed25519PrivateKey::generate(&mut rng);
+        let secondary_key = Ed25519PrivateKey::generate(&mut rng);
+        let fee_payer_key = Ed25519PrivateKey::generate(&mut rng);
+        let secondary_address = AccountAddress::random();
+        let fee_payer_address = AccountAddress::random();
+        let raw_txn = RawTransaction::new_script(
+            AccountAddress::random(),
+            0,
+            Script::new(vec![], vec![], vec![]),
+            0,
+            0,
+            0,
+            ChainId::test(),
+        );
+
+        let txn = raw_txn
+            .clone()
+            .sign_fee_payer(
+                &sender_key,
+                vec![secondary_address],
+                vec![&secondary_key],
+                fee_payer_address,
+                &fee_payer_key,
+            )
+            .unwrap()
+            .into_inner();
+        let authenticator = txn.authenticator();
+        assert!(authenticator.verify(&raw_txn).is_ok());
+        assert_eq!(authenticator.fee_payer_address(), Some(fee_payer_address));
+        assert_eq!(authenticator.secondary_signer_addreses(), vec![
+            secondary_address
+        ]);
+
+        // The signatures cover the fee payer address.
+        let authenticator = TransactionAuthenticator::fee_payer(
+            authenticator.sender(),
+            vec![secondary_address],
+            authenticator.secondary_signers(),
+            AccountAddress::random(),
+            authenticator.fee_payer_signer().unwrap(),
+        );
+        assert!(authenticator.verify(&raw_txn).is_err());
+    }
 }
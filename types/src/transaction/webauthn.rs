@@ -0,0 +1,198 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signatures of transactions by WebAuthn authenticators, e.g., device passkeys.
+//!
+//! A WebAuthn authenticator does not sign the message it is given, which WebAuthn calls the
+//! challenge, but the concatenation of its authenticator data and of the SHA-256 hash of the
+//! client data, a JSON object which embeds the challenge. See
+//! https://www.w3.org/TR/webauthn-2/#sctn-op-get-assertion for the details.
+
+use anyhow::{ensure, Result};
+use aptos_crypto::{
+    hash::CryptoHash,
+    secp256r1_ecdsa::{Secp256r1EcdsaPublicKey, Secp256r1EcdsaSignature},
+    traits::{signing_message, Signature},
+    HashValue,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The `type` of the client data of an assertion.
+const WEBAUTHN_GET: &str = "webauthn.get";
+
+/// The authenticator data starts with the 32-byte hash of the relying party id, followed by a
+/// 1-byte flags field and a 4-byte signature counter.
+const MIN_AUTHENTICATOR_DATA_LENGTH: usize = 37;
+const FLAGS_INDEX: usize = 32;
+const USER_PRESENT_FLAG: u8 = 0x01;
+
+/// The members of the client data which are checked when verifying an assertion.
+#[derive(Deserialize)]
+struct CollectedClientData {
+    #[serde(rename = "type")]
+    ty: String,
+    challenge: String,
+}
+
+/// The parts of a WebAuthn `AuthenticatorAssertionResponse` needed to verify it, see
+/// https://www.w3.org/TR/webauthn-2/#authenticatorassertionresponse.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct PartialAuthenticatorAssertionResponse {
+    /// The signature over `authenticator_data || sha256(client_data_json)`.
+    signature: Secp256r1EcdsaSignature,
+    #[serde(with = "serde_bytes")]
+    authenticator_data: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    client_data_json: Vec<u8>,
+}
+
+impl PartialAuthenticatorAssertionResponse {
+    pub fn new(
+        signature: Secp256r1EcdsaSignature,
+        authenticator_data: Vec<u8>,
+        client_data_json: Vec<u8>,
+    ) -> Self {
+        Self {
+            signature,
+            authenticator_data,
+            client_data_json,
+        }
+    }
+
+    pub fn signature(&self) -> &Secp256r1EcdsaSignature {
+        &self.signature
+    }
+
+    pub fn authenticator_data(&self) -> &[u8] {
+        &self.authenticator_data
+    }
+
+    pub fn client_data_json(&self) -> &[u8] {
+        &self.client_data_json
+    }
+
+    /// Return Ok if this is an assertion by `public_key` of `message`, i.e., whose challenge is
+    /// the [`challenge`] of `message`, Err otherwise.
+    pub fn verify<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+        public_key: &Secp256r1EcdsaPublicKey,
+    ) -> Result<()> {
+        let client_data: CollectedClientData = serde_json::from_slice(&self.client_data_json)?;
+        ensure!(
+            client_data.ty == WEBAUTHN_GET,
+            "WebAuthn client data has type {}, expected {}",
+            client_data.ty,
+            WEBAUTHN_GET
+        );
+        ensure!(
+            client_data.challenge == encode_challenge(&challenge(message)?),
+            "WebAuthn challenge does not match the message"
+        );
+        ensure!(
+            self.authenticator_data.len() >= MIN_AUTHENTICATOR_DATA_LENGTH,
+            "WebAuthn authenticator data is too short"
+        );
+        ensure!(
+            self.authenticator_data[FLAGS_INDEX] & USER_PRESENT_FLAG != 0,
+            "WebAuthn assertion was made without the user being present"
+        );
+
+        self.signature
+            .verify_arbitrary_msg(&self.signed_bytes(), public_key)
+    }
+
+    /// The bytes signed by the authenticator.
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.authenticator_data.clone();
+        bytes.extend_from_slice(&Sha256::digest(&self.client_data_json));
+        bytes
+    }
+}
+
+/// Return the challenge a WebAuthn assertion of `message` must be made over: the SHA3-256 hash of
+/// the signing message of `message`, i.e., of what the other schemes sign.
+pub fn challenge<T: CryptoHash + Serialize>(message: &T) -> Result<HashValue> {
+    Ok(HashValue::sha3_256_of(&signing_message(message)?))
+}
+
+/// Return a challenge as it appears in the client data: base64url encoded without padding.
+pub fn encode_challenge(challenge: &HashValue) -> String {
+    base64::encode_config(challenge.as_ref(), base64::URL_SAFE_NO_PAD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{RawTransaction, Script},
+    };
+    use aptos_crypto::{
+        secp256r1_ecdsa::Secp256r1EcdsaPrivateKey,
+        traits::{PrivateKey, SigningKey, Uniform},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn raw_txn(sender: AccountAddress, sequence_number: u64) -> RawTransaction {
+        RawTransaction::new_script(
+            sender,
+            sequence_number,
+            Script::new(vec![], vec![], vec![]),
+            0,
+            0,
+            0,
+            ChainId::test(),
+        )
+    }
+
+    /// Return an assertion of `raw_txn` by `private_key`, with the given client data type and
+    /// authenticator flags.
+    fn assertion(
+        raw_txn: &RawTransaction,
+        private_key: &Secp256r1EcdsaPrivateKey,
+        ty: &str,
+        flags: u8,
+    ) -> PartialAuthenticatorAssertionResponse {
+        let client_data_json = format!(
+            r#"{{"type":"{}","challenge":"{}","origin":"https://wallet.example"}}"#,
+            ty,
+            encode_challenge(&challenge(raw_txn).unwrap())
+        )
+        .into_bytes();
+        let mut authenticator_data = vec![0u8; MIN_AUTHENTICATOR_DATA_LENGTH];
+        authenticator_data[FLAGS_INDEX] = flags;
+
+        let mut signed_bytes = authenticator_data.clone();
+        signed_bytes.extend_from_slice(&Sha256::digest(&client_data_json));
+        PartialAuthenticatorAssertionResponse::new(
+            private_key.sign_arbitrary_message(&signed_bytes),
+            authenticator_data,
+            client_data_json,
+        )
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let private_key = Secp256r1EcdsaPrivateKey::generate(&mut rng);
+        let public_key = private_key.public_key();
+        let sender = AccountAddress::random();
+        let txn = raw_txn(sender, 0);
+
+        let response = assertion(&txn, &private_key, WEBAUTHN_GET, USER_PRESENT_FLAG);
+        assert!(response.verify(&txn, &public_key).is_ok());
+
+        let other_key = Secp256r1EcdsaPrivateKey::generate(&mut rng).public_key();
+        assert!(response.verify(&txn, &other_key).is_err());
+        assert!(response.verify(&raw_txn(sender, 1), &public_key).is_err());
+
+        let response = assertion(&txn, &private_key, "webauthn.create", USER_PRESENT_FLAG);
+        assert!(response.verify(&txn, &public_key).is_err());
+
+        let response = assertion(&txn, &private_key, WEBAUTHN_GET, 0);
+        assert!(response.verify(&txn, &public_key).is_err());
+    }
+}